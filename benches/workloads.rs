@@ -0,0 +1,95 @@
+use std::convert::TryFrom;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use cyrano::borrowed::MessageRef;
+use cyrano::fixtures;
+use cyrano::logfile;
+use cyrano::message::Message;
+use cyrano::state::PisteManager;
+
+/// Size of the generated day: 16 pistes running 30 bouts each.
+const PISTES: usize = 16;
+const BOUTS_PER_PISTE: usize = 30;
+
+fn day_messages() -> Vec<Message> {
+    fixtures::day_log(0, PISTES, BOUTS_PER_PISTE)
+        .iter()
+        .filter_map(|entry| entry.message().ok())
+        .collect()
+}
+
+fn single_message(c: &mut Criterion) {
+    let frames: Vec<&str> = fixtures::valid_frames().collect();
+
+    let mut group = c.benchmark_group("single_message");
+    group.throughput(Throughput::Elements(frames.len() as u64));
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            for frame in &frames {
+                let _ = black_box(Message::try_from(black_box(*frame)));
+            }
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            for frame in &frames {
+                let _ = black_box(MessageRef::try_from(black_box(*frame)));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn day_log(c: &mut Criterion) {
+    let text = fixtures::day_log_text(0, PISTES, BOUTS_PER_PISTE);
+
+    let mut group = c.benchmark_group("day_log");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.sample_size(20);
+    group.bench_function("read", |b| {
+        b.iter(|| logfile::read(black_box(text.as_bytes())).messages().count())
+    });
+    group.bench_function("read_bytes", |b| {
+        b.iter(|| {
+            logfile::read_bytes(black_box(text.as_bytes()))
+                .filter_map(|entry| entry.ok()?.message().ok())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn serialize_roundtrip(c: &mut Criterion) {
+    let messages = day_messages();
+
+    let mut group = c.benchmark_group("serialize_roundtrip");
+    group.throughput(Throughput::Elements(messages.len() as u64));
+    group.sample_size(20);
+    group.bench_function("to_string_and_parse", |b| {
+        b.iter(|| {
+            for message in &messages {
+                let _ = black_box(Message::try_from(message.to_string()));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn state_fold(c: &mut Criterion) {
+    let messages = day_messages();
+
+    let mut group = c.benchmark_group("state_fold");
+    group.throughput(Throughput::Elements(messages.len() as u64));
+    group.sample_size(20);
+    group.bench_function("piste_manager", |b| {
+        b.iter(|| {
+            let mut manager = PisteManager::new();
+            messages.iter().map(|message| manager.apply(message).len()).sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, single_message, day_log, serialize_roundtrip, state_fold);
+criterion_main!(benches);
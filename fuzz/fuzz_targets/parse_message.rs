@@ -0,0 +1,31 @@
+#![no_main]
+
+use cyrano::message::Message;
+use cyrano::options::{ParseLimits, ParseOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(raw) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    // Strict parsing must never panic, whatever the outcome.
+    let _ = Message::try_from_strict(raw);
+
+    // Whatever parses leniently must survive a serialize/parse roundtrip.
+    // Serialization pads missing fields, so the reparse is not size-limited.
+    if let Ok(message) = Message::try_from(raw) {
+        let unlimited = ParseOptions {
+            limits: ParseLimits {
+                max_message_length: usize::MAX,
+                max_zones: usize::MAX,
+                max_fields_per_zone: usize::MAX,
+            },
+            ..ParseOptions::default()
+        };
+        let serialized = message.to_string();
+        let reparsed = Message::parse_with(&serialized, &unlimited)
+            .expect("serialized message must parse");
+        assert_eq!(message, reparsed);
+    }
+});
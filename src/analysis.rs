@@ -0,0 +1,619 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+
+use crate::enums::{ApparatusState, PCard, Side, Weapon};
+use crate::fencer::Fencer;
+use crate::group::{BoutKey, CollectedMessage, PhaseKey};
+use crate::locale::Locale;
+use crate::message::Message;
+use crate::piste::PisteId;
+use crate::rules::RuleProfile;
+use crate::state::{double_touch, MatchEvent, MatchState};
+use crate::summary::BoutSummary;
+use crate::utils::clock_seconds;
+
+/// How a bout of a stream ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoutOutcome {
+    /// The bout reached a final status, or the apparatus reported its end.
+    Finished,
+    /// Another bout was loaded on the piste, or the bout was reset to be
+    /// fenced again, before it finished.
+    Aborted,
+    /// The stream ended before the bout finished.
+    Unfinished,
+}
+
+/// A bout fenced on a piste, with its messages and its summary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoutRecord<T = Message> {
+    /// Piste of the bout.
+    pub piste: PisteId,
+    /// Identity of the bout.
+    pub bout: BoutKey,
+    /// 1 for the first time the bout is fenced, 2 and more each time it is
+    /// fenced again after an abort or a reset.
+    pub attempt: u32,
+    /// How the bout ended.
+    pub outcome: BoutOutcome,
+    /// Messages of the piste from the bout being loaded to the next bout, in
+    /// stream order.
+    pub messages: Vec<T>,
+    /// Summary of the bout, up to its end or abort.
+    pub summary: Option<BoutSummary>,
+}
+
+/// A bout of a piste being followed.
+struct OpenBout {
+    record: usize,
+    fenced: bool,
+    touches: u16,
+}
+
+/// Splits a stream of messages into the bouts fenced on each piste.
+///
+/// A bout starts with the message loading it, usually while the apparatus is
+/// waiting, and ends when it finishes (a victory or defeat status, or the
+/// apparatus reporting the end of the bout) or when the next bout is loaded.
+/// A bout whose scores are reset to zero after touches is being fenced again:
+/// the first attempt ends as [`BoutOutcome::Aborted`] and the next one starts,
+/// with the next [`attempt`](BoutRecord::attempt) number.
+///
+/// Bouts loaded but never fenced, such as a bout shown ahead of time, are left
+/// out. Unlike [`split_bouts`](crate::group::split_bouts), the records own the
+/// messages.
+///
+/// # Returns
+///
+/// The bouts, in the order they were loaded.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::analysis::{segment_bouts, BoutOutcome};
+/// use cyrano::message::Message;
+///
+/// let stream = [
+///     "|EFP1.1|INFO|3|fm-eq|1|P3|1||3:00|||E||W|%|28|P.Martin|FRA|0|U|%|32|B. Panini|ITA|0|U|%|",
+///     "|EFP1.1|INFO|3|fm-eq|1|P3|1||2:40|||E||F|%|28|P.Martin|FRA|1|U|%|32|B. Panini|ITA|0|U|%|",
+///     "|EFP1.1|INFO|3|fm-eq|1|P3|1||3:00|||E||W|%|28|P.Martin|FRA|0|U|%|32|B. Panini|ITA|0|U|%|",
+///     "|EFP1.1|INFO|3|fm-eq|1|P3|1||1:10|||E||F|%|28|P.Martin|FRA|5|V|%|32|B. Panini|ITA|3|D|%|",
+///     "|EFP1.1|INFO|3|fm-eq|1|P3|2||3:00|||E||W|%|28|P.Martin|FRA|0|U|%|40|L.Rossi|ITA|0|U|%|",
+/// ];
+///
+/// let bouts = segment_bouts(stream.iter().map(|raw| Message::try_from(*raw).unwrap()));
+/// let outcomes: Vec<_> = bouts.iter().map(|b| (b.attempt, b.outcome, b.messages.len())).collect();
+/// assert_eq!(outcomes, [(1, BoutOutcome::Aborted, 2), (2, BoutOutcome::Finished, 2)]);
+/// assert_eq!(bouts[1].summary.as_ref().unwrap().right_fencer.score, Some(5));
+/// ```
+pub fn segment_bouts<T, I>(messages: I) -> Vec<BoutRecord<T>>
+where
+    T: CollectedMessage,
+    I: IntoIterator<Item = T>,
+{
+    let mut records: Vec<BoutRecord<T>> = Vec::new();
+    let mut fenced: Vec<bool> = Vec::new();
+    let mut pistes: HashMap<PisteId, (MatchState, Option<OpenBout>)> = HashMap::new();
+    let mut attempts: HashMap<BoutKey, u32> = HashMap::new();
+
+    for item in messages {
+        let message = item.message();
+        let piste = PisteId::from(message.piste.as_str());
+        let (state, open) = pistes
+            .entry(piste.clone())
+            .or_insert_with(|| (MatchState::new(message.piste.as_str()), None));
+
+        if let Some(current) = open.as_ref().filter(|_| message.carries_bout()) {
+            let reloaded = BoutKey::of(message) != records[current.record].bout;
+            let reset = current.fenced && current.touches > 0 && touches(message) == 0;
+            if reloaded || reset {
+                let record = &mut records[current.record];
+                if record.outcome == BoutOutcome::Unfinished {
+                    record.outcome = BoutOutcome::Aborted;
+                    record.summary = state.summary();
+                }
+                *open = None;
+            }
+            if reset && !reloaded {
+                *state = MatchState::new(message.piste.as_str());
+            }
+        }
+
+        let events = state.apply(message);
+        if events.contains(&MatchEvent::BoutStarted) {
+            *open = Some(OpenBout {
+                record: records.len(),
+                fenced: false,
+                touches: 0,
+            });
+            records.push(BoutRecord {
+                piste,
+                bout: BoutKey::of(message),
+                attempt: 0,
+                outcome: BoutOutcome::Unfinished,
+                messages: Vec::new(),
+                summary: None,
+            });
+            fenced.push(false);
+        }
+
+        let Some(current) = open.as_mut() else {
+            continue;
+        };
+        let record = &mut records[current.record];
+        if message.carries_bout() {
+            current.touches = touches(message);
+            if !current.fenced && (message.state == Some(ApparatusState::Fencing) || current.touches > 0) {
+                current.fenced = true;
+                fenced[current.record] = true;
+                let attempt = attempts.entry(record.bout.clone()).or_insert(0);
+                *attempt += 1;
+                record.attempt = *attempt;
+            }
+        }
+        for event in events {
+            if let MatchEvent::BoutSummary(summary) = event {
+                record.outcome = BoutOutcome::Finished;
+                record.summary = Some(*summary);
+            }
+        }
+        record.messages.push(item);
+    }
+
+    for (state, open) in pistes.values() {
+        if let Some(current) = open {
+            let record = &mut records[current.record];
+            if record.outcome == BoutOutcome::Unfinished {
+                record.summary = state.summary();
+            }
+        }
+    }
+
+    records
+        .into_iter()
+        .zip(fenced)
+        .filter_map(|(record, fenced)| fenced.then_some(record))
+        .collect()
+}
+
+/// Identifiers of the right and left fencers of a bout.
+pub type FencerPair = (Option<String>, Option<String>);
+
+/// Match number of a phase, with the round number.
+type MatchSlot = (PhaseKey, u8, Option<u8>);
+
+/// A protocol anomaly found in a capture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AnomalyKind {
+    /// The score of a fencer went down during a bout.
+    ScoreDecreased { side: Side, from: u8, to: u8 },
+    /// A yellow or red card of a fencer disappeared during a bout.
+    CardRemoved {
+        side: Side,
+        yellow_card: (u8, u8),
+        red_card: (u8, u8),
+    },
+    /// The P-card of a fencer was lowered during a bout.
+    PCardLowered { side: Side, from: PCard, to: PCard },
+    /// Both fencers scored with the same action in a weapon other than épée.
+    DoubleTouchOutsideEpee { weapon: Weapon, right_score: u8, left_score: u8 },
+    /// The white light of a fencer came on in a weapon other than foil,
+    /// which has no off-target signal.
+    WhiteLightOutsideFoil { side: Side, weapon: Weapon },
+    /// The bout clock ran while the apparatus was halted.
+    ClockRunningWhileHalted { from: String, to: String },
+    /// A match number of a phase was used for two different pairs of fencers.
+    DuplicateMatchNumber {
+        match_number: u8,
+        first: FencerPair,
+        second: FencerPair,
+    },
+    /// The referee changed during a bout.
+    RefereeChanged { from: Option<String>, to: Option<String> },
+    /// A P-card was given in an event whose rules have none.
+    UnexpectedPCard { side: Side, p_card: PCard },
+    /// A fencer scored with neither a scoring light nor a red card to the
+    /// opponent, in an event without footwork sanctions to explain it.
+    TouchWithoutLight { side: Side, score: u8 },
+}
+
+/// An anomaly, with where and when it was seen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Anomaly {
+    /// Piste of the message showing the anomaly.
+    pub piste: PisteId,
+    /// Receipt time of the message, if known.
+    pub timestamp_ms: Option<u64>,
+    /// Bout of the message.
+    pub bout: BoutKey,
+    /// What is wrong.
+    pub kind: AnomalyKind,
+}
+
+impl Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(timestamp_ms) = self.timestamp_ms {
+            write!(f, "{} ", timestamp_ms)?;
+        }
+        write!(f, "piste {}", self.piste)?;
+        if let Some(match_number) = self.bout.match_number {
+            write!(f, " match {}", match_number)?;
+        }
+        let pair = |(right, left): &FencerPair| {
+            format!("{}-{}", right.as_deref().unwrap_or("?"), left.as_deref().unwrap_or("?"))
+        };
+        match &self.kind {
+            AnomalyKind::ScoreDecreased { side, from, to } => write!(f, ": {} score went down from {} to {}", side, from, to),
+            AnomalyKind::CardRemoved {
+                side,
+                yellow_card,
+                red_card,
+            } => write!(
+                f,
+                ": {} cards removed (yellow {} to {}, red {} to {})",
+                side, yellow_card.0, yellow_card.1, red_card.0, red_card.1
+            ),
+            AnomalyKind::PCardLowered { side, from, to } => write!(f, ": {} P-card lowered from {:?} to {:?}", side, from, to),
+            AnomalyKind::DoubleTouchOutsideEpee {
+                weapon,
+                right_score,
+                left_score,
+            } => write!(f, ": double touch to {}-{} in {}", right_score, left_score, weapon.display_name(Locale::English)),
+            AnomalyKind::WhiteLightOutsideFoil { side, weapon } => {
+                write!(f, ": {} white light on in {}", side, weapon.display_name(Locale::English))
+            }
+            AnomalyKind::ClockRunningWhileHalted { from, to } => write!(f, ": clock ran from {} to {} while halted", from, to),
+            AnomalyKind::DuplicateMatchNumber { first, second, .. } => {
+                write!(f, ": match number used by {} and {}", pair(first), pair(second))
+            }
+            AnomalyKind::RefereeChanged { from, to } => write!(
+                f,
+                ": referee changed from {} to {}",
+                from.as_deref().unwrap_or("none"),
+                to.as_deref().unwrap_or("none")
+            ),
+            AnomalyKind::UnexpectedPCard { side, p_card } => {
+                write!(f, ": {} given a P-card ({:?}) without P-card rules", side, p_card)
+            }
+            AnomalyKind::TouchWithoutLight { side, score } => write!(f, ": {} scored {} without a light", side, score),
+        }
+    }
+}
+
+/// Checks a capture for protocol anomalies that put results in doubt:
+///
+/// - scores going down during a bout, except both scores being reset to zero
+///   for the bout to be fenced again (see [`segment_bouts`]);
+/// - yellow or red cards disappearing, or P-cards being lowered, during a bout;
+/// - both fencers scoring with the same action in foil or sabre;
+/// - a white light coming on in épée or sabre;
+/// - the bout clock running down while the apparatus is halted;
+/// - a match number of a phase used for two different pairs of fencers;
+/// - the referee changing during a bout.
+///
+/// The messages are expected in receipt order. Each message is compared with
+/// the previous message of the same bout on its piste. The checks assume FIE
+/// senior rules; see [`find_anomalies_with`] for other categories.
+///
+/// # Returns
+///
+/// The anomalies, in the order they were seen.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::analysis::{find_anomalies, AnomalyKind};
+/// use cyrano::enums::Side;
+/// use cyrano::message::Message;
+///
+/// let stream: Vec<Message> = [
+///     "|EFP1.1|INFO|3|fm-eq|1|P3|1||2:40|||E||H|%|28|P.Martin|FRA|3|U|%|32|B. Panini|ITA|1|U|%|",
+///     "|EFP1.1|INFO|3|fm-eq|1|P3|1||2:31|||E||H|%|28|P.Martin|FRA|2|U|%|32|B. Panini|ITA|1|U|%|",
+/// ]
+/// .iter()
+/// .map(|raw| Message::try_from(*raw).unwrap())
+/// .collect();
+///
+/// let anomalies = find_anomalies(&stream);
+/// assert_eq!(anomalies[0].kind, AnomalyKind::ScoreDecreased { side: Side::Right, from: 3, to: 2 });
+/// assert_eq!(anomalies[1].to_string(), "piste 3 match 1: clock ran from 2:40 to 2:31 while halted");
+/// ```
+pub fn find_anomalies<'a, T, I>(messages: I) -> Vec<Anomaly>
+where
+    T: CollectedMessage + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    find_anomalies_with(messages, &RuleProfile::default())
+}
+
+/// Checks a capture for protocol anomalies, under the rules of its category.
+///
+/// The checks of [`find_anomalies`] are adjusted to the rules:
+///
+/// - P-cards are only checked where the rules have them; elsewhere, giving one
+///   is an anomaly;
+/// - in wheelchair events, which have no footwork sanctions, a touch scored
+///   without a scoring light or a red card to the opponent is an anomaly.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::analysis::{find_anomalies, find_anomalies_with};
+/// use cyrano::message::Message;
+/// use cyrano::rules::RuleProfile;
+///
+/// // A touch given for crossing the rear limit of the piste.
+/// let stream: Vec<Message> = ["2|U|0|0|0", "3|U|0|0|0"]
+///     .iter()
+///     .map(|right| Message::try_from(format!("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||H|%|28|P.Martin|FRA|{}|%|32|B. Panini|ITA|1|U|%|", right)).unwrap())
+///     .collect();
+///
+/// assert!(find_anomalies(&stream).is_empty());
+/// let anomalies = find_anomalies_with(&stream, &RuleProfile::WHEELCHAIR);
+/// assert_eq!(anomalies[0].to_string(), "piste 3 match 1: right scored 3 without a light");
+/// ```
+pub fn find_anomalies_with<'a, T, I>(messages: I, rules: &RuleProfile) -> Vec<Anomaly>
+where
+    T: CollectedMessage + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut anomalies = Vec::new();
+    let mut previous: HashMap<PisteId, &Message> = HashMap::new();
+    let mut pairs: HashMap<MatchSlot, FencerPair> = HashMap::new();
+    let mut reported: HashSet<(MatchSlot, FencerPair)> = HashSet::new();
+
+    for item in messages {
+        let message = item.message();
+        if !message.carries_bout() {
+            continue;
+        }
+        let piste = PisteId::from(message.piste.as_str());
+        let bout = BoutKey::of(message);
+        let mut found = Vec::new();
+
+        if let Some(match_number) = message.match_number {
+            let key = (bout.phase.clone(), match_number, message.round);
+            let pair = (bout.right_id.clone(), bout.left_id.clone());
+            let first = pairs.entry(key.clone()).or_insert_with(|| pair.clone()).clone();
+            if first != pair && reported.insert((key, pair.clone())) {
+                found.push(AnomalyKind::DuplicateMatchNumber {
+                    match_number,
+                    first,
+                    second: pair,
+                });
+            }
+        }
+
+        if let Some(before) = previous.insert(piste.clone(), message).filter(|before| BoutKey::of(before) == bout) {
+            compare(before, message, rules, &mut found);
+        }
+
+        anomalies.extend(found.into_iter().map(|kind| Anomaly {
+            piste: piste.clone(),
+            timestamp_ms: item.timestamp_ms(),
+            bout: bout.clone(),
+            kind,
+        }));
+    }
+
+    anomalies
+}
+
+/// Compares two successive messages of the same bout.
+fn compare(before: &Message, after: &Message, rules: &RuleProfile, found: &mut Vec<AnomalyKind>) {
+    let reset = touches(before) > 0 && touches(after) == 0;
+    for side in [Side::Right, Side::Left] {
+        let (was, is) = (before.fencer(side), after.fencer(side));
+        if let (Some(from), Some(to)) = (was.score, is.score) {
+            if to < from && !reset {
+                found.push(AnomalyKind::ScoreDecreased { side, from, to });
+            }
+            let lit = was.light == Some(true) || is.light == Some(true);
+            let red_card = |message: &Message| message.fencer(side.opponent()).red_card.unwrap_or(0);
+            if to > from && !lit && red_card(after) <= red_card(before) && !rules.footwork_sanctions() {
+                found.push(AnomalyKind::TouchWithoutLight { side, score: to });
+            }
+        }
+
+        let cards = |fencer: &Fencer| (fencer.yellow_card.unwrap_or(0), fencer.red_card.unwrap_or(0));
+        let ((yellow_before, red_before), (yellow_after, red_after)) = (cards(was), cards(is));
+        if (yellow_after < yellow_before || red_after < red_before) && !reset {
+            found.push(AnomalyKind::CardRemoved {
+                side,
+                yellow_card: (yellow_before, yellow_after),
+                red_card: (red_before, red_after),
+            });
+        }
+
+        let given = |p_card: &Option<PCard>| p_card.clone().filter(|p_card| *p_card != PCard::None);
+        if !rules.p_cards {
+            if let Some(p_card) = given(&is.p_card).filter(|p_card| given(&was.p_card).is_none_or(|was| was < *p_card)) {
+                found.push(AnomalyKind::UnexpectedPCard { side, p_card });
+            }
+        } else if let (Some(from), Some(to)) = (&was.p_card, &is.p_card) {
+            if to < from {
+                found.push(AnomalyKind::PCardLowered {
+                    side,
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+        }
+    }
+
+    let weapon = after.weapon.as_ref().or(before.weapon.as_ref());
+    if let (Some((right_score, left_score)), Some(weapon)) = (double_touch(before, after), weapon) {
+        if *weapon != Weapon::Epee {
+            found.push(AnomalyKind::DoubleTouchOutsideEpee {
+                weapon: weapon.clone(),
+                right_score,
+                left_score,
+            });
+        }
+    }
+    if let Some(weapon) = weapon.filter(|weapon| **weapon != Weapon::Foil) {
+        for side in [Side::Right, Side::Left] {
+            if before.fencer(side).white_light != Some(true) && after.fencer(side).white_light == Some(true) {
+                found.push(AnomalyKind::WhiteLightOutsideFoil {
+                    side,
+                    weapon: weapon.clone(),
+                });
+            }
+        }
+    }
+
+    let halted = |message: &Message| message.state == Some(ApparatusState::Halt);
+    if halted(before) && halted(after) {
+        let clock = |message: &Message| message.time.as_deref().and_then(clock_seconds);
+        if let (Some(from), Some(to)) = (clock(before), clock(after)) {
+            if to < from {
+                found.push(AnomalyKind::ClockRunningWhileHalted {
+                    from: before.time.as_deref().unwrap_or_default().to_string(),
+                    to: after.time.as_deref().unwrap_or_default().to_string(),
+                });
+            }
+        }
+    }
+
+    let referee = |message: &Message| message.referee.id.as_ref().or(message.referee.name.as_ref()).map(|id| id.to_string());
+    let (from, to) = (referee(before), referee(after));
+    if from.is_some() && from != to {
+        found.push(AnomalyKind::RefereeChanged { from, to });
+    }
+}
+
+fn touches(message: &Message) -> u16 {
+    [Side::Right, Side::Left]
+        .iter()
+        .map(|side| u16::from(message.fencer(*side).score.unwrap_or(0)))
+        .sum()
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::Command;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_segment_aborted_and_refenced_bouts() {
+        let info = |time: u64, pool: &str, left: &str, state: &str, scores: (u8, u8)| {
+            let raw = format!(
+                "|EFP1.1|INFO|2|fm-eq|1|{}|1||3:00|||E||{}|%|28|A|FRA|{}|U|%|{}|B|ITA|{}|U|%|",
+                pool, state, scores.0, left, scores.1
+            );
+            (time, Message::try_from(raw).unwrap())
+        };
+        let stream = vec![
+            (0, Message::new(Command::Hello, "2", "fm-eq")),
+            info(10, "P1", "32", "W", (0, 0)),
+            info(20, "P1", "32", "F", (1, 0)),
+            // Loaded by mistake, never fenced.
+            info(30, "P1", "33", "W", (0, 0)),
+            info(40, "P1", "32", "F", (1, 1)),
+            (45, Message::new(Command::Ack, "2", "fm-eq")),
+            info(50, "P1", "32", "E", (5, 1)),
+            info(60, "P2", "34", "F", (0, 1)),
+        ];
+
+        let bouts = segment_bouts(stream);
+        let shape: Vec<_> = bouts
+            .iter()
+            .map(|b| (b.bout.left_id.as_deref().unwrap(), b.attempt, b.outcome, b.messages.len()))
+            .collect();
+        assert_eq!(
+            shape,
+            [
+                ("32", 1, BoutOutcome::Aborted, 2),
+                ("32", 2, BoutOutcome::Finished, 3),
+                ("34", 1, BoutOutcome::Unfinished, 1),
+            ]
+        );
+        assert_eq!(bouts[0].summary.as_ref().unwrap().right_fencer.score, Some(1));
+        assert_eq!(bouts[1].messages[0].0, 40);
+        assert_eq!(bouts[2].summary.as_ref().unwrap().left_fencer.score, Some(1));
+    }
+
+    #[test]
+    fn test_anomalies_of_a_capture() {
+        let info = |piste: &str, right: &str, referee: &str, time: &str, state: &str, right_fencer: &str| {
+            let raw = format!(
+                "|EFP1.1|INFO|{}|fm-eq|1|P1|4||{}|||E||{}|{}|Ref|FRA|%|{}|A|FRA|{}|%|32|B|ITA|0|U|%|",
+                piste, time, state, referee, right, right_fencer
+            );
+            Message::try_from(raw).unwrap()
+        };
+        let stream = vec![
+            info("1", "28", "7", "2:00", "H", "2|U|1|1|0|0|0|N|2"),
+            info("1", "28", "7", "2:00", "F", "2|U|0|1|0|0|0|N|1"),
+            info("1", "28", "9", "1:50", "H", "2|U|0|1"),
+            info("1", "28", "9", "1:45", "H", "2|U|0|1"),
+            // Both scores reset for the bout to be fenced again.
+            info("1", "28", "9", "3:00", "W", "0|U"),
+            info("2", "29", "5", "3:00", "W", "0|U"),
+            info("2", "29", "5", "3:00", "W", "0|U"),
+        ];
+
+        let anomalies = find_anomalies(&stream);
+        let kinds: Vec<&AnomalyKind> = anomalies.iter().map(|a| &a.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &AnomalyKind::CardRemoved {
+                    side: Side::Right,
+                    yellow_card: (1, 0),
+                    red_card: (1, 1)
+                },
+                &AnomalyKind::PCardLowered {
+                    side: Side::Right,
+                    from: PCard::OneRed,
+                    to: PCard::Yellow
+                },
+                &AnomalyKind::RefereeChanged {
+                    from: Some("7".to_string()),
+                    to: Some("9".to_string())
+                },
+                &AnomalyKind::ClockRunningWhileHalted {
+                    from: "1:50".to_string(),
+                    to: "1:45".to_string()
+                },
+                &AnomalyKind::DuplicateMatchNumber {
+                    match_number: 4,
+                    first: (Some("28".to_string()), Some("32".to_string())),
+                    second: (Some("29".to_string()), Some("32".to_string())),
+                },
+            ]
+        );
+        assert_eq!(anomalies[4].piste.as_str(), "2");
+
+        let sabre = ["0|U", "1|U", "1|U|0|0|0|1"].map(|score| {
+            Message::try_from(format!("|EFP1.1|INFO|3|fm-eq|1|A8|1|||||S||H|%|28|A|FRA|{}|%|32|B|ITA|{}|%|", score, score)).unwrap()
+        });
+        let anomalies: Vec<String> = find_anomalies(&sabre).iter().map(Anomaly::to_string).collect();
+        assert_eq!(
+            anomalies,
+            [
+                "piste 3 match 1: double touch to 1-1 in Sabre",
+                "piste 3 match 1: right white light on in Sabre",
+                "piste 3 match 1: left white light on in Sabre",
+            ]
+        );
+
+        let passive = ["2|U|0|0|0|0|0|N|0", "2|U|0|0|0|0|0|N|1"].map(|right| {
+            Message::try_from(format!("|EFP1.1|INFO|3|fm-eq|1|A8|1|||||E||H|%|28|A|FRA|{}|%|32|B|ITA|1|U|%|", right)).unwrap()
+        });
+        assert!(find_anomalies(&passive).is_empty());
+        assert_eq!(
+            find_anomalies_with(&passive, &RuleProfile::VETERAN)[0].kind,
+            AnomalyKind::UnexpectedPCard { side: Side::Right, p_card: PCard::Yellow }
+        );
+    }
+}
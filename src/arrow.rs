@@ -0,0 +1,245 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::builder::{BooleanBuilder, StringBuilder, UInt64Builder, UInt8Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+
+use super::fencer::FENCER_FIELD_NAMES;
+use super::logfile::LogEntry;
+use super::message::{Message, GENERAL_FIELD_NAMES};
+use crate::utils::FieldRef;
+
+/// Arrow type of each general field, in protocol order.
+const GENERAL_FIELD_TYPES: [DataType; 17] = [
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::UInt8,
+    DataType::Utf8,
+    DataType::UInt8,
+    DataType::UInt8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::Utf8,
+];
+
+/// Arrow type of each fencer field, in protocol order.
+const FENCER_FIELD_TYPES: [DataType; 15] = [
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::UInt8,
+    DataType::Utf8,
+    DataType::UInt8,
+    DataType::UInt8,
+    DataType::Boolean,
+    DataType::Boolean,
+    DataType::UInt8,
+    DataType::Utf8,
+    DataType::Utf8,
+    DataType::UInt8,
+    DataType::UInt8,
+    DataType::Boolean,
+];
+
+/// Returns the Arrow schema of exported message logs.
+///
+/// The columns are `timestamp_ms`, `direction` (`<` or `>`), `raw` (the frame
+/// as received), then one column per protocol field: the general fields named
+/// as in [`GENERAL_FIELD_NAMES`], followed by the fencer fields prefixed with
+/// `right_fencer.` and `left_fencer.`. Numbers are `UInt8`, lights `Boolean`,
+/// and text and enumeration codes `Utf8`. Field columns are null when the
+/// field is empty or the frame does not parse.
+pub fn schema() -> SchemaRef {
+    let mut fields = vec![
+        Field::new("timestamp_ms", DataType::UInt64, false),
+        Field::new("direction", DataType::Utf8, false),
+        Field::new("raw", DataType::Utf8, false),
+    ];
+
+    for (name, data_type) in GENERAL_FIELD_NAMES.iter().zip(GENERAL_FIELD_TYPES) {
+        fields.push(Field::new(*name, data_type, true));
+    }
+    for zone in ["right_fencer", "left_fencer"] {
+        for (name, data_type) in FENCER_FIELD_NAMES.iter().zip(FENCER_FIELD_TYPES) {
+            fields.push(Field::new(format!("{}.{}", zone, name), data_type, true));
+        }
+    }
+
+    Arc::new(Schema::new(fields))
+}
+
+/// Column under construction, typed after the schema.
+enum ColumnBuilder {
+    Text(StringBuilder),
+    Number(UInt8Builder),
+    Flag(BooleanBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::UInt8 => ColumnBuilder::Number(UInt8Builder::new()),
+            DataType::Boolean => ColumnBuilder::Flag(BooleanBuilder::new()),
+            _ => ColumnBuilder::Text(StringBuilder::new()),
+        }
+    }
+
+    fn append(&mut self, value: Option<FieldRef<'_>>) {
+        match (self, value) {
+            (ColumnBuilder::Text(b), Some(FieldRef::Text(s))) if !s.is_empty() => b.append_value(s),
+            (ColumnBuilder::Text(b), Some(FieldRef::Code(c))) => b.append_value(c.to_string()),
+            (ColumnBuilder::Number(b), Some(FieldRef::Number(n))) => b.append_value(n),
+            (ColumnBuilder::Flag(b), Some(FieldRef::Flag(f))) => b.append_value(f),
+            (ColumnBuilder::Text(b), _) => b.append_null(),
+            (ColumnBuilder::Number(b), _) => b.append_null(),
+            (ColumnBuilder::Flag(b), _) => b.append_null(),
+        }
+    }
+
+    fn finish(self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Text(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::Number(mut b) => Arc::new(b.finish()),
+            ColumnBuilder::Flag(mut b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+/// Converts log entries into an Arrow record batch with the layout of [`schema`].
+///
+/// Frames are parsed leniently; frames that do not parse keep their timestamp,
+/// direction and raw text with null field columns.
+///
+/// # Errors
+///
+/// Returns `ArrowError` if the batch cannot be assembled.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::arrow::to_record_batch;
+/// use cyrano::logfile::LogEntry;
+///
+/// let log = vec![
+///     LogEntry::received(0, "|EFP1.1|INFO|17|fm-eq|1|P3|1|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|"),
+///     LogEntry::received(40, "garbage"),
+/// ];
+///
+/// let batch = to_record_batch(&log).unwrap();
+/// assert_eq!(batch.num_rows(), 2);
+/// assert_eq!(batch.column_by_name("right_fencer.score").unwrap().null_count(), 1);
+/// ```
+pub fn to_record_batch<'a>(entries: impl IntoIterator<Item = &'a LogEntry>) -> Result<RecordBatch, ArrowError> {
+    let schema = schema();
+    let mut timestamps = UInt64Builder::new();
+    let mut directions = StringBuilder::new();
+    let mut raws = StringBuilder::new();
+    let mut columns: Vec<ColumnBuilder> = schema
+        .fields()
+        .iter()
+        .skip(3)
+        .map(|field| ColumnBuilder::new(field.data_type()))
+        .collect();
+
+    for entry in entries {
+        timestamps.append_value(entry.timestamp_ms);
+        directions.append_value(entry.direction.to_string());
+        raws.append_value(&entry.raw);
+
+        let message = Message::try_from(entry.raw.as_str()).ok();
+        match &message {
+            Some(message) => {
+                let values = message
+                    .general_field_refs()
+                    .into_iter()
+                    .chain(message.right_fencer.field_refs())
+                    .chain(message.left_fencer.field_refs());
+                for (column, value) in columns.iter_mut().zip(values) {
+                    column.append(Some(value));
+                }
+            }
+            None => columns.iter_mut().for_each(|column| column.append(None)),
+        }
+    }
+
+    let mut arrays: Vec<ArrayRef> = vec![
+        Arc::new(timestamps.finish()),
+        Arc::new(directions.finish()),
+        Arc::new(raws.finish()),
+    ];
+    arrays.extend(columns.into_iter().map(ColumnBuilder::finish));
+
+    RecordBatch::try_new(schema, arrays)
+}
+
+/// Writes log entries to a Parquet file with the layout of [`schema`].
+///
+/// # Errors
+///
+/// Returns `ParquetError` if the batch cannot be assembled or written.
+pub fn write_parquet<'a, W: Write + Send>(
+    writer: W,
+    entries: impl IntoIterator<Item = &'a LogEntry>,
+) -> Result<(), ParquetError> {
+    let batch = to_record_batch(entries)?;
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Array, BooleanArray, StringArray, UInt8Array};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    #[test]
+    fn test_typed_columns() {
+        let log = vec![LogEntry::sent(5, "|EFP1.1|INFO|17|fm-eq|1|P3|1|||||E||F|%|28|P.Martin|FRA|4|U|0|1|1|0|%|%|")];
+        let batch = to_record_batch(&log).unwrap();
+
+        assert_eq!(batch.num_columns(), 3 + 17 + 30);
+        let column = |name| batch.column_by_name(name).unwrap().clone();
+
+        let weapon = column("weapon");
+        assert_eq!(weapon.as_any().downcast_ref::<StringArray>().unwrap().value(0), "E");
+        let score = column("right_fencer.score");
+        assert_eq!(score.as_any().downcast_ref::<UInt8Array>().unwrap().value(0), 4);
+        let light = column("right_fencer.light");
+        assert!(light.as_any().downcast_ref::<BooleanArray>().unwrap().value(0));
+        assert!(column("left_fencer.name").is_null(0));
+    }
+
+    #[test]
+    fn test_parquet_roundtrip() {
+        let log = vec![
+            LogEntry::received(0, "|EFP1.1|HELLO|17|fm-eq|%|"),
+            LogEntry::sent(3, "|EFP1.1|ACK|17|fm-eq|%|"),
+        ];
+
+        let path = std::env::temp_dir().join(format!("cyrano-{}.parquet", std::process::id()));
+        write_parquet(std::fs::File::create(&path).unwrap(), &log).unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+        assert_eq!(rows, 2);
+        std::fs::remove_file(path).unwrap();
+    }
+}
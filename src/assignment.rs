@@ -0,0 +1,171 @@
+use super::enums::{Command, CompetitionType, Weapon};
+use super::error::ParseError;
+use super::fencer::Fencer;
+use super::message::{FieldString, Message, PROTOCOL_VERSION};
+use super::referee::Referee;
+
+/// Identity of a fencer scheduled for an upcoming bout.
+///
+/// Only the identifying fields are carried: scores, cards and lights are owned
+/// by the apparatus once the bout starts.
+#[derive(Debug, Clone, Default)]
+pub struct AssignedFencer {
+    /// Unique identifier for the fencer.
+    pub id: Option<String>,
+    /// Full name of the fencer.
+    pub name: Option<String>,
+    /// Three-letter country code of the fencer's nation (e.g., "FRA", "USA").
+    pub nation: Option<String>,
+    /// Seed (ranking position) of the fencer in the phase.
+    ///
+    /// The seed is not transmitted in EFP frames; it is kept for scheduling purposes.
+    pub seed: Option<u16>,
+}
+
+impl AssignedFencer {
+    /// Converts the assigned identity into a protocol `Fencer`.
+    pub fn to_fencer(&self) -> Fencer {
+        Fencer {
+            id: self.id.as_deref().map(FieldString::from),
+            name: self.name.as_deref().map(FieldString::from),
+            nation: self.nation.as_deref().map(FieldString::from),
+            ..Fencer::default()
+        }
+    }
+}
+
+/// Assignment of an upcoming bout to a piste.
+///
+/// Competition software composes a `MatchAssignment` and turns it into a NEXT
+/// (or PREV) message pushed to the piste apparatus.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::assignment::{AssignedFencer, MatchAssignment};
+/// use cyrano::message::Message;
+///
+/// let assignment = MatchAssignment {
+///     piste: "17".to_string(),
+///     competition_id: "fm-eq".to_string(),
+///     phase: Some(1),
+///     pool_tableau: Some("A32".to_string()),
+///     match_number: Some(12),
+///     right_fencer: AssignedFencer {
+///         id: Some("28".to_string()),
+///         name: Some("P.Martin".to_string()),
+///         ..AssignedFencer::default()
+///     },
+///     left_fencer: AssignedFencer {
+///         id: Some("32".to_string()),
+///         name: Some("B. Panini".to_string()),
+///         ..AssignedFencer::default()
+///     },
+///     ..MatchAssignment::default()
+/// };
+///
+/// let msg = Message::next_match(assignment).unwrap();
+/// assert_eq!(msg.to_string(), "|EFP1.1|NEXT|17|fm-eq|1|A32|12|||||||||||%|28|P.Martin|%|32|B. Panini|%|");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MatchAssignment {
+    /// Piste (strip) identifier.
+    pub piste: String,
+    /// Competition identifier.
+    pub competition_id: String,
+    /// Competition phase number.
+    pub phase: Option<u8>,
+    /// Pool or tableau identifier.
+    pub pool_tableau: Option<String>,
+    /// Match number within the competition.
+    pub match_number: Option<u8>,
+    /// Round number.
+    pub round: Option<u8>,
+    /// Scheduled start time (e.g., "14:30").
+    pub time: Option<String>,
+    /// Type of competition (Individual or Team).
+    pub competition_type: Option<CompetitionType>,
+    /// Weapon type being used.
+    pub weapon: Option<Weapon>,
+    /// Referee assigned to the bout.
+    pub referee: Referee,
+    /// Fencer assigned to the right side.
+    pub right_fencer: AssignedFencer,
+    /// Fencer assigned to the left side.
+    pub left_fencer: AssignedFencer,
+}
+
+impl MatchAssignment {
+    /// Checks that every field required to announce a bout is present.
+    ///
+    /// The piste, competition, phase, pool/tableau, match number and the id and
+    /// name of both fencers are required.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::MissingField` naming the first missing field.
+    pub fn validate(&self) -> Result<(), ParseError> {
+        if self.piste.is_empty() {
+            return Err(ParseError::MissingField("piste"));
+        }
+        if self.competition_id.is_empty() {
+            return Err(ParseError::MissingField("competition_id"));
+        }
+        if self.phase.is_none() {
+            return Err(ParseError::MissingField("phase"));
+        }
+        if is_blank(&self.pool_tableau) {
+            return Err(ParseError::MissingField("pool_tableau"));
+        }
+        if self.match_number.is_none() {
+            return Err(ParseError::MissingField("match_number"));
+        }
+        if is_blank(&self.right_fencer.id) {
+            return Err(ParseError::MissingField("right_fencer.id"));
+        }
+        if is_blank(&self.right_fencer.name) {
+            return Err(ParseError::MissingField("right_fencer.name"));
+        }
+        if is_blank(&self.left_fencer.id) {
+            return Err(ParseError::MissingField("left_fencer.id"));
+        }
+        if is_blank(&self.left_fencer.name) {
+            return Err(ParseError::MissingField("left_fencer.name"));
+        }
+        Ok(())
+    }
+
+    /// Builds a message carrying this assignment with the given command.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::MissingField` if the assignment is incomplete
+    /// (see [`MatchAssignment::validate`]).
+    pub fn to_message(&self, command: Command) -> Result<Message, ParseError> {
+        self.validate()?;
+
+        Ok(Message {
+            protocol: PROTOCOL_VERSION.into(),
+            command,
+            piste: self.piste.as_str().into(),
+            competition_id: self.competition_id.as_str().into(),
+            phase: self.phase,
+            pool_tableau: self.pool_tableau.as_deref().map(FieldString::from),
+            match_number: self.match_number,
+            round: self.round,
+            time: self.time.as_deref().map(FieldString::from),
+            stopwatch: None,
+            competition_type: self.competition_type.clone(),
+            weapon: self.weapon.clone(),
+            priority: None,
+            state: None,
+            referee: self.referee.clone(),
+            right_fencer: self.right_fencer.to_fencer(),
+            left_fencer: self.left_fencer.to_fencer(),
+        })
+    }
+}
+
+fn is_blank(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(str::is_empty)
+}
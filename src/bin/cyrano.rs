@@ -0,0 +1,234 @@
+//! Command-line tools for EFP captures.
+//!
+//! ```text
+//! cyrano diff [--window MS] [--offset MS] LEFT.log RIGHT.log
+//! cyrano stats [--period MS] [--top N] CAPTURE.log
+//! cyrano gen [--count N] [--seed S] [--invalid RATIO] [--out DIR]
+//! cyrano loadgen [--pistes N] [--speed X] [--info-interval MS] [--seconds S] TARGET
+//! ```
+
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::net::ToSocketAddrs;
+use std::path::Path;
+use std::time::Duration;
+use std::process::ExitCode;
+
+use cyrano::diff::{diff, DEFAULT_WINDOW_MS};
+use cyrano::fixtures;
+use cyrano::loadgen::LoadGenerator;
+use cyrano::logfile::{self, LogEntry};
+use cyrano::report::CaptureReport;
+
+const USAGE: &str = "usage: cyrano diff [--window MS] [--offset MS] LEFT.log RIGHT.log
+       cyrano stats [--period MS] [--top N] CAPTURE.log
+       cyrano gen [--count N] [--seed S] [--invalid RATIO] [--out DIR]
+       cyrano loadgen [--pistes N] [--speed X] [--info-interval MS] [--seconds S] TARGET
+
+diff aligns two message logs by time and content and prints the frames found
+in only one of them (- left, + right) and the fields that differ (~).
+
+  --window MS   tolerance when aligning frames, default 2000
+  --offset MS   shift added to the timestamps of RIGHT.log, may be negative
+
+stats prints the bouts, frames per command, parse errors and longest silences
+of each piste, then the busiest periods of the capture.
+
+  --period MS   length of the periods ranked by traffic, default 60000
+  --top N       number of silences and periods listed, default 5
+
+gen prints generated frames, one per line, or writes one file per frame to a
+fuzzer corpus directory. The same seed always gives the same frames.
+
+  --count N         number of frames, default 100
+  --seed S          seed of the generator, default 0
+  --invalid RATIO   share of frames mutated to be invalid, default 0
+  --out DIR         directory to write frame-NNNNNN.efp files to
+
+loadgen simulates pistes sending generated bouts to the software at TARGET
+(host:port) and reports the frames sent and the HELLO acknowledgment latency.
+
+  --pistes N           number of pistes, default 30
+  --speed X            plays bouts X times faster than real time, default 1
+  --info-interval MS   delay before an apparatus repeats its INFO, default 1000
+  --seconds S          length of the run, default 60";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("diff") => match run_diff(&args[1..]) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::from(1),
+            Err(err) => {
+                eprintln!("cyrano: {}", err);
+                ExitCode::from(2)
+            }
+        },
+        Some("stats") => match run_stats(&args[1..]) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("cyrano: {}", err);
+                ExitCode::from(2)
+            }
+        },
+        Some("gen") => match run_gen(&args[1..]) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("cyrano: {}", err);
+                ExitCode::from(2)
+            }
+        },
+        Some("loadgen") => match run_loadgen(&args[1..]) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("cyrano: {}", err);
+                ExitCode::from(2)
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Runs `cyrano diff` and returns `true` if the logs match.
+fn run_diff(args: &[String]) -> Result<bool, String> {
+    let mut window_ms = DEFAULT_WINDOW_MS;
+    let mut offset_ms = 0i64;
+    let mut paths = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--window" => window_ms = number(args.next(), arg)?,
+            "--offset" => offset_ms = number(args.next(), arg)?,
+            _ => paths.push(arg),
+        }
+    }
+    let [left, right] = paths.as_slice() else {
+        return Err(USAGE.to_string());
+    };
+
+    let left = read_log(left)?;
+    let right: Vec<LogEntry> = read_log(right)?
+        .into_iter()
+        .map(|entry| LogEntry {
+            timestamp_ms: entry.timestamp_ms.saturating_add_signed(offset_ms),
+            ..entry
+        })
+        .collect();
+
+    let items = diff(&left, &right, window_ms);
+    for item in &items {
+        println!("{}", item);
+    }
+    Ok(items.is_empty())
+}
+
+/// Runs `cyrano stats`.
+fn run_stats(args: &[String]) -> Result<(), String> {
+    let mut report = CaptureReport::new();
+    let mut paths = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--period" => report = report.period_ms(number(args.next(), arg)?),
+            "--top" => report = report.top(number(args.next(), arg)?),
+            _ => paths.push(arg),
+        }
+    }
+    let [path] = paths.as_slice() else {
+        return Err(USAGE.to_string());
+    };
+
+    for entry in read_log(path)? {
+        report.observe(&entry);
+    }
+    print!("{}", report);
+    Ok(())
+}
+
+/// Runs `cyrano gen`.
+fn run_gen(args: &[String]) -> Result<(), String> {
+    let mut count = 100;
+    let mut seed = 0;
+    let mut invalid_ratio = 0.0;
+    let mut out = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--count" => count = number(args.next(), arg)?,
+            "--seed" => seed = number(args.next(), arg)?,
+            "--invalid" => invalid_ratio = number(args.next(), arg)?,
+            "--out" => out = Some(args.next().ok_or_else(|| format!("{} expects a directory", arg))?),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let frames = fixtures::random_frames(seed, count, invalid_ratio);
+    let Some(out) = out else {
+        for frame in frames {
+            println!("{}", frame);
+        }
+        return Ok(());
+    };
+
+    fs::create_dir_all(out).map_err(|err| format!("{}: {}", out, err))?;
+    for (index, frame) in frames.iter().enumerate() {
+        let path = Path::new(out).join(format!("frame-{:06}.efp", index));
+        fs::write(&path, frame).map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    Ok(())
+}
+
+/// Runs `cyrano loadgen`.
+fn run_loadgen(args: &[String]) -> Result<(), String> {
+    let mut pistes = 30;
+    let mut speed = 1;
+    let mut info_interval_ms = None;
+    let mut seconds = 60;
+    let mut targets = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pistes" => pistes = number(args.next(), arg)?,
+            "--speed" => speed = number(args.next(), arg)?,
+            "--info-interval" => info_interval_ms = Some(number(args.next(), arg)?),
+            "--seconds" => seconds = number(args.next(), arg)?,
+            _ => targets.push(arg),
+        }
+    }
+    let [target] = targets.as_slice() else {
+        return Err(USAGE.to_string());
+    };
+    let target = target
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| format!("{}: not a host:port address", target))?;
+
+    let mut generator = LoadGenerator::new(target).pistes(pistes).speed(speed);
+    if let Some(interval_ms) = info_interval_ms {
+        generator = generator.info_interval_ms(interval_ms);
+    }
+    let report = generator.run(Duration::from_secs(seconds)).map_err(|err| err.to_string())?;
+    print!("{}", report);
+    Ok(())
+}
+
+fn number<T: std::str::FromStr>(value: Option<&String>, flag: &str) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{} expects a number", flag))
+}
+
+fn read_log(path: &str) -> Result<Vec<LogEntry>, String> {
+    let file = File::open(path).map_err(|err| format!("{}: {}", path, err))?;
+    logfile::read(BufReader::new(file))
+        .collect::<Result<_, _>>()
+        .map_err(|err| format!("{}: {}", path, err))
+}
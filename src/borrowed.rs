@@ -0,0 +1,467 @@
+use std::convert::TryFrom;
+
+use crate::enums::*;
+use crate::error::{ParseError, ParseWarning, WarningKind};
+use crate::fencer::Fencer;
+use crate::inspect::FieldIter;
+use crate::message::{FieldString, Message, GENERAL_FIELD_NAMES};
+use crate::options::{ParseOptions, VendorProfile};
+use crate::referee::{Referee, Sanctions};
+use crate::trailer;
+use crate::utils::{get_required_field, split_zone, FieldReader};
+
+/// Referee fields borrowed from a raw message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RefereeRef<'a> {
+    /// Unique identifier for the referee.
+    pub id: Option<&'a str>,
+    /// Full name of the referee.
+    pub name: Option<&'a str>,
+    /// Three-letter country code of the referee's nation.
+    pub nation: Option<&'a str>,
+    /// Sanctions recorded against officials, read with the extended vendor profile.
+    pub sanctions: Option<Sanctions>,
+}
+
+impl RefereeRef<'_> {
+    /// Copies the fields into an owned [`Referee`].
+    pub fn to_referee(&self) -> Referee {
+        Referee {
+            id: self.id.map(FieldString::from),
+            name: self.name.map(FieldString::from),
+            nation: self.nation.map(FieldString::from),
+            sanctions: self.sanctions,
+        }
+    }
+}
+
+/// Fencer fields borrowed from a raw message.
+///
+/// Text fields point into the raw message; numbers and codes are parsed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FencerRef<'a> {
+    /// Unique identifier for the fencer.
+    pub id: Option<&'a str>,
+    /// Full name of the fencer.
+    pub name: Option<&'a str>,
+    /// Three-letter country code of the fencer's nation.
+    pub nation: Option<&'a str>,
+    /// Current score.
+    pub score: Option<u8>,
+    /// Current status in the match.
+    pub status: Option<FencerStatus>,
+    /// Number of yellow cards.
+    pub yellow_card: Option<u8>,
+    /// Number of red cards.
+    pub red_card: Option<u8>,
+    /// Whether the scoring light is on.
+    pub light: Option<bool>,
+    /// Whether the white (off-target) light is on.
+    pub white_light: Option<bool>,
+    /// Number of medical interventions.
+    pub medical: Option<u8>,
+    /// Reserve status (team events).
+    pub reserve: Option<Reserve>,
+    /// P-card (penalty for non-combativity).
+    pub p_card: Option<PCard>,
+    /// Touches scored in the current relay of a team match.
+    pub bout_score: Option<u8>,
+    /// Video appeals left.
+    pub video_appeals: Option<u8>,
+    /// Whether a video review requested by the fencer is in progress.
+    pub video_review: Option<bool>,
+}
+
+impl<'a> FencerRef<'a> {
+    /// Reads fencer data from a zone reader.
+    pub(crate) fn read(reader: &mut FieldReader<'_, 'a, '_>) -> Result<Self, ParseError> {
+        Ok(FencerRef {
+            id: reader.str(0),
+            name: reader.str(1),
+            nation: reader.str(2),
+            score: reader.u8(3, "score")?,
+            status: reader.enumeration(4, "fencer_status")?,
+            yellow_card: reader.u8(5, "yellow_card")?,
+            red_card: reader.u8(6, "red_card")?,
+            light: reader.bool(7, "light")?,
+            white_light: reader.bool(8, "white_light")?,
+            medical: reader.u8(9, "medical")?,
+            reserve: reader.enumeration(10, "reserve")?,
+            p_card: reader.enumeration(11, "p_card")?,
+            bout_score: reader.u8(12, "bout_score")?,
+            video_appeals: reader.u8(13, "video_appeals")?,
+            video_review: reader.bool(14, "video_review")?,
+        })
+    }
+
+    /// Copies the fields into an owned [`Fencer`].
+    pub fn to_fencer(&self) -> Fencer {
+        Fencer {
+            id: self.id.map(FieldString::from),
+            name: self.name.map(FieldString::from),
+            nation: self.nation.map(FieldString::from),
+            score: self.score,
+            status: self.status.clone(),
+            yellow_card: self.yellow_card,
+            red_card: self.red_card,
+            light: self.light,
+            white_light: self.white_light,
+            medical: self.medical,
+            reserve: self.reserve.clone(),
+            p_card: self.p_card.clone(),
+            bout_score: self.bout_score,
+            video_appeals: self.video_appeals,
+            video_review: self.video_review,
+        }
+    }
+}
+
+/// An EFP message whose text fields borrow from the raw frame.
+///
+/// Parsing a `MessageRef` accepts and rejects exactly the frames [`Message`]
+/// does, with the same warnings, but leaves identifiers, names, times and
+/// codes as slices of the input instead of copying each into a `String`. It
+/// suits scans of large logs where most frames are looked at once and dropped;
+/// [`to_message`](MessageRef::to_message) makes an owned copy when needed.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::borrowed::MessageRef;
+/// use cyrano::enums::Command;
+///
+/// let raw = "|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||H|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|";
+/// let view = MessageRef::try_from(raw).unwrap();
+///
+/// assert_eq!(view.command, Command::Info);
+/// assert_eq!(view.right_fencer.name, Some("P.Martin"));
+/// assert_eq!(view.to_message().left_fencer.score, Some(2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MessageRef<'a> {
+    /// The frame the message was parsed from, without the surrounding
+    /// whitespace and pipes.
+    pub frame: &'a str,
+    /// Protocol version (e.g., "EFP1.1").
+    pub protocol: &'a str,
+    /// Message command type.
+    pub command: Command,
+    /// Piste identifier.
+    pub piste: &'a str,
+    /// Competition identifier.
+    pub competition_id: &'a str,
+    /// Competition phase number.
+    pub phase: Option<u8>,
+    /// Pool or tableau identifier.
+    pub pool_tableau: Option<&'a str>,
+    /// Match number within the competition.
+    pub match_number: Option<u8>,
+    /// Round number.
+    pub round: Option<u8>,
+    /// Current match time.
+    pub time: Option<&'a str>,
+    /// Stopwatch time.
+    pub stopwatch: Option<&'a str>,
+    /// Type of competition.
+    pub competition_type: Option<CompetitionType>,
+    /// Weapon used in the match.
+    pub weapon: Option<Weapon>,
+    /// Priority indicator.
+    pub priority: Option<Priority>,
+    /// Current state of the scoring apparatus.
+    pub state: Option<ApparatusState>,
+    /// Referee information.
+    pub referee: RefereeRef<'a>,
+    /// Fencer on the right side.
+    pub right_fencer: FencerRef<'a>,
+    /// Fencer on the left side.
+    pub left_fencer: FencerRef<'a>,
+}
+
+impl<'a> MessageRef<'a> {
+    /// Parses a message with the given options.
+    ///
+    /// # Errors
+    ///
+    /// See [`Message::parse_with`].
+    pub fn parse_with(raw: &'a str, options: &ParseOptions) -> Result<Self, ParseError> {
+        let (view, _) = MessageRef::parse_zones(raw, options)?;
+        if options.strict {
+            let message = view.to_message();
+            message.validate()?;
+            message.check_white_lights()?;
+        }
+        Ok(view)
+    }
+
+    /// Splits a frame into its zones and reads the fields, without the strict
+    /// length checks done on the owned message.
+    pub(crate) fn parse_zones(
+        raw: &'a str,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>), ParseError> {
+        options.limits.check(raw)?;
+
+        let raw = match options.checksum {
+            Some(algorithm) => trailer::verify(raw, algorithm)?,
+            None => raw.trim(),
+        };
+
+        if raw.is_empty() {
+            return Err(ParseError::EmptyMessage);
+        }
+
+        let raw = raw.trim_matches('|');
+        let zones: Vec<&str> = raw.split('%').collect();
+
+        if zones.is_empty() {
+            return Err(ParseError::InvalidFormat);
+        }
+
+        let general_fields = split_zone(zones[0]);
+
+        let protocol = get_required_field(&general_fields, 0, "protocol")?;
+        if protocol != "EFP1.1" && protocol != "EFP1" {
+            return Err(ParseError::InvalidProtocol(protocol.to_string()));
+        }
+
+        let command = Command::try_from(get_required_field(&general_fields, 1, "command")?)?;
+
+        let mut warnings = Vec::new();
+
+        let carries_bout = matches!(
+            command,
+            Command::Info | Command::Disp | Command::Next | Command::Prev
+        );
+        if carries_bout {
+            for name in GENERAL_FIELD_NAMES.iter().skip(general_fields.len()) {
+                warnings.push(ParseWarning {
+                    zone: Zone::General,
+                    field: name,
+                    kind: WarningKind::FieldMissing,
+                });
+            }
+        }
+
+        // The fields of a HELLO after the competition identify the apparatus
+        // (see `Hello`); they are not bout fields.
+        let bout_fields = match command {
+            Command::Hello => &general_fields[..general_fields.len().min(4)],
+            _ => &general_fields[..],
+        };
+        let mut general = FieldReader::new(bout_fields, Zone::General, options.strict, &mut warnings);
+
+        let piste = general.str(2).unwrap_or_default();
+        let competition_id = general.str(3).unwrap_or_default();
+
+        let phase = general.u8(4, "phase")?;
+        let pool_tableau = general.str(5);
+        let match_number = general.u8(6, "match_number")?;
+        let round = general.u8(7, "round")?;
+        let time = general.str(8);
+        let stopwatch = general.str(9);
+        let competition_type = general.enumeration::<CompetitionType>(10, "competition_type")?;
+        let weapon = general.enumeration::<Weapon>(11, "weapon")?;
+        let priority = general.enumeration::<Priority>(12, "priority")?;
+        let state = general.enumeration::<ApparatusState>(13, "state")?;
+
+        let referee = RefereeRef {
+            id: general.str(14),
+            name: general.str(15),
+            nation: general.str(16),
+            sanctions: if options.profile == VendorProfile::Extended && protocol == "EFP1.1" && bout_fields.len() > 17 {
+                Some(Sanctions {
+                    yellow_card: general.u8(17, "sanctions.yellow_card")?,
+                    red_card: general.u8(18, "sanctions.red_card")?,
+                    black_card: general.u8(19, "sanctions.black_card")?,
+                })
+            } else {
+                None
+            },
+        };
+
+        let (mut right_fencer, mut left_fencer) = if command == Command::Hello {
+            reject_fencer_zones(&zones, options.strict, &mut warnings)?;
+            (FencerRef::default(), FencerRef::default())
+        } else {
+            (
+                read_fencer(&zones, 1, Zone::RightFencer, carries_bout, options, &mut warnings)?,
+                read_fencer(&zones, 2, Zone::LeftFencer, carries_bout, options, &mut warnings)?,
+            )
+        };
+
+        // The relay score is an EFP1.1 addition that only means something in team matches.
+        if protocol != "EFP1.1" || competition_type != Some(CompetitionType::Team) {
+            right_fencer.bout_score = None;
+            left_fencer.bout_score = None;
+        }
+
+        let view = MessageRef {
+            frame: raw,
+            protocol,
+            command,
+            piste,
+            competition_id,
+            phase,
+            pool_tableau,
+            match_number,
+            round,
+            time,
+            stopwatch,
+            competition_type,
+            weapon,
+            priority,
+            state,
+            referee,
+            right_fencer,
+            left_fencer,
+        };
+
+        Ok((view, warnings))
+    }
+
+    /// Returns the zones of the frame as received, separated at `%`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::borrowed::MessageRef;
+    ///
+    /// let view = MessageRef::try_from("|EFP1.1|INFO|17|fm-eq|%|28|P.Martin|FRA|4|%|").unwrap();
+    /// assert_eq!(view.raw_zones(), ["EFP1.1|INFO|17|fm-eq|", "|28|P.Martin|FRA|4|", ""]);
+    /// assert_eq!(view.raw_general_fields(), ["EFP1.1", "INFO", "17", "fm-eq"]);
+    /// ```
+    pub fn raw_zones(&self) -> Vec<&'a str> {
+        self.frame.split('%').collect()
+    }
+
+    /// Returns the fields of the general zone as received.
+    pub fn raw_general_fields(&self) -> Vec<&'a str> {
+        self.frame.split('%').next().map(split_zone).unwrap_or_default()
+    }
+
+    /// Iterates over every known field with its raw text and parsed value,
+    /// for generic inspectors and diff viewers.
+    pub fn fields(&self) -> FieldIter<'a> {
+        FieldIter::new(self)
+    }
+
+    /// Returns the fencer on the given side.
+    pub fn fencer(&self, side: Side) -> &FencerRef<'a> {
+        match side {
+            Side::Right => &self.right_fencer,
+            Side::Left => &self.left_fencer,
+        }
+    }
+
+    /// Copies the message into an owned [`Message`].
+    pub fn to_message(&self) -> Message {
+        Message {
+            protocol: self.protocol.into(),
+            command: self.command.clone(),
+            piste: self.piste.into(),
+            competition_id: self.competition_id.into(),
+            phase: self.phase,
+            pool_tableau: self.pool_tableau.map(FieldString::from),
+            match_number: self.match_number,
+            round: self.round,
+            time: self.time.map(FieldString::from),
+            stopwatch: self.stopwatch.map(FieldString::from),
+            competition_type: self.competition_type.clone(),
+            weapon: self.weapon.clone(),
+            priority: self.priority.clone(),
+            state: self.state.clone(),
+            referee: self.referee.to_referee(),
+            right_fencer: self.right_fencer.to_fencer(),
+            left_fencer: self.left_fencer.to_fencer(),
+        }
+    }
+}
+
+fn read_fencer<'a>(
+    zones: &[&'a str],
+    index: usize,
+    zone: Zone,
+    expected: bool,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<FencerRef<'a>, ParseError> {
+    match zones.get(index) {
+        Some(raw_zone) => {
+            let fields = split_zone(raw_zone);
+            FencerRef::read(&mut FieldReader::new(&fields, zone, options.strict, warnings))
+        }
+        None => {
+            if expected {
+                warnings.push(ParseWarning {
+                    zone,
+                    field: "zone",
+                    kind: WarningKind::FieldMissing,
+                });
+            }
+            Ok(FencerRef::default())
+        }
+    }
+}
+
+/// Rejects the fencer zones filled in a HELLO, or drops them with a warning in lenient mode.
+fn reject_fencer_zones(zones: &[&str], strict: bool, warnings: &mut Vec<ParseWarning>) -> Result<(), ParseError> {
+    for (index, zone) in [(1, Zone::RightFencer), (2, Zone::LeftFencer)] {
+        let Some(raw_zone) = zones.get(index) else {
+            continue;
+        };
+        if split_zone(raw_zone).iter().all(|field| field.is_empty()) {
+            continue;
+        }
+        if strict {
+            return Err(ParseError::UnexpectedZone {
+                command: Command::Hello,
+                zone,
+            });
+        }
+        warnings.push(ParseWarning {
+            zone,
+            field: "zone",
+            kind: WarningKind::FieldInvalid(raw_zone.trim_matches('|').to_string()),
+        });
+    }
+    Ok(())
+}
+
+impl<'a> TryFrom<&'a str> for MessageRef<'a> {
+    type Error = ParseError;
+
+    fn try_from(raw: &'a str) -> Result<Self, Self::Error> {
+        MessageRef::parse_with(raw, &ParseOptions::default())
+    }
+}
+
+impl From<&MessageRef<'_>> for Message {
+    fn from(view: &MessageRef<'_>) -> Self {
+        view.to_message()
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus;
+
+    #[test]
+    fn test_matches_owned_parser() {
+        let options = ParseOptions::default();
+        for entry in corpus::corpus() {
+            let owned = Message::parse_with_warnings(entry.frame, &options);
+            let view = MessageRef::parse_zones(entry.frame, &options);
+            match (owned, view) {
+                (Ok(owned), Ok((view, warnings))) => assert_eq!(owned, (view.to_message(), warnings), "{}", entry.name),
+                (Err(owned), Err(view)) => assert_eq!(owned.to_string(), view.to_string(), "{}", entry.name),
+                _ => panic!("parsers disagree on {}", entry.name),
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use super::enums::{ApparatusState, Command, CompetitionType, FencerStatus, Priority, Reserve, Side, Weapon};
+use super::error::{BuildError, ValueError, Violation};
+use super::fencer::Fencer;
+use super::limits::{
+    check, sanitize, MAX_COMPETITION_ID_LENGTH, MAX_PISTE_LENGTH, MAX_POOL_TABLEAU_LENGTH, MAX_TIME_LENGTH,
+};
+use super::message::Message;
+use super::options::TextPolicy;
+use super::referee::Referee;
+use super::utils::field;
+
+/// Step-by-step construction of an outgoing message.
+///
+/// Text values are checked against the specification limits as they are set.
+/// With `TextPolicy::Reject` (the default) invalid values are reported by
+/// [`MessageBuilder::build`]; with `TextPolicy::Sanitize` values are fixed on
+/// the fly. Rules spanning several fields are checked by `build` under both
+/// policies.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::builder::MessageBuilder;
+/// use cyrano::enums::{Command, Weapon};
+///
+/// let msg = MessageBuilder::new(Command::Disp)
+///     .piste("17")
+///     .competition_id("fm-eq")
+///     .phase(1)
+///     .weapon(Weapon::Foil)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(msg.piste, "17");
+/// assert_eq!(msg.weapon, Some(Weapon::Foil));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MessageBuilder {
+    message: Message,
+    policy: TextPolicy,
+    errors: Vec<ValueError>,
+}
+
+impl MessageBuilder {
+    /// Creates a builder for a message with the given command.
+    pub fn new(command: Command) -> Self {
+        MessageBuilder {
+            message: Message::new(command, "", ""),
+            policy: TextPolicy::default(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Sets the policy applied to text values set after this call.
+    pub fn policy(mut self, policy: TextPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets the piste identifier.
+    pub fn piste(mut self, piste: impl Into<String>) -> Self {
+        self.message.piste = field(self.text("piste", piste.into(), MAX_PISTE_LENGTH));
+        self
+    }
+
+    /// Sets the competition identifier.
+    pub fn competition_id(mut self, competition_id: impl Into<String>) -> Self {
+        self.message.competition_id =
+            field(self.text("competition_id", competition_id.into(), MAX_COMPETITION_ID_LENGTH));
+        self
+    }
+
+    /// Sets the competition phase number.
+    pub fn phase(mut self, phase: u8) -> Self {
+        self.message.phase = Some(phase);
+        self
+    }
+
+    /// Sets the pool or tableau identifier.
+    pub fn pool_tableau(mut self, pool_tableau: impl Into<String>) -> Self {
+        self.message.pool_tableau =
+            Some(field(self.text("pool_tableau", pool_tableau.into(), MAX_POOL_TABLEAU_LENGTH)));
+        self
+    }
+
+    /// Sets the match number.
+    pub fn match_number(mut self, match_number: u8) -> Self {
+        self.message.match_number = Some(match_number);
+        self
+    }
+
+    /// Sets the round number.
+    pub fn round(mut self, round: u8) -> Self {
+        self.message.round = Some(round);
+        self
+    }
+
+    /// Sets the current match time.
+    pub fn time(mut self, time: impl Into<String>) -> Self {
+        self.message.time = Some(field(self.text("time", time.into(), MAX_TIME_LENGTH)));
+        self
+    }
+
+    /// Sets the stopwatch time.
+    pub fn stopwatch(mut self, stopwatch: impl Into<String>) -> Self {
+        self.message.stopwatch = Some(field(self.text("stopwatch", stopwatch.into(), MAX_TIME_LENGTH)));
+        self
+    }
+
+    /// Sets the competition type.
+    pub fn competition_type(mut self, competition_type: CompetitionType) -> Self {
+        self.message.competition_type = Some(competition_type);
+        self
+    }
+
+    /// Sets the weapon.
+    pub fn weapon(mut self, weapon: Weapon) -> Self {
+        self.message.weapon = Some(weapon);
+        self
+    }
+
+    /// Sets the priority indicator.
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.message.priority = Some(priority);
+        self
+    }
+
+    /// Sets the apparatus state.
+    pub fn state(mut self, state: ApparatusState) -> Self {
+        self.message.state = Some(state);
+        self
+    }
+
+    /// Sets the referee.
+    pub fn referee(mut self, mut referee: Referee) -> Self {
+        match self.policy {
+            TextPolicy::Reject => self.record(referee.validate()),
+            TextPolicy::Sanitize => referee.sanitize(),
+        }
+        self.message.referee = referee;
+        self
+    }
+
+    /// Sets the fencer on the right.
+    pub fn right_fencer(mut self, fencer: Fencer) -> Self {
+        self.message.right_fencer =
+            self.fencer(fencer, ["right_fencer.id", "right_fencer.name", "right_fencer.nation"]);
+        self
+    }
+
+    /// Sets the fencer on the left.
+    pub fn left_fencer(mut self, fencer: Fencer) -> Self {
+        self.message.left_fencer =
+            self.fencer(fencer, ["left_fencer.id", "left_fencer.name", "left_fencer.nation"]);
+        self
+    }
+
+    /// Finishes the message.
+    ///
+    /// Besides the text values, the message must follow these rules:
+    ///
+    /// - a priority other than `N` is only given at foil and sabre
+    /// - the white light is only lit at foil
+    /// - a reserve is only introduced in team competitions
+    /// - a fencer only wins if the opponent lost, abandoned or was excluded
+    ///
+    /// # Errors
+    ///
+    /// Returns a `BuildError` listing every `ValueError` recorded by a setter
+    /// under `TextPolicy::Reject` and every broken rule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::builder::MessageBuilder;
+    /// use cyrano::enums::{Command, Priority, Weapon};
+    /// use cyrano::error::Violation;
+    ///
+    /// let err = MessageBuilder::new(Command::Disp)
+    ///     .piste("17|A")
+    ///     .weapon(Weapon::Epee)
+    ///     .priority(Priority::Right)
+    ///     .build()
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(err.violations.len(), 2);
+    /// assert_eq!(err.violations[1], Violation::PriorityAtEpee);
+    /// ```
+    pub fn build(self) -> Result<Message, BuildError> {
+        let mut violations: Vec<Violation> = self.errors.into_iter().map(Violation::Value).collect();
+        violations.extend(inconsistencies(&self.message));
+
+        if violations.is_empty() {
+            Ok(self.message)
+        } else {
+            Err(BuildError { violations })
+        }
+    }
+
+    fn text(&mut self, field: &'static str, value: String, max: usize) -> String {
+        match self.policy {
+            TextPolicy::Reject => {
+                self.record(check(field, &value, max));
+                value
+            }
+            TextPolicy::Sanitize => sanitize(&value, max),
+        }
+    }
+
+    fn fencer(&mut self, mut fencer: Fencer, names: [&'static str; 3]) -> Fencer {
+        match self.policy {
+            TextPolicy::Reject => self.record(fencer.validate_named(names)),
+            TextPolicy::Sanitize => fencer.sanitize(),
+        }
+        fencer
+    }
+
+    fn record(&mut self, result: Result<(), ValueError>) {
+        if let Err(err) = result {
+            self.errors.push(err);
+        }
+    }
+}
+
+/// Returns the cross-field rules broken by a message.
+fn inconsistencies(message: &Message) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let weapon = message.weapon.as_ref();
+
+    if weapon == Some(&Weapon::Epee) && matches!(message.priority, Some(Priority::Right | Priority::Left)) {
+        violations.push(Violation::PriorityAtEpee);
+    }
+
+    let fencers = [
+        (Side::Right, &message.right_fencer, &message.left_fencer),
+        (Side::Left, &message.left_fencer, &message.right_fencer),
+    ];
+    for (side, fencer, opponent) in fencers {
+        if fencer.white_light == Some(true) && weapon.is_some_and(|w| *w != Weapon::Foil) {
+            violations.push(Violation::WhiteLightOutsideFoil(side));
+        }
+        if fencer.reserve == Some(Reserve::Introduce) && message.competition_type != Some(CompetitionType::Team) {
+            violations.push(Violation::TeamFieldOutsideTeam(match side {
+                Side::Right => "right_fencer.reserve",
+                Side::Left => "left_fencer.reserve",
+            }));
+        }
+        let lost = matches!(
+            opponent.status,
+            Some(FencerStatus::Defeat | FencerStatus::Abandonment | FencerStatus::Exclusion)
+        );
+        if fencer.status == Some(FencerStatus::Victory) && !lost {
+            violations.push(Violation::VictoryWithoutLoser(side));
+        }
+    }
+
+    violations
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cross_field_rules() {
+        let fencer = |status, white_light, reserve| Fencer {
+            status: Some(status),
+            white_light: Some(white_light),
+            reserve: Some(reserve),
+            ..Fencer::default()
+        };
+
+        let err = MessageBuilder::new(Command::Info)
+            .weapon(Weapon::Sabre)
+            .priority(Priority::Left)
+            .right_fencer(fencer(FencerStatus::Victory, true, Reserve::Introduce))
+            .left_fencer(fencer(FencerStatus::Undefined, false, Reserve::None))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.violations,
+            vec![
+                Violation::WhiteLightOutsideFoil(Side::Right),
+                Violation::TeamFieldOutsideTeam("right_fencer.reserve"),
+                Violation::VictoryWithoutLoser(Side::Right),
+            ]
+        );
+
+        let message = MessageBuilder::new(Command::Info)
+            .competition_type(CompetitionType::Team)
+            .weapon(Weapon::Foil)
+            .right_fencer(fencer(FencerStatus::Victory, true, Reserve::Introduce))
+            .left_fencer(fencer(FencerStatus::Abandonment, false, Reserve::None))
+            .build();
+        assert!(message.is_ok());
+    }
+
+    #[test]
+    fn test_time_fields_follow_text_policy() {
+        let err = MessageBuilder::new(Command::Info)
+            .time("1|2")
+            .stopwatch("12:34:56:78")
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.violations,
+            vec![
+                Violation::Value(ValueError::ForbiddenCharacter { field: "time", character: '|' }),
+                Violation::Value(ValueError::TooLong {
+                    field: "stopwatch",
+                    max: MAX_TIME_LENGTH,
+                    value: "12:34:56:78".to_string(),
+                }),
+            ]
+        );
+
+        let message = MessageBuilder::new(Command::Info)
+            .policy(TextPolicy::Sanitize)
+            .time("1|2")
+            .stopwatch("12:34:56:78")
+            .build()
+            .unwrap();
+        assert_eq!(message.time.as_deref(), Some("1 2"));
+        assert_eq!(message.stopwatch.as_deref(), Some("12:34:56"));
+    }
+}
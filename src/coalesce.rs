@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use crate::enums::Command;
+use crate::message::Message;
+use crate::piste::PisteId;
+
+/// Default window during which repeats of the last INFO are dropped, in milliseconds.
+pub const DEFAULT_REPEAT_WINDOW_MS: u64 = 1000;
+
+/// Default minimum delay between two INFO that only move the clock, in milliseconds.
+pub const DEFAULT_CLOCK_INTERVAL_MS: u64 = 250;
+
+/// Thins out the INFO flood of an apparatus before it reaches consumers.
+///
+/// Each INFO is compared with the last one let through for its piste:
+///
+/// - an identical INFO is dropped, unless the repeat window has elapsed, so
+///   that consumers still hear from an idle piste;
+/// - an INFO that only moves the clock is let through at most once per clock
+///   interval; the latest one held back is returned by [`flush`](Coalescer::flush);
+/// - any other change (score, lights, cards, state...) is let through at once.
+///
+/// Other commands are always let through. The coalescer does not perform any
+/// I/O: callers offer the messages they receive, with the time of reception.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::coalesce::Coalescer;
+/// use cyrano::message::Message;
+///
+/// let mut coalescer = Coalescer::new();
+/// let info = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|2|10:30|2:59|I|F||F|%|").unwrap();
+///
+/// assert!(coalescer.offer(&info, 0));
+/// assert!(!coalescer.offer(&info, 100));
+///
+/// let mut touch = info.clone();
+/// touch.right_fencer.score = Some(1);
+/// assert!(coalescer.offer(&touch, 150));
+/// assert_eq!(coalescer.suppressed(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Coalescer {
+    repeat_window_ms: u64,
+    clock_interval_ms: u64,
+    pistes: HashMap<PisteId, PisteFeed>,
+    suppressed: u64,
+}
+
+#[derive(Debug, Clone)]
+struct PisteFeed {
+    last: Message,
+    last_ms: u64,
+    held: Option<Message>,
+}
+
+impl Default for Coalescer {
+    fn default() -> Self {
+        Coalescer::new()
+    }
+}
+
+impl Coalescer {
+    /// Creates a coalescer with the default windows.
+    pub fn new() -> Self {
+        Coalescer {
+            repeat_window_ms: DEFAULT_REPEAT_WINDOW_MS,
+            clock_interval_ms: DEFAULT_CLOCK_INTERVAL_MS,
+            pistes: HashMap::new(),
+            suppressed: 0,
+        }
+    }
+
+    /// Sets the window during which repeats of the last INFO are dropped.
+    pub fn repeat_window_ms(mut self, window_ms: u64) -> Self {
+        self.repeat_window_ms = window_ms;
+        self
+    }
+
+    /// Sets the minimum delay between two INFO that only move the clock.
+    pub fn clock_interval_ms(mut self, interval_ms: u64) -> Self {
+        self.clock_interval_ms = interval_ms;
+        self
+    }
+
+    /// Returns the number of messages dropped or held back so far.
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed
+    }
+
+    /// Offers a received message and returns `true` if it should be passed on.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message received
+    /// * `now_ms` - Time of reception in milliseconds, on the clock given to [`flush`](Coalescer::flush)
+    pub fn offer(&mut self, message: &Message, now_ms: u64) -> bool {
+        if message.command != Command::Info {
+            return true;
+        }
+
+        let piste = PisteId::from(message.piste.as_ref());
+        let Some(feed) = self.pistes.get_mut(&piste) else {
+            self.pistes.insert(piste, PisteFeed::new(message, now_ms));
+            return true;
+        };
+
+        let elapsed_ms = now_ms.saturating_sub(feed.last_ms);
+        let pass = if *message == feed.last {
+            elapsed_ms >= self.repeat_window_ms
+        } else if clock_only(message, &feed.last) {
+            elapsed_ms >= self.clock_interval_ms
+        } else {
+            true
+        };
+
+        if pass {
+            *feed = PisteFeed::new(message, now_ms);
+        } else {
+            self.suppressed += 1;
+            if *message != feed.last {
+                feed.held = Some(message.clone());
+            }
+        }
+        pass
+    }
+
+    /// Returns the clock updates held back whose interval has now elapsed.
+    ///
+    /// Call it regularly so that the clock shown downstream does not freeze
+    /// when the apparatus stops sending right after a held-back update.
+    pub fn flush(&mut self, now_ms: u64) -> Vec<Message> {
+        let mut released = Vec::new();
+        for feed in self.pistes.values_mut() {
+            if now_ms.saturating_sub(feed.last_ms) < self.clock_interval_ms {
+                continue;
+            }
+            if let Some(held) = feed.held.take() {
+                *feed = PisteFeed::new(&held, now_ms);
+                released.push(held);
+            }
+        }
+        released
+    }
+}
+
+impl PisteFeed {
+    fn new(message: &Message, now_ms: u64) -> Self {
+        PisteFeed {
+            last: message.clone(),
+            last_ms: now_ms,
+            held: None,
+        }
+    }
+}
+
+/// Returns `true` if the two messages only differ by their clock.
+fn clock_only(message: &Message, last: &Message) -> bool {
+    message.time != last.time && Message { time: last.time.clone(), ..message.clone() } == *last
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_clock_updates_are_rate_limited() {
+        let mut coalescer = Coalescer::new().clock_interval_ms(500);
+        let info = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|2|10:30|2:59|F|F||F|%|").unwrap();
+        let at = |time: &str| Message { time: Some(time.into()), ..info.clone() };
+        let hello = Message::new(Command::Hello, "17", "fm-eq");
+
+        assert!(coalescer.offer(&info, 0));
+        assert!(!coalescer.offer(&at("2:58"), 100));
+        assert!(!coalescer.offer(&at("2:57"), 200));
+        assert!(coalescer.offer(&hello, 250));
+        assert!(coalescer.flush(499).is_empty());
+        assert_eq!(coalescer.flush(500), vec![at("2:57")]);
+
+        assert!(!coalescer.offer(&at("2:57"), 600));
+        assert!(coalescer.offer(&at("2:56"), 1_000));
+        assert!(coalescer.flush(2_000).is_empty());
+        assert_eq!(coalescer.suppressed(), 3);
+
+        // Another piste has its own feed.
+        assert!(coalescer.offer(&Message { piste: "3".into(), ..info.clone() }, 1_000));
+    }
+}
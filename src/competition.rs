@@ -0,0 +1,407 @@
+use std::collections::{HashMap, HashSet};
+
+use super::enums::{ApparatusState, Weapon};
+use super::fencer::Fencer;
+use super::locale::Locale;
+use super::message::Message;
+use super::rules::RuleProfile;
+use super::state::{MatchEvent, MatchState, PisteManager};
+use super::summary::BoutSummary;
+
+/// Final result of a bout, recorded when it reaches a final status.
+#[derive(Debug, Clone)]
+pub struct BoutResult {
+    /// Competition identifier.
+    pub competition_id: String,
+    /// Piste the bout was fenced on.
+    pub piste: String,
+    /// Competition phase number.
+    pub phase: Option<u8>,
+    /// Pool or tableau identifier.
+    pub pool_tableau: Option<String>,
+    /// Match number within the competition.
+    pub match_number: Option<u8>,
+    /// Final state of the fencer on the right.
+    pub right_fencer: Fencer,
+    /// Final state of the fencer on the left.
+    pub left_fencer: Fencer,
+}
+
+impl BoutResult {
+    fn from_message(message: &Message) -> Self {
+        BoutResult {
+            competition_id: message.competition_id.to_string(),
+            piste: message.piste.to_string(),
+            phase: message.phase,
+            pool_tableau: message.pool_tableau.as_deref().map(String::from),
+            match_number: message.match_number,
+            right_fencer: message.right_fencer.clone(),
+            left_fencer: message.left_fencer.clone(),
+        }
+    }
+}
+
+/// Event emitted by the [`CompetitionTracker`].
+#[derive(Debug, Clone)]
+pub enum CompetitionEvent {
+    /// A change on one piste of a competition.
+    Piste {
+        competition_id: String,
+        piste: String,
+        event: MatchEvent,
+    },
+    /// A bout reached its final status.
+    BoutCompleted(Box<BoutResult>),
+    /// Every bout of a phase has been fenced.
+    PhaseComplete { competition_id: String, phase: u8 },
+}
+
+/// Venue-wide view aggregating every piste of every competition.
+///
+/// Pistes are grouped by the `competition_id` carried in their messages. A phase
+/// is reported complete once no piste is still fencing one of its bouts and, if
+/// an expected bout count was registered with
+/// [`CompetitionTracker::set_expected_bouts`], once that many results are known.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::competition::{CompetitionEvent, CompetitionTracker};
+/// use cyrano::message::Message;
+///
+/// let mut tracker = CompetitionTracker::new();
+/// tracker.set_expected_bouts("fm-eq", 1, 1);
+///
+/// tracker.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+/// let events = tracker.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1|||||E||E|%|28|P.Martin|FRA|5|V|%|32|B. Panini|ITA|2|D|%|").unwrap());
+///
+/// assert!(events.iter().any(|e| matches!(e, CompetitionEvent::PhaseComplete { phase: 1, .. })));
+/// assert_eq!(tracker.results("fm-eq", 1, Some("P3")).len(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CompetitionTracker {
+    competitions: HashMap<String, PisteManager>,
+    results: Vec<BoutResult>,
+    expected_bouts: HashMap<(String, u8), usize>,
+    completed_phases: HashSet<(String, u8)>,
+}
+
+impl CompetitionTracker {
+    /// Creates an empty tracker.
+    pub fn new() -> Self {
+        CompetitionTracker::default()
+    }
+
+    /// Registers the number of bouts a phase is expected to contain.
+    ///
+    /// # Arguments
+    ///
+    /// * `competition_id` - Competition identifier
+    /// * `phase` - Phase number
+    /// * `count` - Number of bouts in the phase
+    pub fn set_expected_bouts(&mut self, competition_id: impl Into<String>, phase: u8, count: usize) {
+        self.expected_bouts.insert((competition_id.into(), phase), count);
+    }
+
+    /// Applies a message and returns the resulting competition-level events.
+    pub fn apply(&mut self, message: &Message) -> Vec<CompetitionEvent> {
+        if !message.carries_bout() {
+            return Vec::new();
+        }
+
+        let competition_id = message.competition_id.to_string();
+        let piste_events = self
+            .competitions
+            .entry(competition_id.clone())
+            .or_default()
+            .apply(message);
+
+        let mut events = Vec::new();
+        let mut finished = false;
+        for event in piste_events {
+            finished = finished || event == MatchEvent::BoutFinished;
+            events.push(CompetitionEvent::Piste {
+                competition_id: competition_id.clone(),
+                piste: message.piste.to_string(),
+                event,
+            });
+        }
+
+        if finished {
+            let result = BoutResult::from_message(message);
+            self.results.push(result.clone());
+            events.push(CompetitionEvent::BoutCompleted(Box::new(result)));
+
+            if let Some(phase) = message.phase {
+                if self.is_phase_complete(&competition_id, phase)
+                    && self.completed_phases.insert((competition_id.clone(), phase))
+                {
+                    events.push(CompetitionEvent::PhaseComplete {
+                        competition_id,
+                        phase,
+                    });
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Iterates over the known competition identifiers.
+    pub fn competitions(&self) -> impl Iterator<Item = &str> {
+        self.competitions.keys().map(String::as_str)
+    }
+
+    /// Returns the pistes of a competition.
+    pub fn pistes(&self, competition_id: &str) -> Option<&PisteManager> {
+        self.competitions.get(competition_id)
+    }
+
+    /// Returns every bout, across all competitions, whose apparatus is in the given state.
+    pub fn bouts_in_state(&self, state: &ApparatusState) -> Vec<&MatchState> {
+        self.competitions
+            .values()
+            .flat_map(PisteManager::iter)
+            .filter(|m| m.state() == Some(state))
+            .collect()
+    }
+
+    /// Returns the results recorded for a phase, optionally restricted to one pool or tableau.
+    ///
+    /// # Arguments
+    ///
+    /// * `competition_id` - Competition identifier
+    /// * `phase` - Phase number
+    /// * `pool_tableau` - Pool or tableau identifier, or `None` for the whole phase
+    pub fn results(&self, competition_id: &str, phase: u8, pool_tableau: Option<&str>) -> Vec<&BoutResult> {
+        self.results
+            .iter()
+            .filter(|r| r.competition_id == competition_id && r.phase == Some(phase))
+            .filter(|r| pool_tableau.is_none() || r.pool_tableau.as_deref() == pool_tableau)
+            .collect()
+    }
+
+    /// Returns `true` if the phase has been reported complete.
+    pub fn phase_completed(&self, competition_id: &str, phase: u8) -> bool {
+        self.completed_phases
+            .contains(&(competition_id.to_string(), phase))
+    }
+
+    fn is_phase_complete(&self, competition_id: &str, phase: u8) -> bool {
+        let key = (competition_id.to_string(), phase);
+        if let Some(expected) = self.expected_bouts.get(&key) {
+            if self.results(competition_id, phase, None).len() < *expected {
+                return false;
+            }
+        }
+
+        self.competitions
+            .get(competition_id)
+            .map(|pistes| {
+                pistes.iter().all(|m| {
+                    m.is_finished() || m.message().map(|msg| msg.phase) != Some(Some(phase))
+                })
+            })
+            .unwrap_or(true)
+    }
+}
+
+/// Gender of the fencers of a competition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Gender {
+    /// Men's event.
+    Men,
+    /// Women's event.
+    Women,
+    /// Mixed event.
+    Mixed,
+}
+
+/// Classification of wheelchair (IWAS) fencers by their degree of impairment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WheelchairCategory {
+    /// Fencers with good trunk control.
+    A,
+    /// Fencers with impaired trunk control.
+    B,
+    /// Fencers with impairment of all four limbs.
+    C,
+}
+
+/// What spectators should see instead of a competition identifier.
+///
+/// The EFP frames only carry short identifiers such as `fm-eq`; the
+/// competition software knows the rest and registers it once in a
+/// [`CompetitionRegistry`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompetitionInfo {
+    /// Name of the competition, e.g. "Challenge Monal".
+    pub name: String,
+    /// Weapon fenced.
+    pub weapon: Option<Weapon>,
+    /// Gender of the fencers.
+    pub gender: Option<Gender>,
+    /// Date of the competition, as printed, e.g. "2026-05-17".
+    pub date: Option<String>,
+    /// Age category, e.g. "Senior" or "U20".
+    pub category: Option<String>,
+    /// Category of a wheelchair event; `None` for standing fencing.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wheelchair_category: Option<WheelchairCategory>,
+}
+
+impl CompetitionInfo {
+    /// Creates the information of a competition with only its name.
+    pub fn new(name: impl Into<String>) -> Self {
+        CompetitionInfo {
+            name: name.into(),
+            ..CompetitionInfo::default()
+        }
+    }
+
+    /// Sets the weapon.
+    pub fn weapon(mut self, weapon: Weapon) -> Self {
+        self.weapon = Some(weapon);
+        self
+    }
+
+    /// Sets the gender of the fencers.
+    pub fn gender(mut self, gender: Gender) -> Self {
+        self.gender = Some(gender);
+        self
+    }
+
+    /// Sets the date.
+    pub fn date(mut self, date: impl Into<String>) -> Self {
+        self.date = Some(date.into());
+        self
+    }
+
+    /// Sets the age category.
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    /// Sets the category of a wheelchair event.
+    pub fn wheelchair_category(mut self, category: WheelchairCategory) -> Self {
+        self.wheelchair_category = Some(category);
+        self
+    }
+
+    /// Returns the rules the competition is fenced under, from its categories:
+    /// wheelchair rules for wheelchair events, veteran rules for an age
+    /// category starting with "Vet" or "V" and an age (V40, V50...), youth
+    /// rules for under-15 and younger categories (U13...), and FIE senior rules
+    /// otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::competition::{CompetitionInfo, WheelchairCategory};
+    /// use cyrano::rules::RuleProfile;
+    ///
+    /// assert_eq!(CompetitionInfo::new("Open").category("V50").rule_profile(), RuleProfile::VETERAN);
+    /// assert_eq!(CompetitionInfo::new("Open").category("U11").rule_profile(), RuleProfile::YOUTH);
+    /// assert_eq!(CompetitionInfo::new("Open").category("U20").rule_profile(), RuleProfile::FIE_SENIOR);
+    /// let iwas = CompetitionInfo::new("World Cup").wheelchair_category(WheelchairCategory::B);
+    /// assert_eq!(iwas.rule_profile(), RuleProfile::WHEELCHAIR);
+    /// ```
+    pub fn rule_profile(&self) -> RuleProfile {
+        if self.wheelchair_category.is_some() {
+            return RuleProfile::WHEELCHAIR;
+        }
+        let category = self.category.as_deref().unwrap_or_default().trim().to_uppercase();
+        let age = |prefix: &str| category.strip_prefix(prefix).and_then(|age| age.parse::<u8>().ok());
+        if category.starts_with("VET") || category.starts_with("VÉT") || age("V").is_some() {
+            RuleProfile::VETERAN
+        } else if age("U").is_some_and(|age| age <= 15) {
+            RuleProfile::YOUTH
+        } else {
+            RuleProfile::FIE_SENIOR
+        }
+    }
+
+    /// Returns the name followed by the weapon, gender, wheelchair category,
+    /// age category and date known, in the given language.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::competition::{CompetitionInfo, Gender};
+    /// use cyrano::enums::Weapon;
+    /// use cyrano::locale::Locale;
+    ///
+    /// let info = CompetitionInfo::new("Challenge Monal").weapon(Weapon::Epee).gender(Gender::Men).category("Senior");
+    /// assert_eq!(info.title(Locale::English), "Challenge Monal (Épée, Men, Senior)");
+    /// assert_eq!(CompetitionInfo::new("Coupe de Paris").title(Locale::English), "Coupe de Paris");
+    /// ```
+    pub fn title(&self, locale: Locale) -> String {
+        let details: Vec<&str> = [
+            self.weapon.as_ref().map(|weapon| weapon.display_name(locale)),
+            self.gender.map(|gender| gender.display_name(locale)),
+            self.wheelchair_category.map(|category| category.display_name(locale)),
+            self.category.as_deref(),
+            self.date.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if details.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, details.join(", "))
+        }
+    }
+}
+
+/// Competition information keyed by the `competition_id` of the frames,
+/// joined by the outputs shown to spectators.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::competition::{CompetitionInfo, CompetitionRegistry};
+///
+/// let mut registry = CompetitionRegistry::new();
+/// registry.insert("fm-eq", CompetitionInfo::new("Challenge Monal"));
+///
+/// assert_eq!(registry.display_name("fm-eq"), "Challenge Monal");
+/// assert_eq!(registry.display_name("sf-ind"), "sf-ind");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompetitionRegistry {
+    competitions: HashMap<String, CompetitionInfo>,
+}
+
+impl CompetitionRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        CompetitionRegistry::default()
+    }
+
+    /// Registers a competition, replacing any previous information.
+    pub fn insert(&mut self, competition_id: impl Into<String>, info: CompetitionInfo) {
+        self.competitions.insert(competition_id.into(), info);
+    }
+
+    /// Returns the information of a competition.
+    pub fn get(&self, competition_id: &str) -> Option<&CompetitionInfo> {
+        self.competitions.get(competition_id)
+    }
+
+    /// Returns the name of a competition, or its identifier if it is not registered.
+    pub fn display_name<'a>(&'a self, competition_id: &'a str) -> &'a str {
+        self.get(competition_id).map_or(competition_id, |info| info.name.as_str())
+    }
+
+    /// Attaches the information of its competition to a bout summary.
+    pub fn annotate(&self, summary: &mut BoutSummary) {
+        summary.competition = self.get(&summary.competition_id).cloned();
+    }
+}
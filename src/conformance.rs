@@ -0,0 +1,381 @@
+use std::fmt::Display;
+
+use super::enums::Command;
+use super::error::WarningKind;
+use super::logfile::{Direction, LogEntry};
+use super::message::Message;
+use super::options::ParseOptions;
+use super::session::Role;
+
+/// Configuration of a conformance run.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConformanceConfig {
+    /// Role of the device under test.
+    pub role: Role,
+    /// Direction, in the log, of the frames emitted by the device under test.
+    ///
+    /// Logs recorded by the peer of the device see its frames as received.
+    pub device_direction: Direction,
+    /// Maximum delay for the device to answer a frame with an ACK or NAK, in milliseconds.
+    pub ack_timeout_ms: u64,
+}
+
+impl Default for ConformanceConfig {
+    fn default() -> Self {
+        ConformanceConfig {
+            role: Role::Machine,
+            device_direction: Direction::Received,
+            ack_timeout_ms: 1000,
+        }
+    }
+}
+
+/// Outcome of a single conformance check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    /// The device behaved as the specification requires.
+    Pass,
+    /// The device violated the specification at least once.
+    Fail,
+    /// The log did not contain the traffic needed to run the check.
+    Skipped,
+}
+
+/// Result of a single conformance check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CheckResult {
+    /// Stable identifier of the check (e.g., "handshake").
+    pub check: &'static str,
+    /// Outcome of the check.
+    pub outcome: Outcome,
+    /// One line per violation, prefixed with the timestamp of the offending frame.
+    pub details: Vec<String>,
+}
+
+/// Report produced by a conformance run.
+///
+/// The report is serializable with serde (feature `serde`) and its `Display`
+/// implementation follows the Test Anything Protocol, so it can be consumed by
+/// standard tooling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConformanceReport {
+    /// Number of frames emitted by the device under test.
+    pub device_frames: usize,
+    /// Number of frames sent to the device under test.
+    pub peer_frames: usize,
+    /// Largest observed ACK delay, in milliseconds.
+    pub max_ack_delay_ms: Option<u64>,
+    /// Results of the individual checks, in a stable order.
+    pub checks: Vec<CheckResult>,
+}
+
+impl ConformanceReport {
+    /// Returns `true` if no check failed.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.outcome != Outcome::Fail)
+    }
+}
+
+impl Display for ConformanceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "1..{}", self.checks.len())?;
+        for (index, check) in self.checks.iter().enumerate() {
+            match check.outcome {
+                Outcome::Pass => writeln!(f, "ok {} - {}", index + 1, check.check)?,
+                Outcome::Fail => writeln!(f, "not ok {} - {}", index + 1, check.check)?,
+                Outcome::Skipped => writeln!(f, "ok {} - {} # SKIP", index + 1, check.check)?,
+            }
+            for detail in &check.details {
+                writeln!(f, "# {}", detail)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Frame sent to the device that is still waiting for its ACK or NAK.
+#[derive(Debug, Clone)]
+struct PendingAck {
+    timestamp_ms: u64,
+    command: Command,
+}
+
+/// Battery of protocol checks run against the traffic of a device under test.
+///
+/// Feed the frames exchanged with the device to [`Conformance::observe`], either
+/// live as they are captured or from a recorded log, then call
+/// [`Conformance::report`]. The checks are:
+///
+/// - `parse`: every frame of the device is a valid EFP message
+/// - `strict_values`: every field value of the device is valid in strict mode
+/// - `handshake`: the device opens the exchange with a HELLO
+/// - `roles`: the device only sends commands allowed for its role
+/// - `required_fields`: bout messages of the device carry every field and zone
+/// - `ack_timing`: frames calling for an acknowledgment are answered in time
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::conformance::{self, ConformanceConfig};
+/// use cyrano::logfile::LogEntry;
+///
+/// let log = vec![
+///     LogEntry::received(0, "|EFP1.1|HELLO|17|fm-eq|%|"),
+///     LogEntry::sent(100, "|EFP1.1|DISP|17|fm-eq|%|"),
+///     LogEntry::received(180, "|EFP1.1|ACK|17|fm-eq|%|"),
+/// ];
+///
+/// let report = conformance::run(&log, &ConformanceConfig::default());
+/// assert!(report.passed());
+/// assert_eq!(report.max_ack_delay_ms, Some(80));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Conformance {
+    config: ConformanceConfig,
+    device_frames: usize,
+    peer_frames: usize,
+    first_device_command: Option<Command>,
+    parse_failures: Vec<String>,
+    strict_failures: Vec<String>,
+    role_violations: Vec<String>,
+    missing_fields: Vec<String>,
+    ack_failures: Vec<String>,
+    acks_expected: usize,
+    max_ack_delay_ms: Option<u64>,
+    pending: Vec<PendingAck>,
+}
+
+impl Conformance {
+    /// Creates a conformance run with the given configuration.
+    pub fn new(config: ConformanceConfig) -> Self {
+        Conformance {
+            config,
+            device_frames: 0,
+            peer_frames: 0,
+            first_device_command: None,
+            parse_failures: Vec::new(),
+            strict_failures: Vec::new(),
+            role_violations: Vec::new(),
+            missing_fields: Vec::new(),
+            ack_failures: Vec::new(),
+            acks_expected: 0,
+            max_ack_delay_ms: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Records a frame exchanged with the device under test.
+    ///
+    /// Frames must be observed in timestamp order.
+    pub fn observe(&mut self, entry: &LogEntry) {
+        self.expire_pending(entry.timestamp_ms);
+
+        if entry.direction == self.config.device_direction {
+            self.observe_device(entry);
+        } else {
+            self.observe_peer(entry);
+        }
+    }
+
+    fn observe_device(&mut self, entry: &LogEntry) {
+        self.device_frames += 1;
+        let at = entry.timestamp_ms;
+
+        let (message, warnings) =
+            match Message::parse_with_warnings(&entry.raw, &ParseOptions::default()) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    self.parse_failures.push(format!("{}: {}", at, err));
+                    return;
+                }
+            };
+
+        if let Err(err) = Message::try_from_strict(&entry.raw) {
+            self.strict_failures.push(format!("{}: {}", at, err));
+        }
+
+        self.first_device_command
+            .get_or_insert_with(|| message.command.clone());
+
+        if !self.config.role.can_send(&message.command) {
+            self.role_violations.push(format!(
+                "{}: {} may not be sent by the {}",
+                at, message.command, self.config.role
+            ));
+        }
+
+        for warning in warnings
+            .iter()
+            .filter(|w| w.kind == WarningKind::FieldMissing)
+        {
+            self.missing_fields
+                .push(format!("{}: {} {}", at, message.command, warning));
+        }
+
+        if matches!(message.command, Command::Ack | Command::Nak) {
+            if self.pending.is_empty() {
+                self.ack_failures
+                    .push(format!("{}: unsolicited {}", at, message.command));
+            } else {
+                let pending = self.pending.remove(0);
+                let delay = at.saturating_sub(pending.timestamp_ms);
+                self.max_ack_delay_ms = Some(self.max_ack_delay_ms.map_or(delay, |d| d.max(delay)));
+            }
+        }
+    }
+
+    fn observe_peer(&mut self, entry: &LogEntry) {
+        self.peer_frames += 1;
+
+        if let Ok(message) = entry.message() {
+            if self.config.role.acknowledges(&message.command) {
+                self.acks_expected += 1;
+                self.pending.push(PendingAck {
+                    timestamp_ms: entry.timestamp_ms,
+                    command: message.command,
+                });
+            }
+        }
+    }
+
+    fn expire_pending(&mut self, now_ms: u64) {
+        let timeout = self.config.ack_timeout_ms;
+        let failures = &mut self.ack_failures;
+        self.pending.retain(|pending| {
+            let expired = now_ms.saturating_sub(pending.timestamp_ms) > timeout;
+            if expired {
+                failures.push(format!(
+                    "{}: no acknowledgment of {} within {} ms",
+                    pending.timestamp_ms, pending.command, timeout
+                ));
+            }
+            !expired
+        });
+    }
+
+    /// Produces the report for the frames observed so far.
+    ///
+    /// Frames still waiting for an acknowledgment are not counted as failures
+    /// until a later frame shows their timeout has elapsed.
+    pub fn report(&self) -> ConformanceReport {
+        let no_device_frames = self.device_frames == 0;
+
+        let handshake = match &self.first_device_command {
+            None => CheckResult::skipped("handshake"),
+            Some(Command::Hello) => CheckResult::from_failures("handshake", Vec::new()),
+            Some(command) => CheckResult::from_failures(
+                "handshake",
+                vec![format!("first frame of the device is {}, expected HELLO", command)],
+            ),
+        };
+
+        let ack_timing = if self.acks_expected == 0 && self.ack_failures.is_empty() {
+            CheckResult::skipped("ack_timing")
+        } else {
+            CheckResult::from_failures("ack_timing", self.ack_failures.clone())
+        };
+
+        let checks = if no_device_frames {
+            ["parse", "strict_values", "handshake", "roles", "required_fields"]
+                .into_iter()
+                .map(CheckResult::skipped)
+                .chain(std::iter::once(ack_timing))
+                .collect()
+        } else {
+            vec![
+                CheckResult::from_failures("parse", self.parse_failures.clone()),
+                CheckResult::from_failures("strict_values", self.strict_failures.clone()),
+                handshake,
+                CheckResult::from_failures("roles", self.role_violations.clone()),
+                CheckResult::from_failures("required_fields", self.missing_fields.clone()),
+                ack_timing,
+            ]
+        };
+
+        ConformanceReport {
+            device_frames: self.device_frames,
+            peer_frames: self.peer_frames,
+            max_ack_delay_ms: self.max_ack_delay_ms,
+            checks,
+        }
+    }
+}
+
+impl CheckResult {
+    fn from_failures(check: &'static str, details: Vec<String>) -> Self {
+        CheckResult {
+            check,
+            outcome: if details.is_empty() {
+                Outcome::Pass
+            } else {
+                Outcome::Fail
+            },
+            details,
+        }
+    }
+
+    fn skipped(check: &'static str) -> Self {
+        CheckResult {
+            check,
+            outcome: Outcome::Skipped,
+            details: Vec::new(),
+        }
+    }
+}
+
+/// Runs every conformance check over a recorded log.
+///
+/// Frames still waiting for an acknowledgment at the end of the log are
+/// reported as failures.
+///
+/// # Arguments
+///
+/// * `entries` - The frames exchanged with the device, in timestamp order
+/// * `config` - Configuration of the run
+pub fn run(entries: &[LogEntry], config: &ConformanceConfig) -> ConformanceReport {
+    let mut conformance = Conformance::new(config.clone());
+    for entry in entries {
+        conformance.observe(entry);
+    }
+    conformance.expire_pending(u64::MAX);
+    conformance.report()
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_ack_and_bad_handshake() {
+        let log = vec![
+            LogEntry::received(0, "|EFP1.1|INFO|17|fm-eq|%|"),
+            LogEntry::sent(100, "|EFP1.1|DISP|17|fm-eq|%|"),
+            LogEntry::received(2000, "|EFP1.1|DISP|17|fm-eq|%|"),
+        ];
+
+        let report = run(&log, &ConformanceConfig::default());
+        let outcome = |name| report.checks.iter().find(|c| c.check == name).unwrap().outcome;
+
+        assert!(!report.passed());
+        assert_eq!(outcome("handshake"), Outcome::Fail);
+        assert_eq!(outcome("roles"), Outcome::Fail);
+        assert_eq!(outcome("required_fields"), Outcome::Fail);
+        assert_eq!(outcome("ack_timing"), Outcome::Fail);
+        assert_eq!(outcome("parse"), Outcome::Pass);
+    }
+
+    #[test]
+    fn test_report_is_tap() {
+        let report = run(&[], &ConformanceConfig::default());
+        let tap = report.to_string();
+
+        assert!(tap.starts_with("1..6\n"));
+        assert!(tap.contains("ok 1 - parse # SKIP"));
+    }
+}
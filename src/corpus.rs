@@ -0,0 +1,197 @@
+/// How a corpus frame is expected to parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expectation {
+    /// The frame parses in both lenient and strict mode.
+    Valid,
+    /// The frame parses in lenient mode but is rejected in strict mode.
+    LenientOnly,
+    /// The frame is rejected in every mode.
+    Invalid,
+}
+
+/// A sample EFP frame with its expected parse outcome.
+#[derive(Debug, Clone, Copy)]
+pub struct CorpusEntry {
+    /// Short identifier of the sample.
+    pub name: &'static str,
+    /// The raw frame, as received on the wire.
+    pub frame: &'static str,
+    /// Expected parse outcome.
+    pub expectation: Expectation,
+}
+
+const CORPUS: &[CorpusEntry] = &[
+    CorpusEntry {
+        name: "hello",
+        frame: "|EFP1.1|HELLO|17|fm-eq|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "hello_efp1",
+        frame: "|EFP1|HELLO|3|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "info_individual",
+        frame: "|EFP1.1|INFO|17|efj-eq|1|A32|12|2|10:30|3:00|I|S||W|132|J.Smith|GBR|%|28|P.Martin|FRA|8|V|0|1|1|0|0|N|%|32|B. Panini|ITA|6|D|0|1|0|0|0|N|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "info_fencing_foil",
+        frame: "|EFP1.1|INFO|4|sm-ind|2|T64|5|1|14:02|2:41|I|F|N|F|87|A.Rossi|ITA|%|11|K.Meyer|GER|3|U|0|0|1|0|0|N|0|%|54|L.Chen|CHN|2|U|1|0|0|1|0|N|1|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "info_sabre_priority",
+        frame: "|EFP1.1|INFO|Finale|sm-ind|3|T2|1|1|18:45|0:00|I|S|R|H|5|M.Ivanov|UKR|%|3|S.Oh|KOR|14|U|0|0|0|0|0|N|0|%|9|A.Szilagyi|HUN|14|U|0|0|0|0|0|N|0|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "info_team",
+        frame: "|EFP1.1|INFO|2|ef-eq|4|T8|3|5|16:20|1:12|T|E||P|41|R.Garcia|ESP|%|FRA|France|FRA|23|U|0|0|0|0|0|R|0|%|ITA|Italie|ITA|25|U|1|0|0|0|0|N|1|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "info_incomplete",
+        frame: "|EFP1.1|INFO||||||||||||W||%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "info_powered_on",
+        frame: "|EFP1.1|INFO|17||||||||||||W||||%||||0|U|0|0|0|0|0|N|0|%||||0|U|0|0|0|0|0|N|0|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "disp",
+        frame: "|EFP1.1|DISP|17|fm-eq|1|P3|4||||I|E||W|21|D.Novak|CZE|%|5|J.Durand|FRA|%|8|T.Weber|SUI|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "next",
+        frame: "|EFP1.1|NEXT|17|fm-eq|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "prev",
+        frame: "|EFP1.1|PREV|17|fm-eq|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "ack",
+        frame: "|EFP1.1|ACK|17|fm-eq|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "nak",
+        frame: "|EFP1.1|NAK|17|fm-eq|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "vendor_lowercase_command",
+        frame: "|EFP1.1|info|17|fm-eq|%|",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "vendor_crlf",
+        frame: "|EFP1.1|HELLO|17|fm-eq|%|\r\n",
+        expectation: Expectation::Valid,
+    },
+    CorpusEntry {
+        name: "vendor_unknown_weapon",
+        frame: "|EFP1.1|INFO|17|fm-eq|1|A32|12|2|10:30|3:00|I|X||W|%|",
+        expectation: Expectation::LenientOnly,
+    },
+    CorpusEntry {
+        name: "vendor_score_overflow",
+        frame: "|EFP1.1|INFO|17|fm-eq|%|28|P.Martin|FRA|300|U|%|%|",
+        expectation: Expectation::LenientOnly,
+    },
+    CorpusEntry {
+        name: "vendor_light_code",
+        frame: "|EFP1.1|INFO|17|fm-eq|%|28|P.Martin|FRA|3|U|0|0|on|0|%|%|",
+        expectation: Expectation::LenientOnly,
+    },
+    CorpusEntry {
+        name: "empty",
+        frame: "",
+        expectation: Expectation::Invalid,
+    },
+    CorpusEntry {
+        name: "unknown_protocol",
+        frame: "|EFP2.0|HELLO|17|fm-eq|%|",
+        expectation: Expectation::Invalid,
+    },
+    CorpusEntry {
+        name: "unknown_command",
+        frame: "|EFP1.1|PING|17|fm-eq|%|",
+        expectation: Expectation::Invalid,
+    },
+    CorpusEntry {
+        name: "missing_command",
+        frame: "|EFP1.1|",
+        expectation: Expectation::Invalid,
+    },
+    CorpusEntry {
+        name: "garbage",
+        frame: "%%%|||%|%",
+        expectation: Expectation::Invalid,
+    },
+];
+
+/// Returns a corpus of sample EFP frames.
+///
+/// The corpus covers every command, individual and team bouts, and malformed
+/// variants seen from real apparatus. It seeds the crate's fuzz targets and can
+/// be reused in downstream test suites.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::corpus::{corpus, Expectation};
+/// use cyrano::message::Message;
+///
+/// for entry in corpus() {
+///     let parsed = Message::try_from(entry.frame);
+///     assert_eq!(parsed.is_ok(), entry.expectation != Expectation::Invalid, "{}", entry.name);
+/// }
+/// ```
+pub fn corpus() -> &'static [CorpusEntry] {
+    CORPUS
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn test_corpus_expectations() {
+        for entry in corpus() {
+            let lenient = Message::try_from(entry.frame);
+            let strict = Message::try_from_strict(entry.frame);
+
+            match entry.expectation {
+                Expectation::Valid => {
+                    assert!(lenient.is_ok() && strict.is_ok(), "{}", entry.name)
+                }
+                Expectation::LenientOnly => {
+                    assert!(lenient.is_ok() && strict.is_err(), "{}", entry.name)
+                }
+                Expectation::Invalid => {
+                    assert!(lenient.is_err() && strict.is_err(), "{}", entry.name)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_corpus_roundtrip() {
+        for entry in corpus().iter().filter(|e| e.expectation != Expectation::Invalid) {
+            let msg = Message::try_from(entry.frame).unwrap();
+            let reparsed = Message::try_from(msg.to_string()).unwrap();
+            assert_eq!(msg, reparsed, "{}", entry.name);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+use crate::borrowed::MessageRef;
+use crate::logfile::LogEntry;
+use crate::piste::PisteId;
+
+/// Default tolerance when aligning two captures, in milliseconds.
+pub const DEFAULT_WINDOW_MS: u64 = 2000;
+
+/// A field that differs between two aligned frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// Dotted name of the field, such as `left_fencer.score`.
+    pub field: String,
+    /// Text of the field in the left capture, `None` if absent.
+    pub left: Option<String>,
+    /// Text of the field in the right capture, `None` if absent.
+    pub right: Option<String>,
+}
+
+/// A difference between two captures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffItem {
+    /// A frame only found in the left capture.
+    OnlyLeft(LogEntry),
+    /// A frame only found in the right capture.
+    OnlyRight(LogEntry),
+    /// Two frames of the same piste and command, close in time, whose fields differ.
+    Changed {
+        left: LogEntry,
+        right: LogEntry,
+        fields: Vec<FieldDiff>,
+    },
+}
+
+impl DiffItem {
+    /// Returns the time of the item, taken from the left frame when there is one.
+    pub fn timestamp_ms(&self) -> u64 {
+        match self {
+            DiffItem::OnlyLeft(entry) | DiffItem::OnlyRight(entry) => entry.timestamp_ms,
+            DiffItem::Changed { left, .. } => left.timestamp_ms,
+        }
+    }
+}
+
+impl Display for DiffItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffItem::OnlyLeft(entry) => write!(f, "- {} {}", entry.timestamp_ms, entry.raw),
+            DiffItem::OnlyRight(entry) => write!(f, "+ {} {}", entry.timestamp_ms, entry.raw),
+            DiffItem::Changed { left, right, fields } => {
+                write!(f, "~ {} / {}", left.timestamp_ms, right.timestamp_ms)?;
+                for field in fields {
+                    write!(
+                        f,
+                        "\n    {}: {} -> {}",
+                        field.field,
+                        field.left.as_deref().unwrap_or("(absent)"),
+                        field.right.as_deref().unwrap_or("(absent)")
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Aligns two captures of the same link and returns their differences,
+/// ordered by time.
+///
+/// Captures taken at both ends of a link, such as an apparatus-side and a
+/// software-side log, are aligned piste by piste. Identical frames less than
+/// `window_ms` apart are matched first. Remaining frames of the same command
+/// within the window are then paired and reported field by field. Whatever
+/// is left is reported as present in one capture only.
+///
+/// The clocks of both captures are assumed to agree within the window; shift
+/// one of them first if they do not, for instance with [`skew`](crate::skew).
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::diff::{diff, DiffItem};
+/// use cyrano::logfile::LogEntry;
+///
+/// let machine = vec![
+///     LogEntry::sent(1_000, "|EFP1.1|INFO|17|fm-eq|%|28|P.Martin|FRA|15|V|%|"),
+///     LogEntry::sent(2_000, "|EFP1.1|INFO|17|fm-eq|%|28|P.Martin|FRA|15|V|%|"),
+/// ];
+/// let software = vec![LogEntry::received(1_050, "|EFP1.1|INFO|17|fm-eq|%|28|P.Martin|FRA|14|V|%|")];
+///
+/// let items = diff(&machine, &software, 500);
+/// assert_eq!(items.len(), 2);
+/// let DiffItem::Changed { fields, .. } = &items[0] else { panic!() };
+/// assert_eq!(fields[0].field, "right_fencer.score");
+/// assert!(matches!(items[1], DiffItem::OnlyLeft(_)));
+/// ```
+pub fn diff(left: &[LogEntry], right: &[LogEntry], window_ms: u64) -> Vec<DiffItem> {
+    let mut left_pistes = by_piste(left);
+    let mut right_pistes = by_piste(right);
+    let mut pistes: Vec<Option<PisteId>> = left_pistes.keys().chain(right_pistes.keys()).cloned().collect();
+    pistes.sort();
+    pistes.dedup();
+
+    let mut items = Vec::new();
+    for piste in pistes {
+        let lefts = left_pistes.remove(&piste).unwrap_or_default();
+        let rights = right_pistes.remove(&piste).unwrap_or_default();
+        diff_piste(&lefts, &rights, window_ms, &mut items);
+    }
+
+    items.sort_by_key(DiffItem::timestamp_ms);
+    items
+}
+
+fn by_piste(entries: &[LogEntry]) -> BTreeMap<Option<PisteId>, Vec<&LogEntry>> {
+    let mut pistes: BTreeMap<Option<PisteId>, Vec<&LogEntry>> = BTreeMap::new();
+    for entry in entries {
+        let piste = entry.raw.split('|').nth(3).filter(|piste| !piste.is_empty()).map(PisteId::from);
+        pistes.entry(piste).or_default().push(entry);
+    }
+    for entries in pistes.values_mut() {
+        entries.sort_by_key(|entry| entry.timestamp_ms);
+    }
+    pistes
+}
+
+fn diff_piste(lefts: &[&LogEntry], rights: &[&LogEntry], window_ms: u64, items: &mut Vec<DiffItem>) {
+    let mut right_matched = vec![false; rights.len()];
+    let mut left_matched = vec![false; lefts.len()];
+    let close = |a: &LogEntry, b: &LogEntry| a.timestamp_ms.abs_diff(b.timestamp_ms) <= window_ms;
+
+    // Identical frames first, so that a changed frame is not paired with the
+    // copy of a neighbour.
+    for (l, left) in lefts.iter().enumerate() {
+        let found = rights
+            .iter()
+            .enumerate()
+            .find(|(r, right)| !right_matched[*r] && close(left, right) && left.raw.trim_end() == right.raw.trim_end());
+        if let Some((r, _)) = found {
+            right_matched[r] = true;
+            left_matched[l] = true;
+        }
+    }
+
+    for (l, left) in lefts.iter().enumerate() {
+        if left_matched[l] {
+            continue;
+        }
+        let paired = rights
+            .iter()
+            .enumerate()
+            .filter(|(r, right)| !right_matched[*r] && close(left, right) && command(left) == command(right))
+            .min_by_key(|(_, right)| left.timestamp_ms.abs_diff(right.timestamp_ms));
+        if let Some((r, right)) = paired {
+            right_matched[r] = true;
+            left_matched[l] = true;
+            let fields = field_diffs(&left.raw, &right.raw);
+            if !fields.is_empty() {
+                items.push(DiffItem::Changed {
+                    left: (*left).clone(),
+                    right: (*right).clone(),
+                    fields,
+                });
+            }
+        }
+    }
+
+    items.extend(lefts.iter().zip(&left_matched).filter(|(_, &m)| !m).map(|(e, _)| DiffItem::OnlyLeft((*e).clone())));
+    items.extend(rights.iter().zip(&right_matched).filter(|(_, &m)| !m).map(|(e, _)| DiffItem::OnlyRight((*e).clone())));
+}
+
+fn command(entry: &LogEntry) -> Option<String> {
+    entry.raw.split('|').nth(2).map(str::to_ascii_uppercase)
+}
+
+/// Compares two frames field by field, or as a whole if one does not parse.
+fn field_diffs(left: &str, right: &str) -> Vec<FieldDiff> {
+    let (Ok(left_view), Ok(right_view)) = (MessageRef::try_from(left), MessageRef::try_from(right)) else {
+        return vec![FieldDiff {
+            field: "frame".to_string(),
+            left: Some(left.to_string()),
+            right: Some(right.to_string()),
+        }];
+    };
+
+    left_view
+        .fields()
+        .zip(right_view.fields())
+        .filter(|(l, r)| l.raw.filter(|raw| !raw.is_empty()) != r.raw.filter(|raw| !raw.is_empty()))
+        .map(|(l, r)| FieldDiff {
+            field: l.path(),
+            left: l.raw.map(String::from),
+            right: r.raw.map(String::from),
+        })
+        .collect()
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_diff_of_a_lossy_copy() {
+        let sent = fixtures::day_log(0, 2, 1);
+        let mut received: Vec<LogEntry> = sent
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| index % 10 != 3)
+            .map(|(_, entry)| LogEntry::received(entry.timestamp_ms + 40, entry.raw.clone()))
+            .collect();
+        let altered = received.len() / 2;
+        received[altered].raw = received[altered].raw.replacen("|fixture|", "|other|", 1);
+
+        assert!(diff(&sent, &sent, 0).is_empty());
+
+        let items = diff(&sent, &received, 500);
+        let lost = items.iter().filter(|item| matches!(item, DiffItem::OnlyLeft(_))).count();
+        assert_eq!(lost, (0..sent.len()).filter(|index| index % 10 == 3).count());
+        let changed: Vec<&DiffItem> = items.iter().filter(|item| matches!(item, DiffItem::Changed { .. })).collect();
+        assert_eq!(changed.len(), 1);
+        assert!(changed[0].to_string().contains("competition_id: fixture -> other"));
+        assert!(items.windows(2).all(|pair| pair[0].timestamp_ms() <= pair[1].timestamp_ms()));
+    }
+}
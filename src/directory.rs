@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::future::Future;
+
+use crate::enums::Side;
+use crate::state::MatchState;
+
+/// What a fencer directory knows about a fencer beyond the frames.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FencerProfile {
+    /// Full name, where frames carry a truncated one.
+    pub full_name: Option<String>,
+    /// Club of the fencer.
+    pub club: Option<String>,
+    /// Ranking of the fencer.
+    pub ranking: Option<u32>,
+    /// URL of a photo of the fencer.
+    pub photo_url: Option<String>,
+}
+
+/// Source of fencer profiles, such as a registration database or a
+/// federation file, queried by the fencer `id` of the frames.
+///
+/// Closures taking an id implement the trait, and so does a `HashMap` of
+/// profiles loaded beforehand.
+pub trait FencerDirectory {
+    /// Returns the profile of a fencer, or `None` if the directory does not know it.
+    fn lookup(&self, id: &str) -> Option<FencerProfile>;
+}
+
+impl<F: Fn(&str) -> Option<FencerProfile>> FencerDirectory for F {
+    fn lookup(&self, id: &str) -> Option<FencerProfile> {
+        self(id)
+    }
+}
+
+impl FencerDirectory for HashMap<String, FencerProfile> {
+    fn lookup(&self, id: &str) -> Option<FencerProfile> {
+        self.get(id).cloned()
+    }
+}
+
+/// Fencer directory answering asynchronously, such as a web service.
+pub trait AsyncFencerDirectory {
+    /// Returns the profile of a fencer, or `None` if the directory does not know it.
+    fn lookup(&self, id: &str) -> impl Future<Output = Option<FencerProfile>> + Send;
+}
+
+/// Profiles already looked up, so that each fencer is queried once.
+///
+/// Fencers the directory does not know are remembered too. The cache is
+/// handed to [`ScoreboardView::update_with`](crate::output::scoreboard::ScoreboardView::update_with)
+/// to show the profiles.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::directory::{FencerProfile, ProfileCache};
+/// use cyrano::message::Message;
+/// use cyrano::output::scoreboard::ScoreboardView;
+/// use cyrano::state::MatchState;
+///
+/// let directory = |id: &str| {
+///     (id == "28").then(|| FencerProfile {
+///         full_name: Some("Pierre Martin".to_string()),
+///         club: Some("CE Melun".to_string()),
+///         ..FencerProfile::default()
+///     })
+/// };
+///
+/// let mut state = MatchState::new("3");
+/// state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+///
+/// let mut profiles = ProfileCache::new();
+/// profiles.observe(&state, &directory);
+///
+/// let mut view = ScoreboardView::default();
+/// view.update_with(&state, &profiles);
+/// assert_eq!(view.right.name, "Pierre Martin");
+/// assert_eq!(view.right.profile.as_ref().unwrap().club.as_deref(), Some("CE Melun"));
+/// assert_eq!(view.left.name, "B. Panini");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProfileCache {
+    profiles: HashMap<String, Option<FencerProfile>>,
+}
+
+impl ProfileCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        ProfileCache::default()
+    }
+
+    /// Returns the cached profile of a fencer.
+    pub fn get(&self, id: &str) -> Option<&FencerProfile> {
+        self.profiles.get(id).and_then(Option::as_ref)
+    }
+
+    /// Returns `true` if the fencer was looked up, whether it was found or not.
+    pub fn contains(&self, id: &str) -> bool {
+        self.profiles.contains_key(id)
+    }
+
+    /// Records the result of a lookup made elsewhere.
+    pub fn insert(&mut self, id: impl Into<String>, profile: Option<FencerProfile>) {
+        self.profiles.insert(id.into(), profile);
+    }
+
+    /// Forgets every profile, so that fencers are looked up again.
+    pub fn clear(&mut self) {
+        self.profiles.clear();
+    }
+
+    /// Returns the profile of a fencer, querying the directory the first time only.
+    pub fn lookup(&mut self, id: &str, directory: &impl FencerDirectory) -> Option<&FencerProfile> {
+        if !self.profiles.contains_key(id) {
+            self.profiles.insert(id.to_string(), directory.lookup(id));
+        }
+        self.get(id)
+    }
+
+    /// Returns the profile of a fencer, querying the asynchronous directory
+    /// the first time only.
+    pub async fn lookup_async(&mut self, id: &str, directory: &impl AsyncFencerDirectory) -> Option<&FencerProfile> {
+        if !self.profiles.contains_key(id) {
+            let profile = directory.lookup(id).await;
+            self.profiles.insert(id.to_string(), profile);
+        }
+        self.get(id)
+    }
+
+    /// Looks up both fencers of a bout.
+    pub fn observe(&mut self, state: &MatchState, directory: &impl FencerDirectory) {
+        for id in fencer_ids(state) {
+            self.lookup(&id, directory);
+        }
+    }
+
+    /// Looks up both fencers of a bout in an asynchronous directory.
+    pub async fn observe_async(&mut self, state: &MatchState, directory: &impl AsyncFencerDirectory) {
+        for id in fencer_ids(state) {
+            self.lookup_async(&id, directory).await;
+        }
+    }
+}
+
+fn fencer_ids(state: &MatchState) -> Vec<String> {
+    [Side::Right, Side::Left]
+        .into_iter()
+        .filter_map(|side| state.fencer(side)?.id.as_ref().map(|id| id.to_string()))
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use std::cell::Cell;
+    use std::convert::TryFrom;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    struct Federation;
+
+    impl AsyncFencerDirectory for Federation {
+        async fn lookup(&self, id: &str) -> Option<FencerProfile> {
+            Some(FencerProfile {
+                ranking: id.parse().ok(),
+                ..FencerProfile::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_profiles_are_looked_up_once() {
+        let mut state = MatchState::new("3");
+        state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+
+        let queries = Cell::new(0);
+        let directory = |_: &str| {
+            queries.set(queries.get() + 1);
+            None
+        };
+        let mut profiles = ProfileCache::new();
+        profiles.observe(&state, &directory);
+        profiles.observe(&state, &directory);
+        assert_eq!(queries.get(), 2);
+        assert!(profiles.contains("28") && profiles.get("28").is_none());
+
+        profiles.clear();
+        {
+            let mut future = pin!(profiles.observe_async(&state, &Federation));
+            let mut context = Context::from_waker(Waker::noop());
+            assert_eq!(future.as_mut().poll(&mut context), Poll::Ready(()));
+        }
+        assert_eq!(profiles.get("32").unwrap().ranking, Some(32));
+    }
+}
@@ -8,7 +8,8 @@ use super::error::ParseError;
 /// Protocol command types supported by the EFP protocol.
 ///
 /// These commands define the type of message being sent or received.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Command {
     /// Initial handshake command.
     Hello,
@@ -27,7 +28,8 @@ pub enum Command {
 }
 
 /// Type of fencing competition.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CompetitionType {
     /// Individual competition (one fencer per side).
     Individual,
@@ -36,7 +38,8 @@ pub enum CompetitionType {
 }
 
 /// Type of fencing weapon.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Weapon {
     /// Foil weapon.
     Foil,
@@ -49,7 +52,8 @@ pub enum Weapon {
 /// Priority indicator in fencing, typically used in sabre.
 ///
 /// Indicates which fencer has priority (right of way) in the current action.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Priority {
     /// No priority assigned.
     None,
@@ -60,7 +64,8 @@ pub enum Priority {
 }
 
 /// Current state of the fencing apparatus/scoring machine.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ApparatusState {
     /// Fencing is in progress.
     Fencing,
@@ -72,10 +77,16 @@ pub enum ApparatusState {
     Waiting,
     /// Match is ending.
     Ending,
+    /// State code not known to this crate, kept verbatim.
+    ///
+    /// Newer apparatus firmware may report states added after this crate was
+    /// written; any single uppercase letter is accepted and written back as is.
+    Unknown(String),
 }
 
 /// Status of a fencer in the match.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FencerStatus {
     /// Status is undefined (match in progress).
     Undefined,
@@ -87,10 +98,15 @@ pub enum FencerStatus {
     Abandonment,
     /// Fencer has been excluded from the match.
     Exclusion,
+    /// Status code not known to this crate, kept verbatim.
+    ///
+    /// Any single uppercase letter is accepted and written back as is.
+    Unknown(String),
 }
 
 /// Reserve fencer status indicator.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Reserve {
     /// No reserve status.
     None,
@@ -100,8 +116,10 @@ pub enum Reserve {
 
 /// Penalty card status for a fencer.
 ///
-/// Represents the cumulative penalty cards a fencer has received.
-#[derive(Debug, Clone, PartialEq)]
+/// Represents the cumulative penalty cards a fencer has received. Values are
+/// ordered by severity, so a fencer's P-card may only grow during a bout.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PCard {
     /// No penalty cards.
     None,
@@ -117,227 +135,397 @@ pub enum PCard {
     TwoBlack,
 }
 
-// ===== IMPL PARSING ENUMS =====
-
-impl TryFrom<&str> for Command {
-    type Error = ParseError;
+/// Side of the piste, as seen from the apparatus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Side {
+    /// Fencer on the right.
+    Right,
+    /// Fencer on the left.
+    Left,
+}
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value.to_uppercase().as_str() {
-            "HELLO" => Ok(Command::Hello),
-            "DISP" => Ok(Command::Disp),
-            "ACK" => Ok(Command::Ack),
-            "NAK" => Ok(Command::Nak),
-            "INFO" => Ok(Command::Info),
-            "NEXT" => Ok(Command::Next),
-            "PREV" => Ok(Command::Prev),
-            _ => Err(ParseError::InvalidCommand(value.to_string())),
-        }
-    }
+/// Zone of an EFP message: the general fields or one of the two fencer zones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Zone {
+    /// General fields (command, piste, competition, bout data, referee).
+    General,
+    /// Fields of the fencer on the right.
+    RightFencer,
+    /// Fields of the fencer on the left.
+    LeftFencer,
 }
 
-impl Display for Command {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Command::Hello => write!(f, "HELLO"),
-            Command::Disp => write!(f, "DISP"),
-            Command::Ack => write!(f, "ACK"),
-            Command::Nak => write!(f, "NAK"),
-            Command::Info => write!(f, "INFO"),
-            Command::Next => write!(f, "NEXT"),
-            Command::Prev => write!(f, "PREV"),
-        }
+// ===== IMPL HELPERS =====
+
+impl FencerStatus {
+    /// Returns `true` if the status marks the end of the bout for the fencer.
+    ///
+    /// Unknown statuses are not considered final.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            FencerStatus::Victory | FencerStatus::Defeat | FencerStatus::Abandonment | FencerStatus::Exclusion
+        )
     }
 }
 
-impl TryFrom<&str> for CompetitionType {
-    type Error = ParseError;
-
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "I" => Ok(CompetitionType::Individual),
-            "T" => Ok(CompetitionType::Team),
-            _ => Err(ParseError::InvalidValue {
-                field: "competition_type",
-                value: value.to_string(),
-            }),
+impl PCard {
+    /// Returns the sanction given on the next non-combativity offence, or
+    /// `None` once the fencer has received the second black card.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::enums::PCard;
+    ///
+    /// assert_eq!(PCard::Yellow.escalate(), Some(PCard::OneRed));
+    /// assert_eq!(PCard::TwoBlack.escalate(), None);
+    /// assert!(PCard::OneRed < PCard::TwoRed);
+    /// ```
+    pub fn escalate(&self) -> Option<PCard> {
+        match self {
+            PCard::None => Some(PCard::Yellow),
+            PCard::Yellow => Some(PCard::OneRed),
+            PCard::OneRed => Some(PCard::TwoRed),
+            PCard::TwoRed => Some(PCard::OneBlack),
+            PCard::OneBlack => Some(PCard::TwoBlack),
+            PCard::TwoBlack => None,
         }
     }
-}
 
-impl Display for CompetitionType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Returns the number of touches awarded to the opponent when this card
+    /// is given: one for each red card, none otherwise.
+    pub fn penalty_touches(&self) -> u8 {
         match self {
-            CompetitionType::Individual => write!(f, "I"),
-            CompetitionType::Team => write!(f, "T"),
+            PCard::OneRed | PCard::TwoRed => 1,
+            PCard::None | PCard::Yellow | PCard::OneBlack | PCard::TwoBlack => 0,
         }
     }
-}
 
-impl TryFrom<&str> for Weapon {
-    type Error = ParseError;
+    /// Returns the total number of touches awarded to the opponent by the
+    /// cards leading up to and including this one.
+    pub fn total_penalty_touches(&self) -> u8 {
+        PCard::ALL
+            .iter()
+            .take_while(|card| *card <= self)
+            .map(PCard::penalty_touches)
+            .sum()
+    }
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "F" => Ok(Weapon::Foil),
-            "E" => Ok(Weapon::Epee),
-            "S" => Ok(Weapon::Sabre),
-            _ => Err(ParseError::InvalidValue {
-                field: "weapon",
-                value: value.to_string(),
-            }),
-        }
+    /// Returns `true` if the card excludes the fencer from the bout.
+    pub fn is_exclusion(&self) -> bool {
+        matches!(self, PCard::OneBlack | PCard::TwoBlack)
     }
 }
 
-impl Display for Weapon {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Side {
+    /// Returns the opposite side.
+    pub fn opponent(&self) -> Side {
         match self {
-            Weapon::Foil => write!(f, "F"),
-            Weapon::Epee => write!(f, "E"),
-            Weapon::Sabre => write!(f, "S"),
+            Side::Right => Side::Left,
+            Side::Left => Side::Right,
         }
     }
 }
 
-impl TryFrom<&str> for Priority {
-    type Error = ParseError;
-
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "N" => Ok(Priority::None),
-            "R" => Ok(Priority::Right),
-            "L" => Ok(Priority::Left),
-            _ => Err(ParseError::InvalidValue {
-                field: "priority",
-                value: value.to_string(),
-            }),
+impl Display for Zone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Zone::General => write!(f, "general"),
+            Zone::RightFencer => write!(f, "right_fencer"),
+            Zone::LeftFencer => write!(f, "left_fencer"),
         }
     }
 }
 
-impl Display for Priority {
+impl Display for Side {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Priority::None => write!(f, "N"),
-            Priority::Right => write!(f, "R"),
-            Priority::Left => write!(f, "L"),
+            Side::Right => write!(f, "right"),
+            Side::Left => write!(f, "left"),
         }
     }
 }
 
-impl TryFrom<&str> for ApparatusState {
-    type Error = ParseError;
+// ===== CODE TABLES =====
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "F" => Ok(ApparatusState::Fencing),
-            "H" => Ok(ApparatusState::Halt),
-            "P" => Ok(ApparatusState::Pause),
-            "W" => Ok(ApparatusState::Waiting),
-            "E" => Ok(ApparatusState::Ending),
-            _ => Err(ParseError::InvalidValue {
-                field: "state",
-                value: value.to_string(),
-            }),
+/// Compares two strings in a `const` context.
+const fn same_code(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
         }
+        i += 1;
     }
+    true
 }
 
-impl Display for ApparatusState {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ApparatusState::Fencing => write!(f, "F"),
-            ApparatusState::Halt => write!(f, "H"),
-            ApparatusState::Pause => write!(f, "P"),
-            ApparatusState::Waiting => write!(f, "W"),
-            ApparatusState::Ending => write!(f, "E"),
-        }
-    }
+/// Returns `true` for a value shaped like a protocol code: a single uppercase letter.
+fn is_unknown_code(value: &str) -> bool {
+    value.len() == 1 && value.bytes().all(|b| b.is_ascii_uppercase())
 }
 
-impl TryFrom<&str> for FencerStatus {
-    type Error = ParseError;
+/// Declares the protocol codes of an enum in a single table.
+///
+/// Each row gives a variant, its code and its description. `ALL`, `CODES`,
+/// `code`, `from_code`, `description` and `Display` are generated from the
+/// rows, so the two directions of the conversion cannot disagree. With a
+/// field name, `TryFrom<&str>` is generated too; with a trailing
+/// `_ => Unknown, "..."` row, unrecognized single-letter codes are kept verbatim.
+macro_rules! code_table {
+    ($name:ident { $($variant:ident => $code:literal, $description:literal;)+ }) => {
+        code_table!(@table $name { $($variant => $code;)+ });
+
+        impl $name {
+            /// Returns the code used for this value in the protocol.
+            pub const fn code(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $code,)+
+                }
+            }
+
+            /// Returns a short English description, for legends and pickers.
+            pub const fn description(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $description,)+
+                }
+            }
+        }
+    };
+    ($name:ident, $field:literal { $($variant:ident => $code:literal, $description:literal;)+ }) => {
+        code_table!($name { $($variant => $code, $description;)+ });
+
+        impl TryFrom<&str> for $name {
+            type Error = ParseError;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                $name::from_code(value).ok_or_else(|| ParseError::InvalidValue {
+                    field: $field,
+                    value: value.to_string(),
+                })
+            }
+        }
+    };
+    (
+        $name:ident, $field:literal {
+            $($variant:ident => $code:literal, $description:literal;)+
+            _ => $unknown:ident, $unknown_description:literal;
+        }
+    ) => {
+        code_table!(@table $name { $($variant => $code;)+ });
+
+        impl $name {
+            /// Returns the code used for this value in the protocol.
+            pub const fn code(&self) -> &str {
+                match self {
+                    $($name::$variant => $code,)+
+                    $name::$unknown(code) => code.as_str(),
+                }
+            }
+
+            /// Returns a short English description, for legends and pickers.
+            pub const fn description(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $description,)+
+                    $name::$unknown(_) => $unknown_description,
+                }
+            }
+        }
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "U" => Ok(FencerStatus::Undefined),
-            "V" => Ok(FencerStatus::Victory),
-            "D" => Ok(FencerStatus::Defeat),
-            "A" => Ok(FencerStatus::Abandonment),
-            "E" => Ok(FencerStatus::Exclusion),
-            _ => Err(ParseError::InvalidValue {
-                field: "fencer_status",
-                value: value.to_string(),
-            }),
+        impl TryFrom<&str> for $name {
+            type Error = ParseError;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                match $name::from_code(value) {
+                    Some(known) => Ok(known),
+                    None if is_unknown_code(value) => Ok($name::$unknown(value.to_string())),
+                    None => Err(ParseError::InvalidValue {
+                        field: $field,
+                        value: value.to_string(),
+                    }),
+                }
+            }
+        }
+    };
+    (@table $name:ident { $($variant:ident => $code:literal;)+ }) => {
+        impl $name {
+            /// Every variant, in protocol order.
+            pub const ALL: [$name; [$($code),+].len()] = [$($name::$variant),+];
+
+            /// Protocol code of every variant, in protocol order.
+            pub const CODES: [(&'static str, $name); [$($code),+].len()] = [$(($code, $name::$variant)),+];
+
+            /// Returns the variant with the given protocol code, or `None` if
+            /// the code is not in the table. The comparison is exact.
+            pub const fn from_code(code: &str) -> Option<Self> {
+                $(
+                    if same_code(code, $code) {
+                        return Some($name::$variant);
+                    }
+                )+
+                None
+            }
         }
-    }
-}
 
-impl Display for FencerStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            FencerStatus::Undefined => write!(f, "U"),
-            FencerStatus::Victory => write!(f, "V"),
-            FencerStatus::Defeat => write!(f, "D"),
-            FencerStatus::Abandonment => write!(f, "A"),
-            FencerStatus::Exclusion => write!(f, "E"),
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.code())
+            }
         }
-    }
+    };
 }
 
-impl TryFrom<&str> for Reserve {
+code_table!(Command {
+    Hello => "HELLO", "Initial handshake";
+    Disp => "DISP", "Display bout data";
+    Ack => "ACK", "Acknowledgment";
+    Nak => "NAK", "Negative acknowledgment";
+    Info => "INFO", "Bout information from the apparatus";
+    Next => "NEXT", "Move to the next bout";
+    Prev => "PREV", "Move to the previous bout";
+});
+
+code_table!(CompetitionType, "competition_type" {
+    Individual => "I", "Individual competition";
+    Team => "T", "Team competition";
+});
+
+code_table!(Weapon, "weapon" {
+    Foil => "F", "Foil";
+    Epee => "E", "Épée";
+    Sabre => "S", "Sabre";
+});
+
+code_table!(Priority, "priority" {
+    None => "N", "No priority";
+    Right => "R", "Priority to the right fencer";
+    Left => "L", "Priority to the left fencer";
+});
+
+code_table!(ApparatusState, "state" {
+    Fencing => "F", "Fencing";
+    Halt => "H", "Halt";
+    Pause => "P", "Pause";
+    Waiting => "W", "Waiting for the bout to begin";
+    Ending => "E", "End of the bout";
+    _ => Unknown, "Unknown state";
+});
+
+code_table!(FencerStatus, "fencer_status" {
+    Undefined => "U", "Bout in progress";
+    Victory => "V", "Victory";
+    Defeat => "D", "Defeat";
+    Abandonment => "A", "Abandonment";
+    Exclusion => "E", "Exclusion";
+    _ => Unknown, "Unknown status";
+});
+
+code_table!(Reserve, "reserve" {
+    None => "N", "No reserve introduced";
+    Introduce => "R", "Reserve fencer introduced";
+});
+
+code_table!(PCard, "p_card" {
+    None => "0", "No P-card";
+    Yellow => "1", "Yellow P-card";
+    OneRed => "2", "First red P-card";
+    TwoRed => "3", "Second red P-card";
+    OneBlack => "4", "First black P-card";
+    TwoBlack => "5", "Second black P-card";
+});
+
+// ===== IMPL PARSING ENUMS =====
+
+impl TryFrom<&str> for Command {
     type Error = ParseError;
 
+    /// Commands are matched regardless of case.
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "N" => Ok(Reserve::None),
-            "R" => Ok(Reserve::Introduce),
-            _ => Err(ParseError::InvalidValue {
-                field: "reserve",
-                value: value.to_string(),
-            }),
-        }
+        Command::from_code(&value.to_uppercase()).ok_or_else(|| ParseError::InvalidCommand(value.to_string()))
     }
 }
 
-impl Display for Reserve {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Reserve::None => write!(f, "N"),
-            Reserve::Introduce => write!(f, "R"),
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_roundtrip() {
+        for command in Command::ALL {
+            assert_eq!(Command::try_from(command.code()).unwrap(), command);
+        }
+        for weapon in Weapon::ALL {
+            assert_eq!(Weapon::try_from(weapon.code()).unwrap(), weapon);
+        }
+        for state in ApparatusState::ALL {
+            assert_eq!(ApparatusState::try_from(state.code()).unwrap(), state);
+        }
+        for status in FencerStatus::ALL {
+            assert_eq!(FencerStatus::try_from(status.code()).unwrap(), status);
         }
+        for card in PCard::ALL {
+            assert_eq!(PCard::try_from(card.code()).unwrap(), card);
+            assert_eq!(card.to_string(), card.code());
+        }
+        assert_eq!(Weapon::Epee.description(), "Épée");
     }
-}
 
-impl TryFrom<&str> for PCard {
-    type Error = ParseError;
+    #[test]
+    fn test_code_tables() {
+        const EPEE: Option<Weapon> = Weapon::from_code("E");
+        const HALT: &str = ApparatusState::Halt.code();
+        assert_eq!(EPEE, Some(Weapon::Epee));
+        assert_eq!(HALT, "H");
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "0" => Ok(PCard::None),
-            "1" => Ok(PCard::Yellow),
-            "2" => Ok(PCard::OneRed),
-            "3" => Ok(PCard::TwoRed),
-            "4" => Ok(PCard::OneBlack),
-            "5" => Ok(PCard::TwoBlack),
-            _ => Err(ParseError::InvalidValue {
-                field: "p_card",
-                value: value.to_string(),
-            }),
+        for (code, status) in FencerStatus::CODES {
+            assert_eq!(status.code(), code);
+            assert_eq!(FencerStatus::from_code(code), Some(status));
         }
+        for (code, reserve) in Reserve::CODES {
+            assert_eq!(Reserve::try_from(code).unwrap(), reserve);
+        }
+        assert_eq!(Priority::CODES.map(|(_, priority)| priority), Priority::ALL);
+        assert_eq!(Command::from_code("info"), None);
+        assert_eq!(Command::try_from("info").unwrap(), Command::Info);
     }
-}
 
-impl Display for PCard {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            PCard::None => write!(f, "0"),
-            PCard::Yellow => write!(f, "1"),
-            PCard::OneRed => write!(f, "2"),
-            PCard::TwoRed => write!(f, "3"),
-            PCard::OneBlack => write!(f, "4"),
-            PCard::TwoBlack => write!(f, "5"),
+    #[test]
+    fn test_unknown_codes_are_preserved() {
+        let raw = "|EFP1.1|INFO|17|fm-eq|1|P3|1|||||E||X|%|28|P.Martin|FRA|4|Z|%|%|";
+        let message = crate::message::Message::try_from_strict(raw).unwrap();
+
+        assert_eq!(message.state, Some(ApparatusState::Unknown("X".to_string())));
+        assert_eq!(message.right_fencer.status, Some(FencerStatus::Unknown("Z".to_string())));
+        assert!(!FencerStatus::Unknown("Z".to_string()).is_final());
+        assert!(message.to_string().starts_with("|EFP1.1|INFO|17|fm-eq|1|P3|1|||||E||X|"));
+        assert!(message.to_string().contains("|4|Z|"));
+
+        assert!(ApparatusState::try_from("fencing").is_err());
+    }
+
+    #[test]
+    fn test_pcard_escalation() {
+        let mut card = PCard::None;
+        let mut levels = vec![card.clone()];
+        while let Some(next) = card.escalate() {
+            assert!(next > card);
+            card = next;
+            levels.push(card.clone());
         }
+        assert_eq!(levels, PCard::ALL);
+
+        assert_eq!(PCard::Yellow.total_penalty_touches(), 0);
+        assert_eq!(PCard::TwoRed.total_penalty_touches(), 2);
+        assert_eq!(PCard::TwoBlack.total_penalty_touches(), 2);
+        assert!(PCard::OneBlack.is_exclusion());
     }
 }
@@ -1,6 +1,9 @@
 use std::error::Error;
 use std::fmt::Display;
 
+use super::enums::{Command, Side, Zone};
+use super::session::Role;
+
 /// Errors that can occur when parsing EFP protocol messages.
 ///
 /// This enum represents all possible parsing errors that can occur when converting
@@ -27,6 +30,17 @@ pub enum ParseError {
     ///
     /// Contains both the field name and the invalid value.
     InvalidValue { field: &'static str, value: String },
+    /// The message exceeds one of the configured size limits.
+    ///
+    /// Contains the name of the limit and its configured maximum.
+    LimitExceeded { limit: &'static str, max: usize },
+    /// The message carries a zone its command does not allow, such as a
+    /// fencer zone in a HELLO.
+    UnexpectedZone { command: Command, zone: Zone },
+    /// The checksum trailer is missing or does not match the frame.
+    ///
+    /// Contains the checksum computed from the frame and the trailer received.
+    ChecksumMismatch { expected: String, found: Option<String> },
 }
 
 impl Display for ParseError {
@@ -40,8 +54,504 @@ impl Display for ParseError {
             ParseError::InvalidValue { field, value } => {
                 write!(f, "Invalid value for {}: {}", field, value)
             }
+            ParseError::LimitExceeded { limit, max } => {
+                write!(f, "Limit exceeded for {}: maximum is {}", limit, max)
+            }
+            ParseError::UnexpectedZone { command, zone } => {
+                write!(f, "Unexpected {} zone in {}", zone, command)
+            }
+            ParseError::ChecksumMismatch { expected, found: Some(found) } => {
+                write!(f, "Checksum mismatch: expected {}, found {}", expected, found)
+            }
+            ParseError::ChecksumMismatch { expected, found: None } => {
+                write!(f, "Checksum missing: expected {}", expected)
+            }
         }
     }
 }
 
 impl Error for ParseError {}
+
+/// Errors raised when a field value cannot be transmitted as is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueError {
+    /// The value contains a character reserved by the frame format (`|` or `%`).
+    ForbiddenCharacter { field: &'static str, character: char },
+    /// The value exceeds the maximum length allowed by the specification.
+    TooLong {
+        field: &'static str,
+        max: usize,
+        value: String,
+    },
+}
+
+impl Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueError::ForbiddenCharacter { field, character } => {
+                write!(f, "Forbidden character '{}' in {}", character, field)
+            }
+            ValueError::TooLong { field, max, value } => {
+                write!(f, "Value for {} exceeds {} characters: {}", field, max, value)
+            }
+        }
+    }
+}
+
+impl Error for ValueError {}
+
+impl From<ValueError> for ParseError {
+    fn from(err: ValueError) -> Self {
+        match err {
+            ValueError::ForbiddenCharacter { field, character } => ParseError::InvalidValue {
+                field,
+                value: character.to_string(),
+            },
+            ValueError::TooLong { field, value, .. } => ParseError::InvalidValue { field, value },
+        }
+    }
+}
+
+/// A rule broken by a message under construction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A text value cannot be transmitted as is.
+    Value(ValueError),
+    /// A priority is given at épée, which has no right of way.
+    PriorityAtEpee,
+    /// The white light of a fencer is on at a weapon other than foil.
+    WhiteLightOutsideFoil(Side),
+    /// A field only used in team events is set outside a team competition.
+    ///
+    /// The `&'static str` contains the name of the field.
+    TeamFieldOutsideTeam(&'static str),
+    /// A fencer is given the victory while the opponent has not lost,
+    /// abandoned or been excluded.
+    VictoryWithoutLoser(Side),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::Value(err) => write!(f, "{}", err),
+            Violation::PriorityAtEpee => write!(f, "Priority set at épée"),
+            Violation::WhiteLightOutsideFoil(side) => {
+                write!(f, "White light of the {} fencer on outside foil", side)
+            }
+            Violation::TeamFieldOutsideTeam(field) => {
+                write!(f, "Team field {} set outside a team competition", field)
+            }
+            Violation::VictoryWithoutLoser(side) => {
+                write!(f, "Victory of the {} fencer without a loser", side)
+            }
+        }
+    }
+}
+
+/// Error returned by [`MessageBuilder::build`](crate::builder::MessageBuilder::build),
+/// listing every rule the message breaks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildError {
+    /// The broken rules, text values first and then cross-field rules.
+    pub violations: Vec<Violation>,
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid message")?;
+        for (index, violation) in self.violations.iter().enumerate() {
+            let separator = if index == 0 { ": " } else { "; " };
+            write!(f, "{}{}", separator, violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for BuildError {}
+
+/// Errors raised when a field is read or written by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    /// No field has this name.
+    UnknownField(String),
+    /// The value does not have the type of the field, or the field cannot be cleared.
+    WrongType { field: String, value: String },
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldError::UnknownField(name) => write!(f, "Unknown field: {}", name),
+            FieldError::WrongType { field, value } => {
+                write!(f, "Value of the wrong type for {}: {}", field, value)
+            }
+        }
+    }
+}
+
+impl Error for FieldError {}
+
+/// Kind of problem reported by a [`ParseWarning`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarningKind {
+    /// The field was not transmitted at all (the zone is too short).
+    FieldMissing,
+    /// The field was transmitted with a value that could not be parsed.
+    ///
+    /// The `String` contains the invalid value, which was treated as absent.
+    FieldInvalid(String),
+}
+
+/// A problem tolerated while parsing a message in lenient mode.
+///
+/// Warnings let callers tell a field that was absent from the frame apart from
+/// a field whose value was invalid, since both end up as `None` in the message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseWarning {
+    /// Zone containing the field.
+    pub zone: Zone,
+    /// Name of the field.
+    pub field: &'static str,
+    /// What was wrong with the field.
+    pub kind: WarningKind,
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            WarningKind::FieldMissing => write!(f, "Field missing: {}.{}", self.zone, self.field),
+            WarningKind::FieldInvalid(value) => {
+                write!(f, "Invalid value for {}.{}: {}", self.zone, self.field, value)
+            }
+        }
+    }
+}
+
+/// Errors raised by a protocol session when a message violates the link rules.
+#[derive(Debug)]
+pub enum SessionError {
+    /// The command may not be sent by the given role.
+    ForbiddenCommand { role: Role, command: Command },
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::ForbiddenCommand { role, command } => {
+                write!(f, "Command {} may not be sent by the {}", command, role)
+            }
+        }
+    }
+}
+
+impl Error for SessionError {}
+
+/// Origin of a frame that failed to parse, passed to a [`ParseErrorHook`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameSource {
+    /// A datagram or stream frame received from the given address.
+    Network(std::net::SocketAddr),
+    /// A line of a message log.
+    Log { line_number: usize },
+}
+
+impl Display for FrameSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameSource::Network(addr) => write!(f, "{}", addr),
+            FrameSource::Log { line_number } => write!(f, "log line {}", line_number),
+        }
+    }
+}
+
+/// Callback receiving every frame that failed to parse, verbatim, with the
+/// parse error and where the frame came from.
+pub type ParseErrorHook = Box<dyn FnMut(&[u8], &ParseError, &FrameSource) + Send>;
+
+/// Errors that can occur when reading a message log.
+#[derive(Debug)]
+pub enum LogError {
+    /// The underlying reader failed.
+    Io(std::io::Error),
+    /// A line is not a valid log entry.
+    InvalidLine { line_number: usize, line: String },
+}
+
+impl Display for LogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogError::Io(err) => write!(f, "I/O error: {}", err),
+            LogError::InvalidLine { line_number, line } => {
+                write!(f, "Invalid log line {}: {}", line_number, line)
+            }
+        }
+    }
+}
+
+impl Error for LogError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LogError::Io(err) => Some(err),
+            LogError::InvalidLine { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LogError {
+    fn from(err: std::io::Error) -> Self {
+        LogError::Io(err)
+    }
+}
+
+/// Errors that can occur when reading a binary recording.
+#[derive(Debug)]
+pub enum RecordingError {
+    /// The underlying reader or writer failed.
+    Io(std::io::Error),
+    /// The data does not start with the recording magic bytes.
+    NotARecording,
+    /// The recording was written in a format version this crate cannot read.
+    UnsupportedVersion(u8),
+    /// A record is cut short or has an invalid direction byte.
+    CorruptRecord { offset: u64 },
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingError::Io(err) => write!(f, "I/O error: {}", err),
+            RecordingError::NotARecording => write!(f, "Not a recording"),
+            RecordingError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported recording version: {}", version)
+            }
+            RecordingError::CorruptRecord { offset } => write!(f, "Corrupt record at offset {}", offset),
+        }
+    }
+}
+
+impl Error for RecordingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RecordingError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RecordingError {
+    fn from(err: std::io::Error) -> Self {
+        RecordingError::Io(err)
+    }
+}
+
+/// Errors that can occur when reading or replaying a state tracker journal.
+#[derive(Debug)]
+pub enum JournalError {
+    /// The underlying reader failed.
+    Io(std::io::Error),
+    /// A line is not a valid journal entry.
+    InvalidLine { line_number: usize, line: String },
+    /// An entry is not numbered right after the one before it.
+    OutOfSequence { expected: u64, found: u64 },
+    /// A journaled frame is not a valid EFP message.
+    InvalidFrame { seq: u64, error: ParseError },
+}
+
+impl Display for JournalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JournalError::Io(err) => write!(f, "I/O error: {}", err),
+            JournalError::InvalidLine { line_number, line } => {
+                write!(f, "Invalid journal line {}: {}", line_number, line)
+            }
+            JournalError::OutOfSequence { expected, found } => {
+                write!(f, "Journal entry {} found where {} was expected", found, expected)
+            }
+            JournalError::InvalidFrame { seq, error } => {
+                write!(f, "Invalid frame in journal entry {}: {}", seq, error)
+            }
+        }
+    }
+}
+
+impl Error for JournalError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JournalError::Io(err) => Some(err),
+            JournalError::InvalidFrame { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for JournalError {
+    fn from(err: std::io::Error) -> Self {
+        JournalError::Io(err)
+    }
+}
+
+/// Errors that can occur when reading or compiling a bout script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    /// A line of the script could not be understood.
+    InvalidLine { line_number: usize, line: String },
+    /// A clock value is not of the form `m:ss`.
+    InvalidClock(String),
+    /// A step is scheduled at a later clock value than the step before it.
+    ClockReversed { step: usize, clock: String },
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptError::InvalidLine { line_number, line } => {
+                write!(f, "Invalid script line {}: {}", line_number, line)
+            }
+            ScriptError::InvalidClock(clock) => write!(f, "Invalid clock value: {}", clock),
+            ScriptError::ClockReversed { step, clock } => {
+                write!(f, "Step {} at {} runs the clock backwards", step, clock)
+            }
+        }
+    }
+}
+
+impl Error for ScriptError {}
+
+/// Errors that can occur when delivering a webhook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookError {
+    /// The payload could not be encoded.
+    Encoding(String),
+    /// Every delivery attempt failed.
+    Delivery {
+        url: String,
+        attempts: u32,
+        reason: String,
+    },
+}
+
+impl Display for WebhookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookError::Encoding(reason) => write!(f, "Cannot encode webhook payload: {}", reason),
+            WebhookError::Delivery {
+                url,
+                attempts,
+                reason,
+            } => write!(f, "Webhook to {} failed after {} attempts: {}", url, attempts, reason),
+        }
+    }
+}
+
+impl Error for WebhookError {}
+
+/// Errors that can occur when reading an FIE competition XML file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieXmlError {
+    /// The document is not well-formed XML.
+    Xml(String),
+    /// A required attribute is missing from an element.
+    MissingAttribute {
+        element: &'static str,
+        attribute: &'static str,
+    },
+    /// An attribute has a value that cannot be interpreted.
+    InvalidValue { attribute: &'static str, value: String },
+}
+
+impl Display for FieXmlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieXmlError::Xml(reason) => write!(f, "Malformed XML: {}", reason),
+            FieXmlError::MissingAttribute { element, attribute } => {
+                write!(f, "Missing attribute {} on {}", attribute, element)
+            }
+            FieXmlError::InvalidValue { attribute, value } => {
+                write!(f, "Invalid value for {}: {}", attribute, value)
+            }
+        }
+    }
+}
+
+impl Error for FieXmlError {}
+
+/// Errors that can occur when advertising or discovering endpoints over mDNS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiscoveryError {
+    /// The mDNS daemon could not be started or stopped responding.
+    Daemon(String),
+    /// The service description was rejected, e.g. because of an invalid instance name.
+    InvalidService(String),
+}
+
+impl Display for DiscoveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiscoveryError::Daemon(reason) => write!(f, "mDNS daemon error: {}", reason),
+            DiscoveryError::InvalidService(reason) => write!(f, "Invalid mDNS service: {}", reason),
+        }
+    }
+}
+
+impl Error for DiscoveryError {}
+
+/// Errors that can occur when importing a piste assignment export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// The export has no header line.
+    Empty,
+    /// A column required by the import is absent from the header.
+    MissingColumn(&'static str),
+    /// A row has a value that cannot be interpreted.
+    InvalidRow {
+        line_number: usize,
+        column: &'static str,
+        value: String,
+    },
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Empty => write!(f, "Empty export"),
+            ImportError::MissingColumn(column) => write!(f, "Missing column: {}", column),
+            ImportError::InvalidRow {
+                line_number,
+                column,
+                value,
+            } => write!(f, "Invalid {} on line {}: {}", column, line_number, value),
+        }
+    }
+}
+
+impl Error for ImportError {}
+
+/// Errors that can occur when decoding a segment display frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentError {
+    /// The frame does not have the fixed frame length.
+    Length(usize),
+    /// The frame does not start with STX and end with ETX.
+    Framing,
+    /// The checksum byte does not match the frame content.
+    Checksum { expected: u8, found: u8 },
+    /// A byte holds a value outside its documented range.
+    InvalidByte { offset: usize, value: u8 },
+}
+
+impl Display for SegmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SegmentError::Length(length) => write!(f, "Invalid segment frame length: {}", length),
+            SegmentError::Framing => write!(f, "Segment frame is not delimited by STX and ETX"),
+            SegmentError::Checksum { expected, found } => {
+                write!(f, "Segment frame checksum mismatch: expected {:#04x}, found {:#04x}", expected, found)
+            }
+            SegmentError::InvalidByte { offset, value } => {
+                write!(f, "Invalid byte {:#04x} at offset {}", value, offset)
+            }
+        }
+    }
+}
+
+impl Error for SegmentError {}
@@ -1,21 +1,47 @@
-use std::convert::TryFrom;
-use crate::utils::{get_field, parse_optional_bool, parse_optional_u8};
-use super::enums::{FencerStatus, PCard, Reserve};
-use super::error::ParseError;
+use crate::options::ParseOptions;
+use std::fmt::{self, Write};
+
+use crate::borrowed::FencerRef;
+use crate::message::FieldString;
+use crate::utils::{canonical_code, canonical_text, CountingWriter, FieldReader, FieldRef};
+use super::enums::{FencerStatus, PCard, Reserve, Zone};
+use super::error::{ParseError, ValueError};
+use super::limits::{check_optional, sanitize_optional, MAX_ID_LENGTH, MAX_NAME_LENGTH, MAX_NATION_LENGTH};
+
+/// Names of the fields of a fencer zone, in protocol order.
+pub const FENCER_FIELD_NAMES: [&str; 15] = [
+    "id",
+    "name",
+    "nation",
+    "score",
+    "status",
+    "yellow_card",
+    "red_card",
+    "light",
+    "white_light",
+    "medical",
+    "reserve",
+    "p_card",
+    "bout_score",
+    "video_appeals",
+    "video_review",
+];
 
 /// Information about a fencer participating in a match.
 ///
 /// Contains all relevant data about a fencer including their identity, score,
 /// penalties, and status indicators.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Fencer {
     /// Unique identifier for the fencer.
-    pub id: Option<String>,
+    pub id: Option<FieldString>,
     /// Full name of the fencer.
-    pub name: Option<String>,
+    pub name: Option<FieldString>,
     /// Three-letter country code of the fencer's nation (e.g., "FRA", "USA").
-    pub nation: Option<String>,
-    /// Current score in the match.
+    pub nation: Option<FieldString>,
+    /// Score shown by the apparatus: the score of the bout, or the cumulative
+    /// team score in a team match (see [`Message::relay_score`](crate::message::Message::relay_score)).
     pub score: Option<u8>,
     /// Match status (victory, defeat, etc.).
     pub status: Option<FencerStatus>,
@@ -33,6 +59,16 @@ pub struct Fencer {
     pub reserve: Option<Reserve>,
     /// Fencer P-Card.
     pub p_card: Option<PCard>,
+    /// Touches scored in the current relay of a team match, reported by some
+    /// apparatus after the P-card (see [`Message::bout_score`](crate::message::Message::bout_score)).
+    ///
+    /// Only kept for EFP1.1 team frames.
+    pub bout_score: Option<u8>,
+    /// Video appeals the fencer has left, sent by finals pistes with video
+    /// refereeing after the bout score.
+    pub video_appeals: Option<u8>,
+    /// Whether a video review requested by the fencer is in progress.
+    pub video_review: Option<bool>,
 }
 
 impl Fencer {
@@ -50,56 +86,133 @@ impl Fencer {
     ///
     /// Returns `ParseError` if any required field is missing or contains invalid data.
     pub fn parse(fields: &[&str]) -> Result<Self, ParseError> {
-        Ok(Fencer {
-            id: get_field(fields, 0).map(String::from),
-            name: get_field(fields, 1).map(String::from),
-            nation: get_field(fields, 2).map(String::from),
-            score: parse_optional_u8(fields, 3),
-            status: get_field(fields, 4).and_then(|s| FencerStatus::try_from(s).ok()),
-            yellow_card: parse_optional_u8(fields, 5),
-            red_card: parse_optional_u8(fields, 6),
-            light: parse_optional_bool(fields, 7),
-            white_light: parse_optional_bool(fields, 8),
-            medical: parse_optional_u8(fields, 9),
-            reserve: get_field(fields, 10).and_then(|s| Reserve::try_from(s).ok()),
-            p_card: get_field(fields, 11).and_then(|s| PCard::try_from(s).ok()),
-        })
+        Fencer::parse_with(fields, &ParseOptions::default())
+    }
+
+    /// Parses fencer data from an array of string fields with the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - Array of string slices containing fencer data in protocol format
+    /// * `options` - Parsing options
+    ///
+    /// # Errors
+    ///
+    /// In strict mode, returns `ParseError::InvalidValue` if a numeric, boolean or
+    /// enumerated field contains an invalid value.
+    pub fn parse_with(fields: &[&str], options: &ParseOptions) -> Result<Self, ParseError> {
+        let mut warnings = Vec::new();
+        Fencer::read(&mut FieldReader::new(
+            fields,
+            Zone::RightFencer,
+            options.strict,
+            &mut warnings,
+        ))
+    }
+
+    /// Reads fencer data from a zone reader.
+    pub(crate) fn read(reader: &mut FieldReader<'_, '_, '_>) -> Result<Self, ParseError> {
+        FencerRef::read(reader).map(|fencer| fencer.to_fencer())
+    }
+
+    /// Checks the text fields against the specification limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ValueError` for the first field containing `|` or `%`, or
+    /// exceeding its maximum length.
+    pub fn validate(&self) -> Result<(), ValueError> {
+        self.validate_named(["fencer.id", "fencer.name", "fencer.nation"])
+    }
+
+    /// Checks the text fields, reporting errors under the given id, name and nation field names.
+    pub(crate) fn validate_named(&self, names: [&'static str; 3]) -> Result<(), ValueError> {
+        check_optional(names[0], &self.id, MAX_ID_LENGTH)?;
+        check_optional(names[1], &self.name, MAX_NAME_LENGTH)?;
+        check_optional(names[2], &self.nation, MAX_NATION_LENGTH)
+    }
+
+    /// Returns the fencer with trimmed id and name and an uppercase nation code.
+    pub fn canonicalize(&self) -> Fencer {
+        Fencer {
+            id: canonical_text(&self.id),
+            name: canonical_text(&self.name),
+            nation: canonical_code(&self.nation),
+            ..self.clone()
+        }
+    }
+
+    /// Replaces forbidden characters and truncates the text fields to their maximum length.
+    pub fn sanitize(&mut self) {
+        sanitize_optional(&mut self.id, MAX_ID_LENGTH);
+        sanitize_optional(&mut self.name, MAX_NAME_LENGTH);
+        sanitize_optional(&mut self.nation, MAX_NATION_LENGTH);
     }
 
     /// Serializes the fencer data into protocol format.
     ///
     /// Converts the fencer's data into a pipe-delimited string format according
-    /// to the EFP protocol specification. Empty trailing fields are trimmed.
+    /// to the EFP protocol specification. Empty trailing fields are trimmed, and
+    /// `|` or `%` characters inside values are replaced by spaces so they cannot
+    /// corrupt the frame.
     ///
     /// # Returns
     ///
     /// A `String` containing the serialized fencer data.
     pub fn serialize(&self) -> String {
-        let fields: Vec<String> = vec![
-            self.id.clone().unwrap_or_default(),
-            self.name.clone().unwrap_or_default(),
-            self.nation.clone().unwrap_or_default(),
-            self.score.map(|v| v.to_string()).unwrap_or_default(),
-            self.status.as_ref().map(|v| v.to_string()).unwrap_or_default(),
-            self.yellow_card.map(|v| v.to_string()).unwrap_or_default(),
-            self.red_card.map(|v| v.to_string()).unwrap_or_default(),
-            self.light.map(|v| if v { "1" } else { "0" }.to_string()).unwrap_or_default(),
-            self.white_light.map(|v| if v { "1" } else { "0" }.to_string()).unwrap_or_default(),
-            self.medical.map(|v| v.to_string()).unwrap_or_default(),
-            self.reserve.as_ref().map(|v| v.to_string()).unwrap_or_default(),
-            self.p_card.as_ref().map(|v| v.to_string()).unwrap_or_default(),
-        ];
+        let mut out = String::new();
+        // Writing to a `String` cannot fail.
+        let _ = self.write_to(&mut out);
+        out
+    }
+
+    /// Writes the fencer data in protocol format to any `fmt::Write` sink.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - The destination
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `fmt::Error` if the destination fails.
+    pub fn write_to<W: fmt::Write + ?Sized>(&self, out: &mut W) -> Result<usize, fmt::Error> {
+        let fields = self.field_refs();
+        let mut out = CountingWriter::new(out);
 
         // Remove empty fields at the end
-        let trimmed = fields
-            .into_iter()
-            .rev()
-            .skip_while(|s| s.is_empty())
-            .collect::<Vec<_>>()
-            .into_iter()
-            .rev()
-            .collect::<Vec<_>>();
+        let len = fields.iter().rposition(|f| !f.is_empty()).map_or(0, |i| i + 1);
+        for (index, field) in fields[..len].iter().enumerate() {
+            if index > 0 {
+                out.write_char('|')?;
+            }
+            field.write(&mut out)?;
+        }
+
+        Ok(out.count())
+    }
 
-        trimmed.join("|")
+    /// Returns views of the fencer fields, in protocol order.
+    pub(crate) fn field_refs(&self) -> [FieldRef<'_>; 15] {
+        [
+            FieldRef::text(&self.id),
+            FieldRef::text(&self.name),
+            FieldRef::text(&self.nation),
+            FieldRef::number(self.score),
+            FieldRef::code(&self.status),
+            FieldRef::number(self.yellow_card),
+            FieldRef::number(self.red_card),
+            FieldRef::flag(self.light),
+            FieldRef::flag(self.white_light),
+            FieldRef::number(self.medical),
+            FieldRef::code(&self.reserve),
+            FieldRef::code(&self.p_card),
+            FieldRef::number(self.bout_score),
+            FieldRef::number(self.video_appeals),
+            FieldRef::flag(self.video_review),
+        ]
     }
 }
@@ -0,0 +1,536 @@
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::competition::BoutResult;
+use super::enums::{FencerStatus, Weapon};
+use super::error::FieXmlError;
+use super::fencer::Fencer;
+use super::referee::Referee;
+use super::utils::field;
+
+/// Fencer entry (`<Tireur>`) of an FIE competition file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieFencer {
+    /// Identifier referenced by the matches (`ID`).
+    pub id: String,
+    /// Family name (`Nom`), upper case by FIE convention.
+    pub surname: String,
+    /// Given name (`Prenom`).
+    pub first_name: String,
+    /// Three-letter nation code (`Nation`).
+    pub nation: Option<String>,
+}
+
+impl FieFencer {
+    /// Maps an EFP fencer to an FIE entry.
+    ///
+    /// EFP carries a single name field: a leading run of upper-case words is
+    /// taken as the family name ("MARTIN Paul"), otherwise the whole name is.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the fencer has no identifier.
+    pub fn from_fencer(fencer: &Fencer) -> Option<Self> {
+        let (surname, first_name) = split_name(fencer.name.as_deref().unwrap_or(""));
+        Some(FieFencer {
+            id: fencer.id.as_deref().filter(|id| !id.is_empty())?.to_string(),
+            surname,
+            first_name,
+            nation: fencer.nation.as_deref().map(String::from),
+        })
+    }
+
+    /// Maps the entry to an EFP fencer with its identity fields set.
+    pub fn to_fencer(&self) -> Fencer {
+        Fencer {
+            id: Some(field(self.id.clone())),
+            name: Some(field(join_name(&self.surname, &self.first_name))),
+            nation: self.nation.clone().map(field),
+            ..Fencer::default()
+        }
+    }
+}
+
+/// Referee entry (`<Arbitre>`) of an FIE competition file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieReferee {
+    /// Identifier referenced by the matches (`ID`).
+    pub id: String,
+    /// Family name (`Nom`).
+    pub surname: String,
+    /// Given name (`Prenom`).
+    pub first_name: String,
+    /// Three-letter nation code (`Nation`).
+    pub nation: Option<String>,
+    /// Referee category (`Categorie`), e.g. "A" or "B".
+    pub category: Option<String>,
+}
+
+impl FieReferee {
+    /// Maps an EFP referee to an FIE entry.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the referee has no identifier.
+    pub fn from_referee(referee: &Referee) -> Option<Self> {
+        let (surname, first_name) = split_name(referee.name.as_deref().unwrap_or(""));
+        Some(FieReferee {
+            id: referee.id.as_deref().filter(|id| !id.is_empty())?.to_string(),
+            surname,
+            first_name,
+            nation: referee.nation.as_deref().map(String::from),
+            category: None,
+        })
+    }
+
+    /// Maps the entry to an EFP referee. FIE referee entries carry no
+    /// sanctions against officials, which are recorded per bout.
+    pub fn to_referee(&self) -> Referee {
+        Referee {
+            id: Some(field(self.id.clone())),
+            name: Some(field(join_name(&self.surname, &self.first_name))),
+            nation: self.nation.clone().map(field),
+            sanctions: None,
+        }
+    }
+}
+
+/// One side of a match (`<Tireur REF=...>` inside `<Match>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieMatchFencer {
+    /// Identifier of the fencer entry (`REF`).
+    pub fencer_ref: String,
+    /// Touches scored (`Score`).
+    pub score: Option<u8>,
+    /// Outcome for the fencer (`Statut`: V, D, A or E).
+    pub status: Option<FencerStatus>,
+}
+
+impl FieMatchFencer {
+    fn from_fencer(fencer: &Fencer) -> Self {
+        FieMatchFencer {
+            fencer_ref: fencer.id.as_deref().unwrap_or_default().to_string(),
+            score: fencer.score,
+            status: fencer.status.clone().filter(FencerStatus::is_final),
+        }
+    }
+}
+
+/// Match result (`<Match>`) of an FIE competition file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieMatch {
+    /// Match identifier (`ID`).
+    pub id: String,
+    /// Phase number (`ID` of the enclosing `<Phase>`).
+    pub phase: Option<u8>,
+    /// Pool or tableau identifier (`Poule`).
+    pub pool_tableau: Option<String>,
+    /// Piste the match was fenced on (`Piste`).
+    pub piste: Option<String>,
+    /// Identifier of the referee entry (`Arbitre`).
+    pub referee_ref: Option<String>,
+    /// Fencer on the right.
+    pub right: FieMatchFencer,
+    /// Fencer on the left.
+    pub left: FieMatchFencer,
+}
+
+impl FieMatch {
+    /// Maps a bout result recorded by the competition tracker to an FIE match.
+    pub fn from_result(result: &BoutResult) -> Self {
+        FieMatch {
+            id: result.match_number.map(|n| n.to_string()).unwrap_or_default(),
+            phase: result.phase,
+            pool_tableau: result.pool_tableau.clone(),
+            piste: Some(result.piste.clone()),
+            referee_ref: None,
+            right: FieMatchFencer::from_fencer(&result.right_fencer),
+            left: FieMatchFencer::from_fencer(&result.left_fencer),
+        }
+    }
+}
+
+/// FIE competition file (`<CompetitionIndividuelle>`).
+///
+/// Covers the fencer, referee and match elements an EFP-driven results system
+/// can fill in; other elements of FIE files are ignored when reading.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::competition::CompetitionTracker;
+/// use cyrano::fie::FieCompetition;
+/// use cyrano::message::Message;
+///
+/// let mut tracker = CompetitionTracker::new();
+/// tracker.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1|||||E||E|%|28|MARTIN Paul|FRA|5|V|%|32|PANINI Bruno|ITA|2|D|%|").unwrap());
+///
+/// let mut file = FieCompetition::new("fm-eq");
+/// for result in tracker.results("fm-eq", 1, None) {
+///     file.add_result(result);
+/// }
+///
+/// let xml = file.to_xml();
+/// assert!(xml.contains(r#"<Tireur ID="28" Nom="MARTIN" Prenom="Paul" Nation="FRA"/>"#));
+/// assert_eq!(FieCompetition::from_xml(&xml).unwrap(), file);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FieCompetition {
+    /// Competition identifier (`ID`).
+    pub id: String,
+    /// Weapon (`Arme`).
+    pub weapon: Option<Weapon>,
+    /// Competition title (`TitreLong`).
+    pub title: Option<String>,
+    /// Fencer entries.
+    pub fencers: Vec<FieFencer>,
+    /// Referee entries.
+    pub referees: Vec<FieReferee>,
+    /// Match results.
+    pub matches: Vec<FieMatch>,
+}
+
+impl FieCompetition {
+    /// Creates an empty file for the given competition.
+    pub fn new(id: impl Into<String>) -> Self {
+        FieCompetition {
+            id: id.into(),
+            ..FieCompetition::default()
+        }
+    }
+
+    /// Adds a fencer entry unless one with the same identifier exists.
+    pub fn add_fencer(&mut self, fencer: &Fencer) {
+        if let Some(entry) = FieFencer::from_fencer(fencer) {
+            if !self.fencers.iter().any(|f| f.id == entry.id) {
+                self.fencers.push(entry);
+            }
+        }
+    }
+
+    /// Adds a referee entry unless one with the same identifier exists.
+    pub fn add_referee(&mut self, referee: &Referee) {
+        if let Some(entry) = FieReferee::from_referee(referee) {
+            if !self.referees.iter().any(|r| r.id == entry.id) {
+                self.referees.push(entry);
+            }
+        }
+    }
+
+    /// Adds a bout result along with the entries of both fencers.
+    pub fn add_result(&mut self, result: &BoutResult) {
+        self.add_fencer(&result.right_fencer);
+        self.add_fencer(&result.left_fencer);
+        self.matches.push(FieMatch::from_result(result));
+    }
+
+    /// Returns the fencer entry with the given identifier.
+    pub fn fencer(&self, id: &str) -> Option<&FieFencer> {
+        self.fencers.iter().find(|f| f.id == id)
+    }
+
+    /// Writes the file as FIE XML.
+    ///
+    /// Matches are grouped in one `<Phase>` element per phase number, in order
+    /// of first appearance.
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+        let _ = write!(xml, "<CompetitionIndividuelle ID=\"{}\"", escape(&self.id));
+        if let Some(weapon) = &self.weapon {
+            let _ = write!(xml, " Arme=\"{}\"", weapon);
+        }
+        if let Some(title) = &self.title {
+            let _ = write!(xml, " TitreLong=\"{}\"", escape(title));
+        }
+        xml.push_str(">\n");
+
+        xml.push_str("  <Tireurs>\n");
+        for fencer in &self.fencers {
+            let _ = write!(
+                xml,
+                "    <Tireur ID=\"{}\" Nom=\"{}\" Prenom=\"{}\"",
+                escape(&fencer.id),
+                escape(&fencer.surname),
+                escape(&fencer.first_name)
+            );
+            write_optional(&mut xml, "Nation", &fencer.nation);
+            xml.push_str("/>\n");
+        }
+        xml.push_str("  </Tireurs>\n");
+
+        xml.push_str("  <Arbitres>\n");
+        for referee in &self.referees {
+            let _ = write!(
+                xml,
+                "    <Arbitre ID=\"{}\" Nom=\"{}\" Prenom=\"{}\"",
+                escape(&referee.id),
+                escape(&referee.surname),
+                escape(&referee.first_name)
+            );
+            write_optional(&mut xml, "Nation", &referee.nation);
+            write_optional(&mut xml, "Categorie", &referee.category);
+            xml.push_str("/>\n");
+        }
+        xml.push_str("  </Arbitres>\n");
+
+        let mut phases: Vec<Option<u8>> = Vec::new();
+        for m in &self.matches {
+            if !phases.contains(&m.phase) {
+                phases.push(m.phase);
+            }
+        }
+
+        xml.push_str("  <Phases>\n");
+        for phase in phases {
+            xml.push_str("    <Phase");
+            write_optional(&mut xml, "ID", &phase);
+            xml.push_str(">\n");
+            for m in self.matches.iter().filter(|m| m.phase == phase) {
+                let _ = write!(xml, "      <Match ID=\"{}\"", escape(&m.id));
+                write_optional(&mut xml, "Poule", &m.pool_tableau);
+                write_optional(&mut xml, "Piste", &m.piste);
+                write_optional(&mut xml, "Arbitre", &m.referee_ref);
+                xml.push_str(">\n");
+                for side in [&m.right, &m.left] {
+                    let _ = write!(xml, "        <Tireur REF=\"{}\"", escape(&side.fencer_ref));
+                    write_optional(&mut xml, "Score", &side.score);
+                    write_optional(&mut xml, "Statut", &side.status);
+                    xml.push_str("/>\n");
+                }
+                xml.push_str("      </Match>\n");
+            }
+            xml.push_str("    </Phase>\n");
+        }
+        xml.push_str("  </Phases>\n");
+        xml.push_str("</CompetitionIndividuelle>\n");
+        xml
+    }
+
+    /// Reads an FIE XML file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FieXmlError` if the document is malformed, an `ID` or `REF`
+    /// attribute is missing, or a weapon, score or status cannot be interpreted.
+    pub fn from_xml(xml: &str) -> Result<Self, FieXmlError> {
+        let mut reader = Reader::from_str(xml);
+        let mut competition = FieCompetition::default();
+        let mut phase: Option<u8> = None;
+        let mut current: Option<(FieMatch, Vec<FieMatchFencer>)> = None;
+
+        loop {
+            let event = reader
+                .read_event()
+                .map_err(|err| FieXmlError::Xml(err.to_string()))?;
+            let (element, is_empty) = match &event {
+                Event::Start(e) => (e, false),
+                Event::Empty(e) => (e, true),
+                Event::End(e) => {
+                    if e.name().as_ref() == "Match" {
+                        if let Some((mut m, mut sides)) = current.take() {
+                            if sides.len() != 2 {
+                                return Err(FieXmlError::MissingAttribute {
+                                    element: "Match",
+                                    attribute: "Tireur",
+                                });
+                            }
+                            m.left = sides.pop().unwrap_or_else(empty_side);
+                            m.right = sides.pop().unwrap_or_else(empty_side);
+                            competition.matches.push(m);
+                        }
+                    }
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+
+            match element.name().as_ref() {
+                "CompetitionIndividuelle" | "CompetitionParEquipes" => {
+                    competition.id = required(element, "CompetitionIndividuelle", "ID")?;
+                    competition.weapon = optional(element, "Arme")?
+                        .map(|arme| {
+                            Weapon::try_from(arme.as_str()).map_err(|_| FieXmlError::InvalidValue {
+                                attribute: "Arme",
+                                value: arme,
+                            })
+                        })
+                        .transpose()?;
+                    competition.title = optional(element, "TitreLong")?;
+                }
+                "Phase" => phase = number(element, "ID")?,
+                "Match" => {
+                    let m = FieMatch {
+                        id: required(element, "Match", "ID")?,
+                        phase,
+                        pool_tableau: optional(element, "Poule")?,
+                        piste: optional(element, "Piste")?,
+                        referee_ref: optional(element, "Arbitre")?,
+                        right: empty_side(),
+                        left: empty_side(),
+                    };
+                    if is_empty {
+                        competition.matches.push(m);
+                    } else {
+                        current = Some((m, Vec::new()));
+                    }
+                }
+                "Tireur" => match current.as_mut() {
+                    Some((_, sides)) => sides.push(FieMatchFencer {
+                        fencer_ref: required(element, "Tireur", "REF")?,
+                        score: number(element, "Score")?,
+                        status: optional(element, "Statut")?
+                            .map(|statut| {
+                                FencerStatus::try_from(statut.as_str()).map_err(|_| FieXmlError::InvalidValue {
+                                    attribute: "Statut",
+                                    value: statut,
+                                })
+                            })
+                            .transpose()?,
+                    }),
+                    None => competition.fencers.push(FieFencer {
+                        id: required(element, "Tireur", "ID")?,
+                        surname: optional(element, "Nom")?.unwrap_or_default(),
+                        first_name: optional(element, "Prenom")?.unwrap_or_default(),
+                        nation: optional(element, "Nation")?,
+                    }),
+                },
+                "Arbitre" => competition.referees.push(FieReferee {
+                    id: required(element, "Arbitre", "ID")?,
+                    surname: optional(element, "Nom")?.unwrap_or_default(),
+                    first_name: optional(element, "Prenom")?.unwrap_or_default(),
+                    nation: optional(element, "Nation")?,
+                    category: optional(element, "Categorie")?,
+                }),
+                _ => {}
+            }
+        }
+
+        Ok(competition)
+    }
+}
+
+fn empty_side() -> FieMatchFencer {
+    FieMatchFencer {
+        fencer_ref: String::new(),
+        score: None,
+        status: None,
+    }
+}
+
+fn optional(element: &BytesStart<'_>, attribute: &'static str) -> Result<Option<String>, FieXmlError> {
+    for attr in element.attributes() {
+        let attr = attr.map_err(|err| FieXmlError::Xml(err.to_string()))?;
+        if attr.key.as_ref() == attribute {
+            let value = attr
+                .normalized_value(quick_xml::XmlVersion::Implicit1_0)
+                .map_err(|err| FieXmlError::Xml(err.to_string()))?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn required(element: &BytesStart<'_>, name: &'static str, attribute: &'static str) -> Result<String, FieXmlError> {
+    optional(element, attribute)?.ok_or(FieXmlError::MissingAttribute {
+        element: name,
+        attribute,
+    })
+}
+
+fn number(element: &BytesStart<'_>, attribute: &'static str) -> Result<Option<u8>, FieXmlError> {
+    optional(element, attribute)?
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| FieXmlError::InvalidValue { attribute, value })
+        })
+        .transpose()
+}
+
+fn write_optional<T: std::fmt::Display>(xml: &mut String, attribute: &str, value: &Option<T>) {
+    if let Some(value) = value {
+        let _ = write!(xml, " {}=\"{}\"", attribute, escape(&value.to_string()));
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Splits an EFP name into family name and given name.
+fn split_name(name: &str) -> (String, String) {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let is_upper = |w: &str| w.chars().any(char::is_alphabetic) && !w.chars().any(char::is_lowercase);
+    let surname_words = words.iter().take_while(|w| is_upper(w)).count();
+
+    if surname_words == 0 || surname_words == words.len() {
+        (name.trim().to_string(), String::new())
+    } else {
+        (words[..surname_words].join(" "), words[surname_words..].join(" "))
+    }
+}
+
+fn join_name(surname: &str, first_name: &str) -> String {
+    format!("{} {}", surname, first_name).trim().to_string()
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_name() {
+        assert_eq!(split_name("MARTIN Paul"), ("MARTIN".to_string(), "Paul".to_string()));
+        assert_eq!(split_name("DE LA TOUR Anne Marie"), ("DE LA TOUR".to_string(), "Anne Marie".to_string()));
+        assert_eq!(split_name("P.Martin"), ("P.Martin".to_string(), String::new()));
+    }
+
+    #[test]
+    fn test_read_official_file() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CompetitionIndividuelle ID="fm-eq" Arme="E" Sexe="F" TitreLong="Coupe &amp; Challenge">
+  <Tireurs><Tireur ID="28" Nom="MARTIN" Prenom="Paule" Nation="FRA" Club="X"/></Tireurs>
+  <Arbitres><Arbitre ID="5" Nom="SMITH" Prenom="John" Nation="GBR" Categorie="A"/></Arbitres>
+  <Phases>
+    <Phase ID="2">
+      <Match ID="7" Poule="T64" Piste="Rouge"><Tireur REF="28" Score="15" Statut="V"/><Tireur REF="32" Score="9" Statut="D"/></Match>
+    </Phase>
+  </Phases>
+</CompetitionIndividuelle>"#;
+
+        let file = FieCompetition::from_xml(xml).unwrap();
+        assert_eq!(file.weapon, Some(Weapon::Epee));
+        assert_eq!(file.title.as_deref(), Some("Coupe & Challenge"));
+        assert_eq!(file.fencer("28").unwrap().to_fencer().name.as_deref(), Some("MARTIN Paule"));
+        assert_eq!(file.referees[0].category.as_deref(), Some("A"));
+
+        let m = &file.matches[0];
+        assert_eq!(m.phase, Some(2));
+        assert_eq!(m.right.score, Some(15));
+        assert_eq!(m.left.status, Some(FencerStatus::Defeat));
+
+        assert!(matches!(
+            FieCompetition::from_xml(r#"<CompetitionIndividuelle ID="x"><Tireurs><Tireur Nom="A"/></Tireurs></CompetitionIndividuelle>"#),
+            Err(FieXmlError::MissingAttribute { attribute: "ID", .. })
+        ));
+    }
+}
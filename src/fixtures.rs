@@ -0,0 +1,275 @@
+use crate::corpus::{corpus, Expectation};
+use crate::enums::{Command, Side, Weapon};
+use crate::fencer::Fencer;
+use crate::logfile::LogEntry;
+use crate::message::Message;
+use crate::simulator::{BoutScript, ScriptAction, ScriptStep};
+use crate::utils::{field, Rng};
+
+/// Competition identifier used by generated traffic.
+pub const COMPETITION_ID: &str = "fixture";
+
+/// Wall time between two bouts on a piste, in milliseconds.
+const CHANGEOVER_MS: u64 = 90_000;
+
+/// Delay between the start of two consecutive pistes, in milliseconds.
+const PISTE_STAGGER_MS: u64 = 7_000;
+
+const NATIONS: [&str; 8] = ["FRA", "ITA", "HUN", "KOR", "USA", "GER", "JPN", "UKR"];
+
+fn random_side(rng: &mut Rng) -> Side {
+    if rng.below(2) == 0 {
+        Side::Right
+    } else {
+        Side::Left
+    }
+}
+
+/// Returns the corpus frames that parse in lenient mode.
+pub fn valid_frames() -> impl Iterator<Item = &'static str> {
+    corpus()
+        .iter()
+        .filter(|entry| entry.expectation != Expectation::Invalid)
+        .map(|entry| entry.frame)
+}
+
+/// Returns the script of a generated bout.
+///
+/// Everything is derived from `number`: the same number always gives the same
+/// fencers, weapon and actions. The weapon cycles through foil, épée and sabre,
+/// and the bout runs until a fencer reaches 15 touches or the 3 minutes are up.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::fixtures;
+///
+/// let script = fixtures::bout_script("4", 12);
+/// assert_eq!(script, fixtures::bout_script("4", 12));
+/// assert!(script.compile().is_ok());
+/// ```
+pub fn bout_script(piste: &str, number: u64) -> BoutScript {
+    let mut rng = Rng::new(number);
+    let weapon = [Weapon::Foil, Weapon::Epee, Weapon::Sabre][(number % 3) as usize].clone();
+
+    let mut steps = Vec::new();
+    let mut clock = 180;
+    let mut scores = [0u8; 2];
+
+    while scores.iter().all(|&score| score < 15) {
+        steps.push(step(clock, ScriptAction::Fence));
+
+        let fenced = 2 + rng.below(25);
+        if fenced >= clock {
+            clock = 0;
+            break;
+        }
+        clock -= fenced;
+
+        let action = match rng.below(10) {
+            0 if weapon == Weapon::Epee => {
+                scores = [scores[0] + 1, scores[1] + 1];
+                ScriptAction::DoubleTouch
+            }
+            0..=3 => {
+                scores[0] += 1;
+                ScriptAction::Touch(Side::Right)
+            }
+            4..=7 => {
+                scores[1] += 1;
+                ScriptAction::Touch(Side::Left)
+            }
+            8 => ScriptAction::OffTarget(random_side(&mut rng)),
+            _ => ScriptAction::Yellow(random_side(&mut rng)),
+        };
+        steps.push(step(clock, action));
+    }
+    steps.push(step(clock, ScriptAction::End));
+
+    BoutScript {
+        piste: piste.to_string(),
+        competition_id: COMPETITION_ID.to_string(),
+        weapon,
+        duration: "3:00".to_string(),
+        right_fencer: fencer(number * 2),
+        left_fencer: fencer(number * 2 + 1),
+        steps,
+    }
+}
+
+fn step(clock: u64, action: ScriptAction) -> ScriptStep {
+    ScriptStep {
+        clock: format!("{}:{:02}", clock / 60, clock % 60),
+        action,
+    }
+}
+
+fn fencer(number: u64) -> Fencer {
+    Fencer {
+        id: Some(field((100 + number).to_string())),
+        name: Some(field(format!("FENCER {}", number))),
+        nation: Some(NATIONS[(number % NATIONS.len() as u64) as usize].into()),
+        ..Fencer::default()
+    }
+}
+
+/// Returns the INFO messages of the bout generated by [`bout_script`], in order.
+pub fn bout_timeline(piste: &str, number: u64) -> Vec<Message> {
+    // Generated clocks never go up, so the script always compiles.
+    bout_script(piste, number)
+        .compile()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|frame| frame.message)
+        .collect()
+}
+
+/// Returns a day of traffic received from a venue.
+///
+/// Each of the `pistes` pistes, named `1`, `2` and so on, sends a HELLO and
+/// then runs `bouts_per_piste` generated bouts one after the other. Pistes
+/// start a few seconds apart so that their frames interleave. Entries are
+/// ordered by timestamp, the first one at `start_ms`.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::fixtures;
+///
+/// let log = fixtures::day_log(0, 4, 10);
+/// assert!(log.windows(2).all(|pair| pair[0].timestamp_ms <= pair[1].timestamp_ms));
+/// assert!(log.iter().all(|entry| entry.message().is_ok()));
+/// ```
+pub fn day_log(start_ms: u64, pistes: usize, bouts_per_piste: usize) -> Vec<LogEntry> {
+    let mut entries = Vec::new();
+
+    for index in 0..pistes {
+        let piste = (index + 1).to_string();
+        let mut at_ms = start_ms + index as u64 * PISTE_STAGGER_MS;
+        entries.push(LogEntry::received(
+            at_ms,
+            Message::new(Command::Hello, piste.as_str(), COMPETITION_ID).to_string(),
+        ));
+
+        for bout in 0..bouts_per_piste {
+            let number = (index * bouts_per_piste + bout) as u64;
+            let frames = bout_script(&piste, number).compile().unwrap_or_default();
+            let bout_start_ms = at_ms + CHANGEOVER_MS;
+            for frame in &frames {
+                at_ms = bout_start_ms + frame.at_ms;
+                entries.push(LogEntry::received(at_ms, frame.message.to_string()));
+            }
+        }
+    }
+
+    entries.sort_by_key(|entry| entry.timestamp_ms);
+    entries
+}
+
+/// Returns [`day_log`] as the text of a log file, one entry per line.
+pub fn day_log_text(start_ms: u64, pistes: usize, bouts_per_piste: usize) -> String {
+    let mut text = String::new();
+    for entry in day_log(start_ms, pistes, bouts_per_piste) {
+        text.push_str(&entry.to_string());
+        text.push('\n');
+    }
+    text
+}
+
+/// Returns `count` frames for seeding fuzzers or load-testing consumers.
+///
+/// Frames are drawn from generated bouts and from the valid corpus frames;
+/// the same seed always gives the same frames. A share `invalid_ratio` of
+/// them, between 0 and 1, is mutated (truncated, stripped of a separator,
+/// given an unknown command or an out-of-range value...) so that it is
+/// rejected, or at best parsed leniently.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::fixtures;
+/// use cyrano::message::Message;
+///
+/// let frames = fixtures::random_frames(42, 100, 0.0);
+/// assert_eq!(frames, fixtures::random_frames(42, 100, 0.0));
+/// assert!(frames.iter().all(|frame| Message::try_from(frame.as_str()).is_ok()));
+/// ```
+pub fn random_frames(seed: u64, count: usize, invalid_ratio: f64) -> Vec<String> {
+    let mut rng = Rng::new(seed);
+    let corpus: Vec<&str> = valid_frames().collect();
+
+    (0..count)
+        .map(|_| {
+            let frame = if rng.below(4) == 0 {
+                corpus[rng.below(corpus.len() as u64) as usize].to_string()
+            } else {
+                let piste = (1 + rng.below(40)).to_string();
+                let timeline = bout_timeline(&piste, rng.below(1000));
+                timeline[rng.below(timeline.len() as u64) as usize].to_string()
+            };
+            if rng.chance(invalid_ratio) {
+                mutate(&frame, &mut rng)
+            } else {
+                frame
+            }
+        })
+        .collect()
+}
+
+/// Damages a frame in one of a few ways that break real parsers.
+fn mutate(frame: &str, rng: &mut Rng) -> String {
+    let separators: Vec<usize> = frame.match_indices(['|', '%']).map(|(index, _)| index).collect();
+    let at = separators[rng.below(separators.len() as u64) as usize];
+
+    match rng.below(5) {
+        0 => frame[..at].to_string(),
+        1 => format!("{}{}", &frame[..at], &frame[at + 1..]),
+        2 => frame.replacen(frame.split('|').nth(2).unwrap_or_default(), "XYZZY", 1),
+        3 => {
+            let long = "X".repeat(300);
+            let garbage = ["-1", "999999", "\u{0}", "é%|", &long][rng.below(5) as usize];
+            format!("{}{}{}", &frame[..=at], garbage, &frame[at + 1..])
+        }
+        _ => frame.replace('|', "||"),
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::FencerStatus;
+    use crate::logfile;
+
+    #[test]
+    fn test_mutated_random_frames() {
+        let frames = random_frames(7, 500, 1.0);
+        let rejected = frames.iter().filter(|frame| Message::try_from(frame.as_str()).is_err()).count();
+        assert!(rejected > 150, "{} rejected", rejected);
+        assert_ne!(frames, random_frames(8, 500, 1.0));
+    }
+
+    #[test]
+    fn test_generated_bouts_finish() {
+        for number in 0..50 {
+            let timeline = bout_timeline("1", number);
+            let last = timeline.last().unwrap();
+            let scores = [last.right_fencer.score.unwrap(), last.left_fencer.score.unwrap()];
+            assert!(scores.contains(&15) || last.time.as_deref() == Some("0:00"), "bout {}", number);
+            if scores[0] != scores[1] {
+                let statuses = [&last.right_fencer.status, &last.left_fencer.status];
+                assert!(statuses.contains(&&Some(FencerStatus::Victory)), "bout {}", number);
+            }
+        }
+    }
+
+    #[test]
+    fn test_day_log_text_reads_back() {
+        let text = day_log_text(1_700_000_000_000, 3, 4);
+        let messages = logfile::read(text.as_bytes()).messages().count();
+        assert_eq!(messages, day_log(1_700_000_000_000, 3, 4).len());
+        assert!(valid_frames().count() > 0);
+    }
+}
@@ -0,0 +1,304 @@
+use async_graphql::{Context, EmptyMutation, Json, Object, Schema, SimpleObject, Subscription};
+use async_graphql_axum::{GraphQL, GraphQLSubscription};
+use axum::Router;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+use super::competition::BoutResult;
+use super::fencer::Fencer;
+use super::http::{HttpBridge, PisteEvent};
+use super::state::{MatchEvent, MatchState};
+
+/// GraphQL schema served by the [`HttpBridge`].
+pub type BridgeSchema = Schema<QueryRoot, EmptyMutation, SubscriptionRoot>;
+
+/// A fencer of a bout. Enumerated values are given by their protocol code.
+#[derive(Debug, Clone, PartialEq, SimpleObject)]
+#[graphql(name = "Fencer")]
+pub struct FencerObject {
+    /// Fencer identifier.
+    pub id: Option<String>,
+    /// Fencer name.
+    pub name: Option<String>,
+    /// Nation code.
+    pub nation: Option<String>,
+    /// Current score.
+    pub score: Option<u8>,
+    /// Status code, such as `V` for a victory.
+    pub status: Option<String>,
+    /// Number of yellow cards.
+    pub yellow_card: Option<u8>,
+    /// Number of red cards.
+    pub red_card: Option<u8>,
+    /// Whether the scoring light is on.
+    pub light: Option<bool>,
+    /// Whether the white light is on.
+    pub white_light: Option<bool>,
+}
+
+impl From<&Fencer> for FencerObject {
+    fn from(fencer: &Fencer) -> Self {
+        FencerObject {
+            id: fencer.id.as_deref().map(String::from),
+            name: fencer.name.as_deref().map(String::from),
+            nation: fencer.nation.as_deref().map(String::from),
+            score: fencer.score,
+            status: fencer.status.as_ref().map(ToString::to_string),
+            yellow_card: fencer.yellow_card,
+            red_card: fencer.red_card,
+            light: fencer.light,
+            white_light: fencer.white_light,
+        }
+    }
+}
+
+/// The bout on a piste. Enumerated values are given by their protocol code.
+#[derive(Debug, Clone, PartialEq, SimpleObject)]
+#[graphql(name = "Piste")]
+pub struct PisteObject {
+    /// Piste identifier.
+    pub id: String,
+    /// Competition identifier of the bout.
+    pub competition_id: Option<String>,
+    /// Competition phase number.
+    pub phase: Option<u8>,
+    /// Pool or tableau identifier.
+    pub pool_tableau: Option<String>,
+    /// Match number within the competition.
+    pub match_number: Option<u8>,
+    /// Weapon code, such as `E` for épée.
+    pub weapon: Option<String>,
+    /// Apparatus state code, such as `F` while fencing.
+    pub state: Option<String>,
+    /// Bout clock.
+    pub time: Option<String>,
+    /// Current period.
+    pub period: Option<u8>,
+    /// Winning side, `right` or `left`, once decided.
+    pub winner: Option<String>,
+    /// Fencer on the right.
+    pub right_fencer: Option<FencerObject>,
+    /// Fencer on the left.
+    pub left_fencer: Option<FencerObject>,
+}
+
+impl From<&MatchState> for PisteObject {
+    fn from(state: &MatchState) -> Self {
+        let message = state.message();
+        PisteObject {
+            id: state.piste().to_string(),
+            competition_id: state.competition_id().map(String::from),
+            phase: message.and_then(|m| m.phase),
+            pool_tableau: message.and_then(|m| m.pool_tableau.as_deref()).map(String::from),
+            match_number: message.and_then(|m| m.match_number),
+            weapon: message.and_then(|m| m.weapon.as_ref()).map(ToString::to_string),
+            state: state.state().map(ToString::to_string),
+            time: message.and_then(|m| m.time.as_deref()).map(String::from),
+            period: state.current_period(),
+            winner: state.winner().map(|side| side.to_string()),
+            right_fencer: message.map(|m| FencerObject::from(&m.right_fencer)),
+            left_fencer: message.map(|m| FencerObject::from(&m.left_fencer)),
+        }
+    }
+}
+
+/// Final result of a bout.
+#[derive(Debug, Clone, PartialEq, SimpleObject)]
+#[graphql(name = "BoutResult")]
+pub struct ResultObject {
+    /// Competition identifier.
+    pub competition_id: String,
+    /// Piste the bout was fenced on.
+    pub piste: String,
+    /// Competition phase number.
+    pub phase: Option<u8>,
+    /// Pool or tableau identifier.
+    pub pool_tableau: Option<String>,
+    /// Match number within the competition.
+    pub match_number: Option<u8>,
+    /// Final state of the fencer on the right.
+    pub right_fencer: FencerObject,
+    /// Final state of the fencer on the left.
+    pub left_fencer: FencerObject,
+}
+
+impl From<&BoutResult> for ResultObject {
+    fn from(result: &BoutResult) -> Self {
+        ResultObject {
+            competition_id: result.competition_id.clone(),
+            piste: result.piste.clone(),
+            phase: result.phase,
+            pool_tableau: result.pool_tableau.clone(),
+            match_number: result.match_number,
+            right_fencer: FencerObject::from(&result.right_fencer),
+            left_fencer: FencerObject::from(&result.left_fencer),
+        }
+    }
+}
+
+/// A change on a piste, pushed to subscribers.
+#[derive(Debug, Clone, PartialEq, SimpleObject)]
+#[graphql(name = "PisteEvent")]
+pub struct EventObject {
+    /// Piste identifier.
+    pub piste: String,
+    /// What changed, as serialized by serde.
+    pub event: Json<MatchEvent>,
+}
+
+impl From<PisteEvent> for EventObject {
+    fn from(event: PisteEvent) -> Self {
+        EventObject {
+            piste: event.piste,
+            event: Json(event.event),
+        }
+    }
+}
+
+/// Queries over the pistes, bouts and results of the bridge.
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// State of every piste, in natural piste order.
+    async fn pistes(&self, ctx: &Context<'_>) -> Vec<PisteObject> {
+        bridge(ctx).snapshot().iter().map(PisteObject::from).collect()
+    }
+
+    /// State of one piste.
+    async fn piste(&self, ctx: &Context<'_>, id: String) -> Option<PisteObject> {
+        bridge(ctx).piste(&id).as_ref().map(PisteObject::from)
+    }
+
+    /// Identifiers of the competitions seen, sorted.
+    async fn competitions(&self, ctx: &Context<'_>) -> Vec<String> {
+        bridge(ctx).competitions()
+    }
+
+    /// Current or last bout of every piste of a competition.
+    async fn bouts(&self, ctx: &Context<'_>, competition_id: String) -> Vec<PisteObject> {
+        bridge(ctx)
+            .competition_pistes(&competition_id)
+            .iter()
+            .map(PisteObject::from)
+            .collect()
+    }
+
+    /// Results recorded for a phase, optionally restricted to one pool or tableau.
+    async fn results(
+        &self,
+        ctx: &Context<'_>,
+        competition_id: String,
+        phase: u8,
+        pool_tableau: Option<String>,
+    ) -> Vec<ResultObject> {
+        bridge(ctx)
+            .results(&competition_id, phase, pool_tableau.as_deref())
+            .iter()
+            .map(ResultObject::from)
+            .collect()
+    }
+}
+
+/// Subscriptions to the live events of the bridge.
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Changes applied from now on, optionally on one piste only. Subscribers
+    /// lagging behind skip the missed events.
+    async fn events(&self, ctx: &Context<'_>, piste: Option<String>) -> impl Stream<Item = EventObject> {
+        BroadcastStream::new(bridge(ctx).subscribe())
+            .filter_map(|event| event.ok())
+            .filter(move |event| piste.as_ref().is_none_or(|piste| *piste == event.piste))
+            .map(EventObject::from)
+    }
+}
+
+fn bridge<'a>(ctx: &Context<'a>) -> &'a HttpBridge {
+    // The schema is always built with the bridge as data, by `schema`.
+    ctx.data_unchecked::<HttpBridge>()
+}
+
+/// Builds the GraphQL schema over a bridge, to be served with another HTTP
+/// stack than the one of [`router`].
+pub fn schema(bridge: &HttpBridge) -> BridgeSchema {
+    Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+        .data(bridge.clone())
+        .finish()
+}
+
+/// Returns the axum router serving GraphQL queries on `/graphql` and
+/// subscriptions over WebSocket on `/graphql/ws`.
+///
+/// [`HttpBridge::router`] already includes these routes.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use async_graphql::futures_util::FutureExt;
+/// use cyrano::graphql;
+/// use cyrano::http::HttpBridge;
+/// use cyrano::message::Message;
+///
+/// let bridge = HttpBridge::new();
+/// bridge.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+///
+/// let query = "{ pistes { id weapon rightFencer { name score } } }";
+/// let response = graphql::schema(&bridge).execute(query).now_or_never().unwrap();
+/// assert!(response.errors.is_empty());
+/// ```
+pub fn router(bridge: &HttpBridge) -> Router {
+    let schema = schema(bridge);
+    Router::new()
+        .route_service("/graphql", GraphQL::new(schema.clone()))
+        .route_service("/graphql/ws", GraphQLSubscription::new(schema))
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::futures_util::FutureExt;
+    use crate::message::Message;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_queries_and_subscription() {
+        let bridge = HttpBridge::new();
+        let schema = schema(&bridge);
+        let mut events = schema.execute_stream(r#"subscription { events(piste: "3") { piste event } }"#);
+        // The subscription starts listening when first polled.
+        assert!(events.next().now_or_never().is_none());
+
+        bridge.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|4|U|%|").unwrap());
+        bridge.apply(&Message::try_from("|EFP1.1|INFO|8|fm-eq|1|P4|2|||||E||F|%|11|A|FRA|0|U|%|12|B|ITA|0|U|%|").unwrap());
+        bridge.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1|||||E||E|%|28|P.Martin|FRA|5|V|%|32|B. Panini|ITA|4|D|%|").unwrap());
+
+        let query = r#"{
+            piste(id: "3") { weapon state winner rightFencer { name score status } }
+            competitions
+            bouts(competitionId: "fm-eq") { id }
+            results(competitionId: "fm-eq", phase: 1, poolTableau: "P3") { piste leftFencer { score } }
+        }"#;
+        let response = schema.execute(query).now_or_never().unwrap();
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["piste"]["winner"], "right");
+        assert_eq!(data["piste"]["rightFencer"]["status"], "V");
+        assert_eq!(data["competitions"][0], "fm-eq");
+        assert_eq!(data["bouts"].as_array().unwrap().len(), 2);
+        assert_eq!(data["results"][0]["leftFencer"]["score"], 4);
+
+        // Only the events of piste 3 are pushed, the first being its bout start.
+        let mut pushed = Vec::new();
+        while let Some(Some(response)) = events.next().now_or_never() {
+            pushed.push(response.data.into_json().unwrap());
+        }
+        assert!(pushed.len() > 1);
+        assert_eq!(pushed[0]["events"]["event"], "BoutStarted");
+        assert!(pushed.iter().all(|event| event["events"]["piste"] == "3"));
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::merge::MergedMessage;
+use crate::message::Message;
+use crate::piste::PisteId;
+use crate::state::{MatchEvent, MatchState};
+
+/// An element of a message collection: a message, possibly with the time it
+/// was received.
+///
+/// Implemented for bare messages, `(timestamp_ms, message)` pairs as read from
+/// logs and replays, and [`MergedMessage`]s.
+pub trait CollectedMessage {
+    /// Returns the message.
+    fn message(&self) -> &Message;
+
+    /// Returns the time the message was received, in milliseconds.
+    fn timestamp_ms(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl CollectedMessage for Message {
+    fn message(&self) -> &Message {
+        self
+    }
+}
+
+impl CollectedMessage for (u64, Message) {
+    fn message(&self) -> &Message {
+        &self.1
+    }
+
+    fn timestamp_ms(&self) -> Option<u64> {
+        Some(self.0)
+    }
+}
+
+impl CollectedMessage for MergedMessage {
+    fn message(&self) -> &Message {
+        &self.message
+    }
+
+    fn timestamp_ms(&self) -> Option<u64> {
+        Some(self.timestamp_ms)
+    }
+}
+
+/// Phase of a competition: the phase number and the pool or tableau.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhaseKey {
+    /// Competition identifier.
+    pub competition_id: String,
+    /// Phase number.
+    pub phase: Option<u8>,
+    /// Pool or tableau identifier.
+    pub pool_tableau: Option<String>,
+}
+
+impl PhaseKey {
+    /// Returns the phase of a message.
+    pub fn of(message: &Message) -> Self {
+        PhaseKey {
+            competition_id: message.competition_id.to_string(),
+            phase: message.phase,
+            pool_tableau: message.pool_tableau.as_ref().map(|pool| pool.to_string()),
+        }
+    }
+}
+
+/// Identity of a bout: its phase, match and round numbers and its fencers,
+/// the fields that tell bouts apart in the state tracker.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoutKey {
+    /// Phase of the bout.
+    pub phase: PhaseKey,
+    /// Match number within the competition.
+    pub match_number: Option<u8>,
+    /// Round number.
+    pub round: Option<u8>,
+    /// Identifier of the right fencer.
+    pub right_id: Option<String>,
+    /// Identifier of the left fencer.
+    pub left_id: Option<String>,
+}
+
+impl BoutKey {
+    /// Returns the bout of a message.
+    pub fn of(message: &Message) -> Self {
+        BoutKey {
+            phase: PhaseKey::of(message),
+            match_number: message.match_number,
+            round: message.round,
+            right_id: message.right_fencer.id.as_ref().map(|id| id.to_string()),
+            left_id: message.left_fencer.id.as_ref().map(|id| id.to_string()),
+        }
+    }
+}
+
+/// Sorts messages by receipt time. The sort is stable, and messages without
+/// a time come first.
+pub fn sort_by_time<T: CollectedMessage>(items: &mut [T]) {
+    items.sort_by_key(|item| item.timestamp_ms());
+}
+
+/// Sorts messages by piste, in natural piste order, then by receipt time.
+/// The sort is stable.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::enums::Command;
+/// use cyrano::group::sort_by_piste;
+/// use cyrano::message::Message;
+///
+/// let mut log = vec![
+///     (300, Message::new(Command::Hello, "10", "fm-eq")),
+///     (200, Message::new(Command::Hello, "2", "fm-eq")),
+///     (100, Message::new(Command::Hello, "10", "fm-eq")),
+/// ];
+/// sort_by_piste(&mut log);
+/// assert_eq!(log.iter().map(|(time, _)| *time).collect::<Vec<_>>(), [200, 100, 300]);
+/// ```
+pub fn sort_by_piste<T: CollectedMessage>(items: &mut [T]) {
+    items.sort_by_cached_key(|item| (PisteId::from(item.message().piste.as_str()), item.timestamp_ms()));
+}
+
+/// Groups messages by piste, keeping their order within each piste.
+pub fn group_by_piste<T: CollectedMessage>(items: &[T]) -> BTreeMap<PisteId, Vec<&T>> {
+    group_by(items, |message| Some(PisteId::from(message.piste.as_str())))
+}
+
+/// Groups messages by competition, keeping their order within each competition.
+pub fn group_by_competition<T: CollectedMessage>(items: &[T]) -> BTreeMap<String, Vec<&T>> {
+    group_by(items, |message| Some(message.competition_id.to_string()))
+}
+
+/// Groups the bout messages (INFO, DISP, NEXT and PREV) by phase, keeping
+/// their order within each phase. Other messages are left out.
+pub fn group_by_phase<T: CollectedMessage>(items: &[T]) -> BTreeMap<PhaseKey, Vec<&T>> {
+    group_by(items, |message| message.carries_bout().then(|| PhaseKey::of(message)))
+}
+
+/// Groups the bout messages (INFO, DISP, NEXT and PREV) by bout, keeping
+/// their order within each bout. Other messages are left out.
+///
+/// A bout loaded twice on a piste, or on two pistes, forms a single group;
+/// see [`split_bouts`] to keep each run apart.
+pub fn group_by_bout<T: CollectedMessage>(items: &[T]) -> BTreeMap<BoutKey, Vec<&T>> {
+    group_by(items, |message| message.carries_bout().then(|| BoutKey::of(message)))
+}
+
+fn group_by<T: CollectedMessage, K: Ord>(items: &[T], key: impl Fn(&Message) -> Option<K>) -> BTreeMap<K, Vec<&T>> {
+    let mut groups: BTreeMap<K, Vec<&T>> = BTreeMap::new();
+    for item in items {
+        if let Some(key) = key(item.message()) {
+            groups.entry(key).or_default().push(item);
+        }
+    }
+    groups
+}
+
+/// The messages of one bout on one piste, from the message loading it to the
+/// message loading the next bout on the piste.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoutSegment<'a, T> {
+    /// Piste of the bout.
+    pub piste: PisteId,
+    /// Identity of the bout.
+    pub bout: BoutKey,
+    /// Messages of the piste during the bout, bout messages and others such
+    /// as ACK, in their original order.
+    pub messages: Vec<&'a T>,
+    /// Whether the bout reached a final status before the next one was loaded.
+    pub finished: bool,
+}
+
+impl<T: CollectedMessage> BoutSegment<'_, T> {
+    /// Returns the receipt time of the first and last messages of the segment.
+    pub fn span_ms(&self) -> Option<(u64, u64)> {
+        let mut times = self.messages.iter().filter_map(|item| item.timestamp_ms());
+        let first = times.next()?;
+        Some((first, times.next_back().unwrap_or(first)))
+    }
+}
+
+/// Slices a log into per-bout segments, following the bout changes reported
+/// by a [`MatchState`] per piste.
+///
+/// The messages are expected in receipt order, such as a day's log or the
+/// output of a [`Merge`](crate::merge::Merge). Messages of a piste before its
+/// first bout are left out.
+///
+/// # Returns
+///
+/// The segments, in the order their bout was loaded.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::group::split_bouts;
+/// use cyrano::message::Message;
+///
+/// let log: Vec<(u64, Message)> = [
+///     (100, "|EFP1.1|INFO|3|fm-eq|1|P3|1||3:00|||E||W|%|28|P.Martin|FRA|0|U|%|32|B. Panini|ITA|0|U|%|"),
+///     (200, "|EFP1.1|INFO|4|fm-eq|1|P4|1||3:00|||E||W|%|12|J.Smith|GBR|0|U|%|13|K.Weber|GER|0|U|%|"),
+///     (300, "|EFP1.1|INFO|3|fm-eq|1|P3|1||0:10|||E||E|%|28|P.Martin|FRA|5|V|%|32|B. Panini|ITA|2|D|%|"),
+///     (400, "|EFP1.1|INFO|3|fm-eq|1|P3|2||3:00|||E||W|%|28|P.Martin|FRA|0|U|%|40|L.Rossi|ITA|0|U|%|"),
+/// ]
+/// .iter()
+/// .map(|(time, raw)| (*time, Message::try_from(*raw).unwrap()))
+/// .collect();
+///
+/// let bouts = split_bouts(&log);
+/// assert_eq!(bouts.len(), 3);
+/// assert_eq!((bouts[0].piste.as_str(), bouts[0].span_ms(), bouts[0].finished), ("3", Some((100, 300)), true));
+/// assert_eq!(bouts[2].bout.left_id.as_deref(), Some("40"));
+/// ```
+pub fn split_bouts<T: CollectedMessage>(items: &[T]) -> Vec<BoutSegment<'_, T>> {
+    let mut segments: Vec<BoutSegment<'_, T>> = Vec::new();
+    let mut pistes: HashMap<PisteId, (MatchState, Option<usize>)> = HashMap::new();
+
+    for item in items {
+        let message = item.message();
+        let piste = PisteId::from(message.piste.as_str());
+        let (state, current) = pistes
+            .entry(piste.clone())
+            .or_insert_with(|| (MatchState::new(message.piste.as_str()), None));
+
+        let events = state.apply(message);
+        if events.contains(&MatchEvent::BoutStarted) {
+            *current = Some(segments.len());
+            segments.push(BoutSegment {
+                piste,
+                bout: BoutKey::of(message),
+                messages: Vec::new(),
+                finished: false,
+            });
+        }
+
+        if let Some(segment) = current.map(|index| &mut segments[index]) {
+            segment.messages.push(item);
+            if events.contains(&MatchEvent::BoutFinished) {
+                segment.finished = true;
+            }
+        }
+    }
+
+    segments
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::Command;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_group_and_split_a_log() {
+        let info = |piste: &str, pool: &str, right: &str, score: u8| {
+            let raw = format!("|EFP1.1|INFO|{}|fm-eq|1|{}|1||3:00|||E||H|%|{}|A|FRA|{}|U|%|32|B|ITA|0|U|%|", piste, pool, right, score);
+            Message::try_from(raw).unwrap()
+        };
+        let log = vec![
+            Message::new(Command::Hello, "2", "fm-eq"),
+            info("2", "P1", "28", 0),
+            Message::new(Command::Ack, "2", "fm-eq"),
+            info("10", "P2", "29", 0),
+            info("2", "P1", "28", 1),
+            info("2", "P1", "30", 0),
+            info("2", "P1", "28", 2),
+        ];
+
+        assert_eq!(group_by_piste(&log).keys().map(PisteId::as_str).collect::<Vec<_>>(), ["2", "10"]);
+        assert_eq!(group_by_competition(&log)["fm-eq"].len(), 7);
+        let phases = group_by_phase(&log);
+        assert_eq!(phases.values().map(Vec::len).collect::<Vec<_>>(), [4, 1]);
+
+        // The bout reloaded on piste 2 is one group but two segments.
+        let bouts = group_by_bout(&log);
+        assert_eq!(bouts.len(), 3);
+        assert_eq!(bouts[&BoutKey::of(&log[1])].len(), 3);
+
+        let segments = split_bouts(&log);
+        let sizes: Vec<(&str, usize)> = segments.iter().map(|s| (s.piste.as_str(), s.messages.len())).collect();
+        assert_eq!(sizes, [("2", 3), ("10", 1), ("2", 1), ("2", 1)]);
+        assert_eq!(segments[0].span_ms(), None);
+    }
+}
@@ -0,0 +1,170 @@
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+use crate::borrowed::MessageRef;
+use crate::enums::Command;
+use crate::error::ParseError;
+use crate::message::PROTOCOL_VERSION;
+
+/// Payload of a HELLO: who is at the other end of the link.
+///
+/// A HELLO only names the piste and the competition, optionally followed by
+/// the identifier and firmware version of the apparatus in the next general
+/// fields. It carries no bout field and no fencer zone; the parser ignores
+/// the former and rejects the latter in strict mode.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::hello::Hello;
+///
+/// let hello = Hello::new("17", "fm-eq").apparatus("SG-12").firmware("2.4.1");
+/// assert_eq!(hello.to_string(), "|EFP1.1|HELLO|17|fm-eq|SG-12|2.4.1|%||%||%|");
+/// assert_eq!(Hello::try_from(hello.to_string().as_str()).unwrap(), hello);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hello {
+    /// Protocol version (e.g., "EFP1.1" or "EFP1").
+    pub protocol: String,
+    /// Piste (strip) identifier.
+    pub piste: String,
+    /// Competition identifier.
+    pub competition_id: String,
+    /// Identifier of the apparatus, such as its model or serial number.
+    pub apparatus: Option<String>,
+    /// Firmware version of the apparatus.
+    pub firmware: Option<String>,
+}
+
+impl Hello {
+    /// Creates a HELLO for a piste and competition, without apparatus identity.
+    pub fn new(piste: impl Into<String>, competition_id: impl Into<String>) -> Self {
+        Hello {
+            protocol: PROTOCOL_VERSION.to_string(),
+            piste: piste.into(),
+            competition_id: competition_id.into(),
+            apparatus: None,
+            firmware: None,
+        }
+    }
+
+    /// Sets the identifier of the apparatus.
+    pub fn apparatus(mut self, apparatus: impl Into<String>) -> Self {
+        self.apparatus = Some(apparatus.into());
+        self
+    }
+
+    /// Sets the firmware version of the apparatus.
+    pub fn firmware(mut self, firmware: impl Into<String>) -> Self {
+        self.firmware = Some(firmware.into());
+        self
+    }
+
+    fn from_view(view: &MessageRef<'_>) -> Self {
+        let fields = view.raw_general_fields();
+        let identity = |index: usize| fields.get(index).filter(|value| !value.is_empty()).map(|value| value.to_string());
+        Hello {
+            protocol: view.protocol.to_string(),
+            piste: view.piste.to_string(),
+            competition_id: view.competition_id.to_string(),
+            apparatus: identity(4),
+            firmware: identity(5),
+        }
+    }
+}
+
+impl Display for Hello {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "|{}|HELLO|{}|{}|", self.protocol, self.piste, self.competition_id)?;
+        if self.apparatus.is_some() || self.firmware.is_some() {
+            write!(
+                f,
+                "{}|{}|",
+                self.apparatus.as_deref().unwrap_or_default(),
+                self.firmware.as_deref().unwrap_or_default()
+            )?;
+        }
+        write!(f, "%||%||%|")
+    }
+}
+
+impl TryFrom<&str> for Hello {
+    type Error = ParseError;
+
+    /// Parses a HELLO frame.
+    ///
+    /// Returns `ParseError::InvalidCommand` for a frame of another command.
+    fn try_from(raw: &str) -> Result<Self, Self::Error> {
+        let view = MessageRef::try_from(raw)?;
+        match view.kind() {
+            MessageKind::Hello(hello) => Ok(hello),
+            _ => Err(ParseError::InvalidCommand(view.command.to_string())),
+        }
+    }
+}
+
+/// What a message is about, with the payload specific to its command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageKind {
+    /// Handshake, with the identity of the sender.
+    Hello(Hello),
+    /// Acknowledgment of the last message calling for one.
+    Ack,
+    /// Rejection of the last message calling for an acknowledgment.
+    Nak,
+    /// INFO, DISP, NEXT or PREV: the bout fields of the message apply.
+    Bout(Command),
+}
+
+impl MessageRef<'_> {
+    /// Returns the kind of the message, with its command-specific payload.
+    ///
+    /// The apparatus identity of a HELLO is only available here: [`Message`](crate::message::Message)
+    /// has no field for it.
+    pub fn kind(&self) -> MessageKind {
+        match self.command {
+            Command::Hello => MessageKind::Hello(Hello::from_view(self)),
+            Command::Ack => MessageKind::Ack,
+            Command::Nak => MessageKind::Nak,
+            ref command => MessageKind::Bout(command.clone()),
+        }
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::Zone;
+    use crate::error::WarningKind;
+    use crate::message::Message;
+    use crate::options::ParseOptions;
+
+    #[test]
+    fn test_hello_carries_no_bout_data() {
+        let strict = ParseOptions::strict();
+
+        // Identity fields are not mistaken for a phase and a pool in strict mode.
+        let raw = "|EFP1.1|HELLO|17|fm-eq|SG-12|2.4.1|%|";
+        let message = Message::parse_with(raw, &strict).unwrap();
+        assert_eq!((message.phase, message.pool_tableau), (None, None));
+        assert_eq!(Hello::try_from(raw).unwrap().apparatus.as_deref(), Some("SG-12"));
+        assert_eq!(Hello::try_from("|EFP1|HELLO|3|%|").unwrap().firmware, None);
+
+        let raw = "|EFP1.1|HELLO|17|fm-eq|%|28|P.Martin|FRA|%|%|";
+        assert!(matches!(
+            Message::parse_with(raw, &strict),
+            Err(ParseError::UnexpectedZone { zone: Zone::RightFencer, .. })
+        ));
+        let (message, warnings) = Message::parse_with_warnings(raw, &ParseOptions::default()).unwrap();
+        assert_eq!(message.right_fencer.name, None);
+        assert_eq!(warnings[0].kind, WarningKind::FieldInvalid("28|P.Martin|FRA".to_string()));
+
+        let ack = MessageRef::try_from("|EFP1.1|ACK|17|fm-eq|%|").unwrap();
+        assert_eq!(ack.kind(), MessageKind::Ack);
+        assert!(matches!(Hello::try_from("|EFP1.1|INFO|17|fm-eq|%|"), Err(ParseError::InvalidCommand(_))));
+    }
+}
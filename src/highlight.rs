@@ -0,0 +1,225 @@
+use crate::enums::{ApparatusState, Side};
+use crate::phase::TEAM_RELAYS;
+use crate::state::{MatchEvent, MatchState};
+use crate::utils::clock_seconds;
+
+/// Default deficit a fencer must erase for a comeback, in touches.
+pub const DEFAULT_COMEBACK_DEFICIT: u8 = 3;
+
+/// Default length of the final stretch of a period, in seconds.
+pub const DEFAULT_FINAL_SECONDS: u32 = 30;
+
+/// Moment of a bout worth an animation on a broadcast.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Highlight {
+    /// A fencer is one touch from winning the bout (or the team match).
+    MatchPoint { side: Side, score: u8 },
+    /// A fencer erased a deficit of at least the configured size.
+    Comeback { side: Side, deficit: u8 },
+    /// Both fencers are one touch from winning, such as 14-14 in direct elimination.
+    DecidingTouch { score: u8 },
+    /// The running clock entered the final seconds of the period.
+    FinalSeconds { seconds: u32 },
+}
+
+/// Derives broadcast highlights from the events of a piste.
+///
+/// Feed the detector the state of a piste with the events each message
+/// produced. Each highlight fires once when its condition becomes true;
+/// every kind can be turned off.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Side;
+/// use cyrano::highlight::{Highlight, HighlightDetector};
+/// use cyrano::message::Message;
+/// use cyrano::state::MatchState;
+///
+/// let info = |right: u8, left: u8| {
+///     Message::try_from(format!("|EFP1.1|INFO|17|fm-eq|1|A32|12|||I|E||H|%|28|P.Martin|FRA|{}|U|%|32|B. Panini|ITA|{}|U|%|", right, left)).unwrap()
+/// };
+///
+/// let mut state = MatchState::new("17");
+/// let mut detector = HighlightDetector::new().final_seconds(None);
+/// let events = state.apply(&info(13, 14));
+/// detector.observe(&state, &events);
+///
+/// let events = state.apply(&info(14, 14));
+/// assert_eq!(
+///     detector.observe(&state, &events),
+///     vec![Highlight::MatchPoint { side: Side::Right, score: 14 }, Highlight::DecidingTouch { score: 14 }]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct HighlightDetector {
+    match_point: bool,
+    deciding_touch: bool,
+    comeback_deficit: Option<u8>,
+    final_seconds: Option<u32>,
+    deficits: [u8; 2],
+    in_final_seconds: bool,
+}
+
+impl Default for HighlightDetector {
+    fn default() -> Self {
+        HighlightDetector::new()
+    }
+}
+
+impl HighlightDetector {
+    /// Creates a detector with every highlight enabled and the default thresholds.
+    pub fn new() -> Self {
+        HighlightDetector {
+            match_point: true,
+            deciding_touch: true,
+            comeback_deficit: Some(DEFAULT_COMEBACK_DEFICIT),
+            final_seconds: Some(DEFAULT_FINAL_SECONDS),
+            deficits: [0, 0],
+            in_final_seconds: false,
+        }
+    }
+
+    /// Enables or disables match point highlights.
+    pub fn match_point(mut self, enabled: bool) -> Self {
+        self.match_point = enabled;
+        self
+    }
+
+    /// Enables or disables deciding touch highlights.
+    pub fn deciding_touch(mut self, enabled: bool) -> Self {
+        self.deciding_touch = enabled;
+        self
+    }
+
+    /// Sets the deficit a comeback must erase, or `None` to disable comebacks.
+    pub fn comeback_deficit(mut self, deficit: Option<u8>) -> Self {
+        self.comeback_deficit = deficit;
+        self
+    }
+
+    /// Sets the length of the final stretch of a period in seconds, or `None`
+    /// to disable final seconds highlights.
+    pub fn final_seconds(mut self, seconds: Option<u32>) -> Self {
+        self.final_seconds = seconds;
+        self
+    }
+
+    /// Returns the highlights of the message that produced `events`.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state of the piste, after the message was applied
+    /// * `events` - The events returned by [`MatchState::apply`]
+    pub fn observe(&mut self, state: &MatchState, events: &[MatchEvent]) -> Vec<Highlight> {
+        if events.contains(&MatchEvent::BoutStarted) {
+            self.deficits = [0, 0];
+            self.in_final_seconds = false;
+        }
+
+        let mut highlights = Vec::new();
+        let scores = [state.score(Side::Right).unwrap_or(0), state.score(Side::Left).unwrap_or(0)];
+        let scored = events
+            .iter()
+            .any(|event| matches!(event, MatchEvent::ScoreChanged { .. } | MatchEvent::DoubleTouch { .. }));
+
+        // Team relays have their own targets; only the last one wins the match.
+        let target = state.target_score().filter(|_| state.relay().is_none_or(|relay| relay == TEAM_RELAYS));
+        if let Some(winning) = target.and_then(|target| target.checked_sub(1)) {
+            for event in events {
+                let scores = match event {
+                    MatchEvent::ScoreChanged { side, to: Some(score), .. } => vec![(*side, *score)],
+                    MatchEvent::DoubleTouch { right_score, left_score } => {
+                        vec![(Side::Right, *right_score), (Side::Left, *left_score)]
+                    }
+                    _ => Vec::new(),
+                };
+                for (side, score) in scores {
+                    if self.match_point && score == winning {
+                        highlights.push(Highlight::MatchPoint { side, score });
+                    }
+                }
+            }
+            if self.deciding_touch && scored && scores == [winning, winning] {
+                highlights.push(Highlight::DecidingTouch { score: winning });
+            }
+        }
+
+        if let Some(threshold) = self.comeback_deficit {
+            for (index, side) in [Side::Right, Side::Left].into_iter().enumerate() {
+                let (own, other) = (scores[index], scores[1 - index]);
+                self.deficits[index] = self.deficits[index].max(other.saturating_sub(own));
+                if scored && own >= other && self.deficits[index] >= threshold.max(1) {
+                    highlights.push(Highlight::Comeback {
+                        side,
+                        deficit: self.deficits[index],
+                    });
+                    self.deficits[index] = 0;
+                }
+            }
+        }
+
+        if let Some(threshold) = self.final_seconds {
+            let message = state.message();
+            let seconds = message.and_then(|m| m.time.as_deref()).and_then(clock_seconds);
+            let fencing = state.state() == Some(&ApparatusState::Fencing);
+            match seconds {
+                Some(seconds) if seconds > threshold => self.in_final_seconds = false,
+                Some(seconds) if fencing && seconds > 0 && !self.in_final_seconds => {
+                    self.in_final_seconds = true;
+                    highlights.push(Highlight::FinalSeconds { seconds });
+                }
+                _ => {}
+            }
+        }
+
+        highlights
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use std::convert::TryFrom;
+
+    fn info(state: &str, time: &str, right: u8, left: u8) -> Message {
+        let raw = format!(
+            "|EFP1.1|INFO|17|fm-eq|1|A32|12||{}|||E||{}|%|28|P.Martin|FRA|{}|U|%|32|B. Panini|ITA|{}|U|%|",
+            time, state, right, left
+        );
+        Message::try_from(raw).unwrap()
+    }
+
+    #[test]
+    fn test_comeback_and_final_seconds() {
+        let mut state = MatchState::new("17");
+        let mut detector = HighlightDetector::new();
+        let mut feed = |message: Message| {
+            let events = state.apply(&message);
+            detector.observe(&state, &events)
+        };
+
+        assert!(feed(info("F", "1:10", 2, 6)).is_empty());
+        assert!(feed(info("H", "0:50", 5, 6)).is_empty());
+        assert_eq!(feed(info("H", "0:40", 6, 6)), vec![Highlight::Comeback { side: Side::Right, deficit: 4 }]);
+        assert!(feed(info("H", "0:35", 7, 6)).is_empty());
+
+        assert_eq!(feed(info("F", "0:29", 7, 6)), vec![Highlight::FinalSeconds { seconds: 29 }]);
+        assert!(feed(info("F", "0:20", 7, 6)).is_empty());
+        // The clock set again for the next period arms the highlight again.
+        feed(info("H", "3:00", 7, 6));
+        assert_eq!(feed(info("F", "0:30", 7, 6)), vec![Highlight::FinalSeconds { seconds: 30 }]);
+
+        let mut quiet = HighlightDetector::new().comeback_deficit(None).match_point(false);
+        let mut state = MatchState::new("17");
+        for (right, left) in [(0, 3), (3, 3), (14, 13)] {
+            let events = state.apply(&info("H", "2:00", right, left));
+            assert!(quiet.observe(&state, &events).is_empty());
+        }
+    }
+}
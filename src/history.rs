@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+
+use crate::message::Message;
+
+/// Default number of changes a history keeps for undo.
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+/// A modification applied to a composed message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Description of the modification, as shown in an undo menu.
+    pub label: String,
+    /// The message before the modification.
+    pub before: Message,
+    /// The message after the modification.
+    pub after: Message,
+}
+
+/// Undo and redo of the modifications made to a message composed by a
+/// referee console.
+///
+/// Each modification is recorded with the message before and after it.
+/// Undoing or redoing returns the corrected message, to be sent again as the
+/// new INFO or DISP frame. Only the last [`DEFAULT_HISTORY_DEPTH`] changes are
+/// kept unless another depth is set.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::enums::{Command, Side};
+/// use cyrano::history::MessageHistory;
+/// use cyrano::message::Message;
+///
+/// let mut history = MessageHistory::new(Message::new(Command::Disp, "17", "fm-eq"));
+/// history.apply("Touch right", |m| m.fencer_mut(Side::Right).score = Some(1));
+/// history.apply("Touch right", |m| m.fencer_mut(Side::Right).score = Some(2));
+///
+/// // The second touch was given by mistake.
+/// let corrected = history.undo(1).unwrap();
+/// assert_eq!(corrected.right_fencer.score, Some(1));
+/// assert_eq!(history.redo_labels().collect::<Vec<_>>(), ["Touch right"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MessageHistory {
+    current: Message,
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    depth: usize,
+}
+
+impl MessageHistory {
+    /// Starts a history from the message currently shown.
+    pub fn new(message: Message) -> Self {
+        MessageHistory {
+            current: message,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            depth: DEFAULT_HISTORY_DEPTH,
+        }
+    }
+
+    /// Sets the number of changes kept for undo.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self.trim();
+        self
+    }
+
+    /// Returns the current message.
+    pub fn current(&self) -> &Message {
+        &self.current
+    }
+
+    /// Modifies the current message and records the change.
+    ///
+    /// A modification leaving the message unchanged is not recorded. Any
+    /// recorded change clears the redo list.
+    ///
+    /// # Returns
+    ///
+    /// The message to send.
+    pub fn apply(&mut self, label: impl Into<String>, modify: impl FnOnce(&mut Message)) -> &Message {
+        let mut message = self.current.clone();
+        modify(&mut message);
+        self.replace(label, message)
+    }
+
+    /// Replaces the current message, e.g. with one rebuilt by a
+    /// [`MessageBuilder`](crate::builder::MessageBuilder), and records the change.
+    ///
+    /// # Returns
+    ///
+    /// The message to send.
+    pub fn replace(&mut self, label: impl Into<String>, message: Message) -> &Message {
+        if message != self.current {
+            let before = std::mem::replace(&mut self.current, message);
+            self.undo.push_back(Change {
+                label: label.into(),
+                before,
+                after: self.current.clone(),
+            });
+            self.redo.clear();
+            self.trim();
+        }
+        &self.current
+    }
+
+    /// Rolls back the last `count` changes, or all of them if fewer were made.
+    ///
+    /// # Returns
+    ///
+    /// The corrected message to send, or `None` if there was nothing to undo.
+    pub fn undo(&mut self, count: usize) -> Option<&Message> {
+        let mut undone = false;
+        for _ in 0..count {
+            let Some(change) = self.undo.pop_back() else {
+                break;
+            };
+            self.current = change.before.clone();
+            self.redo.push(change);
+            undone = true;
+        }
+        undone.then_some(&self.current)
+    }
+
+    /// Applies again the last `count` undone changes, or all of them if fewer
+    /// were undone.
+    ///
+    /// # Returns
+    ///
+    /// The message to send, or `None` if there was nothing to redo.
+    pub fn redo(&mut self, count: usize) -> Option<&Message> {
+        let mut redone = false;
+        for _ in 0..count {
+            let Some(change) = self.redo.pop() else {
+                break;
+            };
+            self.current = change.after.clone();
+            self.undo.push_back(change);
+            redone = true;
+        }
+        redone.then_some(&self.current)
+    }
+
+    /// Returns the changes that can be undone, the most recent first.
+    pub fn changes(&self) -> impl Iterator<Item = &Change> {
+        self.undo.iter().rev()
+    }
+
+    /// Returns the labels of the changes that can be undone, the most recent first.
+    pub fn undo_labels(&self) -> impl Iterator<Item = &str> {
+        self.changes().map(|change| change.label.as_str())
+    }
+
+    /// Returns the labels of the changes that can be redone, the next one first.
+    pub fn redo_labels(&self) -> impl Iterator<Item = &str> {
+        self.redo.iter().rev().map(|change| change.label.as_str())
+    }
+
+    fn trim(&mut self) {
+        while self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::{Command, Side};
+    use crate::fencer::Fencer;
+
+    #[test]
+    fn test_undo_redo_regenerates_frames() {
+        let mut history = MessageHistory::new(Message::new(Command::Info, "3", "fm-eq")).depth(2);
+        history.apply("Touch left", |m| m.fencer_mut(Side::Left).score = Some(1));
+        history.apply("Yellow card right", |m| m.fencer_mut(Side::Right).yellow_card = Some(1));
+        history.apply("No change", |m| m.piste = "3".into());
+        history.apply("Touch left", |m| m.fencer_mut(Side::Left).score = Some(2));
+        assert_eq!(history.undo_labels().collect::<Vec<_>>(), ["Touch left", "Yellow card right"]);
+
+        // The first touch fell out of the history.
+        let corrected = history.undo(5).unwrap();
+        assert_eq!(corrected.left_fencer.score, Some(1));
+        assert_eq!(corrected.right_fencer.yellow_card, None);
+        assert!(corrected.to_string().starts_with("|EFP1.1|INFO|3|fm-eq|"));
+        assert_eq!(history.undo(1), None);
+
+        assert_eq!(history.redo(5).unwrap().left_fencer.score, Some(2));
+        assert_eq!(history.redo(1), None);
+
+        // A new change after an undo discards the redo list.
+        history.undo(1);
+        let mut rebuilt = history.current().clone();
+        rebuilt.left_fencer = Fencer {
+            score: Some(5),
+            ..Fencer::default()
+        };
+        history.replace("Score corrected", rebuilt);
+        assert_eq!(history.redo_labels().count(), 0);
+        assert_eq!(history.changes().next().unwrap().after.left_fencer.score, Some(5));
+    }
+}
@@ -0,0 +1,217 @@
+use std::convert::Infallible;
+use std::sync::{Arc, RwLock};
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+use super::competition::{BoutResult, CompetitionTracker};
+use super::message::Message;
+use super::state::{MatchEvent, MatchState, PisteManager};
+
+/// Number of events buffered for slow SSE clients before they start missing events.
+const EVENT_BUFFER: usize = 256;
+
+/// Event pushed to SSE clients, tagged with the piste it happened on.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PisteEvent {
+    /// Piste identifier.
+    pub piste: String,
+    /// What changed.
+    pub event: MatchEvent,
+}
+
+/// HTTP bridge exposing the live state of every piste.
+///
+/// The bridge owns a [`PisteManager`] and a [`CompetitionTracker`]; the application feeds it the messages
+/// received from the apparatus with [`HttpBridge::apply`] and serves
+/// [`HttpBridge::router`] with axum. The routes are:
+///
+/// - `GET /pistes`: JSON array with the state of every piste, in natural piste order
+/// - `GET /pistes/{id}/state`: JSON state of one piste, or 404
+/// - `GET /events`: server-sent events, one JSON [`PisteEvent`] per change
+/// - `GET`/`POST /graphql` and `GET /graphql/ws`: GraphQL queries and
+///   subscriptions over the same data, see the `graphql` module (feature `graphql`)
+///
+/// The bridge is cheap to clone; clones share the same state.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cyrano::http::HttpBridge;
+///
+/// # async fn run() -> std::io::Result<()> {
+/// let bridge = HttpBridge::new();
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
+///
+/// // Feed bridge.apply(&message) from the UDP receive loop, then:
+/// axum::serve(listener, bridge.router()).await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HttpBridge {
+    pistes: Arc<RwLock<PisteManager>>,
+    competitions: Arc<RwLock<CompetitionTracker>>,
+    events: broadcast::Sender<PisteEvent>,
+}
+
+impl Default for HttpBridge {
+    fn default() -> Self {
+        HttpBridge::new()
+    }
+}
+
+impl HttpBridge {
+    /// Creates a bridge with no known piste.
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        HttpBridge {
+            pistes: Arc::new(RwLock::new(PisteManager::new())),
+            competitions: Arc::new(RwLock::new(CompetitionTracker::new())),
+            events,
+        }
+    }
+
+    /// Applies a message to the piste state and pushes the resulting events to SSE clients.
+    pub fn apply(&self, message: &Message) -> Vec<MatchEvent> {
+        let events = self
+            .pistes
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .apply(message);
+        self.competitions
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .apply(message);
+
+        for event in &events {
+            // Sending only fails when no client is connected.
+            let _ = self.events.send(PisteEvent {
+                piste: message.piste.to_string(),
+                event: event.clone(),
+            });
+        }
+        events
+    }
+
+    /// Returns the state of every piste, in natural piste order.
+    pub fn snapshot(&self) -> Vec<MatchState> {
+        let pistes = self.pistes.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        pistes.iter().cloned().collect()
+    }
+
+    /// Returns the state of one piste.
+    pub fn piste(&self, piste: &str) -> Option<MatchState> {
+        self.pistes
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(piste)
+            .cloned()
+    }
+
+    /// Returns the identifiers of the competitions seen, sorted.
+    pub fn competitions(&self) -> Vec<String> {
+        let competitions = self.competitions.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut ids: Vec<String> = competitions.competitions().map(String::from).collect();
+        ids.sort();
+        ids
+    }
+
+    /// Returns the state of every piste of a competition, in natural piste order.
+    pub fn competition_pistes(&self, competition_id: &str) -> Vec<MatchState> {
+        let competitions = self.competitions.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        competitions
+            .pistes(competition_id)
+            .map(|pistes| pistes.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the results recorded for a phase, optionally restricted to one
+    /// pool or tableau; see [`CompetitionTracker::results`].
+    pub fn results(&self, competition_id: &str, phase: u8, pool_tableau: Option<&str>) -> Vec<BoutResult> {
+        let competitions = self.competitions.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        competitions
+            .results(competition_id, phase, pool_tableau)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Subscribes to the events applied from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<PisteEvent> {
+        self.events.subscribe()
+    }
+
+    /// Returns the axum router serving the bridge routes.
+    pub fn router(&self) -> Router {
+        let router = Router::new()
+            .route("/pistes", get(list_pistes))
+            .route("/pistes/{id}/state", get(piste_state))
+            .route("/events", get(events))
+            .with_state(self.clone());
+        #[cfg(feature = "graphql")]
+        let router = router.merge(super::graphql::router(self));
+        router
+    }
+}
+
+async fn list_pistes(State(bridge): State<HttpBridge>) -> Json<Vec<MatchState>> {
+    Json(bridge.snapshot())
+}
+
+async fn piste_state(State(bridge): State<HttpBridge>, Path(id): Path<String>) -> Response {
+    match bridge.piste(&id) {
+        Some(state) => Json(state).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn events(State(bridge): State<HttpBridge>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    // Clients lagging behind the buffer skip the missed events.
+    let stream = BroadcastStream::new(bridge.subscribe())
+        .filter_map(|event| event.ok())
+        .filter_map(|event| Event::default().event("piste").json_data(event).ok())
+        .map(Ok);
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_bridge_broadcasts_events() {
+        let bridge = HttpBridge::new();
+        let mut events = bridge.subscribe();
+
+        bridge.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1|||||E||F|%|28|P.Martin|FRA|0|U|%|32|Panini|ITA|0|U|%|").unwrap());
+
+        assert_eq!(events.try_recv().unwrap().event, MatchEvent::BoutStarted);
+        assert_eq!(bridge.snapshot().len(), 1);
+        assert!(bridge.piste("3").is_some());
+        assert!(bridge.piste("4").is_none());
+
+        let json = serde_json::to_string(&bridge.snapshot()).unwrap();
+        assert!(json.contains("P.Martin"));
+    }
+
+    #[test]
+    fn test_pistes_in_natural_order() {
+        let bridge = HttpBridge::new();
+        for piste in ["10", "2"] {
+            bridge.apply(&Message::try_from(format!("|EFP1.1|INFO|{}|fm-eq|1|P3|1|||||E||F|%|%|%|", piste)).unwrap());
+        }
+        let pistes: Vec<String> = bridge.snapshot().iter().map(|state| state.piste().to_string()).collect();
+        assert_eq!(pistes, ["2", "10"]);
+    }
+}
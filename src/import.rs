@@ -0,0 +1,302 @@
+use std::convert::TryFrom;
+
+use super::assignment::MatchAssignment;
+use super::enums::Weapon;
+use super::error::ImportError;
+
+/// Competition management software producing the export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    /// Engarde piste assignment export (French column headers).
+    Engarde,
+    /// Ophardt piste assignment export (English column headers).
+    Ophardt,
+}
+
+/// Field of a [`MatchAssignment`] filled from an export column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Piste,
+    Competition,
+    Phase,
+    PoolTableau,
+    Match,
+    Round,
+    Time,
+    Weapon,
+    RightId,
+    RightName,
+    RightNation,
+    LeftId,
+    LeftName,
+    LeftNation,
+    RefereeId,
+    RefereeName,
+    RefereeNation,
+}
+
+impl Column {
+    fn name(&self) -> &'static str {
+        match self {
+            Column::Piste => "piste",
+            Column::Competition => "competition_id",
+            Column::Phase => "phase",
+            Column::PoolTableau => "pool_tableau",
+            Column::Match => "match_number",
+            Column::Round => "round",
+            Column::Time => "time",
+            Column::Weapon => "weapon",
+            Column::RightId => "right_fencer.id",
+            Column::RightName => "right_fencer.name",
+            Column::RightNation => "right_fencer.nation",
+            Column::LeftId => "left_fencer.id",
+            Column::LeftName => "left_fencer.name",
+            Column::LeftNation => "left_fencer.nation",
+            Column::RefereeId => "referee.id",
+            Column::RefereeName => "referee.name",
+            Column::RefereeNation => "referee.nation",
+        }
+    }
+}
+
+const ENGARDE_HEADERS: &[(&str, Column)] = &[
+    ("piste", Column::Piste),
+    ("competition", Column::Competition),
+    ("phase", Column::Phase),
+    ("poule", Column::PoolTableau),
+    ("tableau", Column::PoolTableau),
+    ("match", Column::Match),
+    ("tour", Column::Round),
+    ("heure", Column::Time),
+    ("arme", Column::Weapon),
+    ("id droite", Column::RightId),
+    ("nom droite", Column::RightName),
+    ("nation droite", Column::RightNation),
+    ("id gauche", Column::LeftId),
+    ("nom gauche", Column::LeftName),
+    ("nation gauche", Column::LeftNation),
+    ("id arbitre", Column::RefereeId),
+    ("arbitre", Column::RefereeName),
+    ("nation arbitre", Column::RefereeNation),
+];
+
+const OPHARDT_HEADERS: &[(&str, Column)] = &[
+    ("piste", Column::Piste),
+    ("strip", Column::Piste),
+    ("competition", Column::Competition),
+    ("phase", Column::Phase),
+    ("group", Column::PoolTableau),
+    ("pool", Column::PoolTableau),
+    ("table", Column::PoolTableau),
+    ("bout", Column::Match),
+    ("round", Column::Round),
+    ("time", Column::Time),
+    ("weapon", Column::Weapon),
+    ("right id", Column::RightId),
+    ("right name", Column::RightName),
+    ("right nation", Column::RightNation),
+    ("left id", Column::LeftId),
+    ("left name", Column::LeftName),
+    ("left nation", Column::LeftNation),
+    ("referee id", Column::RefereeId),
+    ("referee", Column::RefereeName),
+    ("referee nation", Column::RefereeNation),
+];
+
+impl ImportSource {
+    fn headers(&self) -> &'static [(&'static str, Column)] {
+        match self {
+            ImportSource::Engarde => ENGARDE_HEADERS,
+            ImportSource::Ophardt => OPHARDT_HEADERS,
+        }
+    }
+}
+
+/// Imports the piste assignments exported by Engarde or Ophardt.
+///
+/// The export is a delimited text file (`;`, `,` or tab, detected from the
+/// header line) with one bout per row. Columns are matched by header name,
+/// case-insensitively and in any order; unknown columns are ignored. Engarde
+/// headers are `Piste`, `Phase`, `Poule` or `Tableau`, `Match`, `Tour`,
+/// `Heure`, `Arme`, `Id droite`, `Nom droite`, `Nation droite`, the same three
+/// with `gauche`, and `Id arbitre`, `Arbitre`, `Nation arbitre`. Ophardt
+/// headers are `Strip` or `Piste`, `Phase`, `Group`, `Pool` or `Table`, `Bout`,
+/// `Round`, `Time`, `Weapon`, `Right ID`, `Right Name`, `Right Nation`, the
+/// same three with `Left`, and `Referee ID`, `Referee`, `Referee Nation`.
+/// A `Competition` column overrides `competition_id` per row.
+///
+/// # Arguments
+///
+/// * `text` - Contents of the export
+/// * `source` - Software that produced the export
+/// * `competition_id` - Competition identifier used when the export has none
+///
+/// # Errors
+///
+/// Returns `ImportError::MissingColumn` if there is no piste column and
+/// `ImportError::InvalidRow` for a number or weapon that cannot be read.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::enums::Command;
+/// use cyrano::import::{import_assignments, ImportSource};
+///
+/// let export = "Piste;Phase;Poule;Match;Id droite;Nom droite;Nation droite;Id gauche;Nom gauche;Nation gauche\n\
+///               17;1;A32;12;28;P.Martin;FRA;32;B. Panini;ITA\n";
+///
+/// let assignments = import_assignments(export, ImportSource::Engarde, "fm-eq").unwrap();
+/// let next = assignments[0].to_message(Command::Next).unwrap();
+/// assert_eq!(next.to_string(), "|EFP1.1|NEXT|17|fm-eq|1|A32|12|||||||||||%|28|P.Martin|FRA|%|32|B. Panini|ITA|%|");
+/// ```
+pub fn import_assignments(
+    text: &str,
+    source: ImportSource,
+    competition_id: &str,
+) -> Result<Vec<MatchAssignment>, ImportError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let (_, header) = lines.next().ok_or(ImportError::Empty)?;
+    let delimiter = [';', '\t', ',']
+        .into_iter()
+        .max_by_key(|d| header.matches(*d).count())
+        .unwrap_or(';');
+
+    let columns: Vec<Option<Column>> = split_row(header.trim_start_matches('\u{feff}'), delimiter)
+        .iter()
+        .map(|name| {
+            let name = name.trim().to_lowercase();
+            source
+                .headers()
+                .iter()
+                .find(|(header, _)| *header == name)
+                .map(|(_, column)| *column)
+        })
+        .collect();
+
+    if !columns.contains(&Some(Column::Piste)) {
+        return Err(ImportError::MissingColumn(Column::Piste.name()));
+    }
+
+    let mut assignments = Vec::new();
+    for (index, line) in lines {
+        let mut assignment = MatchAssignment {
+            competition_id: competition_id.to_string(),
+            ..MatchAssignment::default()
+        };
+
+        for (column, value) in columns.iter().zip(split_row(line, delimiter)) {
+            let value = value.trim();
+            if let (Some(column), false) = (column, value.is_empty()) {
+                set(&mut assignment, *column, value).map_err(|_| ImportError::InvalidRow {
+                    line_number: index + 1,
+                    column: column.name(),
+                    value: value.to_string(),
+                })?;
+            }
+        }
+        assignments.push(assignment);
+    }
+
+    Ok(assignments)
+}
+
+fn set(assignment: &mut MatchAssignment, column: Column, value: &str) -> Result<(), ()> {
+    let text = || Some(value.to_string());
+    let number = || value.parse::<u8>().map(Some).map_err(|_| ());
+
+    match column {
+        Column::Piste => assignment.piste = value.to_string(),
+        Column::Competition => assignment.competition_id = value.to_string(),
+        Column::Phase => assignment.phase = number()?,
+        Column::PoolTableau => assignment.pool_tableau = text(),
+        Column::Match => assignment.match_number = number()?,
+        Column::Round => assignment.round = number()?,
+        Column::Time => assignment.time = text(),
+        Column::Weapon => assignment.weapon = Some(weapon(value).ok_or(())?),
+        Column::RightId => assignment.right_fencer.id = text(),
+        Column::RightName => assignment.right_fencer.name = text(),
+        Column::RightNation => assignment.right_fencer.nation = text(),
+        Column::LeftId => assignment.left_fencer.id = text(),
+        Column::LeftName => assignment.left_fencer.name = text(),
+        Column::LeftNation => assignment.left_fencer.nation = text(),
+        Column::RefereeId => assignment.referee.id = Some(value.into()),
+        Column::RefereeName => assignment.referee.name = Some(value.into()),
+        Column::RefereeNation => assignment.referee.nation = Some(value.into()),
+    }
+    Ok(())
+}
+
+/// Reads a weapon given either as its EFP code or spelled out.
+fn weapon(value: &str) -> Option<Weapon> {
+    match value.to_lowercase().as_str() {
+        "foil" | "fleuret" | "florett" => Some(Weapon::Foil),
+        "epee" | "épée" | "degen" => Some(Weapon::Epee),
+        "sabre" | "säbel" => Some(Weapon::Sabre),
+        _ => Weapon::try_from(value.to_uppercase().as_str()).ok(),
+    }
+}
+
+/// Splits a delimited row, honoring double-quoted values.
+fn split_row(line: &str, delimiter: char) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut value = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                value.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => values.push(std::mem::take(&mut value)),
+            c => value.push(c),
+        }
+    }
+    values.push(value);
+    values
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ophardt_export() {
+        let export = "Bout,Strip,Time,Group,Phase,Weapon,Left ID,Left Name,Left Nation,Right ID,Right Name,Right Nation,Referee\r\n\
+                      3,Blue,14:30,T64,2,Sabre,11,\"MEYER, Karl\",GER,54,CHEN Li,CHN,SMITH John\r\n";
+
+        let assignments = import_assignments(export, ImportSource::Ophardt, "sm-ind").unwrap();
+        let a = &assignments[0];
+
+        assert_eq!(a.piste, "Blue");
+        assert_eq!(a.match_number, Some(3));
+        assert_eq!(a.weapon, Some(Weapon::Sabre));
+        assert_eq!(a.left_fencer.name.as_deref(), Some("MEYER, Karl"));
+        assert_eq!(a.right_fencer.id.as_deref(), Some("54"));
+        assert_eq!(a.referee.name.as_deref(), Some("SMITH John"));
+        assert!(a.validate().is_ok());
+    }
+
+    #[test]
+    fn test_import_errors() {
+        assert_eq!(
+            import_assignments("Match;Poule\n1;A\n", ImportSource::Engarde, "x").unwrap_err(),
+            ImportError::MissingColumn("piste")
+        );
+        assert_eq!(
+            import_assignments("Piste;Match\n1;12b\n", ImportSource::Engarde, "x").unwrap_err(),
+            ImportError::InvalidRow {
+                line_number: 2,
+                column: "match_number",
+                value: "12b".to_string()
+            }
+        );
+    }
+}
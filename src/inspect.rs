@@ -0,0 +1,395 @@
+use std::fmt::Display;
+
+use crate::borrowed::{FencerRef, MessageRef};
+use crate::enums::*;
+use crate::error::FieldError;
+use crate::fencer::{Fencer, FENCER_FIELD_NAMES};
+use crate::message::{FieldString, Message, GENERAL_FIELD_NAMES};
+use crate::utils::split_zone;
+
+/// Parsed value of a protocol field.
+///
+/// Text values borrow from the message they were read from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FieldValue<'a> {
+    /// Free text: identifiers, names, nations and times.
+    Text(&'a str),
+    /// Numbers: phase, match and round numbers, scores, card counts.
+    Number(u8),
+    /// Lights, written `0` or `1`.
+    Flag(bool),
+    /// Message command.
+    Command(Command),
+    /// Competition type.
+    CompetitionType(CompetitionType),
+    /// Weapon.
+    Weapon(Weapon),
+    /// Priority indicator.
+    Priority(Priority),
+    /// Apparatus state.
+    State(ApparatusState),
+    /// Fencer status.
+    Status(FencerStatus),
+    /// Reserve status.
+    Reserve(Reserve),
+    /// P-card.
+    PCard(PCard),
+}
+
+impl Display for FieldValue<'_> {
+    /// Formats the value as written in a frame.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::Text(text) => f.write_str(text),
+            FieldValue::Number(number) => write!(f, "{}", number),
+            FieldValue::Flag(flag) => f.write_str(if *flag { "1" } else { "0" }),
+            FieldValue::Command(command) => command.fmt(f),
+            FieldValue::CompetitionType(competition_type) => competition_type.fmt(f),
+            FieldValue::Weapon(weapon) => weapon.fmt(f),
+            FieldValue::Priority(priority) => priority.fmt(f),
+            FieldValue::State(state) => state.fmt(f),
+            FieldValue::Status(status) => status.fmt(f),
+            FieldValue::Reserve(reserve) => reserve.fmt(f),
+            FieldValue::PCard(p_card) => p_card.fmt(f),
+        }
+    }
+}
+
+/// One field of a parsed frame, as yielded by [`FieldIter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field<'a> {
+    /// Zone of the field.
+    pub zone: Zone,
+    /// Position of the field in its zone.
+    pub index: usize,
+    /// Name of the field, as in [`GENERAL_FIELD_NAMES`] or [`FENCER_FIELD_NAMES`].
+    pub name: &'static str,
+    /// Text of the field in the frame, or `None` if the frame stops before it.
+    pub raw: Option<&'a str>,
+    /// Parsed value, or `None` if the field is empty or its value was rejected.
+    pub value: Option<FieldValue<'a>>,
+}
+
+impl Field<'_> {
+    /// Returns the dotted name of the field, as accepted by [`Message::get`],
+    /// such as `weapon` or `left_fencer.score`.
+    pub fn path(&self) -> String {
+        match self.zone {
+            Zone::General => self.name.to_string(),
+            Zone::RightFencer => format!("right_fencer.{}", self.name),
+            Zone::LeftFencer => format!("left_fencer.{}", self.name),
+        }
+    }
+}
+
+/// Iterator over every field of a frame, created by [`MessageRef::fields`].
+///
+/// Fields come in protocol order: the general zone, then the right and left
+/// fencer zones. Each zone yields all its known fields, whether or not the
+/// frame carried them; extra trailing fields are not yielded.
+#[derive(Debug, Clone)]
+pub struct FieldIter<'a> {
+    fields: std::vec::IntoIter<Field<'a>>,
+}
+
+impl<'a> FieldIter<'a> {
+    pub(crate) fn new(view: &MessageRef<'a>) -> Self {
+        let zones = view.raw_zones();
+        let zone_fields = |index: usize| zones.get(index).map(|zone| split_zone(zone)).unwrap_or_default();
+
+        let mut fields = Vec::with_capacity(GENERAL_FIELD_NAMES.len() + 2 * FENCER_FIELD_NAMES.len());
+
+        let general = zone_fields(0);
+        for (index, name) in GENERAL_FIELD_NAMES.iter().enumerate() {
+            fields.push(Field {
+                zone: Zone::General,
+                index,
+                name,
+                raw: general.get(index).copied(),
+                value: general_value(view, index),
+            });
+        }
+
+        for (position, zone, fencer) in [
+            (1, Zone::RightFencer, &view.right_fencer),
+            (2, Zone::LeftFencer, &view.left_fencer),
+        ] {
+            let raw = zone_fields(position);
+            for (index, name) in FENCER_FIELD_NAMES.iter().enumerate() {
+                fields.push(Field {
+                    zone,
+                    index,
+                    name,
+                    raw: raw.get(index).copied(),
+                    value: fencer_value(fencer, index),
+                });
+            }
+        }
+
+        FieldIter {
+            fields: fields.into_iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for FieldIter<'a> {
+    type Item = Field<'a>;
+
+    fn next(&mut self) -> Option<Field<'a>> {
+        self.fields.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.fields.size_hint()
+    }
+}
+
+impl ExactSizeIterator for FieldIter<'_> {}
+
+/// Returns the parsed value of a general field of a borrowed message.
+fn general_value<'a>(view: &MessageRef<'a>, index: usize) -> Option<FieldValue<'a>> {
+    let text = |value: &'a str| Some(value).filter(|v| !v.is_empty()).map(FieldValue::Text);
+    match index {
+        0 => text(view.protocol),
+        1 => Some(FieldValue::Command(view.command.clone())),
+        2 => text(view.piste),
+        3 => text(view.competition_id),
+        4 => view.phase.map(FieldValue::Number),
+        5 => view.pool_tableau.map(FieldValue::Text),
+        6 => view.match_number.map(FieldValue::Number),
+        7 => view.round.map(FieldValue::Number),
+        8 => view.time.map(FieldValue::Text),
+        9 => view.stopwatch.map(FieldValue::Text),
+        10 => view.competition_type.clone().map(FieldValue::CompetitionType),
+        11 => view.weapon.clone().map(FieldValue::Weapon),
+        12 => view.priority.clone().map(FieldValue::Priority),
+        13 => view.state.clone().map(FieldValue::State),
+        14 => view.referee.id.map(FieldValue::Text),
+        15 => view.referee.name.map(FieldValue::Text),
+        16 => view.referee.nation.map(FieldValue::Text),
+        _ => None,
+    }
+}
+
+/// Returns the parsed value of a field of a borrowed fencer zone.
+fn fencer_value<'a>(fencer: &FencerRef<'a>, index: usize) -> Option<FieldValue<'a>> {
+    match index {
+        0 => fencer.id.map(FieldValue::Text),
+        1 => fencer.name.map(FieldValue::Text),
+        2 => fencer.nation.map(FieldValue::Text),
+        3 => fencer.score.map(FieldValue::Number),
+        4 => fencer.status.clone().map(FieldValue::Status),
+        5 => fencer.yellow_card.map(FieldValue::Number),
+        6 => fencer.red_card.map(FieldValue::Number),
+        7 => fencer.light.map(FieldValue::Flag),
+        8 => fencer.white_light.map(FieldValue::Flag),
+        9 => fencer.medical.map(FieldValue::Number),
+        10 => fencer.reserve.clone().map(FieldValue::Reserve),
+        11 => fencer.p_card.clone().map(FieldValue::PCard),
+        12 => fencer.bout_score.map(FieldValue::Number),
+        13 => fencer.video_appeals.map(FieldValue::Number),
+        14 => fencer.video_review.map(FieldValue::Flag),
+        _ => None,
+    }
+}
+
+/// Finds a field by its dotted name, such as `weapon`, `referee.name` or
+/// `left_fencer.score`.
+fn locate(name: &str) -> Option<(Zone, usize)> {
+    let in_zone = |names: &[&str], field: &str| names.iter().position(|n| *n == field);
+    if let Some(index) = in_zone(&GENERAL_FIELD_NAMES, name) {
+        return Some((Zone::General, index));
+    }
+    let (zone, field) = name.split_once('.')?;
+    let zone = match zone {
+        "right_fencer" => Zone::RightFencer,
+        "left_fencer" => Zone::LeftFencer,
+        _ => return None,
+    };
+    in_zone(&FENCER_FIELD_NAMES, field).map(|index| (zone, index))
+}
+
+pub(crate) fn get<'m>(message: &'m Message, name: &str) -> Result<Option<FieldValue<'m>>, FieldError> {
+    let value = match locate(name).ok_or_else(|| FieldError::UnknownField(name.to_string()))? {
+        (Zone::General, index) => match index {
+            0 => Some(message.protocol.as_str()).filter(|v| !v.is_empty()).map(FieldValue::Text),
+            1 => Some(FieldValue::Command(message.command.clone())),
+            2 => Some(message.piste.as_str()).filter(|v| !v.is_empty()).map(FieldValue::Text),
+            3 => Some(message.competition_id.as_str()).filter(|v| !v.is_empty()).map(FieldValue::Text),
+            4 => message.phase.map(FieldValue::Number),
+            5 => message.pool_tableau.as_deref().map(FieldValue::Text),
+            6 => message.match_number.map(FieldValue::Number),
+            7 => message.round.map(FieldValue::Number),
+            8 => message.time.as_deref().map(FieldValue::Text),
+            9 => message.stopwatch.as_deref().map(FieldValue::Text),
+            10 => message.competition_type.clone().map(FieldValue::CompetitionType),
+            11 => message.weapon.clone().map(FieldValue::Weapon),
+            12 => message.priority.clone().map(FieldValue::Priority),
+            13 => message.state.clone().map(FieldValue::State),
+            14 => message.referee.id.as_deref().map(FieldValue::Text),
+            15 => message.referee.name.as_deref().map(FieldValue::Text),
+            _ => message.referee.nation.as_deref().map(FieldValue::Text),
+        },
+        (Zone::RightFencer, index) => get_fencer(&message.right_fencer, index),
+        (Zone::LeftFencer, index) => get_fencer(&message.left_fencer, index),
+    };
+    Ok(value)
+}
+
+fn get_fencer(fencer: &Fencer, index: usize) -> Option<FieldValue<'_>> {
+    match index {
+        0 => fencer.id.as_deref().map(FieldValue::Text),
+        1 => fencer.name.as_deref().map(FieldValue::Text),
+        2 => fencer.nation.as_deref().map(FieldValue::Text),
+        3 => fencer.score.map(FieldValue::Number),
+        4 => fencer.status.clone().map(FieldValue::Status),
+        5 => fencer.yellow_card.map(FieldValue::Number),
+        6 => fencer.red_card.map(FieldValue::Number),
+        7 => fencer.light.map(FieldValue::Flag),
+        8 => fencer.white_light.map(FieldValue::Flag),
+        9 => fencer.medical.map(FieldValue::Number),
+        10 => fencer.reserve.clone().map(FieldValue::Reserve),
+        11 => fencer.p_card.clone().map(FieldValue::PCard),
+        12 => fencer.bout_score.map(FieldValue::Number),
+        13 => fencer.video_appeals.map(FieldValue::Number),
+        _ => fencer.video_review.map(FieldValue::Flag),
+    }
+}
+
+/// Extracts the content of a value of the given variant; fails on any other variant.
+macro_rules! take {
+    ($value:expr, $variant:ident) => {
+        match $value {
+            None => Ok(None),
+            Some(FieldValue::$variant(inner)) => Ok(Some(inner)),
+            Some(_) => Err(()),
+        }
+    };
+}
+
+fn text(value: Option<FieldValue<'_>>) -> Result<Option<FieldString>, ()> {
+    take!(value, Text).map(|text| text.map(FieldString::from))
+}
+
+pub(crate) fn set(message: &mut Message, name: &str, value: Option<FieldValue<'_>>) -> Result<(), FieldError> {
+    let (zone, index) = locate(name).ok_or_else(|| FieldError::UnknownField(name.to_string()))?;
+    let written = value.as_ref().map(ToString::to_string).unwrap_or_default();
+
+    let result = match zone {
+        Zone::General => set_general(message, index, value),
+        Zone::RightFencer => set_fencer(&mut message.right_fencer, index, value),
+        Zone::LeftFencer => set_fencer(&mut message.left_fencer, index, value),
+    };
+    result.map_err(|()| FieldError::WrongType {
+        field: name.to_string(),
+        value: written,
+    })
+}
+
+fn set_general(message: &mut Message, index: usize, value: Option<FieldValue<'_>>) -> Result<(), ()> {
+    match index {
+        0 => message.protocol = text(value)?.unwrap_or_default(),
+        1 => message.command = take!(value, Command)?.ok_or(())?,
+        2 => message.piste = text(value)?.unwrap_or_default(),
+        3 => message.competition_id = text(value)?.unwrap_or_default(),
+        4 => message.phase = take!(value, Number)?,
+        5 => message.pool_tableau = text(value)?,
+        6 => message.match_number = take!(value, Number)?,
+        7 => message.round = take!(value, Number)?,
+        8 => message.time = text(value)?,
+        9 => message.stopwatch = text(value)?,
+        10 => message.competition_type = take!(value, CompetitionType)?,
+        11 => message.weapon = take!(value, Weapon)?,
+        12 => message.priority = take!(value, Priority)?,
+        13 => message.state = take!(value, State)?,
+        14 => message.referee.id = text(value)?,
+        15 => message.referee.name = text(value)?,
+        _ => message.referee.nation = text(value)?,
+    }
+    Ok(())
+}
+
+fn set_fencer(fencer: &mut Fencer, index: usize, value: Option<FieldValue<'_>>) -> Result<(), ()> {
+    match index {
+        0 => fencer.id = text(value)?,
+        1 => fencer.name = text(value)?,
+        2 => fencer.nation = text(value)?,
+        3 => fencer.score = take!(value, Number)?,
+        4 => fencer.status = take!(value, Status)?,
+        5 => fencer.yellow_card = take!(value, Number)?,
+        6 => fencer.red_card = take!(value, Number)?,
+        7 => fencer.light = take!(value, Flag)?,
+        8 => fencer.white_light = take!(value, Flag)?,
+        9 => fencer.medical = take!(value, Number)?,
+        10 => fencer.reserve = take!(value, Reserve)?,
+        11 => fencer.p_card = take!(value, PCard)?,
+        12 => fencer.bout_score = take!(value, Number)?,
+        13 => fencer.video_appeals = take!(value, Number)?,
+        _ => fencer.video_review = take!(value, Flag)?,
+    }
+    Ok(())
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::corpus;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_text_fields_borrow_raw_text() {
+        for entry in corpus() {
+            let Ok(view) = MessageRef::try_from(entry.frame) else {
+                continue;
+            };
+            for field in view.fields() {
+                if let Some(FieldValue::Text(text)) = field.value {
+                    assert_eq!(field.raw, Some(text), "{} {}.{}", entry.name, field.zone, field.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_missing_and_invalid_fields() {
+        let view = MessageRef::try_from("|EFP1.1|INFO|17|fm-eq|x|%|28|P.Martin|FRA|4|").unwrap();
+        let fields: Vec<Field> = view.fields().collect();
+
+        assert_eq!(fields.len(), 47);
+        assert_eq!(fields[4].raw, Some("x"));
+        assert_eq!(fields[4].value, None);
+        assert_eq!(fields[5].raw, None);
+        assert_eq!(fields[20].name, "score");
+        assert_eq!(fields[20].value, Some(FieldValue::Number(4)));
+        assert_eq!(fields[32].zone, Zone::LeftFencer);
+        assert_eq!(fields[32].raw, None);
+    }
+
+    #[test]
+    fn test_get_and_set_by_name() {
+        let mut message = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||H|%|28|P.Martin|FRA|4|U|%|%|").unwrap();
+
+        assert_eq!(message.get("right_fencer.score"), Ok(Some(FieldValue::Number(4))));
+        assert_eq!(message.get("referee.name"), Ok(None));
+        assert_eq!(message.get("weapon"), Ok(Some(FieldValue::Weapon(Weapon::Epee))));
+
+        message.set("left_fencer.name", Some(FieldValue::Text("B. Panini"))).unwrap();
+        message.set("right_fencer.status", Some(FieldValue::Status(FencerStatus::Victory))).unwrap();
+        message.set("phase", None).unwrap();
+        assert_eq!(message.left_fencer.name.as_deref(), Some("B. Panini"));
+        assert_eq!(message.right_fencer.status, Some(FencerStatus::Victory));
+        assert_eq!(message.phase, None);
+
+        assert_eq!(message.get("right_fencer.age"), Err(FieldError::UnknownField("right_fencer.age".to_string())));
+        assert_eq!(
+            message.set("round", Some(FieldValue::Flag(true))),
+            Err(FieldError::WrongType {
+                field: "round".to_string(),
+                value: "1".to_string(),
+            })
+        );
+        assert!(message.set("command", None).is_err());
+    }
+}
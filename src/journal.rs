@@ -0,0 +1,367 @@
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+
+use super::error::{JournalError, ParseError};
+use super::message::Message;
+use super::piste::PisteId;
+use super::state::{MatchEvent, PisteManager};
+use super::utils::frame_piste;
+
+/// A message applied to the state tracker, as recorded in a journal.
+///
+/// Journals hold one entry per line in the form `<seq> <timestamp_ms> <frame>`,
+/// where `seq` numbers the entries from 1 without gaps and the timestamp is
+/// the time the message was applied, in milliseconds.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::journal::JournalEntry;
+///
+/// let entry = JournalEntry::parse_line("12 1700000000123 |EFP1.1|INFO|17|fm-eq|1|A32|12|%|").unwrap();
+/// assert_eq!((entry.seq, entry.timestamp_ms), (12, 1700000000123));
+/// assert_eq!(entry.message().unwrap().piste, "17");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JournalEntry {
+    /// Position of the entry in the journal, starting at 1.
+    pub seq: u64,
+    /// Time the message was applied, in milliseconds.
+    pub timestamp_ms: u64,
+    /// The applied message, serialized.
+    pub raw: String,
+}
+
+impl JournalEntry {
+    /// Parses a journal line.
+    ///
+    /// # Returns
+    ///
+    /// `Some(JournalEntry)` if the line is of the form `<seq> <timestamp_ms> <frame>`,
+    /// `None` otherwise.
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let mut parts = line.splitn(3, ' ');
+        let seq = parts.next()?.parse().ok()?;
+        let timestamp_ms = parts.next()?.parse().ok()?;
+        let raw = parts.next()?;
+        if !raw.starts_with('|') {
+            return None;
+        }
+        Some(JournalEntry {
+            seq,
+            timestamp_ms,
+            raw: raw.to_string(),
+        })
+    }
+
+    /// Returns the piste of the journaled message, read without parsing it.
+    pub fn piste(&self) -> Option<PisteId> {
+        frame_piste(&self.raw)
+    }
+
+    /// Parses the journaled message.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the frame is not a valid EFP message.
+    pub fn message(&self) -> Result<Message, ParseError> {
+        Message::try_from(self.raw.as_str())
+    }
+}
+
+impl Display for JournalEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.seq, self.timestamp_ms, self.raw)
+    }
+}
+
+/// Appends the messages applied to the state tracker to a journal.
+///
+/// Each entry is written and flushed before the message is applied, so that
+/// whatever the tracker has seen is on disk should the software crash. Open
+/// the journal file in append mode; see [`read`] and [`replay`] to rebuild
+/// the state from it.
+pub struct JournalWriter<W: Write> {
+    writer: W,
+    next_seq: u64,
+}
+
+impl<W: Write> JournalWriter<W> {
+    /// Starts a journal whose first entry is numbered 1.
+    pub fn new(writer: W) -> Self {
+        JournalWriter { writer, next_seq: 1 }
+    }
+
+    /// Continues a journal after the entry numbered `last_seq`, e.g. once it
+    /// has been replayed after a restart.
+    pub fn after(mut self, last_seq: u64) -> Self {
+        self.next_seq = last_seq + 1;
+        self
+    }
+
+    /// Returns the number of the last entry written, 0 if there is none.
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+
+    /// Appends a message and flushes the journal.
+    ///
+    /// # Returns
+    ///
+    /// The number of the entry written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the writer.
+    pub fn append(&mut self, message: &Message, timestamp_ms: u64) -> io::Result<u64> {
+        let entry = JournalEntry {
+            seq: self.next_seq,
+            timestamp_ms,
+            raw: message.to_string(),
+        };
+        writeln!(self.writer, "{}", entry)?;
+        self.writer.flush()?;
+        self.next_seq += 1;
+        Ok(entry.seq)
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// A [`PisteManager`] whose applied messages are journaled.
+///
+/// A snapshot of the manager together with [`last_seq`](Journaled::last_seq)
+/// is enough to rebuild the exact state after a crash: [`replay`] the journal
+/// entries that came after the snapshot on top of it.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Side;
+/// use cyrano::journal::{self, Journaled, JournalWriter};
+/// use cyrano::message::Message;
+/// use cyrano::state::PisteManager;
+///
+/// let mut pistes = Journaled::new(PisteManager::new(), JournalWriter::new(Vec::new()));
+/// let info = |right: u8| {
+///     let raw = format!("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||F|%|28|P.Martin|FRA|{}|%|32|B. Panini|ITA|0|%|", right);
+///     Message::try_from(raw).unwrap()
+/// };
+/// pistes.apply(&info(1), 1000)?;
+/// pistes.apply(&info(2), 2000)?;
+///
+/// // After a crash, the journal alone brings the state back.
+/// let (_, writer) = pistes.into_parts();
+/// let journal = writer.into_inner();
+/// let entries = journal::read(journal.as_slice())?;
+/// let mut recovered = PisteManager::new();
+/// assert_eq!(journal::replay(&mut recovered, &entries, 0)?, 2);
+/// assert_eq!(recovered.get("17").unwrap().score(Side::Right), Some(2));
+/// # Ok::<(), cyrano::error::JournalError>(())
+/// ```
+pub struct Journaled<W: Write> {
+    manager: PisteManager,
+    journal: JournalWriter<W>,
+}
+
+impl<W: Write> Journaled<W> {
+    /// Journals the messages applied to `manager` from now on.
+    pub fn new(manager: PisteManager, journal: JournalWriter<W>) -> Self {
+        Journaled { manager, journal }
+    }
+
+    /// Journals a message, then applies it and returns the resulting events.
+    ///
+    /// Messages that do not carry bout data are neither journaled nor applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the journal, in which case the message is not
+    /// applied.
+    pub fn apply(&mut self, message: &Message, timestamp_ms: u64) -> io::Result<Vec<MatchEvent>> {
+        if !message.carries_bout() {
+            return Ok(Vec::new());
+        }
+        self.journal.append(message, timestamp_ms)?;
+        Ok(self.manager.apply(message))
+    }
+
+    /// Returns the tracked state.
+    pub fn manager(&self) -> &PisteManager {
+        &self.manager
+    }
+
+    /// Returns the number of the last journaled entry, the one the current
+    /// state includes.
+    pub fn last_seq(&self) -> u64 {
+        self.journal.last_seq()
+    }
+
+    /// Returns the tracked state and the journal.
+    pub fn into_parts(self) -> (PisteManager, JournalWriter<W>) {
+        (self.manager, self.journal)
+    }
+}
+
+/// Reads the entries of a journal.
+///
+/// A last line without its end of line was cut short by a crash while being
+/// written, and is left out: its message was never applied.
+///
+/// # Errors
+///
+/// Returns `JournalError` if the reader fails, a line is not a journal entry,
+/// or the entries are not numbered one after the other.
+pub fn read<R: BufRead>(mut reader: R) -> Result<Vec<JournalEntry>, JournalError> {
+    let mut entries: Vec<JournalEntry> = Vec::new();
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || !line.ends_with('\n') {
+            return Ok(entries);
+        }
+        line_number += 1;
+
+        let text = line.trim_end();
+        let entry = JournalEntry::parse_line(text).ok_or_else(|| JournalError::InvalidLine {
+            line_number,
+            line: text.to_string(),
+        })?;
+        let expected = entries.last().map_or(entry.seq, |last| last.seq + 1);
+        if entry.seq != expected {
+            return Err(JournalError::OutOfSequence { expected, found: entry.seq });
+        }
+        entries.push(entry);
+    }
+}
+
+/// Applies the journal entries numbered after `after_seq` to a manager.
+///
+/// Start from an empty manager with `after_seq` 0, or from a snapshot with
+/// the sequence number it was taken at. Apply the entries to the snapshot
+/// itself rather than to [`PisteManager::resume_from`]: nothing was missed
+/// between the two, so the state comes back exactly as it was.
+///
+/// # Returns
+///
+/// The number of the last entry applied, `after_seq` if there is none.
+///
+/// # Errors
+///
+/// Returns `JournalError::InvalidFrame` if a journaled frame cannot be parsed;
+/// the entries before it remain applied.
+pub fn replay<'a>(
+    manager: &mut PisteManager,
+    entries: impl IntoIterator<Item = &'a JournalEntry>,
+    after_seq: u64,
+) -> Result<u64, JournalError> {
+    let mut last_seq = after_seq;
+    for entry in entries.into_iter().filter(|entry| entry.seq > after_seq) {
+        let message = entry
+            .message()
+            .map_err(|error| JournalError::InvalidFrame { seq: entry.seq, error })?;
+        manager.apply(&message);
+        last_seq = entry.seq;
+    }
+    Ok(last_seq)
+}
+
+/// A journal entry and the events it produced on a piste.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuditStep {
+    /// Number of the journal entry.
+    pub seq: u64,
+    /// Time the message was applied, in milliseconds.
+    pub timestamp_ms: u64,
+    /// Events the message produced.
+    pub events: Vec<MatchEvent>,
+}
+
+/// Replays a journal from the start and returns, for one piste, every entry
+/// that changed something, with the events it produced: how the scoreboard
+/// got to where it is.
+///
+/// # Errors
+///
+/// Returns `JournalError::InvalidFrame` if a journaled frame cannot be parsed.
+pub fn audit<'a>(
+    entries: impl IntoIterator<Item = &'a JournalEntry>,
+    piste: &str,
+) -> Result<Vec<AuditStep>, JournalError> {
+    let piste = PisteId::from(piste);
+    let mut manager = PisteManager::new();
+    let mut steps = Vec::new();
+    for entry in entries {
+        let message = entry
+            .message()
+            .map_err(|error| JournalError::InvalidFrame { seq: entry.seq, error })?;
+        let events = manager.apply(&message);
+        if !events.is_empty() && PisteId::from(message.piste.as_str()) == piste {
+            steps.push(AuditStep {
+                seq: entry.seq,
+                timestamp_ms: entry.timestamp_ms,
+                events,
+            });
+        }
+    }
+    Ok(steps)
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::Side;
+    use crate::fixtures;
+
+    #[test]
+    fn test_recover_from_snapshot_and_torn_journal() {
+        let mut timeline = fixtures::bout_timeline("4", 1);
+        timeline.extend(fixtures::bout_timeline("9", 2));
+        let mut pistes = Journaled::new(PisteManager::new(), JournalWriter::new(Vec::new()));
+        let mut snapshot = None;
+        for (index, message) in timeline.iter().enumerate() {
+            pistes.apply(message, index as u64 * 100).unwrap();
+            if index == timeline.len() / 2 {
+                snapshot = Some((pistes.manager().clone(), pistes.last_seq()));
+            }
+        }
+        let (live, writer) = pistes.into_parts();
+        let mut journal = writer.into_inner();
+        // The software crashed while writing one more entry.
+        journal.extend_from_slice(b"999 123 |EFP1.1|IN");
+
+        let entries = read(journal.as_slice()).unwrap();
+        assert_eq!(entries.len(), timeline.len());
+        let (mut recovered, seq) = snapshot.unwrap();
+        assert_eq!(replay(&mut recovered, &entries, seq).unwrap(), timeline.len() as u64);
+        for piste in ["4", "9"] {
+            let (expected, actual) = (live.get(piste).unwrap(), recovered.get(piste).unwrap());
+            assert_eq!(actual.message(), expected.message());
+            assert_eq!(actual.score(Side::Left), expected.score(Side::Left));
+        }
+
+        // Every score change of piste 4 is in the audit trail.
+        let steps = audit(&entries, "4").unwrap();
+        let score_changes = steps
+            .iter()
+            .flat_map(|step| &step.events)
+            .filter(|event| matches!(event, MatchEvent::ScoreChanged { .. } | MatchEvent::DoubleTouch { .. }))
+            .count();
+        assert!(score_changes > 0);
+
+        let gap = format!("{}\n{}\n", entries[0], JournalEntry { seq: 3, ..entries[1].clone() });
+        assert!(matches!(
+            read(gap.as_bytes()),
+            Err(JournalError::OutOfSequence { expected: 2, found: 3 })
+        ));
+    }
+}
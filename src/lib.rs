@@ -12,6 +12,10 @@
 //! - Type-safe representation of all protocol fields
 //! - Support for all fencing weapons (Foil, Épée, Sabre)
 //! - Comprehensive error handling
+//! - Optional `tracing` spans and events for parsing and sessions (feature `tracing`)
+//! - Parallel parsing of large log archives (feature `rayon`)
+//! - Memory-mapped reading of log archives (feature `mmap`)
+//! - Inline storage of short text fields (feature `small-strings`)
 //!
 //! ## Quick Start
 //!
@@ -43,10 +47,68 @@
 //! ## Modules
 //!
 //! - [`message`] - The main `Message` type and parsing logic
+//! - [`borrowed`] - `MessageRef`, a parsed message borrowing its text from the frame
+//! - [`hello`] - Typed HELLO payload and per-command message kinds
+//! - [`inspect`] - Field iteration and access by name for generic tools
 //! - [`error`] - Error types for parsing failures
+//! - [`options`] - Parsing options (lenient or strict) and text policies
+//! - [`limits`] - Field length limits and forbidden characters
+//! - [`builder`] - Step-by-step construction of outgoing messages
+//! - [`history`] - Undo and redo of the modifications made to a composed message
+//! - [`templates`] - Prefilled messages for common exchanges
+//! - [`corpus`] - Sample frames for tests and fuzzing
+//! - [`fixtures`] - Generated bouts and day-long logs for tests and benchmarks
 //! - [`enums`] - Enumerations for protocol values (commands, weapons, states, etc.)
+//! - [`locale`] - Localized display names of protocol values
 //! - [`fencer`] - Fencer information and data structures
-//! - [`referee`] - Referee information
+//! - [`names`] - Splitting of name fields into first name and surname, and display styles
+//! - [`nation`] - IOC nation codes with their flag emoji and localized country names
+//! - [`referee`] - Referee information and assignment history
+//! - [`phase`] - Pool, tableau and team relay interpretation of bout fields
+//! - [`rules`] - Rule profiles of competition categories, and referee decisions drawn at random such as priority
+//! - [`assignment`] - Bout assignments used to compose NEXT/PREV messages
+//! - [`team`] - Teams and their rosters for team matches
+//! - [`directory`] - Fencer directory lookups adding full names, clubs, rankings and photos
+//! - [`piste`] - Piste identifiers with natural ordering
+//! - [`schedule`] - Per-piste queues of upcoming bouts
+//! - [`session`] - Role-aware protocol session (apparatus or software side)
+//! - [`remote`] - Typed DISP commands setting score, time and cards on the apparatus
+//! - [`state`] - Live per-piste bout state and change events
+//! - [`summary`] - Bout summaries emitted at the end of each bout
+//! - [`highlight`] - Broadcast highlights such as match points and comebacks
+//! - [`stats`] - Per-fencer and per-bout statistics from a bout timeline
+//! - [`touches`] - Attribution of touches from the lights, scores and cards of successive frames
+//! - [`competition`] - Venue-wide tracking across competitions and pistes, and competition names
+//! - [`import`] - Piste assignments imported from Engarde and Ophardt exports
+//! - [`logfile`] - Timestamped logs of exchanged frames
+//! - [`recording`] - Indexed binary recordings for fast seeking during replay
+//! - [`journal`] - Write-ahead journal of the messages applied to the state tracker, for recovery and audit
+//! - [`merge`] - Time-ordered merge of several piste streams
+//! - [`group`] - Sorting and grouping of message collections, and per-bout slicing of logs
+//! - [`analysis`] - Bout segmentation of message streams and protocol anomaly checks on captures
+//! - [`report`] - Health report of a capture, used by `cyrano stats`
+//! - [`diff`] - Alignment and comparison of two captures, used by `cyrano diff`
+//! - [`coalesce`] - Suppression of repeated INFO frames and clock update rate limiting
+//! - [`lights`] - Debouncing of flickering lights into timed light periods
+//! - [`traffic`] - Per-piste link statistics and their Prometheus rendering
+//! - [`skew`] - Apparatus clock offset and drift estimation, break and injury countdowns
+//! - [`conformance`] - Protocol conformance checks for apparatus vendors
+//! - [`simulator`] - Scripted bouts compiled into timed message sequences
+//! - [`trailer`] - Checksum trailers appended after frames by some bridges
+//! - [`sequence`] - Sequence-numbered frames: wraparound checks and reordering
+//! - [`stream`] - Frame splitting for byte streams
+//! - [`net`] - UDP and TCP endpoints exchanging messages, multicast listening and piste discovery
+//! - [`proxy`] - Relay fanning an apparatus feed out to several consumers
+//! - [`loadgen`] - Simulated venue of pistes for stress-testing software, used by `cyrano loadgen`
+//! - [`output`] - HTML and other presentation renderers
+//! - `tui` - Terminal scoreboard widget (feature `tui`)
+//! - `http` - REST and server-sent events bridge (feature `http`)
+//! - `graphql` - GraphQL queries and subscriptions served by the HTTP bridge (feature `graphql`)
+//! - `fie` - FIE competition XML interop (feature `fie`)
+//! - `arrow` - Arrow record batch and Parquet export of message logs (feature `arrow`)
+//! - `proto` - Protobuf encoding matching `proto/cyrano.proto` (feature `protobuf`)
+//! - `msgpack` - Compact MessagePack encoding for constrained links (feature `msgpack`)
+//! - `mdns` - Zeroconf advertisement and discovery of endpoints (feature `mdns`)
 //!
 //! ## Examples
 //!
@@ -67,14 +129,86 @@
 //! ```
 
 pub mod message;
+pub mod borrowed;
+pub mod hello;
+pub mod inspect;
 pub mod error;
+pub mod options;
+pub mod limits;
+pub mod builder;
+pub mod history;
+pub mod templates;
+pub mod corpus;
+pub mod fixtures;
 pub mod enums;
+pub mod locale;
 pub mod fencer;
+pub mod names;
+pub mod nation;
 pub mod referee;
+pub mod phase;
+pub mod rules;
+pub mod assignment;
+pub mod team;
+pub mod directory;
+pub mod piste;
+pub mod schedule;
+pub mod session;
+pub mod remote;
+pub mod state;
+pub mod summary;
+pub mod highlight;
+pub mod stats;
+pub mod touches;
+pub mod competition;
+pub mod import;
+pub mod logfile;
+pub mod recording;
+pub mod journal;
+pub mod merge;
+pub mod group;
+pub mod analysis;
+pub mod report;
+pub mod diff;
+pub mod coalesce;
+pub mod lights;
+pub mod traffic;
+pub mod skew;
+pub mod conformance;
+pub mod simulator;
+pub mod trailer;
+pub mod sequence;
+pub mod stream;
+pub mod net;
+pub mod proxy;
+pub mod loadgen;
+pub mod output;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "fie")]
+pub mod fie;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+#[cfg(feature = "mdns")]
+pub mod mdns;
 mod utils;
 
 // Re-export main types for convenience
 pub use message::Message;
-pub use error::ParseError;
+pub use builder::MessageBuilder;
+pub use error::{BuildError, ParseError, ParseWarning, SessionError, ValueError};
 pub use referee::Referee;
 pub use fencer::Fencer;
+pub use assignment::MatchAssignment;
+pub use session::{LinkEvent, LinkHealth, ProtocolSession, Role, SendHandle, SendOutcome};
+pub use state::{MatchState, PisteManager};
+pub use competition::CompetitionTracker;
+pub use corpus::corpus;
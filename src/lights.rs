@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+use crate::enums::Side;
+use crate::group::CollectedMessage;
+use crate::message::Message;
+use crate::piste::PisteId;
+
+/// Default delay a light must stay off before it is considered off, in milliseconds.
+pub const DEFAULT_DEBOUNCE_MS: u64 = 200;
+
+/// The lights of a fencer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LightKind {
+    /// The coloured light of a valid touch (`light` field).
+    Scoring,
+    /// The white light of an off-target hit at foil (`white_light` field).
+    OffTarget,
+}
+
+/// The lights of a piste, in the order they are reported.
+const LAMPS: [(Side, LightKind); 4] = [
+    (Side::Right, LightKind::Scoring),
+    (Side::Right, LightKind::OffTarget),
+    (Side::Left, LightKind::Scoring),
+    (Side::Left, LightKind::OffTarget),
+];
+
+/// A light that was on for a while, once debounced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LightOn {
+    /// Piste of the light.
+    pub piste: PisteId,
+    /// Fencer the light belongs to.
+    pub side: Side,
+    /// Which light it is.
+    pub kind: LightKind,
+    /// Time the light came on, in milliseconds.
+    pub at_ms: u64,
+    /// How long the light stayed on, in milliseconds.
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lamp {
+    Off,
+    On { since_ms: u64 },
+    /// Reported off, but not for long enough to be considered off.
+    Dimming { since_ms: u64, off_ms: u64 },
+}
+
+impl Lamp {
+    /// Moves to the reported value and returns the start and duration of the
+    /// period that ended, if any.
+    fn update(&mut self, on: bool, now_ms: u64, debounce_ms: u64) -> Option<(u64, u64)> {
+        let ended = self.settle(now_ms, debounce_ms);
+        *self = match (*self, on) {
+            (Lamp::Off, true) => Lamp::On { since_ms: now_ms },
+            (Lamp::On { since_ms }, false) => Lamp::Dimming { since_ms, off_ms: now_ms },
+            (Lamp::Dimming { since_ms, .. }, true) => Lamp::On { since_ms },
+            (lamp, _) => lamp,
+        };
+        ended.or_else(|| self.settle(now_ms, debounce_ms))
+    }
+
+    /// Turns the light off if it has been reported off for the debounce delay.
+    fn settle(&mut self, now_ms: u64, debounce_ms: u64) -> Option<(u64, u64)> {
+        match *self {
+            Lamp::Dimming { since_ms, off_ms } if now_ms.saturating_sub(off_ms) >= debounce_ms => {
+                *self = Lamp::Off;
+                Some((since_ms, off_ms - since_ms))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Turns the light fields of successive frames into clean light periods.
+///
+/// Around a touch, apparatus often report a light going off and back on over
+/// a few frames. Each light of each fencer is tracked by a small state
+/// machine: a light reported off comes back on without a break if it is
+/// reported on again within the debounce delay, and is only considered off
+/// once that delay has elapsed. A [`LightOn`] is then returned with the time
+/// the light came on and how long it stayed on.
+///
+/// Scoring and white lights are tracked separately. Frames without fencer
+/// zones leave the lights as they were. The tracker does not perform any
+/// I/O: callers offer the messages they receive, with the time of reception.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Side;
+/// use cyrano::lights::{LightKind, LightTracker};
+/// use cyrano::message::Message;
+///
+/// let frame = |light: u8| {
+///     let raw = format!("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|0|0|{}|%|32|B. Panini|ITA|2|U|%|", light);
+///     Message::try_from(raw).unwrap()
+/// };
+///
+/// let mut tracker = LightTracker::new().debounce_ms(200);
+/// assert!(tracker.offer(&frame(1), 1000).is_empty());
+/// // A flicker shorter than the debounce delay is ignored.
+/// assert!(tracker.offer(&frame(0), 1500).is_empty());
+/// assert!(tracker.offer(&frame(1), 1550).is_empty());
+/// assert!(tracker.offer(&frame(0), 3000).is_empty());
+///
+/// let lights = tracker.flush(3200);
+/// assert_eq!((lights[0].side, lights[0].kind), (Side::Right, LightKind::Scoring));
+/// assert_eq!((lights[0].at_ms, lights[0].duration_ms), (1000, 2000));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LightTracker {
+    debounce_ms: u64,
+    pistes: HashMap<PisteId, [Lamp; 4]>,
+}
+
+impl Default for LightTracker {
+    fn default() -> Self {
+        LightTracker::new()
+    }
+}
+
+impl LightTracker {
+    /// Creates a tracker with the default debounce delay.
+    pub fn new() -> Self {
+        LightTracker {
+            debounce_ms: DEFAULT_DEBOUNCE_MS,
+            pistes: HashMap::new(),
+        }
+    }
+
+    /// Sets the delay a light must stay off before it is considered off.
+    pub fn debounce_ms(mut self, debounce_ms: u64) -> Self {
+        self.debounce_ms = debounce_ms;
+        self
+    }
+
+    /// Offers a received message and returns the lights of its piste that
+    /// have now been off for the debounce delay.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message received
+    /// * `now_ms` - Time of reception in milliseconds, on the clock given to [`flush`](LightTracker::flush)
+    pub fn offer(&mut self, message: &Message, now_ms: u64) -> Vec<LightOn> {
+        if !message.carries_bout() {
+            return Vec::new();
+        }
+
+        let piste = PisteId::from(message.piste.as_str());
+        let lamps = self.pistes.entry(piste.clone()).or_insert([Lamp::Off; 4]);
+        let mut lights = Vec::new();
+        for ((side, kind), lamp) in LAMPS.into_iter().zip(lamps.iter_mut()) {
+            let fencer = message.fencer(side);
+            let reported = match kind {
+                LightKind::Scoring => fencer.light,
+                LightKind::OffTarget => fencer.white_light,
+            };
+            let ended = match reported {
+                Some(on) => lamp.update(on, now_ms, self.debounce_ms),
+                None => lamp.settle(now_ms, self.debounce_ms),
+            };
+            if let Some((at_ms, duration_ms)) = ended {
+                lights.push(LightOn {
+                    piste: piste.clone(),
+                    side,
+                    kind,
+                    at_ms,
+                    duration_ms,
+                });
+            }
+        }
+        lights
+    }
+
+    /// Returns the lights of every piste that have now been off for the
+    /// debounce delay, in the order they went off.
+    ///
+    /// Call it regularly so that a light is reported even when the apparatus
+    /// stops sending right after switching it off.
+    pub fn flush(&mut self, now_ms: u64) -> Vec<LightOn> {
+        let mut lights = Vec::new();
+        for (piste, lamps) in &mut self.pistes {
+            for ((side, kind), lamp) in LAMPS.into_iter().zip(lamps.iter_mut()) {
+                if let Some((at_ms, duration_ms)) = lamp.settle(now_ms, self.debounce_ms) {
+                    lights.push(LightOn {
+                        piste: piste.clone(),
+                        side,
+                        kind,
+                        at_ms,
+                        duration_ms,
+                    });
+                }
+            }
+        }
+        lights.sort_by(|a, b| (a.at_ms + a.duration_ms, &a.piste).cmp(&(b.at_ms + b.duration_ms, &b.piste)));
+        lights
+    }
+}
+
+/// Returns the debounced light periods of a timestamped log, for replay and
+/// analytics.
+///
+/// Messages without a receipt time are skipped. Lights still on at the end of
+/// the log are left out, since their duration is unknown.
+///
+/// # Returns
+///
+/// The light periods, in the order they ended.
+pub fn light_periods<'a, T, I>(messages: I, debounce_ms: u64) -> Vec<LightOn>
+where
+    T: CollectedMessage + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut tracker = LightTracker::new().debounce_ms(debounce_ms);
+    let mut lights = Vec::new();
+    for item in messages {
+        if let Some(timestamp_ms) = item.timestamp_ms() {
+            lights.extend(tracker.offer(item.message(), timestamp_ms));
+        }
+    }
+    lights.extend(tracker.flush(u64::MAX));
+    lights
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_debounced_light_periods() {
+        let frame = |time: u64, right: &str, left: &str| {
+            let raw = format!("|EFP1.1|INFO|5|fm-eq|1|P3|1||2:12|||F||F|%|28|A|FRA|0|U|0|0|{}|%|32|B|ITA|0|U|0|0|{}|%|", right, left);
+            (time, Message::try_from(raw).unwrap())
+        };
+        let log = vec![
+            frame(0, "0|0", "0|0"),
+            frame(100, "1|0", "0|1"),
+            frame(150, "0|0", "0|1"),
+            frame(200, "1|0", "0|0"),
+            // The left white light went off at 200, for good.
+            frame(500, "1|0", "0|0"),
+            frame(900, "0|0", "0|0"),
+            frame(950, "0|0", "0|1"),
+        ];
+
+        let lights: Vec<(Side, LightKind, u64, u64)> = light_periods(&log, 100)
+            .iter()
+            .map(|light| (light.side, light.kind, light.at_ms, light.duration_ms))
+            .collect();
+        assert_eq!(
+            lights,
+            [
+                (Side::Left, LightKind::OffTarget, 100, 100),
+                (Side::Right, LightKind::Scoring, 100, 800),
+            ]
+        );
+
+        // Without debouncing, every flicker is a period of its own.
+        assert_eq!(light_periods(&log, 0).len(), 3);
+    }
+}
@@ -0,0 +1,103 @@
+use super::error::ValueError;
+
+/// Characters that may never appear inside a field value.
+///
+/// `|` separates fields and `%` separates zones, so a value containing either
+/// would corrupt the frame.
+pub const FORBIDDEN_CHARACTERS: [char; 2] = ['|', '%'];
+
+/// Maximum length of the piste identifier.
+pub const MAX_PISTE_LENGTH: usize = 8;
+/// Maximum length of the competition identifier.
+pub const MAX_COMPETITION_ID_LENGTH: usize = 8;
+/// Maximum length of the pool or tableau identifier.
+pub const MAX_POOL_TABLEAU_LENGTH: usize = 8;
+/// Maximum length of the match time and stopwatch, such as `"1:23:45"`.
+pub const MAX_TIME_LENGTH: usize = 8;
+/// Maximum length of a fencer or referee identifier.
+pub const MAX_ID_LENGTH: usize = 8;
+/// Maximum length of a fencer or referee name.
+pub const MAX_NAME_LENGTH: usize = 20;
+/// Maximum length of a nation code.
+pub const MAX_NATION_LENGTH: usize = 3;
+
+/// Checks a field value against the character set and a maximum length.
+///
+/// # Arguments
+///
+/// * `field` - Name of the field for error reporting
+/// * `value` - The value to check
+/// * `max` - Maximum length in characters
+///
+/// # Errors
+///
+/// Returns `ValueError::ForbiddenCharacter` if the value contains `|` or `%`,
+/// or `ValueError::TooLong` if it exceeds `max` characters.
+pub fn check(field: &'static str, value: &str, max: usize) -> Result<(), ValueError> {
+    if let Some(character) = value.chars().find(|c| FORBIDDEN_CHARACTERS.contains(c)) {
+        return Err(ValueError::ForbiddenCharacter { field, character });
+    }
+
+    if value.chars().count() > max {
+        return Err(ValueError::TooLong {
+            field,
+            max,
+            value: value.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks an optional field value. Absent values are always valid.
+///
+/// # Errors
+///
+/// See [`check`].
+pub fn check_optional<S: AsRef<str>>(field: &'static str, value: &Option<S>, max: usize) -> Result<(), ValueError> {
+    match value {
+        Some(value) => check(field, value.as_ref(), max),
+        None => Ok(()),
+    }
+}
+
+/// Sanitizes an optional field value in place.
+pub fn sanitize_optional<S: AsRef<str> + From<String>>(value: &mut Option<S>, max: usize) {
+    if let Some(value) = value {
+        *value = sanitize(value.as_ref(), max).into();
+    }
+}
+
+/// Makes a value safe to transmit.
+///
+/// Forbidden characters are replaced by spaces and the value is truncated to
+/// `max` characters.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::limits::{sanitize, MAX_NAME_LENGTH};
+///
+/// assert_eq!(sanitize("O|Brien", MAX_NAME_LENGTH), "O Brien");
+/// assert_eq!(sanitize("Montgomery-Fitzwilliam", MAX_NAME_LENGTH), "Montgomery-Fitzwilli");
+/// ```
+pub fn sanitize(value: &str, max: usize) -> String {
+    value.chars().take(max).map(escape).collect()
+}
+
+/// Replaces forbidden characters by spaces, without truncating.
+pub fn neutralize(value: &str) -> String {
+    if value.contains(FORBIDDEN_CHARACTERS) {
+        value.chars().map(escape).collect()
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape(c: char) -> char {
+    if FORBIDDEN_CHARACTERS.contains(&c) {
+        ' '
+    } else {
+        c
+    }
+}
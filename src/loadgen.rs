@@ -0,0 +1,352 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::enums::Command;
+use crate::fixtures;
+use crate::message::Message;
+use crate::net::MAX_DATAGRAM_SIZE;
+use crate::session::{ProtocolSession, Role};
+use crate::simulator::Simulator;
+
+/// Default delay after which an apparatus repeats its current INFO, in milliseconds.
+pub const DEFAULT_INFO_INTERVAL_MS: u64 = 1000;
+
+/// Bout time between two bouts on a simulated piste, in milliseconds.
+const CHANGEOVER_MS: u64 = 10_000;
+
+/// Outcome of a load run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LoadReport {
+    /// Number of simulated pistes.
+    pub pistes: usize,
+    /// Length of the run, in milliseconds.
+    pub duration_ms: u64,
+    /// Frames sent by the simulated apparatus, replies excluded.
+    pub frames_sent: u64,
+    /// ACK and NAK sent in answer to the system under test.
+    pub replies_sent: u64,
+    /// HELLO acknowledged by the system under test.
+    pub acks: u64,
+    /// HELLO rejected by the system under test.
+    pub naks: u64,
+    /// HELLO left unanswered past the acknowledgment timeout or the end of the run.
+    pub unanswered: u64,
+    /// Delay between each acknowledged HELLO and its ACK, in microseconds.
+    pub ack_latencies_us: Vec<u64>,
+}
+
+impl LoadReport {
+    /// Returns the frames sent per second over the run.
+    pub fn frames_per_second(&self) -> f64 {
+        if self.duration_ms == 0 {
+            0.0
+        } else {
+            self.frames_sent as f64 * 1000.0 / self.duration_ms as f64
+        }
+    }
+
+    /// Returns the acknowledgment latency below which `percentile` percent of
+    /// the samples fall, or `None` without samples.
+    pub fn latency_percentile_us(&self, percentile: f64) -> Option<u64> {
+        let mut latencies = self.ack_latencies_us.clone();
+        latencies.sort_unstable();
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * latencies.len() as f64).ceil() as usize;
+        latencies.get(rank.saturating_sub(1)).copied()
+    }
+}
+
+impl Display for LoadReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} pistes for {} ms: {} frames sent ({:.1}/s), {} replies",
+            self.pistes,
+            self.duration_ms,
+            self.frames_sent,
+            self.frames_per_second(),
+            self.replies_sent
+        )?;
+        writeln!(f, "HELLO: {} acked, {} nacked, {} unanswered", self.acks, self.naks, self.unanswered)?;
+        match [50.0, 95.0, 99.0, 100.0].map(|percentile| self.latency_percentile_us(percentile)) {
+            [Some(p50), Some(p95), Some(p99), Some(max)] => writeln!(
+                f,
+                "ack latency: p50 {} us, p95 {} us, p99 {} us, max {} us",
+                p50, p95, p99, max
+            ),
+            _ => writeln!(f, "ack latency: no sample"),
+        }
+    }
+}
+
+/// Load generator simulating a venue of pistes against a scoreboard or
+/// competition management software.
+///
+/// Each simulated piste has its own UDP socket and plays generated bouts one
+/// after the other (see [`fixtures::bout_script`]), repeating its current INFO
+/// between events like a real apparatus. It also runs the machine side of the
+/// protocol: it sends a HELLO every heartbeat interval, measures how long the
+/// system under test takes to acknowledge it, and acknowledges the DISP,
+/// NEXT and PREV it receives.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use cyrano::loadgen::LoadGenerator;
+///
+/// let report = LoadGenerator::new("10.0.0.5:50100".parse().unwrap())
+///     .pistes(30)
+///     .speed(4)
+///     .run(Duration::from_secs(60))?;
+/// println!("{}", report);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoadGenerator {
+    target: SocketAddr,
+    pistes: usize,
+    speed: u64,
+    info_interval_ms: u64,
+    heartbeat_interval_ms: Option<u64>,
+}
+
+impl LoadGenerator {
+    /// Creates a generator of one piste sending to the system under test at `target`.
+    pub fn new(target: SocketAddr) -> Self {
+        LoadGenerator {
+            target,
+            pistes: 1,
+            speed: 1,
+            info_interval_ms: DEFAULT_INFO_INTERVAL_MS,
+            heartbeat_interval_ms: None,
+        }
+    }
+
+    /// Sets the number of simulated pistes, named `1`, `2` and so on.
+    pub fn pistes(mut self, pistes: usize) -> Self {
+        self.pistes = pistes;
+        self
+    }
+
+    /// Plays bouts `factor` times faster than real time, for more events per second.
+    pub fn speed(mut self, factor: u64) -> Self {
+        self.speed = factor.max(1);
+        self
+    }
+
+    /// Sets the delay after which an apparatus repeats its current INFO.
+    pub fn info_interval_ms(mut self, interval_ms: u64) -> Self {
+        self.info_interval_ms = interval_ms;
+        self
+    }
+
+    /// Sets the delay between two HELLO of a piste.
+    pub fn heartbeat_interval_ms(mut self, interval_ms: u64) -> Self {
+        self.heartbeat_interval_ms = Some(interval_ms);
+        self
+    }
+
+    /// Runs the simulated pistes for `duration` and reports what was measured.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of a socket. A system under test that is not
+    /// listening is not an error: its HELLO are reported as unanswered.
+    pub fn run(&self, duration: Duration) -> io::Result<LoadReport> {
+        let mut pistes = (1..=self.pistes)
+            .map(|index| SimulatedPiste::open(self, index))
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut report = LoadReport {
+            pistes: self.pistes,
+            ..LoadReport::default()
+        };
+        let mut buffer = vec![0; MAX_DATAGRAM_SIZE];
+
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            let now_ms = start.elapsed().as_millis() as u64;
+            for piste in &mut pistes {
+                piste.send_due(self, now_ms, &mut report)?;
+                piste.receive(now_ms, &mut buffer, &mut report)?;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        report.duration_ms = start.elapsed().as_millis() as u64;
+        report.unanswered += pistes.iter().map(|piste| piste.hellos.len() as u64).sum::<u64>();
+        Ok(report)
+    }
+}
+
+/// State of one simulated apparatus.
+struct SimulatedPiste {
+    name: String,
+    socket: UdpSocket,
+    session: ProtocolSession,
+    simulator: Simulator,
+    bout: u64,
+    bout_start_ms: u64,
+    bout_length_ms: u64,
+    current: Option<Message>,
+    last_sent_ms: u64,
+    hellos: VecDeque<Instant>,
+}
+
+impl SimulatedPiste {
+    fn open(generator: &LoadGenerator, index: usize) -> io::Result<Self> {
+        let local: SocketAddr = if generator.target.is_ipv4() {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(generator.target)?;
+        socket.set_nonblocking(true)?;
+
+        let name = index.to_string();
+        let mut session = ProtocolSession::new(Role::Machine, name.as_str());
+        if let Some(interval_ms) = generator.heartbeat_interval_ms {
+            session = session.heartbeat_interval_ms(interval_ms);
+        }
+        let mut piste = SimulatedPiste {
+            name,
+            socket,
+            session,
+            simulator: Simulator::from_frames(Vec::new()),
+            bout: index as u64 * 1000,
+            bout_start_ms: 0,
+            bout_length_ms: 0,
+            current: None,
+            last_sent_ms: 0,
+            hellos: VecDeque::new(),
+        };
+        piste.start_bout(0);
+        Ok(piste)
+    }
+
+    fn start_bout(&mut self, bout_ms: u64) {
+        // Generated clocks never go up, so the script always compiles.
+        let frames = fixtures::bout_script(&self.name, self.bout).compile().unwrap_or_default();
+        self.bout += 1;
+        self.bout_start_ms = bout_ms;
+        self.bout_length_ms = frames.last().map_or(0, |frame| frame.at_ms);
+        self.simulator = Simulator::from_frames(frames);
+    }
+
+    fn send_due(&mut self, generator: &LoadGenerator, now_ms: u64, report: &mut LoadReport) -> io::Result<()> {
+        if let Some(hello) = self.session.heartbeat(now_ms) {
+            self.send(&hello)?;
+            self.hellos.push_back(Instant::now());
+        }
+        for _ in self.session.expire(now_ms) {
+            self.hellos.pop_front();
+            report.unanswered += 1;
+        }
+
+        let bout_ms = now_ms * generator.speed;
+        if self.simulator.is_finished() && bout_ms >= self.bout_start_ms + self.bout_length_ms + CHANGEOVER_MS {
+            self.start_bout(bout_ms);
+        }
+
+        let mut due = self.simulator.poll(bout_ms - self.bout_start_ms);
+        if due.is_empty() && now_ms.saturating_sub(self.last_sent_ms) >= generator.info_interval_ms {
+            due.extend(self.current.clone());
+        }
+        for message in due {
+            self.send(&message)?;
+            report.frames_sent += 1;
+            self.last_sent_ms = now_ms;
+            self.current = Some(message);
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, now_ms: u64, buffer: &mut [u8], report: &mut LoadReport) -> io::Result<()> {
+        loop {
+            let len = match self.socket.recv(buffer) {
+                Ok(len) => len,
+                // A closed port on the target is reported on the next read of a connected socket.
+                Err(err) if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::ConnectionRefused) => {
+                    return Ok(())
+                }
+                Err(err) => return Err(err),
+            };
+            let Some(message) = std::str::from_utf8(&buffer[..len]).ok().and_then(|text| Message::try_from(text).ok()) else {
+                continue;
+            };
+
+            if matches!(message.command, Command::Ack | Command::Nak) {
+                if let Some(sent) = self.hellos.pop_front() {
+                    if message.command == Command::Ack {
+                        report.acks += 1;
+                        report.ack_latencies_us.push(sent.elapsed().as_micros() as u64);
+                    } else {
+                        report.naks += 1;
+                    }
+                }
+            }
+            if let Some(reply) = self.session.receive(&message, now_ms) {
+                self.send(&reply)?;
+                report.replies_sent += 1;
+            }
+        }
+    }
+
+    fn send(&self, message: &Message) -> io::Result<()> {
+        match self.socket.send(message.to_string().as_bytes()) {
+            Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_against_an_acknowledging_software() {
+        let software = UdpSocket::bind("127.0.0.1:0").unwrap();
+        software.set_read_timeout(Some(Duration::from_millis(50))).unwrap();
+        let target = software.local_addr().unwrap();
+        let responder = thread::spawn(move || {
+            let mut buffer = [0; MAX_DATAGRAM_SIZE];
+            let deadline = Instant::now() + Duration::from_millis(600);
+            let mut received = 0;
+            while Instant::now() < deadline {
+                let Ok((len, from)) = software.recv_from(&mut buffer) else { continue };
+                received += 1;
+                let message = Message::try_from(std::str::from_utf8(&buffer[..len]).unwrap()).unwrap();
+                if message.command == Command::Hello {
+                    let ack = Message::new(Command::Ack, message.piste.to_string(), message.competition_id.to_string());
+                    software.send_to(ack.to_string().as_bytes(), from).unwrap();
+                }
+            }
+            received
+        });
+
+        let report = LoadGenerator::new(target)
+            .pistes(3)
+            .speed(100)
+            .heartbeat_interval_ms(100)
+            .run(Duration::from_millis(400))
+            .unwrap();
+        let received = responder.join().unwrap();
+
+        assert_eq!(report.pistes, 3);
+        assert!(report.frames_sent > 10, "{}", report);
+        assert!(received as u64 >= report.frames_sent);
+        assert!(report.acks >= 3, "{}", report);
+        assert_eq!(report.ack_latencies_us.len() as u64, report.acks);
+        assert!(report.latency_percentile_us(50.0) <= report.latency_percentile_us(100.0));
+        assert!(report.to_string().contains("ack latency: p50"));
+    }
+}
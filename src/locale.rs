@@ -0,0 +1,198 @@
+use crate::competition::{Gender, WheelchairCategory};
+use crate::enums::{ApparatusState, FencerStatus, PCard, Weapon};
+
+/// Language of the names shown to spectators and officials.
+///
+/// English is always available; other locales are enabled by a feature each
+/// (`locale-fr` for French).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Locale {
+    /// English.
+    #[default]
+    English,
+    /// French (feature `locale-fr`).
+    #[cfg(feature = "locale-fr")]
+    French,
+}
+
+impl Weapon {
+    /// Returns the name of the weapon in the given language.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::enums::Weapon;
+    /// use cyrano::locale::Locale;
+    ///
+    /// assert_eq!(Weapon::Epee.display_name(Locale::English), "Épée");
+    /// ```
+    pub fn display_name(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::English => match self {
+                Weapon::Foil => "Foil",
+                Weapon::Epee => "Épée",
+                Weapon::Sabre => "Sabre",
+            },
+            #[cfg(feature = "locale-fr")]
+            Locale::French => match self {
+                Weapon::Foil => "Fleuret",
+                Weapon::Epee => "Épée",
+                Weapon::Sabre => "Sabre",
+            },
+        }
+    }
+}
+
+impl Gender {
+    /// Returns the name of the gender category in the given language.
+    pub fn display_name(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::English => match self {
+                Gender::Men => "Men",
+                Gender::Women => "Women",
+                Gender::Mixed => "Mixed",
+            },
+            #[cfg(feature = "locale-fr")]
+            Locale::French => match self {
+                Gender::Men => "Hommes",
+                Gender::Women => "Dames",
+                Gender::Mixed => "Mixte",
+            },
+        }
+    }
+}
+
+impl WheelchairCategory {
+    /// Returns the name of the wheelchair category in the given language.
+    pub fn display_name(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::English => match self {
+                WheelchairCategory::A => "Category A",
+                WheelchairCategory::B => "Category B",
+                WheelchairCategory::C => "Category C",
+            },
+            #[cfg(feature = "locale-fr")]
+            Locale::French => match self {
+                WheelchairCategory::A => "Catégorie A",
+                WheelchairCategory::B => "Catégorie B",
+                WheelchairCategory::C => "Catégorie C",
+            },
+        }
+    }
+}
+
+impl ApparatusState {
+    /// Returns the name of the state in the given language.
+    pub fn display_name(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::English => match self {
+                ApparatusState::Fencing => "Fencing",
+                ApparatusState::Halt => "Halt",
+                ApparatusState::Pause => "Pause",
+                ApparatusState::Waiting => "Waiting",
+                ApparatusState::Ending => "Ending",
+                ApparatusState::Unknown(_) => "Unknown",
+            },
+            #[cfg(feature = "locale-fr")]
+            Locale::French => match self {
+                ApparatusState::Fencing => "Combat",
+                ApparatusState::Halt => "Halte",
+                ApparatusState::Pause => "Pause",
+                ApparatusState::Waiting => "En attente",
+                ApparatusState::Ending => "Fin",
+                ApparatusState::Unknown(_) => "Inconnu",
+            },
+        }
+    }
+}
+
+impl FencerStatus {
+    /// Returns the name of the status in the given language.
+    pub fn display_name(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::English => match self {
+                FencerStatus::Undefined => "In progress",
+                FencerStatus::Victory => "Victory",
+                FencerStatus::Defeat => "Defeat",
+                FencerStatus::Abandonment => "Abandonment",
+                FencerStatus::Exclusion => "Exclusion",
+                FencerStatus::Unknown(_) => "Unknown",
+            },
+            #[cfg(feature = "locale-fr")]
+            Locale::French => match self {
+                FencerStatus::Undefined => "En cours",
+                FencerStatus::Victory => "Victoire",
+                FencerStatus::Defeat => "Défaite",
+                FencerStatus::Abandonment => "Abandon",
+                FencerStatus::Exclusion => "Exclusion",
+                FencerStatus::Unknown(_) => "Inconnu",
+            },
+        }
+    }
+}
+
+impl PCard {
+    /// Returns the name of the card in the given language.
+    pub fn display_name(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::English => match self {
+                PCard::None => "No card",
+                PCard::Yellow => "Yellow card",
+                PCard::OneRed => "Red card",
+                PCard::TwoRed => "Second red card",
+                PCard::OneBlack => "Black card",
+                PCard::TwoBlack => "Second black card",
+            },
+            #[cfg(feature = "locale-fr")]
+            Locale::French => match self {
+                PCard::None => "Aucun carton",
+                PCard::Yellow => "Carton jaune",
+                PCard::OneRed => "Carton rouge",
+                PCard::TwoRed => "Deuxième carton rouge",
+                PCard::OneBlack => "Carton noir",
+                PCard::TwoBlack => "Deuxième carton noir",
+            },
+        }
+    }
+}
+
+/// Word shown when the apparatus has not reported a state yet.
+pub(crate) fn idle(locale: Locale) -> &'static str {
+    match locale {
+        Locale::English => "Idle",
+        #[cfg(feature = "locale-fr")]
+        Locale::French => "Inactif",
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_locale_names_every_value() {
+        let locales = [
+            Locale::English,
+            #[cfg(feature = "locale-fr")]
+            Locale::French,
+        ];
+
+        for locale in locales {
+            assert!(Weapon::ALL.iter().all(|w| !w.display_name(locale).is_empty()));
+            assert!(ApparatusState::ALL.iter().all(|s| !s.display_name(locale).is_empty()));
+            assert!(FencerStatus::ALL.iter().all(|s| !s.display_name(locale).is_empty()));
+            assert!(PCard::ALL.iter().all(|c| !c.display_name(locale).is_empty()));
+        }
+    }
+
+    #[cfg(feature = "locale-fr")]
+    #[test]
+    fn test_french_names() {
+        assert_eq!(ApparatusState::Halt.display_name(Locale::French), "Halte");
+        assert_eq!(PCard::Yellow.display_name(Locale::French), "Carton jaune");
+    }
+}
@@ -0,0 +1,656 @@
+use std::fmt::{Debug, Display};
+use std::io::{self, BufRead, Write};
+
+use super::error::{FrameSource, LogError, ParseError, ParseErrorHook};
+use super::fencer::{Fencer, FENCER_FIELD_NAMES};
+use super::borrowed::MessageRef;
+use super::message::{Message, GENERAL_FIELD_NAMES};
+use crate::utils::write_csv_row;
+
+/// Direction of a logged frame, relative to the side that recorded the log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// The frame was received from the peer.
+    Received,
+    /// The frame was sent to the peer.
+    Sent,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Received => write!(f, "<"),
+            Direction::Sent => write!(f, ">"),
+        }
+    }
+}
+
+/// A single frame of a message log.
+///
+/// Log files hold one entry per line in the form `<timestamp_ms> <direction> <frame>`,
+/// where the timestamp is in milliseconds and the direction is `<` for received
+/// frames and `>` for sent frames. Blank lines and lines starting with `#` are
+/// ignored.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::logfile::{Direction, LogEntry};
+///
+/// let entry = LogEntry::parse_line("1700000000123 < |EFP1.1|HELLO|17|fm-eq|%|").unwrap();
+/// assert_eq!(entry.timestamp_ms, 1700000000123);
+/// assert_eq!(entry.direction, Direction::Received);
+/// assert_eq!(entry.message().unwrap().piste, "17");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogEntry {
+    /// Time the frame was seen, in milliseconds.
+    pub timestamp_ms: u64,
+    /// Whether the frame was received or sent.
+    pub direction: Direction,
+    /// The raw frame.
+    pub raw: String,
+}
+
+impl LogEntry {
+    /// Creates an entry for a received frame.
+    pub fn received(timestamp_ms: u64, raw: impl Into<String>) -> Self {
+        LogEntry {
+            timestamp_ms,
+            direction: Direction::Received,
+            raw: raw.into(),
+        }
+    }
+
+    /// Creates an entry for a sent frame.
+    pub fn sent(timestamp_ms: u64, raw: impl Into<String>) -> Self {
+        LogEntry {
+            timestamp_ms,
+            direction: Direction::Sent,
+            raw: raw.into(),
+        }
+    }
+
+    /// Parses a log line.
+    ///
+    /// # Returns
+    ///
+    /// `Some(LogEntry)` if the line is of the form `<timestamp_ms> <direction> <frame>`,
+    /// `None` otherwise.
+    pub fn parse_line(line: &str) -> Option<Self> {
+        LogEntryRef::parse_line(line).map(|entry| entry.to_entry())
+    }
+
+    /// Parses the logged frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the frame is not a valid EFP message.
+    pub fn message(&self) -> Result<Message, ParseError> {
+        Message::try_from(self.raw.as_str())
+    }
+}
+
+impl Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.timestamp_ms, self.direction, self.raw)
+    }
+}
+
+/// A log entry whose frame borrows from the log text.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::logfile::LogEntryRef;
+///
+/// let entry = LogEntryRef::parse_line("1000 < |EFP1.1|HELLO|17|fm-eq|%|").unwrap();
+/// assert_eq!(entry.raw, "|EFP1.1|HELLO|17|fm-eq|%|");
+/// assert_eq!(entry.message().unwrap().piste, "17");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogEntryRef<'a> {
+    /// Time the frame was seen, in milliseconds.
+    pub timestamp_ms: u64,
+    /// Whether the frame was received or sent.
+    pub direction: Direction,
+    /// The raw frame.
+    pub raw: &'a str,
+}
+
+impl<'a> LogEntryRef<'a> {
+    /// Parses a log line.
+    ///
+    /// # Returns
+    ///
+    /// `Some(LogEntryRef)` if the line is of the form `<timestamp_ms> <direction> <frame>`,
+    /// `None` otherwise.
+    pub fn parse_line(line: &'a str) -> Option<Self> {
+        let mut parts = line.trim_end_matches(['\r', '\n']).splitn(3, ' ');
+        let timestamp_ms = parts.next()?.parse().ok()?;
+        let direction = match parts.next()? {
+            "<" => Direction::Received,
+            ">" => Direction::Sent,
+            _ => return None,
+        };
+        let raw = parts.next()?;
+
+        Some(LogEntryRef {
+            timestamp_ms,
+            direction,
+            raw,
+        })
+    }
+
+    /// Parses the logged frame without copying its fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the frame is not a valid EFP message.
+    pub fn message(&self) -> Result<MessageRef<'a>, ParseError> {
+        MessageRef::try_from(self.raw)
+    }
+
+    /// Copies the entry into an owned [`LogEntry`].
+    pub fn to_entry(&self) -> LogEntry {
+        LogEntry {
+            timestamp_ms: self.timestamp_ms,
+            direction: self.direction,
+            raw: self.raw.to_string(),
+        }
+    }
+}
+
+/// Iterator over the entries of a log held in memory, created by [`read_bytes`].
+pub struct LogEntryRefs<'a> {
+    bytes: &'a [u8],
+    line_number: usize,
+}
+
+impl<'a> Iterator for LogEntryRefs<'a> {
+    type Item = Result<LogEntryRef<'a>, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.bytes.is_empty() {
+                return None;
+            }
+            let end = self.bytes.iter().position(|&b| b == b'\n').unwrap_or(self.bytes.len());
+            let line = &self.bytes[..end];
+            self.bytes = self.bytes.get(end + 1..).unwrap_or_default();
+            self.line_number += 1;
+
+            let Ok(line) = std::str::from_utf8(line) else {
+                return Some(Err(LogError::InvalidLine {
+                    line_number: self.line_number,
+                    line: String::from_utf8_lossy(line).trim().to_string(),
+                }));
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            return Some(LogEntryRef::parse_line(line).ok_or_else(|| LogError::InvalidLine {
+                line_number: self.line_number,
+                line: line.to_string(),
+            }));
+        }
+    }
+}
+
+/// Reads log entries from a log held in memory, borrowing their frames.
+///
+/// Lines are split and parsed lazily, one per call to `next`; no line is
+/// copied. Lines that are not valid UTF-8 are reported as
+/// `LogError::InvalidLine`.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::logfile;
+///
+/// let log = b"1000 < |EFP1.1|HELLO|17|fm-eq|%|\n1002 > |EFP1.1|ACK|17|fm-eq|%|\n";
+/// let pistes: Vec<&str> = logfile::read_bytes(log)
+///     .map(|entry| entry.unwrap().message().unwrap().piste)
+///     .collect();
+/// assert_eq!(pistes, ["17", "17"]);
+/// ```
+pub fn read_bytes(bytes: &[u8]) -> LogEntryRefs<'_> {
+    LogEntryRefs { bytes, line_number: 0 }
+}
+
+/// A log file mapped in memory.
+///
+/// The file is paged in by the operating system as it is read, so scanning a
+/// multi-gigabyte archive keeps memory flat: entries and messages borrow from
+/// the mapping instead of being copied.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cyrano::enums::Command;
+/// use cyrano::logfile::MappedLog;
+///
+/// // Safety: the archive is not modified while it is mapped.
+/// let log = unsafe { MappedLog::open("season.log") }.unwrap();
+/// let hellos = log
+///     .entries()
+///     .filter_map(Result::ok)
+///     .filter_map(|entry| entry.message().ok())
+///     .filter(|message| message.command == Command::Hello)
+///     .count();
+/// println!("{} HELLO frames", hellos);
+/// ```
+#[cfg(feature = "mmap")]
+pub struct MappedLog {
+    map: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedLog {
+    /// Maps a log file in memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it is mapped, by this
+    /// process or another one; doing so is undefined behavior.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LogError::Io` if the file cannot be opened or mapped.
+    pub unsafe fn open(path: impl AsRef<std::path::Path>) -> Result<Self, LogError> {
+        let file = std::fs::File::open(path).map_err(LogError::Io)?;
+        let map = memmap2::Mmap::map(&file).map_err(LogError::Io)?;
+        Ok(MappedLog { map })
+    }
+
+    /// Returns the size of the mapped file in bytes.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the mapped file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Iterates over the entries of the log, borrowing their frames from the mapping.
+    pub fn entries(&self) -> LogEntryRefs<'_> {
+        read_bytes(&self.map)
+    }
+}
+
+/// Iterator over the entries of a log, created by [`read`].
+pub struct LogReader<R> {
+    reader: R,
+    line_number: usize,
+    line: String,
+    on_parse_error: Option<ParseErrorHook>,
+}
+
+impl<R: BufRead> LogReader<R> {
+    /// Registers a hook called by [`messages`](LogReader::messages) with every
+    /// frame that does not parse.
+    pub fn on_parse_error(
+        mut self,
+        hook: impl FnMut(&[u8], &ParseError, &FrameSource) + Send + 'static,
+    ) -> Self {
+        self.on_parse_error = Some(Box::new(hook));
+        self
+    }
+
+    /// Parses the logged frames, skipping those that do not parse after
+    /// handing them to the parse error hook.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use cyrano::error::FrameSource;
+    /// use cyrano::logfile;
+    ///
+    /// let log = "1000 < |EFP1.1|HELLO|17|fm-eq|%|\n1002 < |EFP2|HELLO|17|%|\n";
+    /// let rejected = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = Arc::clone(&rejected);
+    ///
+    /// let messages: Vec<_> = logfile::read(log.as_bytes())
+    ///     .on_parse_error(move |raw, _, source| sink.lock().unwrap().push((raw.to_vec(), source.clone())))
+    ///     .messages()
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(messages.len(), 1);
+    /// assert_eq!(*rejected.lock().unwrap(), vec![(b"|EFP2|HELLO|17|%|".to_vec(), FrameSource::Log { line_number: 2 })]);
+    /// ```
+    pub fn messages(self) -> LogMessages<R> {
+        LogMessages { entries: self }
+    }
+}
+
+/// Iterator over the parsed frames of a log, created by [`LogReader::messages`].
+pub struct LogMessages<R> {
+    entries: LogReader<R>,
+}
+
+impl<R: BufRead> Iterator for LogMessages<R> {
+    type Item = Result<(LogEntry, Message), LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = match self.entries.next()? {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err)),
+            };
+
+            match entry.message() {
+                Ok(message) => return Some(Ok((entry, message))),
+                Err(err) => {
+                    let source = FrameSource::Log {
+                        line_number: self.entries.line_number,
+                    };
+                    if let Some(hook) = self.entries.on_parse_error.as_mut() {
+                        hook(entry.raw.as_bytes(), &err, &source);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for LogReader<R> {
+    type Item = Result<LogEntry, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.line_number += 1,
+                Err(err) => return Some(Err(LogError::Io(err))),
+            }
+
+            let line = self.line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            return Some(LogEntry::parse_line(line).ok_or_else(|| LogError::InvalidLine {
+                line_number: self.line_number,
+                line: line.to_string(),
+            }));
+        }
+    }
+}
+
+/// Reads log entries from a buffered reader.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::logfile;
+///
+/// let log = "# piste 17\n1000 < |EFP1.1|HELLO|17|fm-eq|%|\n1002 > |EFP1.1|ACK|17|fm-eq|%|\n";
+/// let entries: Vec<_> = logfile::read(log.as_bytes()).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(entries.len(), 2);
+/// ```
+pub fn read<R: BufRead>(reader: R) -> LogReader<R> {
+    LogReader {
+        reader,
+        line_number: 0,
+        line: String::new(),
+        on_parse_error: None,
+    }
+}
+
+/// Number of lines parsed by one task of [`parse_parallel`].
+#[cfg(feature = "rayon")]
+const PARALLEL_CHUNK_LINES: usize = 4096;
+
+/// A log entry with the result of parsing its frame.
+#[cfg(feature = "rayon")]
+pub type ParsedEntry = (LogEntry, Result<Message, ParseError>);
+
+/// Reads and parses a whole log file, spreading the work over the rayon thread pool.
+///
+/// The file is split into chunks of lines parsed in parallel; the output keeps
+/// the order of the file. Frames that do not parse are kept with their error
+/// rather than skipped, so the result covers every entry of the log.
+///
+/// # Errors
+///
+/// Returns `LogError::Io` if the file cannot be read as UTF-8 text, and
+/// `LogError::InvalidLine` for the first line that is not a log entry.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cyrano::logfile;
+///
+/// let entries = logfile::parse_parallel("competition.log").unwrap();
+/// let valid = entries.iter().filter(|(_, message)| message.is_ok()).count();
+/// println!("{} of {} frames parsed", valid, entries.len());
+/// ```
+#[cfg(feature = "rayon")]
+pub fn parse_parallel(
+    path: impl AsRef<std::path::Path>,
+) -> Result<Vec<ParsedEntry>, LogError> {
+    use rayon::prelude::*;
+
+    let content = std::fs::read_to_string(path).map_err(LogError::Io)?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    let chunks: Vec<Result<Vec<_>, LogError>> = lines
+        .par_chunks(PARALLEL_CHUNK_LINES)
+        .enumerate()
+        .map(|(chunk, lines)| {
+            let mut entries = Vec::with_capacity(lines.len());
+            for (offset, line) in lines.iter().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let entry = LogEntry::parse_line(line).ok_or_else(|| LogError::InvalidLine {
+                    line_number: chunk * PARALLEL_CHUNK_LINES + offset + 1,
+                    line: line.to_string(),
+                })?;
+                let message = entry.message();
+                entries.push((entry, message));
+            }
+            Ok(entries)
+        })
+        .collect();
+
+    let mut entries = Vec::with_capacity(lines.len());
+    for chunk in chunks {
+        entries.extend(chunk?);
+    }
+    Ok(entries)
+}
+
+/// Writes log entries as CSV, one row per frame.
+///
+/// The layout is stable: `timestamp_ms`, `direction` (`<` received, `>` sent),
+/// then one column per general field named as in [`GENERAL_FIELD_NAMES`], one
+/// column per fencer field named as in [`FENCER_FIELD_NAMES`] and prefixed with
+/// `right_fencer.` then `left_fencer.`, and finally `error`. Enumerations are
+/// written with their variant name (`Epee`, `Halt`, `OneRed`...) rather than
+/// their protocol code, lights as `1` or `0`, and empty fields as empty cells.
+/// Frames that do not parse leave every field empty and describe the failure
+/// in `error`. Values are quoted as per RFC 4180 when needed.
+///
+/// # Errors
+///
+/// Returns the I/O error of the writer.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::logfile::{self, LogEntry};
+///
+/// let log = vec![LogEntry::received(1000, "|EFP1.1|INFO|17|fm-eq|1|P3|1|||||E||H|%|%|%|")];
+/// let mut csv = Vec::new();
+/// logfile::to_csv(&mut csv, &log).unwrap();
+///
+/// let csv = String::from_utf8(csv).unwrap();
+/// let row = csv.lines().nth(1).unwrap();
+/// assert!(row.starts_with("1000,<,EFP1.1,INFO,17,fm-eq,1,P3,1,,,,,Epee,,Halt,"));
+/// ```
+pub fn to_csv<'a, W: Write>(mut writer: W, entries: impl IntoIterator<Item = &'a LogEntry>) -> io::Result<()> {
+    let mut header = vec!["timestamp_ms".to_string(), "direction".to_string()];
+    header.extend(GENERAL_FIELD_NAMES.iter().map(|name| name.to_string()));
+    for zone in ["right_fencer", "left_fencer"] {
+        header.extend(FENCER_FIELD_NAMES.iter().map(|name| format!("{}.{}", zone, name)));
+    }
+    header.push("error".to_string());
+    write_csv_row(&mut writer, &header)?;
+
+    for entry in entries {
+        let mut row = vec![entry.timestamp_ms.to_string(), entry.direction.to_string()];
+        match entry.message() {
+            Ok(message) => {
+                row.extend(csv_general_fields(&message));
+                row.extend(csv_fencer_fields(&message.right_fencer));
+                row.extend(csv_fencer_fields(&message.left_fencer));
+                row.push(String::new());
+            }
+            Err(err) => {
+                row.extend(std::iter::repeat_n(String::new(), GENERAL_FIELD_NAMES.len() + 2 * FENCER_FIELD_NAMES.len()));
+                row.push(err.to_string());
+            }
+        }
+        write_csv_row(&mut writer, &row)?;
+    }
+
+    writer.flush()
+}
+
+fn csv_general_fields(message: &Message) -> [String; 17] {
+    [
+        message.protocol.to_string(),
+        message.command.to_string(),
+        message.piste.to_string(),
+        message.competition_id.to_string(),
+        csv_value(&message.phase),
+        message.pool_tableau.as_deref().unwrap_or_default().to_string(),
+        csv_value(&message.match_number),
+        csv_value(&message.round),
+        message.time.as_deref().unwrap_or_default().to_string(),
+        message.stopwatch.as_deref().unwrap_or_default().to_string(),
+        csv_variant(&message.competition_type),
+        csv_variant(&message.weapon),
+        csv_variant(&message.priority),
+        csv_variant(&message.state),
+        message.referee.id.as_deref().unwrap_or_default().to_string(),
+        message.referee.name.as_deref().unwrap_or_default().to_string(),
+        message.referee.nation.as_deref().unwrap_or_default().to_string(),
+    ]
+}
+
+fn csv_fencer_fields(fencer: &Fencer) -> [String; 15] {
+    [
+        fencer.id.as_deref().unwrap_or_default().to_string(),
+        fencer.name.as_deref().unwrap_or_default().to_string(),
+        fencer.nation.as_deref().unwrap_or_default().to_string(),
+        csv_value(&fencer.score),
+        csv_variant(&fencer.status),
+        csv_value(&fencer.yellow_card),
+        csv_value(&fencer.red_card),
+        csv_value(&fencer.light.map(u8::from)),
+        csv_value(&fencer.white_light.map(u8::from)),
+        csv_value(&fencer.medical),
+        csv_variant(&fencer.reserve),
+        csv_variant(&fencer.p_card),
+        csv_value(&fencer.bout_score),
+        csv_value(&fencer.video_appeals),
+        csv_value(&fencer.video_review.map(u8::from)),
+    ]
+}
+
+fn csv_value<T: Display>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+fn csv_variant<T: Debug>(value: &Option<T>) -> String {
+    value.as_ref().map(|v| format!("{:?}", v)).unwrap_or_default()
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_layout() {
+        let log = vec![
+            LogEntry::received(0, "|EFP1.1|INFO|17|fm-eq|%|28|Martin, P.|FRA|4|U|0|1|1|0|0|N|2|%|%|"),
+            LogEntry::sent(5, "|EFP1.1|PING|17|%|"),
+        ];
+        let mut csv = Vec::new();
+        to_csv(&mut csv, &log).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+
+        let columns = lines[0].split(',').count();
+        assert_eq!(columns, 2 + 17 + 30 + 1);
+        assert!(lines[0].starts_with("timestamp_ms,direction,protocol,command,"));
+        assert!(lines[0].ends_with("left_fencer.bout_score,left_fencer.video_appeals,left_fencer.video_review,error"));
+
+        assert!(lines[1].contains(",\"Martin, P.\",FRA,4,Undefined,0,1,1,0,0,None,OneRed,"));
+        assert!(lines[2].starts_with("5,>,,,"));
+        assert!(lines[2].ends_with(",Invalid command: PING"));
+    }
+
+    #[test]
+    fn test_read_bytes_reports_lines() {
+        let log = b"# header\n1000 < |EFP1.1|HELLO|17|fm-eq|%|\r\n\n\xff\xfe\n1002 > |EFP1.1|PING|17|%|\nbad\n";
+        let entries: Vec<_> = read_bytes(log).collect();
+
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].as_ref().unwrap().raw, "|EFP1.1|HELLO|17|fm-eq|%|");
+        assert!(matches!(entries[1], Err(LogError::InvalidLine { line_number: 4, .. })));
+        assert!(entries[2].as_ref().unwrap().message().is_err());
+        assert!(matches!(entries[3], Err(LogError::InvalidLine { line_number: 6, .. })));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_log() {
+        let path = std::env::temp_dir().join(format!("cyrano-mapped-{}.log", std::process::id()));
+        std::fs::write(&path, "1000 < |EFP1.1|HELLO|17|fm-eq|%|\n1001 < |EFP1.1|HELLO|3|fm-eq|%|\n").unwrap();
+
+        let log = unsafe { MappedLog::open(&path) }.unwrap();
+        let pistes: Vec<&str> = log.entries().map(|e| e.unwrap().message().unwrap().piste).collect();
+        assert_eq!(pistes, ["17", "3"]);
+
+        drop(log);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parse_parallel_keeps_order() {
+        let path = std::env::temp_dir().join(format!("cyrano-parallel-{}.log", std::process::id()));
+        let mut log = String::from("# day 1\n");
+        for i in 0..10_000u64 {
+            log.push_str(&format!("{} < |EFP1.1|HELLO|{}|fm-eq|%|\n", i, i % 20 + 1));
+        }
+        log.push_str("10000 < |EFP1.1|PING|17|%|\n");
+        std::fs::write(&path, &log).unwrap();
+
+        let entries = parse_parallel(&path).unwrap();
+        assert_eq!(entries.len(), 10_001);
+        assert!(entries.windows(2).all(|w| w[0].0.timestamp_ms < w[1].0.timestamp_ms));
+        assert!(entries[10_000].1.is_err());
+
+        std::fs::write(&path, "1 < |EFP1.1|HELLO|17|%|\n\nnot a log line\n").unwrap();
+        match parse_parallel(&path) {
+            Err(LogError::InvalidLine { line_number, .. }) => assert_eq!(line_number, 3),
+            other => panic!("unexpected result: {:?}", other.map(|e| e.len())),
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+
+use super::error::DiscoveryError;
+use super::message::PROTOCOL_VERSION;
+use super::piste::PisteId;
+use super::session::Role;
+
+/// Service type advertised by competition management software.
+pub const SOFTWARE_SERVICE_TYPE: &str = "_cyrano._udp.local.";
+
+/// Service type advertised by apparatus and apparatus bridges.
+pub const MACHINE_SERVICE_TYPE: &str = "_cyrano-piste._udp.local.";
+
+/// TXT key holding the protocol version spoken by the endpoint.
+const PROTOCOL_KEY: &str = "protocol";
+
+/// TXT key holding the comma-separated pistes served by the endpoint.
+const PISTES_KEY: &str = "pistes";
+
+/// Returns the service type advertised by endpoints of the given role.
+pub fn service_type(role: Role) -> &'static str {
+    match role {
+        Role::Software => SOFTWARE_SERVICE_TYPE,
+        Role::Machine => MACHINE_SERVICE_TYPE,
+    }
+}
+
+/// An endpoint found by [`discover`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    /// Instance name chosen by the advertiser, e.g. "Salle B bridge".
+    pub instance: String,
+    /// Side of the link the endpoint speaks for.
+    pub role: Role,
+    /// Addresses to send EFP datagrams to.
+    pub addresses: Vec<SocketAddr>,
+    /// Pistes served by the endpoint, empty if it did not say.
+    pub pistes: Vec<PisteId>,
+    /// Protocol version announced by the endpoint, if any.
+    pub protocol: Option<String>,
+}
+
+/// A running mDNS advertisement, withdrawn when dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cyrano::mdns::Advertisement;
+/// use cyrano::session::Role;
+///
+/// let advertisement = Advertisement::start(Role::Software, "Engarde hall A", 50100, &[])?;
+/// // ... run the competition ...
+/// drop(advertisement);
+/// # Ok::<(), cyrano::error::DiscoveryError>(())
+/// ```
+pub struct Advertisement {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl Advertisement {
+    /// Advertises an endpoint on every local interface.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - Side of the link the endpoint speaks for, which selects the service type
+    /// * `instance` - Human-readable instance name shown by browsers
+    /// * `port` - UDP port the endpoint listens on
+    /// * `pistes` - Pistes served by the endpoint, empty for a software server
+    ///
+    /// # Errors
+    ///
+    /// Returns `DiscoveryError` if the daemon cannot start or the service is invalid.
+    pub fn start(role: Role, instance: &str, port: u16, pistes: &[&str]) -> Result<Self, DiscoveryError> {
+        let daemon = ServiceDaemon::new().map_err(|err| DiscoveryError::Daemon(err.to_string()))?;
+
+        let host_name = format!("{}.local.", host_label(instance));
+        let properties = [(PROTOCOL_KEY, PROTOCOL_VERSION.to_string()), (PISTES_KEY, pistes.join(","))];
+        let service = ServiceInfo::new(service_type(role), instance, &host_name, (), port, &properties[..])
+            .map_err(|err| DiscoveryError::InvalidService(err.to_string()))?
+            .enable_addr_auto();
+        let fullname = service.get_fullname().to_string();
+
+        daemon.register(service).map_err(|err| DiscoveryError::Daemon(err.to_string()))?;
+        Ok(Advertisement { daemon, fullname })
+    }
+
+    /// Returns the full mDNS name of the advertised service.
+    pub fn fullname(&self) -> &str {
+        &self.fullname
+    }
+}
+
+impl Drop for Advertisement {
+    fn drop(&mut self) {
+        let _ = self.daemon.unregister(&self.fullname);
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Browses the network for `timeout` and returns the endpoints of a role.
+///
+/// Endpoints withdrawn before the end of the browse are left out. Endpoints
+/// are sorted by instance name.
+///
+/// # Errors
+///
+/// Returns `DiscoveryError::Daemon` if the daemon cannot start or stops responding.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use cyrano::mdns;
+/// use cyrano::session::Role;
+///
+/// for endpoint in mdns::discover(Role::Machine, Duration::from_secs(3))? {
+///     println!("{} at {:?} serving {:?}", endpoint.instance, endpoint.addresses, endpoint.pistes);
+/// }
+/// # Ok::<(), cyrano::error::DiscoveryError>(())
+/// ```
+pub fn discover(role: Role, timeout: Duration) -> Result<Vec<Endpoint>, DiscoveryError> {
+    let daemon = ServiceDaemon::new().map_err(|err| DiscoveryError::Daemon(err.to_string()))?;
+    let ty = service_type(role);
+    let events = daemon.browse(ty).map_err(|err| DiscoveryError::Daemon(err.to_string()))?;
+
+    let deadline = Instant::now() + timeout;
+    let mut found = BTreeMap::new();
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match events.recv_timeout(remaining) {
+            Ok(ServiceEvent::ServiceResolved(service)) => {
+                let mut addresses: Vec<SocketAddr> = service
+                    .addresses
+                    .iter()
+                    .map(|ip| SocketAddr::new(ip.to_ip_addr(), service.port))
+                    .collect();
+                addresses.sort();
+
+                let endpoint = Endpoint {
+                    instance: instance_name(&service.fullname, ty).to_string(),
+                    role,
+                    addresses,
+                    pistes: service.get_property_val_str(PISTES_KEY).map(parse_pistes).unwrap_or_default(),
+                    protocol: service.get_property_val_str(PROTOCOL_KEY).map(String::from),
+                };
+                found.insert(service.fullname.clone(), endpoint);
+            }
+            Ok(ServiceEvent::ServiceRemoved(_, fullname)) => {
+                found.remove(&fullname);
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+
+    let _ = daemon.stop_browse(ty);
+    let _ = daemon.shutdown();
+
+    let mut endpoints: Vec<Endpoint> = found.into_values().collect();
+    endpoints.sort_by(|a, b| a.instance.cmp(&b.instance));
+    Ok(endpoints)
+}
+
+/// Returns the instance part of a full service name.
+fn instance_name<'a>(fullname: &'a str, ty: &str) -> &'a str {
+    fullname
+        .strip_suffix(ty)
+        .and_then(|name| name.strip_suffix('.'))
+        .unwrap_or(fullname)
+}
+
+/// Turns an instance name into a valid host label.
+fn host_label(instance: &str) -> String {
+    let label: String = instance
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    if label.is_empty() {
+        "cyrano".to_string()
+    } else {
+        label
+    }
+}
+
+fn parse_pistes(pistes: &str) -> Vec<PisteId> {
+    pistes
+        .split(',')
+        .map(str::trim)
+        .filter(|piste| !piste.is_empty())
+        .map(PisteId::from)
+        .collect()
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_names() {
+        assert_eq!(service_type(Role::Machine), MACHINE_SERVICE_TYPE);
+        assert_eq!(instance_name("Hall B bridge._cyrano-piste._udp.local.", MACHINE_SERVICE_TYPE), "Hall B bridge");
+        assert_eq!(host_label("Hall B bridge"), "hall-b-bridge");
+
+        let pistes = parse_pistes("1, 2,,Finale");
+        let names: Vec<&str> = pistes.iter().map(PisteId::as_str).collect();
+        assert_eq!(names, ["1", "2", "Finale"]);
+    }
+}
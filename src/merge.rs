@@ -0,0 +1,246 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::message::Message;
+use crate::piste::PisteId;
+
+/// A message of a merged feed, with the piste it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedMessage {
+    /// Piste of the source the message was read from.
+    pub piste: PisteId,
+    /// Receipt time in milliseconds, corrected by the source offset.
+    pub timestamp_ms: u64,
+    /// The message.
+    pub message: Message,
+}
+
+struct Source<'a> {
+    piste: PisteId,
+    offset_ms: i64,
+    messages: Box<dyn Iterator<Item = (u64, Message)> + 'a>,
+    latest_ms: Option<u64>,
+    exhausted: bool,
+}
+
+/// Time-ordered merge of several per-piste message streams.
+///
+/// Each source yields `(timestamp_ms, message)` pairs, roughly in time order:
+/// a source may deliver a message up to `window_ms` later than a message
+/// stamped after it, as happens with UDP reordering. The merge holds messages
+/// back until no source can still deliver an earlier one, so the output is
+/// globally ordered while buffering at most one window per source. Messages
+/// arriving later than the window are emitted as soon as they are read.
+///
+/// Apparatus clocks are corrected with a per-source offset, added to every
+/// timestamp of the source before ordering. Equal timestamps keep the order in
+/// which the sources were added.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::merge::Merge;
+/// use cyrano::message::Message;
+///
+/// let hello = |piste: &str| Message::try_from(format!("|EFP1.1|HELLO|{}|fm-eq|%|", piste)).unwrap();
+///
+/// let mut merge = Merge::new(50);
+/// merge.add("1", vec![(100, hello("1")), (300, hello("1"))]);
+/// merge.add("2", vec![(200, hello("2")), (160, hello("2"))]);
+///
+/// let order: Vec<u64> = merge.map(|m| m.timestamp_ms).collect();
+/// assert_eq!(order, [100, 160, 200, 300]);
+/// ```
+pub struct Merge<'a> {
+    window_ms: u64,
+    sources: Vec<Source<'a>>,
+    pending: BinaryHeap<Reverse<Pending>>,
+    sequence: u64,
+}
+
+/// A message read from a source and held back until it is safe to emit.
+struct Pending {
+    timestamp_ms: u64,
+    source: usize,
+    sequence: u64,
+    message: Message,
+}
+
+impl Pending {
+    fn key(&self) -> (u64, usize, u64) {
+        (self.timestamp_ms, self.source, self.sequence)
+    }
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Pending {}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Merge<'a> {
+    /// Creates an empty merge tolerating `window_ms` of reordering per source.
+    pub fn new(window_ms: u64) -> Self {
+        Merge {
+            window_ms,
+            sources: Vec::new(),
+            pending: BinaryHeap::new(),
+            sequence: 0,
+        }
+    }
+
+    /// Adds the stream of a piste.
+    pub fn add<I>(&mut self, piste: impl Into<PisteId>, messages: I)
+    where
+        I: IntoIterator<Item = (u64, Message)>,
+        I::IntoIter: 'a,
+    {
+        self.add_with_offset(piste, 0, messages);
+    }
+
+    /// Adds the stream of a piste whose timestamps are off by `offset_ms`.
+    ///
+    /// # Arguments
+    ///
+    /// * `piste` - The piste of the stream
+    /// * `offset_ms` - Correction added to each timestamp of the stream
+    /// * `messages` - The `(timestamp_ms, message)` pairs of the stream
+    pub fn add_with_offset<I>(&mut self, piste: impl Into<PisteId>, offset_ms: i64, messages: I)
+    where
+        I: IntoIterator<Item = (u64, Message)>,
+        I::IntoIter: 'a,
+    {
+        self.sources.push(Source {
+            piste: piste.into(),
+            offset_ms,
+            messages: Box::new(messages.into_iter()),
+            latest_ms: None,
+            exhausted: false,
+        });
+    }
+
+    /// Returns the number of messages held back for ordering.
+    pub fn buffered(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the earliest time a source may still deliver, with that source.
+    ///
+    /// `Err` carries a source that has not been read yet.
+    fn horizon(&self) -> Result<Option<(u64, usize)>, usize> {
+        let mut horizon: Option<(u64, usize)> = None;
+        for (index, source) in self.sources.iter().enumerate() {
+            if source.exhausted {
+                continue;
+            }
+            let bound = source.latest_ms.ok_or(index)?.saturating_sub(self.window_ms);
+            if horizon.is_none_or(|(current, _)| bound < current) {
+                horizon = Some((bound, index));
+            }
+        }
+        Ok(horizon)
+    }
+
+    fn pull(&mut self, index: usize) {
+        let source = &mut self.sources[index];
+        match source.messages.next() {
+            Some((timestamp_ms, message)) => {
+                let timestamp_ms = timestamp_ms.saturating_add_signed(source.offset_ms);
+                source.latest_ms = Some(source.latest_ms.map_or(timestamp_ms, |t| t.max(timestamp_ms)));
+                self.pending.push(Reverse(Pending {
+                    timestamp_ms,
+                    source: index,
+                    sequence: self.sequence,
+                    message,
+                }));
+                self.sequence += 1;
+            }
+            None => source.exhausted = true,
+        }
+    }
+}
+
+impl Iterator for Merge<'_> {
+    type Item = MergedMessage;
+
+    fn next(&mut self) -> Option<MergedMessage> {
+        loop {
+            let ready = match self.horizon() {
+                Err(unread) => {
+                    self.pull(unread);
+                    continue;
+                }
+                Ok(None) => true,
+                Ok(Some((bound, limiting))) => {
+                    let ready = self
+                        .pending
+                        .peek()
+                        .is_some_and(|Reverse(pending)| pending.timestamp_ms <= bound);
+                    if !ready {
+                        self.pull(limiting);
+                    }
+                    ready
+                }
+            };
+
+            if ready {
+                let Reverse(pending) = self.pending.pop()?;
+                return Some(MergedMessage {
+                    piste: self.sources[pending.source].piste.clone(),
+                    timestamp_ms: pending.timestamp_ms,
+                    message: pending.message,
+                });
+            }
+        }
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn hello(piste: &str) -> Message {
+        Message::try_from(format!("|EFP1.1|HELLO|{}|fm-eq|%|", piste)).unwrap()
+    }
+
+    #[test]
+    fn test_merge_orders_within_window() {
+        let mut merge = Merge::new(100);
+        merge.add("2", vec![(10, hello("2")), (50, hello("2")), (20, hello("2")), (400, hello("2"))]);
+        merge.add("10", vec![(30, hello("10")), (30, hello("10")), (500, hello("10"))]);
+        merge.add_with_offset("Finale", -1000, vec![(1_025, hello("Finale"))]);
+
+        let merged: Vec<(u64, String)> = merge.map(|m| (m.timestamp_ms, m.piste.to_string())).collect();
+        assert_eq!(
+            merged,
+            [
+                (10, "2".to_string()),
+                (20, "2".to_string()),
+                (25, "Finale".to_string()),
+                (30, "10".to_string()),
+                (30, "10".to_string()),
+                (50, "2".to_string()),
+                (400, "2".to_string()),
+                (500, "10".to_string()),
+            ]
+        );
+    }
+}
@@ -1,11 +1,61 @@
 use std::convert::TryFrom;
-use std::fmt::Display;
+use std::fmt::{self, Display, Write};
 
+use crate::assignment::MatchAssignment;
+use crate::borrowed::MessageRef;
 use crate::enums::*;
-use crate::error::ParseError;
+use crate::error::{FieldError, ParseError, ParseWarning, ValueError};
 use crate::fencer::Fencer;
+use crate::inspect::{self, FieldValue};
 use crate::referee::Referee;
-use crate::utils::{get_field, get_required_field, parse_optional_u8};
+use crate::limits::{
+    check, check_optional, sanitize, sanitize_optional, MAX_COMPETITION_ID_LENGTH,
+    MAX_PISTE_LENGTH, MAX_POOL_TABLEAU_LENGTH,
+};
+use crate::options::{ParseOptions, TextPolicy};
+use crate::utils::{canonical_code, canonical_text, canonical_time, field, CountingWriter, FieldRef};
+
+#[cfg(feature = "small-strings")]
+use compact_str::CompactString as FieldStorage;
+#[cfg(not(feature = "small-strings"))]
+use std::string::String as FieldStorage;
+
+/// Storage of the text fields of messages, fencers and referees.
+///
+/// A `String` by default. With the `small-strings` feature it is a
+/// `CompactString` from the `compact_str` crate, which keeps values of up to
+/// 24 bytes inline and so parses typical frames without one heap allocation
+/// per field. Both convert from `&str` and `String` with `into()` and
+/// dereference to `str`.
+///
+/// To measure the difference on a given machine, compare the `single_message`
+/// group of `cargo bench --bench workloads` with and without
+/// `--features small-strings`.
+pub type FieldString = FieldStorage;
+
+/// Protocol version written by this crate when composing messages.
+pub const PROTOCOL_VERSION: &str = "EFP1.1";
+
+/// Names of the fields of the general zone, in protocol order.
+pub const GENERAL_FIELD_NAMES: [&str; 17] = [
+    "protocol",
+    "command",
+    "piste",
+    "competition_id",
+    "phase",
+    "pool_tableau",
+    "match_number",
+    "round",
+    "time",
+    "stopwatch",
+    "competition_type",
+    "weapon",
+    "priority",
+    "state",
+    "referee.id",
+    "referee.name",
+    "referee.nation",
+];
 
 /// A complete EFP protocol message.
 ///
@@ -18,6 +68,12 @@ use crate::utils::{get_field, get_required_field, parse_optional_u8};
 /// Messages follow the format: `|general_fields|%|right_fencer|%|left_fencer|%|`
 /// where fields are pipe-delimited and sections are separated by percent signs.
 ///
+/// A `Message` keeps parsed values only, not the frame they came from. The
+/// fields as received are available from the borrowed [`MessageRef`], through
+/// [`raw_zones`](MessageRef::raw_zones),
+/// [`raw_general_fields`](MessageRef::raw_general_fields) and
+/// [`fields`](MessageRef::fields).
+///
 /// # Examples
 ///
 /// ```
@@ -28,28 +84,29 @@ use crate::utils::{get_field, get_required_field, parse_optional_u8};
 /// let msg = Message::try_from(raw).unwrap();
 /// assert_eq!(msg.piste, "17");
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Message {
     /// Protocol version (e.g., "EFP1.1" or "EFP1").
-    pub protocol: String,
+    pub protocol: FieldString,
     /// The command type of this message.
     pub command: Command,
     /// Piste (strip) identifier.
-    pub piste: String,
+    pub piste: FieldString,
     /// Competition identifier.
-    pub competition_id: String,
+    pub competition_id: FieldString,
     /// Competition phase number.
     pub phase: Option<u8>,
     /// Pool or tableau identifier.
-    pub pool_tableau: Option<String>,
+    pub pool_tableau: Option<FieldString>,
     /// Match number within the competition.
     pub match_number: Option<u8>,
     /// Round number.
     pub round: Option<u8>,
     /// Current match time.
-    pub time: Option<String>,
+    pub time: Option<FieldString>,
     /// Stopwatch time.
-    pub stopwatch: Option<String>,
+    pub stopwatch: Option<FieldString>,
     /// Type of competition (Individual or Team).
     pub competition_type: Option<CompetitionType>,
     /// Weapon type being used.
@@ -66,6 +123,472 @@ pub struct Message {
     pub left_fencer: Fencer,
 }
 
+impl Message {
+    /// Creates a message with only the command, piste and competition set.
+    ///
+    /// This is the shape of HELLO, ACK and NAK messages; other fields can be
+    /// filled in afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command type of the message
+    /// * `piste` - Piste (strip) identifier
+    /// * `competition_id` - Competition identifier
+    pub fn new(command: Command, piste: impl Into<FieldString>, competition_id: impl Into<FieldString>) -> Self {
+        Message {
+            protocol: PROTOCOL_VERSION.into(),
+            command,
+            piste: piste.into(),
+            competition_id: competition_id.into(),
+            phase: None,
+            pool_tableau: None,
+            match_number: None,
+            round: None,
+            time: None,
+            stopwatch: None,
+            competition_type: None,
+            weapon: None,
+            priority: None,
+            state: None,
+            referee: Referee::default(),
+            right_fencer: Fencer::default(),
+            left_fencer: Fencer::default(),
+        }
+    }
+
+    /// Returns the fencer on the given side.
+    pub fn fencer(&self, side: Side) -> &Fencer {
+        match side {
+            Side::Right => &self.right_fencer,
+            Side::Left => &self.left_fencer,
+        }
+    }
+
+    /// Returns a mutable reference to the fencer on the given side.
+    pub fn fencer_mut(&mut self, side: Side) -> &mut Fencer {
+        match side {
+            Side::Right => &mut self.right_fencer,
+            Side::Left => &mut self.left_fencer,
+        }
+    }
+
+    /// Reads a field by name.
+    ///
+    /// General fields are named as in [`GENERAL_FIELD_NAMES`]; fencer fields
+    /// are named as in [`FENCER_FIELD_NAMES`](crate::fencer::FENCER_FIELD_NAMES),
+    /// prefixed with `right_fencer.` or `left_fencer.`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FieldError::UnknownField` if no field has this name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::inspect::FieldValue;
+    /// use cyrano::message::Message;
+    ///
+    /// let mut msg = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|P3|1|||||E||H|%|28|P.Martin|FRA|4|U|%|%|").unwrap();
+    /// assert_eq!(msg.get("right_fencer.score").unwrap(), Some(FieldValue::Number(4)));
+    ///
+    /// msg.set("right_fencer.score", Some(FieldValue::Number(5))).unwrap();
+    /// assert_eq!(msg.right_fencer.score, Some(5));
+    /// ```
+    pub fn get(&self, name: &str) -> Result<Option<FieldValue<'_>>, FieldError> {
+        inspect::get(self, name)
+    }
+
+    /// Writes a field by name; `None` clears it.
+    ///
+    /// Fields are named as for [`Message::get`]. Clearing the protocol, piste
+    /// or competition identifier leaves it empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FieldError::UnknownField` if no field has this name and
+    /// `FieldError::WrongType` if the value does not fit the field or the
+    /// command is cleared.
+    pub fn set(&mut self, name: &str, value: Option<FieldValue<'_>>) -> Result<(), FieldError> {
+        inspect::set(self, name, value)
+    }
+
+    /// Returns `true` if the message carries bout data (INFO, DISP, NEXT or PREV).
+    pub fn carries_bout(&self) -> bool {
+        matches!(
+            self.command,
+            Command::Info | Command::Disp | Command::Next | Command::Prev
+        )
+    }
+
+    /// Composes a NEXT message announcing the upcoming bout on a piste.
+    ///
+    /// # Arguments
+    ///
+    /// * `assignment` - The bout to push to the piste
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::MissingField` if a required assignment field is missing.
+    pub fn next_match(assignment: MatchAssignment) -> Result<Self, ParseError> {
+        assignment.to_message(Command::Next)
+    }
+
+    /// Composes a PREV message recalling the previous bout on a piste.
+    ///
+    /// # Arguments
+    ///
+    /// * `assignment` - The bout to push to the piste
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::MissingField` if a required assignment field is missing.
+    pub fn prev_match(assignment: MatchAssignment) -> Result<Self, ParseError> {
+        assignment.to_message(Command::Prev)
+    }
+
+    /// Parses an EFP protocol message with the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The raw protocol message string
+    /// * `options` - Parsing options
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as `TryFrom<&str>`, and `ParseError::LimitExceeded`
+    /// if the message exceeds the configured size limits. In strict mode, also returns
+    /// `ParseError::InvalidValue` when a numeric, boolean or enumerated field
+    /// contains an invalid value instead of treating it as absent or when a white
+    /// light is on at épée or sabre, and `ParseError::UnexpectedZone` for a HELLO
+    /// carrying fencer data.
+    pub fn parse_with(raw: &str, options: &ParseOptions) -> Result<Self, ParseError> {
+        Message::parse_with_warnings(raw, options).map(|(message, _)| message)
+    }
+
+    /// Parses an EFP protocol message and reports the problems tolerated on the way.
+    ///
+    /// In lenient mode, every invalid value treated as absent is reported as
+    /// `WarningKind::FieldInvalid`. For bout messages (INFO, DISP, NEXT, PREV),
+    /// general fields cut off by a short zone and absent fencer zones are
+    /// reported as `WarningKind::FieldMissing`; empty fields are legitimate and
+    /// never reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The raw protocol message string
+    /// * `options` - Parsing options
+    ///
+    /// # Returns
+    ///
+    /// The parsed message together with the list of warnings.
+    ///
+    /// # Errors
+    ///
+    /// See [`Message::parse_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::error::{ParseWarning, WarningKind};
+    /// use cyrano::enums::Zone;
+    /// use cyrano::message::Message;
+    /// use cyrano::options::ParseOptions;
+    ///
+    /// let raw = "|EFP1.1|INFO|17|fm-eq|1|A32|12|2|10:30|3:00|I|S||W|||%|28|P.Martin|FRA|8a|%|%|";
+    /// let (msg, warnings) = Message::parse_with_warnings(raw, &ParseOptions::default()).unwrap();
+    ///
+    /// assert_eq!(msg.right_fencer.score, None);
+    /// assert_eq!(warnings, vec![
+    ///     ParseWarning { zone: Zone::General, field: "referee.nation", kind: WarningKind::FieldMissing },
+    ///     ParseWarning { zone: Zone::RightFencer, field: "score", kind: WarningKind::FieldInvalid("8a".to_string()) },
+    /// ]);
+    /// ```
+    pub fn parse_with_warnings(
+        raw: &str,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>), ParseError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "parse_message",
+            piste = tracing::field::Empty,
+            command = tracing::field::Empty,
+        )
+        .entered();
+
+        let result = Message::parse_zones(raw, options);
+
+        #[cfg(feature = "tracing")]
+        match &result {
+            Ok((message, warnings)) => {
+                let span = tracing::Span::current();
+                span.record("piste", message.piste.as_str());
+                span.record("command", tracing::field::display(&message.command));
+                for warning in warnings {
+                    tracing::warn!(zone = %warning.zone, field = warning.field, kind = ?warning.kind, "lenient parse fix");
+                }
+            }
+            Err(err) => tracing::debug!(error = %err, raw, "message rejected"),
+        }
+
+        result
+    }
+
+    fn parse_zones(raw: &str, options: &ParseOptions) -> Result<(Self, Vec<ParseWarning>), ParseError> {
+        let (view, warnings) = MessageRef::parse_zones(raw, options)?;
+        let message = view.to_message();
+
+        if options.strict {
+            message.validate()?;
+            message.check_white_lights()?;
+        }
+
+        Ok((message, warnings))
+    }
+
+    /// Checks the text fields against the specification limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ValueError` for the first field containing `|` or `%`, or
+    /// exceeding its maximum length (see [`crate::limits`]).
+    pub fn validate(&self) -> Result<(), ValueError> {
+        check("piste", &self.piste, MAX_PISTE_LENGTH)?;
+        check("competition_id", &self.competition_id, MAX_COMPETITION_ID_LENGTH)?;
+        check_optional("pool_tableau", &self.pool_tableau, MAX_POOL_TABLEAU_LENGTH)?;
+        self.referee.validate()?;
+        self.right_fencer
+            .validate_named(["right_fencer.id", "right_fencer.name", "right_fencer.nation"])?;
+        self.left_fencer
+            .validate_named(["left_fencer.id", "left_fencer.name", "left_fencer.nation"])
+    }
+
+    /// Rejects a white light reported at a weapon other than foil, the only
+    /// weapon with an off-target signal.
+    pub(crate) fn check_white_lights(&self) -> Result<(), ParseError> {
+        if self.weapon.as_ref().is_some_and(|weapon| *weapon != Weapon::Foil) {
+            let fencers = [
+                (&self.right_fencer, "right_fencer.white_light"),
+                (&self.left_fencer, "left_fencer.white_light"),
+            ];
+            for (fencer, field) in fencers {
+                if fencer.white_light == Some(true) {
+                    return Err(ParseError::InvalidValue { field, value: "1".to_string() });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces forbidden characters and truncates the text fields to their maximum length.
+    pub fn sanitize(&mut self) {
+        self.piste = field(sanitize(&self.piste, MAX_PISTE_LENGTH));
+        self.competition_id = field(sanitize(&self.competition_id, MAX_COMPETITION_ID_LENGTH));
+        sanitize_optional(&mut self.pool_tableau, MAX_POOL_TABLEAU_LENGTH);
+        self.referee.sanitize();
+        self.right_fencer.sanitize();
+        self.left_fencer.sanitize();
+    }
+
+    /// Returns the normalized form of the message.
+    ///
+    /// Two messages describing the same state compare (and hash) equal once
+    /// canonicalized:
+    /// - the protocol, nation codes and pool/tableau are trimmed and uppercased
+    /// - piste, competition, ids and names are trimmed, with inner whitespace collapsed
+    /// - times are normalized to `M:SS` / `H:MM` without leading zeros ("03:00" becomes "3:00")
+    /// - empty optional text fields become `None`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::message::Message;
+    ///
+    /// let a = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|a32|12|2|10:30|03:00|%|28| P.Martin |fra|%|%|").unwrap();
+    /// let b = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|2|10:30|3:00|%|28|P.Martin|FRA|%|%|").unwrap();
+    ///
+    /// assert_ne!(a, b);
+    /// assert_eq!(a.canonicalize(), b.canonicalize());
+    /// ```
+    pub fn canonicalize(&self) -> Message {
+        Message {
+            protocol: field(self.protocol.trim().to_uppercase()),
+            command: self.command.clone(),
+            piste: canonical_text(&Some(self.piste.clone())).unwrap_or_default(),
+            competition_id: canonical_text(&Some(self.competition_id.clone())).unwrap_or_default(),
+            phase: self.phase,
+            pool_tableau: canonical_code(&self.pool_tableau),
+            match_number: self.match_number,
+            round: self.round,
+            time: canonical_time(&self.time),
+            stopwatch: canonical_time(&self.stopwatch),
+            competition_type: self.competition_type.clone(),
+            weapon: self.weapon.clone(),
+            priority: self.priority.clone(),
+            state: self.state.clone(),
+            referee: self.referee.canonicalize(),
+            right_fencer: self.right_fencer.canonicalize(),
+            left_fencer: self.left_fencer.canonicalize(),
+        }
+    }
+
+    /// Serializes the message, applying a policy to values that cannot be transmitted as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Whether to reject or sanitize invalid text values
+    ///
+    /// # Errors
+    ///
+    /// With `TextPolicy::Reject`, returns `ValueError` if a text field contains
+    /// `|` or `%` or exceeds its maximum length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::enums::Command;
+    /// use cyrano::message::Message;
+    /// use cyrano::options::TextPolicy;
+    ///
+    /// let mut msg = Message::new(Command::Disp, "17", "fm-eq");
+    /// msg.right_fencer.name = Some("O|Brien".into());
+    ///
+    /// assert!(msg.serialize_with(TextPolicy::Reject).is_err());
+    /// assert!(msg.serialize_with(TextPolicy::Sanitize).unwrap().contains("|O Brien|"));
+    /// ```
+    pub fn serialize_with(&self, policy: TextPolicy) -> Result<String, ValueError> {
+        match policy {
+            TextPolicy::Reject => {
+                self.validate()?;
+                Ok(self.to_string())
+            }
+            TextPolicy::Sanitize => {
+                let mut sanitized = self.clone();
+                sanitized.sanitize();
+                Ok(sanitized.to_string())
+            }
+        }
+    }
+
+    /// Writes the message in protocol format to any `fmt::Write` sink.
+    ///
+    /// This is the allocation-free counterpart of `to_string()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - The destination
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `fmt::Error` if the destination fails.
+    pub fn write_to<W: fmt::Write + ?Sized>(&self, out: &mut W) -> Result<usize, fmt::Error> {
+        let mut out = CountingWriter::new(out);
+
+        // General zone
+        out.write_char('|')?;
+        for field in self.general_field_refs() {
+            field.write(&mut out)?;
+            out.write_char('|')?;
+        }
+        if let Some(sanctions) = self.referee.sanctions.filter(|_| self.protocol.as_str() == "EFP1.1") {
+            for count in [sanctions.yellow_card, sanctions.red_card, sanctions.black_card] {
+                FieldRef::number(count).write(&mut out)?;
+                out.write_char('|')?;
+            }
+        }
+
+        // Fencer zones
+        out.write_str("%|")?;
+        self.right_fencer.write_to(&mut out)?;
+        out.write_str("|%|")?;
+        self.left_fencer.write_to(&mut out)?;
+        out.write_str("|%|")?;
+
+        Ok(out.count())
+    }
+
+    /// Appends the message in protocol format to a reusable buffer.
+    ///
+    /// The buffer is not cleared first, so several messages can be batched
+    /// into it; call `clear()` between frames to reuse its allocation.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A `String` or `Vec<u8>` to append to
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes appended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::enums::Command;
+    /// use cyrano::message::Message;
+    ///
+    /// let msg = Message::new(Command::Hello, "17", "fm-eq");
+    /// let mut buffer: Vec<u8> = Vec::with_capacity(256);
+    ///
+    /// let written = msg.serialize_into(&mut buffer);
+    /// assert_eq!(written, buffer.len());
+    /// assert_eq!(buffer, msg.to_string().into_bytes());
+    /// ```
+    pub fn serialize_into<B: SerializeBuffer + ?Sized>(&self, buffer: &mut B) -> usize {
+        let mut appender = Appender(buffer);
+        // Appending to an in-memory buffer cannot fail.
+        self.write_to(&mut appender).unwrap_or(0)
+    }
+
+    /// Returns views of the general fields, in protocol order.
+    pub(crate) fn general_field_refs(&self) -> [FieldRef<'_>; 17] {
+        [
+            FieldRef::Text(&self.protocol),
+            FieldRef::Code(&self.command),
+            FieldRef::Text(&self.piste),
+            FieldRef::Text(&self.competition_id),
+            FieldRef::number(self.phase),
+            FieldRef::text(&self.pool_tableau),
+            FieldRef::number(self.match_number),
+            FieldRef::number(self.round),
+            FieldRef::text(&self.time),
+            FieldRef::text(&self.stopwatch),
+            FieldRef::code(&self.competition_type),
+            FieldRef::code(&self.weapon),
+            FieldRef::code(&self.priority),
+            FieldRef::code(&self.state),
+            FieldRef::text(&self.referee.id),
+            FieldRef::text(&self.referee.name),
+            FieldRef::text(&self.referee.nation),
+        ]
+    }
+
+    /// Parses an EFP protocol message, rejecting invalid field values.
+    ///
+    /// Shorthand for [`Message::parse_with`] with [`ParseOptions::strict`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Message::parse_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::error::ParseError;
+    /// use cyrano::message::Message;
+    ///
+    /// let result = Message::try_from_strict("|EFP1.1|INFO|17|fm-eq|1|A32|12|2|10:30|3:00|I|Q||W|%|");
+    /// assert!(matches!(result, Err(ParseError::InvalidValue { field: "weapon", .. })));
+    /// ```
+    pub fn try_from_strict(raw: &str) -> Result<Self, ParseError> {
+        Message::parse_with(raw, &ParseOptions::strict())
+    }
+}
+
 impl TryFrom<&str> for Message {
     type Error = ParseError;
 
@@ -87,6 +610,7 @@ impl TryFrom<&str> for Message {
     /// - Required fields are missing
     /// - The protocol version is not supported (only EFP1 and EFP1.1 are supported)
     /// - Field values are invalid
+    /// - The message exceeds the default size limits (see [`ParseLimits`](crate::options::ParseLimits))
     ///
     /// # Examples
     ///
@@ -98,82 +622,7 @@ impl TryFrom<&str> for Message {
     /// let msg = Message::try_from(raw).unwrap();
     /// ```
     fn try_from(raw: &str) -> Result<Self, Self::Error> {
-        let raw = raw.trim();
-
-        if raw.is_empty() {
-            return Err(ParseError::EmptyMessage);
-        }
-
-        let raw = raw.trim_matches('|');
-        let zones: Vec<&str> = raw.split('%').collect();
-
-        if zones.is_empty() {
-            return Err(ParseError::InvalidFormat);
-        }
-
-        let general_fields: Vec<&str> = zones[0].trim_matches('|').split('|').collect();
-
-        let protocol = get_required_field(&general_fields, 0, "protocol")?;
-        if protocol != "EFP1.1" && protocol != "EFP1" {
-            return Err(ParseError::InvalidProtocol(protocol.to_string()));
-        }
-
-        let command = Command::try_from(get_required_field(&general_fields, 1, "command")?)?;
-        let piste = get_field(&general_fields, 2).map(String::from).unwrap_or_else(String::new);
-        let competition_id = get_field(&general_fields, 3).map(String::from).unwrap_or_else(String::new);
-
-        let phase = parse_optional_u8(&general_fields, 4);
-        let pool_tableau = get_field(&general_fields, 5).map(String::from);
-        let match_number = parse_optional_u8(&general_fields, 6);
-        let round = parse_optional_u8(&general_fields, 7);
-        let time = get_field(&general_fields, 8).map(String::from);
-        let stopwatch = get_field(&general_fields, 9).map(String::from);
-        let competition_type = get_field(&general_fields, 10)
-            .and_then(|s| CompetitionType::try_from(s).ok());
-        let weapon = get_field(&general_fields, 11).and_then(|s| Weapon::try_from(s).ok());
-        let priority = get_field(&general_fields, 12).and_then(|s| Priority::try_from(s).ok());
-        let state = get_field(&general_fields, 13)
-            .and_then(|s| ApparatusState::try_from(s).ok());
-
-        let referee = Referee {
-            id: get_field(&general_fields, 14).map(String::from),
-            name: get_field(&general_fields, 15).map(String::from),
-            nation: get_field(&general_fields, 16).map(String::from),
-        };
-
-        let right_fencer = if zones.len() > 1 {
-            let right_fields: Vec<&str> = zones[1].trim_matches('|').split('|').collect();
-            Fencer::parse(&right_fields)?
-        } else {
-            Fencer::default()
-        };
-
-        let left_fencer = if zones.len() > 2 {
-            let left_fields: Vec<&str> = zones[2].trim_matches('|').split('|').collect();
-            Fencer::parse(&left_fields)?
-        } else {
-            Fencer::default()
-        };
-
-        Ok(Message {
-            protocol: protocol.to_string(),
-            command,
-            piste,
-            competition_id,
-            phase,
-            pool_tableau,
-            match_number,
-            round,
-            time,
-            stopwatch,
-            competition_type,
-            weapon,
-            priority,
-            state,
-            referee,
-            right_fencer,
-            left_fencer,
-        })
+        Message::parse_with(raw, &ParseOptions::default())
     }
 }
 
@@ -193,51 +642,39 @@ impl Display for Message {
     ///
     /// Serializes the message back into the pipe-delimited format with
     /// percent-separated zones according to the EFP protocol specification.
+    /// `|` or `%` characters inside values are replaced by spaces; use
+    /// [`Message::serialize_with`] to reject them or enforce length limits.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // General zone
-        let general_fields: Vec<String> = vec![
-            self.protocol.clone(),
-            self.command.to_string(),
-            self.piste.clone(),
-            self.competition_id.clone(),
-            self.phase.map(|v| v.to_string()).unwrap_or_default(),
-            self.pool_tableau.clone().unwrap_or_default(),
-            self.match_number.map(|v| v.to_string()).unwrap_or_default(),
-            self.round.map(|v| v.to_string()).unwrap_or_default(),
-            self.time.clone().unwrap_or_default(),
-            self.stopwatch.clone().unwrap_or_default(),
-            self.competition_type
-                .as_ref()
-                .map(|v| v.to_string())
-                .unwrap_or_default(),
-            self.weapon
-                .as_ref()
-                .map(|v| v.to_string())
-                .unwrap_or_default(),
-            self.priority
-                .as_ref()
-                .map(|v| v.to_string())
-                .unwrap_or_default(),
-            self.state
-                .as_ref()
-                .map(|v| v.to_string())
-                .unwrap_or_default(),
-            self.referee.id.clone().unwrap_or_default(),
-            self.referee.name.clone().unwrap_or_default(),
-            self.referee.nation.clone().unwrap_or_default(),
-        ];
-
-        let right_serialized = self.right_fencer.serialize();
-        let left_serialized = self.left_fencer.serialize();
-
-        // Build the complete message
-        write!(
-            f,
-            "|{}|%|{}|%|{}|%|",
-            general_fields.join("|"),
-            right_serialized,
-            left_serialized
-        )
+        self.write_to(f).map(|_| ())
+    }
+}
+
+/// A growable buffer messages can be serialized into.
+///
+/// Implemented for `String` and `Vec<u8>`; see [`Message::serialize_into`].
+pub trait SerializeBuffer {
+    /// Appends a string slice to the buffer.
+    fn append(&mut self, s: &str);
+}
+
+impl SerializeBuffer for String {
+    fn append(&mut self, s: &str) {
+        self.push_str(s);
+    }
+}
+
+impl SerializeBuffer for Vec<u8> {
+    fn append(&mut self, s: &str) {
+        self.extend_from_slice(s.as_bytes());
+    }
+}
+
+struct Appender<'a, B: ?Sized>(&'a mut B);
+
+impl<B: SerializeBuffer + ?Sized> fmt::Write for Appender<'_, B> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.append(s);
+        Ok(())
     }
 }
 
@@ -255,6 +692,7 @@ impl From<Message> for String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::WarningKind;
 
     #[test]
     fn test_parse_hello() {
@@ -274,16 +712,16 @@ mod tests {
         assert_eq!(msg.command, Command::Info);
         assert_eq!(msg.piste, "17");
         assert_eq!(msg.phase, Some(1));
-        assert_eq!(msg.pool_tableau, Some("A32".to_string()));
+        assert_eq!(msg.pool_tableau, Some("A32".into()));
         assert_eq!(msg.state, Some(ApparatusState::Waiting));
         assert_eq!(msg.weapon, Some(Weapon::Sabre));
 
-        assert_eq!(msg.right_fencer.id, Some("28".to_string()));
-        assert_eq!(msg.right_fencer.name, Some("P.Martin".to_string()));
+        assert_eq!(msg.right_fencer.id, Some("28".into()));
+        assert_eq!(msg.right_fencer.name, Some("P.Martin".into()));
         assert_eq!(msg.right_fencer.score, Some(8));
         assert_eq!(msg.right_fencer.status, Some(FencerStatus::Victory));
 
-        assert_eq!(msg.left_fencer.id, Some("32".to_string()));
+        assert_eq!(msg.left_fencer.id, Some("32".into()));
         assert_eq!(msg.left_fencer.score, Some(6));
         assert_eq!(msg.left_fencer.status, Some(FencerStatus::Defeat));
     }
@@ -311,6 +749,148 @@ mod tests {
         assert_eq!(msg.competition_id, reparsed.competition_id);
     }
 
+    #[test]
+    fn test_next_match_roundtrip() {
+        let assignment = MatchAssignment {
+            piste: "17".to_string(),
+            competition_id: "fm-eq".to_string(),
+            phase: Some(1),
+            pool_tableau: Some("A32".to_string()),
+            match_number: Some(12),
+            weapon: Some(Weapon::Epee),
+            right_fencer: crate::assignment::AssignedFencer {
+                id: Some("28".to_string()),
+                name: Some("P.Martin".to_string()),
+                nation: Some("FRA".to_string()),
+                seed: Some(3),
+            },
+            left_fencer: crate::assignment::AssignedFencer {
+                id: Some("32".to_string()),
+                name: Some("B. Panini".to_string()),
+                nation: Some("ITA".to_string()),
+                seed: Some(30),
+            },
+            ..MatchAssignment::default()
+        };
+
+        let msg = Message::next_match(assignment).unwrap();
+        let reparsed = Message::try_from(msg.to_string()).unwrap();
+
+        assert_eq!(reparsed.command, Command::Next);
+        assert_eq!(reparsed.pool_tableau, Some("A32".into()));
+        assert_eq!(reparsed.weapon, Some(Weapon::Epee));
+        assert_eq!(reparsed.right_fencer.name, Some("P.Martin".into()));
+        assert_eq!(reparsed.left_fencer.nation, Some("ITA".into()));
+    }
+
+    #[test]
+    fn test_prev_match_missing_field() {
+        let assignment = MatchAssignment {
+            piste: "17".to_string(),
+            competition_id: "fm-eq".to_string(),
+            phase: Some(1),
+            pool_tableau: Some("A32".to_string()),
+            ..MatchAssignment::default()
+        };
+
+        let result = Message::prev_match(assignment);
+        assert!(matches!(result, Err(ParseError::MissingField("match_number"))));
+    }
+
+    #[test]
+    fn test_strict_rejects_invalid_enum() {
+        let raw = "|EFP1.1|INFO|17|efj-eq|1|A32|12|2|10:30|3:00|I|S||W|%|28|P.Martin|FRA|8|x?|%|%|";
+
+        let lenient = Message::try_from(raw).unwrap();
+        assert_eq!(lenient.right_fencer.status, None);
+
+        let strict = Message::try_from_strict(raw);
+        assert!(matches!(
+            strict,
+            Err(ParseError::InvalidValue { field: "fencer_status", .. })
+        ));
+    }
+
+    #[test]
+    fn test_strict_rejects_white_light_outside_foil() {
+        let raw = "|EFP1.1|INFO|17|efj-eq|1|A32|12|||||S||H|%|28|P.Martin|FRA|8|U|0|0|0|1|%|32|B. Panini|ITA|2|U|%|";
+        assert_eq!(Message::try_from(raw).unwrap().right_fencer.white_light, Some(true));
+        assert!(matches!(
+            Message::try_from_strict(raw),
+            Err(ParseError::InvalidValue { field: "right_fencer.white_light", .. })
+        ));
+        assert!(MessageRef::parse_with(raw, &ParseOptions::strict()).is_err());
+        assert!(Message::try_from_strict(&raw.replace("|S||H|", "|F||H|")).is_ok());
+    }
+
+    #[test]
+    fn test_strict_rejects_invalid_number() {
+        let raw = "|EFP1.1|INFO|17|efj-eq|300|A32|12|%|";
+
+        let (lenient, warnings) = Message::parse_with_warnings(raw, &ParseOptions::default()).unwrap();
+        assert_eq!(lenient.phase, None);
+        assert!(warnings.contains(&ParseWarning {
+            zone: Zone::General,
+            field: "phase",
+            kind: WarningKind::FieldInvalid("300".to_string()),
+        }));
+
+        let strict = Message::try_from_strict(raw);
+        assert!(matches!(strict, Err(ParseError::InvalidValue { field: "phase", .. })));
+    }
+
+    #[test]
+    fn test_forbidden_character_is_neutralized() {
+        let mut msg = Message::new(Command::Info, "17", "fm-eq");
+        msg.right_fencer.name = Some("O|Brien".into());
+
+        let reparsed = Message::try_from(msg.to_string()).unwrap();
+        assert_eq!(reparsed.right_fencer.name, Some("O Brien".into()));
+        assert!(matches!(
+            msg.validate(),
+            Err(ValueError::ForbiddenCharacter { field: "right_fencer.name", character: '|' })
+        ));
+    }
+
+    #[test]
+    fn test_strict_rejects_long_name() {
+        let raw = "|EFP1.1|INFO|17|fm-eq|%|28|Alexandre-Maximilien Dupont|FRA|%|%|";
+
+        assert!(Message::try_from(raw).is_ok());
+        assert!(matches!(
+            Message::try_from_strict(raw),
+            Err(ParseError::InvalidValue { field: "right_fencer.name", .. })
+        ));
+    }
+
+    #[test]
+    fn test_canonicalize_times() {
+        let raw = "|EFP1.1|INFO|17|fm-eq|1|A32|12|2|09:05|0:7|%|";
+        let msg = Message::try_from(raw).unwrap().canonicalize();
+
+        assert_eq!(msg.time, Some("9:05".into()));
+        assert_eq!(msg.stopwatch, Some("0:07".into()));
+        assert_eq!(msg.canonicalize(), msg);
+    }
+
+    #[test]
+    fn test_limits() {
+        let many_fields = format!("|EFP1.1|INFO|17|{}%|", "x|".repeat(100));
+        assert!(matches!(
+            Message::try_from(many_fields.as_str()),
+            Err(ParseError::LimitExceeded { limit: "fields_per_zone", .. })
+        ));
+
+        let many_zones = format!("|EFP1.1|INFO|17|fm-eq|{}", "%|".repeat(10));
+        assert!(matches!(
+            Message::try_from(many_zones.as_str()),
+            Err(ParseError::LimitExceeded { limit: "zones", .. })
+        ));
+
+        let complete = "|EFP1.1|INFO|17|efj-eq|1|A32|12|2|10:30|3:00|I|S||W|132|J.Smith|GBR|%|28|P.Martin|FRA|8|V|0|1|1|0|0|N|%|32|B. Panini|ITA|6|D|0|1|0|0|0|N|%|";
+        assert!(Message::try_from(complete).is_ok());
+    }
+
     #[test]
     fn test_invalid_command() {
         let raw = "|EFP1.1|INVALID|17|fm-eq|%|";
@@ -324,4 +904,24 @@ mod tests {
         let result = Message::try_from(raw);
         assert!(matches!(result, Err(ParseError::InvalidProtocol(_))));
     }
+
+    #[test]
+    fn test_official_sanctions_need_extended_profile() {
+        use crate::options::VendorProfile;
+
+        let extended = ParseOptions { profile: VendorProfile::Extended, ..ParseOptions::default() };
+        let raw = "|EFP1.1|INFO|17|fm-eq|1|A8|2|||||E||H|7|J. Doe|GER|0|1||%|%|%|";
+        let mut message = Message::parse_with(raw, &extended).unwrap();
+        assert_eq!(message.referee.sanctions.unwrap().red_card, Some(1));
+        assert_eq!(Message::parse_with(&message.to_string(), &extended).unwrap(), message);
+
+        let strict = ParseOptions { strict: true, ..extended.clone() };
+        let invalid = Message::parse_with(&raw.replace("|0|1||%", "|0|X||%"), &strict);
+        assert!(matches!(invalid, Err(ParseError::InvalidValue { field: "sanctions.red_card", .. })));
+
+        // EFP1 defines no field after the referee.
+        assert_eq!(Message::parse_with(&raw.replace("EFP1.1", "EFP1"), &extended).unwrap().referee.sanctions, None);
+        message.protocol = "EFP1".into();
+        assert!(message.to_string().contains("|GER|%|"));
+    }
 }
@@ -0,0 +1,355 @@
+//! Compact MessagePack encoding of messages.
+//!
+//! Meant for forwarding messages over constrained links (LoRa bridges, radio
+//! links to outdoor pistes) where the pipe-delimited format wastes bandwidth.
+//!
+//! # Layout
+//!
+//! A message is a MessagePack array of up to 17 elements in protocol order:
+//! `protocol`, `command`, `piste`, `competition_id`, `phase`, `pool_tableau`,
+//! `match_number`, `round`, `time`, `stopwatch`, `competition_type`, `weapon`,
+//! `priority`, `state`, `referee`, `right_fencer`, `left_fencer`. The referee
+//! is an array of `id`, `name`, `nation` and `sanctions`, the latter an array
+//! of the yellow, red and black cards given to officials. Each fencer is an
+//! array of the 15 fields listed in [`FENCER_FIELD_NAMES`](crate::fencer::FENCER_FIELD_NAMES).
+//!
+//! Empty fields are `nil`, numbers are unsigned integers, lights are booleans
+//! and enumeration values are their protocol codes (`"INFO"`, `"S"`, `"V"`).
+//! Trailing empty fields are left out of every array, and a fencer or referee
+//! with no field set is an empty array, so that sparse frames such as HELLO
+//! stay short; decoders take missing elements as empty. The layout does not
+//! depend on Rust type or variant names, so it stays stable across releases
+//! of this crate.
+
+use std::convert::TryFrom;
+
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::error::ParseError;
+use crate::fencer::Fencer;
+use crate::message::{FieldString, Message};
+use crate::referee::{Referee, Sanctions};
+
+pub use rmp_serde::decode::Error as DecodeError;
+pub use rmp_serde::encode::Error as EncodeError;
+
+/// Field as written on the wire.
+enum WireField<'a> {
+    Nil,
+    Text(&'a str),
+    Number(u8),
+    Flag(bool),
+    Zone(Vec<WireField<'a>>),
+}
+
+impl WireField<'_> {
+    fn is_empty(&self) -> bool {
+        match self {
+            WireField::Nil => true,
+            WireField::Zone(fields) => fields.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl<'a> From<Option<&'a str>> for WireField<'a> {
+    fn from(value: Option<&'a str>) -> Self {
+        value.map_or(WireField::Nil, WireField::Text)
+    }
+}
+
+impl From<Option<u8>> for WireField<'_> {
+    fn from(value: Option<u8>) -> Self {
+        value.map_or(WireField::Nil, WireField::Number)
+    }
+}
+
+impl From<Option<bool>> for WireField<'_> {
+    fn from(value: Option<bool>) -> Self {
+        value.map_or(WireField::Nil, WireField::Flag)
+    }
+}
+
+impl Serialize for WireField<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            WireField::Nil => serializer.serialize_none(),
+            WireField::Text(text) => serializer.serialize_str(text),
+            WireField::Number(number) => serializer.serialize_u8(*number),
+            WireField::Flag(flag) => serializer.serialize_bool(*flag),
+            WireField::Zone(fields) => {
+                let mut seq = serializer.serialize_seq(Some(fields.len()))?;
+                for field in fields {
+                    seq.serialize_element(field)?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+/// Returns a zone without its trailing empty fields.
+fn zone(mut fields: Vec<WireField<'_>>) -> WireField<'_> {
+    while fields.last().is_some_and(WireField::is_empty) {
+        fields.pop();
+    }
+    WireField::Zone(fields)
+}
+
+/// Fencer zone as read from the wire.
+#[derive(Default, Deserialize)]
+struct WireFencer(
+    #[serde(default)] Option<FieldString>,
+    #[serde(default)] Option<FieldString>,
+    #[serde(default)] Option<FieldString>,
+    #[serde(default)] Option<u8>,
+    #[serde(default)] Option<String>,
+    #[serde(default)] Option<u8>,
+    #[serde(default)] Option<u8>,
+    #[serde(default)] Option<bool>,
+    #[serde(default)] Option<bool>,
+    #[serde(default)] Option<u8>,
+    #[serde(default)] Option<String>,
+    #[serde(default)] Option<String>,
+    #[serde(default)] Option<u8>,
+    #[serde(default)] Option<u8>,
+    #[serde(default)] Option<bool>,
+);
+
+/// Referee zone as read from the wire.
+#[derive(Default, Deserialize)]
+struct WireReferee(
+    #[serde(default)] Option<FieldString>,
+    #[serde(default)] Option<FieldString>,
+    #[serde(default)] Option<FieldString>,
+    #[serde(default)] Option<(Option<u8>, Option<u8>, Option<u8>)>,
+);
+
+/// Message as read from the wire.
+#[derive(Deserialize)]
+struct WireMessage(
+    FieldString,
+    String,
+    FieldString,
+    FieldString,
+    #[serde(default)] Option<u8>,
+    #[serde(default)] Option<FieldString>,
+    #[serde(default)] Option<u8>,
+    #[serde(default)] Option<u8>,
+    #[serde(default)] Option<FieldString>,
+    #[serde(default)] Option<FieldString>,
+    #[serde(default)] Option<String>,
+    #[serde(default)] Option<String>,
+    #[serde(default)] Option<String>,
+    #[serde(default)] Option<String>,
+    #[serde(default)] WireReferee,
+    #[serde(default)] WireFencer,
+    #[serde(default)] WireFencer,
+);
+
+fn parse<'a, T>(value: &'a Option<String>) -> Result<Option<T>, DecodeError>
+where
+    T: TryFrom<&'a str, Error = ParseError>,
+{
+    value
+        .as_deref()
+        .map(T::try_from)
+        .transpose()
+        .map_err(|err| DecodeError::Syntax(err.to_string()))
+}
+
+fn fencer_zone(f: &Fencer) -> WireField<'_> {
+    zone(vec![
+        f.id.as_deref().into(),
+        f.name.as_deref().into(),
+        f.nation.as_deref().into(),
+        f.score.into(),
+        f.status.as_ref().map(|status| status.code()).into(),
+        f.yellow_card.into(),
+        f.red_card.into(),
+        f.light.into(),
+        f.white_light.into(),
+        f.medical.into(),
+        f.reserve.as_ref().map(|reserve| reserve.code()).into(),
+        f.p_card.as_ref().map(|p_card| p_card.code()).into(),
+        f.bout_score.into(),
+        f.video_appeals.into(),
+        f.video_review.into(),
+    ])
+}
+
+impl WireFencer {
+    fn into_fencer(self) -> Result<Fencer, DecodeError> {
+        Ok(Fencer {
+            status: parse(&self.4)?,
+            reserve: parse(&self.10)?,
+            p_card: parse(&self.11)?,
+            bout_score: self.12,
+            video_appeals: self.13,
+            video_review: self.14,
+            id: self.0,
+            name: self.1,
+            nation: self.2,
+            score: self.3,
+            yellow_card: self.5,
+            red_card: self.6,
+            light: self.7,
+            white_light: self.8,
+            medical: self.9,
+        })
+    }
+}
+
+/// Encodes a message with the layout described in the [module documentation](self).
+///
+/// # Errors
+///
+/// Returns `EncodeError` if the output cannot be written, which does not
+/// happen when encoding into memory.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::msgpack;
+///
+/// let raw = "|EFP1.1|INFO|17|fm-eq|1|P3|1|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|";
+/// let message = Message::try_from(raw).unwrap();
+///
+/// let bytes = msgpack::to_vec(&message).unwrap();
+/// assert!(bytes.len() < raw.len());
+/// assert_eq!(msgpack::from_slice(&bytes).unwrap(), message);
+/// ```
+pub fn to_vec(message: &Message) -> Result<Vec<u8>, EncodeError> {
+    let referee = &message.referee;
+    rmp_serde::to_vec(&zone(vec![
+        WireField::Text(&message.protocol),
+        WireField::Text(message.command.code()),
+        WireField::Text(&message.piste),
+        WireField::Text(&message.competition_id),
+        message.phase.into(),
+        message.pool_tableau.as_deref().into(),
+        message.match_number.into(),
+        message.round.into(),
+        message.time.as_deref().into(),
+        message.stopwatch.as_deref().into(),
+        message.competition_type.as_ref().map(|value| value.code()).into(),
+        message.weapon.as_ref().map(|value| value.code()).into(),
+        message.priority.as_ref().map(|value| value.code()).into(),
+        message.state.as_ref().map(|value| value.code()).into(),
+        zone(vec![
+            referee.id.as_deref().into(),
+            referee.name.as_deref().into(),
+            referee.nation.as_deref().into(),
+            referee.sanctions.map_or(WireField::Nil, |sanctions| {
+                WireField::Zone(vec![
+                    sanctions.yellow_card.into(),
+                    sanctions.red_card.into(),
+                    sanctions.black_card.into(),
+                ])
+            }),
+        ]),
+        fencer_zone(&message.right_fencer),
+        fencer_zone(&message.left_fencer),
+    ]))
+}
+
+/// Decodes a message encoded by [`to_vec`].
+///
+/// # Errors
+///
+/// Returns `DecodeError` if the bytes are not a valid encoded message or
+/// contain an unknown protocol code.
+pub fn from_slice(bytes: &[u8]) -> Result<Message, DecodeError> {
+    let wire: WireMessage = rmp_serde::from_slice(bytes)?;
+    let WireReferee(id, name, nation, sanctions) = wire.14;
+
+    Ok(Message {
+        command: parse(&Some(wire.1))?.ok_or_else(|| DecodeError::Syntax("missing command".to_string()))?,
+        competition_type: parse(&wire.10)?,
+        weapon: parse(&wire.11)?,
+        priority: parse(&wire.12)?,
+        state: parse(&wire.13)?,
+        protocol: wire.0,
+        piste: wire.2,
+        competition_id: wire.3,
+        phase: wire.4,
+        pool_tableau: wire.5,
+        match_number: wire.6,
+        round: wire.7,
+        time: wire.8,
+        stopwatch: wire.9,
+        referee: Referee {
+            id,
+            name,
+            nation,
+            sanctions: sanctions.map(|(yellow_card, red_card, black_card)| Sanctions {
+                yellow_card,
+                red_card,
+                black_card,
+            }),
+        },
+        right_fencer: wire.15.into_fencer()?,
+        left_fencer: wire.16.into_fencer()?,
+    })
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_is_smaller() {
+        let raw = "|EFP1.1|INFO|17|efj-eq|1|A32|12|2|10:30|3:00|I|S||W|132|J.Smith|GBR|%|28|P.Martin|FRA|8|V|0|1|1|0|0|N|%|32|B. Panini|ITA|6|D|0|1|0|0|0|N|%|";
+        let message = Message::try_from(raw).unwrap();
+
+        let bytes = to_vec(&message).unwrap();
+        assert!(bytes.len() < raw.len());
+        assert_eq!(from_slice(&bytes).unwrap(), message);
+    }
+
+    #[test]
+    fn test_roundtrip_official_sanctions() {
+        let options = crate::options::ParseOptions {
+            profile: crate::options::VendorProfile::Extended,
+            ..crate::options::ParseOptions::default()
+        };
+        for raw in ["|EFP1.1|INFO|17|fm-eq|1|A8|2|||||E||H|7|J. Doe|GER|1||0|%|%|%|", "|EFP1.1|INFO|17|fm-eq|1|A8|2|||||E||H||||||%|%|%|"] {
+            let message = Message::parse_with(raw, &options).unwrap();
+            assert!(message.referee.sanctions.is_some());
+
+            let decoded = from_slice(&to_vec(&message).unwrap()).unwrap();
+            assert_eq!(decoded.referee.sanctions, message.referee.sanctions);
+            assert_eq!(decoded, message);
+        }
+    }
+
+    #[test]
+    fn test_stable_layout() {
+        let message = Message::try_from("|EFP1.1|HELLO|17|fm-eq|%|").unwrap();
+        let bytes = to_vec(&message).unwrap();
+
+        // fixarray of 4: "EFP1.1", "HELLO", "17", "fm-eq", the rest left out
+        assert_eq!(bytes[0], 0x94);
+        assert_eq!(&bytes[1..8], b"\xa6EFP1.1");
+        assert_eq!(&bytes[8..14], b"\xa5HELLO");
+        assert_eq!(&bytes[14..17], b"\xa217");
+        assert_eq!(&bytes[17..], b"\xa5fm-eq");
+        assert_eq!(from_slice(&bytes).unwrap(), message);
+        assert!(from_slice(&bytes[..17]).is_err());
+
+        let mut unknown = bytes.clone();
+        unknown[9..14].copy_from_slice(b"HOLLA");
+        assert!(from_slice(&unknown).is_err());
+
+        // An empty right fencer before a set left fencer is an empty array.
+        let message = Message::try_from("|EFP1.1|INFO|17|fm-eq|%|%|32|B. Panini|%|").unwrap();
+        let bytes = to_vec(&message).unwrap();
+        assert_eq!(from_slice(&bytes).unwrap(), message);
+        assert!(bytes.windows(2).any(|pair| pair == b"\x90\x92"));
+    }
+}
@@ -0,0 +1,327 @@
+use crate::fencer::Fencer;
+use crate::limits::{sanitize, MAX_NAME_LENGTH};
+
+/// A name split into first name and surname.
+///
+/// Apparatus and software fill the name fields in various shapes, such as
+/// `J.Smith`, `B. Panini`, `SMITH John` or `Smith, John`; [`parse`](PersonName::parse)
+/// recognizes them so that names can be shown in a single [`NameStyle`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PersonName {
+    /// First name, or its initials.
+    pub first: Option<String>,
+    /// Surname.
+    pub last: String,
+}
+
+impl PersonName {
+    /// Creates a name from its components.
+    pub fn new(first: Option<&str>, last: &str) -> Self {
+        PersonName {
+            first: first.map(str::to_string),
+            last: last.to_string(),
+        }
+    }
+
+    /// Splits a name field into first name and surname.
+    ///
+    /// The surname is, in order of precedence:
+    ///
+    /// 1. the part before a comma (`Smith, John`);
+    /// 2. the words in uppercase, when the others are not (`SMITH John`, `John DE LA TORRE`);
+    /// 3. the words that are not initials (`J.Smith`, `Smith J.`);
+    /// 4. the last word (`John Smith`).
+    ///
+    /// # Returns
+    ///
+    /// The name, or `None` if the field is blank.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::names::PersonName;
+    ///
+    /// assert_eq!(PersonName::parse("J.Smith"), Some(PersonName::new(Some("J."), "Smith")));
+    /// assert_eq!(PersonName::parse("SMITH John"), Some(PersonName::new(Some("John"), "SMITH")));
+    /// assert_eq!(PersonName::parse("Smith, John"), Some(PersonName::new(Some("John"), "Smith")));
+    /// assert_eq!(PersonName::parse("John Smith"), Some(PersonName::new(Some("John"), "Smith")));
+    /// assert_eq!(PersonName::parse("  "), None);
+    /// ```
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return None;
+        }
+
+        if let Some((last, first)) = raw.split_once(',') {
+            return Some(PersonName {
+                first: Some(join(first.split_whitespace())).filter(|first| !first.is_empty()),
+                last: join(last.split_whitespace()),
+            })
+            .filter(|name| !name.last.is_empty());
+        }
+
+        let words = split_words(raw);
+        let (last, first): (Vec<&str>, Vec<&str>) = if words.iter().any(|w| is_uppercase(w)) && !words.iter().all(|w| is_uppercase(w)) {
+            words.iter().partition(|w| is_uppercase(w))
+        } else if words.iter().any(|w| is_initial(w)) && !words.iter().all(|w| is_initial(w)) {
+            words.iter().partition(|w| !is_initial(w))
+        } else {
+            let (last, first) = words.split_last()?;
+            (vec![*last], first.to_vec())
+        };
+
+        Some(PersonName {
+            first: (!first.is_empty()).then(|| join(first.into_iter())),
+            last: join(last.into_iter()),
+        })
+    }
+
+    /// Returns the initials of the first name, such as `J.` or `J.-P.`.
+    pub fn initials(&self) -> Option<String> {
+        let first = self.first.as_deref()?;
+        let initials: String = first
+            .split(|c: char| c.is_whitespace() || c == '.')
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                word.split('-')
+                    .filter_map(|part| part.chars().find(|c| c.is_alphabetic()))
+                    .map(|c| format!("{}.", c.to_uppercase()))
+                    .collect::<Vec<_>>()
+                    .join("-")
+            })
+            .collect();
+        (!initials.is_empty()).then_some(initials)
+    }
+
+    /// Formats the name in a display style.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::names::{NameStyle, PersonName};
+    ///
+    /// let name = PersonName::parse("Jean-Pierre DE LA TORRE").unwrap();
+    /// assert_eq!(name.format(&NameStyle::SURNAME_INITIALS), "DE LA TORRE J.-P.");
+    /// assert_eq!(name.format(&NameStyle::FULL_NAME), "Jean-Pierre De La Torre");
+    /// ```
+    pub fn format(&self, style: &NameStyle) -> String {
+        let last = style.surname.apply(&self.last);
+        let first = match style.first_name {
+            FirstName::Full => self.first.as_deref().map(|first| match style.surname {
+                Casing::AsIs => first.to_string(),
+                _ => Casing::Title.apply(first),
+            }),
+            FirstName::Initials => self.initials(),
+            FirstName::Omitted => None,
+        };
+
+        match (first, style.order) {
+            (None, _) => last,
+            (Some(first), NameOrder::SurnameFirst) => format!("{} {}", last, first),
+            (Some(first), NameOrder::FirstNameFirst) => format!("{} {}", first, last),
+        }
+    }
+
+    /// Formats the name for a name field of at most `max` characters.
+    ///
+    /// A name too long in the requested style is shortened: the first name is
+    /// reduced to its initials, then left out, then the surname is truncated.
+    /// Characters forbidden in a frame are replaced by spaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::names::{NameStyle, PersonName};
+    ///
+    /// let name = PersonName::new(Some("Maximilian"), "Hartmann-Oberndorfer");
+    /// assert_eq!(name.format_within(&NameStyle::SURNAME_FIRST, 24), "HARTMANN-OBERNDORFER M.");
+    /// assert_eq!(name.format_within(&NameStyle::SURNAME_FIRST, 12), "HARTMANN-OBE");
+    /// ```
+    pub fn format_within(&self, style: &NameStyle, max: usize) -> String {
+        let formatted = [FirstName::Full, FirstName::Initials, FirstName::Omitted]
+            .iter()
+            .filter(|first_name| **first_name >= style.first_name)
+            .map(|first_name| {
+                self.format(&NameStyle {
+                    first_name: *first_name,
+                    ..*style
+                })
+            })
+            .find(|formatted| formatted.chars().count() <= max)
+            .unwrap_or_else(|| self.format(&NameStyle { first_name: FirstName::Omitted, ..*style }));
+        sanitize(&formatted, max)
+    }
+
+    /// Formats the name for the name field of a frame, within
+    /// [`MAX_NAME_LENGTH`] characters.
+    pub fn to_field(&self, style: &NameStyle) -> String {
+        self.format_within(style, MAX_NAME_LENGTH)
+    }
+}
+
+/// Which component of a name comes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameOrder {
+    /// Surname, then first name: `SMITH John`.
+    SurnameFirst,
+    /// First name, then surname: `John Smith`.
+    FirstNameFirst,
+}
+
+/// How much of the first name is shown, from the most to the least.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FirstName {
+    /// The whole first name.
+    Full,
+    /// Its initials only.
+    Initials,
+    /// No first name.
+    Omitted,
+}
+
+/// Casing applied to a surname.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Casing {
+    /// As received.
+    AsIs,
+    /// All in uppercase, as on FIE scoreboards.
+    Upper,
+    /// First letter of each word in uppercase.
+    Title,
+}
+
+impl Casing {
+    fn apply(&self, text: &str) -> String {
+        match self {
+            Casing::AsIs => text.to_string(),
+            Casing::Upper => text.to_uppercase(),
+            Casing::Title => {
+                let mut titled = String::with_capacity(text.len());
+                let mut start = true;
+                for c in text.chars() {
+                    if start {
+                        titled.extend(c.to_uppercase());
+                    } else {
+                        titled.extend(c.to_lowercase());
+                    }
+                    start = !c.is_alphabetic() && c != '\'';
+                }
+                titled
+            }
+        }
+    }
+}
+
+/// Display style of names.
+///
+/// The first name follows the casing of the surname, in title case unless the
+/// surname is kept as received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NameStyle {
+    /// Which component comes first.
+    pub order: NameOrder,
+    /// How much of the first name is shown.
+    pub first_name: FirstName,
+    /// Casing of the surname.
+    pub surname: Casing,
+}
+
+impl NameStyle {
+    /// `SMITH J.`, the usual scoreboard style.
+    pub const SURNAME_INITIALS: NameStyle = NameStyle {
+        order: NameOrder::SurnameFirst,
+        first_name: FirstName::Initials,
+        surname: Casing::Upper,
+    };
+    /// `SMITH John`, the FIE result lists style.
+    pub const SURNAME_FIRST: NameStyle = NameStyle {
+        order: NameOrder::SurnameFirst,
+        first_name: FirstName::Full,
+        surname: Casing::Upper,
+    };
+    /// `J. Smith`.
+    pub const INITIALS_SURNAME: NameStyle = NameStyle {
+        order: NameOrder::FirstNameFirst,
+        first_name: FirstName::Initials,
+        surname: Casing::Title,
+    };
+    /// `John Smith`.
+    pub const FULL_NAME: NameStyle = NameStyle {
+        order: NameOrder::FirstNameFirst,
+        first_name: FirstName::Full,
+        surname: Casing::Title,
+    };
+}
+
+impl Default for NameStyle {
+    fn default() -> Self {
+        NameStyle::SURNAME_INITIALS
+    }
+}
+
+impl Fencer {
+    /// Returns the name of the fencer split into first name and surname.
+    pub fn person_name(&self) -> Option<PersonName> {
+        self.name.as_deref().and_then(PersonName::parse)
+    }
+}
+
+/// Splits a name into words, separating initials glued to the surname (`J.Smith`).
+fn split_words(raw: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for word in raw.split_whitespace() {
+        match word.rfind('.') {
+            Some(dot) if dot + 1 < word.len() => {
+                words.push(&word[..=dot]);
+                words.push(&word[dot + 1..]);
+            }
+            _ => words.push(word),
+        }
+    }
+    words
+}
+
+/// Returns `true` for a word of at least two letters, all in uppercase.
+fn is_uppercase(word: &str) -> bool {
+    word.chars().filter(|c| c.is_alphabetic()).count() >= 2 && !word.chars().any(char::is_lowercase)
+}
+
+/// Returns `true` for an initial such as `J`, `J.` or `J.-P.`.
+fn is_initial(word: &str) -> bool {
+    word.ends_with('.') || word.chars().filter(|c| c.is_alphabetic()).count() == 1
+}
+
+fn join<'a>(words: impl Iterator<Item = &'a str>) -> String {
+    words.collect::<Vec<_>>().join(" ")
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_parse_and_format_names() {
+        let message = Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||F||H|%|28|P.Martin|FRA|4|U|%|32|PANINI Bruno|ITA|2|U|%|").unwrap();
+        let right = message.right_fencer.person_name().unwrap();
+        let left = message.left_fencer.person_name().unwrap();
+        assert_eq!(right.format(&NameStyle::default()), "MARTIN P.");
+        assert_eq!(left.format(&NameStyle::default()), "PANINI B.");
+        assert_eq!(left.format(&NameStyle::INITIALS_SURNAME), "B. Panini");
+        assert_eq!(right.format(&NameStyle::FULL_NAME), "P. Martin");
+
+        assert_eq!(PersonName::parse("Smith J."), Some(PersonName::new(Some("J."), "Smith")));
+        assert_eq!(PersonName::parse("O'Brien"), Some(PersonName::new(None, "O'Brien")));
+        assert_eq!(PersonName::parse("o'brien").unwrap().format(&NameStyle::FULL_NAME), "O'brien");
+
+        let long = PersonName::new(Some("Anna-Lena"), "Schwarzenberg|Lichtenstein");
+        assert_eq!(long.to_field(&NameStyle::FULL_NAME), "Schwarzenberg Lichte");
+        assert_eq!(long.format_within(&NameStyle::INITIALS_SURNAME, 40), "A.-L. Schwarzenberg Lichtenstein");
+        assert!(long.to_field(&NameStyle::SURNAME_FIRST).chars().count() <= MAX_NAME_LENGTH);
+    }
+}
@@ -0,0 +1,276 @@
+use std::fmt::{self, Display};
+
+use crate::fencer::Fencer;
+use crate::locale::Locale;
+
+/// Nations known by their IOC code, with their ISO 3166 code (empty when the
+/// delegation has no flag of its own) and their English and French names.
+const NATIONS: &[(&str, &str, &str, &str)] = &[
+    ("AFG", "AF", "Afghanistan", "Afghanistan"),
+    ("AIN", "", "Individual Neutral Athletes", "Athlètes individuels neutres"),
+    ("ALB", "AL", "Albania", "Albanie"),
+    ("ALG", "DZ", "Algeria", "Algérie"),
+    ("AND", "AD", "Andorra", "Andorre"),
+    ("ANG", "AO", "Angola", "Angola"),
+    ("ANT", "AG", "Antigua and Barbuda", "Antigua-et-Barbuda"),
+    ("ARG", "AR", "Argentina", "Argentine"),
+    ("ARM", "AM", "Armenia", "Arménie"),
+    ("ARU", "AW", "Aruba", "Aruba"),
+    ("AUS", "AU", "Australia", "Australie"),
+    ("AUT", "AT", "Austria", "Autriche"),
+    ("AZE", "AZ", "Azerbaijan", "Azerbaïdjan"),
+    ("BAH", "BS", "Bahamas", "Bahamas"),
+    ("BAN", "BD", "Bangladesh", "Bangladesh"),
+    ("BAR", "BB", "Barbados", "Barbade"),
+    ("BEL", "BE", "Belgium", "Belgique"),
+    ("BEN", "BJ", "Benin", "Bénin"),
+    ("BER", "BM", "Bermuda", "Bermudes"),
+    ("BIH", "BA", "Bosnia and Herzegovina", "Bosnie-Herzégovine"),
+    ("BLR", "BY", "Belarus", "Biélorussie"),
+    ("BOL", "BO", "Bolivia", "Bolivie"),
+    ("BOT", "BW", "Botswana", "Botswana"),
+    ("BRA", "BR", "Brazil", "Brésil"),
+    ("BRN", "BH", "Bahrain", "Bahreïn"),
+    ("BUL", "BG", "Bulgaria", "Bulgarie"),
+    ("BUR", "BF", "Burkina Faso", "Burkina Faso"),
+    ("CAN", "CA", "Canada", "Canada"),
+    ("CGO", "CG", "Congo", "Congo"),
+    ("CHI", "CL", "Chile", "Chili"),
+    ("CHN", "CN", "China", "Chine"),
+    ("CIV", "CI", "Côte d'Ivoire", "Côte d'Ivoire"),
+    ("CMR", "CM", "Cameroon", "Cameroun"),
+    ("COD", "CD", "DR Congo", "RD Congo"),
+    ("COL", "CO", "Colombia", "Colombie"),
+    ("CRC", "CR", "Costa Rica", "Costa Rica"),
+    ("CRO", "HR", "Croatia", "Croatie"),
+    ("CUB", "CU", "Cuba", "Cuba"),
+    ("CYP", "CY", "Cyprus", "Chypre"),
+    ("CZE", "CZ", "Czechia", "Tchéquie"),
+    ("DEN", "DK", "Denmark", "Danemark"),
+    ("DOM", "DO", "Dominican Republic", "République dominicaine"),
+    ("ECU", "EC", "Ecuador", "Équateur"),
+    ("EGY", "EG", "Egypt", "Égypte"),
+    ("ESA", "SV", "El Salvador", "Salvador"),
+    ("ESP", "ES", "Spain", "Espagne"),
+    ("EST", "EE", "Estonia", "Estonie"),
+    ("FIN", "FI", "Finland", "Finlande"),
+    ("FRA", "FR", "France", "France"),
+    ("GAB", "GA", "Gabon", "Gabon"),
+    ("GBR", "GB", "Great Britain", "Grande-Bretagne"),
+    ("GEO", "GE", "Georgia", "Géorgie"),
+    ("GER", "DE", "Germany", "Allemagne"),
+    ("GHA", "GH", "Ghana", "Ghana"),
+    ("GRE", "GR", "Greece", "Grèce"),
+    ("GUA", "GT", "Guatemala", "Guatemala"),
+    ("HAI", "HT", "Haiti", "Haïti"),
+    ("HKG", "HK", "Hong Kong, China", "Hong Kong, Chine"),
+    ("HON", "HN", "Honduras", "Honduras"),
+    ("HUN", "HU", "Hungary", "Hongrie"),
+    ("INA", "ID", "Indonesia", "Indonésie"),
+    ("IND", "IN", "India", "Inde"),
+    ("IRI", "IR", "Iran", "Iran"),
+    ("IRL", "IE", "Ireland", "Irlande"),
+    ("IRQ", "IQ", "Iraq", "Irak"),
+    ("ISL", "IS", "Iceland", "Islande"),
+    ("ISR", "IL", "Israel", "Israël"),
+    ("ISV", "VI", "US Virgin Islands", "Îles Vierges américaines"),
+    ("ITA", "IT", "Italy", "Italie"),
+    ("JAM", "JM", "Jamaica", "Jamaïque"),
+    ("JOR", "JO", "Jordan", "Jordanie"),
+    ("JPN", "JP", "Japan", "Japon"),
+    ("KAZ", "KZ", "Kazakhstan", "Kazakhstan"),
+    ("KEN", "KE", "Kenya", "Kenya"),
+    ("KGZ", "KG", "Kyrgyzstan", "Kirghizistan"),
+    ("KOR", "KR", "Korea", "Corée"),
+    ("KSA", "SA", "Saudi Arabia", "Arabie saoudite"),
+    ("KUW", "KW", "Kuwait", "Koweït"),
+    ("LAT", "LV", "Latvia", "Lettonie"),
+    ("LBA", "LY", "Libya", "Libye"),
+    ("LBN", "LB", "Lebanon", "Liban"),
+    ("LIE", "LI", "Liechtenstein", "Liechtenstein"),
+    ("LTU", "LT", "Lithuania", "Lituanie"),
+    ("LUX", "LU", "Luxembourg", "Luxembourg"),
+    ("MAC", "MO", "Macao, China", "Macao, Chine"),
+    ("MAD", "MG", "Madagascar", "Madagascar"),
+    ("MAR", "MA", "Morocco", "Maroc"),
+    ("MAS", "MY", "Malaysia", "Malaisie"),
+    ("MDA", "MD", "Moldova", "Moldavie"),
+    ("MEX", "MX", "Mexico", "Mexique"),
+    ("MGL", "MN", "Mongolia", "Mongolie"),
+    ("MKD", "MK", "North Macedonia", "Macédoine du Nord"),
+    ("MLI", "ML", "Mali", "Mali"),
+    ("MLT", "MT", "Malta", "Malte"),
+    ("MNE", "ME", "Montenegro", "Monténégro"),
+    ("MON", "MC", "Monaco", "Monaco"),
+    ("NED", "NL", "Netherlands", "Pays-Bas"),
+    ("NEP", "NP", "Nepal", "Népal"),
+    ("NGR", "NG", "Nigeria", "Nigeria"),
+    ("NOR", "NO", "Norway", "Norvège"),
+    ("NZL", "NZ", "New Zealand", "Nouvelle-Zélande"),
+    ("PAK", "PK", "Pakistan", "Pakistan"),
+    ("PAN", "PA", "Panama", "Panama"),
+    ("PAR", "PY", "Paraguay", "Paraguay"),
+    ("PER", "PE", "Peru", "Pérou"),
+    ("PHI", "PH", "Philippines", "Philippines"),
+    ("POL", "PL", "Poland", "Pologne"),
+    ("POR", "PT", "Portugal", "Portugal"),
+    ("PRK", "KP", "DPR Korea", "RPD de Corée"),
+    ("PUR", "PR", "Puerto Rico", "Porto Rico"),
+    ("QAT", "QA", "Qatar", "Qatar"),
+    ("ROU", "RO", "Romania", "Roumanie"),
+    ("RSA", "ZA", "South Africa", "Afrique du Sud"),
+    ("RUS", "RU", "Russia", "Russie"),
+    ("SEN", "SN", "Senegal", "Sénégal"),
+    ("SGP", "SG", "Singapore", "Singapour"),
+    ("SLO", "SI", "Slovenia", "Slovénie"),
+    ("SMR", "SM", "San Marino", "Saint-Marin"),
+    ("SRB", "RS", "Serbia", "Serbie"),
+    ("SRI", "LK", "Sri Lanka", "Sri Lanka"),
+    ("SUI", "CH", "Switzerland", "Suisse"),
+    ("SVK", "SK", "Slovakia", "Slovaquie"),
+    ("SWE", "SE", "Sweden", "Suède"),
+    ("SYR", "SY", "Syria", "Syrie"),
+    ("THA", "TH", "Thailand", "Thaïlande"),
+    ("TJK", "TJ", "Tajikistan", "Tadjikistan"),
+    ("TKM", "TM", "Turkmenistan", "Turkménistan"),
+    ("TPE", "", "Chinese Taipei", "Taipei chinois"),
+    ("TTO", "TT", "Trinidad and Tobago", "Trinité-et-Tobago"),
+    ("TUN", "TN", "Tunisia", "Tunisie"),
+    ("TUR", "TR", "Türkiye", "Turquie"),
+    ("UAE", "AE", "United Arab Emirates", "Émirats arabes unis"),
+    ("UGA", "UG", "Uganda", "Ouganda"),
+    ("UKR", "UA", "Ukraine", "Ukraine"),
+    ("URU", "UY", "Uruguay", "Uruguay"),
+    ("USA", "US", "United States", "États-Unis"),
+    ("UZB", "UZ", "Uzbekistan", "Ouzbékistan"),
+    ("VEN", "VE", "Venezuela", "Venezuela"),
+    ("VIE", "VN", "Vietnam", "Viêt Nam"),
+    ("ZAM", "ZM", "Zambia", "Zambie"),
+    ("ZIM", "ZW", "Zimbabwe", "Zimbabwe"),
+];
+
+/// Three-letter IOC nation code of a fencer or referee, such as `FRA` or `GER`.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::locale::Locale;
+/// use cyrano::nation::NationCode;
+///
+/// let germany = NationCode::parse(" ger ").unwrap();
+/// assert_eq!(germany.as_str(), "GER");
+/// assert_eq!(germany.iso_code(), Some("DE"));
+/// assert_eq!(germany.flag_emoji().as_deref(), Some("🇩🇪"));
+/// assert_eq!(germany.display_name(Locale::English), Some("Germany"));
+/// assert_eq!(NationCode::parse("FR"), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NationCode([u8; 3]);
+
+impl NationCode {
+    /// Reads a nation code, ignoring case and surrounding whitespace.
+    ///
+    /// # Returns
+    ///
+    /// The code, or `None` if the value is not three ASCII letters. Codes
+    /// that are not in the table of known nations are accepted.
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code.trim().as_bytes();
+        match code {
+            [a, b, c] if code.iter().all(u8::is_ascii_alphabetic) => Some(NationCode([
+                a.to_ascii_uppercase(),
+                b.to_ascii_uppercase(),
+                c.to_ascii_uppercase(),
+            ])),
+            _ => None,
+        }
+    }
+
+    /// Returns the code in uppercase.
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+
+    fn nation(&self) -> Option<&'static (&'static str, &'static str, &'static str, &'static str)> {
+        NATIONS.iter().find(|(ioc, ..)| *ioc == self.as_str())
+    }
+
+    /// Returns the two-letter ISO 3166 code of the country, which often
+    /// differs from the IOC code (`GER` is `DE`, `SUI` is `CH`).
+    ///
+    /// # Returns
+    ///
+    /// The ISO code, or `None` for unknown codes and delegations without a
+    /// country, such as neutral athletes.
+    pub fn iso_code(&self) -> Option<&'static str> {
+        self.nation().map(|(_, iso, ..)| *iso).filter(|iso| !iso.is_empty())
+    }
+
+    /// Returns the flag of the country as an emoji, made of the two regional
+    /// indicator symbols of its ISO code.
+    ///
+    /// # Returns
+    ///
+    /// The flag, or `None` when [`iso_code`](NationCode::iso_code) is `None`.
+    pub fn flag_emoji(&self) -> Option<String> {
+        let iso = self.iso_code()?;
+        iso.chars()
+            .map(|letter| char::from_u32(0x1F1E6 + u32::from(letter) - u32::from('A')))
+            .collect()
+    }
+
+    /// Returns the name of the country in the given language.
+    ///
+    /// # Returns
+    ///
+    /// The name, or `None` for codes that are not in the table of known nations.
+    pub fn display_name(&self, locale: Locale) -> Option<&'static str> {
+        let (_, _, english, _french) = self.nation()?;
+        match locale {
+            Locale::English => Some(english),
+            #[cfg(feature = "locale-fr")]
+            Locale::French => Some(_french),
+        }
+    }
+}
+
+impl Display for NationCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Fencer {
+    /// Returns the nation code of the fencer, if it is three letters.
+    pub fn nation_code(&self) -> Option<NationCode> {
+        self.nation.as_deref().and_then(NationCode::parse)
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_every_nation_has_a_flag_and_name() {
+        let codes: HashSet<&str> = NATIONS.iter().map(|(ioc, ..)| *ioc).collect();
+        assert_eq!(codes.len(), NATIONS.len());
+
+        for (ioc, iso, ..) in NATIONS.iter().copied() {
+            let code = NationCode::parse(ioc).unwrap();
+            assert!(code.display_name(Locale::English).is_some());
+            match code.flag_emoji() {
+                Some(flag) => assert_eq!(flag.chars().count(), 2),
+                None => assert!(iso.is_empty()),
+            }
+        }
+
+        assert_eq!(NationCode::parse("sui").unwrap().flag_emoji().as_deref(), Some("🇨🇭"));
+        assert_eq!(NationCode::parse("AIN").unwrap().flag_emoji(), None);
+        assert_eq!(NationCode::parse("XYZ").unwrap().display_name(Locale::English), None);
+        assert_eq!(NationCode::parse("F1A"), None);
+    }
+}
@@ -0,0 +1,984 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::error::{FrameSource, ParseError, ParseErrorHook};
+use crate::message::Message;
+use crate::options::ParseOptions;
+use crate::piste::PisteId;
+use crate::sequence::Reorderer;
+use crate::stream::{FrameDecoder, FrameTrailer};
+
+/// Largest payload of a UDP datagram over IPv4.
+pub const MAX_DATAGRAM_SIZE: usize = 65_507;
+
+/// Standard UDP port of the protocol, on which both the apparatus and the
+/// software listen. The piste is named in every frame, not by the port.
+pub const DEFAULT_PORT: u16 = 50100;
+
+/// Local address an endpoint listens on.
+///
+/// Venue networks often have several interfaces and VLANs: binding to the
+/// address of one interface, rather than to every interface, keeps the
+/// traffic of other networks out. IPv6 addresses are supported.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::Ipv4Addr;
+/// use cyrano::net::BindConfig;
+///
+/// let config = BindConfig::new().address(Ipv4Addr::new(10, 1, 0, 2)).port(50200);
+/// assert_eq!(config.socket_addr().to_string(), "10.1.0.2:50200");
+/// assert_eq!(BindConfig::new().ipv6().socket_addr().to_string(), "[::]:50100");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BindConfig {
+    address: IpAddr,
+    port: u16,
+}
+
+impl Default for BindConfig {
+    fn default() -> Self {
+        BindConfig {
+            address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+impl BindConfig {
+    /// Creates a configuration listening on every IPv4 interface on [`DEFAULT_PORT`].
+    pub fn new() -> Self {
+        BindConfig::default()
+    }
+
+    /// Listens on the interface with the given address only.
+    pub fn address(mut self, address: impl Into<IpAddr>) -> Self {
+        self.address = address.into();
+        self
+    }
+
+    /// Listens on every IPv6 interface. Depending on the system, IPv4 traffic
+    /// may be received as well, from IPv4-mapped addresses.
+    pub fn ipv6(mut self) -> Self {
+        self.address = IpAddr::V6(Ipv6Addr::UNSPECIFIED);
+        self
+    }
+
+    /// Listens on the given port instead of [`DEFAULT_PORT`]; 0 lets the system choose.
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Returns the socket address to bind to.
+    pub fn socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.address, self.port)
+    }
+}
+
+/// Source addresses allowed to speak for each piste.
+///
+/// Messages naming a piste with registered sources are dropped when they come
+/// from another address, so that a misconfigured apparatus on another VLAN
+/// cannot overwrite a piste. Pistes without registered sources are accepted
+/// from anywhere.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use cyrano::net::SourceFilter;
+///
+/// let filter = SourceFilter::new().allow("17", Ipv4Addr::new(10, 1, 0, 17));
+/// assert!(filter.accepts("17", IpAddr::V4(Ipv4Addr::new(10, 1, 0, 17))));
+/// assert!(!filter.accepts("17", IpAddr::V4(Ipv4Addr::new(10, 2, 0, 17))));
+/// assert!(filter.accepts("3", IpAddr::V4(Ipv4Addr::new(10, 2, 0, 3))));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceFilter {
+    allowed: HashMap<PisteId, Vec<IpAddr>>,
+}
+
+impl SourceFilter {
+    /// Creates a filter accepting every source.
+    pub fn new() -> Self {
+        SourceFilter::default()
+    }
+
+    /// Allows an address to speak for a piste, in addition to those already allowed.
+    pub fn allow(mut self, piste: impl Into<PisteId>, address: impl Into<IpAddr>) -> Self {
+        self.allowed.entry(piste.into()).or_default().push(address.into());
+        self
+    }
+
+    /// Returns `true` if messages for `piste` are accepted from `address`.
+    ///
+    /// IPv4-mapped IPv6 addresses match the IPv4 address they carry.
+    pub fn accepts(&self, piste: &str, address: IpAddr) -> bool {
+        let address = address.to_canonical();
+        self.allowed
+            .get(&PisteId::from(piste))
+            .is_none_or(|allowed| allowed.iter().any(|ip| ip.to_canonical() == address))
+    }
+}
+
+/// A piste heard on the network by [`UdpEndpoint::discover`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Announcement {
+    /// Piste named in the messages.
+    pub piste: PisteId,
+    /// Address the messages were sent from.
+    pub source: SocketAddr,
+    /// Protocol version of the messages (e.g. "EFP1.1").
+    pub protocol: String,
+}
+
+/// Complete configuration of a [`UdpEndpoint`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::net::Ipv4Addr;
+/// use cyrano::net::{BindConfig, EndpointConfig, UdpEndpoint};
+///
+/// // The standard setup, on the competition VLAN only.
+/// let config = EndpointConfig::standard().bind(BindConfig::new().address(Ipv4Addr::new(10, 1, 0, 2)));
+/// let endpoint = UdpEndpoint::open(&config)?;
+///
+/// let apparatus = config.peer_addr(Ipv4Addr::new(10, 1, 0, 17));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EndpointConfig {
+    bind: BindConfig,
+    peer_port: Option<u16>,
+    broadcast: bool,
+    multicast_group: Option<Ipv4Addr>,
+    sources: SourceFilter,
+    options: ParseOptions,
+}
+
+impl EndpointConfig {
+    /// Returns the setup described by the protocol: one message per datagram,
+    /// exchanged on [`DEFAULT_PORT`] on both sides, listening on every IPv4
+    /// interface, with lenient parsing.
+    pub fn standard() -> Self {
+        EndpointConfig::default()
+    }
+
+    /// Sets the local address to listen on.
+    pub fn bind(mut self, bind: BindConfig) -> Self {
+        self.bind = bind;
+        self
+    }
+
+    /// Sets the port the peer listens on, when it differs from the local port.
+    pub fn peer_port(mut self, port: u16) -> Self {
+        self.peer_port = Some(port);
+        self
+    }
+
+    /// Allows sending to broadcast addresses.
+    pub fn broadcast(mut self, broadcast: bool) -> Self {
+        self.broadcast = broadcast;
+        self
+    }
+
+    /// Joins an IPv4 multicast group on the bound interface.
+    pub fn multicast_group(mut self, group: Ipv4Addr) -> Self {
+        self.multicast_group = Some(group);
+        self
+    }
+
+    /// Sets the source filter of the endpoint.
+    pub fn source_filter(mut self, filter: SourceFilter) -> Self {
+        self.sources = filter;
+        self
+    }
+
+    /// Sets the options used to parse received datagrams.
+    pub fn options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the address to send to a peer at the given address.
+    pub fn peer_addr(&self, address: impl Into<IpAddr>) -> SocketAddr {
+        let port = self.peer_port.unwrap_or(match self.bind.port {
+            0 => DEFAULT_PORT,
+            port => port,
+        });
+        SocketAddr::new(address.into(), port)
+    }
+}
+
+/// UDP endpoint exchanging EFP messages, one message per datagram.
+///
+/// Datagrams that do not parse are skipped by [`recv_from`](UdpEndpoint::recv_from)
+/// after being handed, byte for byte, to the hook registered with
+/// [`on_parse_error`](UdpEndpoint::on_parse_error), so applications can keep
+/// them for vendor escalation.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::fs::OpenOptions;
+/// use std::io::Write;
+/// use cyrano::net::UdpEndpoint;
+///
+/// let mut rejected = OpenOptions::new().append(true).create(true).open("rejected.bin")?;
+/// let mut endpoint = UdpEndpoint::bind("0.0.0.0:50100")?.on_parse_error(move |raw, err, source| {
+///     eprintln!("{}: {}", source, err);
+///     let _ = rejected.write_all(raw);
+///     let _ = rejected.write_all(b"\n");
+/// });
+///
+/// let (message, from) = endpoint.recv_from()?;
+/// println!("{} from {}", message.command, from);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct UdpEndpoint {
+    socket: UdpSocket,
+    options: ParseOptions,
+    buffer: Vec<u8>,
+    on_parse_error: Option<ParseErrorHook>,
+    sources: SourceFilter,
+    reorderer: Option<Reorderer>,
+    released: VecDeque<(Message, SocketAddr)>,
+}
+
+impl UdpEndpoint {
+    /// Binds an endpoint to a local address.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the socket cannot be bound.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(UdpEndpoint::from_socket(UdpSocket::bind(addr)?))
+    }
+
+    /// Binds an endpoint as described by a [`BindConfig`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the socket cannot be bound.
+    pub fn bind_with(config: &BindConfig) -> io::Result<Self> {
+        UdpEndpoint::bind(config.socket_addr())
+    }
+
+    /// Binds and configures an endpoint as described by an [`EndpointConfig`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the socket cannot be bound or configured.
+    pub fn open(config: &EndpointConfig) -> io::Result<Self> {
+        let endpoint = UdpEndpoint::bind_with(&config.bind)?
+            .options(config.options.clone())
+            .source_filter(config.sources.clone());
+
+        if config.broadcast {
+            endpoint.set_broadcast(true)?;
+        }
+        if let Some(group) = config.multicast_group {
+            let interface = match config.bind.address {
+                IpAddr::V4(address) => address,
+                IpAddr::V6(_) => Ipv4Addr::UNSPECIFIED,
+            };
+            endpoint.join_multicast(group, interface)?;
+        }
+        Ok(endpoint)
+    }
+
+    /// Wraps an already configured socket.
+    pub fn from_socket(socket: UdpSocket) -> Self {
+        UdpEndpoint {
+            socket,
+            options: ParseOptions::default(),
+            buffer: vec![0; MAX_DATAGRAM_SIZE],
+            on_parse_error: None,
+            sources: SourceFilter::default(),
+            reorderer: None,
+            released: VecDeque::new(),
+        }
+    }
+
+    /// Sets the options used to parse received datagrams.
+    pub fn options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Registers a hook called with every datagram that does not parse.
+    pub fn on_parse_error(
+        mut self,
+        hook: impl FnMut(&[u8], &ParseError, &FrameSource) + Send + 'static,
+    ) -> Self {
+        self.on_parse_error = Some(Box::new(hook));
+        self
+    }
+
+    /// Drops the messages coming from addresses not allowed for their piste.
+    pub fn source_filter(mut self, filter: SourceFilter) -> Self {
+        self.sources = filter;
+        self
+    }
+
+    /// Receives sequence-numbered frames (see [`Message::parse_sequenced`]),
+    /// passing them on in sequence order.
+    ///
+    /// Frames ahead of the one expected are held back by the reorderer, and
+    /// released with the address of the datagram that completed the sequence.
+    /// Frames without a sequence number are passed on as they come.
+    pub fn sequenced(mut self, reorderer: Reorderer) -> Self {
+        self.reorderer = Some(reorderer);
+        self
+    }
+
+    /// Returns the reorderer of a sequenced endpoint, e.g. to count the frames
+    /// missed so far.
+    pub fn reorderer(&self) -> Option<&Reorderer> {
+        self.reorderer.as_ref()
+    }
+
+    /// Returns the underlying socket, e.g. to set a read timeout.
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Joins an IPv4 multicast group to receive the messages sent to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Multicast group address, e.g. `239.0.0.1`
+    /// * `interface` - Address of the local interface, or `0.0.0.0` to let the system choose
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the group cannot be joined.
+    pub fn join_multicast(&self, group: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+        self.socket.join_multicast_v4(&group, &interface)
+    }
+
+    /// Joins an IPv6 multicast group to receive the messages sent to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Multicast group address, e.g. `ff02::1234`
+    /// * `interface` - Index of the local interface, or 0 to let the system choose
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the group cannot be joined.
+    pub fn join_multicast_v6(&self, group: Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.socket.join_multicast_v6(&group, interface)
+    }
+
+    /// Leaves an IPv4 multicast group joined with [`join_multicast`](UdpEndpoint::join_multicast).
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the group cannot be left.
+    pub fn leave_multicast(&self, group: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+        self.socket.leave_multicast_v4(&group, &interface)
+    }
+
+    /// Allows sending to broadcast addresses.
+    ///
+    /// Receiving broadcast only requires binding to the wildcard address.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the option cannot be set.
+    pub fn set_broadcast(&self, broadcast: bool) -> io::Result<()> {
+        self.socket.set_broadcast(broadcast)
+    }
+
+    /// Returns the local address the endpoint is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Waits for the next datagram that parses as a message.
+    ///
+    /// # Returns
+    ///
+    /// The message and the address it was sent from.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the socket, including timeouts.
+    pub fn recv_from(&mut self) -> io::Result<(Message, SocketAddr)> {
+        loop {
+            if let Some(released) = self.released.pop_front() {
+                return Ok(released);
+            }
+
+            let (len, from) = self.socket.recv_from(&mut self.buffer)?;
+            let raw = &self.buffer[..len];
+            let sequenced = self.reorderer.is_some();
+            let Some((message, sequence)) = parse_frame(raw, &self.options, sequenced, &mut self.on_parse_error, from) else {
+                continue;
+            };
+            if !self.sources.accepts(&message.piste, from.ip()) {
+                continue;
+            }
+            match (self.reorderer.as_mut(), sequence) {
+                (Some(reorderer), Some(sequence)) => {
+                    let released = reorderer.offer(sequence, message);
+                    self.released.extend(released.into_iter().map(|message| (message, from)));
+                }
+                _ => return Ok((message, from)),
+            }
+        }
+    }
+
+    /// Listens for `timeout` and returns the pistes heard in the meantime.
+    ///
+    /// Every message received counts as an announcement, so both the HELLO of
+    /// an apparatus starting up and the INFO of a running bout are collected.
+    /// Datagrams that do not parse go to the parse error hook as usual.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the socket, other than the end of the timeout.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::net::Ipv4Addr;
+    /// use std::time::Duration;
+    /// use cyrano::net::UdpEndpoint;
+    ///
+    /// let mut endpoint = UdpEndpoint::bind("0.0.0.0:50100")?;
+    /// endpoint.join_multicast(Ipv4Addr::new(239, 0, 0, 1), Ipv4Addr::UNSPECIFIED)?;
+    ///
+    /// for announcement in endpoint.discover(Duration::from_secs(5))? {
+    ///     println!("piste {} at {} ({})", announcement.piste, announcement.source, announcement.protocol);
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn discover(&mut self, timeout: Duration) -> io::Result<BTreeSet<Announcement>> {
+        let previous_timeout = self.socket.read_timeout()?;
+        let deadline = Instant::now() + timeout;
+        let mut heard = BTreeSet::new();
+
+        let result = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break Ok(());
+            }
+            self.socket.set_read_timeout(Some(remaining))?;
+
+            match self.recv_from() {
+                Ok((message, source)) => {
+                    heard.insert(Announcement {
+                        piste: PisteId::new(message.piste.to_string()),
+                        source,
+                        protocol: message.protocol.to_string(),
+                    });
+                }
+                Err(err) if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                    break Ok(());
+                }
+                Err(err) => break Err(err),
+            }
+        };
+
+        self.socket.set_read_timeout(previous_timeout)?;
+        result.map(|()| heard)
+    }
+
+    /// Sends a message as a single datagram.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the socket.
+    pub fn send_to(&self, message: &Message, addr: impl ToSocketAddrs) -> io::Result<usize> {
+        self.socket.send_to(message.to_string().as_bytes(), addr)
+    }
+}
+
+/// TCP connection exchanging EFP messages over a byte stream.
+///
+/// Used where EFP is tunneled over TCP, through serial-to-TCP bridges or VPNs.
+/// Frames are delimited with a [`FrameDecoder`]; those that do not parse are
+/// skipped after being handed to the parse error hook, as with [`UdpEndpoint`].
+/// Protocol state is kept by a [`ProtocolSession`](crate::session::ProtocolSession),
+/// exactly as over UDP.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cyrano::net::TcpConnection;
+/// use cyrano::session::{ProtocolSession, Role};
+///
+/// let mut connection = TcpConnection::connect("10.0.0.17:50103")?;
+/// let mut session = ProtocolSession::new(Role::Software, "17");
+///
+/// loop {
+///     let message = connection.recv()?;
+///     if let Some(reply) = session.handle_incoming(&message) {
+///         connection.send(&reply)?;
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct TcpConnection {
+    stream: TcpStream,
+    peer: SocketAddr,
+    options: ParseOptions,
+    decoder: FrameDecoder,
+    buffer: Vec<u8>,
+    on_parse_error: Option<ParseErrorHook>,
+    sources: SourceFilter,
+    reorderer: Option<Reorderer>,
+    released: VecDeque<Message>,
+}
+
+impl TcpConnection {
+    /// Connects to a remote endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the connection cannot be established.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        TcpConnection::from_stream(TcpStream::connect(addr)?)
+    }
+
+    /// Wraps an already connected stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the peer address cannot be read.
+    pub fn from_stream(stream: TcpStream) -> io::Result<Self> {
+        Ok(TcpConnection {
+            peer: stream.peer_addr()?,
+            stream,
+            options: ParseOptions::default(),
+            decoder: FrameDecoder::new(),
+            buffer: vec![0; MAX_DATAGRAM_SIZE],
+            on_parse_error: None,
+            sources: SourceFilter::default(),
+            reorderer: None,
+            released: VecDeque::new(),
+        })
+    }
+
+    /// Sets the options used to parse received frames.
+    pub fn options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self.expect_trailer();
+        self
+    }
+
+    /// Registers a hook called with every frame that does not parse.
+    pub fn on_parse_error(
+        mut self,
+        hook: impl FnMut(&[u8], &ParseError, &FrameSource) + Send + 'static,
+    ) -> Self {
+        self.on_parse_error = Some(Box::new(hook));
+        self
+    }
+
+    /// Drops the messages for pistes the peer address is not allowed to speak for.
+    pub fn source_filter(mut self, filter: SourceFilter) -> Self {
+        self.sources = filter;
+        self
+    }
+
+    /// Receives sequence-numbered frames (see [`Message::parse_sequenced`]),
+    /// passing them on in sequence order.
+    ///
+    /// Frames without a sequence number are passed on as they come. Frames
+    /// still held back when the peer closes the connection are released then.
+    pub fn sequenced(mut self, reorderer: Reorderer) -> Self {
+        self.reorderer = Some(reorderer);
+        self.expect_trailer();
+        self
+    }
+
+    /// Has the decoder keep the sequence number or checksum trailer expected
+    /// after each frame.
+    fn expect_trailer(&mut self) {
+        let trailer = match (&self.reorderer, self.options.checksum) {
+            (Some(_), _) => Some(FrameTrailer::Sequence),
+            (None, Some(algorithm)) => Some(FrameTrailer::Checksum(algorithm)),
+            (None, None) => None,
+        };
+        let decoder = std::mem::take(&mut self.decoder);
+        self.decoder = match trailer {
+            Some(trailer) => decoder.trailer(trailer),
+            None => decoder,
+        };
+    }
+
+    /// Returns the reorderer of a sequenced connection, e.g. to count the
+    /// frames missed so far.
+    pub fn reorderer(&self) -> Option<&Reorderer> {
+        self.reorderer.as_ref()
+    }
+
+    /// Returns the underlying stream, e.g. to set a read timeout.
+    pub fn stream(&self) -> &TcpStream {
+        &self.stream
+    }
+
+    /// Returns the address of the remote endpoint.
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Waits for the next frame that parses as a message.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the stream, including timeouts, and
+    /// `io::ErrorKind::UnexpectedEof` once the peer has closed the connection.
+    pub fn recv(&mut self) -> io::Result<Message> {
+        loop {
+            if let Some(message) = self.released.pop_front() {
+                return Ok(message);
+            }
+            if let Some(raw) = self.decoder.next_frame() {
+                if let Some(message) = self.accept_frame(&raw) {
+                    return Ok(message);
+                }
+                continue;
+            }
+
+            let len = self.stream.read(&mut self.buffer)?;
+            if len == 0 {
+                while let Some(raw) = self.decoder.finish() {
+                    if let Some(message) = self.accept_frame(&raw) {
+                        self.released.push_back(message);
+                    }
+                }
+                if let Some(reorderer) = self.reorderer.as_mut() {
+                    self.released.extend(reorderer.flush());
+                }
+                return self.released.pop_front().ok_or_else(|| io::ErrorKind::UnexpectedEof.into());
+            }
+            self.decoder.push(&self.buffer[..len]);
+        }
+    }
+
+    /// Parses a frame, returning the message to pass on now; frames held back
+    /// or released by the reorderer go through `released`.
+    fn accept_frame(&mut self, raw: &[u8]) -> Option<Message> {
+        let sequenced = self.reorderer.is_some();
+        let (message, sequence) = parse_frame(raw, &self.options, sequenced, &mut self.on_parse_error, self.peer)
+            .filter(|(message, _)| self.sources.accepts(&message.piste, self.peer.ip()))?;
+        match (self.reorderer.as_mut(), sequence) {
+            (Some(reorderer), Some(sequence)) => {
+                self.released.extend(reorderer.offer(sequence, message));
+                None
+            }
+            _ => Some(message),
+        }
+    }
+
+    /// Sends a message as a single frame, followed by a line break so that
+    /// the peer can tell where the frame ends without waiting for the next one.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the stream.
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        self.stream.write_all(format!("{}\r\n", message).as_bytes())
+    }
+}
+
+/// TCP listener accepting EFP connections.
+pub struct TcpServer {
+    listener: TcpListener,
+    options: ParseOptions,
+    sources: SourceFilter,
+    reorderer: Option<Reorderer>,
+}
+
+impl TcpServer {
+    /// Binds a server to a local address.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the listener cannot be bound.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(TcpServer {
+            listener: TcpListener::bind(addr)?,
+            options: ParseOptions::default(),
+            sources: SourceFilter::default(),
+            reorderer: None,
+        })
+    }
+
+    /// Binds a server as described by a [`BindConfig`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the listener cannot be bound.
+    pub fn bind_with(config: &BindConfig) -> io::Result<Self> {
+        TcpServer::bind(config.socket_addr())
+    }
+
+    /// Sets the options used to parse frames on accepted connections.
+    pub fn options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the source filter applied on accepted connections.
+    pub fn source_filter(mut self, filter: SourceFilter) -> Self {
+        self.sources = filter;
+        self
+    }
+
+    /// Receives sequence-numbered frames on accepted connections, each with
+    /// its own copy of the reorderer; see [`TcpConnection::sequenced`].
+    pub fn sequenced(mut self, reorderer: Reorderer) -> Self {
+        self.reorderer = Some(reorderer);
+        self
+    }
+
+    /// Returns the local address the server is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Waits for the next incoming connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the listener.
+    pub fn accept(&self) -> io::Result<TcpConnection> {
+        let (stream, _) = self.listener.accept()?;
+        let connection = TcpConnection::from_stream(stream)?
+            .options(self.options.clone())
+            .source_filter(self.sources.clone());
+        Ok(match self.reorderer.clone() {
+            Some(reorderer) => connection.sequenced(reorderer),
+            None => connection,
+        })
+    }
+}
+
+/// Parses a received frame, with its sequence number if `sequenced`, handing
+/// it to the hook if it does not parse.
+fn parse_frame(
+    raw: &[u8],
+    options: &ParseOptions,
+    sequenced: bool,
+    hook: &mut Option<ParseErrorHook>,
+    from: SocketAddr,
+) -> Option<(Message, Option<u32>)> {
+    let parsed = std::str::from_utf8(raw)
+        .map_err(|_| ParseError::InvalidFormat)
+        .and_then(|text| {
+            if sequenced {
+                Message::parse_sequenced(text, options)
+            } else {
+                Message::parse_with(text, options).map(|message| (message, None))
+            }
+        });
+
+    match parsed {
+        Ok(message) => Some(message),
+        Err(err) => {
+            if let Some(hook) = hook.as_mut() {
+                hook(raw, &err, &FrameSource::Network(from));
+            }
+            None
+        }
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_parse_error_hook() {
+        let rejected = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&rejected);
+        let mut receiver = UdpEndpoint::bind("127.0.0.1:0")
+            .unwrap()
+            .on_parse_error(move |raw, err, source| sink.lock().unwrap().push((raw.to_vec(), err.to_string(), source.clone())));
+        let sender = UdpEndpoint::bind("127.0.0.1:0").unwrap();
+        let to = receiver.local_addr().unwrap();
+
+        sender.socket().send_to(b"|EFP9|HELLO|17|%|", to).unwrap();
+        sender.socket().send_to(b"\xff\xfe", to).unwrap();
+        let hello = Message::try_from("|EFP1.1|HELLO|17|fm-eq|%|").unwrap();
+        sender.send_to(&hello, to).unwrap();
+
+        let (message, from) = receiver.recv_from().unwrap();
+        assert_eq!(message, hello);
+        assert_eq!(from, sender.local_addr().unwrap());
+
+        let rejected = rejected.lock().unwrap();
+        assert_eq!(rejected.len(), 2);
+        assert_eq!(rejected[0].0, b"|EFP9|HELLO|17|%|");
+        assert_eq!(rejected[1].0, b"\xff\xfe");
+        assert_eq!(rejected[1].2, FrameSource::Network(from));
+    }
+
+    #[test]
+    fn test_discover() {
+        let mut receiver = UdpEndpoint::bind("127.0.0.1:0").unwrap();
+        let sender = UdpEndpoint::bind("127.0.0.1:0").unwrap();
+        let to = receiver.local_addr().unwrap();
+
+        for frame in ["|EFP1.1|HELLO|17|fm-eq|%|", "|EFP1|INFO|3|fm-eq|%|", "|EFP1.1|INFO|17|fm-eq|%|"] {
+            sender.send_to(&Message::try_from(frame).unwrap(), to).unwrap();
+        }
+
+        let heard = receiver.discover(Duration::from_millis(200)).unwrap();
+        let pistes: Vec<(&str, &str)> = heard.iter().map(|a| (a.piste.as_str(), a.protocol.as_str())).collect();
+        assert_eq!(pistes, [("3", "EFP1"), ("17", "EFP1.1")]);
+        assert!(heard.iter().all(|a| a.source == sender.local_addr().unwrap()));
+        assert_eq!(receiver.socket().read_timeout().unwrap(), None);
+    }
+
+    #[test]
+    fn test_endpoint_config() {
+        let config = EndpointConfig::standard();
+        assert_eq!(config.peer_addr(Ipv4Addr::new(10, 0, 0, 17)).to_string(), "10.0.0.17:50100");
+
+        let config = config.bind(BindConfig::new().address(Ipv4Addr::LOCALHOST).port(0)).broadcast(true);
+        let endpoint = UdpEndpoint::open(&config).unwrap();
+        assert!(endpoint.socket().broadcast().unwrap());
+        assert_eq!(config.peer_port(50200).peer_addr(Ipv4Addr::LOCALHOST).port(), 50200);
+    }
+
+    #[test]
+    fn test_ipv6_and_source_filter() {
+        let config = BindConfig::new().address(Ipv6Addr::LOCALHOST).port(0);
+        let Ok(receiver) = UdpEndpoint::bind_with(&config) else {
+            // No IPv6 loopback on this host.
+            return;
+        };
+        let sender = UdpEndpoint::bind("[::1]:0").unwrap();
+        let mut receiver = receiver.source_filter(SourceFilter::new().allow("3", Ipv4Addr::new(10, 0, 0, 3)));
+        let to = receiver.local_addr().unwrap();
+
+        for frame in ["|EFP1.1|INFO|3|fm-eq|%|", "|EFP1.1|INFO|17|fm-eq|%|"] {
+            sender.send_to(&Message::try_from(frame).unwrap(), to).unwrap();
+        }
+
+        let (message, from) = receiver.recv_from().unwrap();
+        assert_eq!(message.piste, "17");
+        assert!(from.is_ipv6());
+    }
+
+    #[test]
+    fn test_sequenced_datagrams() {
+        let mut receiver = UdpEndpoint::bind("127.0.0.1:0").unwrap().sequenced(Reorderer::new());
+        let sender = UdpEndpoint::bind("127.0.0.1:0").unwrap();
+        let to = receiver.local_addr().unwrap();
+
+        let frame = |time: &str, sequence: u32| format!("|EFP1.1|INFO|17|fm-eq|1|A32|12||{}|%|{}", time, sequence);
+        for raw in [frame("2:59", 65535), frame("2:57", 1), frame("2:57", 1), frame("2:58", 0)] {
+            sender.socket().send_to(raw.as_bytes(), to).unwrap();
+        }
+        sender.socket().send_to(b"|EFP1.1|HELLO|17|fm-eq|%|", to).unwrap();
+
+        let times: Vec<Option<String>> = (0..4)
+            .map(|_| receiver.recv_from().unwrap().0.time.map(|time| time.to_string()))
+            .collect();
+        assert_eq!(times, [Some("2:59".into()), Some("2:58".into()), Some("2:57".into()), None]);
+        assert_eq!(receiver.reorderer().unwrap().dropped(), 1);
+    }
+
+    #[test]
+    fn test_sequenced_tcp_frames() {
+        let server = TcpServer::bind("127.0.0.1:0").unwrap().sequenced(Reorderer::new());
+        let client = TcpConnection::connect(server.local_addr().unwrap()).unwrap();
+        let mut accepted = server.accept().unwrap();
+
+        client.stream().write_all(b"|EFP1.1|INFO|17|fm-eq|1|A32|12||2:59|%|4\r\n").unwrap();
+        client.stream().write_all(b"|EFP1.1|INFO|17|fm-eq|1|A32|12||2:57|%|6\r\n").unwrap();
+        drop(client);
+
+        assert_eq!(accepted.recv().unwrap().time.as_deref(), Some("2:59"));
+        // The missing frame is given up when the peer closes the connection.
+        assert_eq!(accepted.recv().unwrap().time.as_deref(), Some("2:57"));
+        assert_eq!(accepted.recv().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(accepted.reorderer().unwrap().missed(), 1);
+    }
+
+    #[test]
+    fn test_tcp_roundtrip() {
+        let server = TcpServer::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpConnection::connect(server.local_addr().unwrap()).unwrap();
+        let rejected = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&rejected);
+        let mut accepted = server
+            .accept()
+            .unwrap()
+            .on_parse_error(move |raw, _, _| sink.lock().unwrap().push(raw.to_vec()));
+
+        let hello = Message::try_from("|EFP1.1|HELLO|17|fm-eq|%|").unwrap();
+        client.send(&hello).unwrap();
+        client.stream().write_all(b"|EFP9|HELLO|17|%|\r\n").unwrap();
+        client.send(&Message::new(crate::enums::Command::Ack, "17", "fm-eq")).unwrap();
+        drop(client);
+
+        assert_eq!(accepted.recv().unwrap(), hello);
+        assert_eq!(accepted.recv().unwrap().command, crate::enums::Command::Ack);
+        assert_eq!(accepted.recv().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(*rejected.lock().unwrap(), vec![b"|EFP9|HELLO|17|%|".to_vec()]);
+    }
+
+    #[test]
+    fn test_tcp_checksum_trailers() {
+        let options = ParseOptions {
+            checksum: Some(crate::trailer::ChecksumAlgorithm::Crc16),
+            ..ParseOptions::default()
+        };
+        let server = TcpServer::bind("127.0.0.1:0").unwrap().options(options);
+        let client = TcpConnection::connect(server.local_addr().unwrap()).unwrap();
+        let rejected = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&rejected);
+        let mut accepted = server
+            .accept()
+            .unwrap()
+            .on_parse_error(move |raw, _, _| sink.lock().unwrap().push(raw.to_vec()));
+
+        let info = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap();
+        let raw = info.to_string_with_checksum(crate::trailer::ChecksumAlgorithm::Crc16);
+        // The trailer arrives in a segment of its own, right after the final `%|`.
+        let (frame, trailer) = raw.split_at(info.to_string().len());
+        let mut stream = client.stream();
+        stream.write_all(frame.as_bytes()).unwrap();
+        stream.flush().unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        stream.write_all(format!("{}\r\n", trailer).as_bytes()).unwrap();
+        drop(client);
+
+        assert_eq!(accepted.recv().unwrap(), info);
+        assert_eq!(accepted.recv().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert!(rejected.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tcp_frames_without_line_break() {
+        let server = TcpServer::bind("127.0.0.1:0").unwrap();
+        let client = TcpConnection::connect(server.local_addr().unwrap()).unwrap();
+        let mut accepted = server.accept().unwrap();
+        accepted.stream().set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+        let info = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap();
+        let ack = Message::new(crate::enums::Command::Ack, "17", "fm-eq");
+        // Both frames are handed out while the connection is still open.
+        for message in [&info, &ack] {
+            client.stream().write_all(message.to_string().as_bytes()).unwrap();
+            assert_eq!(accepted.recv().unwrap(), *message);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use super::error::ParseError;
+use super::trailer::ChecksumAlgorithm;
+
+/// Options controlling how EFP messages are parsed.
+///
+/// The default options are lenient: invalid values in optional fields are
+/// treated as absent, which is how most apparatus firmware quirks are absorbed.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::message::Message;
+/// use cyrano::options::ParseOptions;
+///
+/// let raw = "|EFP1.1|INFO|17|fm-eq|1|A32|12|2|10:30|3:00|I|Q||W|%|";
+/// assert!(Message::parse_with(raw, &ParseOptions::default()).is_ok());
+/// assert!(Message::parse_with(raw, &ParseOptions::strict()).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Reject invalid non-empty values with `ParseError::InvalidValue` instead of
+    /// treating them as absent.
+    ///
+    /// Strict mode also rejects text values longer than the specification allows,
+    /// and white lights reported at épée or sabre.
+    pub strict: bool,
+    /// Size limits guarding against oversized or malicious input.
+    pub limits: ParseLimits,
+    /// Fields beyond the specification expected from the apparatus.
+    pub profile: VendorProfile,
+    /// Checksum trailer expected after the final `%|`, verified and removed
+    /// before parsing (see [`crate::trailer`]).
+    pub checksum: Option<ChecksumAlgorithm>,
+}
+
+/// Size limits applied before a message is split into fields.
+///
+/// The defaults are generous for real EFP traffic (a complete INFO frame is
+/// around 200 bytes) while bounding the work done on untrusted input.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::error::ParseError;
+/// use cyrano::message::Message;
+/// use cyrano::options::{ParseLimits, ParseOptions};
+///
+/// let options = ParseOptions {
+///     limits: ParseLimits { max_message_length: 16, ..ParseLimits::default() },
+///     ..ParseOptions::default()
+/// };
+///
+/// let result = Message::parse_with("|EFP1.1|HELLO|17|fm-eq|%|", &options);
+/// assert!(matches!(result, Err(ParseError::LimitExceeded { limit: "message_length", .. })));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Maximum length of a message, in bytes.
+    pub max_message_length: usize,
+    /// Maximum number of zones (separated by `%`).
+    pub max_zones: usize,
+    /// Maximum number of fields in a single zone.
+    pub max_fields_per_zone: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits {
+            max_message_length: 1024,
+            max_zones: 8,
+            max_fields_per_zone: 64,
+        }
+    }
+}
+
+impl ParseLimits {
+    /// Checks a raw message against the limits without allocating.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::LimitExceeded` naming the first limit exceeded.
+    pub fn check(&self, raw: &str) -> Result<(), ParseError> {
+        if raw.len() > self.max_message_length {
+            return Err(ParseError::LimitExceeded {
+                limit: "message_length",
+                max: self.max_message_length,
+            });
+        }
+
+        if raw.split('%').count() > self.max_zones {
+            return Err(ParseError::LimitExceeded {
+                limit: "zones",
+                max: self.max_zones,
+            });
+        }
+
+        // The delimiters at both ends of a zone add two empty pieces.
+        if raw.split('%').any(|zone| zone.split('|').count() > self.max_fields_per_zone + 2) {
+            return Err(ParseError::LimitExceeded {
+                limit: "fields_per_zone",
+                max: self.max_fields_per_zone,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Vendor extensions of the general zone that the parser reads.
+///
+/// Fields the specification leaves undefined are ignored by default, since
+/// apparatus do not agree on their meaning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VendorProfile {
+    /// The fields of the specification only.
+    #[default]
+    Standard,
+    /// Finals apparatus sending the cards given to team officials after the
+    /// referee, in EFP1.1 frames (see [`Sanctions`](crate::referee::Sanctions)).
+    Extended,
+}
+
+/// Policy applied to text values that cannot be transmitted as is.
+///
+/// See [`crate::limits`] for the field lengths and forbidden characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextPolicy {
+    /// Reject the value with a `ValueError`.
+    #[default]
+    Reject,
+    /// Replace forbidden characters by spaces and truncate to the maximum length.
+    Sanitize,
+}
+
+impl ParseOptions {
+    /// Returns lenient options (the default).
+    pub fn lenient() -> Self {
+        ParseOptions::default()
+    }
+
+    /// Returns strict options, rejecting invalid values.
+    pub fn strict() -> Self {
+        ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt::Write;
+
+use crate::enums::{ApparatusState, Side};
+use crate::fencer::Fencer;
+use crate::locale::{self, Locale};
+use crate::state::MatchState;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const WHITE: &str = "\x1b[97m";
+
+impl MatchState {
+    /// Renders the bout as a single line colored with ANSI escape codes.
+    ///
+    /// The left fencer comes first, as seen by spectators facing the piste.
+    /// Lights are shown in red (left) and green (right), off-target lights in
+    /// white, penalty cards as colored blocks, and nations with their flag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::message::Message;
+    /// use cyrano::state::MatchState;
+    ///
+    /// let mut state = MatchState::new("3");
+    /// state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+    ///
+    /// let line = state.render_ansi();
+    /// assert!(line.contains("B. Panini"));
+    /// assert!(line.contains("2:12"));
+    /// ```
+    pub fn render_ansi(&self) -> String {
+        self.render_ansi_localized(Locale::English)
+    }
+
+    /// Renders the bout like [`render_ansi`](MatchState::render_ansi), naming
+    /// the apparatus state in the given language.
+    pub fn render_ansi_localized(&self, locale: Locale) -> String {
+        let mut line = String::new();
+        let _ = write!(line, "{}[{}]{} ", BOLD, self.piste(), RESET);
+        self.write_fencer(&mut line, Side::Left);
+        let _ = write!(line, " {} ", self.clock(locale));
+        self.write_fencer(&mut line, Side::Right);
+        line
+    }
+
+    /// Renders the bout as a box colored with ANSI escape codes.
+    ///
+    /// Between the top and bottom borders, the first line holds the piste,
+    /// match time and apparatus state, and the next two lines the left then
+    /// right fencer with score, lights and cards: five lines in all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::message::Message;
+    /// use cyrano::state::MatchState;
+    ///
+    /// let mut state = MatchState::new("3");
+    /// state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+    ///
+    /// let boxed = state.render_ansi_boxed();
+    /// assert_eq!(boxed.lines().count(), 5);
+    /// assert!(boxed.lines().nth(2).unwrap().contains("B. Panini"));
+    /// ```
+    pub fn render_ansi_boxed(&self) -> String {
+        self.render_ansi_boxed_localized(Locale::English)
+    }
+
+    /// Renders the bout like [`render_ansi_boxed`](MatchState::render_ansi_boxed),
+    /// naming the apparatus state in the given language.
+    pub fn render_ansi_boxed_localized(&self, locale: Locale) -> String {
+        let mut left = String::new();
+        self.write_fencer(&mut left, Side::Left);
+        let mut right = String::new();
+        self.write_fencer(&mut right, Side::Right);
+        let header = format!("Piste {}  {}", self.piste(), self.clock(locale));
+
+        let width = [&header, &left, &right]
+            .iter()
+            .map(|s| visible_width(s))
+            .max()
+            .unwrap_or(0);
+        let pad = |s: &str| " ".repeat(width - visible_width(s));
+
+        let mut boxed = String::new();
+        let _ = writeln!(boxed, "┌─{}─┐", "─".repeat(width));
+        let _ = writeln!(boxed, "│ {}{} │", header, pad(&header));
+        let _ = writeln!(boxed, "│ {}{} │", left, pad(&left));
+        let _ = writeln!(boxed, "│ {}{} │", right, pad(&right));
+        let _ = write!(boxed, "└─{}─┘", "─".repeat(width));
+        boxed
+    }
+
+    fn clock(&self, locale: Locale) -> String {
+        let time = self.message().and_then(|m| m.time.as_deref()).unwrap_or("-:--");
+        let color = match self.state() {
+            Some(ApparatusState::Fencing) => GREEN,
+            Some(ApparatusState::Halt) | Some(ApparatusState::Pause) => YELLOW,
+            Some(ApparatusState::Ending) => BOLD,
+            Some(ApparatusState::Waiting) | Some(ApparatusState::Unknown(_)) | None => DIM,
+        };
+        let name = self
+            .state()
+            .map(|state| state.display_name(locale))
+            .unwrap_or_else(|| locale::idle(locale));
+        format!("{} {}{}{}", time, color, name.to_lowercase(), RESET)
+    }
+
+    fn write_fencer(&self, out: &mut String, side: Side) {
+        let empty = Fencer::default();
+        let fencer = self.fencer(side).unwrap_or(&empty);
+        let name = fencer.name.as_deref().or(fencer.id.as_deref()).unwrap_or("?");
+        let color = match side {
+            Side::Left => RED,
+            Side::Right => GREEN,
+        };
+
+        let light = if fencer.light == Some(true) {
+            format!("{}●{}", color, RESET)
+        } else if fencer.white_light == Some(true) {
+            format!("{}●{}", WHITE, RESET)
+        } else {
+            format!("{}○{}", DIM, RESET)
+        };
+
+        let mut cards = String::new();
+        if fencer.yellow_card.unwrap_or(0) > 0 {
+            let _ = write!(cards, "{}▮{}", YELLOW, RESET);
+        }
+        for _ in 0..fencer.red_card.unwrap_or(0) {
+            let _ = write!(cards, "{}▮{}", RED, RESET);
+        }
+
+        let flag = fencer
+            .nation_code()
+            .and_then(|code| code.flag_emoji())
+            .map(|flag| format!(" {}", flag))
+            .unwrap_or_default();
+        let nation = fencer
+            .nation
+            .as_deref()
+            .map(|n| format!("{} {}{}{}", flag, DIM, n, RESET))
+            .unwrap_or_default();
+        let score = fencer.score.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string());
+
+        match side {
+            Side::Left => {
+                let _ = write!(out, "{}{}{} {}{}{} {}", name, nation, cards, BOLD, score, RESET, light);
+            }
+            Side::Right => {
+                let _ = write!(out, "{} {}{}{} {}{}{}", light, BOLD, score, RESET, cards, name, nation);
+            }
+        }
+    }
+}
+
+/// Width of a string once ANSI escape sequences are stripped.
+fn visible_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in s.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+            (false, _) => width += 1,
+        }
+    }
+    width
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_render_ansi_colors() {
+        let mut state = MatchState::new("3");
+        state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||H|%|28|P.Martin|FRA|4|U|1|0|1|%|32|Panini|ITA|2|U|0|2|0|%|").unwrap());
+
+        let line = state.render_ansi();
+        assert!(line.contains(&format!("{}●{}", GREEN, RESET)));
+        assert!(line.contains(&format!("{}▮{}{}▮{}", RED, RESET, RED, RESET)));
+        assert!(line.contains(&format!("{}▮{}", YELLOW, RESET)));
+        assert!(line.find("Panini").unwrap() < line.find("P.Martin").unwrap());
+        assert!(line.contains(&format!("P.Martin 🇫🇷 {}FRA{}", DIM, RESET)));
+        assert!(line.contains(&format!("2:12 {}halt{}", YELLOW, RESET)));
+
+        let boxed = state.render_ansi_boxed();
+        let widths: Vec<usize> = boxed.lines().map(visible_width).collect();
+        assert_eq!(widths.len(), 5);
+        assert!(widths.iter().all(|w| *w == widths[0]));
+    }
+}
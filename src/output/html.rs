@@ -0,0 +1,253 @@
+use std::fmt::Write;
+
+use crate::competition::CompetitionRegistry;
+use crate::enums::{ApparatusState, FencerStatus, Side};
+use crate::fencer::Fencer;
+use crate::locale::Locale;
+use crate::state::MatchState;
+
+/// Default stylesheet embedded in fragments when [`Template::include_style`] is set.
+///
+/// Every selector uses the `cyrano` class prefix; fragments rendered with a
+/// different prefix need their own stylesheet.
+pub const DEFAULT_STYLE: &str = ".cyrano-scoreboard{display:flex;align-items:center;gap:1em;font-family:sans-serif;background:#111;color:#eee;padding:.5em 1em}\
+.cyrano-fencer{display:flex;align-items:center;gap:.5em;flex:1}\
+.cyrano-right{flex-direction:row-reverse}\
+.cyrano-score{font-size:2em;font-weight:bold;min-width:1.5em;text-align:center}\
+.cyrano-left.cyrano-light-on .cyrano-score{background:#c00}\
+.cyrano-right.cyrano-light-on .cyrano-score{background:#0a0}\
+.cyrano-white-light .cyrano-score{outline:2px solid #fff}\
+.cyrano-timer{font-size:1.5em;font-variant-numeric:tabular-nums}\
+.cyrano-card{display:inline-block;width:.6em;height:.9em;margin:0 .1em}\
+.cyrano-card-yellow{background:#fc0}\
+.cyrano-card-red{background:#d00}\
+.cyrano-flag{font-size:.8em;opacity:.8}\
+.cyrano-competition{font-size:.8em;opacity:.8}\
+.cyrano-winner .cyrano-name{font-weight:bold}";
+
+/// Options controlling the HTML produced by [`render`].
+#[derive(Debug, Clone)]
+pub struct Template {
+    /// Prefix of every CSS class in the fragment.
+    pub class_prefix: String,
+    /// Whether to embed [`DEFAULT_STYLE`] in the fragment.
+    pub include_style: bool,
+    /// Whether to show the nation of each fencer.
+    pub flags: bool,
+    /// Whether to put the flag emoji before the nation code, for pages
+    /// without flag images.
+    pub flag_emoji: bool,
+    /// Language of the country names shown when hovering a nation.
+    pub locale: Locale,
+    /// Whether to show the match time.
+    pub timer: bool,
+    /// Whether to show penalty cards.
+    pub cards: bool,
+    /// Competitions whose name is shown before the fencers; bouts of other
+    /// competitions show no name.
+    pub competitions: CompetitionRegistry,
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Template {
+            class_prefix: "cyrano".to_string(),
+            include_style: true,
+            flags: true,
+            flag_emoji: false,
+            locale: Locale::English,
+            timer: true,
+            cards: true,
+            competitions: CompetitionRegistry::default(),
+        }
+    }
+}
+
+/// Renders a bout state as a self-contained HTML fragment.
+///
+/// The fragment is a single `div` with the left fencer first, then the timer,
+/// then the right fencer, as seen by spectators facing the piste. The name of
+/// the competition comes first when it is registered in the template. Elements carry
+/// CSS classes (`<prefix>-name`, `<prefix>-score`, `<prefix>-light-on`,
+/// `<prefix>-card-yellow`, ...) so the look can be restyled freely. Text values
+/// are HTML-escaped.
+///
+/// # Arguments
+///
+/// * `state` - The bout state to render
+/// * `template` - Rendering options
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::output::html::{render, Template};
+/// use cyrano::state::MatchState;
+///
+/// let mut state = MatchState::new("3");
+/// state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+///
+/// let html = render(&state, &Template::default());
+/// assert!(html.contains(r#"<span class="cyrano-name">P.Martin</span>"#));
+/// assert!(html.contains(r#"<div class="cyrano-timer">2:12</div>"#));
+/// ```
+pub fn render(state: &MatchState, template: &Template) -> String {
+    let prefix = &template.class_prefix;
+    let mut html = String::new();
+
+    let _ = write!(
+        html,
+        r#"<div class="{p}-scoreboard {p}-state-{state}" data-piste="{piste}">"#,
+        p = prefix,
+        state = state_class(state.state()),
+        piste = escape(state.piste()),
+    );
+
+    if template.include_style {
+        let _ = write!(html, "<style>{}</style>", DEFAULT_STYLE);
+    }
+
+    if let Some(info) = state.competition_id().and_then(|id| template.competitions.get(id)) {
+        let _ = write!(html, r#"<div class="{}-competition">{}</div>"#, prefix, escape(&info.name));
+    }
+
+    render_fencer(&mut html, state, Side::Left, template);
+
+    if template.timer {
+        let time = state.message().and_then(|m| m.time.as_deref()).unwrap_or("");
+        let _ = write!(html, r#"<div class="{}-timer">{}</div>"#, prefix, escape(time));
+    }
+
+    render_fencer(&mut html, state, Side::Right, template);
+
+    html.push_str("</div>");
+    html
+}
+
+fn render_fencer(html: &mut String, state: &MatchState, side: Side, template: &Template) {
+    let prefix = &template.class_prefix;
+    let empty = Fencer::default();
+    let fencer = state.fencer(side).unwrap_or(&empty);
+
+    let mut classes = format!("{p}-fencer {p}-{side}", p = prefix, side = side);
+    if fencer.light == Some(true) {
+        let _ = write!(classes, " {}-light-on", prefix);
+    }
+    if fencer.white_light == Some(true) {
+        let _ = write!(classes, " {}-white-light", prefix);
+    }
+    if fencer.status == Some(FencerStatus::Victory) {
+        let _ = write!(classes, " {}-winner", prefix);
+    }
+
+    let _ = write!(html, r#"<div class="{}">"#, classes);
+
+    if template.flags {
+        if let Some(nation) = &fencer.nation {
+            let code = fencer.nation_code();
+            let title = code
+                .and_then(|code| code.display_name(template.locale))
+                .map(|name| format!(r#" title="{}""#, escape(name)))
+                .unwrap_or_default();
+            let flag = code
+                .and_then(|code| code.flag_emoji())
+                .filter(|_| template.flag_emoji)
+                .map(|flag| flag + " ")
+                .unwrap_or_default();
+            let _ = write!(
+                html,
+                r#"<span class="{p}-flag {p}-flag-{code}"{title}>{flag}{nation}</span>"#,
+                p = prefix,
+                code = escape(&nation.to_lowercase()),
+                title = title,
+                flag = flag,
+                nation = escape(nation),
+            );
+        }
+    }
+
+    let name = fencer.name.as_deref().or(fencer.id.as_deref()).unwrap_or("");
+    let _ = write!(html, r#"<span class="{}-name">{}</span>"#, prefix, escape(name));
+
+    let score = fencer.score.map(|s| s.to_string()).unwrap_or_default();
+    let _ = write!(html, r#"<span class="{}-score">{}</span>"#, prefix, score);
+
+    if template.cards {
+        let _ = write!(html, r#"<span class="{}-cards">"#, prefix);
+        if fencer.yellow_card.unwrap_or(0) > 0 {
+            let _ = write!(html, r#"<span class="{p}-card {p}-card-yellow"></span>"#, p = prefix);
+        }
+        for _ in 0..fencer.red_card.unwrap_or(0) {
+            let _ = write!(html, r#"<span class="{p}-card {p}-card-red"></span>"#, p = prefix);
+        }
+        html.push_str("</span>");
+    }
+
+    html.push_str("</div>");
+}
+
+fn state_class(state: Option<&ApparatusState>) -> &'static str {
+    match state {
+        Some(ApparatusState::Fencing) => "fencing",
+        Some(ApparatusState::Halt) => "halt",
+        Some(ApparatusState::Pause) => "pause",
+        Some(ApparatusState::Waiting) => "waiting",
+        Some(ApparatusState::Ending) => "ending",
+        Some(ApparatusState::Unknown(_)) | None => "unknown",
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::competition::CompetitionInfo;
+    use crate::message::Message;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_render_escapes_and_cards() {
+        let mut state = MatchState::new("3");
+        state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|<b>Martin|FRA|4|U|1|2|1|%|32|Panini|ITA|2|U|%|").unwrap());
+
+        let mut template = Template {
+            class_prefix: "sb".to_string(),
+            include_style: false,
+            ..Template::default()
+        };
+        let html = render(&state, &template);
+        assert!(!html.contains("sb-competition"));
+
+        assert!(html.starts_with(r#"<div class="sb-scoreboard sb-state-fencing" data-piste="3">"#));
+        assert!(html.contains("&lt;b&gt;Martin"));
+        assert!(html.contains(r#"<div class="sb-fencer sb-right sb-light-on">"#));
+        assert_eq!(html.matches("sb-card-red").count(), 2);
+        assert_eq!(html.matches("sb-card-yellow").count(), 1);
+        assert!(!html.contains("<style>"));
+        assert!(html.find("Panini").unwrap() < html.find("Martin").unwrap());
+        assert!(html.contains(r#"<span class="sb-flag sb-flag-ita" title="Italy">ITA</span>"#));
+
+        template.flag_emoji = true;
+        assert!(render(&state, &template).contains(r#"title="France">🇫🇷 FRA</span>"#));
+
+        template.competitions.insert("fm-eq", CompetitionInfo::new("Coupe d'Europe"));
+        assert!(render(&state, &template).contains(r#"<div class="sb-competition">Coupe d&#39;Europe</div><div class="sb-fencer"#));
+    }
+}
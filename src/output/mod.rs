@@ -0,0 +1,19 @@
+//! Renderers turning tracked bout state into presentation formats.
+//!
+//! - [`html`] - Self-contained HTML scoreboard fragments
+//! - [`ansi`] - Colored terminal rendering (`MatchState::render_ansi`)
+//! - [`scoreboard`] - Pre-formatted text layers with change flags for graphics engines
+//! - [`segment`] - Fixed-width frames for serial LED walls and segment displays
+//! - [`sink`] - `ScoreboardSink` trait plugging third-party renderers into the event pipeline
+//! - `webhook` - JSON webhooks on selected events (feature `webhook`)
+//! - `redis` - Redis keys and pub/sub channels per piste (feature `redis`)
+
+pub mod html;
+pub mod ansi;
+pub mod scoreboard;
+pub mod segment;
+pub mod sink;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+#[cfg(feature = "redis")]
+pub mod redis;
@@ -0,0 +1,156 @@
+use ::redis::{ConnectionLike, Pipeline, RedisResult};
+
+use crate::state::{MatchEvent, MatchState};
+
+/// Publishes piste state and events to Redis.
+///
+/// For a piste `17` and the default `piste` prefix, the publisher:
+///
+/// - stores the JSON state of the piste under the key `piste:17:state`
+/// - publishes each event as JSON on the channel `piste:17:events`
+/// - publishes the piste identifier on the channel `piste:updates`
+///
+/// so web frontends can read the latest state on connect and follow the
+/// channels afterwards. Each call is sent as a single pipeline.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::output::redis::RedisPublisher;
+/// use cyrano::state::PisteManager;
+///
+/// # fn run() -> redis::RedisResult<()> {
+/// let mut connection = redis::Client::open("redis://127.0.0.1/")?.get_connection()?;
+/// let publisher = RedisPublisher::new();
+/// let mut pistes = PisteManager::new();
+///
+/// let message = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|P3|1|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap();
+/// let events = pistes.apply(&message);
+/// publisher.publish(&mut connection, pistes.get("17").unwrap(), &events)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RedisPublisher {
+    prefix: String,
+    ttl_seconds: Option<u64>,
+}
+
+impl Default for RedisPublisher {
+    fn default() -> Self {
+        RedisPublisher::new()
+    }
+}
+
+impl RedisPublisher {
+    /// Creates a publisher using the `piste` key prefix and no expiry.
+    pub fn new() -> Self {
+        RedisPublisher {
+            prefix: "piste".to_string(),
+            ttl_seconds: None,
+        }
+    }
+
+    /// Sets the prefix of every key and channel.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Makes state keys expire if a piste stops sending updates.
+    pub fn ttl_seconds(mut self, ttl_seconds: u64) -> Self {
+        self.ttl_seconds = Some(ttl_seconds);
+        self
+    }
+
+    /// Returns the key holding the state of a piste.
+    pub fn state_key(&self, piste: &str) -> String {
+        format!("{}:{}:state", self.prefix, piste)
+    }
+
+    /// Returns the channel carrying the events of a piste.
+    pub fn events_channel(&self, piste: &str) -> String {
+        format!("{}:{}:events", self.prefix, piste)
+    }
+
+    /// Returns the channel announcing which piste was updated.
+    pub fn updates_channel(&self) -> String {
+        format!("{}:updates", self.prefix)
+    }
+
+    /// Builds the pipeline storing the state and publishing the events of a piste.
+    pub fn pipeline(&self, state: &MatchState, events: &[MatchEvent]) -> serde_json::Result<Pipeline> {
+        let piste = state.piste();
+        let mut pipe = ::redis::pipe();
+
+        let snapshot = serde_json::to_string(state)?;
+        match self.ttl_seconds {
+            Some(ttl) => pipe.set_ex(self.state_key(piste), snapshot, ttl).ignore(),
+            None => pipe.set(self.state_key(piste), snapshot).ignore(),
+        };
+
+        for event in events {
+            pipe.publish(self.events_channel(piste), serde_json::to_string(event)?)
+                .ignore();
+        }
+        pipe.publish(self.updates_channel(), piste).ignore();
+
+        Ok(pipe)
+    }
+
+    /// Stores the state and publishes the events of a piste.
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - An open Redis connection
+    /// * `state` - The piste state after the events were applied
+    /// * `events` - The events returned by `MatchState::apply`
+    ///
+    /// # Errors
+    ///
+    /// Returns the Redis error if the pipeline failed.
+    pub fn publish<C: ConnectionLike>(
+        &self,
+        connection: &mut C,
+        state: &MatchState,
+        events: &[MatchEvent],
+    ) -> RedisResult<()> {
+        let pipe = self
+            .pipeline(state, events)
+            .map_err(|err| ::redis::RedisError::from(std::io::Error::new(std::io::ErrorKind::InvalidData, err)))?;
+        pipe.exec(connection)
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_layout() {
+        let publisher = RedisPublisher::new().prefix("venue:piste");
+
+        assert_eq!(publisher.state_key("17"), "venue:piste:17:state");
+        assert_eq!(publisher.events_channel("17"), "venue:piste:17:events");
+        assert_eq!(publisher.updates_channel(), "venue:piste:updates");
+    }
+
+    #[test]
+    fn test_pipeline_commands() {
+        let state = MatchState::new("17");
+        let pipe = RedisPublisher::new()
+            .ttl_seconds(60)
+            .pipeline(&state, &[MatchEvent::BoutStarted])
+            .unwrap();
+
+        let packed = String::from_utf8_lossy(&pipe.get_packed_pipeline()).into_owned();
+        assert!(packed.contains("SETEX"));
+        assert!(packed.contains("piste:17:state"));
+        assert!(packed.contains("piste:17:events"));
+        assert!(packed.contains("BoutStarted"));
+    }
+}
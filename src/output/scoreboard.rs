@@ -0,0 +1,290 @@
+use std::ops::BitOr;
+
+use crate::directory::{FencerProfile, ProfileCache};
+use crate::enums::{PCard, Side};
+use crate::fencer::Fencer;
+use crate::locale::Locale;
+use crate::state::MatchState;
+use crate::utils::clock_seconds;
+
+/// Set of scoreboard text layers that changed in the last update.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::output::scoreboard::DirtyFields;
+///
+/// let dirty = DirtyFields::TIMER | DirtyFields::LEFT_SCORE;
+/// assert!(dirty.contains(DirtyFields::TIMER));
+/// assert!(!dirty.contains(DirtyFields::RIGHT_SCORE));
+/// assert_eq!(dirty.bits(), 0b100_0100_0000);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DirtyFields(u16);
+
+impl DirtyFields {
+    /// Nothing changed.
+    pub const NONE: DirtyFields = DirtyFields(0);
+    /// Name of the right fencer.
+    pub const RIGHT_NAME: DirtyFields = DirtyFields(1);
+    /// Score of the right fencer.
+    pub const RIGHT_SCORE: DirtyFields = DirtyFields(1 << 1);
+    /// Flag code of the right fencer.
+    pub const RIGHT_FLAG: DirtyFields = DirtyFields(1 << 2);
+    /// Card pips of the right fencer.
+    pub const RIGHT_CARDS: DirtyFields = DirtyFields(1 << 3);
+    /// Scoring lights of the right fencer.
+    pub const RIGHT_LIGHTS: DirtyFields = DirtyFields(1 << 4);
+    /// Name of the left fencer.
+    pub const LEFT_NAME: DirtyFields = DirtyFields(1 << 5);
+    /// Score of the left fencer.
+    pub const LEFT_SCORE: DirtyFields = DirtyFields(1 << 6);
+    /// Flag code of the left fencer.
+    pub const LEFT_FLAG: DirtyFields = DirtyFields(1 << 7);
+    /// Card pips of the left fencer.
+    pub const LEFT_CARDS: DirtyFields = DirtyFields(1 << 8);
+    /// Scoring lights of the left fencer.
+    pub const LEFT_LIGHTS: DirtyFields = DirtyFields(1 << 9);
+    /// Bout clock.
+    pub const TIMER: DirtyFields = DirtyFields(1 << 10);
+    /// Every layer.
+    pub const ALL: DirtyFields = DirtyFields(0b111_1111_1111);
+
+    /// Returns the mask as an integer, for engines taking raw bit sets.
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    /// Returns `true` if every layer of `other` is in the set.
+    pub fn contains(&self, other: DirtyFields) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if no layer changed.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for DirtyFields {
+    type Output = DirtyFields;
+
+    fn bitor(self, other: DirtyFields) -> DirtyFields {
+        DirtyFields(self.0 | other.0)
+    }
+}
+
+/// Text layers of one fencer, formatted for display.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FencerView {
+    /// Full name from the fencer directory, else the name sent, or the id
+    /// when no name is sent.
+    pub name: String,
+    /// Score right-aligned on two characters, such as `" 4"` or `"14"`.
+    pub score: String,
+    /// Lowercase nation code, as used for flag image names.
+    pub flag: String,
+    /// Flag emoji of the nation, for engines without flag images.
+    pub flag_emoji: String,
+    /// Name of the country of the nation, in the language of the view.
+    pub country: String,
+    /// One pip per card: `Y` for the yellow card, `R` per red card, then `P`
+    /// for a P-card.
+    pub cards: String,
+    /// `C` for the scoring light, `W` for the white light.
+    pub lights: String,
+    /// Profile of the fencer in the directory; a change marks the name layer.
+    pub profile: Option<FencerProfile>,
+}
+
+impl FencerView {
+    fn of(fencer: &Fencer, profiles: &ProfileCache, locale: Locale) -> Self {
+        let mut cards = String::new();
+        if fencer.yellow_card.unwrap_or(0) > 0 {
+            cards.push('Y');
+        }
+        for _ in 0..fencer.red_card.unwrap_or(0) {
+            cards.push('R');
+        }
+        if fencer.p_card.as_ref().is_some_and(|p_card| *p_card != PCard::None) {
+            cards.push('P');
+        }
+
+        let mut lights = String::new();
+        if fencer.light == Some(true) {
+            lights.push('C');
+        }
+        if fencer.white_light == Some(true) {
+            lights.push('W');
+        }
+
+        let profile = fencer.id.as_deref().and_then(|id| profiles.get(id)).cloned();
+        let full_name = profile.as_ref().and_then(|profile| profile.full_name.as_deref());
+        let nation = fencer.nation_code();
+
+        FencerView {
+            name: full_name.or(fencer.name.as_deref()).or(fencer.id.as_deref()).unwrap_or_default().to_string(),
+            score: fencer.score.map(|score| format!("{:>2}", score)).unwrap_or_default(),
+            flag: fencer.nation.as_deref().unwrap_or_default().to_lowercase(),
+            flag_emoji: nation.and_then(|code| code.flag_emoji()).unwrap_or_default(),
+            country: nation.and_then(|code| code.display_name(locale)).unwrap_or_default().to_string(),
+            cards,
+            lights,
+            profile,
+        }
+    }
+
+    fn changes(&self, before: &FencerView, side: Side) -> DirtyFields {
+        let layers = match side {
+            Side::Right => [
+                DirtyFields::RIGHT_NAME,
+                DirtyFields::RIGHT_SCORE,
+                DirtyFields::RIGHT_FLAG,
+                DirtyFields::RIGHT_CARDS,
+                DirtyFields::RIGHT_LIGHTS,
+            ],
+            Side::Left => [
+                DirtyFields::LEFT_NAME,
+                DirtyFields::LEFT_SCORE,
+                DirtyFields::LEFT_FLAG,
+                DirtyFields::LEFT_CARDS,
+                DirtyFields::LEFT_LIGHTS,
+            ],
+        };
+        let texts = [
+            (&self.name, &before.name),
+            (&self.score, &before.score),
+            (&self.flag, &before.flag),
+            (&self.cards, &before.cards),
+            (&self.lights, &before.lights),
+        ];
+        let texts = texts
+            .iter()
+            .zip(layers)
+            .filter(|((after, before), _)| after != before)
+            .fold(DirtyFields::NONE, |dirty, (_, layer)| dirty | layer);
+        if self.profile != before.profile {
+            texts | layers[0]
+        } else {
+            texts
+        }
+    }
+}
+
+/// Scoreboard text layers of a piste, with the layers changed by the last
+/// update, so that a graphics engine only redraws what changed.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::output::scoreboard::{DirtyFields, ScoreboardView};
+/// use cyrano::state::MatchState;
+///
+/// let mut state = MatchState::new("3");
+/// let mut view = ScoreboardView::default();
+/// state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+/// view.update(&state);
+/// assert_eq!((view.timer.as_str(), view.right.score.as_str(), view.left.flag.as_str()), ("02:12", " 4", "ita"));
+///
+/// state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||H|%|28|P.Martin|FRA|5|U|0|0|1|%|32|B. Panini|ITA|2|U|%|").unwrap());
+/// view.update(&state);
+/// assert_eq!(view.dirty_fields(), DirtyFields::RIGHT_SCORE | DirtyFields::RIGHT_LIGHTS);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreboardView {
+    /// Right fencer.
+    pub right: FencerView,
+    /// Left fencer.
+    pub left: FencerView,
+    /// Bout clock as `MM:SS`, or the raw time when it is not a clock.
+    pub timer: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dirty: DirtyFields,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    locale: Locale,
+}
+
+impl ScoreboardView {
+    /// Returns the view of a bout state, with every layer marked as changed.
+    pub fn of(state: &MatchState) -> Self {
+        let mut view = ScoreboardView::default();
+        view.update(state);
+        view.dirty = DirtyFields::ALL;
+        view
+    }
+
+    /// Sets the language of the country names.
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Formats the state again and records which layers changed.
+    pub fn update(&mut self, state: &MatchState) {
+        self.update_with(state, &ProfileCache::default());
+    }
+
+    /// Formats the state again with the fencer profiles looked up so far,
+    /// and records which layers changed.
+    pub fn update_with(&mut self, state: &MatchState, profiles: &ProfileCache) {
+        let empty = Fencer::default();
+        let right = FencerView::of(state.fencer(Side::Right).unwrap_or(&empty), profiles, self.locale);
+        let left = FencerView::of(state.fencer(Side::Left).unwrap_or(&empty), profiles, self.locale);
+        let time = state.message().and_then(|m| m.time.as_deref()).unwrap_or_default();
+        let timer = match clock_seconds(time) {
+            Some(seconds) => format!("{:02}:{:02}", seconds / 60, seconds % 60),
+            None => time.to_string(),
+        };
+
+        let mut dirty = right.changes(&self.right, Side::Right) | left.changes(&self.left, Side::Left);
+        if timer != self.timer {
+            dirty = dirty | DirtyFields::TIMER;
+        }
+
+        self.right = right;
+        self.left = left;
+        self.timer = timer;
+        self.dirty = dirty;
+    }
+
+    /// Returns the layers changed by the last update.
+    pub fn dirty_fields(&self) -> DirtyFields {
+        self.dirty
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_only_changed_layers_are_dirty() {
+        let mut state = MatchState::new("3");
+        let info = |time: &str, cards: &str| {
+            let raw = format!("|EFP1.1|INFO|3|fm-eq|1|A32|1||{}|||E||H|%|28|P.Martin|FRA|12|U|%|32|B. Panini|ITA|9|U|{}|%|", time, cards);
+            Message::try_from(raw).unwrap()
+        };
+
+        state.apply(&info("0:09.45", "0|0"));
+        let mut view = ScoreboardView::of(&state);
+        assert_eq!(view.dirty_fields(), DirtyFields::ALL);
+        assert_eq!(view.timer, "00:09");
+        assert_eq!((view.left.flag_emoji.as_str(), view.left.country.as_str()), ("🇮🇹", "Italy"));
+
+        view.update(&state);
+        assert!(view.dirty_fields().is_empty());
+
+        state.apply(&info("0:09.10", "1|2|0|0|0|N|1"));
+        view.update(&state);
+        assert_eq!(view.dirty_fields(), DirtyFields::LEFT_CARDS);
+        assert_eq!(view.left.cards, "YRRP");
+    }
+}
@@ -0,0 +1,316 @@
+use crate::enums::{ApparatusState, PCard, Priority, Side};
+use crate::error::SegmentError;
+use crate::fencer::Fencer;
+use crate::state::MatchState;
+use crate::utils::clock_millis;
+
+/// Length of an encoded frame, in bytes.
+pub const FRAME_LEN: usize = 13;
+
+/// Byte starting every frame.
+pub const STX: u8 = 0x02;
+
+/// Byte ending every frame.
+pub const ETX: u8 = 0x03;
+
+/// Value of a score or clock byte when the value is unknown.
+const UNKNOWN: u8 = 0xFF;
+
+/// Highest red card count a card byte can hold.
+pub const MAX_RED_CARDS: u8 = 3;
+
+/// Score lights and cards of one fencer on a segment display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentFencer {
+    /// Score, or `None` when not sent.
+    pub score: Option<u8>,
+    /// Scoring light.
+    pub light: bool,
+    /// White (off-target) light.
+    pub white_light: bool,
+    /// Yellow card.
+    pub yellow_card: bool,
+    /// Red cards, capped to [`MAX_RED_CARDS`].
+    pub red_cards: u8,
+    /// P-card level.
+    pub p_card: PCard,
+}
+
+impl Default for SegmentFencer {
+    fn default() -> Self {
+        SegmentFencer {
+            score: None,
+            light: false,
+            white_light: false,
+            yellow_card: false,
+            red_cards: 0,
+            p_card: PCard::None,
+        }
+    }
+}
+
+impl SegmentFencer {
+    fn of(fencer: &Fencer) -> Self {
+        SegmentFencer {
+            score: fencer.score.filter(|score| *score != UNKNOWN),
+            light: fencer.light == Some(true),
+            white_light: fencer.white_light == Some(true),
+            yellow_card: fencer.yellow_card.unwrap_or(0) > 0,
+            red_cards: fencer.red_card.unwrap_or(0).min(MAX_RED_CARDS),
+            p_card: fencer.p_card.clone().unwrap_or(PCard::None),
+        }
+    }
+
+    fn card_byte(&self) -> u8 {
+        u8::from(self.yellow_card) | (self.red_cards.min(MAX_RED_CARDS) << 1) | (p_card_code(&self.p_card) << 3)
+    }
+
+    fn from_bytes(score: u8, cards: u8, offset: usize) -> Result<Self, SegmentError> {
+        let p_card = match cards >> 3 {
+            0 => PCard::None,
+            1 => PCard::Yellow,
+            2 => PCard::OneRed,
+            3 => PCard::TwoRed,
+            4 => PCard::OneBlack,
+            5 => PCard::TwoBlack,
+            _ => return Err(SegmentError::InvalidByte { offset, value: cards }),
+        };
+        Ok(SegmentFencer {
+            score: (score != UNKNOWN).then_some(score),
+            light: false,
+            white_light: false,
+            yellow_card: cards & 1 != 0,
+            red_cards: (cards >> 1) & 0b11,
+            p_card,
+        })
+    }
+}
+
+fn p_card_code(p_card: &PCard) -> u8 {
+    match p_card {
+        PCard::None => 0,
+        PCard::Yellow => 1,
+        PCard::OneRed => 2,
+        PCard::TwoRed => 3,
+        PCard::OneBlack => 4,
+        PCard::TwoBlack => 5,
+    }
+}
+
+/// Fixed-width frame driving serial LED walls and piste-side repeaters.
+///
+/// Every frame is [`FRAME_LEN`] bytes long, so a display controller can read
+/// it without parsing:
+///
+/// | Offset | Length | Content |
+/// |--------|--------|---------|
+/// | 0      | 1      | [`STX`] (`0x02`) |
+/// | 1      | 3      | Piste, ASCII, right-aligned and padded with spaces; the last three characters of longer names |
+/// | 4      | 1      | Right score, `0xFF` when unknown |
+/// | 5      | 1      | Left score, `0xFF` when unknown |
+/// | 6      | 2      | Clock in tenths of a second, big-endian, `0xFFFF` when not a clock |
+/// | 8      | 1      | Status: bit 0 right light, bit 1 right white light, bit 2 left light, bit 3 left white light, bit 4 fencing, bits 5-6 priority (0 none, 1 right, 2 left) |
+/// | 9      | 1      | Right cards: bit 0 yellow card, bits 1-2 red cards, bits 3-5 P-card (0 none, 1 yellow, 2 and 3 red, 4 and 5 black) |
+/// | 10     | 1      | Left cards, as the right cards |
+/// | 11     | 1      | Checksum: XOR of bytes 1 to 10 |
+/// | 12     | 1      | [`ETX`] (`0x03`) |
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::output::segment::{SegmentFrame, FRAME_LEN};
+/// use cyrano::state::MatchState;
+///
+/// let mut state = MatchState::new("3");
+/// state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+///
+/// let bytes = SegmentFrame::of(&state).encode();
+/// assert_eq!(bytes.len(), FRAME_LEN);
+/// assert_eq!(&bytes[1..8], b"  3\x04\x02\x05\x28");
+///
+/// let frame = SegmentFrame::decode(&bytes).unwrap();
+/// assert_eq!((frame.right.score, frame.left.score, frame.tenths), (Some(4), Some(2), Some(1320)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentFrame {
+    /// Piste, at most three characters.
+    pub piste: String,
+    /// Right fencer.
+    pub right: SegmentFencer,
+    /// Left fencer.
+    pub left: SegmentFencer,
+    /// Clock in tenths of a second, or `None` when the time is not a clock.
+    pub tenths: Option<u16>,
+    /// Whether fencing is in progress, i.e. the clock is running.
+    pub fencing: bool,
+    /// Priority, if given.
+    pub priority: Priority,
+}
+
+impl Default for SegmentFrame {
+    fn default() -> Self {
+        SegmentFrame {
+            piste: String::new(),
+            right: SegmentFencer::default(),
+            left: SegmentFencer::default(),
+            tenths: None,
+            fencing: false,
+            priority: Priority::None,
+        }
+    }
+}
+
+impl SegmentFrame {
+    /// Returns the frame showing a bout state.
+    pub fn of(state: &MatchState) -> Self {
+        let empty = Fencer::default();
+        let message = state.message();
+        let time = message.and_then(|m| m.time.as_deref());
+        SegmentFrame {
+            piste: last_chars(state.piste(), 3),
+            right: SegmentFencer::of(state.fencer(Side::Right).unwrap_or(&empty)),
+            left: SegmentFencer::of(state.fencer(Side::Left).unwrap_or(&empty)),
+            tenths: time
+                .and_then(clock_millis)
+                .map(|ms| (ms / 100).min(u64::from(u16::MAX - 1)) as u16),
+            fencing: state.state() == Some(&ApparatusState::Fencing),
+            priority: message.and_then(|m| m.priority.clone()).unwrap_or(Priority::None),
+        }
+    }
+
+    /// Encodes the frame.
+    ///
+    /// Non-ASCII piste characters are sent as `?`.
+    pub fn encode(&self) -> [u8; FRAME_LEN] {
+        let mut bytes = [0u8; FRAME_LEN];
+        bytes[0] = STX;
+
+        let piste: Vec<u8> = last_chars(&self.piste, 3)
+            .chars()
+            .map(|c| if c.is_ascii() { c as u8 } else { b'?' })
+            .collect();
+        bytes[1..4].fill(b' ');
+        bytes[4 - piste.len()..4].copy_from_slice(&piste);
+
+        bytes[4] = self.right.score.unwrap_or(UNKNOWN);
+        bytes[5] = self.left.score.unwrap_or(UNKNOWN);
+        let tenths = self.tenths.map_or(u16::MAX, |tenths| tenths.min(u16::MAX - 1));
+        bytes[6..8].copy_from_slice(&tenths.to_be_bytes());
+
+        let priority = match self.priority {
+            Priority::None => 0,
+            Priority::Right => 1,
+            Priority::Left => 2,
+        };
+        bytes[8] = u8::from(self.right.light)
+            | u8::from(self.right.white_light) << 1
+            | u8::from(self.left.light) << 2
+            | u8::from(self.left.white_light) << 3
+            | u8::from(self.fencing) << 4
+            | priority << 5;
+        bytes[9] = self.right.card_byte();
+        bytes[10] = self.left.card_byte();
+        bytes[11] = checksum(&bytes);
+        bytes[12] = ETX;
+        bytes
+    }
+
+    /// Decodes a frame produced by [`encode`](SegmentFrame::encode).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SegmentError`] if the frame has the wrong length, framing
+    /// bytes or checksum, or a byte out of its documented range.
+    pub fn decode(bytes: &[u8]) -> Result<Self, SegmentError> {
+        if bytes.len() != FRAME_LEN {
+            return Err(SegmentError::Length(bytes.len()));
+        }
+        if bytes[0] != STX || bytes[12] != ETX {
+            return Err(SegmentError::Framing);
+        }
+        let expected = checksum(bytes);
+        if bytes[11] != expected {
+            return Err(SegmentError::Checksum {
+                expected,
+                found: bytes[11],
+            });
+        }
+
+        if let Some(offset) = (1..4).find(|&offset| !bytes[offset].is_ascii()) {
+            return Err(SegmentError::InvalidByte {
+                offset,
+                value: bytes[offset],
+            });
+        }
+        let status = bytes[8];
+        let priority = match (status >> 5) & 0b11 {
+            0 => Priority::None,
+            1 => Priority::Right,
+            2 => Priority::Left,
+            _ => return Err(SegmentError::InvalidByte { offset: 8, value: status }),
+        };
+        if status & 0x80 != 0 {
+            return Err(SegmentError::InvalidByte { offset: 8, value: status });
+        }
+
+        let mut right = SegmentFencer::from_bytes(bytes[4], bytes[9], 9)?;
+        right.light = status & 1 != 0;
+        right.white_light = status & 1 << 1 != 0;
+        let mut left = SegmentFencer::from_bytes(bytes[5], bytes[10], 10)?;
+        left.light = status & 1 << 2 != 0;
+        left.white_light = status & 1 << 3 != 0;
+
+        let tenths = u16::from_be_bytes([bytes[6], bytes[7]]);
+        Ok(SegmentFrame {
+            piste: String::from_utf8_lossy(&bytes[1..4]).trim_start().to_string(),
+            right,
+            left,
+            tenths: (tenths != u16::MAX).then_some(tenths),
+            fencing: status & 1 << 4 != 0,
+            priority,
+        })
+    }
+}
+
+fn last_chars(text: &str, count: usize) -> String {
+    let skip = text.chars().count().saturating_sub(count);
+    text.chars().skip(skip).collect()
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes[1..11].iter().fold(0, |sum, byte| sum ^ byte)
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_segment_frame_roundtrip() {
+        let mut state = MatchState::new("Finale");
+        let raw = "|EFP1.1|INFO|Finale|fm-eq|1|A32|12||0:09.45|||S|L|H|%|28|P.Martin|FRA|14|U|1|0|1|1|%|32|B. Panini|ITA|9|U|0|5|0|0|0|N|4|%|";
+        state.apply(&Message::try_from(raw).unwrap());
+
+        let frame = SegmentFrame::of(&state);
+        assert_eq!(frame.piste, "ale");
+        assert_eq!(frame.tenths, Some(94));
+        assert_eq!(frame.left.red_cards, MAX_RED_CARDS);
+
+        let bytes = frame.encode();
+        assert_eq!(bytes[8], 0b100_0011);
+        assert_eq!(bytes[10], 0b10_0110);
+        assert_eq!(SegmentFrame::decode(&bytes), Ok(frame));
+
+        let mut corrupted = bytes;
+        corrupted[4] = 15;
+        assert!(matches!(SegmentFrame::decode(&corrupted), Err(SegmentError::Checksum { .. })));
+        assert_eq!(SegmentFrame::decode(&bytes[..12]), Err(SegmentError::Length(12)));
+        assert_eq!(SegmentFrame::decode(&SegmentFrame::default().encode()).unwrap().right.score, None);
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::message::Message;
+use crate::output::scoreboard::ScoreboardView;
+use crate::piste::PisteId;
+use crate::state::{MatchEvent, PisteManager};
+
+/// Renderer fed with scoreboard updates, such as an NDI tally, a CasparCG
+/// template or an LED wall driver.
+///
+/// Closures taking the same arguments implement the trait, so a renderer can
+/// be plugged in without a dedicated type.
+pub trait ScoreboardSink {
+    /// Receives the scoreboard of a piste after a message changed it.
+    ///
+    /// # Arguments
+    ///
+    /// * `piste` - The piste identifier
+    /// * `view` - The formatted scoreboard; its [`dirty_fields`](ScoreboardView::dirty_fields)
+    ///   tell which layers changed
+    /// * `events` - The events of the message
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the renderer; it is recorded by the feed and does
+    /// not stop the other sinks.
+    fn update(&mut self, piste: &str, view: &ScoreboardView, events: &[MatchEvent]) -> io::Result<()>;
+}
+
+impl<F> ScoreboardSink for F
+where
+    F: FnMut(&str, &ScoreboardView, &[MatchEvent]) -> io::Result<()>,
+{
+    fn update(&mut self, piste: &str, view: &ScoreboardView, events: &[MatchEvent]) -> io::Result<()> {
+        self(piste, view, events)
+    }
+}
+
+/// Reference sink writing one tab-separated line per update.
+///
+/// The columns are the piste, the dirty layer mask, the timer, then the name,
+/// score, flag, cards and lights of the left fencer followed by those of the
+/// right fencer. Tools driving text-based displays can read it from a pipe or
+/// a socket.
+#[derive(Debug)]
+pub struct TextSink<W: Write> {
+    out: W,
+}
+
+impl<W: Write> TextSink<W> {
+    /// Creates a sink writing to `out`.
+    pub fn new(out: W) -> Self {
+        TextSink { out }
+    }
+
+    /// Returns the destination back.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> ScoreboardSink for TextSink<W> {
+    fn update(&mut self, piste: &str, view: &ScoreboardView, _events: &[MatchEvent]) -> io::Result<()> {
+        write!(self.out, "{}\t{}\t{}", piste, view.dirty_fields().bits(), view.timer)?;
+        for fencer in [&view.left, &view.right] {
+            write!(
+                self.out,
+                "\t{}\t{}\t{}\t{}\t{}",
+                fencer.name, fencer.score, fencer.flag, fencer.cards, fencer.lights
+            )?;
+        }
+        writeln!(self.out)
+    }
+}
+
+/// Event pipeline feeding scoreboard sinks.
+///
+/// The feed tracks the state of every piste, formats its scoreboard and hands
+/// it to each sink whenever a message changes a layer or produces events.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::output::scoreboard::ScoreboardView;
+/// use cyrano::output::sink::{ScoreboardFeed, TextSink};
+/// use cyrano::state::MatchEvent;
+///
+/// let mut feed = ScoreboardFeed::new()
+///     .sink(TextSink::new(std::io::sink()))
+///     .sink(|piste: &str, view: &ScoreboardView, _: &[MatchEvent]| {
+///         println!("piste {}: {}-{}", piste, view.left.score, view.right.score);
+///         Ok(())
+///     });
+///
+/// feed.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+/// assert_eq!(feed.view("3").unwrap().left.name, "B. Panini");
+/// assert!(feed.take_errors().is_empty());
+/// ```
+#[derive(Default)]
+pub struct ScoreboardFeed {
+    pistes: PisteManager,
+    views: BTreeMap<PisteId, ScoreboardView>,
+    sinks: Vec<Box<dyn ScoreboardSink>>,
+    errors: Vec<io::Error>,
+}
+
+impl ScoreboardFeed {
+    /// Creates a feed without sinks.
+    pub fn new() -> Self {
+        ScoreboardFeed::default()
+    }
+
+    /// Adds a sink.
+    pub fn sink(mut self, sink: impl ScoreboardSink + 'static) -> Self {
+        self.add_sink(sink);
+        self
+    }
+
+    /// Adds a sink to a running feed.
+    pub fn add_sink(&mut self, sink: impl ScoreboardSink + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    /// Applies a message and feeds the resulting scoreboard to every sink.
+    ///
+    /// # Returns
+    ///
+    /// The events of the message, as returned by [`PisteManager::apply`].
+    pub fn apply(&mut self, message: &Message) -> Vec<MatchEvent> {
+        let events = self.pistes.apply(message);
+        let Some(state) = self.pistes.get(&message.piste) else {
+            return events;
+        };
+
+        let view = match self.views.entry(PisteId::from(message.piste.as_str())) {
+            Entry::Occupied(entry) => {
+                let view = entry.into_mut();
+                view.update(state);
+                view
+            }
+            Entry::Vacant(entry) => entry.insert(ScoreboardView::of(state)),
+        };
+        if view.dirty_fields().is_empty() && events.is_empty() {
+            return events;
+        }
+        for sink in &mut self.sinks {
+            if let Err(err) = sink.update(&message.piste, view, &events) {
+                self.errors.push(err);
+            }
+        }
+        events
+    }
+
+    /// Returns the current scoreboard of a piste.
+    pub fn view(&self, piste: &str) -> Option<&ScoreboardView> {
+        self.views.get(&PisteId::from(piste))
+    }
+
+    /// Returns and clears the errors reported by sinks.
+    pub fn take_errors(&mut self) -> Vec<io::Error> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::convert::TryFrom;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_sinks_receive_changes_only() {
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let written = Rc::clone(&lines);
+        let mut feed = ScoreboardFeed::new()
+            .sink(move |piste: &str, view: &ScoreboardView, _: &[MatchEvent]| {
+                let mut text = TextSink::new(Vec::new());
+                text.update(piste, view, &[])?;
+                written.borrow_mut().push(String::from_utf8(text.into_inner()).unwrap());
+                Ok(())
+            })
+            .sink(|_: &str, _: &ScoreboardView, _: &[MatchEvent]| Err(io::Error::other("wall offline")));
+
+        let info = Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap();
+        feed.apply(&info);
+        feed.apply(&info);
+
+        let lines = lines.borrow();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0], "3\t2047\t02:12\tB. Panini\t 2\tita\t\t\tP.Martin\t 4\tfra\t\t\n");
+        assert_eq!(feed.take_errors().len(), 1);
+        assert!(feed.take_errors().is_empty());
+    }
+}
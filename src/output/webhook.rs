@@ -0,0 +1,438 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::competition::{CompetitionInfo, CompetitionRegistry};
+use crate::error::WebhookError;
+use crate::message::Message;
+use crate::state::{MatchEvent, MatchState};
+
+/// Event selected for delivery to a webhook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// A bout reached its final status.
+    BoutFinished,
+    /// A fencer received a yellow, red or P card.
+    CardGiven,
+    /// A fencer's score reached a multiple of the given step (e.g. 5, 10, 15).
+    ScoreMilestone { every: u8 },
+}
+
+impl Trigger {
+    /// Returns the name of the trigger used in payloads.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Trigger::BoutFinished => "bout_finished",
+            Trigger::CardGiven => "card_given",
+            Trigger::ScoreMilestone { .. } => "score_milestone",
+        }
+    }
+
+    /// Returns `true` if the event fires this trigger.
+    pub fn matches(&self, event: &MatchEvent) -> bool {
+        match (self, event) {
+            (Trigger::BoutFinished, MatchEvent::BoutFinished) => true,
+            (Trigger::CardGiven, MatchEvent::CardsChanged { .. }) => true,
+            (Trigger::ScoreMilestone { every }, MatchEvent::ScoreChanged { to: Some(score), .. }) => {
+                *every > 0 && *score > 0 && score % every == 0
+            }
+            (Trigger::ScoreMilestone { every }, MatchEvent::DoubleTouch { right_score, left_score }) => {
+                *every > 0 && [right_score, left_score].iter().any(|score| **score > 0 && *score % every == 0)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A webhook endpoint and the events it subscribes to.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    /// URL the payloads are POSTed to.
+    pub url: String,
+    /// Events delivered to the endpoint.
+    pub triggers: Vec<Trigger>,
+}
+
+impl Webhook {
+    /// Creates a webhook for the given URL and triggers.
+    pub fn new(url: impl Into<String>, triggers: impl Into<Vec<Trigger>>) -> Self {
+        Webhook {
+            url: url.into(),
+            triggers: triggers.into(),
+        }
+    }
+}
+
+/// Retry schedule applied to failed deliveries.
+///
+/// The delay before retry `n` (starting at 1) is `initial_backoff * 2^(n-1)`,
+/// capped at `max_backoff`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the delay to wait before the given retry (1 for the first retry).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u32.checked_shl(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+}
+
+/// JSON body POSTed to a webhook.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WebhookPayload {
+    /// Name of the trigger that fired (e.g. "bout_finished").
+    pub trigger: &'static str,
+    /// Piste identifier.
+    pub piste: String,
+    /// Competition identifier of the bout.
+    pub competition_id: Option<String>,
+    /// The event that fired the trigger.
+    pub event: MatchEvent,
+    /// The last message of the bout when the event happened.
+    pub message: Option<Message>,
+    /// The competition of the bout, if registered with the dispatcher.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub competition: Option<CompetitionInfo>,
+}
+
+/// Sends encoded payloads to webhook endpoints.
+pub trait Transport {
+    /// POSTs a JSON body to the URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the failure if the endpoint was not reached or
+    /// did not answer with a success status.
+    fn post(&self, url: &str, body: &str) -> Result<(), String>;
+}
+
+/// Transport over HTTP, built on `ureq`.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    agent: ureq::Agent,
+}
+
+impl HttpTransport {
+    /// Creates a transport with the given per-request timeout.
+    pub fn new(timeout: Duration) -> Self {
+        let config = ureq::Agent::config_builder()
+            .timeout_global(Some(timeout))
+            .build();
+        HttpTransport {
+            agent: config.into(),
+        }
+    }
+}
+
+impl Default for HttpTransport {
+    fn default() -> Self {
+        HttpTransport::new(Duration::from_secs(10))
+    }
+}
+
+impl Transport for HttpTransport {
+    fn post(&self, url: &str, body: &str) -> Result<(), String> {
+        self.agent
+            .post(url)
+            .content_type("application/json")
+            .send(body)
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Delivers selected piste events to webhook endpoints.
+///
+/// [`WebhookDispatcher::payloads`] selects what to send and
+/// [`WebhookDispatcher::deliver`] sends one payload, blocking through the retry
+/// schedule. To keep the receive loop responsive, [`WebhookDispatcher::spawn`]
+/// moves delivery to a background thread.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::output::webhook::{Trigger, Webhook, WebhookDispatcher};
+/// use cyrano::state::MatchState;
+///
+/// let dispatcher = WebhookDispatcher::new(vec![
+///     Webhook::new("https://results.example.org/hooks/bouts", [Trigger::BoutFinished]),
+/// ]);
+/// let worker = dispatcher.spawn();
+///
+/// let mut state = MatchState::new("3");
+/// let events = state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1|||||E||E|%|28|P.Martin|FRA|5|V|%|32|B. Panini|ITA|2|D|%|").unwrap());
+/// worker.notify(&state, &events);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WebhookDispatcher<T = HttpTransport> {
+    hooks: Vec<Webhook>,
+    retry: RetryPolicy,
+    transport: T,
+    competitions: CompetitionRegistry,
+}
+
+impl WebhookDispatcher<HttpTransport> {
+    /// Creates a dispatcher sending over HTTP with the default retry policy.
+    pub fn new(hooks: Vec<Webhook>) -> Self {
+        WebhookDispatcher::with_transport(hooks, HttpTransport::default())
+    }
+}
+
+impl<T: Transport> WebhookDispatcher<T> {
+    /// Creates a dispatcher sending through the given transport.
+    pub fn with_transport(hooks: Vec<Webhook>, transport: T) -> Self {
+        WebhookDispatcher {
+            hooks,
+            retry: RetryPolicy::default(),
+            transport,
+            competitions: CompetitionRegistry::default(),
+        }
+    }
+
+    /// Sets the retry policy.
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the competitions whose information is joined to the payloads.
+    pub fn competitions(mut self, competitions: CompetitionRegistry) -> Self {
+        self.competitions = competitions;
+        self
+    }
+
+    /// Returns the payloads to send for the events of a piste, with their destination URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The piste state after the events were applied
+    /// * `events` - The events returned by `MatchState::apply`
+    pub fn payloads(&self, state: &MatchState, events: &[MatchEvent]) -> Vec<(String, WebhookPayload)> {
+        payloads(&self.hooks, &self.competitions, state, events)
+    }
+
+    /// Sends a payload, retrying with backoff until it succeeds or the attempts run out.
+    ///
+    /// # Returns
+    ///
+    /// The number of attempts it took.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WebhookError::Delivery` with the last failure once every attempt failed.
+    pub fn deliver(&self, url: &str, payload: &WebhookPayload) -> Result<u32, WebhookError> {
+        let body = serde_json::to_string(payload).map_err(|err| WebhookError::Encoding(err.to_string()))?;
+
+        let mut attempt = 1;
+        loop {
+            match self.transport.post(url, &body) {
+                Ok(()) => return Ok(attempt),
+                Err(reason) if attempt >= self.retry.max_attempts => {
+                    return Err(WebhookError::Delivery {
+                        url: url.to_string(),
+                        attempts: attempt,
+                        reason,
+                    })
+                }
+                Err(_reason) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(url, attempt, reason = %_reason, "webhook delivery failed, retrying");
+                    thread::sleep(self.retry.backoff(attempt));
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Moves delivery to a background thread.
+    ///
+    /// The thread stops once the returned worker is dropped and its queue is drained.
+    pub fn spawn(self) -> WebhookWorker
+    where
+        T: Send + 'static,
+    {
+        let hooks = self.hooks.clone();
+        let competitions = self.competitions.clone();
+        let (sender, receiver) = mpsc::channel::<(String, WebhookPayload)>();
+        let (error_sender, errors) = mpsc::channel();
+
+        let thread = thread::spawn(move || {
+            for (url, payload) in receiver {
+                if let Err(err) = self.deliver(&url, &payload) {
+                    let _ = error_sender.send(err);
+                }
+            }
+        });
+
+        WebhookWorker {
+            hooks,
+            competitions,
+            sender: Some(sender),
+            errors,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Handle to a dispatcher running on a background thread, created by
+/// [`WebhookDispatcher::spawn`].
+#[derive(Debug)]
+pub struct WebhookWorker {
+    hooks: Vec<Webhook>,
+    competitions: CompetitionRegistry,
+    sender: Option<Sender<(String, WebhookPayload)>>,
+    errors: Receiver<WebhookError>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WebhookWorker {
+    /// Queues the payloads selected for the events of a piste. Never blocks.
+    pub fn notify(&self, state: &MatchState, events: &[MatchEvent]) {
+        if let Some(sender) = &self.sender {
+            for payload in payloads(&self.hooks, &self.competitions, state, events) {
+                let _ = sender.send(payload);
+            }
+        }
+    }
+
+    /// Returns the deliveries that failed since the last call.
+    pub fn failures(&self) -> Vec<WebhookError> {
+        self.errors.try_iter().collect()
+    }
+}
+
+impl Drop for WebhookWorker {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn payloads(
+    hooks: &[Webhook],
+    competitions: &CompetitionRegistry,
+    state: &MatchState,
+    events: &[MatchEvent],
+) -> Vec<(String, WebhookPayload)> {
+    let competition = state.competition_id().and_then(|id| competitions.get(id));
+    let mut payloads = Vec::new();
+    for hook in hooks {
+        for event in events {
+            if let Some(trigger) = hook.triggers.iter().find(|t| t.matches(event)) {
+                payloads.push((
+                    hook.url.clone(),
+                    WebhookPayload {
+                        trigger: trigger.name(),
+                        piste: state.piste().to_string(),
+                        competition_id: state.competition_id().map(str::to_string),
+                        event: event.clone(),
+                        message: state.message().cloned(),
+                        competition: competition.cloned(),
+                    },
+                ));
+            }
+        }
+    }
+    payloads
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::convert::TryFrom;
+
+    struct FlakyTransport {
+        failures: RefCell<u32>,
+        bodies: RefCell<Vec<String>>,
+    }
+
+    impl Transport for FlakyTransport {
+        fn post(&self, _url: &str, body: &str) -> Result<(), String> {
+            let mut failures = self.failures.borrow_mut();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err("connection refused".to_string());
+            }
+            self.bodies.borrow_mut().push(body.to_string());
+            Ok(())
+        }
+    }
+
+    fn flaky_dispatcher(failures: u32) -> WebhookDispatcher<FlakyTransport> {
+        let hooks = vec![Webhook::new(
+            "http://localhost/hook",
+            [Trigger::BoutFinished, Trigger::ScoreMilestone { every: 5 }],
+        )];
+        let transport = FlakyTransport {
+            failures: RefCell::new(failures),
+            bodies: RefCell::new(Vec::new()),
+        };
+        WebhookDispatcher::with_transport(hooks, transport).retry(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+        })
+    }
+
+    #[test]
+    fn test_payload_selection_and_retry() {
+        let mut state = MatchState::new("3");
+        state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1|||||E||F|%|28|P.Martin|FRA|4|U|%|32|Panini|ITA|2|U|%|").unwrap());
+        let events = state.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1|||||E||E|%|28|P.Martin|FRA|5|V|%|32|Panini|ITA|2|D|%|").unwrap());
+
+        let dispatcher = flaky_dispatcher(2);
+        let payloads = dispatcher.payloads(&state, &events);
+        let triggers: Vec<&str> = payloads.iter().map(|(_, p)| p.trigger).collect();
+        assert_eq!(triggers, vec!["score_milestone", "bout_finished"]);
+
+        assert_eq!(dispatcher.deliver(&payloads[0].0, &payloads[0].1), Ok(3));
+        assert!(dispatcher.transport.bodies.borrow()[0].contains("\"trigger\":\"score_milestone\""));
+
+        let mut competitions = CompetitionRegistry::new();
+        competitions.insert("fm-eq", CompetitionInfo::new("Challenge Monal"));
+        let (_, payload) = &flaky_dispatcher(0).competitions(competitions).payloads(&state, &events)[1];
+        assert_eq!(payload.competition.as_ref().map(|c| c.name.as_str()), Some("Challenge Monal"));
+        assert!(!dispatcher.transport.bodies.borrow()[0].contains("\"competition\""));
+
+        let failing = flaky_dispatcher(5);
+        assert!(matches!(
+            failing.deliver(&payloads[1].0, &payloads[1].1),
+            Err(WebhookError::Delivery { attempts: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let retry = RetryPolicy::default();
+        assert_eq!(retry.backoff(1), Duration::from_millis(500));
+        assert_eq!(retry.backoff(3), Duration::from_secs(2));
+        assert_eq!(retry.backoff(40), Duration::from_secs(30));
+    }
+}
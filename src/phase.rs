@@ -0,0 +1,245 @@
+use crate::enums::{CompetitionType, Side};
+use crate::message::Message;
+use crate::rules::RuleProfile;
+
+/// Number of relays in a team match.
+pub const TEAM_RELAYS: u8 = 9;
+
+/// Team positions meeting in each relay of a team match, per the FIE order:
+/// positions 1 to 3 for the team on the right, 4 to 6 for the team on the left.
+pub const RELAY_ORDER: [(u8, u8); TEAM_RELAYS as usize] =
+    [(3, 6), (1, 5), (2, 4), (1, 6), (3, 4), (2, 5), (1, 4), (2, 6), (3, 5)];
+
+/// Returns the team position fencing a relay (1 to 9) on the given side.
+pub fn relay_position(relay: u8, side: Side) -> Option<u8> {
+    let (right, left) = *RELAY_ORDER.get(usize::from(relay).checked_sub(1)?)?;
+    Some(match side {
+        Side::Right => right,
+        Side::Left => left,
+    })
+}
+
+/// Touches added to the target score by each relay of a team match.
+pub const RELAY_TOUCHES: u8 = 5;
+
+/// Target score of an individual pool bout.
+pub const POOL_TARGET_SCORE: u8 = 5;
+
+/// Target score of an individual direct elimination bout.
+pub const ELIMINATION_TARGET_SCORE: u8 = 15;
+
+/// Periods of an individual direct elimination bout, separated by breaks.
+pub const ELIMINATION_PERIODS: u8 = 3;
+
+/// Length of the break between two periods, in seconds.
+pub const BREAK_SECONDS: u32 = 60;
+
+/// Stage of the competition a bout belongs to, read from the `pool_tableau` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stage {
+    /// A pool (`"P3"`, or a bare pool number `"3"`).
+    Pool {
+        /// Pool number.
+        number: u16,
+    },
+    /// A direct elimination tableau (`"A32"`: table A of 32 fencers).
+    Tableau {
+        /// Letter of the table (`A` for the main table).
+        table: char,
+        /// Number of places in the tableau (64, 32, ... 2).
+        size: u16,
+    },
+}
+
+impl Stage {
+    /// Reads a stage from a `pool_tableau` value.
+    ///
+    /// Pools are written `P` followed by the pool number, or as a bare number.
+    /// Tableaux are written with a table letter followed by the tableau size,
+    /// which must be a power of two. Other values return `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::phase::Stage;
+    ///
+    /// assert_eq!(Stage::parse("P3"), Some(Stage::Pool { number: 3 }));
+    /// assert_eq!(Stage::parse("A32"), Some(Stage::Tableau { table: 'A', size: 32 }));
+    /// assert_eq!(Stage::parse("A33"), None);
+    /// ```
+    pub fn parse(pool_tableau: &str) -> Option<Stage> {
+        let value = pool_tableau.trim();
+        let mut chars = value.chars();
+        let first = chars.next()?;
+
+        if first.is_ascii_digit() {
+            return value.parse().ok().map(|number| Stage::Pool { number });
+        }
+
+        let number: u16 = chars.as_str().parse().ok()?;
+        match first.to_ascii_uppercase() {
+            'P' => Some(Stage::Pool { number }),
+            table if table.is_ascii_uppercase() && number >= 2 && number.is_power_of_two() => {
+                Some(Stage::Tableau { table, size: number })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `true` for a pool.
+    pub fn is_pool(&self) -> bool {
+        matches!(self, Stage::Pool { .. })
+    }
+
+    /// Returns `true` for a direct elimination tableau.
+    pub fn is_direct_elimination(&self) -> bool {
+        matches!(self, Stage::Tableau { .. })
+    }
+
+    /// Returns `true` for the final (tableau of 2).
+    pub fn is_final(&self) -> bool {
+        matches!(self, Stage::Tableau { size: 2, .. })
+    }
+}
+
+impl Message {
+    /// Returns the stage of the bout, read from `pool_tableau`.
+    pub fn stage(&self) -> Option<Stage> {
+        self.pool_tableau.as_deref().and_then(Stage::parse)
+    }
+
+    /// Returns `true` if the bout is fenced in a pool.
+    pub fn is_pool_phase(&self) -> bool {
+        self.stage().is_some_and(|stage| stage.is_pool())
+    }
+
+    /// Returns `true` if the bout is fenced in a direct elimination tableau.
+    pub fn is_direct_elimination(&self) -> bool {
+        self.stage().is_some_and(|stage| stage.is_direct_elimination())
+    }
+
+    /// Returns the relay of a team match (1 to 9), carried by the `round` field.
+    ///
+    /// In individual competitions `round` is the round of the pool or tableau
+    /// and this returns `None`.
+    pub fn relay(&self) -> Option<u8> {
+        match self.competition_type {
+            Some(CompetitionType::Team) => self.round.filter(|r| (1..=TEAM_RELAYS).contains(r)),
+            _ => None,
+        }
+    }
+
+    /// Returns the score of the current bout of a fencer.
+    ///
+    /// In individual bouts this is the displayed `score`. In team matches it is
+    /// the touches scored in the current relay, which only some apparatus
+    /// report (in [`Fencer::bout_score`](crate::fencer::Fencer::bout_score));
+    /// `None` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::enums::Side;
+    /// use cyrano::message::Message;
+    ///
+    /// let team = Message::try_from("|EFP1.1|INFO|2|ef-eq|4|T8|3|5|16:20|1:12|T|E||F|%|FRA|France|FRA|23|U|0|0|0|0|0|N|0|3|%|ITA|Italie|ITA|25|U|0|0|0|0|0|N|0|4|%|").unwrap();
+    /// assert_eq!(team.relay_score(Side::Right), Some(23));
+    /// assert_eq!(team.bout_score(Side::Right), Some(3));
+    /// ```
+    pub fn bout_score(&self, side: Side) -> Option<u8> {
+        let fencer = self.fencer(side);
+        match self.competition_type {
+            Some(CompetitionType::Team) => fencer.bout_score,
+            _ => fencer.score,
+        }
+    }
+
+    /// Returns the cumulative score of a team in a team match, shown as
+    /// `score` by the apparatus, or `None` in individual bouts.
+    pub fn relay_score(&self, side: Side) -> Option<u8> {
+        match self.competition_type {
+            Some(CompetitionType::Team) => self.fencer(side).score,
+            _ => None,
+        }
+    }
+
+    /// Returns the score that ends the bout, or relay for team matches.
+    ///
+    /// Individual bouts are fenced to 5 touches in pools and 15 in direct
+    /// elimination. Team relays are fenced to a cumulative 5, 10, ... 45.
+    /// Returns `None` when the stage or relay is unknown.
+    ///
+    /// These are the FIE senior rules; see [`RuleProfile::target_score`] for
+    /// other categories.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::message::Message;
+    ///
+    /// let team = Message::try_from("|EFP1.1|INFO|17|fm-eq|2|A8|1|4|||T|E||W|%|%|%|").unwrap();
+    /// assert_eq!(team.relay(), Some(4));
+    /// assert_eq!(team.target_score(), Some(20));
+    /// ```
+    pub fn target_score(&self) -> Option<u8> {
+        RuleProfile::FIE_SENIOR.target_score(self)
+    }
+
+    /// Returns the number of periods of the bout: 3 in individual direct
+    /// elimination, 1 in pools and for each relay of a team match.
+    ///
+    /// Extra time fenced with priority after a tie is not counted. Returns
+    /// `None` when the stage is unknown. These are the FIE senior rules; see
+    /// [`RuleProfile::periods`] for other categories.
+    pub fn periods(&self) -> Option<u8> {
+        RuleProfile::FIE_SENIOR.periods(self)
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_stage_parsing() {
+        assert_eq!(Stage::parse("p12"), Some(Stage::Pool { number: 12 }));
+        assert_eq!(Stage::parse("7"), Some(Stage::Pool { number: 7 }));
+        assert_eq!(Stage::parse("B16"), Some(Stage::Tableau { table: 'B', size: 16 }));
+        assert!(Stage::parse("A2").unwrap().is_final());
+        assert_eq!(Stage::parse("Finale"), None);
+        assert_eq!(Stage::parse(""), None);
+    }
+
+    #[test]
+    fn test_individual_target_scores() {
+        let pool = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|P3|4|2|||I|E||W|%|%|%|").unwrap();
+        assert!(pool.is_pool_phase());
+        assert_eq!(pool.target_score(), Some(5));
+        assert_eq!(pool.relay(), None);
+
+        let de = Message::try_from("|EFP1.1|INFO|17|fm-eq|2|A32|12|1|||I|E||W|%|%|%|").unwrap();
+        assert!(de.is_direct_elimination());
+        assert_eq!(de.target_score(), Some(15));
+    }
+
+    #[test]
+    fn test_team_scores() {
+        let raw = "|EFP1.1|INFO|2|ef-eq|4|T8|3|5|16:20|1:12|T|E||F|%|FRA|France|FRA|23|U|0|0|0|0|0|N|0|3|%|ITA|Italie|ITA|25|U|0|0|0|0|0|N|0|4|%|";
+        let team = Message::try_from(raw).unwrap();
+        assert_eq!((team.bout_score(Side::Left), team.relay_score(Side::Left)), (Some(4), Some(25)));
+        assert_eq!(Message::try_from(team.to_string().as_str()).unwrap(), team);
+
+        // The relay score is dropped outside EFP1.1 team frames.
+        let individual = Message::try_from(raw.replace("|T|E|", "|I|E|").as_str()).unwrap();
+        assert_eq!((individual.bout_score(Side::Left), individual.relay_score(Side::Left)), (Some(25), None));
+        assert_eq!(individual.left_fencer.bout_score, None);
+        let efp1 = Message::try_from(raw.replace("EFP1.1", "EFP1").as_str()).unwrap();
+        assert_eq!(efp1.bout_score(Side::Left), None);
+    }
+}
@@ -0,0 +1,113 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+/// Identifier of a piste, numeric (`"1"`..`"20"`) or named (`"Finale"`, `"Verte"`).
+///
+/// The identifier keeps the text sent by the apparatus. Pistes sort in natural
+/// order: numeric pistes first by number, so piste 2 comes before piste 10,
+/// then named pistes alphabetically, ignoring case.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::piste::PisteId;
+///
+/// let mut pistes: Vec<PisteId> = ["10", "Finale", "2", "bleue"].iter().map(|p| PisteId::from(*p)).collect();
+/// pistes.sort();
+///
+/// let names: Vec<&str> = pistes.iter().map(PisteId::as_str).collect();
+/// assert_eq!(names, ["2", "10", "bleue", "Finale"]);
+/// assert_eq!(pistes[0].number(), Some(2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct PisteId(String);
+
+impl PisteId {
+    /// Creates a piste identifier from its protocol text.
+    pub fn new(piste: impl Into<String>) -> Self {
+        PisteId(piste.into())
+    }
+
+    /// Returns the identifier as sent in messages.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the piste number, or `None` for a named piste.
+    pub fn number(&self) -> Option<u32> {
+        if self.0.bytes().all(|b| b.is_ascii_digit()) {
+            self.0.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` for a named piste such as `"Finale"`.
+    pub fn is_named(&self) -> bool {
+        self.number().is_none()
+    }
+}
+
+impl Ord for PisteId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.number(), other.number()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => self.0.to_lowercase().cmp(&other.0.to_lowercase()),
+        }
+        .then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for PisteId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<&str> for PisteId {
+    fn from(piste: &str) -> Self {
+        PisteId::new(piste)
+    }
+}
+
+impl From<String> for PisteId {
+    fn from(piste: String) -> Self {
+        PisteId(piste)
+    }
+}
+
+impl AsRef<str> for PisteId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for PisteId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_natural_order() {
+        let mut pistes: Vec<PisteId> = ["Rouge", "07", "12", "7", "1", "Finale", "verte"]
+            .iter()
+            .map(|p| PisteId::from(*p))
+            .collect();
+        pistes.sort();
+
+        let names: Vec<&str> = pistes.iter().map(PisteId::as_str).collect();
+        assert_eq!(names, ["1", "07", "7", "12", "Finale", "Rouge", "verte"]);
+        assert_ne!(PisteId::from("07"), PisteId::from("7"));
+        assert!(PisteId::from("-1").is_named());
+    }
+}
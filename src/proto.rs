@@ -0,0 +1,480 @@
+//! Protobuf encoding of messages, matching `proto/cyrano.proto`.
+//!
+//! The types of this module mirror the schema published in the repository so
+//! they can be encoded with [`prost`] without a build step. Empty protocol
+//! fields map to absent optional fields and to the `UNSPECIFIED` enumeration
+//! value.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::convert::TryFrom;
+//! use prost::Message as _;
+//! use cyrano::message::Message;
+//! use cyrano::proto;
+//!
+//! let message = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|P3|1|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap();
+//!
+//! let bytes = proto::Message::from(&message).encode_to_vec();
+//! let decoded = proto::Message::decode(bytes.as_slice()).unwrap();
+//! assert_eq!(Message::try_from(decoded).unwrap(), message);
+//! ```
+
+use std::convert::TryFrom;
+
+use crate::enums;
+use crate::error::ParseError;
+use crate::utils::field;
+
+/// Command type of a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Command {
+    Unspecified = 0,
+    Hello = 1,
+    Disp = 2,
+    Ack = 3,
+    Nak = 4,
+    Info = 5,
+    Next = 6,
+    Prev = 7,
+}
+
+/// Individual or team competition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum CompetitionType {
+    Unspecified = 0,
+    Individual = 1,
+    Team = 2,
+}
+
+/// Weapon used in the bout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Weapon {
+    Unspecified = 0,
+    Foil = 1,
+    Epee = 2,
+    Sabre = 3,
+}
+
+/// Priority indicator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Priority {
+    Unspecified = 0,
+    None = 1,
+    Right = 2,
+    Left = 3,
+}
+
+/// State of the apparatus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum ApparatusState {
+    Unspecified = 0,
+    Fencing = 1,
+    Halt = 2,
+    Pause = 3,
+    Waiting = 4,
+    Ending = 5,
+}
+
+/// Status of a fencer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum FencerStatus {
+    Unspecified = 0,
+    Undefined = 1,
+    Victory = 2,
+    Defeat = 3,
+    Abandonment = 4,
+    Exclusion = 5,
+}
+
+/// Reserve introduction in team events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Reserve {
+    Unspecified = 0,
+    None = 1,
+    Introduce = 2,
+}
+
+/// Penalty card for non-combativity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum PCard {
+    Unspecified = 0,
+    None = 1,
+    Yellow = 2,
+    OneRed = 3,
+    TwoRed = 4,
+    OneBlack = 5,
+    TwoBlack = 6,
+}
+
+/// Cards given by the referee to officials of the bout.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Sanctions {
+    #[prost(uint32, optional, tag = "1")]
+    pub yellow_card: Option<u32>,
+    #[prost(uint32, optional, tag = "2")]
+    pub red_card: Option<u32>,
+    #[prost(uint32, optional, tag = "3")]
+    pub black_card: Option<u32>,
+}
+
+/// Referee zone of a message.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Referee {
+    #[prost(string, optional, tag = "1")]
+    pub id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub name: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub nation: Option<String>,
+    #[prost(message, optional, tag = "4")]
+    pub sanctions: Option<Sanctions>,
+}
+
+/// Fencer zone of a message.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Fencer {
+    #[prost(string, optional, tag = "1")]
+    pub id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub name: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub nation: Option<String>,
+    #[prost(uint32, optional, tag = "4")]
+    pub score: Option<u32>,
+    #[prost(enumeration = "FencerStatus", tag = "5")]
+    pub status: i32,
+    #[prost(uint32, optional, tag = "6")]
+    pub yellow_card: Option<u32>,
+    #[prost(uint32, optional, tag = "7")]
+    pub red_card: Option<u32>,
+    #[prost(bool, optional, tag = "8")]
+    pub light: Option<bool>,
+    #[prost(bool, optional, tag = "9")]
+    pub white_light: Option<bool>,
+    #[prost(uint32, optional, tag = "10")]
+    pub medical: Option<u32>,
+    #[prost(enumeration = "Reserve", tag = "11")]
+    pub reserve: i32,
+    #[prost(enumeration = "PCard", tag = "12")]
+    pub p_card: i32,
+    #[prost(uint32, optional, tag = "13")]
+    pub bout_score: Option<u32>,
+    #[prost(uint32, optional, tag = "14")]
+    pub video_appeals: Option<u32>,
+    #[prost(bool, optional, tag = "15")]
+    pub video_review: Option<bool>,
+}
+
+/// A complete message.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Message {
+    #[prost(string, tag = "1")]
+    pub protocol: String,
+    #[prost(enumeration = "Command", tag = "2")]
+    pub command: i32,
+    #[prost(string, tag = "3")]
+    pub piste: String,
+    #[prost(string, tag = "4")]
+    pub competition_id: String,
+    #[prost(uint32, optional, tag = "5")]
+    pub phase: Option<u32>,
+    #[prost(string, optional, tag = "6")]
+    pub pool_tableau: Option<String>,
+    #[prost(uint32, optional, tag = "7")]
+    pub match_number: Option<u32>,
+    #[prost(uint32, optional, tag = "8")]
+    pub round: Option<u32>,
+    #[prost(string, optional, tag = "9")]
+    pub time: Option<String>,
+    #[prost(string, optional, tag = "10")]
+    pub stopwatch: Option<String>,
+    #[prost(enumeration = "CompetitionType", tag = "11")]
+    pub competition_type: i32,
+    #[prost(enumeration = "Weapon", tag = "12")]
+    pub weapon: i32,
+    #[prost(enumeration = "Priority", tag = "13")]
+    pub priority: i32,
+    #[prost(enumeration = "ApparatusState", tag = "14")]
+    pub state: i32,
+    #[prost(message, optional, tag = "15")]
+    pub referee: Option<Referee>,
+    #[prost(message, optional, tag = "16")]
+    pub right_fencer: Option<Fencer>,
+    #[prost(message, optional, tag = "17")]
+    pub left_fencer: Option<Fencer>,
+}
+
+/// Maps an optional crate enumeration to its protobuf value and back.
+trait Mapping: Sized {
+    fn encode(value: Option<&Self>) -> i32;
+    fn decode(value: i32, field: &'static str) -> Result<Option<Self>, ParseError>;
+}
+
+macro_rules! mapping {
+    ($name:ident { $($variant:ident),+ $(,)? }) => {
+        impl Mapping for enums::$name {
+            fn encode(value: Option<&Self>) -> i32 {
+                match value {
+                    None => $name::Unspecified as i32,
+                    $(Some(enums::$name::$variant) => $name::$variant as i32,)+
+                    // Codes unknown to this crate have no protobuf value.
+                    #[allow(unreachable_patterns)]
+                    Some(_) => $name::Unspecified as i32,
+                }
+            }
+
+            fn decode(value: i32, field: &'static str) -> Result<Option<Self>, ParseError> {
+                match $name::try_from(value) {
+                    Ok($name::Unspecified) => Ok(None),
+                    $(Ok($name::$variant) => Ok(Some(enums::$name::$variant)),)+
+                    Err(_) => Err(ParseError::InvalidValue {
+                        field,
+                        value: value.to_string(),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+mapping!(Command { Hello, Disp, Ack, Nak, Info, Next, Prev });
+mapping!(CompetitionType { Individual, Team });
+mapping!(Weapon { Foil, Epee, Sabre });
+mapping!(Priority { None, Right, Left });
+mapping!(ApparatusState { Fencing, Halt, Pause, Waiting, Ending });
+mapping!(FencerStatus { Undefined, Victory, Defeat, Abandonment, Exclusion });
+mapping!(Reserve { None, Introduce });
+mapping!(PCard { None, Yellow, OneRed, TwoRed, OneBlack, TwoBlack });
+
+fn number(value: Option<u32>, field: &'static str) -> Result<Option<u8>, ParseError> {
+    value
+        .map(|n| {
+            u8::try_from(n).map_err(|_| ParseError::InvalidValue {
+                field,
+                value: n.to_string(),
+            })
+        })
+        .transpose()
+}
+
+impl From<&crate::Referee> for Referee {
+    fn from(referee: &crate::Referee) -> Self {
+        Referee {
+            id: referee.id.as_deref().map(String::from),
+            name: referee.name.as_deref().map(String::from),
+            nation: referee.nation.as_deref().map(String::from),
+            sanctions: referee.sanctions.map(|sanctions| Sanctions {
+                yellow_card: sanctions.yellow_card.map(u32::from),
+                red_card: sanctions.red_card.map(u32::from),
+                black_card: sanctions.black_card.map(u32::from),
+            }),
+        }
+    }
+}
+
+impl TryFrom<Referee> for crate::Referee {
+    type Error = ParseError;
+
+    fn try_from(referee: Referee) -> Result<Self, Self::Error> {
+        let sanctions = referee
+            .sanctions
+            .map(|sanctions| {
+                Ok::<_, ParseError>(crate::referee::Sanctions {
+                    yellow_card: number(sanctions.yellow_card, "sanctions.yellow_card")?,
+                    red_card: number(sanctions.red_card, "sanctions.red_card")?,
+                    black_card: number(sanctions.black_card, "sanctions.black_card")?,
+                })
+            })
+            .transpose()?;
+        Ok(crate::Referee {
+            id: referee.id.map(field),
+            name: referee.name.map(field),
+            nation: referee.nation.map(field),
+            sanctions,
+        })
+    }
+}
+
+impl From<&crate::Fencer> for Fencer {
+    fn from(fencer: &crate::Fencer) -> Self {
+        Fencer {
+            id: fencer.id.as_deref().map(String::from),
+            name: fencer.name.as_deref().map(String::from),
+            nation: fencer.nation.as_deref().map(String::from),
+            score: fencer.score.map(u32::from),
+            status: Mapping::encode(fencer.status.as_ref()),
+            yellow_card: fencer.yellow_card.map(u32::from),
+            red_card: fencer.red_card.map(u32::from),
+            light: fencer.light,
+            white_light: fencer.white_light,
+            medical: fencer.medical.map(u32::from),
+            reserve: Mapping::encode(fencer.reserve.as_ref()),
+            p_card: Mapping::encode(fencer.p_card.as_ref()),
+            bout_score: fencer.bout_score.map(u32::from),
+            video_appeals: fencer.video_appeals.map(u32::from),
+            video_review: fencer.video_review,
+        }
+    }
+}
+
+impl TryFrom<Fencer> for crate::Fencer {
+    type Error = ParseError;
+
+    fn try_from(fencer: Fencer) -> Result<Self, Self::Error> {
+        Ok(crate::Fencer {
+            id: fencer.id.map(field),
+            name: fencer.name.map(field),
+            nation: fencer.nation.map(field),
+            score: number(fencer.score, "score")?,
+            status: Mapping::decode(fencer.status, "status")?,
+            yellow_card: number(fencer.yellow_card, "yellow_card")?,
+            red_card: number(fencer.red_card, "red_card")?,
+            light: fencer.light,
+            white_light: fencer.white_light,
+            medical: number(fencer.medical, "medical")?,
+            reserve: Mapping::decode(fencer.reserve, "reserve")?,
+            p_card: Mapping::decode(fencer.p_card, "p_card")?,
+            bout_score: number(fencer.bout_score, "bout_score")?,
+            video_appeals: number(fencer.video_appeals, "video_appeals")?,
+            video_review: fencer.video_review,
+        })
+    }
+}
+
+impl From<&crate::Message> for Message {
+    fn from(message: &crate::Message) -> Self {
+        Message {
+            protocol: message.protocol.to_string(),
+            command: Mapping::encode(Some(&message.command)),
+            piste: message.piste.to_string(),
+            competition_id: message.competition_id.to_string(),
+            phase: message.phase.map(u32::from),
+            pool_tableau: message.pool_tableau.as_deref().map(String::from),
+            match_number: message.match_number.map(u32::from),
+            round: message.round.map(u32::from),
+            time: message.time.as_deref().map(String::from),
+            stopwatch: message.stopwatch.as_deref().map(String::from),
+            competition_type: Mapping::encode(message.competition_type.as_ref()),
+            weapon: Mapping::encode(message.weapon.as_ref()),
+            priority: Mapping::encode(message.priority.as_ref()),
+            state: Mapping::encode(message.state.as_ref()),
+            referee: Some(Referee::from(&message.referee)),
+            right_fencer: Some(Fencer::from(&message.right_fencer)),
+            left_fencer: Some(Fencer::from(&message.left_fencer)),
+        }
+    }
+}
+
+impl TryFrom<Message> for crate::Message {
+    type Error = ParseError;
+
+    /// Converts a decoded protobuf message back into a [`crate::Message`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::MissingField` if the command is unspecified and
+    /// `ParseError::InvalidValue` for an unknown enumeration value or a number
+    /// above 255.
+    fn try_from(message: Message) -> Result<Self, Self::Error> {
+        let command = enums::Command::decode(message.command, "command")?
+            .ok_or(ParseError::MissingField("command"))?;
+
+        Ok(crate::Message {
+            protocol: field(message.protocol),
+            command,
+            piste: field(message.piste),
+            competition_id: field(message.competition_id),
+            phase: number(message.phase, "phase")?,
+            pool_tableau: message.pool_tableau.map(field),
+            match_number: number(message.match_number, "match_number")?,
+            round: number(message.round, "round")?,
+            time: message.time.map(field),
+            stopwatch: message.stopwatch.map(field),
+            competition_type: Mapping::decode(message.competition_type, "competition_type")?,
+            weapon: Mapping::decode(message.weapon, "weapon")?,
+            priority: Mapping::decode(message.priority, "priority")?,
+            state: Mapping::decode(message.state, "state")?,
+            referee: message
+                .referee
+                .map(crate::Referee::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+            right_fencer: message
+                .right_fencer
+                .map(crate::Fencer::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+            left_fencer: message
+                .left_fencer
+                .map(crate::Fencer::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message as _;
+
+    #[test]
+    fn test_roundtrip() {
+        let raw = "|EFP1.1|INFO|17|efj-eq|1|A32|12|2|10:30|3:00|I|S||W|132|J.Smith|GBR|%|28|P.Martin|FRA|8|V|0|1|1|0|0|N|%|32|B. Panini|ITA|6|D|0|1|0|0|0|N|%|";
+        let message = crate::Message::try_from(raw).unwrap();
+
+        let bytes = Message::from(&message).encode_to_vec();
+        let decoded = crate::Message::try_from(Message::decode(bytes.as_slice()).unwrap()).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(decoded.to_string(), raw);
+    }
+
+    #[test]
+    fn test_roundtrip_official_sanctions() {
+        let options = crate::options::ParseOptions {
+            profile: crate::options::VendorProfile::Extended,
+            ..crate::options::ParseOptions::default()
+        };
+        let message = crate::Message::parse_with("|EFP1.1|INFO|17|fm-eq|1|A8|2|||||E||H|7|J. Doe|GER|1||0|%|%|%|", &options).unwrap();
+
+        let bytes = Message::from(&message).encode_to_vec();
+        let decoded = crate::Message::try_from(Message::decode(bytes.as_slice()).unwrap()).unwrap();
+        assert_eq!(decoded.referee.sanctions, message.referee.sanctions);
+        assert_eq!(decoded, message);
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let mut message = Message::from(&crate::Message::new(enums::Command::Hello, "17", "fm-eq"));
+        message.phase = Some(300);
+        assert!(matches!(
+            crate::Message::try_from(message.clone()),
+            Err(ParseError::InvalidValue { field: "phase", .. })
+        ));
+
+        message.phase = None;
+        message.weapon = 9;
+        assert!(matches!(
+            crate::Message::try_from(message.clone()),
+            Err(ParseError::InvalidValue { field: "weapon", .. })
+        ));
+
+        message.weapon = 0;
+        message.command = 0;
+        assert!(matches!(
+            crate::Message::try_from(message),
+            Err(ParseError::MissingField("command"))
+        ));
+    }
+}
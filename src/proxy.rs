@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::message::Message;
+use crate::net::MAX_DATAGRAM_SIZE;
+use crate::options::ParseOptions;
+use crate::piste::PisteId;
+
+/// Filter deciding which messages a [`Consumer`] receives.
+pub type ConsumerFilter = Box<dyn Fn(&Message) -> bool + Send>;
+
+/// A downstream application fed by a [`Proxy`].
+pub struct Consumer {
+    addr: SocketAddr,
+    filter: Option<ConsumerFilter>,
+    unparsed: bool,
+    upstream: bool,
+}
+
+impl Consumer {
+    /// Creates a consumer receiving every message at the given address.
+    pub fn new(addr: SocketAddr) -> Self {
+        Consumer {
+            addr,
+            filter: None,
+            unparsed: false,
+            upstream: false,
+        }
+    }
+
+    /// Only forwards the messages for which `filter` returns `true`.
+    pub fn filter(mut self, filter: impl Fn(&Message) -> bool + Send + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Also forwards the datagrams that do not parse, e.g. to a logger.
+    pub fn unparsed(mut self, forward: bool) -> Self {
+        self.unparsed = forward;
+        self
+    }
+
+    /// Forwards the datagrams this consumer sends back to the apparatus, so
+    /// that it can answer and drive the piste. Only one consumer per piste,
+    /// usually the competition management software, should be upstream.
+    pub fn upstream(mut self, upstream: bool) -> Self {
+        self.upstream = upstream;
+        self
+    }
+
+    /// Returns the address the consumer receives on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn wants(&self, message: Option<&Message>) -> bool {
+        match (message, &self.filter) {
+            (None, _) => self.unparsed,
+            (Some(_), None) => true,
+            (Some(message), Some(filter)) => filter(message),
+        }
+    }
+}
+
+/// What a [`Proxy`] did with a received datagram.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relayed {
+    /// Address the datagram came from.
+    pub from: SocketAddr,
+    /// The message, or `None` if the datagram does not parse.
+    pub message: Option<Message>,
+    /// Number of addresses the datagram was forwarded to.
+    pub forwarded: usize,
+}
+
+/// Relay binding the apparatus feed and fanning it out to several consumers.
+///
+/// Only one socket can bind the port the apparatus sends to, so overlays,
+/// results servers and loggers are fed by the proxy instead. Datagrams are
+/// forwarded verbatim, so fields this crate does not know about reach the
+/// consumers untouched. Datagrams from an upstream consumer go back to the
+/// apparatus of the piste they name.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cyrano::enums::Command;
+/// use cyrano::proxy::{Consumer, Proxy};
+///
+/// let mut proxy = Proxy::bind("0.0.0.0:50100")?;
+/// proxy.add_consumer(Consumer::new("10.0.0.5:50100".parse().unwrap()).upstream(true));
+/// proxy.add_consumer(Consumer::new("10.0.0.9:50100".parse().unwrap()).filter(|message| message.command == Command::Info));
+/// proxy.add_consumer(Consumer::new("127.0.0.1:6000".parse().unwrap()).unparsed(true));
+/// proxy.run()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Proxy {
+    socket: UdpSocket,
+    options: ParseOptions,
+    buffer: Vec<u8>,
+    consumers: Vec<Consumer>,
+    apparatus: HashMap<PisteId, SocketAddr>,
+}
+
+impl Proxy {
+    /// Binds a proxy to the address the apparatus sends to.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the socket cannot be bound.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Proxy::from_socket(UdpSocket::bind(addr)?))
+    }
+
+    /// Wraps an already configured socket.
+    pub fn from_socket(socket: UdpSocket) -> Self {
+        Proxy {
+            socket,
+            options: ParseOptions::default(),
+            buffer: vec![0; MAX_DATAGRAM_SIZE],
+            consumers: Vec::new(),
+            apparatus: HashMap::new(),
+        }
+    }
+
+    /// Sets the options used to parse datagrams for filtering.
+    pub fn options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Adds a downstream consumer.
+    pub fn add_consumer(&mut self, consumer: Consumer) {
+        self.consumers.push(consumer);
+    }
+
+    /// Returns the underlying socket, e.g. to set a read timeout.
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Returns the local address the proxy is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Receives one datagram and forwards it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the socket, including timeouts.
+    pub fn relay_one(&mut self) -> io::Result<Relayed> {
+        let (len, from) = self.socket.recv_from(&mut self.buffer)?;
+        let raw = &self.buffer[..len];
+        let message = std::str::from_utf8(raw)
+            .ok()
+            .and_then(|text| Message::parse_with(text, &self.options).ok());
+
+        let mut forwarded = 0;
+        if let Some(consumer) = self.consumers.iter().find(|consumer| consumer.addr == from) {
+            let apparatus = message.as_ref().and_then(|message| self.apparatus.get(&PisteId::from(message.piste.as_ref())));
+            if let (true, Some(&apparatus)) = (consumer.upstream, apparatus) {
+                self.socket.send_to(raw, apparatus)?;
+                forwarded += 1;
+            }
+        } else {
+            if let Some(message) = &message {
+                self.apparatus.insert(PisteId::from(message.piste.as_ref()), from);
+            }
+            for consumer in self.consumers.iter().filter(|consumer| consumer.wants(message.as_ref())) {
+                self.socket.send_to(raw, consumer.addr)?;
+                forwarded += 1;
+            }
+        }
+
+        Ok(Relayed { from, message, forwarded })
+    }
+
+    /// Relays datagrams until the socket fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first I/O error of the socket.
+    pub fn run(&mut self) -> io::Result<()> {
+        loop {
+            self.relay_one()?;
+        }
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::Command;
+    use std::time::Duration;
+
+    fn recv(socket: &UdpSocket) -> Option<Vec<u8>> {
+        let mut buffer = [0; 512];
+        socket.recv(&mut buffer).ok().map(|len| buffer[..len].to_vec())
+    }
+
+    #[test]
+    fn test_fan_out_and_upstream() {
+        let mut proxy = Proxy::bind("127.0.0.1:0").unwrap();
+        let apparatus = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let software = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let overlay = UdpSocket::bind("127.0.0.1:0").unwrap();
+        for socket in [&apparatus, &software, &overlay] {
+            socket.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        }
+        proxy.add_consumer(Consumer::new(software.local_addr().unwrap()).upstream(true).unparsed(true));
+        proxy.add_consumer(Consumer::new(overlay.local_addr().unwrap()).filter(|message| message.command == Command::Info));
+        let to = proxy.local_addr().unwrap();
+
+        // Unknown trailing fields reach the consumers untouched.
+        let hello = b"|EFP1.1|HELLO|17|fm-eq|%|vendor|";
+        apparatus.send_to(hello, to).unwrap();
+        assert_eq!(proxy.relay_one().unwrap().forwarded, 1);
+        assert_eq!(recv(&software).unwrap(), hello);
+
+        apparatus.send_to(b"garbage", to).unwrap();
+        let relayed = proxy.relay_one().unwrap();
+        assert_eq!((relayed.message, relayed.forwarded), (None, 1));
+        assert_eq!(recv(&software).unwrap(), b"garbage");
+
+        apparatus.send_to(b"|EFP1.1|INFO|17|fm-eq|%|", to).unwrap();
+        assert_eq!(proxy.relay_one().unwrap().forwarded, 2);
+        assert_eq!(recv(&overlay).unwrap(), b"|EFP1.1|INFO|17|fm-eq|%|");
+
+        software.send_to(b"|EFP1.1|ACK|17|fm-eq|%|", to).unwrap();
+        assert_eq!(proxy.relay_one().unwrap().forwarded, 1);
+        assert_eq!(recv(&apparatus).unwrap(), b"|EFP1.1|ACK|17|fm-eq|%|");
+        overlay.send_to(b"|EFP1.1|ACK|17|fm-eq|%|", to).unwrap();
+        assert_eq!(proxy.relay_one().unwrap().forwarded, 0);
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+
+use super::error::RecordingError;
+use super::logfile::{Direction, LogEntry};
+use super::piste::PisteId;
+use super::utils::frame_piste;
+
+/// Bytes starting every recording.
+const MAGIC: &[u8; 7] = b"CYRREC\0";
+
+/// Version of the recording format written by this crate.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Bytes ending a recording whose index was written.
+const INDEX_MAGIC: &[u8; 8] = b"CYRIDX\0\x01";
+
+/// Length of the header: magic bytes and version.
+const HEADER_LEN: u64 = 8;
+
+/// Length of a record header: timestamp, direction and frame length.
+const RECORD_HEADER_LEN: usize = 13;
+
+const MINUTE_MS: u64 = 60_000;
+
+/// Index of a recording: for every minute, the offset of the first record of
+/// each piste seen during that minute, and of the first record overall.
+type Index = BTreeMap<u64, MinuteIndex>;
+
+#[derive(Debug, Clone, Default)]
+struct MinuteIndex {
+    first: u64,
+    pistes: BTreeMap<PisteId, u64>,
+}
+
+/// Writes a binary recording of exchanged frames.
+///
+/// A recording is a header followed by length-prefixed records, each holding
+/// the timestamp, direction and raw frame of a [`LogEntry`]. [`finish`](RecordingWriter::finish)
+/// appends an index of the first record of every minute and piste, which lets
+/// [`Recording::seek`] jump straight into an hours-long capture. Entries must
+/// be written in timestamp order.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use cyrano::fixtures;
+/// use cyrano::recording::{Recording, RecordingWriter};
+///
+/// let mut writer = RecordingWriter::new(Vec::new())?;
+/// for entry in fixtures::day_log(0, 8, 4) {
+///     writer.write(&entry)?;
+/// }
+/// let bytes = writer.finish()?;
+///
+/// // Jump to piste 7 at 14 minutes.
+/// let mut recording = Recording::open(Cursor::new(bytes))?;
+/// recording.seek(14 * 60_000, Some("7"))?;
+/// let entry = recording.next().unwrap()?;
+/// assert!(entry.raw.contains("|INFO|7|"));
+/// assert!(entry.timestamp_ms >= 14 * 60_000);
+/// # Ok::<(), cyrano::error::RecordingError>(())
+/// ```
+pub struct RecordingWriter<W: Write> {
+    writer: W,
+    offset: u64,
+    index: Index,
+}
+
+impl<W: Write> RecordingWriter<W> {
+    /// Starts a recording by writing its header.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the writer.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[FORMAT_VERSION])?;
+        Ok(RecordingWriter {
+            writer,
+            offset: HEADER_LEN,
+            index: Index::new(),
+        })
+    }
+
+    /// Appends an entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the writer, or `ErrorKind::InvalidInput` if
+    /// the frame is longer than 4 GiB.
+    pub fn write(&mut self, entry: &LogEntry) -> io::Result<()> {
+        let len = u32::try_from(entry.raw.len()).map_err(|_| io::Error::from(ErrorKind::InvalidInput))?;
+        let direction = match entry.direction {
+            Direction::Received => 0u8,
+            Direction::Sent => 1u8,
+        };
+
+        self.writer.write_all(&entry.timestamp_ms.to_le_bytes())?;
+        self.writer.write_all(&[direction])?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(entry.raw.as_bytes())?;
+
+        let minute = self.index.entry(entry.timestamp_ms / MINUTE_MS).or_insert_with(|| MinuteIndex {
+            first: self.offset,
+            pistes: BTreeMap::new(),
+        });
+        if let Some(piste) = frame_piste(&entry.raw) {
+            minute.pistes.entry(piste).or_insert(self.offset);
+        }
+        self.offset += (RECORD_HEADER_LEN + entry.raw.len()) as u64;
+        Ok(())
+    }
+
+    /// Writes the index and returns the underlying writer.
+    ///
+    /// A recording that was never finished, e.g. after a crash, can still be
+    /// read: its index is rebuilt by scanning it when it is opened.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let index_offset = self.offset;
+        self.writer.write_all(&(self.index.len() as u64).to_le_bytes())?;
+        for (minute, entries) in &self.index {
+            self.writer.write_all(&minute.to_le_bytes())?;
+            self.writer.write_all(&entries.first.to_le_bytes())?;
+            self.writer.write_all(&(entries.pistes.len() as u32).to_le_bytes())?;
+            for (piste, offset) in &entries.pistes {
+                let name = piste.as_str().as_bytes();
+                self.writer.write_all(&[name.len().min(255) as u8])?;
+                self.writer.write_all(&name[..name.len().min(255)])?;
+                self.writer.write_all(&offset.to_le_bytes())?;
+            }
+        }
+        self.writer.write_all(&index_offset.to_le_bytes())?;
+        self.writer.write_all(INDEX_MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads a binary recording written by [`RecordingWriter`].
+///
+/// The recording is an iterator over its entries, from the position set by
+/// [`seek`](Recording::seek) or from the start.
+pub struct Recording<R: Read + Seek> {
+    reader: R,
+    index: Index,
+    position: u64,
+    end: u64,
+}
+
+impl<R: Read + Seek> Recording<R> {
+    /// Opens a recording, reading its index or rebuilding it if the
+    /// recording was not finished.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RecordingError` if the data is not a recording of a supported
+    /// version, or if a record is corrupt while rebuilding the index.
+    pub fn open(mut reader: R) -> Result<Self, RecordingError> {
+        let mut header = [0u8; HEADER_LEN as usize];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut header).map_err(|_| RecordingError::NotARecording)?;
+        if &header[..MAGIC.len()] != MAGIC {
+            return Err(RecordingError::NotARecording);
+        }
+        if header[MAGIC.len()] != FORMAT_VERSION {
+            return Err(RecordingError::UnsupportedVersion(header[MAGIC.len()]));
+        }
+
+        let len = reader.seek(SeekFrom::End(0))?;
+        let mut recording = Recording {
+            reader,
+            index: Index::new(),
+            position: HEADER_LEN,
+            end: len,
+        };
+        if !recording.read_index()? {
+            recording.rebuild_index()?;
+        }
+        recording.reader.seek(SeekFrom::Start(HEADER_LEN))?;
+        Ok(recording)
+    }
+
+    /// Moves to the first record of the minute of `timestamp_ms`, or of the
+    /// first later minute with records, restricted to `piste` if given.
+    ///
+    /// Entries before `timestamp_ms` within that minute are still returned;
+    /// callers skip them, along with the entries of other pistes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the reader.
+    pub fn seek(&mut self, timestamp_ms: u64, piste: Option<&str>) -> Result<(), RecordingError> {
+        let piste = piste.map(PisteId::from);
+        let offset = self
+            .index
+            .range(timestamp_ms / MINUTE_MS..)
+            .find_map(|(_, minute)| match &piste {
+                Some(piste) => minute.pistes.get(piste).copied(),
+                None => Some(minute.first),
+            })
+            .unwrap_or(self.end);
+
+        self.position = offset;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        Ok(())
+    }
+
+    /// Returns the pistes recorded during the minute of `timestamp_ms`.
+    pub fn pistes_at(&self, timestamp_ms: u64) -> Vec<&PisteId> {
+        self.index
+            .get(&(timestamp_ms / MINUTE_MS))
+            .map(|minute| minute.pistes.keys().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the first and last minutes of the recording, in milliseconds.
+    pub fn span_ms(&self) -> Option<(u64, u64)> {
+        let first = self.index.keys().next()?;
+        let last = self.index.keys().next_back()?;
+        Some((first * MINUTE_MS, last * MINUTE_MS + MINUTE_MS - 1))
+    }
+
+    fn read_record(&mut self) -> Result<Option<LogEntry>, RecordingError> {
+        if self.position >= self.end {
+            return Ok(None);
+        }
+        let offset = self.position;
+        let corrupt = |_| RecordingError::CorruptRecord { offset };
+
+        let mut header = [0u8; RECORD_HEADER_LEN];
+        self.reader.read_exact(&mut header).map_err(corrupt)?;
+        let timestamp_ms = u64::from_le_bytes(header[..8].try_into().unwrap_or_default());
+        let direction = match header[8] {
+            0 => Direction::Received,
+            1 => Direction::Sent,
+            _ => return Err(RecordingError::CorruptRecord { offset }),
+        };
+        let len = u32::from_le_bytes(header[9..].try_into().unwrap_or_default()) as usize;
+        // Checked before allocating, so that a damaged length cannot claim gigabytes.
+        if len as u64 > self.end.saturating_sub(offset + RECORD_HEADER_LEN as u64) {
+            return Err(RecordingError::CorruptRecord { offset });
+        }
+
+        let mut raw = vec![0u8; len];
+        self.reader.read_exact(&mut raw).map_err(corrupt)?;
+        self.position += (RECORD_HEADER_LEN + len) as u64;
+
+        Ok(Some(LogEntry {
+            timestamp_ms,
+            direction,
+            raw: String::from_utf8_lossy(&raw).into_owned(),
+        }))
+    }
+
+    /// Reads the index written by [`RecordingWriter::finish`], returning
+    /// `false` if there is none.
+    fn read_index(&mut self) -> Result<bool, RecordingError> {
+        let trailer_len = (8 + INDEX_MAGIC.len()) as u64;
+        if self.end < HEADER_LEN + trailer_len {
+            return Ok(false);
+        }
+        let mut trailer = [0u8; 16];
+        self.reader.seek(SeekFrom::Start(self.end - trailer_len))?;
+        self.reader.read_exact(&mut trailer)?;
+        if &trailer[8..] != INDEX_MAGIC {
+            return Ok(false);
+        }
+        let index_offset = u64::from_le_bytes(trailer[..8].try_into().unwrap_or_default());
+        if index_offset < HEADER_LEN || index_offset > self.end - trailer_len {
+            return Ok(false);
+        }
+
+        self.reader.seek(SeekFrom::Start(index_offset))?;
+        let corrupt = |_| RecordingError::CorruptRecord { offset: index_offset };
+        let minutes = read_u64(&mut self.reader).map_err(corrupt)?;
+        for _ in 0..minutes {
+            let minute = read_u64(&mut self.reader).map_err(corrupt)?;
+            let first = read_u64(&mut self.reader).map_err(corrupt)?;
+            let mut count = [0u8; 4];
+            self.reader.read_exact(&mut count).map_err(corrupt)?;
+
+            let mut pistes = BTreeMap::new();
+            for _ in 0..u32::from_le_bytes(count) {
+                let mut len = [0u8; 1];
+                self.reader.read_exact(&mut len).map_err(corrupt)?;
+                let mut name = vec![0u8; len[0] as usize];
+                self.reader.read_exact(&mut name).map_err(corrupt)?;
+                let offset = read_u64(&mut self.reader).map_err(corrupt)?;
+                pistes.insert(PisteId::new(String::from_utf8_lossy(&name)), offset);
+            }
+            self.index.insert(minute, MinuteIndex { first, pistes });
+        }
+
+        self.end = index_offset;
+        Ok(true)
+    }
+
+    fn rebuild_index(&mut self) -> Result<(), RecordingError> {
+        self.reader.seek(SeekFrom::Start(HEADER_LEN))?;
+        self.position = HEADER_LEN;
+        loop {
+            let offset = self.position;
+            let entry = match self.read_record() {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                // A record cut short by a crash ends the recording.
+                Err(RecordingError::CorruptRecord { .. }) if offset > HEADER_LEN => {
+                    self.end = offset;
+                    break;
+                }
+                Err(err) => return Err(err),
+            };
+            let minute = self.index.entry(entry.timestamp_ms / MINUTE_MS).or_insert_with(|| MinuteIndex {
+                first: offset,
+                pistes: BTreeMap::new(),
+            });
+            if let Some(piste) = frame_piste(&entry.raw) {
+                minute.pistes.entry(piste).or_insert(offset);
+            }
+        }
+        self.position = HEADER_LEN;
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Iterator for Recording<R> {
+    type Item = Result<LogEntry, RecordingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_seek_with_and_without_index() {
+        let log = fixtures::day_log(0, 6, 3);
+        let mut writer = RecordingWriter::new(Vec::new()).unwrap();
+        for entry in &log {
+            writer.write(entry).unwrap();
+        }
+        let unfinished_len = writer.offset as usize;
+        let bytes = writer.finish().unwrap();
+
+        // Truncating the index and half of the last record must not lose the rest.
+        for data in [bytes.clone(), bytes[..unfinished_len - 5].to_vec()] {
+            let mut recording = Recording::open(Cursor::new(data.clone())).unwrap();
+            let entries: Vec<LogEntry> = recording.by_ref().map(Result::unwrap).collect();
+            let complete = if data.len() == bytes.len() { log.len() } else { log.len() - 1 };
+            assert_eq!(entries, log[..complete]);
+
+            let target = log[log.len() / 2].timestamp_ms;
+            recording.seek(target, Some("4")).unwrap();
+            let first = recording.next().unwrap().unwrap();
+            assert_eq!(frame_piste(&first.raw), Some(PisteId::from("4")));
+            assert!(first.timestamp_ms / MINUTE_MS >= target / MINUTE_MS);
+            assert!(!recording.pistes_at(target).is_empty());
+
+            recording.seek(u64::MAX, None).unwrap();
+            assert!(recording.next().is_none());
+        }
+
+        assert!(matches!(Recording::open(Cursor::new(b"not a recording".to_vec())), Err(RecordingError::NotARecording)));
+    }
+
+    #[test]
+    fn test_damaged_length_is_corrupt() {
+        let log = fixtures::day_log(0, 1, 2);
+        let mut writer = RecordingWriter::new(Vec::new()).unwrap();
+        for entry in &log {
+            writer.write(entry).unwrap();
+        }
+        let mut bytes = writer.finish().unwrap();
+        let second = HEADER_LEN as usize + RECORD_HEADER_LEN + log[0].raw.len();
+        bytes[second + 9..second + RECORD_HEADER_LEN].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let mut recording = Recording::open(Cursor::new(bytes)).unwrap();
+        assert_eq!(recording.next().unwrap().unwrap(), log[0]);
+        assert!(matches!(
+            recording.next(),
+            Some(Err(RecordingError::CorruptRecord { offset })) if offset == second as u64
+        ));
+    }
+}
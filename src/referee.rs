@@ -1,13 +1,320 @@
+use std::collections::HashMap;
+
+use super::error::ValueError;
+use super::limits::{check_optional, sanitize_optional, MAX_ID_LENGTH, MAX_NAME_LENGTH, MAX_NATION_LENGTH};
+use super::message::{FieldString, Message};
+use super::utils::{canonical_code, canonical_text};
+
 /// Information about the referee officiating a fencing match.
 ///
 /// Contains identifying information about the referee including their ID,
 /// name, and national affiliation.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Referee {
     /// Unique identifier for the referee.
-    pub id: Option<String>,
+    pub id: Option<FieldString>,
     /// Full name of the referee.
-    pub name: Option<String>,
+    pub name: Option<FieldString>,
     /// Three-letter country code of the referee's nation (e.g., "FRA", "USA").
-    pub nation: Option<String>,
+    pub nation: Option<FieldString>,
+    /// Sanctions recorded by the referee against officials, when the
+    /// apparatus sends them.
+    pub sanctions: Option<Sanctions>,
+}
+
+/// Cards given by the referee to officials of the bout, such as team captains
+/// and coaches, rather than to the fencers.
+///
+/// Extended apparatus send them after the referee nation, in general fields
+/// 18 to 20 of EFP1.1 frames; they are only read with
+/// [`VendorProfile::Extended`](crate::options::VendorProfile::Extended) and
+/// only written in EFP1.1 frames.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::message::Message;
+/// use cyrano::options::{ParseOptions, VendorProfile};
+///
+/// let raw = "|EFP1.1|INFO|17|fm-eq|1|A8|2|||||E||H|7|J. Doe|GER|1|0|0|%|%|%|";
+/// let options = ParseOptions { profile: VendorProfile::Extended, ..ParseOptions::default() };
+/// let message = Message::parse_with(raw, &options).unwrap();
+///
+/// let sanctions = message.referee.sanctions.unwrap();
+/// assert_eq!(sanctions.yellow_card, Some(1));
+/// assert!(message.to_string().starts_with("|EFP1.1|INFO|17|fm-eq|1|A8|2|||||E||H|7|J. Doe|GER|1|0|0|%|"));
+/// assert_eq!(Message::parse_with(raw, &ParseOptions::default()).unwrap().referee.sanctions, None);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sanctions {
+    /// Yellow cards (warnings) given to officials.
+    pub yellow_card: Option<u8>,
+    /// Red cards (penalty touches) given to officials.
+    pub red_card: Option<u8>,
+    /// Black cards (exclusions) given to officials.
+    pub black_card: Option<u8>,
+}
+
+impl Referee {
+    /// Checks the text fields against the specification limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ValueError` for the first field containing `|` or `%`, or
+    /// exceeding its maximum length.
+    pub fn validate(&self) -> Result<(), ValueError> {
+        check_optional("referee.id", &self.id, MAX_ID_LENGTH)?;
+        check_optional("referee.name", &self.name, MAX_NAME_LENGTH)?;
+        check_optional("referee.nation", &self.nation, MAX_NATION_LENGTH)
+    }
+
+    /// Returns the referee with trimmed id and name and an uppercase nation code.
+    pub fn canonicalize(&self) -> Referee {
+        Referee {
+            id: canonical_text(&self.id),
+            name: canonical_text(&self.name),
+            nation: canonical_code(&self.nation),
+            sanctions: self.sanctions,
+        }
+    }
+
+    /// Replaces forbidden characters and truncates the text fields to their maximum length.
+    pub fn sanitize(&mut self) {
+        sanitize_optional(&mut self.id, MAX_ID_LENGTH);
+        sanitize_optional(&mut self.name, MAX_NAME_LENGTH);
+        sanitize_optional(&mut self.nation, MAX_NATION_LENGTH);
+    }
+
+    /// Returns the value identifying the referee: the id, or the name when no id is set.
+    pub fn key(&self) -> Option<&str> {
+        self.id
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .or_else(|| self.name.as_deref().filter(|s| !s.is_empty()))
+    }
+}
+
+/// A bout officiated by a referee, as recorded by [`RefereeHistory`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefereeAssignment {
+    /// Referee id (or name when the id is not transmitted).
+    pub referee: String,
+    /// Competition identifier.
+    pub competition_id: String,
+    /// Piste the bout was fenced on.
+    pub piste: String,
+    /// Competition phase number.
+    pub phase: Option<u8>,
+    /// Pool or tableau identifier.
+    pub pool_tableau: Option<String>,
+    /// Match number within the competition.
+    pub match_number: Option<u8>,
+    /// Round number.
+    pub round: Option<u8>,
+}
+
+impl RefereeAssignment {
+    fn same_bout(&self, other: &RefereeAssignment) -> bool {
+        self.competition_id == other.competition_id
+            && self.piste == other.piste
+            && self.phase == other.phase
+            && self.pool_tableau == other.pool_tableau
+            && self.match_number == other.match_number
+            && self.round == other.round
+    }
+}
+
+/// A referee assigned to unfinished bouts on two pistes at the same time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefereeConflict {
+    /// Referee id (or name when the id is not transmitted).
+    pub referee: String,
+    /// The bout the referee was already officiating.
+    pub current: RefereeAssignment,
+    /// The simultaneous bout on another piste.
+    pub conflicting: RefereeAssignment,
+}
+
+/// History of which referee officiated which bouts over a session.
+///
+/// Feed every received message to [`RefereeHistory::record`]; the referee fields
+/// of bout messages (INFO, DISP, NEXT, PREV) are used to build the history.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::referee::RefereeHistory;
+///
+/// let mut history = RefereeHistory::new();
+/// history.record(&Message::try_from("|EFP1.1|NEXT|1|fm-eq|1|P1|1||||||||132|J.Smith|GBR|%|").unwrap());
+/// let conflict = history.record(&Message::try_from("|EFP1.1|NEXT|2|fm-eq|1|P2|4||||||||132|J.Smith|GBR|%|").unwrap());
+///
+/// assert!(conflict.is_some());
+/// assert_eq!(history.bout_count("132"), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RefereeHistory {
+    assignments: Vec<RefereeAssignment>,
+    current: HashMap<String, (RefereeAssignment, bool)>,
+    conflicts: Vec<RefereeConflict>,
+}
+
+impl RefereeHistory {
+    /// Creates an empty history.
+    pub fn new() -> Self {
+        RefereeHistory::default()
+    }
+
+    /// Records the referee of a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - A message received or sent on any piste
+    ///
+    /// # Returns
+    ///
+    /// `Some(RefereeConflict)` if the message assigns a referee who is already
+    /// officiating an unfinished bout on another piste, `None` otherwise.
+    pub fn record(&mut self, message: &Message) -> Option<RefereeConflict> {
+        if !message.carries_bout() {
+            return None;
+        }
+
+        let finished = [&message.right_fencer, &message.left_fencer]
+            .iter()
+            .any(|f| f.status.as_ref().is_some_and(|s| s.is_final()));
+
+        let referee = match message.referee.key() {
+            Some(referee) => referee.to_string(),
+            None => {
+                // The piste moved on without a referee: it no longer holds anyone.
+                self.current.remove(message.piste.as_str());
+                return None;
+            }
+        };
+
+        let assignment = RefereeAssignment {
+            referee: referee.clone(),
+            competition_id: message.competition_id.to_string(),
+            piste: message.piste.to_string(),
+            phase: message.phase,
+            pool_tableau: message.pool_tableau.as_deref().map(String::from),
+            match_number: message.match_number,
+            round: message.round,
+        };
+
+        if !self.assignments.iter().any(|a| a.referee == referee && a.same_bout(&assignment)) {
+            self.assignments.push(assignment.clone());
+        }
+
+        let conflict = self
+            .current
+            .values()
+            .find(|(current, done)| {
+                !done && current.referee == referee && current.piste != assignment.piste
+            })
+            .map(|(current, _)| RefereeConflict {
+                referee: referee.clone(),
+                current: current.clone(),
+                conflicting: assignment.clone(),
+            });
+
+        self.current
+            .insert(message.piste.to_string(), (assignment, finished));
+
+        if let Some(conflict) = &conflict {
+            self.conflicts.push(conflict.clone());
+        }
+        conflict
+    }
+
+    /// Returns every recorded assignment, in the order they were first seen.
+    pub fn assignments(&self) -> &[RefereeAssignment] {
+        &self.assignments
+    }
+
+    /// Returns the assignments of a single referee.
+    pub fn assignments_of<'a>(&'a self, referee: &'a str) -> impl Iterator<Item = &'a RefereeAssignment> {
+        self.assignments.iter().filter(move |a| a.referee == referee)
+    }
+
+    /// Returns the number of distinct bouts officiated by a referee.
+    pub fn bout_count(&self, referee: &str) -> usize {
+        self.assignments_of(referee).count()
+    }
+
+    /// Returns the number of distinct bouts officiated by every referee.
+    pub fn bout_counts(&self) -> HashMap<&str, usize> {
+        let mut counts = HashMap::new();
+        for assignment in &self.assignments {
+            *counts.entry(assignment.referee.as_str()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Returns every conflict detected so far.
+    pub fn conflicts(&self) -> &[RefereeConflict] {
+        &self.conflicts
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn info(piste: &str, match_number: u8, referee: &str) -> Message {
+        let raw = format!(
+            "|EFP1.1|INFO|{}|fm-eq|1|P1|{}|||||E||F|{}|%|28|P.Martin|FRA|0|U|%|32|B. Panini|ITA|0|U|%|",
+            piste, match_number, referee
+        );
+        Message::try_from(raw).unwrap()
+    }
+
+    #[test]
+    fn test_referee_change_mid_bout() {
+        let mut history = RefereeHistory::new();
+        assert!(history.record(&info("1", 4, "132|J.Smith|GBR")).is_none());
+        assert!(history.record(&info("1", 4, "140|K.Muller|GER")).is_none());
+
+        let referees: Vec<&str> = history.assignments().iter().map(|a| a.referee.as_str()).collect();
+        assert_eq!(referees, ["132", "140"]);
+        assert_eq!(history.assignments()[0].match_number, history.assignments()[1].match_number);
+
+        // The replaced referee no longer holds piste 1.
+        assert!(history.record(&info("2", 7, "132|J.Smith|GBR")).is_none());
+        assert!(history.record(&info("3", 9, "140|K.Muller|GER")).is_some());
+    }
+
+    #[test]
+    fn test_repeated_referee_recorded_once() {
+        let mut history = RefereeHistory::new();
+        for _ in 0..3 {
+            assert!(history.record(&info("1", 4, "132|J.Smith|GBR")).is_none());
+        }
+
+        assert_eq!(history.assignments().len(), 1);
+        assert_eq!(history.bout_count("132"), 1);
+        assert!(history.conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_empty_referee_zone() {
+        let mut history = RefereeHistory::new();
+        assert!(history.record(&info("1", 4, "||")).is_none());
+        assert!(history.assignments().is_empty());
+        assert!(history.bout_counts().is_empty());
+
+        // A bout without referee frees the piste of the previous one.
+        history.record(&info("1", 4, "132|J.Smith|GBR"));
+        history.record(&info("1", 5, "||"));
+        assert!(history.record(&info("2", 7, "132|J.Smith|GBR")).is_none());
+        assert_eq!(history.bout_count("132"), 2);
+    }
 }
@@ -0,0 +1,199 @@
+use crate::enums::{Command, PCard, Side};
+use crate::error::SessionError;
+use crate::message::Message;
+use crate::session::{ProtocolSession, SendHandle};
+use crate::utils::field;
+
+/// A change the software asks the apparatus to make, sent as a DISP.
+pub trait RemoteCommand {
+    /// Applies the change to the bout data sent to the apparatus.
+    fn apply_to(&self, message: &mut Message);
+}
+
+/// Sets the score of one fencer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetScore {
+    /// Fencer whose score is set.
+    pub side: Side,
+    /// New score.
+    pub score: u8,
+}
+
+impl RemoteCommand for SetScore {
+    fn apply_to(&self, message: &mut Message) {
+        message.fencer_mut(self.side).score = Some(self.score);
+    }
+}
+
+/// Sets the match clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetTime {
+    /// Remaining time, in seconds; sent as `m:ss`.
+    pub seconds: u32,
+}
+
+impl RemoteCommand for SetTime {
+    fn apply_to(&self, message: &mut Message) {
+        message.time = Some(field(format!("{}:{:02}", self.seconds / 60, self.seconds % 60)));
+    }
+}
+
+/// Card given by the referee.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Card {
+    /// Yellow card (warning).
+    Yellow,
+    /// Red card, scoring a penalty touch for the opponent.
+    Red,
+    /// Non-combativity P-card, replacing the previous one.
+    PCard(PCard),
+}
+
+/// Gives a card to one fencer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignCard {
+    /// Fencer receiving the card.
+    pub side: Side,
+    /// Card given.
+    pub card: Card,
+}
+
+impl RemoteCommand for AssignCard {
+    fn apply_to(&self, message: &mut Message) {
+        let fencer = message.fencer_mut(self.side);
+        match &self.card {
+            Card::Yellow => fencer.yellow_card = Some(1),
+            Card::Red => {
+                fencer.red_card = Some(fencer.red_card.unwrap_or(0).saturating_add(1));
+                let opponent = message.fencer_mut(self.side.opponent());
+                opponent.score = Some(opponent.score.unwrap_or(0).saturating_add(1));
+            }
+            Card::PCard(p_card) => fencer.p_card = Some(p_card.clone()),
+        }
+    }
+}
+
+/// Composer of the DISP frames remotely setting score, time and cards on an
+/// apparatus that accepts them.
+///
+/// A DISP carries the whole bout, so each command is applied to the last bout
+/// data known: the INFO frames received from the apparatus, fed to
+/// [`observe`](RemoteControl::observe), and the commands sent since.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::{Command, Side};
+/// use cyrano::message::Message;
+/// use cyrano::remote::{AssignCard, Card, RemoteControl, SetScore};
+/// use cyrano::session::{ProtocolSession, Role, SendOutcome};
+///
+/// let mut session = ProtocolSession::new(Role::Software, "17");
+/// let mut remote = RemoteControl::new("17", "fm-eq");
+/// remote.observe(&Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12||2:12|||E||H|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+///
+/// let (disp, handle) = remote.send(&mut session, &SetScore { side: Side::Left, score: 3 }, 1_000).unwrap();
+/// assert_eq!(disp.command, Command::Disp);
+/// assert_eq!((disp.right_fencer.score, disp.left_fencer.score), (Some(4), Some(3)));
+///
+/// session.handle_incoming(&Message::try_from("|EFP1.1|ACK|17|fm-eq|%|").unwrap());
+/// assert_eq!(session.outcome(handle.unwrap()), Some(SendOutcome::Acked));
+///
+/// let disp = remote.compose(&AssignCard { side: Side::Right, card: Card::Red });
+/// assert_eq!((disp.right_fencer.red_card, disp.left_fencer.score), (Some(1), Some(4)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RemoteControl {
+    bout: Message,
+}
+
+impl RemoteControl {
+    /// Creates a composer for a piste, with no bout data yet.
+    pub fn new(piste: &str, competition_id: &str) -> Self {
+        RemoteControl {
+            bout: Message::new(Command::Disp, piste, competition_id),
+        }
+    }
+
+    /// Takes the bout data of a message received from the apparatus.
+    ///
+    /// Messages of other pistes and messages without bout data, such as ACK
+    /// and HELLO, are ignored.
+    pub fn observe(&mut self, message: &Message) {
+        if message.piste == self.bout.piste && matches!(message.command, Command::Info | Command::Disp) {
+            self.bout = message.clone();
+            self.bout.command = Command::Disp;
+        }
+    }
+
+    /// Returns the bout data the next command applies to.
+    pub fn bout(&self) -> &Message {
+        &self.bout
+    }
+
+    /// Returns the DISP applying a command, without sending it.
+    pub fn compose(&self, command: &impl RemoteCommand) -> Message {
+        let mut message = self.bout.clone();
+        command.apply_to(&mut message);
+        message
+    }
+
+    /// Composes the DISP applying a command and registers it with the
+    /// session, to track the apparatus acknowledgment.
+    ///
+    /// The composed data is kept as the bout data of the next commands until
+    /// the apparatus sends an INFO.
+    ///
+    /// # Returns
+    ///
+    /// The DISP to transmit and the handle of its acknowledgment.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SessionError::ForbiddenCommand` if the session is not on the
+    /// software side.
+    pub fn send(
+        &mut self,
+        session: &mut ProtocolSession,
+        command: &impl RemoteCommand,
+        now_ms: u64,
+    ) -> Result<(Message, Option<SendHandle>), SessionError> {
+        let message = self.compose(command);
+        let handle = session.send(&message, now_ms)?;
+        self.bout = message.clone();
+        Ok((message, handle))
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Role;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_commands_build_on_each_other() {
+        let mut session = ProtocolSession::new(Role::Software, "3");
+        let mut remote = RemoteControl::new("3", "fm-eq");
+        remote.observe(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||F||H|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+        remote.observe(&Message::try_from("|EFP1.1|INFO|4|fm-eq|1|P3|1||0:10|||F||H|%|%|%|").unwrap());
+
+        remote.send(&mut session, &SetTime { seconds: 65 }, 0).unwrap();
+        remote.send(&mut session, &AssignCard { side: Side::Left, card: Card::Yellow }, 10).unwrap();
+        let (disp, _) = remote
+            .send(&mut session, &AssignCard { side: Side::Left, card: Card::PCard(PCard::OneRed) }, 20)
+            .unwrap();
+        assert_eq!(disp.time.as_deref(), Some("1:05"));
+        assert_eq!(disp.left_fencer.yellow_card, Some(1));
+        assert_eq!(disp.left_fencer.p_card, Some(PCard::OneRed));
+        assert!(disp.to_string().starts_with("|EFP1.1|DISP|3|fm-eq|1|P3|1||1:05|"));
+        assert_eq!(session.awaiting(), 3);
+
+        let mut machine = ProtocolSession::new(Role::Machine, "3");
+        assert!(remote.send(&mut machine, &SetScore { side: Side::Right, score: 5 }, 30).is_err());
+        assert_eq!(remote.bout().right_fencer.score, Some(4));
+    }
+}
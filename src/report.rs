@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use crate::logfile::LogEntry;
+use crate::piste::PisteId;
+use crate::state::{MatchEvent, PisteManager};
+
+/// Default length of the periods ranked by traffic, in milliseconds.
+pub const DEFAULT_PERIOD_MS: u64 = 60_000;
+
+/// Default number of busiest periods and longest silences kept.
+pub const DEFAULT_TOP: usize = 5;
+
+/// A stretch of time during which a piste sent nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Silence {
+    /// Time of the last frame before the silence, in milliseconds.
+    pub from_ms: u64,
+    /// Time of the first frame after the silence, in milliseconds.
+    pub to_ms: u64,
+}
+
+impl Silence {
+    /// Returns the length of the silence in milliseconds.
+    pub fn duration_ms(&self) -> u64 {
+        self.to_ms - self.from_ms
+    }
+}
+
+/// A period of the capture and the number of frames seen during it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Period {
+    /// Start of the period, in milliseconds.
+    pub start_ms: u64,
+    /// Frames seen during the period, all pistes included.
+    pub frames: u64,
+}
+
+/// What a capture holds for one piste.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PisteReport {
+    /// Bouts loaded on the piste.
+    pub bouts: u64,
+    /// Frames that parse, per command code.
+    pub commands: BTreeMap<String, u64>,
+    /// Frames that do not parse.
+    pub parse_errors: u64,
+    /// Longest silences of the piste, longest first.
+    pub longest_silences: Vec<Silence>,
+    last_seen_ms: Option<u64>,
+}
+
+impl PisteReport {
+    /// Returns the number of frames that parse.
+    pub fn frames(&self) -> u64 {
+        self.commands.values().sum()
+    }
+}
+
+/// Health report of a capture, for a quick look after an event.
+///
+/// The report is folded from the entries of a message log, in the order they
+/// were logged. Each piste gets its bout count, its frame count per command,
+/// its parse errors and its longest silences; the capture as a whole gets its
+/// busiest periods.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::fixtures;
+/// use cyrano::report::CaptureReport;
+///
+/// let report = CaptureReport::from_entries(&fixtures::day_log(0, 2, 3));
+///
+/// assert_eq!(report.piste("1").unwrap().bouts, 3);
+/// assert!(report.piste("2").unwrap().commands["INFO"] > 0);
+/// assert!(report.to_string().starts_with("piste 1: 3 bouts"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CaptureReport {
+    period_ms: u64,
+    top: usize,
+    pistes: BTreeMap<PisteId, PisteReport>,
+    periods: BTreeMap<u64, u64>,
+    unattributed_errors: u64,
+    bouts: PisteManager,
+}
+
+impl Default for CaptureReport {
+    fn default() -> Self {
+        CaptureReport::new()
+    }
+}
+
+impl CaptureReport {
+    /// Creates an empty report with the default period length and ranking size.
+    pub fn new() -> Self {
+        CaptureReport {
+            period_ms: DEFAULT_PERIOD_MS,
+            top: DEFAULT_TOP,
+            pistes: BTreeMap::new(),
+            periods: BTreeMap::new(),
+            unattributed_errors: 0,
+            bouts: PisteManager::new(),
+        }
+    }
+
+    /// Folds a whole log with the default settings.
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a LogEntry>) -> Self {
+        let mut report = CaptureReport::new();
+        for entry in entries {
+            report.observe(entry);
+        }
+        report
+    }
+
+    /// Sets the length of the periods ranked by traffic.
+    pub fn period_ms(mut self, period_ms: u64) -> Self {
+        self.period_ms = period_ms.max(1);
+        self
+    }
+
+    /// Sets how many busiest periods and longest silences are kept.
+    pub fn top(mut self, top: usize) -> Self {
+        self.top = top;
+        self
+    }
+
+    /// Adds a log entry to the report.
+    pub fn observe(&mut self, entry: &LogEntry) {
+        *self.periods.entry(entry.timestamp_ms / self.period_ms * self.period_ms).or_default() += 1;
+
+        let message = entry.message().ok();
+        let piste = match &message {
+            Some(message) => Some(message.piste.as_ref()),
+            None => entry.raw.split('|').nth(3).filter(|piste| !piste.is_empty()),
+        };
+        let Some(piste) = piste else {
+            self.unattributed_errors += 1;
+            return;
+        };
+
+        let top = self.top;
+        let report = self.pistes.entry(PisteId::from(piste)).or_default();
+        if let Some(last_ms) = report.last_seen_ms {
+            let silence = Silence {
+                from_ms: last_ms,
+                to_ms: entry.timestamp_ms.max(last_ms),
+            };
+            report.longest_silences.push(silence);
+            report.longest_silences.sort_by_key(|silence| std::cmp::Reverse(silence.duration_ms()));
+            report.longest_silences.truncate(top);
+        }
+        report.last_seen_ms = Some(entry.timestamp_ms);
+
+        match message {
+            Some(message) => {
+                *report.commands.entry(message.command.to_string()).or_default() += 1;
+                let events = self.bouts.apply(&message);
+                report.bouts += events.iter().filter(|event| **event == MatchEvent::BoutStarted).count() as u64;
+            }
+            None => report.parse_errors += 1,
+        }
+    }
+
+    /// Returns the report of a piste, or `None` if the capture holds nothing from it.
+    pub fn piste(&self, piste: &str) -> Option<&PisteReport> {
+        self.pistes.get(&PisteId::from(piste))
+    }
+
+    /// Returns the report of every piste, in natural piste order.
+    pub fn pistes(&self) -> impl Iterator<Item = (&PisteId, &PisteReport)> {
+        self.pistes.iter()
+    }
+
+    /// Returns the number of unparsable frames that named no piste.
+    pub fn unattributed_errors(&self) -> u64 {
+        self.unattributed_errors
+    }
+
+    /// Returns the periods with the most frames, busiest first.
+    pub fn busiest_periods(&self) -> Vec<Period> {
+        let mut periods: Vec<Period> = self
+            .periods
+            .iter()
+            .map(|(&start_ms, &frames)| Period { start_ms, frames })
+            .collect();
+        periods.sort_by_key(|period| std::cmp::Reverse(period.frames));
+        periods.truncate(self.top);
+        periods
+    }
+}
+
+impl Display for CaptureReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (piste, report) in &self.pistes {
+            writeln!(
+                f,
+                "piste {}: {} bouts, {} frames, {} parse errors",
+                piste, report.bouts, report.frames(), report.parse_errors
+            )?;
+            for (command, count) in &report.commands {
+                writeln!(f, "    {:<6} {}", command, count)?;
+            }
+            for silence in &report.longest_silences {
+                writeln!(
+                    f,
+                    "    silent {} ms from {} to {}",
+                    silence.duration_ms(),
+                    silence.from_ms,
+                    silence.to_ms
+                )?;
+            }
+        }
+        if self.unattributed_errors > 0 {
+            writeln!(f, "{} unparsable frames naming no piste", self.unattributed_errors)?;
+        }
+        writeln!(f, "busiest periods of {} ms:", self.period_ms)?;
+        for period in self.busiest_periods() {
+            writeln!(f, "    {} frames from {}", period.frames, period.start_ms)?;
+        }
+        Ok(())
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_report_of_a_day_log() {
+        let mut log = fixtures::day_log(0, 3, 2);
+        log.push(LogEntry::received(log.last().unwrap().timestamp_ms, "|EFP1.1|BOGUS|2|fm-eq|%|"));
+        log.push(LogEntry::received(log.last().unwrap().timestamp_ms, "garbage"));
+
+        let mut report = CaptureReport::new().top(2);
+        for entry in &log {
+            report.observe(entry);
+        }
+        let names: Vec<&str> = report.pistes().map(|(piste, _)| piste.as_str()).collect();
+        assert_eq!(names, ["1", "2", "3"]);
+        let piste = report.piste("2").unwrap();
+        assert_eq!((piste.bouts, piste.parse_errors), (2, 1));
+        assert_eq!(piste.longest_silences.len(), 2);
+        assert!(piste.longest_silences.windows(2).all(|pair| pair[0].duration_ms() >= pair[1].duration_ms()));
+        assert_eq!(report.unattributed_errors(), 1);
+
+        let busiest = report.busiest_periods();
+        assert_eq!(busiest.len(), 2);
+        assert!(busiest[0].frames >= busiest[1].frames);
+        let total: u64 = report.periods.values().sum();
+        assert_eq!(total, log.len() as u64);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::enums::{CompetitionType, FencerStatus, Priority, Side};
+use crate::message::Message;
+use crate::phase::{
+    Stage, BREAK_SECONDS, ELIMINATION_PERIODS, ELIMINATION_TARGET_SCORE, POOL_TARGET_SCORE, RELAY_TOUCHES, TEAM_RELAYS,
+};
+use crate::utils::Rng;
+
+/// How the fencers of an event move.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Discipline {
+    /// Standing fencing, on a piste.
+    #[default]
+    Standing,
+    /// Wheelchair fencing (IWAS), with the chairs fixed to a frame. Without
+    /// footwork, there are no sanctions for leaving the piste.
+    Wheelchair,
+}
+
+/// Bout rules of a category of competition: target scores, bout length and
+/// card rules.
+///
+/// The profile is given to a [`MatchState`](crate::state::MatchState) so that
+/// the same tracker follows club youth events and World Cup finals alike.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Side;
+/// use cyrano::message::Message;
+/// use cyrano::rules::RuleProfile;
+///
+/// let de = Message::try_from("|EFP1.1|INFO|17|fm-eq|2|A32|12|1|||I|E||H|%|28|P.Martin|FRA|10|U|%|32|B. Panini|ITA|7|U|%|").unwrap();
+/// assert_eq!(RuleProfile::FIE_SENIOR.target_score(&de), Some(15));
+/// assert_eq!(RuleProfile::FIE_SENIOR.winner(&de), None);
+/// assert_eq!(RuleProfile::VETERAN.winner(&de), Some(Side::Right));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleProfile {
+    /// Target score of a pool bout.
+    pub pool_target: u8,
+    /// Target score of a direct elimination bout.
+    pub elimination_target: u8,
+    /// Periods of a direct elimination bout. Pool bouts and team relays are
+    /// fenced in one period.
+    pub elimination_periods: u8,
+    /// Length of a period, in seconds.
+    pub period_seconds: u32,
+    /// Length of the break between two periods, in seconds.
+    pub break_seconds: u32,
+    /// Touches added to the target score by each relay of a team match.
+    pub relay_touches: u8,
+    /// Whether a bout tied at the end of time goes to an extra minute with
+    /// priority. Without it, both fencers lose a tied bout.
+    pub priority_minute: bool,
+    /// Whether non-combativity is penalized with P-cards.
+    pub p_cards: bool,
+    /// Standing or wheelchair fencing.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub discipline: Discipline,
+}
+
+impl RuleProfile {
+    /// FIE rules for senior and junior events: pools to 5 touches, direct
+    /// elimination to 15 in three periods of three minutes.
+    pub const FIE_SENIOR: RuleProfile = RuleProfile {
+        pool_target: POOL_TARGET_SCORE,
+        elimination_target: ELIMINATION_TARGET_SCORE,
+        elimination_periods: ELIMINATION_PERIODS,
+        period_seconds: 180,
+        break_seconds: BREAK_SECONDS,
+        relay_touches: RELAY_TOUCHES,
+        priority_minute: true,
+        p_cards: true,
+        discipline: Discipline::Standing,
+    };
+    /// Veteran events: direct elimination to 10 touches in two periods,
+    /// without P-cards.
+    pub const VETERAN: RuleProfile = RuleProfile {
+        elimination_target: 10,
+        elimination_periods: 2,
+        p_cards: false,
+        ..RuleProfile::FIE_SENIOR
+    };
+    /// Club youth events: pools to 4 touches, direct elimination to 10 in two
+    /// periods of two minutes, without P-cards.
+    pub const YOUTH: RuleProfile = RuleProfile {
+        pool_target: 4,
+        elimination_target: 10,
+        elimination_periods: 2,
+        period_seconds: 120,
+        p_cards: false,
+        ..RuleProfile::FIE_SENIOR
+    };
+    /// Modern pentathlon fencing: one-touch épée bouts of one minute, lost by
+    /// both fencers if no touch is scored.
+    pub const MODERN_PENTATHLON: RuleProfile = RuleProfile {
+        pool_target: 1,
+        elimination_target: 1,
+        elimination_periods: 1,
+        period_seconds: 60,
+        break_seconds: 0,
+        relay_touches: 1,
+        priority_minute: false,
+        p_cards: false,
+        discipline: Discipline::Standing,
+    };
+    /// Wheelchair (IWAS) events: FIE bout format, fenced seated.
+    pub const WHEELCHAIR: RuleProfile = RuleProfile {
+        discipline: Discipline::Wheelchair,
+        ..RuleProfile::FIE_SENIOR
+    };
+
+    /// Returns `true` if fencers can be sanctioned for their footwork, such as
+    /// a touch against a fencer crossing the rear limit of the piste.
+    pub fn footwork_sanctions(&self) -> bool {
+        self.discipline == Discipline::Standing
+    }
+
+    /// Returns the score that ends the bout, or relay for team matches
+    /// (a cumulative `relay_touches`, twice that, ...).
+    ///
+    /// Returns `None` when the stage or relay is unknown.
+    pub fn target_score(&self, message: &Message) -> Option<u8> {
+        match message.competition_type {
+            Some(CompetitionType::Team) => message.relay().map(|relay| relay * self.relay_touches),
+            _ => match message.stage()? {
+                Stage::Pool { .. } => Some(self.pool_target),
+                Stage::Tableau { .. } => Some(self.elimination_target),
+            },
+        }
+    }
+
+    /// Returns the number of periods of the bout, extra time not counted.
+    ///
+    /// Returns `None` when the stage is unknown.
+    pub fn periods(&self, message: &Message) -> Option<u8> {
+        match message.competition_type {
+            Some(CompetitionType::Team) => Some(1),
+            _ => match message.stage()? {
+                Stage::Pool { .. } => Some(1),
+                Stage::Tableau { .. } => Some(self.elimination_periods),
+            },
+        }
+    }
+
+    /// Returns the winner of a bout as far as a single message tells: the
+    /// fencer given the victory or whose opponent lost, or the fencer who
+    /// reached the target score (of the last relay in team matches).
+    ///
+    /// Bouts decided on time need the period being fenced; see
+    /// [`MatchState::winner`](crate::state::MatchState::winner).
+    pub fn winner(&self, message: &Message) -> Option<Side> {
+        let lost = |side: Side| {
+            matches!(
+                message.fencer(side).status,
+                Some(FencerStatus::Defeat | FencerStatus::Abandonment | FencerStatus::Exclusion)
+            )
+        };
+        if let Some(side) = [Side::Right, Side::Left]
+            .into_iter()
+            .find(|side| message.fencer(*side).status == Some(FencerStatus::Victory) || lost(side.opponent()))
+        {
+            return Some(side);
+        }
+
+        if message.competition_type == Some(CompetitionType::Team) && message.relay() != Some(TEAM_RELAYS) {
+            return None;
+        }
+        let target = self.target_score(message)?;
+        [Side::Right, Side::Left]
+            .into_iter()
+            .find(|side| message.fencer(*side).score.is_some_and(|score| score >= target))
+    }
+}
+
+impl Default for RuleProfile {
+    fn default() -> Self {
+        RuleProfile::FIE_SENIOR
+    }
+}
+
+/// Source of the random draws made on behalf of the referee.
+///
+/// Any generator can be plugged in through a closure returning `u64`; use
+/// [`SeededRandom`] when the draws must be replayed for an audit.
+pub trait RandomSource {
+    /// Returns the next random number.
+    fn next_u64(&mut self) -> u64;
+}
+
+impl<F: FnMut() -> u64> RandomSource for F {
+    fn next_u64(&mut self) -> u64 {
+        self()
+    }
+}
+
+/// Reproducible random source: logging its seed and the number of draws
+/// made is enough to replay every draw.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::rules::{assign_priority, SeededRandom};
+///
+/// let mut rng = SeededRandom::new(20260415);
+/// let drawn = assign_priority(&mut rng);
+///
+/// let mut replay = SeededRandom::new(rng.seed());
+/// assert_eq!(assign_priority(&mut replay), drawn);
+/// assert_eq!(replay.draws(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SeededRandom {
+    seed: u64,
+    draws: u64,
+    rng: Rng,
+}
+
+impl SeededRandom {
+    /// Creates a source from a seed.
+    pub fn new(seed: u64) -> Self {
+        SeededRandom {
+            seed,
+            draws: 0,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Returns the seed of the source.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the number of draws made so far.
+    pub fn draws(&self) -> u64 {
+        self.draws
+    }
+}
+
+impl RandomSource for SeededRandom {
+    fn next_u64(&mut self) -> u64 {
+        self.draws += 1;
+        self.rng.next_u64()
+    }
+}
+
+/// Why priority was given to a fencer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PriorityReason {
+    /// Scores were tied: the extra minute is fenced with priority to the
+    /// fencer drawn.
+    Tie,
+    /// Scores were not tied, as when the priority is set by hand or corrected.
+    Other,
+}
+
+/// Draws the fencer getting priority for the extra minute of a tied bout,
+/// as the referee's coin toss would.
+///
+/// Returns `Priority::Right` or `Priority::Left`, each with even odds.
+pub fn assign_priority(rng: &mut impl RandomSource) -> Priority {
+    if rng.next_u64().is_multiple_of(2) {
+        Priority::Right
+    } else {
+        Priority::Left
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_profiles_change_targets_and_winners() {
+        let pool = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|P3|4|1|||I|E||H|%|28|A|FRA|4|U|%|32|B|ITA|1|U|%|").unwrap();
+        assert_eq!(RuleProfile::FIE_SENIOR.winner(&pool), None);
+        assert_eq!(RuleProfile::YOUTH.winner(&pool), Some(Side::Right));
+        assert_eq!(RuleProfile::MODERN_PENTATHLON.target_score(&pool), Some(1));
+
+        let de = Message::try_from("|EFP1.1|INFO|17|fm-eq|2|A8|1|1|||I|E||H|%|28|A|FRA|6|U|%|32|B|ITA|7|U|%|").unwrap();
+        assert_eq!(RuleProfile::VETERAN.periods(&de), Some(2));
+        assert_eq!(RuleProfile::FIE_SENIOR.winner(&de), None);
+
+        let team = Message::try_from("|EFP1.1|INFO|2|ef-eq|4|T8|3|8|||T|E||F|%|FRA|France|FRA|40|U|%|ITA|Italie|ITA|38|U|%|").unwrap();
+        assert_eq!(RuleProfile::FIE_SENIOR.target_score(&team), Some(40));
+        assert_eq!(RuleProfile::FIE_SENIOR.winner(&team), None);
+
+        let abandoned = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|P3|4|1|||I|E||H|%|28|A|FRA|0|U|%|32|B|ITA|3|A|%|").unwrap();
+        assert_eq!(RuleProfile::FIE_SENIOR.winner(&abandoned), Some(Side::Right));
+    }
+
+    #[test]
+    fn test_priority_draws_are_even_and_replayable() {
+        let mut rng = SeededRandom::new(7);
+        let draws: Vec<Priority> = (0..1000).map(|_| assign_priority(&mut rng)).collect();
+        let right = draws.iter().filter(|p| **p == Priority::Right).count();
+        assert!((400..600).contains(&right), "{} right of 1000", right);
+        assert_eq!(rng.draws(), 1000);
+
+        let mut replay = SeededRandom::new(7);
+        assert!(draws.iter().all(|p| assign_priority(&mut replay) == *p));
+
+        let mut counter = 0u64;
+        let mut source = || {
+            counter += 1;
+            counter
+        };
+        assert_eq!(assign_priority(&mut source), Priority::Left);
+        assert_eq!(assign_priority(&mut source), Priority::Right);
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::assignment::MatchAssignment;
+use crate::enums::Command;
+use crate::error::ParseError;
+use crate::message::Message;
+use crate::piste::PisteId;
+use crate::state::MatchEvent;
+
+/// Queues of upcoming bouts, one per piste.
+///
+/// Assignments are queued in the order they should be fenced. Once the bout
+/// running on a piste reaches a final status, [`advance`](Schedule::advance)
+/// pops the next assignment of that piste and returns the NEXT message loading
+/// it on the apparatus.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Command;
+/// use cyrano::import::{import_assignments, ImportSource};
+/// use cyrano::message::Message;
+/// use cyrano::schedule::Schedule;
+/// use cyrano::state::PisteManager;
+///
+/// let export = "Piste;Phase;Poule;Match;Id droite;Nom droite;Id gauche;Nom gauche\n\
+///               17;1;P3;12;28;P.Martin;32;B. Panini\n\
+///               17;1;P3;13;28;P.Martin;45;J.Smith\n";
+///
+/// let mut schedule = Schedule::new();
+/// for assignment in import_assignments(export, ImportSource::Engarde, "fm-eq").unwrap() {
+///     schedule.push(assignment).unwrap();
+/// }
+///
+/// // Load the first bout, then wait for it to finish.
+/// let first = schedule.next_message("17").unwrap();
+/// assert_eq!(first.match_number, Some(12));
+///
+/// let mut pistes = PisteManager::new();
+/// let end = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|P3|12|||||E||E|%|28|P.Martin||5|V|%|32|B. Panini||3|D|%|").unwrap();
+/// let events = pistes.apply(&end);
+///
+/// let next = schedule.advance("17", &events).unwrap();
+/// assert_eq!(next.command, Command::Next);
+/// assert_eq!(next.match_number, Some(13));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Schedule {
+    queues: BTreeMap<PisteId, VecDeque<MatchAssignment>>,
+}
+
+impl Schedule {
+    /// Creates an empty schedule.
+    pub fn new() -> Self {
+        Schedule::default()
+    }
+
+    /// Queues an assignment on its piste, after the ones already queued.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::MissingField` if the assignment is incomplete
+    /// (see [`MatchAssignment::validate`]); it is not queued.
+    pub fn push(&mut self, assignment: MatchAssignment) -> Result<(), ParseError> {
+        assignment.validate()?;
+        self.queues
+            .entry(PisteId::from(assignment.piste.as_str()))
+            .or_default()
+            .push_back(assignment);
+        Ok(())
+    }
+
+    /// Returns the assignment that will be loaded next on a piste.
+    pub fn peek(&self, piste: &str) -> Option<&MatchAssignment> {
+        self.queues.get(&PisteId::from(piste)).and_then(VecDeque::front)
+    }
+
+    /// Iterates over the assignments queued on a piste, in order.
+    pub fn upcoming(&self, piste: &str) -> impl Iterator<Item = &MatchAssignment> {
+        self.queues.get(&PisteId::from(piste)).into_iter().flatten()
+    }
+
+    /// Removes and returns the next assignment of a piste.
+    pub fn pop(&mut self, piste: &str) -> Option<MatchAssignment> {
+        let id = PisteId::from(piste);
+        let queue = self.queues.get_mut(&id)?;
+        let assignment = queue.pop_front();
+        if queue.is_empty() {
+            self.queues.remove(&id);
+        }
+        assignment
+    }
+
+    /// Pops the next assignment of a piste and returns the NEXT message announcing it.
+    pub fn next_message(&mut self, piste: &str) -> Option<Message> {
+        self.pop(piste)
+            .and_then(|assignment| assignment.to_message(Command::Next).ok())
+    }
+
+    /// Returns the NEXT message for a piste if its bout has just finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `piste` - The piste the events were observed on
+    /// * `events` - The events returned by `MatchState::apply` for that piste
+    ///
+    /// # Returns
+    ///
+    /// `Some(Message)` if the events contain `MatchEvent::BoutFinished` and an
+    /// assignment was queued for the piste, `None` otherwise.
+    pub fn advance(&mut self, piste: &str, events: &[MatchEvent]) -> Option<Message> {
+        if events.contains(&MatchEvent::BoutFinished) {
+            self.next_message(piste)
+        } else {
+            None
+        }
+    }
+
+    /// Iterates over the pistes with queued assignments, in natural order.
+    pub fn pistes(&self) -> impl Iterator<Item = &PisteId> {
+        self.queues.keys()
+    }
+
+    /// Returns the number of queued assignments over every piste.
+    pub fn len(&self) -> usize {
+        self.queues.values().map(VecDeque::len).sum()
+    }
+
+    /// Returns `true` if no assignment is queued.
+    pub fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assignment::AssignedFencer;
+
+    fn assignment(piste: &str, match_number: u8) -> MatchAssignment {
+        let fencer = |id: &str| AssignedFencer {
+            id: Some(id.to_string()),
+            name: Some(format!("Fencer {}", id)),
+            ..AssignedFencer::default()
+        };
+        MatchAssignment {
+            piste: piste.to_string(),
+            competition_id: "fm-eq".to_string(),
+            phase: Some(1),
+            pool_tableau: Some("P1".to_string()),
+            match_number: Some(match_number),
+            right_fencer: fencer("1"),
+            left_fencer: fencer("2"),
+            ..MatchAssignment::default()
+        }
+    }
+
+    #[test]
+    fn test_queues_per_piste() {
+        let mut schedule = Schedule::new();
+        schedule.push(assignment("10", 1)).unwrap();
+        schedule.push(assignment("2", 2)).unwrap();
+        schedule.push(assignment("10", 3)).unwrap();
+        assert!(schedule.push(MatchAssignment::default()).is_err());
+
+        assert_eq!(schedule.len(), 3);
+        let pistes: Vec<&str> = schedule.pistes().map(PisteId::as_str).collect();
+        assert_eq!(pistes, ["2", "10"]);
+
+        assert_eq!(schedule.advance("10", &[MatchEvent::BoutStarted]), None);
+        let next = schedule.advance("10", &[MatchEvent::BoutFinished]).unwrap();
+        assert_eq!(next.match_number, Some(1));
+        assert_eq!(schedule.peek("10").unwrap().match_number, Some(3));
+
+        assert_eq!(schedule.pop("2").unwrap().match_number, Some(2));
+        assert_eq!(schedule.upcoming("2").count(), 0);
+        assert_eq!(schedule.len(), 1);
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+
+use crate::error::ParseError;
+use crate::message::Message;
+use crate::options::ParseOptions;
+use crate::piste::PisteId;
+use crate::trailer::split_trailer;
+
+/// Default modulus of sequence numbers: they wrap around after 65535.
+pub const DEFAULT_SEQUENCE_MODULUS: u32 = 1 << 16;
+
+/// Default number of frames held back while waiting for a missing one.
+pub const DEFAULT_REORDER_WINDOW: usize = 4;
+
+impl Message {
+    /// Parses a sequence-numbered frame, whose sequence number is the decimal
+    /// trailer appended after the final `%|` by the vendor extension.
+    ///
+    /// # Returns
+    ///
+    /// The message and its sequence number, or `None` for a frame without trailer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::InvalidValue` for the `sequence` field if the
+    /// trailer is not a number, or any error of [`Message::parse_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::message::Message;
+    /// use cyrano::options::ParseOptions;
+    ///
+    /// let (message, sequence) = Message::parse_sequenced("|EFP1.1|HELLO|17|fm-eq|%|4711", &ParseOptions::default()).unwrap();
+    /// assert_eq!((message.piste.as_str(), sequence), ("17", Some(4711)));
+    /// ```
+    pub fn parse_sequenced(raw: &str, options: &ParseOptions) -> Result<(Self, Option<u32>), ParseError> {
+        let (frame, trailer) = split_trailer(raw);
+        let sequence = match trailer {
+            Some(trailer) => Some(trailer.parse().map_err(|_| ParseError::InvalidValue {
+                field: "sequence",
+                value: trailer.to_string(),
+            })?),
+            None => None,
+        };
+        Message::parse_with(frame, options).map(|message| (message, sequence))
+    }
+}
+
+/// Position of a sequence number relative to the one expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceCheck {
+    /// The number expected.
+    InOrder,
+    /// A number ahead of the one expected: frames were lost or are late.
+    Gap { missed: u32 },
+    /// The number of the last frame, received again.
+    Duplicate,
+    /// A number behind the last frame: the frame arrived out of order.
+    Late { behind: u32 },
+}
+
+/// Returns the signed distance from `from` to `to`, taking wraparound into
+/// account: numbers less than half the modulus ahead are ahead.
+fn distance(from: u32, to: u32, modulus: u32) -> i64 {
+    let ahead = i64::from(to) - i64::from(from);
+    let modulus = i64::from(modulus);
+    let ahead = ahead.rem_euclid(modulus);
+    if ahead < modulus / 2 {
+        ahead
+    } else {
+        ahead - modulus
+    }
+}
+
+/// Checks that the sequence numbers of each piste increase, wrapping around
+/// at the modulus.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::sequence::{SequenceCheck, SequenceTracker};
+///
+/// let mut tracker = SequenceTracker::new();
+/// assert_eq!(tracker.check("17", 65534), SequenceCheck::InOrder);
+/// assert_eq!(tracker.check("17", 65535), SequenceCheck::InOrder);
+/// assert_eq!(tracker.check("17", 2), SequenceCheck::Gap { missed: 2 });
+/// assert_eq!(tracker.check("17", 0), SequenceCheck::Late { behind: 2 });
+/// ```
+#[derive(Debug, Clone)]
+pub struct SequenceTracker {
+    modulus: u32,
+    last: HashMap<PisteId, u32>,
+}
+
+impl Default for SequenceTracker {
+    fn default() -> Self {
+        SequenceTracker::new()
+    }
+}
+
+impl SequenceTracker {
+    /// Creates a tracker with the default modulus.
+    pub fn new() -> Self {
+        SequenceTracker {
+            modulus: DEFAULT_SEQUENCE_MODULUS,
+            last: HashMap::new(),
+        }
+    }
+
+    /// Sets the modulus at which sequence numbers wrap around.
+    pub fn modulus(mut self, modulus: u32) -> Self {
+        self.modulus = modulus.max(2);
+        self
+    }
+
+    /// Checks the sequence number of a frame received on a piste.
+    ///
+    /// The first number of a piste is in order. Numbers ahead become the new
+    /// reference; duplicates and late numbers do not.
+    pub fn check(&mut self, piste: &str, sequence: u32) -> SequenceCheck {
+        let sequence = sequence % self.modulus;
+        let piste = PisteId::from(piste);
+        let Some(last) = self.last.get(&piste).copied() else {
+            self.last.insert(piste, sequence);
+            return SequenceCheck::InOrder;
+        };
+
+        match distance(last, sequence, self.modulus) {
+            0 => SequenceCheck::Duplicate,
+            1 => {
+                self.last.insert(piste, sequence);
+                SequenceCheck::InOrder
+            }
+            ahead if ahead > 1 => {
+                self.last.insert(piste, sequence);
+                SequenceCheck::Gap {
+                    missed: (ahead - 1) as u32,
+                }
+            }
+            behind => SequenceCheck::Late {
+                behind: behind.unsigned_abs() as u32,
+            },
+        }
+    }
+}
+
+/// Puts slightly out-of-order frames of each piste back in sequence order.
+///
+/// A frame ahead of the one expected is held back until the missing frames
+/// arrive, or until more than the reorder window is held, when the missing
+/// frames are given up. Frames behind the last one released and duplicates
+/// are dropped: each INFO carries the whole bout, so releasing an older one
+/// would roll the bout back.
+///
+/// Like the [`Coalescer`](crate::coalesce::Coalescer), the reorderer does not
+/// perform any I/O: callers offer the messages they receive, or have
+/// [`UdpEndpoint::sequenced`](crate::net::UdpEndpoint::sequenced) and
+/// [`TcpConnection::sequenced`](crate::net::TcpConnection::sequenced) run one
+/// in their receive path.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::enums::Command;
+/// use cyrano::message::Message;
+/// use cyrano::sequence::Reorderer;
+///
+/// let mut reorderer = Reorderer::new();
+/// let frame = |time: &str| {
+///     let mut message = Message::new(Command::Info, "17", "fm-eq");
+///     message.time = Some(time.into());
+///     message
+/// };
+///
+/// assert_eq!(reorderer.offer(10, frame("2:59")).len(), 1);
+/// assert!(reorderer.offer(12, frame("2:57")).is_empty());
+/// let released = reorderer.offer(11, frame("2:58"));
+/// assert_eq!(released.iter().map(|m| m.time.as_deref().unwrap()).collect::<Vec<_>>(), ["2:58", "2:57"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Reorderer {
+    modulus: u32,
+    window: usize,
+    pistes: HashMap<PisteId, PisteQueue>,
+    missed: u64,
+    dropped: u64,
+}
+
+#[derive(Debug, Clone)]
+struct PisteQueue {
+    next: u32,
+    held: Vec<(u32, Message)>,
+}
+
+impl Default for Reorderer {
+    fn default() -> Self {
+        Reorderer::new()
+    }
+}
+
+impl Reorderer {
+    /// Creates a reorderer with the default modulus and window.
+    pub fn new() -> Self {
+        Reorderer {
+            modulus: DEFAULT_SEQUENCE_MODULUS,
+            window: DEFAULT_REORDER_WINDOW,
+            pistes: HashMap::new(),
+            missed: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Sets the modulus at which sequence numbers wrap around.
+    pub fn modulus(mut self, modulus: u32) -> Self {
+        self.modulus = modulus.max(2);
+        self
+    }
+
+    /// Sets the number of frames held back while waiting for a missing one.
+    pub fn window(mut self, window: usize) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Returns the number of frames given up so far.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    /// Returns the number of late and duplicate frames dropped so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Offers a received frame with its sequence number.
+    ///
+    /// # Returns
+    ///
+    /// The frames of the piste that can now be passed on, in sequence order.
+    pub fn offer(&mut self, sequence: u32, message: Message) -> Vec<Message> {
+        let modulus = self.modulus;
+        let sequence = sequence % modulus;
+        let piste = PisteId::from(message.piste.as_str());
+        let Some(queue) = self.pistes.get_mut(&piste) else {
+            self.pistes.insert(
+                piste,
+                PisteQueue {
+                    next: (sequence + 1) % modulus,
+                    held: Vec::new(),
+                },
+            );
+            return vec![message];
+        };
+
+        let ahead = distance(queue.next, sequence, modulus);
+        if ahead < 0 || queue.held.iter().any(|(held, _)| *held == sequence) {
+            self.dropped += 1;
+            return Vec::new();
+        }
+        queue.held.push((sequence, message));
+
+        let mut released = Vec::new();
+        queue.release(modulus, &mut released);
+        while queue.held.len() > self.window {
+            self.missed += queue.skip(modulus);
+            queue.release(modulus, &mut released);
+        }
+        released
+    }
+
+    /// Releases every frame held back, giving up the missing ones.
+    pub fn flush(&mut self) -> Vec<Message> {
+        let mut released = Vec::new();
+        for queue in self.pistes.values_mut() {
+            while !queue.held.is_empty() {
+                self.missed += queue.skip(self.modulus);
+                queue.release(self.modulus, &mut released);
+            }
+        }
+        released
+    }
+}
+
+impl PisteQueue {
+    /// Releases the held frames following the expected number.
+    fn release(&mut self, modulus: u32, released: &mut Vec<Message>) {
+        while let Some(index) = self.held.iter().position(|(sequence, _)| *sequence == self.next) {
+            let (_, message) = self.held.swap_remove(index);
+            released.push(message);
+            self.next = (self.next + 1) % modulus;
+        }
+    }
+
+    /// Gives up the frames missing before the first one held, returning their count.
+    fn skip(&mut self, modulus: u32) -> u64 {
+        let first = self
+            .held
+            .iter()
+            .map(|(sequence, _)| distance(self.next, *sequence, modulus))
+            .min()
+            .unwrap_or(0);
+        self.next = ((i64::from(self.next) + first).rem_euclid(i64::from(modulus))) as u32;
+        first as u64
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::Command;
+
+    fn frame(piste: &str, score: u8) -> Message {
+        let mut message = Message::new(Command::Info, piste, "fm-eq");
+        message.right_fencer.score = Some(score);
+        message
+    }
+
+    fn scores(messages: Vec<Message>) -> Vec<u8> {
+        messages.iter().map(|m| m.right_fencer.score.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_reorder_across_wraparound() {
+        let mut reorderer = Reorderer::new().window(2);
+        assert_eq!(scores(reorderer.offer(65534, frame("1", 0))), [0]);
+        assert_eq!(scores(reorderer.offer(0, frame("1", 2))), [0; 0]);
+        assert_eq!(scores(reorderer.offer(7, frame("2", 9))), [9]);
+        assert_eq!(scores(reorderer.offer(65535, frame("1", 1))), [1, 2]);
+
+        // Late and duplicate frames are dropped.
+        assert!(reorderer.offer(65535, frame("1", 1)).is_empty());
+        assert!(reorderer.offer(2, frame("1", 4)).is_empty());
+        assert!(reorderer.offer(2, frame("1", 4)).is_empty());
+        assert_eq!(reorderer.dropped(), 2);
+
+        // A full window gives up the missing frame.
+        assert!(reorderer.offer(3, frame("1", 5)).is_empty());
+        assert_eq!(scores(reorderer.offer(4, frame("1", 6))), [4, 5, 6]);
+        assert_eq!(reorderer.missed(), 1);
+
+        assert!(reorderer.offer(6, frame("1", 8)).is_empty());
+        assert_eq!(scores(reorderer.flush()), [8]);
+        assert_eq!(reorderer.missed(), 2);
+
+        assert!(matches!(
+            Message::parse_sequenced("|EFP1.1|HELLO|17|%|x1", &ParseOptions::default()),
+            Err(ParseError::InvalidValue { field: "sequence", .. })
+        ));
+    }
+}
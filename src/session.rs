@@ -0,0 +1,905 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+
+use super::enums::Command;
+use super::error::SessionError;
+use super::message::Message;
+
+/// Side of the link a protocol session speaks for.
+///
+/// The EFP protocol is asymmetric: the scoring apparatus reports the bout with
+/// INFO messages while the competition management software drives the piste
+/// with DISP and NEXT/PREV messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Role {
+    /// The piste apparatus (scoring machine).
+    Machine,
+    /// The competition management software.
+    Software,
+}
+
+impl Role {
+    /// Returns the role at the other end of the link.
+    pub fn peer(&self) -> Role {
+        match self {
+            Role::Machine => Role::Software,
+            Role::Software => Role::Machine,
+        }
+    }
+
+    /// Returns `true` if this role is allowed to send the given command.
+    ///
+    /// Both sides may send HELLO, ACK and NAK. Only the apparatus sends INFO,
+    /// and only the software sends DISP, NEXT and PREV.
+    pub fn can_send(&self, command: &Command) -> bool {
+        match command {
+            Command::Hello | Command::Ack | Command::Nak => true,
+            Command::Info => *self == Role::Machine,
+            Command::Disp | Command::Next | Command::Prev => *self == Role::Software,
+        }
+    }
+
+    /// Returns `true` if this role answers the given received command with an ACK.
+    ///
+    /// The apparatus acknowledges DISP, NEXT and PREV; the software acknowledges
+    /// the apparatus HELLO. INFO messages are periodic and never acknowledged.
+    pub fn acknowledges(&self, command: &Command) -> bool {
+        match self {
+            Role::Machine => matches!(command, Command::Disp | Command::Next | Command::Prev),
+            Role::Software => matches!(command, Command::Hello),
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Machine => write!(f, "machine"),
+            Role::Software => write!(f, "software"),
+        }
+    }
+}
+
+/// Default delay for the peer to acknowledge a sent message, in milliseconds.
+pub const DEFAULT_ACK_TIMEOUT_MS: u64 = 1000;
+
+/// A sent message waiting for the peer's answer.
+#[derive(Debug, Clone)]
+struct Pending {
+    handle: SendHandle,
+    piste: String,
+    command: Command,
+    sent_ms: u64,
+    /// Whether a caller holds the handle and will take the outcome; heartbeats
+    /// are only awaited to measure the round trip.
+    tracked: bool,
+}
+
+/// Identifies a message registered with [`ProtocolSession::send`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SendHandle(u64);
+
+/// How the peer answered a sent message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SendOutcome {
+    /// The peer answered with an ACK.
+    Acked,
+    /// The peer answered with a NAK. EFP 1.1 NAK frames carry no reason.
+    Nacked,
+    /// No answer arrived within the acknowledgment timeout.
+    TimedOut,
+}
+
+/// Default interval between two heartbeat HELLO messages, in milliseconds.
+pub const DEFAULT_HEARTBEAT_INTERVAL_MS: u64 = 5000;
+
+/// Default number of heartbeats in a row the peer may miss before the link is down.
+pub const DEFAULT_MISSED_HEARTBEATS_BEFORE_DOWN: u32 = 3;
+
+/// Default upper bound of the delay between two HELLO messages while the
+/// link is down, in milliseconds.
+pub const DEFAULT_RECONNECT_BACKOFF_MAX_MS: u64 = 60_000;
+
+/// Health of the link to the peer, as seen by a [`ProtocolSession`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkHealth {
+    /// Time the last message was received from the peer, in milliseconds.
+    pub last_seen_ms: Option<u64>,
+    /// Smoothed delay between a send and its acknowledgment, in milliseconds.
+    pub rtt_ms: Option<u64>,
+    /// Number of heartbeat intervals in a row without hearing from the peer.
+    pub missed_heartbeats: u32,
+    /// Whether the peer is considered reachable.
+    pub up: bool,
+}
+
+/// Change in the health of the link to the peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LinkEvent {
+    /// The peer was heard from for the first time.
+    Up,
+    /// The peer was heard from again after the link went down, without a new
+    /// handshake: it kept its state through the outage.
+    Resumed,
+    /// The peer sent a HELLO after the link went down. It may have restarted,
+    /// so the state accumulated before the outage may no longer match its own.
+    ContinuityLost,
+    /// A heartbeat interval went by without hearing from the peer.
+    HeartbeatMissed { missed_heartbeats: u32 },
+    /// The peer missed too many heartbeats in a row.
+    Down { last_seen_ms: Option<u64> },
+}
+
+/// What a [`ProtocolSession`] keeps across a restart of the software: its
+/// role, piste and settings, the competition in progress and the last bout
+/// assignment sent.
+///
+/// Sends waiting for an answer and the link health are left out, since they
+/// are measured on a clock that does not survive the restart. With the `serde`
+/// feature, snapshots can be written to disk alongside the
+/// [`PisteManager`](crate::state::PisteManager) they go with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionSnapshot {
+    /// Side of the link the session speaks for.
+    pub role: Role,
+    /// Piste identifier.
+    pub piste: String,
+    /// Competition identifier last seen on the link.
+    pub competition_id: String,
+    /// Delay for the peer to acknowledge a sent message, in milliseconds.
+    pub ack_timeout_ms: u64,
+    /// Interval between two heartbeat HELLO messages, in milliseconds.
+    pub heartbeat_interval_ms: u64,
+    /// Heartbeats in a row the peer may miss before the link is down.
+    pub missed_heartbeats_before_down: u32,
+    /// Upper bound of the delay between two HELLO messages while the link is
+    /// down, in milliseconds.
+    pub reconnect_backoff_max_ms: u64,
+    /// Last DISP, NEXT or PREV sent.
+    pub last_assignment: Option<Message>,
+}
+
+/// Protocol state for one end of an EFP link on a given piste.
+///
+/// The session does not perform any I/O: callers feed it the messages they
+/// receive and send, and transmit the replies it produces.
+///
+/// Messages the peer acknowledges are registered with [`send`](ProtocolSession::send),
+/// which returns a [`SendHandle`]. The peer answers in order, so each ACK or
+/// NAK received resolves the oldest send still waiting on the same piste, and
+/// [`expire`](ProtocolSession::expire) resolves the sends left unanswered for
+/// too long.
+///
+/// EFP has no dedicated keepalive, so [`heartbeat`](ProtocolSession::heartbeat)
+/// returns a HELLO to send at a regular interval. Messages fed through
+/// [`receive`](ProtocolSession::receive) keep the [`LinkHealth`] up to date,
+/// and changes are reported as [`LinkEvent`]s, so that a silent apparatus is
+/// noticed before the referee does.
+///
+/// EFP runs over UDP, so reconnecting means handshaking again. While the link
+/// is down, heartbeats are spaced out with an exponential backoff. When the
+/// peer comes back, the session keeps its competition and send tracking, and
+/// [`resubscribe`](ProtocolSession::resubscribe) returns the last bout
+/// assignment to send again.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Command;
+/// use cyrano::message::Message;
+/// use cyrano::session::{ProtocolSession, Role};
+///
+/// let mut session = ProtocolSession::new(Role::Machine, "17");
+///
+/// // The apparatus acknowledges a DISP from the software...
+/// let disp = Message::try_from("|EFP1.1|DISP|17|fm-eq|%|").unwrap();
+/// let reply = session.handle_incoming(&disp).unwrap();
+/// assert_eq!(reply.command, Command::Ack);
+///
+/// // ...and rejects an INFO, which only an apparatus may send.
+/// let info = Message::try_from("|EFP1.1|INFO|17|fm-eq|%|").unwrap();
+/// let reply = session.handle_incoming(&info).unwrap();
+/// assert_eq!(reply.command, Command::Nak);
+/// ```
+///
+/// Tracking the answer to a sent message:
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Command;
+/// use cyrano::message::Message;
+/// use cyrano::session::{ProtocolSession, Role, SendOutcome};
+///
+/// let mut session = ProtocolSession::new(Role::Software, "17");
+///
+/// let disp = Message::new(Command::Disp, "17", "fm-eq");
+/// let handle = session.send(&disp, 1_000).unwrap().unwrap();
+/// assert_eq!(session.outcome(handle), None);
+///
+/// session.handle_incoming(&Message::try_from("|EFP1.1|ACK|17|fm-eq|%|").unwrap());
+/// assert_eq!(session.outcome(handle), Some(SendOutcome::Acked));
+/// ```
+///
+/// Watching the link with heartbeats:
+///
+/// ```
+/// use cyrano::enums::Command;
+/// use cyrano::message::Message;
+/// use cyrano::session::{LinkEvent, ProtocolSession, Role};
+///
+/// let mut session = ProtocolSession::new(Role::Software, "17").heartbeat_interval_ms(1_000);
+///
+/// assert_eq!(session.heartbeat(0).unwrap().command, Command::Hello);
+/// assert!(session.heartbeat(500).is_none());
+///
+/// session.receive(&Message::new(Command::Info, "17", "fm-eq"), 800);
+/// assert_eq!(session.take_link_events(), vec![LinkEvent::Up]);
+/// assert_eq!(session.health().last_seen_ms, Some(800));
+///
+/// // Nothing heard for three intervals: the apparatus is reported down.
+/// for now_ms in [2_000, 3_000, 4_000, 5_000] {
+///     session.heartbeat(now_ms);
+/// }
+/// assert_eq!(
+///     session.take_link_events().last(),
+///     Some(&LinkEvent::Down { last_seen_ms: Some(800) })
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProtocolSession {
+    role: Role,
+    piste: String,
+    competition_id: String,
+    ack_timeout_ms: u64,
+    next_handle: u64,
+    awaiting: VecDeque<Pending>,
+    outcomes: HashMap<SendHandle, SendOutcome>,
+    heartbeat_interval_ms: u64,
+    missed_before_down: u32,
+    last_heartbeat_ms: Option<u64>,
+    health: LinkHealth,
+    link_events: Vec<LinkEvent>,
+    backoff_max_ms: u64,
+    retry_interval_ms: u64,
+    was_down: bool,
+    last_assignment: Option<Message>,
+}
+
+impl ProtocolSession {
+    /// Creates a session for the given role on a piste.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The side of the link this session speaks for
+    /// * `piste` - Piste identifier used in replies
+    pub fn new(role: Role, piste: impl Into<String>) -> Self {
+        ProtocolSession {
+            role,
+            piste: piste.into(),
+            competition_id: String::new(),
+            ack_timeout_ms: DEFAULT_ACK_TIMEOUT_MS,
+            next_handle: 0,
+            awaiting: VecDeque::new(),
+            outcomes: HashMap::new(),
+            heartbeat_interval_ms: DEFAULT_HEARTBEAT_INTERVAL_MS,
+            missed_before_down: DEFAULT_MISSED_HEARTBEATS_BEFORE_DOWN,
+            last_heartbeat_ms: None,
+            health: LinkHealth::default(),
+            link_events: Vec::new(),
+            backoff_max_ms: DEFAULT_RECONNECT_BACKOFF_MAX_MS,
+            retry_interval_ms: DEFAULT_HEARTBEAT_INTERVAL_MS,
+            was_down: false,
+            last_assignment: None,
+        }
+    }
+
+    /// Returns what the session keeps across a restart.
+    pub fn snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            role: self.role,
+            piste: self.piste.clone(),
+            competition_id: self.competition_id.clone(),
+            ack_timeout_ms: self.ack_timeout_ms,
+            heartbeat_interval_ms: self.heartbeat_interval_ms,
+            missed_heartbeats_before_down: self.missed_before_down,
+            reconnect_backoff_max_ms: self.backoff_max_ms,
+            last_assignment: self.last_assignment.clone(),
+        }
+    }
+
+    /// Resumes a session from a snapshot taken before the software restarted.
+    ///
+    /// The link is taken as having gone down: the first message received
+    /// reports [`LinkEvent::Resumed`], or [`LinkEvent::ContinuityLost`] if it
+    /// is a HELLO, after which [`resubscribe`](ProtocolSession::resubscribe)
+    /// sends the last bout assignment again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::message::Message;
+    /// use cyrano::session::{LinkEvent, ProtocolSession, Role};
+    ///
+    /// let mut session = ProtocolSession::new(Role::Software, "17");
+    /// let next = Message::try_from("|EFP1.1|NEXT|17|fm-eq|1|A32|12|%|").unwrap();
+    /// session.send(&next, 0).unwrap();
+    /// let snapshot = session.snapshot();
+    ///
+    /// let mut resumed = ProtocolSession::resume_from(snapshot);
+    /// resumed.receive(&Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|%|").unwrap(), 10);
+    /// assert_eq!(resumed.take_link_events(), vec![LinkEvent::Resumed]);
+    /// assert_eq!(resumed.resubscribe(10).unwrap().0, next);
+    /// ```
+    pub fn resume_from(snapshot: SessionSnapshot) -> Self {
+        let mut session = ProtocolSession::new(snapshot.role, snapshot.piste)
+            .ack_timeout_ms(snapshot.ack_timeout_ms)
+            .heartbeat_interval_ms(snapshot.heartbeat_interval_ms)
+            .missed_heartbeats_before_down(snapshot.missed_heartbeats_before_down)
+            .reconnect_backoff_max_ms(snapshot.reconnect_backoff_max_ms);
+        session.competition_id = snapshot.competition_id;
+        session.last_assignment = snapshot.last_assignment;
+        session.was_down = true;
+        session
+    }
+
+    /// Sets the delay after which an unanswered send times out.
+    pub fn ack_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.ack_timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Sets the interval between two heartbeat HELLO messages.
+    pub fn heartbeat_interval_ms(mut self, interval_ms: u64) -> Self {
+        self.heartbeat_interval_ms = interval_ms;
+        self.retry_interval_ms = interval_ms;
+        self
+    }
+
+    /// Sets the upper bound of the delay between two HELLO messages while the
+    /// link is down.
+    pub fn reconnect_backoff_max_ms(mut self, max_ms: u64) -> Self {
+        self.backoff_max_ms = max_ms;
+        self
+    }
+
+    /// Sets the number of heartbeats in a row the peer may miss before the
+    /// link is reported down.
+    pub fn missed_heartbeats_before_down(mut self, missed: u32) -> Self {
+        self.missed_before_down = missed.max(1);
+        self
+    }
+
+    /// Returns the role of this session.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Returns the piste this session is bound to.
+    pub fn piste(&self) -> &str {
+        &self.piste
+    }
+
+    /// Returns the competition identifier last seen on the link.
+    pub fn competition_id(&self) -> &str {
+        &self.competition_id
+    }
+
+    /// Checks that a message may be sent by the local role.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SessionError::ForbiddenCommand` if the local role is not allowed
+    /// to send the message's command.
+    pub fn check_outgoing(&self, message: &Message) -> Result<(), SessionError> {
+        if self.role.can_send(&message.command) {
+            Ok(())
+        } else {
+            Err(SessionError::ForbiddenCommand {
+                role: self.role,
+                command: message.command.clone(),
+            })
+        }
+    }
+
+    /// Checks that a received message may have been sent by the peer role.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SessionError::ForbiddenCommand` if the peer role is not allowed
+    /// to send the message's command.
+    pub fn check_incoming(&self, message: &Message) -> Result<(), SessionError> {
+        let peer = self.role.peer();
+        if peer.can_send(&message.command) {
+            Ok(())
+        } else {
+            Err(SessionError::ForbiddenCommand {
+                role: peer,
+                command: message.command.clone(),
+            })
+        }
+    }
+
+    /// Registers a message about to be sent, to track the peer's answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message sent to the peer
+    /// * `now_ms` - Current time in milliseconds, on the clock given to [`expire`](ProtocolSession::expire)
+    ///
+    /// # Returns
+    ///
+    /// `Some(SendHandle)` if the peer acknowledges the command, `None` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SessionError::ForbiddenCommand` if the local role is not allowed
+    /// to send the message's command.
+    pub fn send(&mut self, message: &Message, now_ms: u64) -> Result<Option<SendHandle>, SessionError> {
+        self.check_outgoing(message)?;
+        if !self.role.peer().acknowledges(&message.command) {
+            return Ok(None);
+        }
+
+        if matches!(message.command, Command::Disp | Command::Next | Command::Prev) {
+            self.last_assignment = Some(message.clone());
+        }
+
+        Ok(Some(self.register(message, now_ms, true)))
+    }
+
+    fn register(&mut self, message: &Message, now_ms: u64, tracked: bool) -> SendHandle {
+        let handle = SendHandle(self.next_handle);
+        self.next_handle += 1;
+        self.awaiting.push_back(Pending {
+            handle,
+            piste: message.piste.to_string(),
+            command: message.command.clone(),
+            sent_ms: now_ms,
+            tracked,
+        });
+        handle
+    }
+
+    /// Returns the position of the oldest send the received ACK or NAK may
+    /// answer: one on the same piste, with a command the answer applies to.
+    fn answered(&self, answer: &Message) -> Option<usize> {
+        self.awaiting.iter().position(|pending| {
+            let command = match answer.command {
+                Command::Ack => self.role.peer().acknowledges(&pending.command),
+                Command::Nak => self.role.can_send(&pending.command),
+                _ => false,
+            };
+            command && (answer.piste.is_empty() || *answer.piste == *pending.piste)
+        })
+    }
+
+    /// Records the outcome of a send, unless nobody will take it.
+    fn resolve(&mut self, pending: Pending, outcome: SendOutcome) -> Option<SendHandle> {
+        if !pending.tracked {
+            return None;
+        }
+        self.outcomes.insert(pending.handle, outcome);
+        Some(pending.handle)
+    }
+
+    /// Returns the outcome of a send, or `None` while it is still waiting for an answer.
+    pub fn outcome(&self, handle: SendHandle) -> Option<SendOutcome> {
+        self.outcomes.get(&handle).copied()
+    }
+
+    /// Returns the outcome of a send and forgets it, or `None` while it is
+    /// still waiting for an answer.
+    pub fn take_outcome(&mut self, handle: SendHandle) -> Option<SendOutcome> {
+        self.outcomes.remove(&handle)
+    }
+
+    /// Returns the number of sends waiting for an answer.
+    pub fn awaiting(&self) -> usize {
+        self.awaiting.len()
+    }
+
+    /// Times out the sends left unanswered for longer than the acknowledgment
+    /// timeout and returns their handles, oldest first.
+    pub fn expire(&mut self, now_ms: u64) -> Vec<SendHandle> {
+        let mut expired = Vec::new();
+        while let Some(pending) = self.awaiting.front() {
+            if now_ms.saturating_sub(pending.sent_ms) <= self.ack_timeout_ms {
+                break;
+            }
+            let pending = self.awaiting.pop_front().expect("front was just checked");
+            expired.extend(self.resolve(pending, SendOutcome::TimedOut));
+        }
+        expired
+    }
+
+    /// Returns the current health of the link to the peer.
+    pub fn health(&self) -> &LinkHealth {
+        &self.health
+    }
+
+    /// Returns the link events produced since the last call, oldest first.
+    pub fn take_link_events(&mut self) -> Vec<LinkEvent> {
+        std::mem::take(&mut self.link_events)
+    }
+
+    /// Returns the HELLO to send if a heartbeat is due, and counts the
+    /// heartbeat intervals in which the peer stayed silent.
+    ///
+    /// Call it regularly, at least once per heartbeat interval. The first call
+    /// always returns a HELLO. A HELLO the peer acknowledges is registered as
+    /// a send, so that its answer feeds the round-trip estimate.
+    ///
+    /// While the link is down, the delay between two HELLO messages doubles
+    /// after each one, up to the reconnect backoff bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `now_ms` - Current time in milliseconds, on the clock given to [`receive`](ProtocolSession::receive)
+    pub fn heartbeat(&mut self, now_ms: u64) -> Option<Message> {
+        if let Some(last_ms) = self.last_heartbeat_ms {
+            let interval_ms = if self.was_down { self.retry_interval_ms } else { self.heartbeat_interval_ms };
+            if now_ms.saturating_sub(last_ms) < interval_ms {
+                return None;
+            }
+            if self.health.last_seen_ms.is_none_or(|seen_ms| seen_ms < last_ms) {
+                self.miss_heartbeat();
+            }
+            if self.was_down {
+                self.retry_interval_ms = interval_ms.saturating_mul(2).min(self.backoff_max_ms).max(self.heartbeat_interval_ms);
+            }
+        }
+
+        self.last_heartbeat_ms = Some(now_ms);
+        let hello = self.reply(Command::Hello);
+        // Nobody holds the handle of a heartbeat: its answer only measures the
+        // round trip, and no outcome is kept for it.
+        if self.role.peer().acknowledges(&hello.command) {
+            self.register(&hello, now_ms, false);
+        }
+        Some(hello)
+    }
+
+    fn miss_heartbeat(&mut self) {
+        self.health.missed_heartbeats += 1;
+        let missed_heartbeats = self.health.missed_heartbeats;
+        self.link_events.push(LinkEvent::HeartbeatMissed { missed_heartbeats });
+
+        if self.health.up && missed_heartbeats >= self.missed_before_down {
+            self.health.up = false;
+            self.was_down = true;
+            self.retry_interval_ms = self.heartbeat_interval_ms;
+            #[cfg(feature = "tracing")]
+            tracing::warn!(piste = %self.piste, missed_heartbeats, "link down");
+            self.link_events.push(LinkEvent::Down { last_seen_ms: self.health.last_seen_ms });
+        }
+    }
+
+    /// Processes a message received at a given time and returns the reply to
+    /// send, if any.
+    ///
+    /// Behaves like [`handle_incoming`](ProtocolSession::handle_incoming), and
+    /// also updates the link health: the peer is marked as seen, and the delay
+    /// of an acknowledged send feeds the round-trip estimate.
+    ///
+    /// A HELLO received after the link went down reports
+    /// [`LinkEvent::ContinuityLost`] and times out the sends still waiting,
+    /// since a restarted peer will not answer them.
+    pub fn receive(&mut self, message: &Message, now_ms: u64) -> Option<Message> {
+        self.health.last_seen_ms = Some(now_ms);
+        self.health.missed_heartbeats = 0;
+        if !self.health.up {
+            self.health.up = true;
+            let event = if !self.was_down {
+                LinkEvent::Up
+            } else if message.command == Command::Hello {
+                for pending in std::mem::take(&mut self.awaiting) {
+                    self.resolve(pending, SendOutcome::TimedOut);
+                }
+                LinkEvent::ContinuityLost
+            } else {
+                LinkEvent::Resumed
+            };
+            self.was_down = false;
+            #[cfg(feature = "tracing")]
+            tracing::info!(piste = %self.piste, event = ?event, "link up");
+            self.link_events.push(event);
+        }
+
+        if message.command == Command::Ack {
+            if let Some(pending) = self.answered(message).map(|index| &self.awaiting[index]) {
+                let sample = now_ms.saturating_sub(pending.sent_ms);
+                // Exponential smoothing with a 1/8 gain, as TCP does.
+                self.health.rtt_ms = Some(match self.health.rtt_ms {
+                    Some(rtt_ms) => (rtt_ms * 7 + sample) / 8,
+                    None => sample,
+                });
+            }
+        }
+
+        self.handle_incoming(message)
+    }
+
+    /// Returns the last DISP, NEXT or PREV registered with
+    /// [`send`](ProtocolSession::send), registered again to be resent.
+    ///
+    /// Call it after [`LinkEvent::Resumed`] or [`LinkEvent::ContinuityLost`]
+    /// so that the apparatus shows the current bout again.
+    pub fn resubscribe(&mut self, now_ms: u64) -> Option<(Message, SendHandle)> {
+        let message = self.last_assignment.clone()?;
+        let handle = self.send(&message, now_ms).ok().flatten()?;
+        Some((message, handle))
+    }
+
+    /// Processes a received message and returns the reply to send, if any.
+    ///
+    /// Commands the peer is not allowed to send are answered with a NAK.
+    /// Commands the local role acknowledges are answered with an ACK. A
+    /// received ACK or NAK resolves the oldest send waiting for an answer on
+    /// the same piste; answers matching no send are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message received from the peer
+    ///
+    /// # Returns
+    ///
+    /// `Some(Message)` containing the ACK or NAK to send back, `None` if the
+    /// message does not call for a reply.
+    pub fn handle_incoming(&mut self, message: &Message) -> Option<Message> {
+        if !message.competition_id.is_empty() && message.competition_id != self.competition_id {
+            #[cfg(feature = "tracing")]
+            tracing::info!(
+                piste = %self.piste,
+                from = %self.competition_id,
+                to = %message.competition_id,
+                "competition changed"
+            );
+            self.competition_id = message.competition_id.to_string();
+        }
+
+        if let Err(_err) = self.check_incoming(message) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(piste = %self.piste, error = %_err, "rejecting message with NAK");
+            return Some(self.reply(Command::Nak));
+        }
+
+        let outcome = match message.command {
+            Command::Ack => Some(SendOutcome::Acked),
+            Command::Nak => Some(SendOutcome::Nacked),
+            _ => None,
+        };
+        if let Some(outcome) = outcome {
+            if let Some(pending) = self.answered(message).and_then(|index| self.awaiting.remove(index)) {
+                self.resolve(pending, outcome);
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        if message.command == Command::Hello {
+            tracing::info!(piste = %self.piste, role = %self.role, "handshake received");
+        }
+
+        if self.role.acknowledges(&message.command) {
+            Some(self.reply(Command::Ack))
+        } else {
+            None
+        }
+    }
+
+    fn reply(&self, command: Command) -> Message {
+        Message::new(command, self.piste.clone(), self.competition_id.clone())
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_software_acks_hello() {
+        let mut session = ProtocolSession::new(Role::Software, "17");
+        let hello = Message::try_from("|EFP1.1|HELLO|17|fm-eq|%|").unwrap();
+
+        let reply = session.handle_incoming(&hello).unwrap();
+        assert_eq!(reply.command, Command::Ack);
+        assert_eq!(reply.piste, "17");
+        assert_eq!(reply.competition_id, "fm-eq");
+    }
+
+    #[test]
+    fn test_software_does_not_ack_info() {
+        let mut session = ProtocolSession::new(Role::Software, "17");
+        let info = Message::try_from("|EFP1.1|INFO|17|fm-eq|%|").unwrap();
+
+        assert!(session.handle_incoming(&info).is_none());
+    }
+
+    #[test]
+    fn test_software_naks_disp() {
+        let mut session = ProtocolSession::new(Role::Software, "17");
+        let disp = Message::try_from("|EFP1.1|DISP|17|fm-eq|%|").unwrap();
+
+        let reply = session.handle_incoming(&disp).unwrap();
+        assert_eq!(reply.command, Command::Nak);
+    }
+
+    #[test]
+    fn test_machine_cannot_send_next() {
+        let session = ProtocolSession::new(Role::Machine, "17");
+        let next = Message::new(Command::Next, "17", "fm-eq");
+
+        let result = session.check_outgoing(&next);
+        assert!(matches!(
+            result,
+            Err(SessionError::ForbiddenCommand { role: Role::Machine, command: Command::Next })
+        ));
+    }
+
+    #[test]
+    fn test_answers_resolve_sends_in_order() {
+        let mut session = ProtocolSession::new(Role::Software, "17").ack_timeout_ms(500);
+        let disp = Message::new(Command::Disp, "17", "fm-eq");
+        let ack = Message::try_from("|EFP1.1|ACK|17|fm-eq|%|").unwrap();
+        let nak = Message::try_from("|EFP1.1|NAK|17|fm-eq|%|").unwrap();
+
+        let first = session.send(&disp, 0).unwrap().unwrap();
+        let second = session.send(&Message::new(Command::Next, "17", "fm-eq"), 100).unwrap().unwrap();
+        let third = session.send(&disp, 400).unwrap().unwrap();
+        assert_eq!(session.send(&Message::new(Command::Hello, "17", "fm-eq"), 400).unwrap(), None);
+
+        session.handle_incoming(&nak);
+        assert_eq!(session.outcome(first), Some(SendOutcome::Nacked));
+        assert_eq!(session.expire(700), vec![second]);
+        session.handle_incoming(&ack);
+
+        assert_eq!(session.take_outcome(second), Some(SendOutcome::TimedOut));
+        assert_eq!(session.outcome(third), Some(SendOutcome::Acked));
+        assert_eq!(session.awaiting(), 0);
+        assert_eq!(session.take_outcome(second), None);
+    }
+
+    #[test]
+    fn test_answers_resolve_sends_on_their_piste() {
+        let mut session = ProtocolSession::new(Role::Software, "17");
+        let on_17 = session.send(&Message::new(Command::Disp, "17", "fm-eq"), 0).unwrap().unwrap();
+        let on_18 = session.send(&Message::new(Command::Next, "18", "fm-eq"), 10).unwrap().unwrap();
+
+        session.receive(&Message::try_from("|EFP1.1|ACK|19|fm-eq|%|").unwrap(), 20);
+        assert_eq!(session.awaiting(), 2);
+        assert_eq!(session.health().rtt_ms, None);
+
+        session.receive(&Message::try_from("|EFP1.1|ACK|18|fm-eq|%|").unwrap(), 40);
+        assert_eq!(session.outcome(on_17), None);
+        assert_eq!(session.outcome(on_18), Some(SendOutcome::Acked));
+        assert_eq!(session.health().rtt_ms, Some(30));
+
+        session.receive(&Message::try_from("|EFP1.1|NAK|17|fm-eq|%|").unwrap(), 50);
+        assert_eq!(session.outcome(on_17), Some(SendOutcome::Nacked));
+        assert_eq!(session.awaiting(), 0);
+    }
+
+    #[test]
+    fn test_link_health_follows_heartbeats() {
+        let mut session = ProtocolSession::new(Role::Machine, "17")
+            .heartbeat_interval_ms(1_000)
+            .missed_heartbeats_before_down(2);
+        let ack = Message::try_from("|EFP1.1|ACK|17|fm-eq|%|").unwrap();
+
+        // The software acknowledges the apparatus HELLO.
+        assert!(session.heartbeat(0).is_some());
+        assert_eq!(session.awaiting(), 1);
+        session.receive(&ack, 40);
+        assert_eq!(session.health().rtt_ms, Some(40));
+
+        session.heartbeat(1_000);
+        session.receive(&ack, 1_120);
+        assert_eq!(session.health().rtt_ms, Some(50));
+        assert_eq!(session.take_link_events(), vec![LinkEvent::Up]);
+
+        assert!(session.heartbeat(1_999).is_none());
+        session.heartbeat(2_000);
+        session.heartbeat(3_000);
+        session.heartbeat(4_000);
+        assert_eq!(
+            session.take_link_events(),
+            vec![
+                LinkEvent::HeartbeatMissed { missed_heartbeats: 1 },
+                LinkEvent::HeartbeatMissed { missed_heartbeats: 2 },
+                LinkEvent::Down { last_seen_ms: Some(1_120) },
+            ]
+        );
+        assert!(!session.health().up);
+
+        session.receive(&Message::new(Command::Disp, "17", "fm-eq"), 4_200);
+        assert_eq!(session.take_link_events(), vec![LinkEvent::Resumed]);
+        assert_eq!(session.health().missed_heartbeats, 0);
+    }
+
+    #[test]
+    fn test_heartbeat_outcomes_are_not_kept() {
+        let mut session = ProtocolSession::new(Role::Machine, "17").heartbeat_interval_ms(1_000);
+        let ack = Message::try_from("|EFP1.1|ACK|17|fm-eq|%|").unwrap();
+
+        for second in 0..100 {
+            session.heartbeat(second * 1_000);
+            session.receive(&ack, second * 1_000 + 30);
+        }
+        assert_eq!(session.health().rtt_ms, Some(30));
+
+        // Unanswered heartbeats time out without leaving an outcome behind.
+        for second in 100..110 {
+            session.heartbeat(second * 1_000);
+            assert!(session.expire(second * 1_000).is_empty());
+        }
+        assert!(session.expire(200_000).is_empty());
+        assert_eq!(session.awaiting(), 0);
+        assert!(session.outcomes.is_empty());
+    }
+
+    #[test]
+    fn test_reconnect_after_apparatus_restart() {
+        let mut session = ProtocolSession::new(Role::Software, "17")
+            .heartbeat_interval_ms(1_000)
+            .missed_heartbeats_before_down(1)
+            .reconnect_backoff_max_ms(3_000);
+        let disp = Message::new(Command::Disp, "17", "fm-eq");
+        let hello = Message::try_from("|EFP1.1|HELLO|17|fm-eq|%|").unwrap();
+
+        session.heartbeat(0);
+        session.receive(&hello, 10);
+        session.send(&disp, 20).unwrap();
+        session.heartbeat(1_000);
+        session.heartbeat(2_000);
+        assert!(!session.health().up);
+
+        // HELLO messages are spaced out while the apparatus is silent.
+        let sent: Vec<u64> = (2_001..12_000).filter(|&now_ms| session.heartbeat(now_ms).is_some()).collect();
+        assert_eq!(sent, vec![4_000, 7_000, 10_000]);
+
+        session.take_link_events();
+        assert!(session.receive(&hello, 10_500).is_some());
+        assert_eq!(session.take_link_events(), vec![LinkEvent::ContinuityLost]);
+        assert_eq!(session.awaiting(), 0);
+
+        let (message, handle) = session.resubscribe(10_500).unwrap();
+        assert_eq!(message, disp);
+        assert_eq!(session.outcome(handle), None);
+        assert!(session.heartbeat(10_999).is_none());
+        assert!(session.heartbeat(11_000).is_some());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_resume_from_json_snapshot() {
+        let mut session = ProtocolSession::new(Role::Software, "17").ack_timeout_ms(800);
+        session.receive(&Message::try_from("|EFP1.1|HELLO|17|fm-eq|%|").unwrap(), 0);
+        let next = Message::new(Command::Next, "17", "fm-eq");
+        session.send(&next, 10).unwrap();
+
+        let json = serde_json::to_string(&session.snapshot()).unwrap();
+        let snapshot: SessionSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot, session.snapshot());
+
+        let mut resumed = ProtocolSession::resume_from(snapshot);
+        assert_eq!(resumed.awaiting(), 0);
+        let info = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap();
+        assert_eq!(resumed.receive(&info, 5), None);
+        assert_eq!(resumed.take_link_events(), vec![LinkEvent::Resumed]);
+
+        let (message, handle) = resumed.resubscribe(5).unwrap();
+        assert_eq!(message, next);
+        assert_eq!(resumed.snapshot().ack_timeout_ms, 800);
+        assert!(resumed.expire(805).is_empty());
+        assert_eq!(resumed.expire(806), vec![handle]);
+    }
+}
@@ -0,0 +1,561 @@
+use std::convert::TryFrom;
+
+use super::enums::{ApparatusState, Command, CompetitionType, FencerStatus, Priority, Side, Weapon};
+use super::error::ScriptError;
+use super::fencer::Fencer;
+use super::message::Message;
+use super::utils::{field, Rng};
+
+/// Action performed at one step of a bout script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScriptAction {
+    /// The referee starts or resumes the bout ("allez").
+    Fence,
+    /// The referee halts the bout.
+    Halt,
+    /// A valid touch for one fencer.
+    Touch(Side),
+    /// A double touch, scored for both fencers.
+    DoubleTouch,
+    /// An off-target touch by one fencer (white light).
+    OffTarget(Side),
+    /// A yellow card for one fencer.
+    Yellow(Side),
+    /// A red card for one fencer, scoring a touch for the opponent.
+    Red(Side),
+    /// Priority drawn for one fencer.
+    Priority(Side),
+    /// Wall-clock time passing while the match clock is stopped, in seconds.
+    Wait(u64),
+    /// End of the bout; the fencer ahead (or with priority on a tie) wins.
+    End,
+}
+
+/// One step of a bout script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScriptStep {
+    /// Remaining match time when the step happens, as shown on the apparatus (`m:ss`).
+    pub clock: String,
+    /// Action performed.
+    pub action: ScriptAction,
+}
+
+/// Declarative description of a bout.
+///
+/// A script is written either as a struct (and loaded with serde under the
+/// `serde` feature) or in a small line-based text format read by
+/// [`BoutScript::parse`]. [`BoutScript::compile`] turns it into the INFO
+/// messages an apparatus would send, with deterministic timestamps.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::simulator::BoutScript;
+///
+/// let script = BoutScript::parse(
+///     "piste 17
+///      competition fm-eq
+///      weapon E
+///      right 28 FRA P. Martin
+///      left 32 ITA B. Panini
+///      3:00 fence
+///      2:43 touch right
+///      1:10 yellow left
+///      0:00 end",
+/// )
+/// .unwrap();
+///
+/// let frames = script.compile().unwrap();
+/// let last = &frames.last().unwrap().message;
+/// assert_eq!(frames[2].at_ms, 17_000);
+/// assert_eq!(last.right_fencer.score, Some(1));
+/// assert_eq!(last.left_fencer.yellow_card, Some(1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoutScript {
+    /// Piste identifier.
+    pub piste: String,
+    /// Competition identifier.
+    pub competition_id: String,
+    /// Weapon fenced.
+    pub weapon: Weapon,
+    /// Duration of the bout (`m:ss`).
+    pub duration: String,
+    /// Fencer on the right.
+    pub right_fencer: Fencer,
+    /// Fencer on the left.
+    pub left_fencer: Fencer,
+    /// Steps of the bout, in order.
+    pub steps: Vec<ScriptStep>,
+}
+
+impl Default for BoutScript {
+    fn default() -> Self {
+        BoutScript {
+            piste: String::new(),
+            competition_id: String::new(),
+            weapon: Weapon::Epee,
+            duration: "3:00".to_string(),
+            right_fencer: Fencer::default(),
+            left_fencer: Fencer::default(),
+            steps: Vec::new(),
+        }
+    }
+}
+
+/// A message scheduled at a fixed offset from the start of a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedMessage {
+    /// Offset from the start of the script, in milliseconds.
+    pub at_ms: u64,
+    /// The message sent at that time.
+    pub message: Message,
+}
+
+impl BoutScript {
+    /// Reads a script in the text format.
+    ///
+    /// Each line is either a header or a step; blank lines and lines starting
+    /// with `#` are ignored. Headers are `piste <id>`, `competition <id>`,
+    /// `weapon <F|E|S>`, `duration <m:ss>`, `right <id> <nation> <name>` and
+    /// `left <id> <nation> <name>`. Steps are `<m:ss> <action>` where the action
+    /// is one of `fence`, `halt`, `touch <side>`, `double`, `off-target <side>`,
+    /// `yellow <side>`, `red <side>`, `priority <side>`, `wait <seconds>` or `end`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ScriptError::InvalidLine` for a line that is not understood.
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut script = BoutScript::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || ScriptError::InvalidLine {
+                line_number: index + 1,
+                line: line.to_string(),
+            };
+
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let rest = rest.trim();
+            match keyword {
+                "piste" => script.piste = rest.to_string(),
+                "competition" => script.competition_id = rest.to_string(),
+                "weapon" => script.weapon = Weapon::try_from(rest).map_err(|_| invalid())?,
+                "duration" => script.duration = rest.to_string(),
+                "right" => script.right_fencer = parse_fencer(rest).ok_or_else(invalid)?,
+                "left" => script.left_fencer = parse_fencer(rest).ok_or_else(invalid)?,
+                clock if clock.contains(':') => script.steps.push(ScriptStep {
+                    clock: clock.to_string(),
+                    action: parse_action(rest).ok_or_else(invalid)?,
+                }),
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(script)
+    }
+
+    /// Compiles the script into the timed sequence of INFO messages an apparatus would send.
+    ///
+    /// The first message, at offset 0, shows the bout waiting to start. Each
+    /// step then produces one message, timestamped by the match time elapsed
+    /// since the start plus every preceding `wait`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ScriptError::InvalidClock` for a malformed clock value and
+    /// `ScriptError::ClockReversed` if the clock goes up between two steps.
+    pub fn compile(&self) -> Result<Vec<TimedMessage>, ScriptError> {
+        let duration = parse_clock(&self.duration)?;
+
+        let mut message = Message::new(Command::Info, self.piste.clone(), self.competition_id.clone());
+        message.competition_type = Some(CompetitionType::Individual);
+        message.weapon = Some(self.weapon.clone());
+        message.priority = Some(Priority::None);
+        message.state = Some(ApparatusState::Waiting);
+        message.time = Some(field(format_clock(duration)));
+        message.right_fencer = starting_fencer(&self.right_fencer);
+        message.left_fencer = starting_fencer(&self.left_fencer);
+
+        let mut frames = vec![TimedMessage {
+            at_ms: 0,
+            message: message.clone(),
+        }];
+        let mut waited_ms = 0;
+        let mut previous = duration;
+
+        for (index, step) in self.steps.iter().enumerate() {
+            let clock = parse_clock(&step.clock)?;
+            if clock > previous {
+                return Err(ScriptError::ClockReversed {
+                    step: index + 1,
+                    clock: step.clock.clone(),
+                });
+            }
+            previous = clock;
+
+            for fencer in [&mut message.right_fencer, &mut message.left_fencer] {
+                fencer.light = Some(false);
+                fencer.white_light = Some(false);
+            }
+
+            match &step.action {
+                ScriptAction::Fence => message.state = Some(ApparatusState::Fencing),
+                ScriptAction::Halt => message.state = Some(ApparatusState::Halt),
+                ScriptAction::Touch(side) => {
+                    score(fencer_mut(&mut message, *side));
+                    message.state = Some(ApparatusState::Halt);
+                }
+                ScriptAction::DoubleTouch => {
+                    score(&mut message.right_fencer);
+                    score(&mut message.left_fencer);
+                    message.state = Some(ApparatusState::Halt);
+                }
+                ScriptAction::OffTarget(side) => {
+                    fencer_mut(&mut message, *side).white_light = Some(true);
+                    message.state = Some(ApparatusState::Halt);
+                }
+                ScriptAction::Yellow(side) => fencer_mut(&mut message, *side).yellow_card = Some(1),
+                ScriptAction::Red(side) => {
+                    let fencer = fencer_mut(&mut message, *side);
+                    fencer.red_card = Some(fencer.red_card.unwrap_or(0).saturating_add(1));
+                    let opponent = fencer_mut(&mut message, side.opponent());
+                    opponent.score = Some(opponent.score.unwrap_or(0).saturating_add(1));
+                }
+                ScriptAction::Priority(side) => {
+                    message.priority = Some(match side {
+                        Side::Right => Priority::Right,
+                        Side::Left => Priority::Left,
+                    })
+                }
+                ScriptAction::Wait(seconds) => {
+                    waited_ms += seconds * 1000;
+                    continue;
+                }
+                ScriptAction::End => {
+                    message.state = Some(ApparatusState::Ending);
+                    if let Some(winner) = winner(&message) {
+                        fencer_mut(&mut message, winner).status = Some(FencerStatus::Victory);
+                        fencer_mut(&mut message, winner.opponent()).status = Some(FencerStatus::Defeat);
+                    }
+                }
+            }
+
+            message.time = Some(field(format_clock(clock)));
+            frames.push(TimedMessage {
+                at_ms: (duration - clock) * 1000 + waited_ms,
+                message: message.clone(),
+            });
+        }
+
+        Ok(frames)
+    }
+}
+
+/// Network impairment applied to a compiled sequence, to rehearse a bad venue link.
+///
+/// Each message is independently lost, delayed, reordered or duplicated with
+/// the configured probabilities. The outcome only depends on the seed, so a
+/// failing run can be replayed exactly.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::simulator::{BoutScript, Impairment, Simulator};
+///
+/// let script = BoutScript::parse("3:00 fence\n2:50 touch left\n2:40 touch right").unwrap();
+/// let impairment = Impairment { loss: 0.2, duplication: 0.1, delay_ms: 40, jitter_ms: 60, ..Impairment::default() };
+///
+/// let simulator = Simulator::new(&script).unwrap().impaired(&impairment);
+/// assert_eq!(simulator, Simulator::new(&script).unwrap().impaired(&impairment));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Impairment {
+    /// Probability that a message is lost, between 0 and 1.
+    pub loss: f64,
+    /// Probability that a message is received twice.
+    pub duplication: f64,
+    /// Probability that a message is held back behind the messages following it.
+    pub reordering: f64,
+    /// Delay added to every message, in milliseconds.
+    pub delay_ms: u64,
+    /// Upper bound of a random delay added on top of `delay_ms`, in milliseconds.
+    pub jitter_ms: u64,
+    /// Seed of the random draws.
+    pub seed: u64,
+}
+
+/// Extra delay of a reordered message, in milliseconds: long enough to
+/// arrive after the frames an apparatus sends right after it.
+const REORDER_DELAY_MS: u64 = 1500;
+
+impl Impairment {
+    /// Applies the impairment to a sequence and returns what the receiver
+    /// sees, ordered by arrival time.
+    pub fn apply(&self, frames: &[TimedMessage]) -> Vec<TimedMessage> {
+        let mut rng = Rng::new(self.seed);
+        let mut received = Vec::with_capacity(frames.len());
+
+        for frame in frames {
+            if rng.chance(self.loss) {
+                continue;
+            }
+            let mut at_ms = frame.at_ms + self.delay_ms + rng.below(self.jitter_ms + 1);
+            if rng.chance(self.reordering) {
+                at_ms += REORDER_DELAY_MS;
+            }
+            received.push(TimedMessage { at_ms, message: frame.message.clone() });
+
+            if rng.chance(self.duplication) {
+                let at_ms = at_ms + rng.below(self.jitter_ms + 1);
+                received.push(TimedMessage { at_ms, message: frame.message.clone() });
+            }
+        }
+
+        received.sort_by_key(|frame| frame.at_ms);
+        received
+    }
+}
+
+/// Plays a compiled script back against a caller-provided clock.
+///
+/// The simulator does no I/O and never sleeps: the caller polls it with the
+/// time elapsed since the start and sends the returned messages itself.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::simulator::{BoutScript, Simulator};
+///
+/// let script = BoutScript::parse("3:00 fence\n2:50 touch left").unwrap();
+/// let mut simulator = Simulator::new(&script).unwrap();
+///
+/// assert_eq!(simulator.poll(0).len(), 2);
+/// assert!(simulator.poll(5_000).is_empty());
+/// assert_eq!(simulator.poll(10_000).len(), 1);
+/// assert!(simulator.is_finished());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulator {
+    frames: Vec<TimedMessage>,
+    next: usize,
+}
+
+impl Simulator {
+    /// Compiles a script and prepares it for playback.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ScriptError` if the script does not compile.
+    pub fn new(script: &BoutScript) -> Result<Self, ScriptError> {
+        Ok(Simulator::from_frames(script.compile()?))
+    }
+
+    /// Prepares an already compiled sequence for playback.
+    pub fn from_frames(frames: Vec<TimedMessage>) -> Self {
+        Simulator { frames, next: 0 }
+    }
+
+    /// Plays the sequence back as received through an impaired link,
+    /// from the beginning.
+    pub fn impaired(self, impairment: &Impairment) -> Self {
+        Simulator::from_frames(impairment.apply(&self.frames))
+    }
+
+    /// Returns the messages due at `elapsed_ms` that have not been returned yet.
+    pub fn poll(&mut self, elapsed_ms: u64) -> Vec<Message> {
+        let due = self.frames[self.next..]
+            .iter()
+            .take_while(|frame| frame.at_ms <= elapsed_ms)
+            .map(|frame| frame.message.clone())
+            .collect::<Vec<_>>();
+        self.next += due.len();
+        due
+    }
+
+    /// Returns the offset of the next pending message, if any.
+    pub fn next_at_ms(&self) -> Option<u64> {
+        self.frames.get(self.next).map(|frame| frame.at_ms)
+    }
+
+    /// Returns `true` once every message has been returned.
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.frames.len()
+    }
+
+    /// Restarts playback from the beginning.
+    pub fn rewind(&mut self) {
+        self.next = 0;
+    }
+}
+
+fn parse_fencer(rest: &str) -> Option<Fencer> {
+    let mut parts = rest.splitn(3, ' ');
+    let id = parts.next().filter(|id| !id.is_empty())?;
+    let nation = parts.next()?;
+    let name = parts.next()?.trim();
+
+    Some(Fencer {
+        id: Some(id.into()),
+        name: Some(name.into()),
+        nation: Some(nation.into()),
+        ..Fencer::default()
+    })
+}
+
+fn parse_action(rest: &str) -> Option<ScriptAction> {
+    let (name, argument) = rest.split_once(' ').unwrap_or((rest, ""));
+    let side = || match argument.trim() {
+        "right" => Some(Side::Right),
+        "left" => Some(Side::Left),
+        _ => None,
+    };
+
+    match name {
+        "fence" => Some(ScriptAction::Fence),
+        "halt" => Some(ScriptAction::Halt),
+        "touch" => side().map(ScriptAction::Touch),
+        "double" => Some(ScriptAction::DoubleTouch),
+        "off-target" => side().map(ScriptAction::OffTarget),
+        "yellow" => side().map(ScriptAction::Yellow),
+        "red" => side().map(ScriptAction::Red),
+        "priority" => side().map(ScriptAction::Priority),
+        "wait" => argument.trim().parse().ok().map(ScriptAction::Wait),
+        "end" => Some(ScriptAction::End),
+        _ => None,
+    }
+}
+
+fn parse_clock(clock: &str) -> Result<u64, ScriptError> {
+    let invalid = || ScriptError::InvalidClock(clock.to_string());
+    let (minutes, seconds) = clock.split_once(':').ok_or_else(invalid)?;
+    let minutes: u64 = minutes.parse().map_err(|_| invalid())?;
+    let seconds: u64 = seconds.parse().map_err(|_| invalid())?;
+
+    if seconds >= 60 {
+        return Err(invalid());
+    }
+    Ok(minutes * 60 + seconds)
+}
+
+fn format_clock(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn starting_fencer(identity: &Fencer) -> Fencer {
+    Fencer {
+        score: Some(0),
+        status: Some(FencerStatus::Undefined),
+        yellow_card: Some(0),
+        red_card: Some(0),
+        light: Some(false),
+        white_light: Some(false),
+        ..identity.clone()
+    }
+}
+
+fn fencer_mut(message: &mut Message, side: Side) -> &mut Fencer {
+    match side {
+        Side::Right => &mut message.right_fencer,
+        Side::Left => &mut message.left_fencer,
+    }
+}
+
+fn score(fencer: &mut Fencer) {
+    fencer.score = Some(fencer.score.unwrap_or(0).saturating_add(1));
+    fencer.light = Some(true);
+}
+
+/// Returns the fencer ahead on the score or, on a tie, the fencer with priority.
+pub(crate) fn winner(message: &Message) -> Option<Side> {
+    let right = message.right_fencer.score.unwrap_or(0);
+    let left = message.left_fencer.score.unwrap_or(0);
+
+    if right > left {
+        Some(Side::Right)
+    } else if left > right {
+        Some(Side::Left)
+    } else {
+        match message.priority {
+            Some(Priority::Right) => Some(Side::Right),
+            Some(Priority::Left) => Some(Side::Left),
+            _ => None,
+        }
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_impairment() {
+        let frames = crate::fixtures::bout_script("1", 7).compile().unwrap();
+
+        assert_eq!(Impairment::default().apply(&frames), frames);
+        let lossy = Impairment { loss: 0.3, seed: 1, ..Impairment::default() }.apply(&frames);
+        assert!(lossy.len() < frames.len() && !lossy.is_empty());
+        assert!(Impairment { loss: 1.0, ..Impairment::default() }.apply(&frames).is_empty());
+        assert_eq!(Impairment { duplication: 1.0, ..Impairment::default() }.apply(&frames).len(), frames.len() * 2);
+
+        let delayed = Impairment { delay_ms: 100, ..Impairment::default() }.apply(&frames);
+        assert_eq!(delayed[0].at_ms, frames[0].at_ms + 100);
+
+        let shuffled = Impairment { reordering: 0.5, jitter_ms: 200, seed: 3, ..Impairment::default() }.apply(&frames);
+        let order: Vec<&Message> = shuffled.iter().map(|frame| &frame.message).collect();
+        assert_eq!(order.len(), frames.len());
+        assert!(order.iter().zip(&frames).any(|(received, sent)| **received != sent.message));
+        assert!(shuffled.windows(2).all(|pair| pair[0].at_ms <= pair[1].at_ms));
+    }
+
+    #[test]
+    fn test_compile_is_deterministic() {
+        let script = BoutScript::parse(
+            "weapon S\n3:00 fence\n2:00 double\n2:00 wait 30\n2:00 fence\n1:00 red right\n0:00 end",
+        )
+        .unwrap();
+
+        let frames = script.compile().unwrap();
+        assert_eq!(frames, script.compile().unwrap());
+
+        let times: Vec<u64> = frames.iter().map(|f| f.at_ms).collect();
+        assert_eq!(times, vec![0, 0, 60_000, 90_000, 150_000, 210_000]);
+
+        let last = &frames.last().unwrap().message;
+        assert_eq!(last.left_fencer.score, Some(2));
+        assert_eq!(last.left_fencer.status, Some(FencerStatus::Victory));
+        assert_eq!(last.right_fencer.status, Some(FencerStatus::Defeat));
+    }
+
+    #[test]
+    fn test_compiled_messages_roundtrip() {
+        let script = BoutScript::parse("piste 4\nright 1 FRA A\nleft 2 ITA B\n3:00 fence\n2:43 touch right").unwrap();
+
+        for frame in script.compile().unwrap() {
+            let text = frame.message.to_string();
+            assert_eq!(Message::try_from_strict(&text).unwrap(), frame.message);
+        }
+    }
+
+    #[test]
+    fn test_script_errors() {
+        assert!(matches!(
+            BoutScript::parse("2:00 lunge"),
+            Err(ScriptError::InvalidLine { line_number: 1, .. })
+        ));
+
+        let script = BoutScript::parse("2:00 fence\n2:10 halt").unwrap();
+        assert!(matches!(script.compile(), Err(ScriptError::ClockReversed { step: 2, .. })));
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::BTreeMap;
+
+use crate::enums::{ApparatusState, Side};
+use crate::message::Message;
+use crate::phase::BREAK_SECONDS;
+use crate::piste::PisteId;
+use crate::utils::clock_millis;
+
+/// Length of an injury timeout, in milliseconds.
+pub const INJURY_TIMEOUT_MS: u64 = 10 * 60_000;
+
+/// What a secondary countdown is timing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimerKind {
+    /// Break between two periods.
+    Break,
+    /// Injury timeout granted to a fencer.
+    InjuryTimeout(Side),
+}
+
+/// Countdown running beside the bout clock, in wall-clock time.
+///
+/// Apparatus only send the bout clock; break and injury timeout countdowns
+/// are started from the state and medical fields of the frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecondaryTimer {
+    /// What is being timed.
+    pub kind: TimerKind,
+    /// Wall time at which the countdown started, in milliseconds.
+    pub started_ms: u64,
+    /// Length of the countdown, in milliseconds.
+    pub duration_ms: u64,
+}
+
+impl SecondaryTimer {
+    /// Returns the time left at wall time `now_ms`, in milliseconds.
+    pub fn remaining_ms(&self, now_ms: u64) -> u64 {
+        self.duration_ms.saturating_sub(now_ms.saturating_sub(self.started_ms))
+    }
+
+    /// Returns `true` once the countdown has run out.
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        self.remaining_ms(now_ms) == 0
+    }
+}
+
+/// Relation between the bout clock of an apparatus and the wall clock.
+///
+/// While the bout clock runs, a reading of `remaining_ms` on the apparatus
+/// happens at wall time `offset_ms - rate * remaining_ms`: `offset_ms` is the
+/// wall time at which the running clock reaches `0:00`, and `rate` is the
+/// number of wall milliseconds per apparatus millisecond.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClockModel {
+    /// Wall time in milliseconds at which the running bout clock reads `0:00`.
+    pub offset_ms: f64,
+    /// Wall milliseconds elapsed per apparatus millisecond.
+    pub rate: f64,
+}
+
+impl ClockModel {
+    /// Returns the drift of the apparatus clock in parts per million.
+    ///
+    /// A positive drift means the apparatus clock runs slow.
+    pub fn drift_ppm(&self) -> f64 {
+        (self.rate - 1.0) * 1_000_000.0
+    }
+
+    /// Returns the wall time in milliseconds at which the running clock read `remaining_ms`.
+    pub fn wall_time_ms(&self, remaining_ms: u64) -> f64 {
+        self.offset_ms - self.rate * remaining_ms as f64
+    }
+
+    /// Returns the wall time at which the running clock read a `time` value
+    /// such as `1:23` or `0:09.45`, or `None` if the value is not a clock.
+    pub fn wall_time_of(&self, time: &str) -> Option<f64> {
+        clock_millis(time).map(|remaining_ms| self.wall_time_ms(remaining_ms))
+    }
+}
+
+/// Running sums of one stretch of uninterrupted clock.
+///
+/// Receipt times are kept relative to the first sample to stay precise in `f64`.
+#[derive(Debug, Clone, Default)]
+struct Segment {
+    origin_ms: u64,
+    count: f64,
+    sum_x: f64,
+    sum_y: f64,
+    sum_xx: f64,
+    sum_xy: f64,
+}
+
+impl Segment {
+    fn start(receipt_ms: u64, remaining_ms: u64) -> Self {
+        let mut segment = Segment {
+            origin_ms: receipt_ms,
+            ..Segment::default()
+        };
+        segment.add(receipt_ms, remaining_ms);
+        segment
+    }
+
+    fn add(&mut self, receipt_ms: u64, remaining_ms: u64) {
+        let x = remaining_ms as f64;
+        let y = receipt_ms as f64 - self.origin_ms as f64;
+        self.count += 1.0;
+        self.sum_x += x;
+        self.sum_y += y;
+        self.sum_xx += x * x;
+        self.sum_xy += x * y;
+    }
+
+    /// Centered sums of squares and products.
+    fn spread(&self) -> (f64, f64) {
+        if self.count < 2.0 {
+            return (0.0, 0.0);
+        }
+        (
+            self.sum_xx - self.sum_x * self.sum_x / self.count,
+            self.sum_xy - self.sum_x * self.sum_y / self.count,
+        )
+    }
+
+    fn offset_ms(&self, rate: f64) -> f64 {
+        self.origin_ms as f64 + (self.sum_y + rate * self.sum_x) / self.count
+    }
+}
+
+/// Clock correlation of a single piste.
+#[derive(Debug, Clone, Default)]
+struct PisteClock {
+    last_remaining_ms: Option<u64>,
+    segment: Option<Segment>,
+    last_segment: Option<Segment>,
+    pooled_xx: f64,
+    pooled_xy: f64,
+    samples: usize,
+    state: Option<ApparatusState>,
+    medical: [Option<u8>; 2],
+    timer: Option<SecondaryTimer>,
+}
+
+impl PisteClock {
+    /// Starts or stops the secondary countdown on state and medical changes.
+    fn observe_timers(&mut self, receipt_ms: u64, message: &Message) {
+        let start = |kind, duration_ms| SecondaryTimer {
+            kind,
+            started_ms: receipt_ms,
+            duration_ms,
+        };
+        let clock_out = message.time.as_deref().and_then(clock_millis) == Some(0) || self.last_remaining_ms == Some(0);
+        let paused = message.state == Some(ApparatusState::Pause);
+
+        if message.state == Some(ApparatusState::Fencing) {
+            self.timer = None;
+        } else if paused && self.state != Some(ApparatusState::Pause) && clock_out {
+            self.timer = Some(start(TimerKind::Break, u64::from(BREAK_SECONDS) * 1000));
+        }
+        for (index, side) in [Side::Right, Side::Left].into_iter().enumerate() {
+            let medical = message.fencer(side).medical;
+            if medical.unwrap_or(0) > self.medical[index].unwrap_or(0) {
+                self.timer = Some(start(TimerKind::InjuryTimeout(side), INJURY_TIMEOUT_MS));
+            }
+            self.medical[index] = medical;
+        }
+        self.state = message.state.clone();
+    }
+
+    fn observe(&mut self, receipt_ms: u64, remaining_ms: u64, running: bool) {
+        let continues = self.last_remaining_ms.is_some_and(|last| remaining_ms < last);
+        self.last_remaining_ms = Some(remaining_ms);
+
+        match self.segment.as_mut() {
+            Some(segment) if continues => {
+                segment.add(receipt_ms, remaining_ms);
+                self.samples += 1;
+            }
+            _ => {
+                self.close();
+                if running {
+                    self.segment = Some(Segment::start(receipt_ms, remaining_ms));
+                    self.samples += 1;
+                }
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(segment) = self.segment.take() {
+            if segment.count >= 2.0 {
+                let (xx, xy) = segment.spread();
+                self.pooled_xx += xx;
+                self.pooled_xy += xy;
+                self.last_segment = Some(segment);
+            }
+        }
+    }
+
+    fn model(&self) -> Option<ClockModel> {
+        let (mut xx, mut xy) = (self.pooled_xx, self.pooled_xy);
+        let current = self.segment.as_ref().filter(|s| s.count >= 2.0);
+        if let Some(segment) = current {
+            let (s_xx, s_xy) = segment.spread();
+            xx += s_xx;
+            xy += s_xy;
+        }
+        if xx <= 0.0 {
+            return None;
+        }
+
+        let rate = -xy / xx;
+        let segment = current.or(self.last_segment.as_ref())?;
+        Some(ClockModel {
+            offset_ms: segment.offset_ms(rate),
+            rate,
+        })
+    }
+}
+
+/// Estimates, per piste, how the apparatus bout clock relates to the wall clock.
+///
+/// Each message is fed with the time it was received. While the bout clock runs
+/// down, receipt times and clock readings fall on a line; the estimator fits
+/// that line over every stretch of running clock and pools the stretches to
+/// estimate the drift. Stretches end when the clock stops or is reset, as
+/// between actions or bouts, so halts do not bias the fit.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::skew::ClockSkewEstimator;
+///
+/// let info = |time: &str| {
+///     Message::try_from(format!("|EFP1.1|INFO|17|fm-eq|1|A32|12||{}|||E||F|%|%|%|", time)).unwrap()
+/// };
+///
+/// let mut estimator = ClockSkewEstimator::new();
+/// estimator.observe(1_000_000, &info("3:00"));
+/// estimator.observe(1_010_000, &info("2:50"));
+/// estimator.observe(1_030_000, &info("2:30"));
+///
+/// let model = estimator.model("17").unwrap();
+/// assert_eq!(model.drift_ppm().round(), 0.0);
+/// assert_eq!(model.offset_ms.round(), 1_180_000.0);
+/// assert_eq!(model.wall_time_of("2:00").unwrap().round(), 1_060_000.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClockSkewEstimator {
+    pistes: BTreeMap<PisteId, PisteClock>,
+}
+
+impl ClockSkewEstimator {
+    /// Creates an estimator without any observation.
+    pub fn new() -> Self {
+        ClockSkewEstimator::default()
+    }
+
+    /// Records a message received at `receipt_ms`, in milliseconds of wall clock.
+    ///
+    /// Messages without a readable `time` field only update the secondary
+    /// countdowns.
+    pub fn observe(&mut self, receipt_ms: u64, message: &Message) {
+        if !message.carries_bout() {
+            return;
+        }
+        let clock = self.pistes.entry(PisteId::from(message.piste.as_str())).or_default();
+        clock.observe_timers(receipt_ms, message);
+        let Some(remaining_ms) = message.time.as_deref().and_then(clock_millis) else {
+            return;
+        };
+
+        let running = message.state == Some(ApparatusState::Fencing);
+        clock.observe(receipt_ms, remaining_ms, running);
+    }
+
+    /// Returns the countdown running beside the bout clock of a piste: the
+    /// break after a period, started when the apparatus pauses with the clock
+    /// run out, or the injury timeout of a fencer, started when their medical
+    /// intervention count goes up. Either is cancelled when fencing resumes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::message::Message;
+    /// use cyrano::skew::{ClockSkewEstimator, TimerKind};
+    ///
+    /// let info = |state: &str, time: &str| {
+    ///     Message::try_from(format!("|EFP1.1|INFO|17|fm-eq|1|A32|12||{}|||E||{}|%|%|%|", time, state)).unwrap()
+    /// };
+    ///
+    /// let mut estimator = ClockSkewEstimator::new();
+    /// estimator.observe(1_000, &info("F", "0:01"));
+    /// estimator.observe(2_000, &info("H", "0:00"));
+    /// estimator.observe(5_000, &info("P", "0:00"));
+    ///
+    /// let timer = estimator.secondary_timer("17").unwrap();
+    /// assert_eq!(timer.kind, TimerKind::Break);
+    /// assert_eq!(timer.remaining_ms(20_000), 45_000);
+    /// ```
+    pub fn secondary_timer(&self, piste: &str) -> Option<&SecondaryTimer> {
+        self.pistes.get(&PisteId::from(piste)).and_then(|clock| clock.timer.as_ref())
+    }
+
+    /// Returns the clock model of a piste, once a stretch of running clock
+    /// has been observed with at least two readings.
+    pub fn model(&self, piste: &str) -> Option<ClockModel> {
+        self.pistes.get(&PisteId::from(piste)).and_then(PisteClock::model)
+    }
+
+    /// Returns the number of running clock readings observed on a piste.
+    pub fn samples(&self, piste: &str) -> usize {
+        self.pistes.get(&PisteId::from(piste)).map_or(0, |p| p.samples)
+    }
+
+    /// Iterates over the pistes with a clock model, in natural order.
+    pub fn models(&self) -> impl Iterator<Item = (&PisteId, ClockModel)> {
+        self.pistes
+            .iter()
+            .filter_map(|(piste, clock)| clock.model().map(|model| (piste, model)))
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn info(state: &str, time: &str) -> Message {
+        let raw = format!("|EFP1.1|INFO|3|fm-eq|1|A32|12||{}|||E||{}|%|%|%|", time, state);
+        Message::try_from(raw).unwrap()
+    }
+
+    #[test]
+    fn test_drift_pooled_over_halts() {
+        let mut estimator = ClockSkewEstimator::new();
+        // The apparatus clock runs 1% slow: 10 s of clock take 10.1 s of wall time.
+        estimator.observe(0, &info("F", "3:00"));
+        estimator.observe(10_100, &info("H", "2:50"));
+        estimator.observe(40_000, &info("H", "2:50"));
+        estimator.observe(45_000, &info("F", "2:50"));
+        estimator.observe(55_100, &info("F", "2:40"));
+        estimator.observe(65_200, &info("H", "2:30"));
+
+        let model = estimator.model("3").unwrap();
+        assert!((model.drift_ppm() - 10_000.0).abs() < 1.0);
+        assert!((model.wall_time_of("2:45").unwrap() - 50_050.0).abs() < 1.0);
+        assert_eq!(estimator.samples("3"), 5);
+        assert!(estimator.model("4").is_none());
+    }
+
+    #[test]
+    fn test_injury_timeout() {
+        let with_medical = |state: &str, medical: u8| {
+            let raw = format!(
+                "|EFP1.1|INFO|3|fm-eq|1|A32|12||2:10|||E||{}|%|28|P.Martin|FRA|4|U|0|0|0|0|0|%|32|B. Panini|ITA|3|U|0|0|0|0|{}|%|",
+                state, medical
+            );
+            Message::try_from(raw).unwrap()
+        };
+        let mut estimator = ClockSkewEstimator::new();
+        estimator.observe(0, &with_medical("H", 0));
+        assert!(estimator.secondary_timer("3").is_none());
+        estimator.observe(1_000, &with_medical("H", 1));
+        let timer = *estimator.secondary_timer("3").unwrap();
+        assert_eq!(timer.kind, TimerKind::InjuryTimeout(Side::Left));
+        assert_eq!(timer.remaining_ms(61_000), 540_000);
+        assert!(timer.is_expired(601_000));
+
+        // Pausing with time left on the clock is not a break.
+        estimator.observe(2_000, &with_medical("P", 1));
+        assert_eq!(estimator.secondary_timer("3"), Some(&timer));
+        estimator.observe(300_000, &with_medical("F", 1));
+        assert!(estimator.secondary_timer("3").is_none());
+    }
+
+    #[test]
+    fn test_clock_millis() {
+        assert_eq!(clock_millis("0:09.45"), Some(9_450));
+        assert_eq!(clock_millis("0:09.4"), Some(9_400));
+        assert_eq!(clock_millis("1:02:30"), Some(3_750_000));
+        assert_eq!(clock_millis("9."), None);
+    }
+}
@@ -0,0 +1,1237 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use super::enums::{ApparatusState, CompetitionType, PCard, Priority, Reserve, Side, Weapon};
+use super::error::JournalError;
+use super::fencer::Fencer;
+use super::journal::{self, JournalEntry};
+use super::message::Message;
+use super::phase::relay_position;
+use super::rules::{PriorityReason, RuleProfile};
+use super::piste::PisteId;
+use super::summary::{BoutRecorder, BoutSummary};
+use super::utils::clock_seconds;
+
+/// Most touches a fencer can score between two successive frames of a bout.
+///
+/// A larger jump means frames were lost on the link (see [`MatchEvent::Gap`]).
+pub const MAX_TOUCHES_PER_FRAME: u8 = 1;
+
+/// What must have happened during frames lost between two messages of a bout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gap {
+    /// Touches scored by the right fencer in between.
+    pub right_touches: u8,
+    /// Touches scored by the left fencer in between.
+    pub left_touches: u8,
+    /// Bout clock before and after, when it was reset while fencing.
+    pub clock: Option<(String, String)>,
+}
+
+impl Gap {
+    /// Compares two successive messages of the same bout and returns the gap
+    /// between them, if the transition is not plausible.
+    ///
+    /// A gap is reported when a score jumps by more than
+    /// [`MAX_TOUCHES_PER_FRAME`], or when the clock goes back up while the
+    /// apparatus reports fencing in both frames.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::message::Message;
+    /// use cyrano::state::Gap;
+    ///
+    /// let before = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||H|%|28|P.Martin||4|U|%|32|B. Panini||2|U|%|").unwrap();
+    /// let after = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||H|%|28|P.Martin||7|U|%|32|B. Panini||2|U|%|").unwrap();
+    ///
+    /// let gap = Gap::between(&before, &after).unwrap();
+    /// assert_eq!(gap.right_touches, 3);
+    /// assert_eq!(gap.left_touches, 0);
+    /// ```
+    pub fn between(before: &Message, after: &Message) -> Option<Gap> {
+        let touches = |side: Side| {
+            let from = before.fencer(side).score.unwrap_or(0);
+            after.fencer(side).score.unwrap_or(0).saturating_sub(from)
+        };
+        let right_touches = touches(Side::Right);
+        let left_touches = touches(Side::Left);
+
+        let fencing = |m: &Message| m.state == Some(ApparatusState::Fencing);
+        let clock = match (&before.time, &after.time) {
+            (Some(from), Some(to)) if fencing(before) && fencing(after) => {
+                match (clock_seconds(from), clock_seconds(to)) {
+                    (Some(a), Some(b)) if b > a => Some((from.to_string(), to.to_string())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        if right_touches > MAX_TOUCHES_PER_FRAME || left_touches > MAX_TOUCHES_PER_FRAME || clock.is_some() {
+            Some(Gap {
+                right_touches,
+                left_touches,
+                clock,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Introduction of a reserve fencer by a team.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Substitution {
+    /// Side of the team bringing in its reserve.
+    pub side: Side,
+    /// Relay during which the reserve was introduced, if the frame gave one.
+    pub relay: Option<u8>,
+    /// Team position replaced by the reserve (1 to 6, see
+    /// [`RELAY_ORDER`](crate::phase::RELAY_ORDER)), if the relay is known.
+    pub replaced: Option<u8>,
+}
+
+/// Priority given to a fencer, with the moment of the bout it was given at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PriorityAssignment {
+    /// Fencer given priority.
+    pub side: Side,
+    /// Why it was given.
+    pub reason: PriorityReason,
+    /// Bout clock when it was given, if the frame carried one.
+    pub time: Option<String>,
+    /// Score of the right fencer when it was given.
+    pub right_score: Option<u8>,
+    /// Score of the left fencer when it was given.
+    pub left_score: Option<u8>,
+}
+
+/// Change observed on a piste between two successive bout messages.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MatchEvent {
+    /// First message applied to a state resumed from a snapshot (see
+    /// [`MatchState::resume_from`]). Comes before any other event; the changes
+    /// since the snapshot follow, without a `Gap`.
+    Resumed,
+    /// A new bout was loaded on the piste (different phase, match or fencers).
+    BoutStarted,
+    /// Frames were lost since the previous message; the changes that follow
+    /// summarize several actions and should not be animated as one.
+    Gap(Gap),
+    /// The apparatus state changed.
+    StateChanged {
+        from: Option<ApparatusState>,
+        to: Option<ApparatusState>,
+    },
+    /// A fencer's score changed.
+    ScoreChanged {
+        side: Side,
+        from: Option<u8>,
+        to: Option<u8>,
+    },
+    /// Both fencers scored with the same action, which only épée allows.
+    /// Replaces the two `ScoreChanged` events in épée bouts; in other
+    /// weapons, the scores change separately. Carries the new scores.
+    DoubleTouch { right_score: u8, left_score: u8 },
+    /// A fencer's scoring light was switched on or off.
+    LightChanged { side: Side, on: bool },
+    /// A fencer's white (off-target) light was switched on or off.
+    WhiteLightChanged { side: Side, on: bool },
+    /// A fencer hit off target, at foil. Follows the `WhiteLightChanged`
+    /// switching the white light on; white lights have no meaning in other
+    /// weapons and raise no such event.
+    OffTargetHit { side: Side },
+    /// A fencer's cards changed. Carries the new card values.
+    CardsChanged {
+        side: Side,
+        yellow_card: Option<u8>,
+        red_card: Option<u8>,
+        p_card: Option<PCard>,
+    },
+    /// A fencer asked for a video review of the last action.
+    VideoReviewRequested { side: Side },
+    /// The video review asked for by a fencer is over. Carries the video
+    /// appeals the fencer has left; an appeal is used up when the review
+    /// confirms the referee's decision.
+    VideoReviewResolved { side: Side, appeals_left: Option<u8> },
+    /// The priority indicator changed.
+    PriorityChanged {
+        from: Option<Priority>,
+        to: Option<Priority>,
+    },
+    /// Priority was given to a fencer; follows the matching `PriorityChanged`.
+    PriorityAssigned(PriorityAssignment),
+    /// A team introduced its reserve fencer.
+    SubstitutionMade(Substitution),
+    /// A team introduced a reserve again, although FIE rules allow a single
+    /// substitution per team and match. The first substitution is kept.
+    SubstitutionRefused(Substitution),
+    /// The bout clock ran out, ending a period of the bout.
+    PeriodEnded { period: u8 },
+    /// The break after a period started.
+    BreakStarted { after_period: u8 },
+    /// A relay of a team match ended: a team reached the target score of the
+    /// relay, or the next relay was loaded. Carries the team scores at the end.
+    RelayFinished {
+        relay: u8,
+        right_score: Option<u8>,
+        left_score: Option<u8>,
+    },
+    /// A team score went past the target score of the current relay.
+    RelayCapExceeded { side: Side, relay: u8, score: u8, target: u8 },
+    /// The bout reached a final status (victory, defeat, abandonment or exclusion).
+    BoutFinished,
+    /// Summary of the bout, emitted once when it finishes or the apparatus
+    /// reports the end of the bout.
+    BoutSummary(Box<BoutSummary>),
+}
+
+/// Live state of the bout running on a single piste.
+///
+/// The state is folded from the bout messages (INFO, DISP, NEXT, PREV) seen for
+/// the piste; each update reports what changed as a list of [`MatchEvent`]s.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Side;
+/// use cyrano::message::Message;
+/// use cyrano::state::{MatchEvent, MatchState};
+///
+/// let mut state = MatchState::new("17");
+/// state.apply(&Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||F|%|28|P.Martin|FRA|0|U|%|32|B. Panini|ITA|0|U|%|").unwrap());
+/// let events = state.apply(&Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||H|%|28|P.Martin|FRA|1|U|%|32|B. Panini|ITA|0|U|%|").unwrap());
+///
+/// assert!(events.contains(&MatchEvent::ScoreChanged { side: Side::Right, from: Some(0), to: Some(1) }));
+/// assert_eq!(state.score(Side::Right), Some(1));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchState {
+    piste: String,
+    message: Option<Message>,
+    finished: bool,
+    period: u8,
+    period_ended: bool,
+    on_break: bool,
+    relay_finished: bool,
+    recorder: BoutRecorder,
+    substitutions: [Option<Substitution>; 2],
+    reserves: [bool; 2],
+    #[cfg_attr(feature = "serde", serde(default))]
+    rules: RuleProfile,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    resuming: bool,
+}
+
+impl MatchState {
+    /// Creates an empty state for the given piste.
+    pub fn new(piste: impl Into<String>) -> Self {
+        MatchState {
+            piste: piste.into(),
+            message: None,
+            finished: false,
+            period: 1,
+            period_ended: false,
+            on_break: false,
+            relay_finished: false,
+            recorder: BoutRecorder::default(),
+            substitutions: [None, None],
+            reserves: [false, false],
+            rules: RuleProfile::default(),
+            resuming: false,
+        }
+    }
+
+    /// Resumes tracking from a snapshot of the state, such as one saved
+    /// (with the `serde` feature) before the software restarted.
+    ///
+    /// The next message applied is reconciled with the snapshot: it reports
+    /// [`MatchEvent::Resumed`], then either [`MatchEvent::BoutStarted`] if the
+    /// piste moved on to another bout, or the changes of the bout since the
+    /// snapshot, which are not taken for frames lost on the link.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::enums::Side;
+    /// use cyrano::message::Message;
+    /// use cyrano::state::{MatchEvent, MatchState};
+    ///
+    /// let info = |right: u8| {
+    ///     Message::try_from(format!("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||H|%|28|P.Martin|FRA|{}|U|%|32|B. Panini|ITA|2|U|%|", right)).unwrap()
+    /// };
+    /// let mut state = MatchState::new("17");
+    /// state.apply(&info(4));
+    /// let snapshot = state.clone();
+    ///
+    /// let mut resumed = MatchState::resume_from(snapshot);
+    /// let events = resumed.apply(&info(7));
+    /// assert_eq!(events[0], MatchEvent::Resumed);
+    /// assert_eq!(events[1], MatchEvent::ScoreChanged { side: Side::Right, from: Some(4), to: Some(7) });
+    /// ```
+    pub fn resume_from(snapshot: MatchState) -> Self {
+        MatchState {
+            resuming: true,
+            ..snapshot
+        }
+    }
+
+    /// Reconstructs the state of a piste at a past instant from a journal, e.g.
+    /// to know the score at the moment a video replay is about.
+    ///
+    /// Only the entries of the piste applied at or before `timestamp_ms` are
+    /// replayed, under the rules the tracker was built with; see
+    /// [`PisteManager::at`].
+    ///
+    /// # Returns
+    ///
+    /// The state of the piste, `None` if it had not been seen by then.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JournalError::InvalidFrame` if a journaled frame of the piste
+    /// cannot be parsed.
+    pub fn at<'a>(
+        entries: impl IntoIterator<Item = &'a JournalEntry>,
+        piste: &str,
+        rules: RuleProfile,
+        timestamp_ms: u64,
+    ) -> Result<Option<MatchState>, JournalError> {
+        let id = PisteId::from(piste);
+        let mut state = MatchState::new(piste).rules(rules);
+        let mut seen = false;
+        for entry in entries
+            .into_iter()
+            .filter(|entry| entry.timestamp_ms <= timestamp_ms && entry.piste().as_ref() == Some(&id))
+        {
+            let message = entry
+                .message()
+                .map_err(|error| JournalError::InvalidFrame { seq: entry.seq, error })?;
+            if message.carries_bout() {
+                state.apply(&message);
+                seen = true;
+            }
+        }
+        Ok(seen.then_some(state))
+    }
+
+    /// Sets the rules the bouts of the piste are fenced under, FIE senior
+    /// rules by default.
+    pub fn rules(mut self, rules: RuleProfile) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Returns the rules the bouts of the piste are fenced under.
+    pub fn rule_profile(&self) -> &RuleProfile {
+        &self.rules
+    }
+
+    /// Returns the piste identifier.
+    pub fn piste(&self) -> &str {
+        &self.piste
+    }
+
+    /// Returns the last bout message applied to the state.
+    pub fn message(&self) -> Option<&Message> {
+        self.message.as_ref()
+    }
+
+    /// Returns the competition identifier of the current bout.
+    pub fn competition_id(&self) -> Option<&str> {
+        self.message.as_ref().map(|m| m.competition_id.as_str())
+    }
+
+    /// Returns the current apparatus state.
+    pub fn state(&self) -> Option<&ApparatusState> {
+        self.message.as_ref().and_then(|m| m.state.as_ref())
+    }
+
+    /// Returns the fencer on the given side.
+    pub fn fencer(&self, side: Side) -> Option<&Fencer> {
+        self.message.as_ref().map(|m| m.fencer(side))
+    }
+
+    /// Returns the score of the fencer on the given side.
+    pub fn score(&self, side: Side) -> Option<u8> {
+        self.fencer(side).and_then(|f| f.score)
+    }
+
+    /// Returns the period in progress, from 1.
+    ///
+    /// The period changes when the clock is set again for the next period,
+    /// after the break if there is one; extra time after a tie in the last
+    /// period counts as one more period.
+    pub fn current_period(&self) -> Option<u8> {
+        self.message.as_ref().map(|_| self.period)
+    }
+
+    /// Returns `true` between the end of a period and the start of the next,
+    /// once the apparatus shows the break.
+    pub fn is_on_break(&self) -> bool {
+        self.on_break
+    }
+
+    /// Returns the relay in progress (1 to 9) in a team match.
+    pub fn relay(&self) -> Option<u8> {
+        self.message.as_ref().and_then(Message::relay)
+    }
+
+    /// Returns the score ending the current bout, or the cumulative score
+    /// ending the current relay in a team match (5, 10, ... 45).
+    pub fn target_score(&self) -> Option<u8> {
+        self.message.as_ref().and_then(|m| self.rules.target_score(m))
+    }
+
+    /// Returns the winner of the current bout under the rules of the piste.
+    ///
+    /// Besides the cases of [`RuleProfile::winner`], a bout is won on time by
+    /// the fencer ahead once its last period ended, and a tie by the fencer
+    /// with priority once the extra minute ended. Returns `None` while the
+    /// bout goes on, and for a tie in a profile without priority minute,
+    /// which both fencers lose.
+    pub fn winner(&self) -> Option<Side> {
+        let message = self.message.as_ref()?;
+        if let Some(side) = self.rules.winner(message) {
+            return Some(side);
+        }
+
+        let periods = self.rules.periods(message).unwrap_or(1);
+        if !self.period_ended || self.period < periods {
+            return None;
+        }
+        let (right, left) = (message.right_fencer.score.unwrap_or(0), message.left_fencer.score.unwrap_or(0));
+        match right.cmp(&left) {
+            Ordering::Greater => Some(Side::Right),
+            Ordering::Less => Some(Side::Left),
+            Ordering::Equal if self.rules.priority_minute && self.period > periods => match message.priority {
+                Some(Priority::Right) => Some(Side::Right),
+                Some(Priority::Left) => Some(Side::Left),
+                _ => None,
+            },
+            Ordering::Equal => None,
+        }
+    }
+
+    /// Returns the reserve substitution made by a team in the current match.
+    ///
+    /// Substitutions last for the whole team match, across its relays.
+    pub fn substitution(&self, side: Side) -> Option<&Substitution> {
+        self.substitutions[side_index(side)].as_ref()
+    }
+
+    /// Returns `true` once the current bout has reached a final status.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the summary of the current bout so far.
+    ///
+    /// The tracker also emits [`MatchEvent::BoutSummary`] once, when the bout
+    /// finishes; this gives the same data for a bout still in progress.
+    pub fn summary(&self) -> Option<BoutSummary> {
+        self.message.as_ref().map(|m| self.recorder.summary(m))
+    }
+
+    /// Applies a message to the state and returns the resulting events.
+    ///
+    /// Messages that do not carry bout data (HELLO, ACK, NAK) are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message received for this piste
+    ///
+    /// # Returns
+    ///
+    /// The list of changes, in a stable order: resumption, bout change or gap first, then state,
+    /// double touch, fencer fields and reserve substitutions (right before left), priority,
+    /// period boundaries, relay completion and finally bout completion followed
+    /// by its summary.
+    /// The end of a relay left without reaching its target is reported before
+    /// the next relay starts.
+    pub fn apply(&mut self, message: &Message) -> Vec<MatchEvent> {
+        if !message.carries_bout() {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        let resumed = std::mem::take(&mut self.resuming);
+        if resumed {
+            events.push(MatchEvent::Resumed);
+        }
+
+        let previous = match self.message.take() {
+            Some(previous) if same_bout(&previous, message) => Some(previous),
+            other => {
+                match other {
+                    Some(previous) if same_match(&previous, message) => {
+                        if let Some(relay) = previous.relay().filter(|_| !self.relay_finished) {
+                            events.push(relay_finished(relay, &previous));
+                        }
+                    }
+                    _ => {
+                        self.substitutions = [None, None];
+                        self.reserves = [false, false];
+                    }
+                }
+                events.push(MatchEvent::BoutStarted);
+                self.finished = false;
+                self.period = 1;
+                self.period_ended = false;
+                self.on_break = false;
+                self.relay_finished = false;
+                self.recorder = BoutRecorder::default();
+                None
+            }
+        };
+
+        if let Some(gap) = previous.as_ref().filter(|_| !resumed).and_then(|p| Gap::between(p, message)) {
+            events.push(MatchEvent::Gap(gap));
+        }
+
+        let empty = Message::new(message.command.clone(), "", "");
+        let previous_ref = previous.as_ref().unwrap_or(&empty);
+
+        if previous_ref.state != message.state {
+            events.push(MatchEvent::StateChanged {
+                from: previous_ref.state.clone(),
+                to: message.state.clone(),
+            });
+        }
+
+        let weapon = message.weapon.as_ref().or(previous_ref.weapon.as_ref());
+        let double = double_touch(previous_ref, message).filter(|_| weapon == Some(&Weapon::Epee));
+        if let Some((right_score, left_score)) = double {
+            events.push(MatchEvent::DoubleTouch { right_score, left_score });
+        }
+        let foil = weapon == Some(&Weapon::Foil);
+        for side in [Side::Right, Side::Left] {
+            diff_fencer(side, previous_ref.fencer(side), message.fencer(side), double.is_none(), foil, &mut events);
+        }
+        for side in [Side::Right, Side::Left] {
+            if let Some(event) = self.substitute(side, message) {
+                events.push(event);
+            }
+        }
+
+        if previous_ref.priority != message.priority {
+            events.push(MatchEvent::PriorityChanged {
+                from: previous_ref.priority.clone(),
+                to: message.priority.clone(),
+            });
+            if let Some(side) = priority_side(&message.priority).filter(|_| priority_side(&previous_ref.priority).is_none()) {
+                let right_score = message.right_fencer.score;
+                let left_score = message.left_fencer.score;
+                events.push(MatchEvent::PriorityAssigned(PriorityAssignment {
+                    side,
+                    reason: if right_score == left_score { PriorityReason::Tie } else { PriorityReason::Other },
+                    time: message.time.as_ref().map(|time| time.to_string()),
+                    right_score,
+                    left_score,
+                }));
+            }
+        }
+
+        if !self.finished {
+            self.track_period(previous_ref, message, &mut events);
+        }
+
+        if let (Some(relay), Some(target)) = (message.relay(), self.rules.target_score(message)) {
+            for side in [Side::Right, Side::Left] {
+                let score = message.fencer(side).score.unwrap_or(0);
+                if score > target && previous_ref.fencer(side).score != message.fencer(side).score {
+                    events.push(MatchEvent::RelayCapExceeded { side, relay, score, target });
+                }
+            }
+            let reached = [Side::Right, Side::Left]
+                .iter()
+                .any(|side| message.fencer(*side).score.is_some_and(|score| score >= target));
+            if reached && !self.relay_finished {
+                self.relay_finished = true;
+                events.push(relay_finished(relay, message));
+            }
+        }
+
+        let finished = [Side::Right, Side::Left].iter().any(|side| {
+            message
+                .fencer(*side)
+                .status
+                .as_ref()
+                .is_some_and(|s| s.is_final())
+        });
+        if finished && !self.finished {
+            events.push(MatchEvent::BoutFinished);
+        }
+        self.finished = self.finished || finished;
+
+        self.recorder.observe(message);
+        if let Some(summary) = self.recorder.finish(message, self.finished) {
+            events.push(MatchEvent::BoutSummary(Box::new(summary)));
+        }
+
+        self.message = Some(message.clone());
+        events
+    }
+
+    /// Follows the bout clock across periods and breaks.
+    ///
+    /// A period ends when the clock runs down to zero. If periods remain, the
+    /// break starts when the apparatus pauses or sets the clock to the break
+    /// length; the next period starts when the clock is set again past it.
+    fn track_period(&mut self, previous: &Message, message: &Message, events: &mut Vec<MatchEvent>) {
+        let seconds = |m: &Message| m.time.as_deref().and_then(clock_seconds);
+        let (before, after) = (seconds(previous), seconds(message));
+
+        if !self.period_ended {
+            if before.is_some_and(|s| s > 0) && after == Some(0) {
+                self.period_ended = true;
+                events.push(MatchEvent::PeriodEnded { period: self.period });
+            }
+            return;
+        }
+
+        let reset = matches!((before, after), (Some(before), Some(after)) if after > before);
+        let paused = message.state == Some(ApparatusState::Pause);
+        let last = self.period >= self.rules.periods(message).unwrap_or(1);
+        let break_length = after.is_some_and(|s| s <= self.rules.break_seconds);
+        if !last && !self.on_break && (paused || (reset && break_length)) {
+            self.on_break = true;
+            events.push(MatchEvent::BreakStarted { after_period: self.period });
+        } else if reset && (last || !break_length) {
+            self.period += 1;
+            self.period_ended = false;
+            self.on_break = false;
+        }
+    }
+
+    /// Records the introduction of a reserve, on the frame where the team's
+    /// reserve flag is first raised.
+    fn substitute(&mut self, side: Side, message: &Message) -> Option<MatchEvent> {
+        if message.competition_type != Some(CompetitionType::Team) {
+            return None;
+        }
+        let index = side_index(side);
+        let introduced = message.fencer(side).reserve == Some(Reserve::Introduce);
+        let raised = introduced && !self.reserves[index];
+        self.reserves[index] = introduced;
+        if !raised {
+            return None;
+        }
+
+        let relay = message.relay();
+        let substitution = Substitution {
+            side,
+            relay,
+            replaced: relay.and_then(|relay| relay_position(relay, side)),
+        };
+        if self.substitutions[index].is_some() {
+            return Some(MatchEvent::SubstitutionRefused(substitution));
+        }
+        self.substitutions[index] = Some(substitution.clone());
+        Some(MatchEvent::SubstitutionMade(substitution))
+    }
+}
+
+fn priority_side(priority: &Option<Priority>) -> Option<Side> {
+    match priority {
+        Some(Priority::Right) => Some(Side::Right),
+        Some(Priority::Left) => Some(Side::Left),
+        _ => None,
+    }
+}
+
+fn relay_finished(relay: u8, message: &Message) -> MatchEvent {
+    MatchEvent::RelayFinished {
+        relay,
+        right_score: message.right_fencer.score,
+        left_score: message.left_fencer.score,
+    }
+}
+
+fn side_index(side: Side) -> usize {
+    match side {
+        Side::Right => 0,
+        Side::Left => 1,
+    }
+}
+
+/// Returns `true` if both messages describe the same match: the same bout, or
+/// another relay of the same team match.
+fn same_match(a: &Message, b: &Message) -> bool {
+    let team = a.competition_type == Some(CompetitionType::Team) && b.competition_type == Some(CompetitionType::Team);
+    a.competition_id == b.competition_id
+        && a.phase == b.phase
+        && a.pool_tableau == b.pool_tableau
+        && a.match_number == b.match_number
+        && (team || a.round == b.round)
+        && a.right_fencer.id == b.right_fencer.id
+        && a.left_fencer.id == b.left_fencer.id
+}
+
+/// Returns `true` if both messages describe the same bout.
+fn same_bout(a: &Message, b: &Message) -> bool {
+    a.competition_id == b.competition_id
+        && a.phase == b.phase
+        && a.pool_tableau == b.pool_tableau
+        && a.match_number == b.match_number
+        && a.round == b.round
+        && a.right_fencer.id == b.right_fencer.id
+        && a.left_fencer.id == b.left_fencer.id
+}
+
+/// Returns the new scores if both fencers scored exactly one touch between
+/// two messages of a bout.
+pub(crate) fn double_touch(before: &Message, after: &Message) -> Option<(u8, u8)> {
+    let touched = |side: Side| {
+        let (from, to) = (before.fencer(side).score?, after.fencer(side).score?);
+        (to == from.checked_add(1)?).then_some(to)
+    };
+    Some((touched(Side::Right)?, touched(Side::Left)?))
+}
+
+fn diff_fencer(side: Side, before: &Fencer, after: &Fencer, scores: bool, foil: bool, events: &mut Vec<MatchEvent>) {
+    if scores && before.score != after.score {
+        events.push(MatchEvent::ScoreChanged {
+            side,
+            from: before.score,
+            to: after.score,
+        });
+    }
+    if before.light.unwrap_or(false) != after.light.unwrap_or(false) {
+        events.push(MatchEvent::LightChanged {
+            side,
+            on: after.light.unwrap_or(false),
+        });
+    }
+    if before.white_light.unwrap_or(false) != after.white_light.unwrap_or(false) {
+        let on = after.white_light.unwrap_or(false);
+        events.push(MatchEvent::WhiteLightChanged { side, on });
+        if on && foil {
+            events.push(MatchEvent::OffTargetHit { side });
+        }
+    }
+    if before.yellow_card != after.yellow_card
+        || before.red_card != after.red_card
+        || before.p_card != after.p_card
+    {
+        events.push(MatchEvent::CardsChanged {
+            side,
+            yellow_card: after.yellow_card,
+            red_card: after.red_card,
+            p_card: after.p_card.clone(),
+        });
+    }
+    match (before.video_review.unwrap_or(false), after.video_review.unwrap_or(false)) {
+        (false, true) => events.push(MatchEvent::VideoReviewRequested { side }),
+        (true, false) => events.push(MatchEvent::VideoReviewResolved {
+            side,
+            appeals_left: after.video_appeals,
+        }),
+        _ => {}
+    }
+}
+
+/// Live state of every piste seen on the link, keyed by piste identifier.
+///
+/// Pistes are kept in natural order (see [`PisteId`]), so iterating yields
+/// piste 2 before piste 10 and named pistes last.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PisteManager {
+    pistes: BTreeMap<PisteId, MatchState>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    rules: RuleProfile,
+}
+
+impl PisteManager {
+    /// Creates an empty manager.
+    pub fn new() -> Self {
+        PisteManager::default()
+    }
+
+    /// Sets the rules the bouts of new pistes are fenced under, FIE senior
+    /// rules by default.
+    pub fn rules(mut self, rules: RuleProfile) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Resumes tracking every piste of a snapshot; see [`MatchState::resume_from`].
+    pub fn resume_from(snapshot: PisteManager) -> Self {
+        PisteManager {
+            pistes: snapshot
+                .pistes
+                .into_iter()
+                .map(|(piste, state)| (piste, MatchState::resume_from(state)))
+                .collect(),
+            ..snapshot
+        }
+    }
+
+    /// Reconstructs the state of every piste at a past instant from a journal
+    /// (see [`journal`](crate::journal)).
+    ///
+    /// The entries applied at or before `timestamp_ms` are replayed from the
+    /// start of the journal under `rules`, which must be the rules the tracker
+    /// was built with, so the state is exactly the one it had at that instant.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JournalError::InvalidFrame` if a journaled frame cannot be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use cyrano::enums::Side;
+    /// use cyrano::journal::{Journaled, JournalWriter};
+    /// use cyrano::message::Message;
+    /// use cyrano::rules::RuleProfile;
+    /// use cyrano::state::{MatchState, PisteManager};
+    ///
+    /// let mut pistes = Journaled::new(PisteManager::new(), JournalWriter::new(Vec::new()));
+    /// for (score, at_ms) in [(12, 60_000), (13, 95_000), (14, 130_000)] {
+    ///     let raw = format!("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||F|%|28|P.Martin|FRA|{}|%|32|B. Panini|ITA|13|%|", score);
+    ///     pistes.apply(&Message::try_from(raw).unwrap(), at_ms)?;
+    /// }
+    /// let journal = pistes.into_parts().1.into_inner();
+    /// let entries = cyrano::journal::read(journal.as_slice())?;
+    ///
+    /// let then = PisteManager::at(&entries, RuleProfile::FIE_SENIOR, 100_000)?;
+    /// assert_eq!(then.get("17").unwrap().score(Side::Right), Some(13));
+    /// assert!(MatchState::at(&entries, "17", RuleProfile::FIE_SENIOR, 30_000)?.is_none());
+    /// # Ok::<(), cyrano::error::JournalError>(())
+    /// ```
+    pub fn at<'a>(
+        entries: impl IntoIterator<Item = &'a JournalEntry>,
+        rules: RuleProfile,
+        timestamp_ms: u64,
+    ) -> Result<PisteManager, JournalError> {
+        let mut manager = PisteManager::new().rules(rules);
+        let entries = entries.into_iter().filter(|entry| entry.timestamp_ms <= timestamp_ms);
+        journal::replay(&mut manager, entries, 0)?;
+        Ok(manager)
+    }
+
+    /// Applies a message to the state of its piste and returns the resulting events.
+    pub fn apply(&mut self, message: &Message) -> Vec<MatchEvent> {
+        if !message.carries_bout() {
+            return Vec::new();
+        }
+
+        self.pistes
+            .entry(PisteId::from(message.piste.as_str()))
+            .or_insert_with(|| MatchState::new(message.piste.clone()).rules(self.rules))
+            .apply(message)
+    }
+
+    /// Returns the state of the given piste.
+    pub fn get(&self, piste: &str) -> Option<&MatchState> {
+        self.pistes.get(&PisteId::from(piste))
+    }
+
+    /// Iterates over the identifiers of the known pistes, in natural order.
+    pub fn pistes(&self) -> impl Iterator<Item = &PisteId> {
+        self.pistes.keys()
+    }
+
+    /// Iterates over the state of every known piste, in natural order.
+    pub fn iter(&self) -> impl Iterator<Item = &MatchState> {
+        self.pistes.values()
+    }
+
+    /// Returns the number of known pistes.
+    pub fn len(&self) -> usize {
+        self.pistes.len()
+    }
+
+    /// Returns `true` if no piste has been seen yet.
+    pub fn is_empty(&self) -> bool {
+        self.pistes.is_empty()
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::FencerStatus;
+    use std::convert::TryFrom;
+
+    fn info(state: &str, right: &str, left: &str) -> Message {
+        let raw = format!(
+            "|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||{}|%|28|P.Martin|FRA|{}|%|32|B. Panini|ITA|{}|%|",
+            state, right, left
+        );
+        Message::try_from(raw).unwrap()
+    }
+
+    #[test]
+    fn test_first_message_starts_bout() {
+        let mut state = MatchState::new("17");
+        let events = state.apply(&info("W", "0|U", "0|U"));
+
+        assert_eq!(events[0], MatchEvent::BoutStarted);
+        assert_eq!(state.state(), Some(&ApparatusState::Waiting));
+    }
+
+    #[test]
+    fn test_light_and_score_events() {
+        let mut state = MatchState::new("17");
+        state.apply(&info("F", "0|U|0|0|0|0", "0|U|0|0|0|0"));
+        let events = state.apply(&info("H", "1|U|0|0|1|0", "0|U|0|0|0|0"));
+
+        assert_eq!(
+            events,
+            vec![
+                MatchEvent::StateChanged {
+                    from: Some(ApparatusState::Fencing),
+                    to: Some(ApparatusState::Halt),
+                },
+                MatchEvent::ScoreChanged { side: Side::Right, from: Some(0), to: Some(1) },
+                MatchEvent::LightChanged { side: Side::Right, on: true },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_double_touch_in_epee_only() {
+        let mut state = MatchState::new("17");
+        state.apply(&info("F", "3|U", "2|U"));
+        let events = state.apply(&info("H", "4|U|0|0|1", "3|U|0|0|1"));
+        assert_eq!(events[1], MatchEvent::DoubleTouch { right_score: 4, left_score: 3 });
+        assert!(!events.iter().any(|e| matches!(e, MatchEvent::ScoreChanged { .. })));
+
+        let foil = |right: &str, left: &str| {
+            let raw = format!("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||F||H|%|28|P.Martin|FRA|{}|%|32|B. Panini|ITA|{}|%|", right, left);
+            Message::try_from(raw).unwrap()
+        };
+        let mut state = MatchState::new("17");
+        state.apply(&foil("3|U", "2|U"));
+        let events = state.apply(&foil("4|U", "3|U"));
+        assert_eq!(events.iter().filter(|e| matches!(e, MatchEvent::ScoreChanged { .. })).count(), 2);
+    }
+
+    #[test]
+    fn test_off_target_hit_in_foil_only() {
+        let bout = |weapon: &str, right: &str| {
+            let raw = format!("|EFP1.1|INFO|17|fm-eq|1|A32|12|||||{}||H|%|28|P.Martin|FRA|{}|%|32|B. Panini|ITA|2|U|%|", weapon, right);
+            Message::try_from(raw).unwrap()
+        };
+        let mut state = MatchState::new("17");
+        state.apply(&bout("F", "3|U|0|0|0|0"));
+        let events = state.apply(&bout("F", "3|U|0|0|0|1"));
+        assert_eq!(
+            events,
+            vec![
+                MatchEvent::WhiteLightChanged { side: Side::Right, on: true },
+                MatchEvent::OffTargetHit { side: Side::Right },
+            ]
+        );
+        let events = state.apply(&bout("F", "3|U|0|0|0|0"));
+        assert_eq!(events, vec![MatchEvent::WhiteLightChanged { side: Side::Right, on: false }]);
+
+        let mut state = MatchState::new("17");
+        state.apply(&bout("S", "3|U|0|0|0|0"));
+        let events = state.apply(&bout("S", "3|U|0|0|0|1"));
+        assert!(!events.contains(&MatchEvent::OffTargetHit { side: Side::Right }));
+    }
+
+    #[test]
+    fn test_bout_finished_once() {
+        let mut state = MatchState::new("17");
+        state.apply(&info("F", "14|U", "10|U"));
+        let events = state.apply(&info("E", "15|V", "10|D"));
+        assert!(events.contains(&MatchEvent::BoutFinished));
+        assert!(state.is_finished());
+
+        let events = state.apply(&info("E", "15|V", "10|D"));
+        assert!(events.is_empty());
+        assert_eq!(state.fencer(Side::Left).unwrap().status, Some(FencerStatus::Defeat));
+    }
+
+    #[test]
+    fn test_gap_on_lost_frames() {
+        let mut state = MatchState::new("17");
+        state.apply(&info("H", "3|U", "2|U"));
+        let events = state.apply(&info("H", "4|U", "3|U"));
+        assert!(!events.iter().any(|e| matches!(e, MatchEvent::Gap(_))));
+
+        let events = state.apply(&info("H", "6|U", "3|U"));
+        assert_eq!(
+            events[0],
+            MatchEvent::Gap(Gap { right_touches: 2, left_touches: 0, clock: None })
+        );
+
+        let fencing = |time: &str| {
+            let raw = format!("|EFP1.1|INFO|17|fm-eq|1|A32|12||{}|||E||F|%|28|P.Martin|FRA|6|U|%|32|B. Panini|ITA|3|U|%|", time);
+            Message::try_from(raw).unwrap()
+        };
+        state.apply(&fencing("1:10"));
+        let events = state.apply(&fencing("2:50"));
+        assert_eq!(
+            events[0],
+            MatchEvent::Gap(Gap {
+                right_touches: 0,
+                left_touches: 0,
+                clock: Some(("1:10".to_string(), "2:50".to_string())),
+            })
+        );
+    }
+
+    #[test]
+    fn test_one_substitution_per_team() {
+        let relay = |round: u8, right_reserve: &str, left_reserve: &str| {
+            let raw = format!(
+                "|EFP1.1|INFO|2|ef-eq|4|T8|3|{}|||T|E||F|%|FRA|France|FRA|0|U|0|0|0|0|0|{}|%|ITA|Italie|ITA|0|U|0|0|0|0|0|{}|%|",
+                round, right_reserve, left_reserve
+            );
+            Message::try_from(raw).unwrap()
+        };
+        let mut state = MatchState::new("2");
+        state.apply(&relay(1, "N", "N"));
+        let events = state.apply(&relay(2, "R", "N"));
+        let made = Substitution { side: Side::Right, relay: Some(2), replaced: Some(1) };
+        assert!(events.contains(&MatchEvent::BoutStarted));
+        assert!(events.contains(&MatchEvent::SubstitutionMade(made.clone())));
+
+        // The flag staying up over the next relays is the same substitution.
+        assert!(!state.apply(&relay(3, "R", "N")).iter().any(|e| matches!(e, MatchEvent::SubstitutionMade(_))));
+        state.apply(&relay(4, "N", "N"));
+        let events = state.apply(&relay(5, "R", "R"));
+        assert!(events.contains(&MatchEvent::SubstitutionRefused(Substitution { side: Side::Right, relay: Some(5), replaced: Some(3) })));
+        assert!(events.contains(&MatchEvent::SubstitutionMade(Substitution { side: Side::Left, relay: Some(5), replaced: Some(4) })));
+        assert_eq!(state.substitution(Side::Right), Some(&made));
+
+        // Reserve flags in individual bouts are not substitutions.
+        let mut individual = MatchState::new("17");
+        individual.apply(&info("F", "0|U|0|0|0|0|0|R", "0|U"));
+        assert_eq!(individual.substitution(Side::Right), None);
+    }
+
+    #[test]
+    fn test_video_review_events() {
+        let mut state = MatchState::new("17");
+        state.apply(&info("H", "8|U|0|0|0|0|0|N|0||2|0", "7|U"));
+        let events = state.apply(&info("H", "8|U|0|0|0|0|0|N|0||2|1", "7|U"));
+        assert_eq!(events, vec![MatchEvent::VideoReviewRequested { side: Side::Right }]);
+        assert_eq!(state.fencer(Side::Right).unwrap().video_appeals, Some(2));
+
+        let events = state.apply(&info("H", "8|U|0|0|0|0|0|N|0||1|0", "7|U"));
+        assert_eq!(events, vec![MatchEvent::VideoReviewResolved { side: Side::Right, appeals_left: Some(1) }]);
+    }
+
+    #[test]
+    fn test_priority_assigned_on_tie() {
+        let frame = |priority: &str, right: u8, left: u8| {
+            let raw = format!(
+                "|EFP1.1|INFO|17|fm-eq|1|A32|12||1:00|||E|{}|H|%|28|P.Martin|FRA|{}|U|%|32|B. Panini|ITA|{}|U|%|",
+                priority, right, left
+            );
+            Message::try_from(raw).unwrap()
+        };
+        let mut state = MatchState::new("17");
+        state.apply(&frame("N", 9, 9));
+        let events = state.apply(&frame("L", 9, 9));
+        assert_eq!(
+            events[1],
+            MatchEvent::PriorityAssigned(PriorityAssignment {
+                side: Side::Left,
+                reason: PriorityReason::Tie,
+                time: Some("1:00".to_string()),
+                right_score: Some(9),
+                left_score: Some(9),
+            })
+        );
+
+        // Moving priority from one fencer to the other is a correction, not a new draw.
+        let events = state.apply(&frame("R", 10, 9));
+        assert!(!events.iter().any(|e| matches!(e, MatchEvent::PriorityAssigned(_))));
+    }
+
+    #[test]
+    fn test_periods_and_breaks() {
+        let frame = |state: &str, time: &str, right: u8, left: u8| {
+            let raw = format!(
+                "|EFP1.1|INFO|17|fm-eq|1|A32|12||{}|||E||{}|%|28|P.Martin|FRA|{}|U|%|32|B. Panini|ITA|{}|U|%|",
+                time, state, right, left
+            );
+            Message::try_from(raw).unwrap()
+        };
+        let mut state = MatchState::new("17");
+        state.apply(&frame("F", "0:01", 4, 3));
+        assert_eq!(state.current_period(), Some(1));
+        assert!(state.apply(&frame("H", "0:00", 4, 3)).contains(&MatchEvent::PeriodEnded { period: 1 }));
+        assert!(state.apply(&frame("H", "1:00", 4, 3)).contains(&MatchEvent::BreakStarted { after_period: 1 }));
+        assert!(state.is_on_break());
+        state.apply(&frame("H", "0:00", 4, 3));
+        state.apply(&frame("H", "3:00", 4, 3));
+        assert_eq!((state.current_period(), state.is_on_break()), (Some(2), false));
+
+        state.apply(&frame("F", "0:01", 8, 7));
+        state.apply(&frame("H", "0:00", 8, 7));
+        assert!(state.apply(&frame("P", "0:00", 8, 7)).contains(&MatchEvent::BreakStarted { after_period: 2 }));
+        state.apply(&frame("P", "1:00", 8, 7));
+        state.apply(&frame("H", "3:00", 8, 7));
+        state.apply(&frame("F", "0:01", 12, 12));
+        assert!(state.apply(&frame("H", "0:00", 12, 12)).contains(&MatchEvent::PeriodEnded { period: 3 }));
+
+        // No break after the last period: the minute with priority follows.
+        let events = state.apply(&frame("H", "1:00", 12, 12));
+        assert!(!events.iter().any(|e| matches!(e, MatchEvent::BreakStarted { .. })));
+        assert_eq!(state.current_period(), Some(4));
+    }
+
+    #[test]
+    fn test_winner_under_rule_profiles() {
+        let frame = |time: &str, priority: &str, right: u8, left: u8| {
+            let raw = format!(
+                "|EFP1.1|INFO|17|fm-eq|1|A32|12||{}|||S|{}|H|%|28|P.Martin|FRA|{}|U|%|32|B. Panini|ITA|{}|U|%|",
+                time, priority, right, left
+            );
+            Message::try_from(raw).unwrap()
+        };
+
+        let mut state = MatchState::new("17").rules(RuleProfile::VETERAN);
+        state.apply(&frame("1:00", "", 9, 7));
+        assert_eq!((state.target_score(), state.winner()), (Some(10), None));
+        state.apply(&frame("0:40", "", 10, 7));
+        assert_eq!(state.winner(), Some(Side::Right));
+
+        // A tie after the last period goes to the fencer with priority.
+        let mut state = MatchState::new("17").rules(RuleProfile::VETERAN);
+        for time in ["0:01", "0:00", "3:00", "0:01", "0:00"] {
+            state.apply(&frame(time, "", 6, 6));
+        }
+        assert_eq!((state.current_period(), state.winner()), (Some(2), None));
+        state.apply(&frame("1:00", "L", 6, 6));
+        assert_eq!(state.winner(), None);
+        state.apply(&frame("0:00", "L", 6, 6));
+        assert_eq!(state.winner(), Some(Side::Left));
+
+        // Without a priority minute, a tie is lost by both fencers.
+        let mut state = MatchState::new("17").rules(RuleProfile::MODERN_PENTATHLON);
+        state.apply(&frame("0:01", "", 0, 0));
+        state.apply(&frame("0:00", "", 0, 0));
+        assert!(state.rule_profile().period_seconds == 60 && state.winner().is_none());
+        let mut manager = PisteManager::new().rules(RuleProfile::MODERN_PENTATHLON);
+        manager.apply(&frame("0:30", "", 0, 1));
+        assert_eq!(manager.get("17").unwrap().winner(), Some(Side::Left));
+    }
+
+    #[test]
+    fn test_relay_legs() {
+        let frame = |round: u8, right: u8, left: u8| {
+            let raw = format!(
+                "|EFP1.1|INFO|2|ef-eq|4|T8|3|{}|||T|E||F|%|FRA|France|FRA|{}|U|%|ITA|Italie|ITA|{}|U|%|",
+                round, right, left
+            );
+            Message::try_from(raw).unwrap()
+        };
+        let mut state = MatchState::new("2");
+        state.apply(&frame(1, 3, 4));
+        assert_eq!((state.relay(), state.target_score()), (Some(1), Some(5)));
+        let events = state.apply(&frame(1, 3, 5));
+        assert!(events.contains(&MatchEvent::RelayFinished { relay: 1, right_score: Some(3), left_score: Some(5) }));
+        assert!(!state.apply(&frame(1, 3, 5)).iter().any(|e| matches!(e, MatchEvent::RelayFinished { .. })));
+
+        // Time ran out in the second relay: it ends when the third is loaded.
+        state.apply(&frame(2, 7, 8));
+        let events = state.apply(&frame(3, 7, 8));
+        assert_eq!(events[0], MatchEvent::RelayFinished { relay: 2, right_score: Some(7), left_score: Some(8) });
+        assert_eq!(events[1], MatchEvent::BoutStarted);
+        assert_eq!(state.target_score(), Some(15));
+
+        let events = state.apply(&frame(3, 7, 17));
+        assert!(events.contains(&MatchEvent::RelayCapExceeded { side: Side::Left, relay: 3, score: 17, target: 15 }));
+    }
+
+    #[test]
+    fn test_pistes_in_natural_order() {
+        let mut pistes = PisteManager::new();
+        for piste in ["10", "Finale", "2"] {
+            let raw = format!("|EFP1.1|INFO|{}|fm-eq|1|A32|12|||||E||W|%|%|%|", piste);
+            pistes.apply(&Message::try_from(raw).unwrap());
+        }
+
+        let order: Vec<&str> = pistes.iter().map(MatchState::piste).collect();
+        assert_eq!(order, ["2", "10", "Finale"]);
+        assert!(pistes.get("Finale").is_some());
+    }
+
+    #[test]
+    fn test_state_at_past_instants() {
+        let entry = |seq: u64, timestamp_ms: u64, piste: &str, right: u8| JournalEntry {
+            seq,
+            timestamp_ms,
+            raw: format!("|EFP1.1|INFO|{}|fm-eq|1|A32|12|||||E||F|%|28|A|FRA|{}|%|32|B|ITA|7|%|", piste, right),
+        };
+        let entries = vec![
+            entry(1, 1000, "17", 8),
+            entry(2, 2000, "3", 1),
+            entry(3, 3000, "17", 9),
+            entry(4, 4000, "17", 10),
+        ];
+
+        let then = PisteManager::at(&entries, RuleProfile::FIE_SENIOR, 3500).unwrap();
+        assert_eq!(then.len(), 2);
+        assert_eq!(then.get("17").unwrap().score(Side::Right), Some(9));
+        assert!(MatchState::at(&entries, "3", RuleProfile::FIE_SENIOR, 1500).unwrap().is_none());
+
+        // A veteran elimination bout is won at 10 touches, not 15.
+        let senior = MatchState::at(&entries, "17", RuleProfile::FIE_SENIOR, 4000).unwrap().unwrap();
+        assert_eq!((senior.target_score(), senior.winner()), (Some(15), None));
+        let veteran = MatchState::at(&entries, "17", RuleProfile::VETERAN, 4000).unwrap().unwrap();
+        assert_eq!((veteran.target_score(), veteran.winner()), (Some(10), Some(Side::Right)));
+        let pistes = PisteManager::at(&entries, RuleProfile::VETERAN, 4000).unwrap();
+        assert_eq!(pistes.get("17").unwrap().winner(), Some(Side::Right));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_resume_from_json_snapshot() {
+        let mut state = MatchState::new("17").rules(RuleProfile::VETERAN);
+        state.apply(&info("H", "4|U", "2|U"));
+        let mut pistes = PisteManager::new().rules(RuleProfile::VETERAN);
+        pistes.apply(&info("H", "4|U", "2|U"));
+
+        // A state saved while resuming is loaded as a plain state.
+        let json = serde_json::to_string(&MatchState::resume_from(state)).unwrap();
+        let loaded: MatchState = serde_json::from_str(&json).unwrap();
+        let mut resumed = MatchState::resume_from(loaded.clone());
+        assert_eq!(resumed.rule_profile(), &RuleProfile::VETERAN);
+
+        // The jump from 4 to 7 is the bout moving on while down, not a gap.
+        let events = resumed.apply(&info("H", "7|U", "2|U"));
+        assert_eq!(
+            events,
+            vec![MatchEvent::Resumed, MatchEvent::ScoreChanged { side: Side::Right, from: Some(4), to: Some(7) }]
+        );
+        let mut loaded = loaded;
+        assert!(matches!(loaded.apply(&info("H", "7|U", "2|U"))[0], MatchEvent::Gap(_)));
+
+        let json = serde_json::to_string(&pistes).unwrap();
+        let mut resumed = PisteManager::resume_from(serde_json::from_str(&json).unwrap());
+        assert_eq!(resumed.apply(&info("H", "7|U", "2|U"))[0], MatchEvent::Resumed);
+        assert_eq!(resumed.get("17").unwrap().rule_profile(), &RuleProfile::VETERAN);
+
+        // Snapshots written before rule profiles existed load with senior rules.
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value.as_object_mut().unwrap().remove("rules");
+        for state in value["pistes"].as_object_mut().unwrap().values_mut() {
+            state.as_object_mut().unwrap().remove("rules");
+        }
+        let legacy: PisteManager = serde_json::from_value(value).unwrap();
+        assert_eq!(legacy.get("17").unwrap().rule_profile(), &RuleProfile::FIE_SENIOR);
+    }
+}
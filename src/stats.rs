@@ -0,0 +1,285 @@
+use crate::enums::{PCard, Side, Weapon};
+use crate::fencer::Fencer;
+use crate::message::Message;
+use crate::utils::clock_seconds;
+
+/// Statistics of one fencer over a bout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FencerStats {
+    /// Touches scored, including double touches.
+    pub touches: u8,
+    /// Longest run of touches scored without the opponent scoring.
+    pub longest_run: u8,
+    /// Fencing time spent ahead on the score, in seconds of bout clock.
+    pub seconds_leading: u32,
+    /// Fencing time spent behind on the score, in seconds of bout clock.
+    pub seconds_trailing: u32,
+}
+
+/// A card change on the timeline of a bout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CardEntry {
+    /// Fencing time elapsed when the card was seen, in seconds of bout clock.
+    pub elapsed_seconds: u32,
+    /// Side of the carded fencer.
+    pub side: Side,
+    /// Yellow card count after the change.
+    pub yellow_card: Option<u8>,
+    /// Red card count after the change.
+    pub red_card: Option<u8>,
+    /// P-card after the change.
+    pub p_card: Option<PCard>,
+}
+
+/// Statistics of a bout, folded from its timeline of messages.
+///
+/// Time is measured on the bout clock carried by the `time` field: only the
+/// clock running down counts as fencing time. Messages are expected to belong
+/// to a single bout, in the order they were received.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Side;
+/// use cyrano::message::Message;
+/// use cyrano::stats::BoutStats;
+///
+/// let frames = [
+///     "|EFP1.1|INFO|17|fm-eq|1|A32|12||3:00|||E||F|%|28|P.Martin|FRA|0|U|%|32|B. Panini|ITA|0|U|%|",
+///     "|EFP1.1|INFO|17|fm-eq|1|A32|12||2:30|||E||H|%|28|P.Martin|FRA|1|U|%|32|B. Panini|ITA|1|U|%|",
+///     "|EFP1.1|INFO|17|fm-eq|1|A32|12||2:00|||E||H|%|28|P.Martin|FRA|2|U|%|32|B. Panini|ITA|1|U|%|",
+/// ];
+/// let messages: Vec<Message> = frames.iter().map(|f| Message::try_from(*f).unwrap()).collect();
+///
+/// let stats = BoutStats::from_messages(&messages);
+/// assert_eq!(stats.double_touches, 1);
+/// assert_eq!(stats.fencing_seconds, 60);
+/// assert_eq!(stats.touches_per_minute(Side::Right), Some(2.0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoutStats {
+    /// Statistics of the fencer on the right.
+    pub right: FencerStats,
+    /// Statistics of the fencer on the left.
+    pub left: FencerStats,
+    /// Total fencing time, in seconds of bout clock.
+    pub fencing_seconds: u32,
+    /// Fencing time spent with the scores level, in seconds of bout clock.
+    pub seconds_tied: u32,
+    /// Touches scored by both fencers at once. Only counted at épée.
+    pub double_touches: u32,
+    /// Card changes, in order.
+    pub cards: Vec<CardEntry>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last: Option<Snapshot>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    run: Option<(Side, u8)>,
+}
+
+/// What is kept from the previous message of the timeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Snapshot {
+    clock: Option<u32>,
+    scores: [u8; 2],
+    right: Fencer,
+    left: Fencer,
+}
+
+impl BoutStats {
+    /// Creates empty statistics, to be fed with [`observe`](BoutStats::observe).
+    pub fn new() -> Self {
+        BoutStats::default()
+    }
+
+    /// Computes the statistics of a whole bout timeline.
+    pub fn from_messages<'a>(messages: impl IntoIterator<Item = &'a Message>) -> Self {
+        let mut stats = BoutStats::new();
+        for message in messages {
+            stats.observe(message);
+        }
+        stats
+    }
+
+    /// Returns the statistics of the fencer on the given side.
+    pub fn fencer(&self, side: Side) -> &FencerStats {
+        match side {
+            Side::Right => &self.right,
+            Side::Left => &self.left,
+        }
+    }
+
+    fn fencer_mut(&mut self, side: Side) -> &mut FencerStats {
+        match side {
+            Side::Right => &mut self.right,
+            Side::Left => &mut self.left,
+        }
+    }
+
+    /// Returns the scoring rate of a fencer, in touches per minute of fencing time.
+    ///
+    /// Returns `None` until some fencing time has elapsed.
+    pub fn touches_per_minute(&self, side: Side) -> Option<f64> {
+        if self.fencing_seconds == 0 {
+            return None;
+        }
+        Some(f64::from(self.fencer(side).touches) * 60.0 / f64::from(self.fencing_seconds))
+    }
+
+    /// Folds the next message of the bout into the statistics.
+    ///
+    /// Messages that do not carry bout data (HELLO, ACK, NAK) are ignored.
+    pub fn observe(&mut self, message: &Message) {
+        if !message.carries_bout() {
+            return;
+        }
+
+        let now = Snapshot {
+            clock: message.time.as_deref().and_then(clock_seconds),
+            scores: [
+                message.right_fencer.score.unwrap_or(0),
+                message.left_fencer.score.unwrap_or(0),
+            ],
+            right: message.right_fencer.clone(),
+            left: message.left_fencer.clone(),
+        };
+
+        if let Some(before) = self.last.take() {
+            self.fold_time(&before, &now);
+            self.fold_touches(&before, &now, message.weapon.as_ref());
+            self.fold_cards(&before, &now);
+        } else {
+            self.right.touches = now.scores[0];
+            self.left.touches = now.scores[1];
+        }
+
+        self.last = Some(now);
+    }
+
+    fn fold_time(&mut self, before: &Snapshot, now: &Snapshot) {
+        let elapsed = match (before.clock, now.clock) {
+            (Some(before), Some(now)) if now < before => before - now,
+            _ => return,
+        };
+
+        self.fencing_seconds += elapsed;
+        let [right, left] = before.scores;
+        if right > left {
+            self.right.seconds_leading += elapsed;
+            self.left.seconds_trailing += elapsed;
+        } else if left > right {
+            self.left.seconds_leading += elapsed;
+            self.right.seconds_trailing += elapsed;
+        } else {
+            self.seconds_tied += elapsed;
+        }
+    }
+
+    fn fold_touches(&mut self, before: &Snapshot, now: &Snapshot, weapon: Option<&Weapon>) {
+        self.right.touches = now.scores[0];
+        self.left.touches = now.scores[1];
+
+        let scored = |i: usize| now.scores[i] > before.scores[i];
+        match (scored(0), scored(1)) {
+            (true, true) => {
+                if weapon == Some(&Weapon::Epee) {
+                    self.double_touches += 1;
+                }
+                self.run = None;
+            }
+            (true, false) => self.extend_run(Side::Right, now.scores[0] - before.scores[0]),
+            (false, true) => self.extend_run(Side::Left, now.scores[1] - before.scores[1]),
+            (false, false) => {}
+        }
+    }
+
+    fn extend_run(&mut self, side: Side, touches: u8) {
+        let length = match self.run {
+            Some((current, length)) if current == side => length.saturating_add(touches),
+            _ => touches,
+        };
+        self.run = Some((side, length));
+
+        let stats = self.fencer_mut(side);
+        stats.longest_run = stats.longest_run.max(length);
+    }
+
+    fn fold_cards(&mut self, before: &Snapshot, now: &Snapshot) {
+        for (side, before, after) in [
+            (Side::Right, &before.right, &now.right),
+            (Side::Left, &before.left, &now.left),
+        ] {
+            if before.yellow_card != after.yellow_card
+                || before.red_card != after.red_card
+                || before.p_card != after.p_card
+            {
+                self.cards.push(CardEntry {
+                    elapsed_seconds: self.fencing_seconds,
+                    side,
+                    yellow_card: after.yellow_card,
+                    red_card: after.red_card,
+                    p_card: after.p_card.clone(),
+                });
+            }
+        }
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn info(weapon: &str, time: &str, right: &str, left: &str) -> Message {
+        let raw = format!(
+            "|EFP1.1|INFO|17|fm-eq|1|A32|12||{}|||{}||H|%|28|P.Martin|FRA|{}|%|32|B. Panini|ITA|{}|%|",
+            time, weapon, right, left
+        );
+        Message::try_from(raw).unwrap()
+    }
+
+    #[test]
+    fn test_runs_leading_and_cards() {
+        let timeline = [
+            info("F", "3:00", "0|U", "0|U"),
+            info("F", "2:40", "1|U", "0|U"),
+            info("F", "2:20", "2|U", "0|U|1"),
+            info("F", "2:00", "3|U", "0|U|1"),
+            info("F", "1:00", "3|U", "1|U|1"),
+            info("F", "0:30", "3|U", "1|U|1"),
+        ];
+        let stats = BoutStats::from_messages(&timeline);
+
+        assert_eq!(stats.right.touches, 3);
+        assert_eq!(stats.right.longest_run, 3);
+        assert_eq!(stats.left.longest_run, 1);
+        assert_eq!(stats.seconds_tied, 20);
+        assert_eq!(stats.right.seconds_leading, 130);
+        assert_eq!(stats.left.seconds_trailing, 130);
+        assert_eq!(stats.fencing_seconds, 150);
+        assert_eq!(
+            stats.cards,
+            vec![CardEntry {
+                elapsed_seconds: 40,
+                side: Side::Left,
+                yellow_card: Some(1),
+                red_card: None,
+                p_card: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_double_touches_only_at_epee() {
+        let timeline = |weapon| [info(weapon, "3:00", "0|U", "0|U"), info(weapon, "2:50", "1|U", "1|U")];
+
+        assert_eq!(BoutStats::from_messages(&timeline("E")).double_touches, 1);
+        assert_eq!(BoutStats::from_messages(&timeline("F")).double_touches, 0);
+        assert_eq!(BoutStats::new().touches_per_minute(Side::Left), None);
+    }
+}
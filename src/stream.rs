@@ -0,0 +1,251 @@
+/// Largest frame accepted by a [`FrameDecoder`], in bytes.
+///
+/// Longer runs of bytes without a frame end are handed out as they are, so
+/// that a peer sending garbage cannot make the buffer grow without bound.
+pub const MAX_FRAME_LEN: usize = 4096;
+
+use crate::trailer::ChecksumAlgorithm;
+
+/// Marker starting every EFP frame.
+const FRAME_START: &[u8] = b"|EFP";
+
+/// Marker ending every zone of a frame.
+const ZONE_END: &[u8] = b"|%|";
+
+/// Number of zones of a full frame: the general zone and the two fencers.
+const ZONES: usize = 3;
+
+/// Splits a byte stream into EFP frames.
+///
+/// Datagrams carry one frame each, but byte streams such as TCP tunnels and
+/// serial bridges do not keep frame boundaries. The decoder buffers the bytes
+/// received and hands out each frame once it is complete, that is once its
+/// third zone has ended, a line break follows it, or the next frame starts.
+/// Frames with fewer zones, such as a short HELLO, are therefore only handed
+/// out after the line break or the next frame, or by [`finish`](FrameDecoder::finish).
+///
+/// Frames are returned verbatim, so that those that do not parse can be kept
+/// for vendor escalation. When a bridge appends a checksum or sequence trailer
+/// after the final `%|` (see [`crate::trailer`]), the decoder must be told with
+/// [`trailer`](FrameDecoder::trailer) so that it keeps the trailer with the frame.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::stream::FrameDecoder;
+///
+/// let mut decoder = FrameDecoder::new();
+/// decoder.push(b"|EFP1.1|HELLO|17|fm-eq|%||EFP1.1|INFO|17|fm-eq|1|");
+/// assert_eq!(decoder.next_frame().unwrap(), b"|EFP1.1|HELLO|17|fm-eq|%|");
+/// assert_eq!(decoder.next_frame(), None);
+///
+/// decoder.push(b"%|%|%|");
+/// assert_eq!(decoder.next_frame().unwrap(), b"|EFP1.1|INFO|17|fm-eq|1|%|%|%|");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    trailer: Option<FrameTrailer>,
+}
+
+/// Trailer a bridge appends after the final `%|` of every frame.
+#[derive(Debug, Clone, Copy)]
+pub enum FrameTrailer {
+    /// A checksum, as verified with [`ParseOptions::checksum`](crate::options::ParseOptions::checksum).
+    Checksum(ChecksumAlgorithm),
+    /// A decimal sequence number, as read by [`Message::parse_sequenced`](crate::message::Message::parse_sequenced).
+    Sequence,
+}
+
+impl FrameTrailer {
+    /// Returns the length of the trailer at the start of `bytes`, or `None`
+    /// if it may not be complete yet.
+    ///
+    /// Hexadecimal checksums have a fixed length. Sequence numbers end at the
+    /// first byte that is not a digit, and custom checksums at the line break
+    /// or the next frame.
+    fn len(&self, bytes: &[u8]) -> Option<usize> {
+        let (is_digit, width): (fn(&u8) -> bool, Option<usize>) = match self {
+            FrameTrailer::Checksum(ChecksumAlgorithm::Xor | ChecksumAlgorithm::Sum) => (u8::is_ascii_hexdigit, Some(2)),
+            FrameTrailer::Checksum(ChecksumAlgorithm::Crc16) => (u8::is_ascii_hexdigit, Some(4)),
+            FrameTrailer::Checksum(ChecksumAlgorithm::Custom(_)) => return None,
+            FrameTrailer::Sequence => (u8::is_ascii_digit, None),
+        };
+        let digits = bytes.iter().take_while(|&byte| is_digit(byte)).count();
+        match width {
+            Some(width) if digits >= width => Some(width),
+            _ if digits < bytes.len() => Some(digits),
+            _ => None,
+        }
+    }
+}
+
+impl FrameDecoder {
+    /// Creates an empty decoder.
+    pub fn new() -> Self {
+        FrameDecoder::default()
+    }
+
+    /// Keeps the given trailer with each frame, instead of handing the frame
+    /// out as soon as its final `%|` is received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::stream::{FrameDecoder, FrameTrailer};
+    /// use cyrano::trailer::ChecksumAlgorithm;
+    ///
+    /// let mut decoder = FrameDecoder::new().trailer(FrameTrailer::Checksum(ChecksumAlgorithm::Xor));
+    /// decoder.push(b"|EFP1.1|ACK|17|fm-eq|%||%||%|");
+    /// assert_eq!(decoder.next_frame(), None);
+    /// decoder.push(b"5A|EFP1.1|HELLO|17|");
+    /// assert_eq!(decoder.next_frame().unwrap(), b"|EFP1.1|ACK|17|fm-eq|%||%||%|5A");
+    /// ```
+    pub fn trailer(mut self, trailer: FrameTrailer) -> Self {
+        self.trailer = Some(trailer);
+        self
+    }
+
+    /// Appends bytes received from the stream.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the number of bytes buffered and not yet handed out.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the next complete frame, or `None` if more bytes are needed.
+    pub fn next_frame(&mut self) -> Option<Vec<u8>> {
+        self.skip_line_breaks();
+        let end = frame_end(&self.buffer, self.trailer).or_else(|| (self.buffer.len() > MAX_FRAME_LEN).then_some(MAX_FRAME_LEN))?;
+        Some(self.take(end))
+    }
+
+    /// Returns the bytes left in the buffer once the stream has ended, as a
+    /// last frame, or `None` if there are none.
+    pub fn finish(&mut self) -> Option<Vec<u8>> {
+        self.skip_line_breaks();
+        if self.buffer.is_empty() {
+            None
+        } else {
+            let end = self.buffer.len();
+            Some(self.take(end))
+        }
+    }
+
+    fn skip_line_breaks(&mut self) {
+        let start = self.buffer.iter().take_while(|&&b| b == b'\r' || b == b'\n').count();
+        self.buffer.drain(..start);
+    }
+
+    fn take(&mut self, end: usize) -> Vec<u8> {
+        let frame: Vec<u8> = self.buffer.drain(..end).collect();
+        self.skip_line_breaks();
+        frame
+    }
+}
+
+/// Returns the length of the frame at the start of `buffer`, if it is complete.
+fn frame_end(buffer: &[u8], trailer: Option<FrameTrailer>) -> Option<usize> {
+    let mut seen = 0;
+    let mut index = 0;
+
+    while index < buffer.len() {
+        let rest = &buffer[index..];
+        if rest[0] == b'\r' || rest[0] == b'\n' || (index > 0 && rest.starts_with(FRAME_START)) {
+            return Some(index);
+        }
+        if rest.starts_with(ZONE_END) {
+            seen += 1;
+            if seen == ZONES {
+                let end = index + ZONE_END.len();
+                match trailer {
+                    None => return Some(end),
+                    Some(trailer) => {
+                        if let Some(len) = trailer.len(&buffer[end..]) {
+                            return Some(end + len);
+                        }
+                    }
+                }
+            }
+            // The closing pipe also opens the next zone.
+            index += ZONE_END.len() - 1;
+        } else {
+            index += 1;
+        }
+    }
+    None
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_decoder_splits_frames_cut_anywhere() {
+        let frames: Vec<&str> = fixtures::valid_frames()
+            .map(str::trim_end)
+            .filter(|frame| frame.starts_with("|EFP"))
+            .collect();
+        let stream: Vec<u8> = frames.iter().flat_map(|frame| frame.bytes().chain(*b"\r\n")).collect();
+
+        for chunk in [1, 7, 64, stream.len()] {
+            let mut decoder = FrameDecoder::new();
+            let mut decoded = Vec::new();
+            for bytes in stream.chunks(chunk) {
+                decoder.push(bytes);
+                while let Some(frame) = decoder.next_frame() {
+                    decoded.push(String::from_utf8(frame).unwrap());
+                }
+            }
+            decoded.extend(decoder.finish().map(|frame| String::from_utf8(frame).unwrap()));
+            assert_eq!(decoded, frames, "chunks of {} bytes", chunk);
+        }
+    }
+
+    #[test]
+    fn test_decoder_bounds_garbage() {
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&[b'x'; MAX_FRAME_LEN + 10]);
+        assert_eq!(decoder.next_frame().unwrap().len(), MAX_FRAME_LEN);
+        assert_eq!(decoder.next_frame(), None);
+        assert_eq!(decoder.finish().unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_decoder_releases_frames_without_line_break() {
+        let info = b"|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|";
+        let mut decoder = FrameDecoder::new();
+        decoder.push(info);
+        assert_eq!(decoder.next_frame().unwrap(), info);
+        decoder.push(b"|EFP1.1|NAK|17|fm-eq|%||%||%|");
+        assert_eq!(decoder.next_frame().unwrap(), b"|EFP1.1|NAK|17|fm-eq|%||%||%|");
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn test_decoder_keeps_configured_trailers() {
+        let info = "|EFP1.1|INFO|17|fm-eq|1|A32|12|||||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|";
+        let checksum = ChecksumAlgorithm::Crc16.compute(info);
+        let mut decoder = FrameDecoder::new().trailer(FrameTrailer::Checksum(ChecksumAlgorithm::Crc16));
+        decoder.push(info.as_bytes());
+        assert_eq!(decoder.next_frame(), None);
+        decoder.push(&checksum.as_bytes()[..2]);
+        assert_eq!(decoder.next_frame(), None);
+        // The trailer is complete at its fourth digit, without a line break.
+        decoder.push(&checksum.as_bytes()[2..]);
+        assert_eq!(decoder.next_frame().unwrap(), format!("{}{}", info, checksum).as_bytes());
+
+        let mut decoder = FrameDecoder::new().trailer(FrameTrailer::Sequence);
+        decoder.push(b"|EFP1.1|ACK|17|fm-eq|%||%||%|65");
+        assert_eq!(decoder.next_frame(), None);
+        decoder.push(b"535|EFP1.1|ACK|17|fm-eq|%||%||%|0\r\n");
+        assert_eq!(decoder.next_frame().unwrap(), b"|EFP1.1|ACK|17|fm-eq|%||%||%|65535");
+        assert_eq!(decoder.next_frame().unwrap(), b"|EFP1.1|ACK|17|fm-eq|%||%||%|0");
+    }
+}
@@ -0,0 +1,292 @@
+use std::io::{self, Write};
+
+use crate::competition::CompetitionInfo;
+use crate::enums::{ApparatusState, FencerStatus, Priority, Side, Weapon};
+use crate::fencer::Fencer;
+use crate::message::Message;
+use crate::utils::{clock_seconds, write_csv_row};
+
+/// Touches scored and time fenced during one period of a bout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PeriodSplit {
+    /// Touches scored by the right fencer during the period.
+    pub right_touches: u8,
+    /// Touches scored by the left fencer during the period.
+    pub left_touches: u8,
+    /// Fencing time of the period, in seconds of bout clock.
+    pub fencing_seconds: u32,
+}
+
+/// Outcome of a finished bout, emitted once by the state tracker.
+///
+/// Durations are measured on the bout clock carried by the `time` field: the
+/// clock running down adds fencing time, and the clock being reset upwards
+/// starts a new period.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoutSummary {
+    /// Piste identifier.
+    pub piste: String,
+    /// Competition identifier.
+    pub competition_id: String,
+    /// Competition phase number.
+    pub phase: Option<u8>,
+    /// Pool or tableau identifier.
+    pub pool_tableau: Option<String>,
+    /// Match number within the competition.
+    pub match_number: Option<u8>,
+    /// Round number.
+    pub round: Option<u8>,
+    /// Weapon of the bout.
+    pub weapon: Option<Weapon>,
+    /// Final data of the fencer on the right: score, status and cards.
+    pub right_fencer: Fencer,
+    /// Final data of the fencer on the left: score, status and cards.
+    pub left_fencer: Fencer,
+    /// Side of the winner, if the statuses designate one.
+    pub winner: Option<Side>,
+    /// Total fencing time, in seconds of bout clock.
+    pub fencing_seconds: u32,
+    /// Side given priority, if priority was drawn.
+    pub priority: Option<Side>,
+    /// Touches and fencing time per period, in order.
+    pub periods: Vec<PeriodSplit>,
+    /// Competition information, once joined with
+    /// [`CompetitionRegistry::annotate`](crate::competition::CompetitionRegistry::annotate).
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub competition: Option<CompetitionInfo>,
+}
+
+/// Accumulates what a [`BoutSummary`] needs while a bout is tracked.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct BoutRecorder {
+    clock: Option<u32>,
+    fencing_seconds: u32,
+    periods: Vec<PeriodSplit>,
+    period: PeriodSplit,
+    period_start: [u8; 2],
+    summarized: bool,
+}
+
+impl BoutRecorder {
+    /// Folds the clock and scores of a message of the tracked bout.
+    pub(crate) fn observe(&mut self, message: &Message) {
+        let scores = scores(message);
+        let clock = message.time.as_deref().and_then(clock_seconds);
+
+        if let (Some(before), Some(now)) = (self.clock, clock) {
+            if now < before {
+                self.fencing_seconds += before - now;
+                self.period.fencing_seconds += before - now;
+            } else if now > before {
+                self.close_period(scores);
+            }
+        }
+        if clock.is_some() {
+            self.clock = clock;
+        }
+
+        self.period.right_touches = scores[0].saturating_sub(self.period_start[0]);
+        self.period.left_touches = scores[1].saturating_sub(self.period_start[1]);
+    }
+
+    fn close_period(&mut self, scores: [u8; 2]) {
+        self.periods.push(std::mem::take(&mut self.period));
+        self.period_start = scores;
+    }
+
+    /// Returns the summary once per bout, when it finishes or the apparatus
+    /// reports the end of the bout.
+    pub(crate) fn finish(&mut self, message: &Message, finished: bool) -> Option<BoutSummary> {
+        let ending = message.state == Some(ApparatusState::Ending);
+        if self.summarized || !(finished || ending) {
+            return None;
+        }
+        self.summarized = true;
+        Some(self.summary(message))
+    }
+
+    /// Builds the summary of the bout so far.
+    pub(crate) fn summary(&self, message: &Message) -> BoutSummary {
+        let mut periods = self.periods.clone();
+        periods.push(self.period.clone());
+
+        BoutSummary {
+            piste: message.piste.to_string(),
+            competition_id: message.competition_id.to_string(),
+            phase: message.phase,
+            pool_tableau: message.pool_tableau.as_deref().map(String::from),
+            match_number: message.match_number,
+            round: message.round,
+            weapon: message.weapon.clone(),
+            right_fencer: message.right_fencer.clone(),
+            left_fencer: message.left_fencer.clone(),
+            winner: winner(message),
+            fencing_seconds: self.fencing_seconds,
+            priority: match message.priority {
+                Some(Priority::Right) => Some(Side::Right),
+                Some(Priority::Left) => Some(Side::Left),
+                _ => None,
+            },
+            periods,
+            competition: None,
+        }
+    }
+}
+
+fn scores(message: &Message) -> [u8; 2] {
+    [
+        message.right_fencer.score.unwrap_or(0),
+        message.left_fencer.score.unwrap_or(0),
+    ]
+}
+
+fn winner(message: &Message) -> Option<Side> {
+    let lost = |status: &Option<FencerStatus>| {
+        matches!(
+            status,
+            Some(FencerStatus::Defeat) | Some(FencerStatus::Abandonment) | Some(FencerStatus::Exclusion)
+        )
+    };
+
+    [Side::Right, Side::Left].into_iter().find(|side| {
+        message.fencer(*side).status == Some(FencerStatus::Victory)
+            || lost(&message.fencer(side.opponent()).status)
+    })
+}
+
+/// Column names of [`to_csv`], in order.
+pub const SUMMARY_CSV_COLUMNS: [&str; 22] = [
+    "piste",
+    "competition_id",
+    "phase",
+    "pool_tableau",
+    "match_number",
+    "round",
+    "weapon",
+    "right_fencer.id",
+    "right_fencer.name",
+    "right_fencer.score",
+    "right_fencer.status",
+    "right_fencer.yellow_card",
+    "right_fencer.red_card",
+    "left_fencer.id",
+    "left_fencer.name",
+    "left_fencer.score",
+    "left_fencer.status",
+    "left_fencer.yellow_card",
+    "left_fencer.red_card",
+    "winner",
+    "fencing_seconds",
+    "periods",
+];
+
+/// Writes bout summaries as CSV, one row per bout.
+///
+/// The columns are listed in [`SUMMARY_CSV_COLUMNS`]. Weapons and statuses are
+/// written with their protocol code, the winner as `right` or `left`, and the
+/// periods as `right-left` touch counts separated by `;` (`5-3;4-4`).
+///
+/// # Errors
+///
+/// Returns the I/O error of the writer.
+pub fn to_csv<'a, W: Write>(mut writer: W, summaries: impl IntoIterator<Item = &'a BoutSummary>) -> io::Result<()> {
+    let header: Vec<String> = SUMMARY_CSV_COLUMNS.iter().map(|c| c.to_string()).collect();
+    write_csv_row(&mut writer, &header)?;
+
+    let text = |value: Option<&str>| value.unwrap_or_default().to_string();
+    let code = |value: Option<String>| value.unwrap_or_default();
+
+    for summary in summaries {
+        let mut row = vec![
+            summary.piste.clone(),
+            summary.competition_id.clone(),
+            code(summary.phase.map(|v| v.to_string())),
+            text(summary.pool_tableau.as_deref()),
+            code(summary.match_number.map(|v| v.to_string())),
+            code(summary.round.map(|v| v.to_string())),
+            code(summary.weapon.as_ref().map(|v| v.to_string())),
+        ];
+        for fencer in [&summary.right_fencer, &summary.left_fencer] {
+            row.extend([
+                text(fencer.id.as_deref()),
+                text(fencer.name.as_deref()),
+                code(fencer.score.map(|v| v.to_string())),
+                code(fencer.status.as_ref().map(|v| v.to_string())),
+                code(fencer.yellow_card.map(|v| v.to_string())),
+                code(fencer.red_card.map(|v| v.to_string())),
+            ]);
+        }
+        row.push(code(summary.winner.map(|v| v.to_string())));
+        row.push(summary.fencing_seconds.to_string());
+        row.push(
+            summary
+                .periods
+                .iter()
+                .map(|p| format!("{}-{}", p.right_touches, p.left_touches))
+                .collect::<Vec<_>>()
+                .join(";"),
+        );
+        write_csv_row(&mut writer, &row)?;
+    }
+
+    writer.flush()
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{MatchEvent, MatchState};
+    use std::convert::TryFrom;
+
+    fn info(time: &str, state: &str, right: &str, left: &str) -> Message {
+        let raw = format!(
+            "|EFP1.1|INFO|17|fm-eq|2|A32|12||{}|||E||{}|%|28|P.Martin|FRA|{}|%|32|B. Panini|ITA|{}|%|",
+            time, state, right, left
+        );
+        Message::try_from(raw).unwrap()
+    }
+
+    #[test]
+    fn test_summary_with_periods() {
+        let mut state = MatchState::new("17");
+        state.apply(&info("3:00", "F", "0|U", "0|U"));
+        state.apply(&info("0:00", "H", "5|U", "3|U"));
+        state.apply(&info("3:00", "F", "5|U", "3|U"));
+        state.apply(&info("2:00", "H", "9|U", "4|U"));
+        let events = state.apply(&info("1:30", "E", "15|V", "6|D"));
+
+        let summary = events
+            .iter()
+            .find_map(|event| match event {
+                MatchEvent::BoutSummary(summary) => Some(summary.as_ref().clone()),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(summary.winner, Some(Side::Right));
+        assert_eq!(summary.fencing_seconds, 180 + 90);
+        assert_eq!(
+            summary.periods,
+            vec![
+                PeriodSplit { right_touches: 5, left_touches: 3, fencing_seconds: 180 },
+                PeriodSplit { right_touches: 10, left_touches: 3, fencing_seconds: 90 },
+            ]
+        );
+
+        let again = state.apply(&info("1:30", "E", "15|V", "6|D"));
+        assert!(!again.iter().any(|e| matches!(e, MatchEvent::BoutSummary(_))));
+
+        let mut csv = Vec::new();
+        to_csv(&mut csv, [&summary]).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(
+            csv.lines().nth(1).unwrap(),
+            "17,fm-eq,2,A32,12,,E,28,P.Martin,15,V,,,32,B. Panini,6,D,,,right,270,5-3;10-3"
+        );
+    }
+}
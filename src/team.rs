@@ -0,0 +1,216 @@
+use crate::assignment::{AssignedFencer, MatchAssignment};
+use crate::enums::{CompetitionType, Side};
+use crate::fencer::Fencer;
+use crate::message::{FieldString, Message};
+use crate::phase::relay_position;
+
+/// Number of fencers starting a team match, before any substitution.
+pub const TEAM_SIZE: usize = 3;
+
+/// A squad fencing a team match.
+///
+/// In team frames the fencer zone identifies the team: its id, name and
+/// nation. The roster is not transmitted; it is known to the competition
+/// software, which keeps it here to tell who fences each relay.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Side;
+/// use cyrano::fencer::Fencer;
+/// use cyrano::message::Message;
+///
+/// let message = Message::try_from("|EFP1.1|INFO|2|ef-eq|4|T8|3|2|||T|E||F|%|FRA|France|FRA|7|U|%|ITA|Italie|ITA|9|U|%|").unwrap();
+/// let mut france = message.team(Side::Right).unwrap();
+/// assert_eq!(france.name, "France");
+///
+/// france.fencers = ["Lefort", "Pauty", "Mertine"]
+///     .iter()
+///     .map(|name| Fencer { name: Some((*name).into()), ..Fencer::default() })
+///     .collect();
+/// let fencing = france.relay_fencer(2, Side::Right).unwrap();
+/// assert_eq!(fencing.name.as_deref(), Some("Lefort"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Team {
+    /// Identifier of the team, sent in the fencer `id` field.
+    pub id: Option<String>,
+    /// Name of the team.
+    pub name: String,
+    /// Three-letter country code of the team's nation.
+    pub nation: Option<String>,
+    /// Starting fencers, in team order (positions 1 to 3, or 4 to 6 on the left).
+    pub fencers: Vec<Fencer>,
+    /// Reserve fencer, if the team has one.
+    pub reserve: Option<Fencer>,
+}
+
+impl Team {
+    /// Creates a team with an empty roster.
+    pub fn new(name: impl Into<String>) -> Self {
+        Team {
+            name: name.into(),
+            ..Team::default()
+        }
+    }
+
+    /// Sets the identifier of the team.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the nation of the team.
+    pub fn nation(mut self, nation: impl Into<String>) -> Self {
+        self.nation = Some(nation.into());
+        self
+    }
+
+    /// Adds a starting fencer after those already on the roster.
+    pub fn fencer(mut self, fencer: Fencer) -> Self {
+        self.fencers.push(fencer);
+        self
+    }
+
+    /// Sets the reserve fencer.
+    pub fn reserve(mut self, fencer: Fencer) -> Self {
+        self.reserve = Some(fencer);
+        self
+    }
+
+    /// Reads the team identity of a fencer zone, with an empty roster.
+    pub fn from_zone(zone: &Fencer) -> Self {
+        Team {
+            id: zone.id.as_ref().map(|id| id.to_string()),
+            name: zone.name.as_ref().map(|name| name.to_string()).unwrap_or_default(),
+            nation: zone.nation.as_ref().map(|nation| nation.to_string()),
+            fencers: Vec::new(),
+            reserve: None,
+        }
+    }
+
+    /// Returns the fencer zone identifying the team in a frame.
+    pub fn to_zone(&self) -> Fencer {
+        Fencer {
+            id: self.id.as_deref().map(FieldString::from),
+            name: Some(self.name.as_str().into()),
+            nation: self.nation.as_deref().map(FieldString::from),
+            ..Fencer::default()
+        }
+    }
+
+    /// Returns the starting fencer of the team fencing a relay (1 to 9) on
+    /// the given side, per the FIE relay order.
+    ///
+    /// Substitutions are not taken into account.
+    pub fn relay_fencer(&self, relay: u8, side: Side) -> Option<&Fencer> {
+        let position = usize::from(relay_position(relay, side)?);
+        self.fencers.get((position - 1) % TEAM_SIZE)
+    }
+}
+
+impl From<&Team> for AssignedFencer {
+    fn from(team: &Team) -> Self {
+        AssignedFencer {
+            id: team.id.clone(),
+            name: Some(team.name.clone()),
+            nation: team.nation.clone(),
+            seed: None,
+        }
+    }
+}
+
+impl Message {
+    /// Returns the team on the given side of a team match, read from its
+    /// fencer zone, or `None` in individual competitions.
+    pub fn team(&self, side: Side) -> Option<Team> {
+        match self.competition_type {
+            Some(CompetitionType::Team) => Some(Team::from_zone(self.fencer(side))),
+            _ => None,
+        }
+    }
+}
+
+impl MatchAssignment {
+    /// Assigns a team match: both teams and the team competition type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::assignment::MatchAssignment;
+    /// use cyrano::message::Message;
+    /// use cyrano::team::Team;
+    ///
+    /// let assignment = MatchAssignment {
+    ///     piste: "2".to_string(),
+    ///     competition_id: "ef-eq".to_string(),
+    ///     phase: Some(4),
+    ///     pool_tableau: Some("T8".to_string()),
+    ///     match_number: Some(3),
+    ///     round: Some(1),
+    ///     ..MatchAssignment::default()
+    /// }
+    /// .teams(
+    ///     &Team::new("France").id("FRA").nation("FRA"),
+    ///     &Team::new("Italie").id("ITA").nation("ITA"),
+    /// );
+    ///
+    /// let next = Message::next_match(assignment).unwrap();
+    /// assert_eq!(next.to_string(), "|EFP1.1|NEXT|2|ef-eq|4|T8|3|1|||T|||||||%|FRA|France|FRA|%|ITA|Italie|ITA|%|");
+    /// ```
+    pub fn teams(mut self, right: &Team, left: &Team) -> Self {
+        self.competition_type = Some(CompetitionType::Team);
+        self.right_fencer = right.into();
+        self.left_fencer = left.into();
+        self
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::Command;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_team_roundtrip_through_next() {
+        let member = |name: &str| Fencer {
+            name: Some(name.into()),
+            ..Fencer::default()
+        };
+        let italy = Team::new("Italie")
+            .id("ITA")
+            .nation("ITA")
+            .fencer(member("Garozzo"))
+            .fencer(member("Foconi"))
+            .fencer(member("Avola"))
+            .reserve(member("Cassara"));
+        assert_eq!(italy.relay_fencer(1, Side::Left).unwrap().name.as_deref(), Some("Avola"));
+        assert_eq!(italy.relay_fencer(2, Side::Left).unwrap().name.as_deref(), Some("Foconi"));
+        assert_eq!(italy.relay_fencer(10, Side::Left), None);
+
+        let assignment = MatchAssignment {
+            piste: "2".to_string(),
+            competition_id: "fm-eq".to_string(),
+            phase: Some(4),
+            pool_tableau: Some("T4".to_string()),
+            match_number: Some(1),
+            ..MatchAssignment::default()
+        }
+        .teams(&Team::new("France").id("FRA"), &italy);
+        let next = assignment.to_message(Command::Next).unwrap();
+        let parsed = Message::try_from(next.to_string().as_str()).unwrap();
+
+        let left = parsed.team(Side::Left).unwrap();
+        assert_eq!(left.to_zone(), italy.to_zone());
+        assert!(left.fencers.is_empty() && left.reserve.is_none());
+        assert_eq!(parsed.team(Side::Right).unwrap().nation, None);
+
+        let individual = Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12|||I|E||F|%|28|P.Martin|FRA|%|%|").unwrap();
+        assert_eq!(individual.team(Side::Right), None);
+    }
+}
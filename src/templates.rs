@@ -0,0 +1,143 @@
+use crate::enums::{ApparatusState, Command, FencerStatus, PCard, Reserve, Side};
+use crate::fencer::Fencer;
+use crate::message::Message;
+use crate::simulator::winner;
+use crate::state::MatchState;
+
+/// Returns the HELLO a software sends to the apparatus of a piste when it
+/// starts or takes the piste over.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::templates;
+///
+/// let hello = templates::hello_from_software("17", "fm-eq");
+/// assert!(hello.to_string().starts_with("|EFP1.1|HELLO|17|fm-eq|"));
+/// ```
+pub fn hello_from_software(piste: &str, competition_id: &str) -> Message {
+    Message::new(Command::Hello, piste, competition_id)
+}
+
+/// Returns the INFO an apparatus sends when it is switched on, before any
+/// bout is loaded.
+///
+/// Every general field is empty except the piste and the `W` state, and both
+/// fencers are at zero with no card, light or medical intervention.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::templates;
+///
+/// assert_eq!(
+///     templates::powered_on_info("17").to_string(),
+///     "|EFP1.1|INFO|17|||||||||||W||||%||||0|U|0|0|0|0|0|N|0|%||||0|U|0|0|0|0|0|N|0|%|"
+/// );
+/// ```
+pub fn powered_on_info(piste: &str) -> Message {
+    let mut message = Message::new(Command::Info, piste, "");
+    message.state = Some(ApparatusState::Waiting);
+    message.right_fencer = idle_fencer();
+    message.left_fencer = idle_fencer();
+    message
+}
+
+fn idle_fencer() -> Fencer {
+    Fencer {
+        score: Some(0),
+        status: Some(FencerStatus::Undefined),
+        yellow_card: Some(0),
+        red_card: Some(0),
+        light: Some(false),
+        white_light: Some(false),
+        medical: Some(0),
+        reserve: Some(Reserve::None),
+        p_card: Some(PCard::None),
+        ..Fencer::default()
+    }
+}
+
+/// Returns the last INFO of the bout tracked by `state`.
+///
+/// The message repeats the last one applied to the state, with the apparatus
+/// in the `E` state, the lights off and the outcome set: a fencer who
+/// abandoned or was excluded keeps that status and the opponent wins,
+/// otherwise the fencer ahead wins, or the fencer with priority on a tie.
+///
+/// # Returns
+///
+/// `None` if no bout message was applied to the state, or if the bout is
+/// tied without priority.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::{ApparatusState, FencerStatus};
+/// use cyrano::message::Message;
+/// use cyrano::state::MatchState;
+/// use cyrano::templates;
+///
+/// let mut state = MatchState::new("17");
+/// state.apply(&Message::try_from("|EFP1.1|INFO|17|fm-eq|1|A32|12||0:00|||E||H|%|28|P.Martin|FRA|5|U|%|32|B. Panini|ITA|3|U|%|").unwrap());
+///
+/// let last = templates::end_of_bout(&state).unwrap();
+/// assert_eq!(last.state, Some(ApparatusState::Ending));
+/// assert_eq!(last.right_fencer.status, Some(FencerStatus::Victory));
+/// assert_eq!(last.left_fencer.status, Some(FencerStatus::Defeat));
+/// ```
+pub fn end_of_bout(state: &MatchState) -> Option<Message> {
+    let mut message = state.message()?.clone();
+
+    let withdrawn = |fencer: &Fencer| {
+        matches!(fencer.status, Some(FencerStatus::Abandonment | FencerStatus::Exclusion))
+    };
+    let winner = if withdrawn(&message.left_fencer) {
+        Side::Right
+    } else if withdrawn(&message.right_fencer) {
+        Side::Left
+    } else {
+        let winner = winner(&message)?;
+        message.fencer_mut(winner.opponent()).status = Some(FencerStatus::Defeat);
+        winner
+    };
+    message.fencer_mut(winner).status = Some(FencerStatus::Victory);
+
+    message.command = Command::Info;
+    message.state = Some(ApparatusState::Ending);
+    for fencer in [&mut message.right_fencer, &mut message.left_fencer] {
+        fencer.light = Some(false);
+        fencer.white_light = Some(false);
+    }
+    Some(message)
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_templates_roundtrip_strict() {
+        for message in [hello_from_software("17", "fm-eq"), powered_on_info("17")] {
+            assert_eq!(Message::try_from_strict(message.to_string().as_str()).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn test_end_of_bout_after_abandonment() {
+        let mut state = MatchState::new("4");
+        assert!(end_of_bout(&state).is_none());
+
+        let raw = "|EFP1.1|INFO|4|fm-eq|1|A32|12||1:10|||F||H|%|28|P.Martin|FRA|2|A|%|32|B. Panini|ITA|4|U|0|0|1|%|";
+        state.apply(&Message::try_from(raw).unwrap());
+
+        let last = end_of_bout(&state).unwrap();
+        assert_eq!(last.right_fencer.status, Some(FencerStatus::Abandonment));
+        assert_eq!(last.left_fencer.status, Some(FencerStatus::Victory));
+        assert_eq!(last.left_fencer.light, Some(false));
+    }
+}
@@ -0,0 +1,251 @@
+use crate::enums::{Side, Weapon};
+use crate::group::BoutKey;
+use crate::message::Message;
+
+/// How a touch was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TouchKind {
+    /// A touch awarded to the scorer.
+    Valid,
+    /// A hit off target at foil, which stops the phrase without scoring.
+    OffTarget,
+    /// A touch scored by both fencers with the same action, at épée. Reported
+    /// once for each fencer.
+    Double,
+}
+
+/// A touch attributed to a fencer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TouchEvent {
+    /// Fencer who made the touch.
+    pub scorer: Side,
+    /// Fencer who was hit.
+    pub against: Side,
+    /// How the touch was made.
+    pub kind: TouchKind,
+    /// Bout clock when the light of the touch came on, or when the score
+    /// changed if no light was seen.
+    pub at_time: Option<String>,
+}
+
+impl TouchEvent {
+    fn new(scorer: Side, kind: TouchKind, at_time: Option<String>) -> Self {
+        TouchEvent {
+            scorer,
+            against: scorer.opponent(),
+            kind,
+            at_time,
+        }
+    }
+}
+
+/// Lights of a fencer seen since the lights were last all off.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Lamps {
+    scoring: bool,
+    white: bool,
+    at_time: Option<String>,
+}
+
+/// Attributes the touches of a piste by cross-referencing the lights with the
+/// score and card changes.
+///
+/// The scores decide who was awarded a touch: at foil and sabre, when both
+/// scoring lights are on, the touch goes to the fencer whose score rises, the
+/// one with right of way. The lights give the kind and the time of the touch:
+///
+/// - both scores rising by one at épée, or with both scoring lights on when the
+///   weapon is unknown, is a [`Double`](TouchKind::Double);
+/// - a white light at foil is an [`OffTarget`](TouchKind::OffTarget) hit,
+///   reported when it comes on;
+/// - a touch given with a red card to the opponent is a penalty, not a touch.
+///
+/// Frames arriving slightly out of order are tolerated: a frame with a lower
+/// score than already seen is taken for a late frame and ignored, unless the
+/// next frame confirms it, in which case it is a referee correction.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::enums::Side;
+/// use cyrano::message::Message;
+/// use cyrano::touches::{TouchKind, TouchTracker};
+///
+/// let frame = |time: &str, right: &str, left: &str| {
+///     let raw = format!("|EFP1.1|INFO|3|fm-eq|1|P3|1||{}|||E||H|%|28|P.Martin|FRA|{}|%|32|B. Panini|ITA|{}|%|", time, right, left);
+///     Message::try_from(raw).unwrap()
+/// };
+///
+/// let mut touches = TouchTracker::new();
+/// touches.observe(&frame("2:12", "4|U|0|0|0", "2|U|0|0|0"));
+/// assert!(touches.observe(&frame("2:05", "4|U|0|0|1", "2|U|0|0|1")).is_empty());
+/// let events = touches.observe(&frame("2:05", "5|U|0|0|1", "3|U|0|0|1"));
+/// assert_eq!(events.len(), 2);
+/// assert_eq!((events[0].scorer, events[0].kind), (Side::Right, TouchKind::Double));
+/// assert_eq!(events[1].at_time.as_deref(), Some("2:05"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TouchTracker {
+    bout: Option<BoutKey>,
+    weapon: Option<Weapon>,
+    scores: [u8; 2],
+    red_cards: [u8; 2],
+    lamps: [Lamps; 2],
+    lowered: Option<[u8; 2]>,
+}
+
+impl TouchTracker {
+    /// Creates a tracker, to be fed with the messages of one piste.
+    pub fn new() -> Self {
+        TouchTracker::default()
+    }
+
+    /// Folds the next message of the piste and returns the touches it reveals.
+    ///
+    /// Messages that do not carry bout data (HELLO, ACK, NAK) are ignored. A
+    /// new bout, or both scores going back to zero, starts over without
+    /// reporting touches.
+    pub fn observe(&mut self, message: &Message) -> Vec<TouchEvent> {
+        if !message.carries_bout() {
+            return Vec::new();
+        }
+
+        let scores = [Side::Right, Side::Left].map(|side| message.fencer(side).score.unwrap_or(0));
+        let red_cards = [Side::Right, Side::Left].map(|side| message.fencer(side).red_card.unwrap_or(0));
+        let bout = BoutKey::of(message);
+        if self.bout.as_ref() != Some(&bout) || (scores == [0, 0] && self.scores != [0, 0]) {
+            *self = TouchTracker {
+                bout: Some(bout),
+                weapon: message.weapon.clone(),
+                scores,
+                red_cards,
+                ..TouchTracker::default()
+            };
+        }
+        if let Some(weapon) = &message.weapon {
+            self.weapon = Some(weapon.clone());
+        }
+
+        if scores[0] < self.scores[0] || scores[1] < self.scores[1] {
+            if self.lowered != Some(scores) {
+                self.lowered = Some(scores);
+                return Vec::new();
+            }
+            self.scores = scores;
+        }
+        self.lowered = None;
+
+        let mut events = Vec::new();
+        for (index, side) in [Side::Right, Side::Left].into_iter().enumerate() {
+            let fencer = message.fencer(side);
+            let lamps = &mut self.lamps[index];
+            if fencer.light == Some(true) || fencer.white_light == Some(true) {
+                lamps.at_time.get_or_insert_with(|| message.time.as_deref().unwrap_or_default().to_string());
+            }
+            lamps.scoring |= fencer.light == Some(true);
+            if fencer.white_light == Some(true) && !lamps.white {
+                lamps.white = true;
+                if self.weapon == Some(Weapon::Foil) {
+                    events.push(TouchEvent::new(side, TouchKind::OffTarget, lamps.at_time.clone()));
+                }
+            }
+        }
+
+        let scored = [0, 1].map(|index| scores[index].saturating_sub(self.scores[index]));
+        let double = match self.weapon {
+            Some(Weapon::Epee) => true,
+            Some(_) => false,
+            None => self.lamps.iter().all(|lamps| lamps.scoring),
+        };
+        if scored == [1, 1] && double {
+            for (index, side) in [Side::Right, Side::Left].into_iter().enumerate() {
+                events.push(TouchEvent::new(side, TouchKind::Double, self.touch_time(index, message)));
+            }
+        } else {
+            for (index, side) in [Side::Right, Side::Left].into_iter().enumerate() {
+                let penalties = red_cards[1 - index].saturating_sub(self.red_cards[1 - index]);
+                for _ in penalties..scored[index] {
+                    events.push(TouchEvent::new(side, TouchKind::Valid, self.touch_time(index, message)));
+                }
+            }
+        }
+        self.scores = scores;
+        self.red_cards = red_cards;
+
+        let lit = |side: Side| {
+            let fencer = message.fencer(side);
+            fencer.light == Some(true) || fencer.white_light == Some(true)
+        };
+        if !lit(Side::Right) && !lit(Side::Left) {
+            self.lamps = Default::default();
+        }
+
+        events
+    }
+
+    fn touch_time(&self, index: usize, message: &Message) -> Option<String> {
+        self.lamps[index]
+            .at_time
+            .clone()
+            .or_else(|| message.time.as_deref().map(str::to_string))
+    }
+}
+
+/// Attributes the touches of a timeline of one piste.
+pub fn attribute_touches<'a>(messages: impl IntoIterator<Item = &'a Message>) -> Vec<TouchEvent> {
+    let mut tracker = TouchTracker::new();
+    messages.into_iter().flat_map(|message| tracker.observe(message)).collect()
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_attribute_foil_touches() {
+        let frame = |time: &str, right: &str, left: &str| {
+            let raw = format!("|EFP1.1|INFO|3|fm-eq|1|P3|1||{}|||F||H|%|28|A|FRA|{}|%|32|B|ITA|{}|%|", time, right, left);
+            Message::try_from(raw).unwrap()
+        };
+        let timeline = vec![
+            frame("2:50", "0|U|0|0|0|0", "0|U|0|0|0|0"),
+            // Right off target, left on target: the touch goes to the left.
+            frame("2:40", "0|U|0|0|0|1", "0|U|0|0|1|0"),
+            frame("2:40", "0|U|0|0|0|1", "1|U|0|0|1|0"),
+            frame("2:40", "0|U|0|0|0|0", "1|U|0|0|0|0"),
+            // Both on target, right of way to the right.
+            frame("2:20", "0|U|0|0|1|0", "1|U|0|0|1|0"),
+            frame("2:20", "1|U|0|0|1|0", "1|U|0|0|1|0"),
+            // A late frame from before the touch.
+            frame("2:20", "0|U|0|0|1|0", "1|U|0|0|1|0"),
+            frame("2:20", "1|U|0|0|0|0", "1|U|0|0|0|0"),
+            // A red card to the left gives the right a penalty touch.
+            frame("2:20", "2|U|0|0|0|0", "1|U|0|1|0|0"),
+            // The referee takes a touch back.
+            frame("2:20", "2|U|0|0|0|0", "0|U|0|1|0|0"),
+            frame("2:20", "2|U|0|0|0|0", "0|U|0|1|0|0"),
+            frame("2:10", "2|U|0|0|0|0", "1|U|0|1|0|0"),
+        ];
+
+        let touches = attribute_touches(&timeline);
+        let touches: Vec<(Side, TouchKind, Option<&str>)> = touches
+            .iter()
+            .map(|touch| (touch.scorer, touch.kind, touch.at_time.as_deref()))
+            .collect();
+        assert_eq!(
+            touches,
+            [
+                (Side::Right, TouchKind::OffTarget, Some("2:40")),
+                (Side::Left, TouchKind::Valid, Some("2:40")),
+                (Side::Right, TouchKind::Valid, Some("2:20")),
+                (Side::Left, TouchKind::Valid, Some("2:10")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::message::Message;
+use crate::piste::PisteId;
+
+/// Default delay between two frames of a piste, used to estimate losses, in milliseconds.
+///
+/// An apparatus reports at least once per second while a bout is running.
+pub const DEFAULT_NOMINAL_INTERVAL_MS: u64 = 1000;
+
+/// Link counters of one piste.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PisteTraffic {
+    /// Frames received that parse.
+    pub frames: u64,
+    /// Frames received that do not parse.
+    pub parse_errors: u64,
+    /// Frames identical to the previous one of the piste.
+    pub duplicates: u64,
+    /// Silences longer than twice the nominal interval.
+    pub gaps: u64,
+    /// Frames estimated lost during those silences.
+    pub estimated_lost: u64,
+    /// Time the first frame was received, in milliseconds.
+    pub first_seen_ms: Option<u64>,
+    /// Time the last frame was received, in milliseconds.
+    pub last_seen_ms: Option<u64>,
+}
+
+impl PisteTraffic {
+    /// Returns the share of frames estimated lost or unparsable, between 0 and 1.
+    pub fn loss_ratio(&self) -> f64 {
+        let bad = self.estimated_lost + self.parse_errors;
+        let expected = self.frames + bad;
+        if expected == 0 {
+            0.0
+        } else {
+            bad as f64 / expected as f64
+        }
+    }
+
+    fn seen(&mut self, now_ms: u64, nominal_interval_ms: u64) {
+        if let Some(last_ms) = self.last_seen_ms {
+            let silence_ms = now_ms.saturating_sub(last_ms);
+            if nominal_interval_ms > 0 && silence_ms > 2 * nominal_interval_ms {
+                self.gaps += 1;
+                self.estimated_lost += silence_ms / nominal_interval_ms - 1;
+            }
+        }
+        self.first_seen_ms.get_or_insert(now_ms);
+        self.last_seen_ms = Some(now_ms);
+    }
+}
+
+/// Per-piste link statistics, answering "is piste 12 flaky?".
+///
+/// EFP frames carry no sequence number, so losses are estimated from the
+/// silences of each piste: a piste silent for several nominal intervals is
+/// assumed to have lost the frames it would have sent in the meantime. The
+/// estimate is only meaningful while bouts are running.
+///
+/// Frames that do not parse are attributed to the piste named in their
+/// fourth field when there is one, which suits a
+/// [`ParseErrorHook`](crate::error::ParseErrorHook).
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::traffic::TrafficStats;
+///
+/// let mut stats = TrafficStats::new();
+/// let info = Message::try_from("|EFP1.1|INFO|12|fm-eq|%|").unwrap();
+///
+/// stats.record_message(&info, 0);
+/// stats.record_parse_error(b"|EFP1.1|INFO|12|fm-eq|1|A32|12|2|10:30|2:59|Z|", 1_000);
+/// stats.record_message(&info, 5_000);
+///
+/// let piste = stats.piste("12").unwrap();
+/// assert_eq!((piste.frames, piste.parse_errors, piste.duplicates), (2, 1, 1));
+/// assert_eq!(piste.estimated_lost, 3);
+/// assert!(stats.to_prometheus().contains("cyrano_frames_total{piste=\"12\"} 2"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TrafficStats {
+    nominal_interval_ms: u64,
+    pistes: BTreeMap<PisteId, PisteTraffic>,
+    last_messages: BTreeMap<PisteId, Message>,
+    unattributed_errors: u64,
+}
+
+impl Default for TrafficStats {
+    fn default() -> Self {
+        TrafficStats::new()
+    }
+}
+
+impl TrafficStats {
+    /// Creates empty statistics with the default nominal interval.
+    pub fn new() -> Self {
+        TrafficStats {
+            nominal_interval_ms: DEFAULT_NOMINAL_INTERVAL_MS,
+            pistes: BTreeMap::new(),
+            last_messages: BTreeMap::new(),
+            unattributed_errors: 0,
+        }
+    }
+
+    /// Sets the delay between two frames of a piste used to estimate losses.
+    pub fn nominal_interval_ms(mut self, interval_ms: u64) -> Self {
+        self.nominal_interval_ms = interval_ms;
+        self
+    }
+
+    /// Records a frame that parsed.
+    pub fn record_message(&mut self, message: &Message, now_ms: u64) {
+        let piste = PisteId::from(message.piste.as_ref());
+        let traffic = self.pistes.entry(piste.clone()).or_default();
+        traffic.seen(now_ms, self.nominal_interval_ms);
+        traffic.frames += 1;
+
+        if self.last_messages.get(&piste) == Some(message) {
+            traffic.duplicates += 1;
+        } else {
+            self.last_messages.insert(piste, message.clone());
+        }
+    }
+
+    /// Records a frame that did not parse.
+    pub fn record_parse_error(&mut self, raw: &[u8], now_ms: u64) {
+        let piste = std::str::from_utf8(raw)
+            .ok()
+            .and_then(|text| text.split('|').nth(3))
+            .filter(|piste| !piste.is_empty());
+
+        match piste {
+            Some(piste) => {
+                let traffic = self.pistes.entry(PisteId::from(piste)).or_default();
+                traffic.seen(now_ms, self.nominal_interval_ms);
+                traffic.parse_errors += 1;
+            }
+            None => self.unattributed_errors += 1,
+        }
+    }
+
+    /// Returns the counters of a piste, or `None` if nothing was received from it.
+    pub fn piste(&self, piste: &str) -> Option<&PisteTraffic> {
+        self.pistes.get(&PisteId::from(piste))
+    }
+
+    /// Returns the counters of every piste, in natural piste order.
+    pub fn pistes(&self) -> impl Iterator<Item = (&PisteId, &PisteTraffic)> {
+        self.pistes.iter()
+    }
+
+    /// Returns the number of unparsable frames that named no piste.
+    pub fn unattributed_errors(&self) -> u64 {
+        self.unattributed_errors
+    }
+
+    /// Renders the counters in the Prometheus text exposition format, to be
+    /// served to a metrics scraper.
+    pub fn to_prometheus(&self) -> String {
+        let mut text = String::new();
+        let counters: [Counter; 5] = [
+            ("cyrano_frames_total", "Frames received that parse.", |t| t.frames),
+            ("cyrano_parse_errors_total", "Frames received that do not parse.", |t| t.parse_errors),
+            ("cyrano_duplicates_total", "Frames identical to the previous one.", |t| t.duplicates),
+            ("cyrano_gaps_total", "Silences longer than twice the nominal interval.", |t| t.gaps),
+            ("cyrano_estimated_lost_total", "Frames estimated lost during silences.", |t| t.estimated_lost),
+        ];
+
+        for (name, help, value) in counters {
+            let _ = writeln!(text, "# HELP {} {}\n# TYPE {} counter", name, help, name);
+            for (piste, traffic) in &self.pistes {
+                let _ = writeln!(text, "{}{{piste=\"{}\"}} {}", name, label(piste.as_str()), value(traffic));
+            }
+        }
+
+        let _ = writeln!(text, "# HELP cyrano_last_seen_ms Time the last frame was received.\n# TYPE cyrano_last_seen_ms gauge");
+        for (piste, traffic) in &self.pistes {
+            if let Some(last_seen_ms) = traffic.last_seen_ms {
+                let _ = writeln!(text, "cyrano_last_seen_ms{{piste=\"{}\"}} {}", label(piste.as_str()), last_seen_ms);
+            }
+        }
+
+        let _ = writeln!(
+            text,
+            "# HELP cyrano_unattributed_errors_total Unparsable frames naming no piste.\n# TYPE cyrano_unattributed_errors_total counter\ncyrano_unattributed_errors_total {}",
+            self.unattributed_errors
+        );
+        text
+    }
+}
+
+/// Metric name, help text and value of a counter exported per piste.
+type Counter = (&'static str, &'static str, fn(&PisteTraffic) -> u64);
+
+/// Escapes a Prometheus label value.
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    #[test]
+    fn test_gaps_in_a_day_log() {
+        let mut stats = TrafficStats::new().nominal_interval_ms(30_000);
+        for entry in fixtures::day_log(0, 3, 2) {
+            stats.record_message(&entry.message().unwrap(), entry.timestamp_ms);
+        }
+        stats.record_parse_error(b"\xff", 0);
+
+        let names: Vec<&str> = stats.pistes().map(|(piste, _)| piste.as_str()).collect();
+        assert_eq!(names, ["1", "2", "3"]);
+        let piste = stats.piste("2").unwrap();
+        assert_eq!(piste.first_seen_ms, Some(7_000));
+        assert!(piste.gaps >= 1, "the changeover between bouts is a gap");
+        assert!(piste.loss_ratio() > 0.0 && piste.loss_ratio() < 1.0);
+        assert_eq!(stats.unattributed_errors(), 1);
+        assert!(stats.to_prometheus().ends_with("cyrano_unattributed_errors_total 1\n"));
+        assert_eq!(label("Fin\"ale"), "Fin\\\"ale");
+    }
+}
@@ -0,0 +1,162 @@
+use crate::error::ParseError;
+use crate::message::Message;
+
+/// Algorithm of the checksum some bridges append after the final `%|` of a
+/// frame.
+///
+/// The checksum covers every byte of the frame, from the first `|` to the
+/// final `%|` included, and is written in uppercase hexadecimal.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::trailer::ChecksumAlgorithm;
+///
+/// assert_eq!(ChecksumAlgorithm::Xor.compute("|EFP1.1|HELLO|17|%|"), "60");
+///
+/// fn length(frame: &[u8]) -> String {
+///     frame.len().to_string()
+/// }
+/// assert_eq!(ChecksumAlgorithm::Custom(length).compute("|EFP1.1|HELLO|17|%|"), "19");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum ChecksumAlgorithm {
+    /// XOR of all bytes, on two hexadecimal digits.
+    Xor,
+    /// Sum of all bytes modulo 256, on two hexadecimal digits.
+    Sum,
+    /// CRC-16/CCITT-FALSE (polynomial `0x1021`, initial value `0xFFFF`), on
+    /// four hexadecimal digits.
+    Crc16,
+    /// Algorithm of the deployment, returning the trailer text of a frame.
+    Custom(fn(&[u8]) -> String),
+}
+
+impl ChecksumAlgorithm {
+    /// Returns the trailer of a frame.
+    pub fn compute(&self, frame: &str) -> String {
+        let bytes = frame.as_bytes();
+        match self {
+            ChecksumAlgorithm::Xor => format!("{:02X}", bytes.iter().fold(0u8, |sum, byte| sum ^ byte)),
+            ChecksumAlgorithm::Sum => format!("{:02X}", bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))),
+            ChecksumAlgorithm::Crc16 => format!("{:04X}", crc16(bytes)),
+            ChecksumAlgorithm::Custom(compute) => compute(bytes),
+        }
+    }
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for byte in bytes {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// Splits a raw frame into the frame itself, ending with `%|`, and the
+/// trailer appended after it, if any.
+///
+/// Surrounding whitespace, such as a line terminator, is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use cyrano::trailer::split_trailer;
+///
+/// assert_eq!(split_trailer("|EFP1.1|HELLO|17|%|0042\r\n"), ("|EFP1.1|HELLO|17|%|", Some("0042")));
+/// assert_eq!(split_trailer("|EFP1.1|HELLO|17|%|"), ("|EFP1.1|HELLO|17|%|", None));
+/// ```
+pub fn split_trailer(raw: &str) -> (&str, Option<&str>) {
+    let raw = raw.trim();
+    match raw.rfind("%|") {
+        Some(end) if end + 2 < raw.len() => (&raw[..end + 2], Some(raw[end + 2..].trim())),
+        _ => (raw, None),
+    }
+}
+
+/// Checks the checksum trailer of a raw frame.
+///
+/// # Returns
+///
+/// The frame without its trailer.
+///
+/// # Errors
+///
+/// Returns `ParseError::ChecksumMismatch` if the trailer is missing or does
+/// not match the frame. Hexadecimal trailers are compared ignoring case.
+pub fn verify(raw: &str, algorithm: ChecksumAlgorithm) -> Result<&str, ParseError> {
+    let (frame, trailer) = split_trailer(raw);
+    let expected = algorithm.compute(frame);
+    match trailer {
+        Some(found) if found.eq_ignore_ascii_case(&expected) => Ok(frame),
+        found => Err(ParseError::ChecksumMismatch {
+            expected,
+            found: found.map(str::to_string),
+        }),
+    }
+}
+
+impl Message {
+    /// Serializes the message followed by its checksum trailer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cyrano::enums::Command;
+    /// use cyrano::message::Message;
+    /// use cyrano::options::ParseOptions;
+    /// use cyrano::trailer::ChecksumAlgorithm;
+    ///
+    /// let msg = Message::new(Command::Hello, "17", "fm-eq");
+    /// let raw = msg.to_string_with_checksum(ChecksumAlgorithm::Crc16);
+    ///
+    /// let options = ParseOptions { checksum: Some(ChecksumAlgorithm::Crc16), ..ParseOptions::default() };
+    /// assert_eq!(Message::parse_with(&raw, &options).unwrap(), msg);
+    /// ```
+    pub fn to_string_with_checksum(&self, algorithm: ChecksumAlgorithm) -> String {
+        let mut raw = self.to_string();
+        let trailer = algorithm.compute(&raw);
+        raw.push_str(&trailer);
+        raw
+    }
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::ParseOptions;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_checksum_trailers() {
+        assert_eq!(ChecksumAlgorithm::Crc16.compute("123456789"), "29B1");
+        assert_eq!(ChecksumAlgorithm::Sum.compute("|%|"), "1D");
+
+        let raw = "|EFP1.1|INFO|17|fm-eq|1|A32|12||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|";
+        let message = Message::try_from(raw).unwrap();
+        let options = ParseOptions {
+            checksum: Some(ChecksumAlgorithm::Xor),
+            ..ParseOptions::default()
+        };
+
+        let sealed = message.to_string_with_checksum(ChecksumAlgorithm::Xor);
+        assert_eq!(Message::parse_with(&sealed, &options).unwrap(), message);
+        let lowercase = format!("{}{}\r\n", raw, ChecksumAlgorithm::Xor.compute(raw).to_lowercase());
+        assert!(Message::parse_with(&lowercase, &options).is_ok());
+
+        let tampered = sealed.replace("|4|U|", "|5|U|");
+        assert!(matches!(
+            Message::parse_with(&tampered, &options),
+            Err(ParseError::ChecksumMismatch { found: Some(_), .. })
+        ));
+        assert!(matches!(
+            Message::parse_with(raw, &options),
+            Err(ParseError::ChecksumMismatch { found: None, .. })
+        ));
+    }
+}
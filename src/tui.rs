@@ -0,0 +1,206 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Row, Table, Widget};
+
+use super::enums::{ApparatusState, Side};
+use super::fencer::Fencer;
+use super::state::{MatchState, PisteManager};
+
+/// Terminal scoreboard listing every piste of a [`PisteManager`].
+///
+/// The widget shows, per piste, the fencers, score, match time, apparatus
+/// state, scoring lights and cards. It only renders: the application owns the
+/// terminal and redraws the widget after feeding new messages to the manager.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use cyrano::message::Message;
+/// use cyrano::state::PisteManager;
+/// use cyrano::tui::Scoreboard;
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::widgets::Widget;
+///
+/// let mut pistes = PisteManager::new();
+/// pistes.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|%|32|B. Panini|ITA|2|U|%|").unwrap());
+///
+/// let area = Rect::new(0, 0, 80, 5);
+/// let mut buffer = Buffer::empty(area);
+/// Scoreboard::new(&pistes).render(area, &mut buffer);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scoreboard<'a> {
+    pistes: &'a PisteManager,
+    title: Option<&'a str>,
+}
+
+impl<'a> Scoreboard<'a> {
+    /// Creates a scoreboard for the given pistes.
+    pub fn new(pistes: &'a PisteManager) -> Self {
+        Scoreboard {
+            pistes,
+            title: None,
+        }
+    }
+
+    /// Sets the title shown on the border of the scoreboard.
+    pub fn title(mut self, title: &'a str) -> Self {
+        self.title = Some(title);
+        self
+    }
+}
+
+impl Widget for Scoreboard<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let header = Row::new(["Piste", "Right", "Score", "Left", "Time", "State", "Lights", "Cards"])
+            .style(Style::default().add_modifier(Modifier::BOLD));
+
+        let rows = self.pistes.iter().map(piste_row);
+
+        let widths = [
+            Constraint::Length(6),
+            Constraint::Fill(1),
+            Constraint::Length(7),
+            Constraint::Fill(1),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(6),
+            Constraint::Length(9),
+        ];
+
+        let mut block = Block::default().borders(Borders::ALL);
+        if let Some(title) = self.title {
+            block = block.title(title);
+        }
+
+        Widget::render(Table::new(rows, widths).header(header).block(block), area, buf);
+    }
+}
+
+fn piste_row(state: &MatchState) -> Row<'static> {
+    let message = state.message();
+    let name = |side| {
+        state
+            .fencer(side)
+            .and_then(|f: &Fencer| f.name.as_deref().or(f.id.as_deref()))
+            .unwrap_or_default()
+            .to_string()
+    };
+    let score = match (state.score(Side::Right), state.score(Side::Left)) {
+        (None, None) => String::new(),
+        (right, left) => format!("{} - {}", right.unwrap_or(0), left.unwrap_or(0)),
+    };
+    let time = message.and_then(|m| m.time.as_deref()).unwrap_or_default().to_string();
+
+    let row = Row::new([
+        Cell::from(state.piste().to_string()),
+        Cell::from(name(Side::Right)),
+        Cell::from(score),
+        Cell::from(name(Side::Left)),
+        Cell::from(time),
+        Cell::from(state_label(state.state())),
+        Cell::from(lights(state)),
+        Cell::from(cards(state)),
+    ]);
+
+    if state.is_finished() {
+        row.style(Style::default().add_modifier(Modifier::DIM))
+    } else {
+        row
+    }
+}
+
+fn state_label(state: Option<&ApparatusState>) -> &'static str {
+    match state {
+        Some(ApparatusState::Fencing) => "fencing",
+        Some(ApparatusState::Halt) => "halt",
+        Some(ApparatusState::Pause) => "pause",
+        Some(ApparatusState::Waiting) => "waiting",
+        Some(ApparatusState::Ending) => "ending",
+        Some(ApparatusState::Unknown(_)) => "unknown",
+        None => "",
+    }
+}
+
+fn lights(state: &MatchState) -> Line<'static> {
+    let lamp = |side, color| {
+        let fencer = state.fencer(side);
+        if fencer.and_then(|f| f.light) == Some(true) {
+            Span::styled("●", Style::default().fg(color))
+        } else if fencer.and_then(|f| f.white_light) == Some(true) {
+            Span::styled("●", Style::default().fg(Color::White))
+        } else {
+            Span::styled("○", Style::default().fg(Color::DarkGray))
+        }
+    };
+
+    Line::from(vec![lamp(Side::Right, Color::Green), Span::raw(" "), lamp(Side::Left, Color::Red)])
+}
+
+fn cards(state: &MatchState) -> Line<'static> {
+    let mut spans = Vec::new();
+    for side in [Side::Right, Side::Left] {
+        if !spans.is_empty() {
+            spans.push(Span::raw(" | "));
+        }
+        let fencer = state.fencer(side);
+        let yellow = fencer.and_then(|f| f.yellow_card).unwrap_or(0);
+        let red = fencer.and_then(|f| f.red_card).unwrap_or(0);
+
+        if yellow == 0 && red == 0 {
+            spans.push(Span::raw("-"));
+        }
+        if yellow > 0 {
+            spans.push(Span::styled("Y", Style::default().fg(Color::Yellow)));
+        }
+        if red > 0 {
+            spans.push(Span::styled(format!("R{}", red), Style::default().fg(Color::Red)));
+        }
+    }
+    Line::from(spans)
+}
+
+// ===== TESTS =====
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_scoreboard_shows_pistes() {
+        let mut pistes = PisteManager::new();
+        pistes.apply(&Message::try_from("|EFP1.1|INFO|3|fm-eq|1|P3|1||2:12|||E||F|%|28|P.Martin|FRA|4|U|0|1|%|32|Panini|ITA|2|U|%|").unwrap());
+
+        let area = Rect::new(0, 0, 90, 5);
+        let mut buffer = Buffer::empty(area);
+        Scoreboard::new(&pistes).title("Venue").render(area, &mut buffer);
+
+        let text: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(text.contains("Venue"));
+        assert!(text.contains("P.Martin"));
+        assert!(text.contains("4 - 2"));
+        assert!(text.contains("2:12"));
+        assert!(text.contains("R1"));
+    }
+
+    #[test]
+    fn test_scoreboard_in_natural_piste_order() {
+        let mut pistes = PisteManager::new();
+        for (piste, name) in [("10", "Tenth"), ("2", "Second")] {
+            pistes.apply(&Message::try_from(format!("|EFP1.1|INFO|{}|fm-eq|1|P3|1|||||E||F|%|28|{}|FRA|0|U|%|%|", piste, name)).unwrap());
+        }
+
+        let area = Rect::new(0, 0, 90, 6);
+        let mut buffer = Buffer::empty(area);
+        Scoreboard::new(&pistes).render(area, &mut buffer);
+
+        let text: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(text.find("Second").unwrap() < text.find("Tenth").unwrap());
+    }
+}
@@ -1,4 +1,12 @@
-use super::error::ParseError;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Write};
+use std::io;
+
+use super::enums::Zone;
+use super::error::{ParseError, ParseWarning, WarningKind};
+use super::limits::FORBIDDEN_CHARACTERS;
+use super::message::FieldString;
+use super::piste::PisteId;
 
 /// Retrieves an optional field from an array of string slices.
 ///
@@ -12,7 +20,7 @@ use super::error::ParseError;
 /// # Returns
 ///
 /// `Some(&str)` if the field exists and is non-empty, `None` otherwise.
-pub fn get_field<'a>(fields: &'a [&str], index: usize) -> Option<&'a str> {
+pub fn get_field<'a>(fields: &[&'a str], index: usize) -> Option<&'a str> {
     fields.get(index).and_then(|s| {
         if s.is_empty() {
             None
@@ -22,6 +30,24 @@ pub fn get_field<'a>(fields: &'a [&str], index: usize) -> Option<&'a str> {
     })
 }
 
+/// Splits a zone into its fields.
+///
+/// Only the single delimiter at each end of the zone is removed, so leading and
+/// trailing empty fields keep their position.
+///
+/// # Arguments
+///
+/// * `zone` - The raw zone, as found between two `%` separators
+///
+/// # Returns
+///
+/// The fields of the zone, in order.
+pub fn split_zone(zone: &str) -> Vec<&str> {
+    let zone = zone.strip_prefix('|').unwrap_or(zone);
+    let zone = zone.strip_suffix('|').unwrap_or(zone);
+    zone.split('|').collect()
+}
+
 /// Retrieves a required field from an array of string slices.
 ///
 /// # Arguments
@@ -38,39 +64,365 @@ pub fn get_field<'a>(fields: &'a [&str], index: usize) -> Option<&'a str> {
 ///
 /// Returns `ParseError::MissingField` if the field is missing or empty.
 pub fn get_required_field<'a>(
-    fields: &'a [&str],
+    fields: &[&'a str],
     index: usize,
     name: &'static str,
 ) -> Result<&'a str, ParseError> {
     get_field(fields, index).ok_or(ParseError::MissingField(name))
 }
 
-/// Parses an optional unsigned 8-bit integer from a field.
-///
-/// # Arguments
-///
-/// * `fields` - Array of string slices to search
-/// * `index` - Index of the field to parse
-///
-/// # Returns
-///
-/// `Some(u8)` if the field exists and can be parsed as a `u8`, `None` otherwise.
-pub fn parse_optional_u8(fields: &[&str], index: usize) -> Option<u8> {
-    get_field(fields, index).and_then(|s| s.parse().ok())
+/// Normalizes a free-text value: trims it, collapses inner whitespace, and maps
+/// empty values to `None`.
+pub fn canonical_text<S: AsRef<str> + From<String>>(value: &Option<S>) -> Option<S> {
+    value
+        .as_ref()
+        .map(|s| s.as_ref().split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|s| !s.is_empty())
+        .map(S::from)
+}
+
+/// Normalizes a code (nation, pool or tableau): trims and uppercases it, and
+/// maps empty values to `None`.
+pub fn canonical_code<S: AsRef<str> + From<String>>(value: &Option<S>) -> Option<S> {
+    value
+        .as_ref()
+        .map(|s| s.as_ref().trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .map(S::from)
 }
 
-/// Parses an optional boolean from a field.
+/// Normalizes a time value such as "03:00" or "3:0" to "3:00".
 ///
-/// Interprets "1" as `true` and any other value as `false`.
+/// The leading component loses its leading zeros and the following components
+/// are padded to two digits. Values that are not colon-separated numbers are
+/// only trimmed.
+pub fn canonical_time<S: AsRef<str> + From<String>>(value: &Option<S>) -> Option<S> {
+    let value = value.as_ref()?.as_ref().trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    let parts: Vec<&str> = value.split(':').collect();
+    let numeric = parts
+        .iter()
+        .all(|p| !p.is_empty() && p.len() <= 2 && p.bytes().all(|b| b.is_ascii_digit()));
+    if parts.len() < 2 || !numeric {
+        return Some(value.to_string().into());
+    }
+
+    let mut canonical = parts[0].trim_start_matches('0').to_string();
+    if canonical.is_empty() {
+        canonical.push('0');
+    }
+    for part in &parts[1..] {
+        canonical.push_str(&format!(":{:0>2}", part));
+    }
+    Some(canonical.into())
+}
+
+/// Reads the typed fields of one zone of a message.
 ///
-/// # Arguments
+/// In lenient mode, invalid values are treated as absent and recorded as
+/// warnings. In strict mode, they are reported as `ParseError::InvalidValue`.
+pub struct FieldReader<'a, 's, 'w> {
+    fields: &'a [&'s str],
+    zone: Zone,
+    strict: bool,
+    warnings: &'w mut Vec<ParseWarning>,
+}
+
+impl<'a, 's, 'w> FieldReader<'a, 's, 'w> {
+    /// Creates a reader over the fields of a zone.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - Array of string slices of the zone
+    /// * `zone` - Zone the fields belong to, for warnings
+    /// * `strict` - Whether invalid values are errors
+    /// * `warnings` - Collector for lenient-mode warnings
+    pub fn new(
+        fields: &'a [&'s str],
+        zone: Zone,
+        strict: bool,
+        warnings: &'w mut Vec<ParseWarning>,
+    ) -> Self {
+        FieldReader {
+            fields,
+            zone,
+            strict,
+            warnings,
+        }
+    }
+
+    /// Returns the raw value of an optional field, borrowed from the message.
+    pub fn str(&self, index: usize) -> Option<&'s str> {
+        get_field(self.fields, index)
+    }
+
+    /// Parses an optional unsigned 8-bit integer.
+    ///
+    /// # Errors
+    ///
+    /// In strict mode, returns `ParseError::InvalidValue` if the value is not a `u8`.
+    pub fn u8(&mut self, index: usize, name: &'static str) -> Result<Option<u8>, ParseError> {
+        self.parse(index, name, |s| s.parse().ok())
+    }
+
+    /// Parses an optional boolean, encoded as "1" (true) or "0" (false).
+    ///
+    /// In lenient mode, any value other than "1" is read as `false`.
+    ///
+    /// # Errors
+    ///
+    /// In strict mode, returns `ParseError::InvalidValue` if the value is neither "0" nor "1".
+    pub fn bool(&mut self, index: usize, name: &'static str) -> Result<Option<bool>, ParseError> {
+        match get_field(self.fields, index) {
+            Some("1") => Ok(Some(true)),
+            Some("0") => Ok(Some(false)),
+            Some(_) if !self.strict => Ok(Some(false)),
+            Some(value) => Err(ParseError::InvalidValue {
+                field: name,
+                value: value.to_string(),
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses an optional enumeration value.
+    ///
+    /// # Errors
+    ///
+    /// In strict mode, returns the `ParseError::InvalidValue` produced by the
+    /// enumeration's `TryFrom` implementation.
+    pub fn enumeration<T>(&mut self, index: usize, name: &'static str) -> Result<Option<T>, ParseError>
+    where
+        T: for<'v> TryFrom<&'v str, Error = ParseError>,
+    {
+        let Some(value) = get_field(self.fields, index) else {
+            return Ok(None);
+        };
+
+        match T::try_from(value) {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(err) if self.strict => Err(err),
+            Err(_) => {
+                self.warn(name, WarningKind::FieldInvalid(value.to_string()));
+                Ok(None)
+            }
+        }
+    }
+
+    fn parse<T>(
+        &mut self,
+        index: usize,
+        name: &'static str,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> Result<Option<T>, ParseError> {
+        let Some(value) = get_field(self.fields, index) else {
+            return Ok(None);
+        };
+
+        match parse(value) {
+            Some(parsed) => Ok(Some(parsed)),
+            None if self.strict => Err(ParseError::InvalidValue {
+                field: name,
+                value: value.to_string(),
+            }),
+            None => {
+                self.warn(name, WarningKind::FieldInvalid(value.to_string()));
+                Ok(None)
+            }
+        }
+    }
+
+    fn warn(&mut self, field: &'static str, kind: WarningKind) {
+        self.warnings.push(ParseWarning {
+            zone: self.zone,
+            field,
+            kind,
+        });
+    }
+}
+
+/// Borrowed view of a field value, written without intermediate allocation.
+pub enum FieldRef<'a> {
+    /// The field has no value.
+    Empty,
+    /// Free text; forbidden characters are replaced by spaces when written.
+    Text(&'a str),
+    /// Unsigned number.
+    Number(u8),
+    /// Boolean, written as "1" or "0".
+    Flag(bool),
+    /// Enumerated protocol code.
+    Code(&'a dyn Display),
+}
+
+impl<'a> FieldRef<'a> {
+    /// Builds a text view from an optional string.
+    pub fn text<S: AsRef<str>>(value: &'a Option<S>) -> Self {
+        value.as_ref().map_or(FieldRef::Empty, |s| FieldRef::Text(s.as_ref()))
+    }
+
+    /// Builds a number view from an optional number.
+    pub fn number(value: Option<u8>) -> Self {
+        value.map_or(FieldRef::Empty, FieldRef::Number)
+    }
+
+    /// Builds a boolean view from an optional boolean.
+    pub fn flag(value: Option<bool>) -> Self {
+        value.map_or(FieldRef::Empty, FieldRef::Flag)
+    }
+
+    /// Builds a code view from an optional enumeration value.
+    pub fn code<T: Display>(value: &'a Option<T>) -> Self {
+        value.as_ref().map_or(FieldRef::Empty, |v| FieldRef::Code(v))
+    }
+
+    /// Returns `true` if the field serializes to an empty string.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            FieldRef::Empty => true,
+            FieldRef::Text(s) => s.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Writes the protocol representation of the field.
+    pub fn write<W: Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        match self {
+            FieldRef::Empty => Ok(()),
+            FieldRef::Text(s) => write_text(out, s),
+            FieldRef::Number(n) => write!(out, "{}", n),
+            FieldRef::Flag(b) => out.write_char(if *b { '1' } else { '0' }),
+            FieldRef::Code(c) => write!(out, "{}", c),
+        }
+    }
+}
+
+/// Writes a text value, replacing forbidden characters by spaces.
+pub fn write_text<W: Write + ?Sized>(out: &mut W, value: &str) -> fmt::Result {
+    let mut rest = value;
+    while let Some(pos) = rest.find(FORBIDDEN_CHARACTERS) {
+        out.write_str(&rest[..pos])?;
+        out.write_char(' ')?;
+        rest = &rest[pos + 1..];
+    }
+    out.write_str(rest)
+}
+
+/// Writer adapter counting the bytes written through it.
+pub struct CountingWriter<'a, W: ?Sized> {
+    inner: &'a mut W,
+    count: usize,
+}
+
+impl<'a, W: Write + ?Sized> CountingWriter<'a, W> {
+    /// Wraps a writer.
+    pub fn new(inner: &'a mut W) -> Self {
+        CountingWriter { inner, count: 0 }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl<W: Write + ?Sized> Write for CountingWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_str(s)?;
+        self.count += s.len();
+        Ok(())
+    }
+}
+
+/// Writes one CSV row, quoting values as per RFC 4180 and ending it with CRLF.
+pub(crate) fn write_csv_row<W: io::Write>(writer: &mut W, row: &[String]) -> io::Result<()> {
+    for (index, value) in row.iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+        if value.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", value.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(value.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")
+}
+
+/// Converts a text value into the storage type of message fields.
 ///
-/// * `fields` - Array of string slices to search
-/// * `index` - Index of the field to parse
+/// A move when [`FieldString`] is `String`, a conversion with the
+/// `small-strings` feature.
+pub(crate) fn field<T: Into<FieldString>>(value: T) -> FieldString {
+    value.into()
+}
+
+/// Returns the piste named in a raw frame, if any.
+pub(crate) fn frame_piste(raw: &str) -> Option<PisteId> {
+    raw.split('|').nth(3).filter(|piste| !piste.is_empty()).map(PisteId::from)
+}
+
+/// Reads a bout clock such as `2:12` or `1:02:30` as a number of seconds.
 ///
-/// # Returns
+/// Hundredths (`0:09.45`) are dropped. Returns `None` for any other shape.
+pub(crate) fn clock_seconds(time: &str) -> Option<u32> {
+    clock_millis(time).map(|ms| (ms / 1000) as u32)
+}
+
+/// Reads a bout clock such as `2:12` or `0:09.45` as a number of milliseconds.
 ///
-/// `Some(bool)` if the field exists, `None` if the field is missing or empty.
-pub fn parse_optional_bool(fields: &[&str], index: usize) -> Option<bool> {
-    get_field(fields, index).map(|s| s == "1")
+/// Returns `None` for any other shape.
+pub(crate) fn clock_millis(time: &str) -> Option<u64> {
+    let (whole, hundredths) = match time.trim().split_once('.') {
+        Some((whole, fraction)) => {
+            if fraction.is_empty() || fraction.len() > 2 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let value: u64 = fraction.parse().ok()?;
+            (whole, if fraction.len() == 1 { value * 10 } else { value })
+        }
+        None => (time.trim(), 0),
+    };
+
+    let mut seconds = 0u64;
+    let mut parts = 0;
+    for part in whole.split(':') {
+        if part.is_empty() || part.len() > 2 || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+        parts += 1;
+    }
+    (2..=3).contains(&parts).then_some(seconds * 1000 + hundredths * 10)
+}
+
+/// Xorshift generator, so that generated data is identical on every run and platform.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// Returns the next number of the sequence.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `0..bound`; `bound` must not be zero.
+    pub(crate) fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// Returns `true` with the given probability.
+    pub(crate) fn chance(&mut self, probability: f64) -> bool {
+        probability > 0.0 && (self.below(1_000_000) as f64) < probability * 1_000_000.0
+    }
 }